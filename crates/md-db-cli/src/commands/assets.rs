@@ -0,0 +1,143 @@
+use std::path::PathBuf;
+
+use clap::Args;
+use md_db::assets::AssetStore;
+use md_db::undo::Recorder;
+
+#[derive(Debug, Args)]
+pub struct AssetsArgs {
+    /// Action: add, list, gc
+    pub action: String,
+
+    /// File to store (for `add`)
+    pub file: Option<PathBuf>,
+
+    /// Document to link the stored asset from (for `add`)
+    #[arg(long)]
+    pub to: Option<PathBuf>,
+
+    /// Section the link is appended to (created if missing)
+    #[arg(long, default_value = "Attachments")]
+    pub section: String,
+
+    /// Docs root holding the .md-db/assets store
+    #[arg(long, default_value = ".")]
+    pub dir: PathBuf,
+
+    /// Show what would change without writing files
+    #[arg(long)]
+    pub dry_run: bool,
+
+    /// Output format: text, json
+    #[arg(long, default_value = "text")]
+    pub format: String,
+}
+
+pub fn run(args: &AssetsArgs) -> Result<(), Box<dyn std::error::Error>> {
+    match args.action.as_str() {
+        "add" => add(args),
+        "list" => list(args),
+        "gc" => gc(args),
+        _ => Err(format!("unknown action: {} (expected: add, list, gc)", args.action).into()),
+    }
+}
+
+fn add(args: &AssetsArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let file = args.file.as_ref().ok_or("assets add requires a file argument")?;
+    let mut store = AssetStore::open(&args.dir)?;
+
+    if args.dry_run {
+        println!("Would store {} under {}", file.display(), store.assets_dir().display());
+        return Ok(());
+    }
+
+    let added = store.add(file)?;
+    let linked = match &args.to {
+        Some(doc_path) => {
+            let doc = store.link(&added.name, doc_path, &args.section)?;
+            let mut undo = Recorder::new("assets add");
+            undo.track(doc_path);
+            doc.save()?;
+            undo.finish()?;
+            Some(doc_path.display().to_string())
+        }
+        None => None,
+    };
+    store.save()?;
+
+    if args.format == "json" {
+        let result = serde_json::json!({
+            "name": added.name,
+            "path": added.path.display().to_string(),
+            "deduplicated": added.deduplicated,
+            "linked_from": linked,
+        });
+        println!("{}", serde_json::to_string_pretty(&result)?);
+    } else {
+        let note = if added.deduplicated { " (already stored)" } else { "" };
+        println!("Stored {} as {}{note}", file.display(), added.path.display());
+        if let Some(doc) = linked {
+            println!("Linked from {doc} under \"{}\"", args.section);
+        }
+    }
+    Ok(())
+}
+
+fn list(args: &AssetsArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let store = AssetStore::open(&args.dir)?;
+
+    if args.format == "json" {
+        let items: Vec<serde_json::Value> = store
+            .records()
+            .iter()
+            .map(|(name, r)| {
+                serde_json::json!({
+                    "name": name,
+                    "original_name": r.original_name,
+                    "size": r.size,
+                    "referrers": r.referrers,
+                })
+            })
+            .collect();
+        println!("{}", serde_json::to_string_pretty(&items)?);
+        return Ok(());
+    }
+
+    if store.records().is_empty() {
+        println!("No assets stored.");
+        return Ok(());
+    }
+    for (name, r) in store.records() {
+        println!("{name}  {}  ({} bytes)", r.original_name, r.size);
+        for referrer in &r.referrers {
+            println!("  <- {}", referrer.display());
+        }
+    }
+    Ok(())
+}
+
+fn gc(args: &AssetsArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let mut store = AssetStore::open(&args.dir)?;
+    let removed = store.gc(args.dry_run)?;
+    if !args.dry_run {
+        store.save()?;
+    }
+
+    if args.format == "json" {
+        let result = serde_json::json!({
+            "removed": removed,
+            "count": removed.len(),
+            "dry_run": args.dry_run,
+        });
+        println!("{}", serde_json::to_string_pretty(&result)?);
+    } else if removed.is_empty() {
+        println!("No unreferenced assets.");
+    } else {
+        let verb = if args.dry_run { "Would remove" } else { "Removed" };
+        for name in &removed {
+            println!("{verb} {name}");
+        }
+        println!("\n{} unreferenced asset(s).", removed.len());
+    }
+    Ok(())
+}
@@ -0,0 +1,83 @@
+use std::path::PathBuf;
+
+use clap::Args;
+use md_db::badges;
+use md_db::marker;
+use md_db::schema::Schema;
+use md_db::undo::Recorder;
+use md_db::users::UserConfig;
+use md_db::validation;
+
+#[derive(Debug, Args)]
+pub struct BadgesArgs {
+    /// Directory containing markdown files
+    #[arg(default_value = ".")]
+    pub dir: PathBuf,
+
+    /// Path to KDL schema file, or `auto` to use the closest one above the target
+    #[arg(long, default_value = "auto")]
+    pub schema: PathBuf,
+
+    /// Path to user/team config YAML file (for the validation status)
+    #[arg(long)]
+    pub users: Option<PathBuf>,
+
+    /// Rewrite the `<!-- md-db:badges -->` block in this file (e.g. README.md)
+    #[arg(long)]
+    pub write: Option<PathBuf>,
+
+    /// With --write: fail if the block is out of date instead of rewriting it (for CI)
+    #[arg(long, requires = "write")]
+    pub check: bool,
+
+    /// Output format when printing: markdown, json
+    #[arg(long, default_value = "markdown")]
+    pub format: String,
+}
+
+pub fn run(args: &BadgesArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let schema = Schema::from_file(md_db::project::resolve_schema(&args.schema, &args.dir)?)?;
+    let user_config = match &args.users {
+        Some(path) => Some(UserConfig::from_file(path)?),
+        None => None,
+    };
+    let result = validation::validate_directory(&args.dir, &schema, None, user_config.as_ref())?;
+    let today = md_db::template::format_today();
+    let project = badges::project(&args.dir, &schema, &today, &result)?;
+
+    let Some(ref file) = args.write else {
+        match args.format.as_str() {
+            "json" => println!("{}", serde_json::to_string_pretty(&project)?),
+            _ => print!("{}", project.to_markdown()),
+        }
+        return Ok(());
+    };
+
+    let text = std::fs::read_to_string(file)?;
+    let updated = marker::replace_block(&text, badges::BLOCK_NAME, &project.to_markdown())?.ok_or_else(|| {
+        format!(
+            "{} has no badge block; add the lines {} and {} where the badges should go",
+            file.display(),
+            marker::start_marker(badges::BLOCK_NAME),
+            marker::end_marker(badges::BLOCK_NAME)
+        )
+    })?;
+
+    if updated == text {
+        eprintln!("{}: badges up to date", file.display());
+    } else if args.check {
+        return Err(format!(
+            "{}: badges are out of date; run `md-db badges --write {}`",
+            file.display(),
+            file.display()
+        )
+        .into());
+    } else {
+        let mut undo = Recorder::new("badges");
+        undo.track(file);
+        md_db::durability::write(file, updated)?;
+        undo.finish()?;
+        eprintln!("{}: badges updated", file.display());
+    }
+    Ok(())
+}
@@ -4,6 +4,8 @@ use std::path::PathBuf;
 use clap::Args;
 use md_db::discovery::{self, Filter};
 use md_db::document::Document;
+use md_db::schema::Schema;
+use md_db::undo::Recorder;
 
 #[derive(Debug, Args)]
 pub struct BatchArgs {
@@ -41,6 +43,14 @@ pub struct BatchArgs {
     /// Glob pattern for filenames (default: "*.md")
     #[arg(long)]
     pub pattern: Option<String>,
+
+    /// Path to KDL schema file, or `auto`; status changes are checked against its `workflow`
+    #[arg(long, default_value = "auto")]
+    pub schema: PathBuf,
+
+    /// Allow status changes the type's workflow forbids
+    #[arg(long)]
+    pub force: bool,
 }
 
 pub fn run(args: &BatchArgs) -> Result<(), Box<dyn std::error::Error>> {
@@ -68,45 +78,7 @@ pub fn run(args: &BatchArgs) -> Result<(), Box<dyn std::error::Error>> {
         })
         .collect::<std::result::Result<Vec<_>, _>>()?;
 
-    // Build filters (same logic as list.rs)
-    let mut filters = Vec::new();
-    for f in &args.fields {
-        if let Some((key, value)) = f.split_once('=') {
-            filters.push(Filter::FieldEquals {
-                key: key.to_string(),
-                value: value.to_string(),
-            });
-        }
-    }
-    for f in &args.not_fields {
-        if let Some((key, value)) = f.split_once("!=") {
-            filters.push(Filter::FieldNotEquals {
-                key: key.to_string(),
-                value: value.to_string(),
-            });
-        } else if let Some((key, value)) = f.split_once('=') {
-            filters.push(Filter::FieldNotEquals {
-                key: key.to_string(),
-                value: value.to_string(),
-            });
-        }
-    }
-    for f in &args.contains {
-        if let Some((key, value)) = f.split_once("~=") {
-            filters.push(Filter::FieldContains {
-                key: key.to_string(),
-                value: value.to_string(),
-            });
-        } else if let Some((key, value)) = f.split_once('=') {
-            filters.push(Filter::FieldContains {
-                key: key.to_string(),
-                value: value.to_string(),
-            });
-        }
-    }
-    for f in &args.has_fields {
-        filters.push(Filter::HasField(f.clone()));
-    }
+    let filters = build_filters(&args.fields, &args.not_fields, &args.contains, &args.has_fields);
 
     let pattern = args.pattern.as_deref();
     let files = discovery::discover_files(&args.dir, pattern, &filters, false)?;
@@ -116,6 +88,25 @@ pub fn run(args: &BatchArgs) -> Result<(), Box<dyn std::error::Error>> {
         return Ok(());
     }
 
+    // Refuse the whole batch before writing anything if a change breaks a workflow
+    if let Some(schema_path) = md_db::project::resolve_optional_schema(&args.schema, &args.dir)? {
+        let schema = Schema::from_file(schema_path)?;
+        let violations = workflow_violations(&files, &set_pairs, &schema)?;
+        if !violations.is_empty() {
+            if !args.force {
+                return Err(format!(
+                    "{} document(s) would break their workflow; pass --force to override:\n  {}",
+                    violations.len(),
+                    violations.join("\n  ")
+                )
+                .into());
+            }
+            for v in &violations {
+                eprintln!("warning: {v} (forced)");
+            }
+        }
+    }
+
     // Confirmation prompt (skip for --yes or --dry-run)
     if !args.yes && !args.dry_run {
         print!(
@@ -131,6 +122,7 @@ pub fn run(args: &BatchArgs) -> Result<(), Box<dyn std::error::Error>> {
         }
     }
 
+    let mut undo = Recorder::new("batch");
     let mut changed = 0usize;
     for path in &files {
         if args.dry_run {
@@ -143,10 +135,16 @@ pub fn run(args: &BatchArgs) -> Result<(), Box<dyn std::error::Error>> {
         for &(key, value) in &set_pairs {
             doc.set_field_from_str(key, value);
         }
-        doc.save()?;
+        undo.track(path);
+        if let Err(e) = doc.save() {
+            // Keep what was already written undoable
+            undo.finish()?;
+            return Err(e.into());
+        }
         println!("updated {}", path.display());
         changed += 1;
     }
+    undo.finish()?;
 
     if args.dry_run {
         println!(
@@ -160,6 +158,75 @@ pub fn run(args: &BatchArgs) -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
+/// Frontmatter filters from `key=value` (equals), `key!=value` (not equals),
+/// `key~=value` (contains), and field-name (has) lists; shared with the MCP `md-db-batch` tool.
+pub(super) fn build_filters(
+    fields: &[String],
+    not_fields: &[String],
+    contains: &[String],
+    has_fields: &[String],
+) -> Vec<Filter> {
+    let mut filters = Vec::new();
+    for f in fields {
+        if let Some((key, value)) = f.split_once('=') {
+            filters.push(Filter::FieldEquals {
+                key: key.to_string(),
+                value: value.to_string(),
+            });
+        }
+    }
+    for f in not_fields {
+        if let Some((key, value)) = f.split_once("!=") {
+            filters.push(Filter::FieldNotEquals {
+                key: key.to_string(),
+                value: value.to_string(),
+            });
+        } else if let Some((key, value)) = f.split_once('=') {
+            filters.push(Filter::FieldNotEquals {
+                key: key.to_string(),
+                value: value.to_string(),
+            });
+        }
+    }
+    for f in contains {
+        if let Some((key, value)) = f.split_once("~=") {
+            filters.push(Filter::FieldContains {
+                key: key.to_string(),
+                value: value.to_string(),
+            });
+        } else if let Some((key, value)) = f.split_once('=') {
+            filters.push(Filter::FieldContains {
+                key: key.to_string(),
+                value: value.to_string(),
+            });
+        }
+    }
+    for f in has_fields {
+        filters.push(Filter::HasField(f.clone()));
+    }
+    filters
+}
+
+/// `path: message` for each file whose status change under `set_pairs` its workflow forbids.
+pub(super) fn workflow_violations(
+    files: &[PathBuf],
+    set_pairs: &[(&str, &str)],
+    schema: &Schema,
+) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+    let mut violations = Vec::new();
+    for path in files {
+        let mut doc = Document::from_file(path)?;
+        let Some(before) = doc.frontmatter.clone() else { continue };
+        for &(key, value) in set_pairs {
+            doc.set_field_from_str(key, value);
+        }
+        if let Some(msg) = doc.frontmatter.as_ref().and_then(|after| schema.transition_violation(&before, after)) {
+            violations.push(format!("{}: {msg}", path.display()));
+        }
+    }
+    Ok(violations)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -198,6 +265,8 @@ mod tests {
             dry_run: true,
             yes: false,
             pattern: None,
+            schema: PathBuf::from("auto"),
+            force: false,
         };
 
         run(&args).unwrap();
@@ -238,6 +307,8 @@ mod tests {
             dry_run: false,
             yes: true,
             pattern: None,
+            schema: PathBuf::from("auto"),
+            force: false,
         };
 
         run(&args).unwrap();
@@ -254,6 +325,41 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_batch_rejects_illegal_transition() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(
+            dir.path().join("schema.kdl"),
+            "type \"adr\" {\n    workflow {\n        transition \"proposed\" to=\"accepted\"\n    }\n}\n",
+        )
+        .unwrap();
+        write_doc(dir.path(), "a.md", "---\ntype: adr\nstatus: proposed\n---\n# A\n");
+        write_doc(dir.path(), "b.md", "---\ntype: adr\nstatus: accepted\n---\n# B\n");
+
+        let mut args = BatchArgs {
+            dir: dir.path().to_path_buf(),
+            fields: vec!["type=adr".to_string()],
+            not_fields: vec![],
+            has_fields: vec![],
+            contains: vec![],
+            set_fields: vec!["status=proposed".to_string()],
+            dry_run: false,
+            yes: true,
+            pattern: None,
+            schema: PathBuf::from("auto"),
+            force: false,
+        };
+
+        let msg = run(&args).unwrap_err().to_string();
+        assert!(msg.contains("b.md: status: accepted \u{2192} proposed is not an allowed transition"), "{msg}");
+        // Nothing was written
+        assert!(fs::read_to_string(dir.path().join("b.md")).unwrap().contains("status: accepted"));
+
+        args.force = true;
+        run(&args).unwrap();
+        assert!(fs::read_to_string(dir.path().join("b.md")).unwrap().contains("status: proposed"));
+    }
+
     #[test]
     fn test_batch_requires_filter() {
         let dir = tempfile::tempdir().unwrap();
@@ -267,6 +373,8 @@ mod tests {
             dry_run: false,
             yes: true,
             pattern: None,
+            schema: PathBuf::from("auto"),
+            force: false,
         };
 
         let result = run(&args);
@@ -0,0 +1,55 @@
+use std::path::PathBuf;
+
+use clap::Args;
+use md_db::history;
+
+#[derive(Debug, Args)]
+pub struct BlameArgs {
+    /// Path to the markdown file
+    pub file: PathBuf,
+
+    /// Frontmatter field to blame (dotted path for nested values); repeatable, default every field
+    #[arg(long)]
+    pub field: Vec<String>,
+
+    /// Output format: text, json
+    #[arg(long, default_value = "text")]
+    pub format: String,
+}
+
+pub fn run(args: &BlameArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let blamed = history::field_blame(&args.file, &args.field)?;
+
+    if args.format == "json" {
+        println!("{}", serde_json::to_string_pretty(&blamed)?);
+        return Ok(());
+    }
+    if blamed.is_empty() {
+        eprintln!("{}: no frontmatter fields", args.file.display());
+        return Ok(());
+    }
+
+    let field_width = blamed.iter().map(|b| b.field.chars().count()).max().unwrap_or(0).max(5);
+    let author_width = blamed
+        .iter()
+        .filter_map(|b| b.change.as_ref().map(|c| c.author.chars().count()))
+        .max()
+        .unwrap_or(0)
+        .max(13);
+    println!("{:<field_width$}  {:<10}  {:<7}  {:<author_width$}  VALUE", "FIELD", "DATE", "COMMIT", "AUTHOR");
+    for b in &blamed {
+        let value = b.value.as_deref().unwrap_or("(not set)");
+        match &b.change {
+            Some(c) => {
+                let short = &c.commit[..c.commit.len().min(7)];
+                println!("{:<field_width$}  {:<10}  {short:<7}  {:<author_width$}  {value}", b.field, c.date, c.author);
+            }
+            // Like `git blame`, edits in the working tree belong to no commit yet
+            None => println!(
+                "{:<field_width$}  {:<10}  {:<7}  {:<author_width$}  {value}",
+                b.field, "-", "-", "(uncommitted)"
+            ),
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,90 @@
+use std::path::PathBuf;
+
+use clap::Args;
+use md_db::cache_file::{self, CacheState};
+use md_db::schema::Schema;
+
+#[derive(Debug, Args)]
+pub struct CacheArgs {
+    /// Action: status, clear
+    pub action: String,
+
+    /// Docs directory whose .md-db/ caches to inspect
+    #[arg(default_value = ".")]
+    pub dir: PathBuf,
+
+    /// Path to KDL schema file, or `auto`; `status` checks the graph snapshot against it
+    #[arg(long, default_value = "auto")]
+    pub schema: PathBuf,
+
+    /// Output format: text, json
+    #[arg(long, default_value = "text")]
+    pub format: String,
+}
+
+pub fn run(args: &CacheArgs) -> Result<(), Box<dyn std::error::Error>> {
+    match args.action.as_str() {
+        "status" => status(args),
+        "clear" => {
+            let removed = cache_file::clear(&args.dir)?;
+            if args.format == "json" {
+                let paths: Vec<String> = removed.iter().map(|p| p.display().to_string()).collect();
+                println!("{}", serde_json::to_string_pretty(&serde_json::json!({ "removed": paths }))?);
+            } else {
+                for path in &removed {
+                    println!("removed {}", path.display());
+                }
+                eprintln!("removed {} cache file(s); they are rebuilt on next use", removed.len());
+            }
+            Ok(())
+        }
+        _ => Err(format!("unknown action: {} (expected: status, clear)", args.action).into()),
+    }
+}
+
+fn status(args: &CacheArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let schema = md_db::project::resolve_optional_schema(&args.schema, &args.dir)?
+        .map(Schema::from_file)
+        .transpose()?;
+    let files = cache_file::status(&args.dir, schema.as_ref())?;
+
+    if args.format == "json" {
+        let entries: Vec<serde_json::Value> = files
+            .iter()
+            .map(|f| {
+                let (state, reason) = match &f.state {
+                    CacheState::Ok => ("ok", None),
+                    CacheState::Outdated(r) => ("outdated", Some(r.as_str())),
+                    CacheState::Corrupt(r) => ("corrupt", Some(r.as_str())),
+                    CacheState::Legacy => ("legacy", None),
+                };
+                serde_json::json!({
+                    "path": f.path.display().to_string(),
+                    "kind": f.kind_name(),
+                    "version": f.header.map(|h| h.version),
+                    "size": f.size,
+                    "state": state,
+                    "reason": reason,
+                })
+            })
+            .collect();
+        println!("{}", serde_json::to_string_pretty(&entries)?);
+        return Ok(());
+    }
+
+    if files.is_empty() {
+        println!("no caches in {}", args.dir.join(md_db::graph_cache::CACHE_DIR).display());
+        return Ok(());
+    }
+    for f in &files {
+        let version = f.header.map(|h| format!(" v{}", h.version)).unwrap_or_default();
+        let state = match &f.state {
+            CacheState::Ok => "ok".to_string(),
+            CacheState::Outdated(r) => format!("outdated ({r}); rebuilt on next use"),
+            CacheState::Corrupt(r) => format!("corrupt ({r}); rebuilt on next use"),
+            CacheState::Legacy => "legacy, no longer read; remove with `md-db cache clear`".to_string(),
+        };
+        println!("{}  {}{version}  {} bytes  {state}", f.path.display(), f.kind_name(), f.size);
+    }
+    Ok(())
+}
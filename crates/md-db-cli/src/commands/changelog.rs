@@ -0,0 +1,99 @@
+use std::path::PathBuf;
+
+use clap::Args;
+use md_db::changelog;
+use md_db::document::Document;
+use md_db::schema::Schema;
+use md_db::undo::Recorder;
+use md_db::validation::{self, Severity};
+
+#[derive(Debug, Args)]
+pub struct ChangelogArgs {
+    /// Action: add, release
+    pub action: String,
+
+    /// Entry text (for `add`) or version (for `release`)
+    pub value: String,
+
+    /// Changelog file (created on the first `add`)
+    #[arg(long, default_value = "CHANGELOG.md")]
+    pub file: PathBuf,
+
+    /// Release the entry goes under (for `add`)
+    #[arg(long, default_value = changelog::UNRELEASED)]
+    pub section: String,
+
+    /// Change kind: added, changed, deprecated, removed, fixed, security (for `add`)
+    #[arg(long)]
+    pub kind: Option<String>,
+
+    /// Release date as YYYY-MM-DD, or "today" (for `release`)
+    #[arg(long, default_value = "today")]
+    pub date: String,
+
+    /// Validate the result against the schema's singleton type matching the file name (`auto` finds the schema)
+    #[arg(long)]
+    pub schema: Option<PathBuf>,
+
+    /// Print the updated changelog instead of writing it
+    #[arg(long)]
+    pub dry_run: bool,
+}
+
+pub fn run(args: &ChangelogArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let mut doc = match args.action.as_str() {
+        "add" => {
+            let kind = args.kind.as_deref().ok_or("changelog add requires --kind")?;
+            let mut doc = if args.file.exists() {
+                Document::from_file(&args.file)?
+            } else {
+                changelog::new_changelog()
+            };
+            changelog::add_entry(&mut doc, &args.section, kind, &args.value)?;
+            eprintln!(
+                "{}: {} / {}: {}",
+                args.file.display(),
+                args.section,
+                changelog::normalize_kind(kind)?,
+                args.value.trim()
+            );
+            doc
+        }
+        "release" => {
+            let date = match args.date.as_str() {
+                "today" => md_db::template::format_today(),
+                other => other.to_string(),
+            };
+            let mut doc = Document::from_file(&args.file)?;
+            changelog::release(&mut doc, &args.value, &date)?;
+            eprintln!("{}: released {} ({date})", args.file.display(), args.value);
+            doc
+        }
+        _ => return Err(format!("unknown action: {} (expected: add, release)", args.action).into()),
+    };
+    doc.path = Some(args.file.clone());
+
+    if let Some(ref schema_path) = args.schema {
+        let schema = Schema::from_file(md_db::project::resolve_schema(schema_path, &args.file)?)?;
+        let type_def = schema
+            .singleton_for(&args.file)
+            .ok_or_else(|| format!("schema has no singleton type matching {}", args.file.display()))?;
+        let result = validation::validate_singleton(&doc, type_def, None);
+        if result.diagnostics.iter().any(|d| d.severity == Severity::Error) {
+            for d in &result.diagnostics {
+                eprintln!("  {}: {} ({})", d.code, d.message, d.location);
+            }
+            return Err(format!("{} would not validate; nothing written", args.file.display()).into());
+        }
+    }
+
+    if args.dry_run {
+        print!("{}", doc.raw);
+    } else {
+        let mut undo = Recorder::new("changelog");
+        undo.track(&args.file);
+        doc.save()?;
+        undo.finish()?;
+    }
+    Ok(())
+}
@@ -0,0 +1,76 @@
+use std::path::PathBuf;
+
+use clap::Args;
+use md_db::conflicts;
+use md_db::schema::Schema;
+use md_db::validation::Severity;
+
+#[derive(Debug, Args)]
+pub struct ConflictsArgs {
+    /// Docs directory inside the repository
+    #[arg(default_value = ".")]
+    pub dir: PathBuf,
+
+    /// Path to KDL schema file, or `auto` to use the closest one above the target
+    #[arg(long, default_value = "auto")]
+    pub schema: PathBuf,
+
+    /// Branch being merged into
+    #[arg(long, default_value = "origin/main")]
+    pub base: String,
+
+    /// Branch being merged
+    #[arg(long, default_value = "HEAD")]
+    pub head: String,
+
+    /// Exit non-zero on warnings as well as errors
+    #[arg(long)]
+    pub strict: bool,
+
+    /// Output format: text, json, github (workflow annotations)
+    #[arg(long, default_value = "text")]
+    pub format: String,
+}
+
+pub fn run(args: &ConflictsArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let schema = Schema::from_file(md_db::project::resolve_schema(&args.schema, &args.dir)?)?;
+    let found = conflicts::detect_between(&args.base, &args.head, &args.dir, &schema)?;
+
+    match args.format.as_str() {
+        "json" => {
+            let items: Vec<serde_json::Value> = found.iter().map(|c| c.to_json()).collect();
+            let result = serde_json::json!({
+                "base": args.base,
+                "head": args.head,
+                "conflicts": items,
+                "count": items.len(),
+            });
+            println!("{}", serde_json::to_string_pretty(&result)?);
+        }
+        "github" => {
+            for c in &found {
+                let level = match c.severity {
+                    Severity::Error => "error",
+                    Severity::Warning => "warning",
+                };
+                println!("::{level} title={} {}::{}", c.code, c.id, c.message);
+            }
+        }
+        _ => {
+            if found.is_empty() {
+                println!("No semantic conflicts between {} and {}.", args.base, args.head);
+            }
+            for c in &found {
+                println!("{}[{}] {}: {}", c.severity, c.code, c.id, c.message);
+            }
+        }
+    }
+
+    let failed = found
+        .iter()
+        .any(|c| c.severity == Severity::Error || args.strict);
+    if failed {
+        std::process::exit(1);
+    }
+    Ok(())
+}
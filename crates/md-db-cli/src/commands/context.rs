@@ -0,0 +1,77 @@
+use std::path::PathBuf;
+
+use clap::Args;
+use md_db::context::{self, ContextOptions};
+use md_db::graph::DocGraph;
+use md_db::schema::Schema;
+
+#[derive(Debug, Args)]
+pub struct ContextArgs {
+    /// Focus document: ID, uid, or file path
+    pub id: Option<String>,
+
+    /// Focus on the top full-text matches for this query instead of a single document
+    #[arg(long)]
+    pub query: Option<String>,
+
+    /// Number of focus documents taken from --query
+    #[arg(long, default_value = "3")]
+    pub limit: usize,
+
+    /// Directory containing markdown files
+    #[arg(long, default_value = ".")]
+    pub dir: PathBuf,
+
+    /// Path to KDL schema file, or `auto` to use the closest one above the target
+    #[arg(long, default_value = "auto")]
+    pub schema: PathBuf,
+
+    /// Token budget for the whole pack (estimated at ~4 characters per token)
+    #[arg(long, default_value = "4000")]
+    pub max_tokens: usize,
+
+    /// Section to include (repeatable; default: the type's required sections)
+    #[arg(long = "section")]
+    pub sections: Vec<String>,
+
+    /// Output format: markdown, json
+    #[arg(long, default_value = "markdown")]
+    pub format: String,
+
+    /// Rebuild the graph from scratch instead of using the .md-db/graph.bin snapshot
+    #[arg(long)]
+    pub no_cache: bool,
+}
+
+pub fn run(args: &ContextArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let schema = Schema::from_file(md_db::project::resolve_schema(&args.schema, &args.dir)?)?;
+    let graph = if args.no_cache {
+        DocGraph::build(&args.dir, &schema)?
+    } else {
+        DocGraph::build_cached(&args.dir, &schema)?
+    };
+
+    let focus = match (&args.id, &args.query) {
+        (Some(id), None) => vec![context::resolve_id(&graph, id)],
+        (None, Some(query)) => {
+            let ids = context::focus_from_query(&graph, &args.dir, query, args.limit)?;
+            if ids.is_empty() {
+                return Err(format!("no documents match: {query}").into());
+            }
+            ids
+        }
+        _ => return Err("specify a document ID or --query (not both)".into()),
+    };
+
+    let opts = ContextOptions {
+        max_tokens: args.max_tokens,
+        sections: args.sections.clone(),
+    };
+    let pack = context::build(&graph, &schema, &focus, &opts)?;
+
+    match args.format.as_str() {
+        "json" => println!("{}", serde_json::to_string_pretty(&pack)?),
+        _ => print!("{}", pack.to_markdown()),
+    }
+    Ok(())
+}
@@ -4,17 +4,19 @@ use clap::Args;
 use md_db::document::Document;
 use md_db::graph::{DocGraph, path_to_id};
 use md_db::schema::Schema;
+use md_db::undo::Recorder;
 
 #[derive(Debug, Args)]
 pub struct DeprecateArgs {
     /// Path to the markdown file to deprecate
     pub file: PathBuf,
 
-    /// Path to KDL schema file
-    #[arg(long)]
+    /// Path to KDL schema file, or `auto` to use the closest one above the target
+    #[arg(long, default_value = "auto")]
     pub schema: PathBuf,
 
-    /// Mark as superseded by this document ID (sets status=superseded + superseded_by field)
+    /// Mark as superseded by this document ID (sets status=superseded + superseded_by field;
+    /// the type's `status-field` can rename both the field and the value)
     #[arg(long)]
     pub superseded_by: Option<String>,
 
@@ -28,25 +30,35 @@ pub struct DeprecateArgs {
 }
 
 pub fn run(args: &DeprecateArgs) -> Result<(), Box<dyn std::error::Error>> {
-    let schema = Schema::from_file(&args.schema)?;
+    let schema = Schema::from_file(md_db::project::resolve_schema(&args.schema, &args.file)?)?;
     let mut doc = Document::from_file(&args.file)?;
     let doc_id = path_to_id(&args.file);
 
+    // The type's status-field names the field and the values to set
+    let doc_type = doc.frontmatter.as_ref().and_then(|fm| fm.get_display("type")).unwrap_or_default();
+    let status = schema.status_field(&doc_type);
     if let Some(ref replacement_id) = args.superseded_by {
         // Set status=superseded and add superseded_by field
-        doc.set_field_from_str("status", "superseded");
+        doc.set_field_from_str(&status.field, status.superseded_value());
         doc.set_field_from_str("superseded_by", replacement_id);
-        eprintln!("{doc_id}: status=superseded, superseded_by={replacement_id}");
+        eprintln!(
+            "{doc_id}: {}={}, superseded_by={replacement_id}",
+            status.field,
+            status.superseded_value()
+        );
     } else {
         // Just deprecate
-        doc.set_field_from_str("status", "deprecated");
-        eprintln!("{doc_id}: status=deprecated");
+        doc.set_field_from_str(&status.field, status.deprecated_value());
+        eprintln!("{doc_id}: {}={}", status.field, status.deprecated_value());
     }
 
     if args.dry_run {
         print!("{}", doc.raw);
     } else {
+        let mut undo = Recorder::new("deprecate");
+        undo.track(&args.file);
         doc.save()?;
+        undo.finish()?;
 
         // If --dir is provided, scan for backlinks and add a warning
         if let Some(ref dir) = args.dir {
@@ -1,12 +1,15 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use clap::Args;
-use md_db::schema::{Cardinality, FieldType, Schema};
+use md_db::schema::{Cardinality, FieldType, PromoteSource, Schema};
+use md_db::template;
+use md_db::terminal::{paint, Style};
+use md_db::users::UserConfig;
 
 #[derive(Debug, Args)]
 pub struct DescribeArgs {
-    /// Path to KDL schema file
-    #[arg(long)]
+    /// Path to KDL schema file, or `auto` to use the closest one above the target
+    #[arg(long, default_value = "auto")]
     pub schema: PathBuf,
 
     /// Show details for a specific type
@@ -25,13 +28,21 @@ pub struct DescribeArgs {
     #[arg(long)]
     pub export: bool,
 
+    /// Print a fully filled-in example document for the type (requires --type)
+    #[arg(long)]
+    pub example: bool,
+
+    /// Path to user/team config YAML file (example documents use real handles)
+    #[arg(long)]
+    pub users: Option<PathBuf>,
+
     /// Output format: text, json
     #[arg(long, default_value = "text")]
     pub format: String,
 }
 
 pub fn run(args: &DescribeArgs) -> Result<(), Box<dyn std::error::Error>> {
-    let schema = Schema::from_file(&args.schema)?;
+    let schema = Schema::from_file(md_db::project::resolve_schema(&args.schema, Path::new("."))?)?;
 
     let json_mode = args.format == "json";
 
@@ -50,11 +61,33 @@ pub fn run(args: &DescribeArgs) -> Result<(), Box<dyn std::error::Error>> {
         return Ok(());
     }
 
+    if args.example && args.doc_type.is_none() {
+        return Err("--example requires --type".into());
+    }
+
     if let Some(ref type_name) = args.doc_type {
         let type_def = schema
             .get_type(type_name)
             .ok_or_else(|| format!("unknown type \"{type_name}\""))?;
 
+        if args.example {
+            let user_config = match &args.users {
+                Some(path) => Some(UserConfig::from_file(path)?),
+                None => None,
+            };
+            let example = template::generate_example(type_def, &schema, user_config.as_ref());
+            if json_mode {
+                let obj = serde_json::json!({
+                    "type": type_def.name,
+                    "example": example,
+                });
+                println!("{}", serde_json::to_string_pretty(&obj)?);
+            } else {
+                print!("{example}");
+            }
+            return Ok(());
+        }
+
         if let Some(ref field_name) = args.field {
             let field_def = type_def
                 .fields
@@ -89,7 +122,7 @@ pub fn run(args: &DescribeArgs) -> Result<(), Box<dyn std::error::Error>> {
 // ─── Text output ─────────────────────────────────────────────────────────────
 
 fn print_overview(schema: &Schema) {
-    println!("Types:");
+    println!("{}", paint("Types:", Style::Heading));
     for t in &schema.types {
         let desc = t
             .description
@@ -112,23 +145,14 @@ fn print_overview(schema: &Schema) {
     }
 
     if !schema.relations.is_empty() {
-        println!("\nRelations:");
-        for r in &schema.relations {
-            let inv = r
-                .inverse
-                .as_ref()
-                .map(|i| format!(" → {i}"))
-                .unwrap_or_default();
-            let card = match r.cardinality {
-                Cardinality::One => "one",
-                Cardinality::Many => "many",
-            };
-            let desc = r
-                .description
-                .as_ref()
-                .map(|d| format!("  {d}"))
-                .unwrap_or_default();
-            println!("  {}{inv}  ({card}){desc}", r.name);
+        println!("\n{}", paint("Relations:", Style::Heading));
+        print_relation_list(schema, false);
+    }
+
+    if !schema.coverage.is_empty() {
+        println!("\n{}", paint("Coverage:", Style::Heading));
+        for c in &schema.coverage {
+            println!("  {}  [{}]", c.describe(), c.severity);
         }
     }
 }
@@ -139,17 +163,20 @@ fn print_type_detail(type_def: &md_db::schema::TypeDef, schema: &Schema) {
         .as_ref()
         .map(|d| format!(" — {d}"))
         .unwrap_or_default();
-    println!("Type: {}{desc}", type_def.name);
+    println!("{}{desc}", paint(format!("Type: {}", type_def.name), Style::Heading));
 
     if let Some(ref folder) = type_def.folder {
         println!("  folder: {folder}");
     }
+    if let Some(ref template) = type_def.template {
+        println!("  template: {template}");
+    }
     if let Some(max) = type_def.max_count {
         println!("  max_count: {max}");
     }
 
     if !type_def.fields.is_empty() {
-        println!("\nFields:");
+        println!("\n{}", paint("Fields:", Style::Heading));
         for f in &type_def.fields {
             let req = if f.required { "required" } else { "" };
             let type_str = field_type_short(&f.field_type);
@@ -170,17 +197,24 @@ fn print_type_detail(type_def: &md_db::schema::TypeDef, schema: &Schema) {
             if let Some(ref def) = f.default {
                 println!("{:>35}default: {def}", "");
             }
+            if let Some(range) = f.range_summary() {
+                println!("{:>35}range: {range}", "");
+            }
+            if !f.target_types.is_empty() {
+                println!("{:>35}target: {}", "", f.target_types.join(", "));
+            }
         }
     }
 
-    if !type_def.sections.is_empty() {
-        println!("\nSections:");
+    if !type_def.sections.is_empty() || !type_def.section_patterns.is_empty() {
+        println!("\n{}", paint("Sections:", Style::Heading));
         print_section_tree(&type_def.sections, 1);
+        print_section_patterns(&type_def.section_patterns, 1);
     }
 
     // Conditional rules
     if !type_def.rules.is_empty() {
-        println!("\nRules:");
+        println!("\n{}", paint("Rules:", Style::Heading));
         for r in &type_def.rules {
             println!(
                 "  \"{}\"  when {}={} -> require {}",
@@ -192,81 +226,99 @@ fn print_type_detail(type_def: &md_db::schema::TypeDef, schema: &Schema) {
         }
     }
 
+    // Body values mirrored into frontmatter
+    if !type_def.promotes.is_empty() {
+        println!("\n{}", paint("Promoted fields:", Style::Heading));
+        for p in &type_def.promotes {
+            println!("  {} <- {}", p.field, promote_source_label(p));
+        }
+    }
+
+    // Cross-document constraints
+    if !type_def.consistency.is_empty() {
+        println!("\n{}", paint("Consistent with:", Style::Heading));
+        for c in &type_def.consistency {
+            println!("  {}  ({})", c.describe(), c.severity);
+        }
+    }
+
     // Relations that apply to all types
     if !schema.relations.is_empty() {
-        println!("\nRelations (all types):");
-        for r in &schema.relations {
-            let inv = r
-                .inverse
-                .as_ref()
-                .map(|i| format!(" → {i}"))
-                .unwrap_or_default();
-            let card = match r.cardinality {
-                Cardinality::One => "one",
-                Cardinality::Many => "many",
-            };
-            let desc = r
-                .description
-                .as_ref()
-                .map(|d| format!("  {d}"))
-                .unwrap_or_default();
-            println!("  {}{inv}  ({card}){desc}", r.name);
-        }
+        println!("\n{}", paint("Relations (all types):", Style::Heading));
+        print_relation_list(schema, false);
     }
 }
 
 fn print_section_tree(sections: &[md_db::schema::SectionDef], depth: usize) {
     for s in sections {
-        let prefix: String = "#".repeat(depth);
         let req = if s.required { "required" } else { "" };
-        let desc = s
+        print_section(s, depth, &s.name, req);
+    }
+}
+
+/// Repeated sections print as `/pattern/` with their allowed count.
+fn print_section_patterns(patterns: &[md_db::schema::SectionPatternDef], depth: usize) {
+    for p in patterns {
+        let count = match (p.min, p.max) {
+            (Some(lo), Some(hi)) => format!("{lo}..{hi}"),
+            (Some(lo), None) => format!("{lo}.."),
+            (None, Some(hi)) => format!("..{hi}"),
+            (None, None) => "any".into(),
+        };
+        print_section(&p.section, depth, &format!("/{}/", p.pattern), &count);
+    }
+}
+
+fn print_section(s: &md_db::schema::SectionDef, depth: usize, label: &str, req: &str) {
+    let prefix: String = "#".repeat(depth);
+    let desc = s
+        .description
+        .as_ref()
+        .map(|d| format!("  {d}"))
+        .unwrap_or_default();
+    println!("  {prefix} {:<20}{:<10}{desc}", label, req);
+
+    // Content constraints
+    if let Some(ref c) = s.content {
+        let min = c
+            .min_paragraphs
+            .map(|n| format!("min {n} paragraph(s)"))
+            .unwrap_or_else(|| "prose".into());
+        println!("{:>35}content: {min}", "");
+    }
+    if let Some(ref l) = s.list {
+        let detail = l
+            .min_items
+            .map(|n| format!("min {n} item(s)"))
+            .unwrap_or_else(|| "required".into());
+        println!("{:>35}list: {detail}", "");
+    }
+    if let Some(ref d) = s.diagram {
+        let detail = d
+            .diagram_type
+            .as_ref()
+            .map(|t| format!("type={t}"))
+            .unwrap_or_else(|| "any".into());
+        println!("{:>35}diagram: {detail}", "");
+    }
+    if let Some(ref t) = s.table {
+        let cols: Vec<&str> = t.columns.iter().map(|c| c.name.as_str()).collect();
+        let desc = t
             .description
             .as_ref()
             .map(|d| format!("  {d}"))
             .unwrap_or_default();
-        println!("  {prefix} {:<20}{:<10}{desc}", s.name, req);
-
-        // Content constraints
-        if let Some(ref c) = s.content {
-            let min = c
-                .min_paragraphs
-                .map(|n| format!("min {n} paragraph(s)"))
-                .unwrap_or_else(|| "prose".into());
-            println!("{:>35}content: {min}", "");
-        }
-        if let Some(ref l) = s.list {
-            let detail = l
-                .min_items
-                .map(|n| format!("min {n} item(s)"))
-                .unwrap_or_else(|| "required".into());
-            println!("{:>35}list: {detail}", "");
-        }
-        if let Some(ref d) = s.diagram {
-            let detail = d
-                .diagram_type
-                .as_ref()
-                .map(|t| format!("type={t}"))
-                .unwrap_or_else(|| "any".into());
-            println!("{:>35}diagram: {detail}", "");
-        }
-        if let Some(ref t) = s.table {
-            let cols: Vec<&str> = t.columns.iter().map(|c| c.name.as_str()).collect();
-            let desc = t
-                .description
-                .as_ref()
-                .map(|d| format!("  {d}"))
-                .unwrap_or_default();
-            println!("{:>35}table: {}{desc}", "", cols.join(" | "));
-        }
+        println!("{:>35}table: {}{desc}", "", cols.join(" | "));
+    }
 
-        if !s.children.is_empty() {
-            print_section_tree(&s.children, depth + 1);
-        }
+    if !s.children.is_empty() {
+        print_section_tree(&s.children, depth + 1);
     }
+    print_section_patterns(&s.section_patterns, depth + 1);
 }
 
 fn print_field_detail(field_def: &md_db::schema::FieldDef) {
-    println!("Field: {}", field_def.name);
+    println!("{}", paint(format!("Field: {}", field_def.name), Style::Heading));
     println!("  type: {}", field_def.field_type);
     println!("  required: {}", field_def.required);
     if let Some(ref desc) = field_def.description {
@@ -281,6 +333,12 @@ fn print_field_detail(field_def: &md_db::schema::FieldDef) {
     if let FieldType::Enum(ref vals) = field_def.field_type {
         println!("  values: {}", vals.join(", "));
     }
+    if let Some(range) = field_def.range_summary() {
+        println!("  range: {range}");
+    }
+    if !field_def.target_types.is_empty() {
+        println!("  target: {}", field_def.target_types.join(", "));
+    }
 }
 
 fn print_relations(schema: &Schema) {
@@ -289,22 +347,49 @@ fn print_relations(schema: &Schema) {
         return;
     }
     println!("Relations:");
-    for r in &schema.relations {
-        let inv = r
-            .inverse
-            .as_ref()
-            .map(|i| format!(" → {i}"))
-            .unwrap_or_default();
-        let card = match r.cardinality {
-            Cardinality::One => "one",
-            Cardinality::Many => "many",
-        };
-        let desc = r
-            .description
-            .as_ref()
-            .map(|d| format!("\n    {d}"))
-            .unwrap_or_default();
-        println!("  {}{inv}  ({card}){desc}", r.name);
+    print_relation_list(schema, true);
+}
+
+/// Print relations organized by group (flat when the schema declares no groups).
+fn print_relation_list(schema: &Schema, desc_on_own_line: bool) {
+    let grouped = schema.has_relation_groups();
+    let indent = if grouped { "    " } else { "  " };
+    for group in schema.grouped_relations() {
+        if grouped {
+            let name = group.name.unwrap_or("other");
+            match group.description {
+                Some(d) => println!("  [{name}]  {d}"),
+                None => println!("  [{name}]"),
+            }
+        }
+        for r in &group.relations {
+            let inv = r
+                .inverse
+                .as_ref()
+                .map(|i| format!(" → {i}"))
+                .unwrap_or_default();
+            let card = match r.cardinality {
+                Cardinality::One => "one",
+                Cardinality::Many => "many",
+            };
+            let target = if r.target_types.is_empty() {
+                String::new()
+            } else {
+                format!(" → {}", r.target_types.join("|"))
+            };
+            let desc = r
+                .description
+                .as_ref()
+                .map(|d| {
+                    if desc_on_own_line {
+                        format!("\n{indent}  {d}")
+                    } else {
+                        format!("  {d}")
+                    }
+                })
+                .unwrap_or_default();
+            println!("{indent}{}{inv}  ({card}{target}){desc}", r.name);
+        }
     }
 }
 
@@ -319,6 +404,8 @@ fn field_type_short(ft: &FieldType) -> String {
         FieldType::RefArray => "ref[]".into(),
         FieldType::User => "user".into(),
         FieldType::UserArray => "user[]".into(),
+        FieldType::Date => "date".into(),
+        FieldType::DateTime => "datetime".into(),
     }
 }
 
@@ -348,6 +435,7 @@ fn overview_to_json(schema: &Schema) -> serde_json::Value {
     serde_json::json!({
         "types": types,
         "relations": relations_to_json(schema),
+        "relation_groups": relation_groups_to_json(schema),
     })
 }
 
@@ -391,12 +479,61 @@ fn type_to_json(
     if let Some(ref f) = type_def.folder {
         obj["folder"] = serde_json::Value::String(f.clone());
     }
+    if let Some(ref t) = type_def.template {
+        obj["template"] = serde_json::Value::String(t.clone());
+    }
     if let Some(m) = type_def.max_count {
         obj["max_count"] = serde_json::json!(m);
     }
+    if !type_def.section_patterns.is_empty() {
+        let patterns: Vec<serde_json::Value> =
+            type_def.section_patterns.iter().map(section_pattern_to_json).collect();
+        obj["section_patterns"] = serde_json::json!(patterns);
+    }
+    if !type_def.promotes.is_empty() {
+        let promotes: Vec<serde_json::Value> = type_def
+            .promotes
+            .iter()
+            .map(|p| serde_json::json!({ "field": p.field, "source": promote_source_label(p) }))
+            .collect();
+        obj["promotes"] = serde_json::json!(promotes);
+    }
+    if !type_def.order_by.is_empty() {
+        let order: Vec<serde_json::Value> = type_def
+            .order_by
+            .iter()
+            .map(|k| serde_json::json!({ "field": k.field, "desc": k.desc }))
+            .collect();
+        obj["order_by"] = serde_json::json!(order);
+    }
+    if !type_def.consistency.is_empty() {
+        let consistency: Vec<serde_json::Value> = type_def
+            .consistency
+            .iter()
+            .map(|c| {
+                serde_json::json!({
+                    "field": c.field,
+                    "relation": c.relation,
+                    "other_field": c.other_field,
+                    "op": c.op.to_string(),
+                    "severity": c.severity,
+                })
+            })
+            .collect();
+        obj["consistent_with"] = serde_json::json!(consistency);
+    }
     obj
 }
 
+fn promote_source_label(p: &md_db::schema::PromoteDef) -> String {
+    match &p.source {
+        PromoteSource::TableCell { table, column, row } => {
+            format!("section \"{}\" table {table} column \"{column}\" row {row}", p.section)
+        }
+        PromoteSource::YamlKey(key) => format!("section \"{}\" yaml key \"{key}\"", p.section),
+    }
+}
+
 fn field_to_json(f: &md_db::schema::FieldDef) -> serde_json::Value {
     let mut obj = serde_json::json!({
         "name": f.name,
@@ -415,6 +552,18 @@ fn field_to_json(f: &md_db::schema::FieldDef) -> serde_json::Value {
     if let FieldType::Enum(ref vals) = f.field_type {
         obj["values"] = serde_json::json!(vals);
     }
+    if let Some(ref min) = f.min {
+        obj["min"] = serde_json::Value::String(min.clone());
+    }
+    if let Some(ref max) = f.max {
+        obj["max"] = serde_json::Value::String(max.clone());
+    }
+    if f.not_future {
+        obj["not_future"] = serde_json::Value::Bool(true);
+    }
+    if !f.target_types.is_empty() {
+        obj["target_types"] = serde_json::json!(f.target_types);
+    }
     obj
 }
 
@@ -455,6 +604,9 @@ fn section_to_json(s: &md_db::schema::SectionDef) -> serde_json::Value {
         if let Some(ref desc) = t.description {
             table_obj["description"] = serde_json::Value::String(desc.clone());
         }
+        if let Some(ref order) = t.ordered_by {
+            table_obj["ordered_by"] = serde_json::json!({ "column": order.column, "ascending": order.ascending });
+        }
         obj["table"] = table_obj;
     }
     if !s.children.is_empty() {
@@ -462,6 +614,23 @@ fn section_to_json(s: &md_db::schema::SectionDef) -> serde_json::Value {
             s.children.iter().map(|c| section_to_json(c)).collect();
         obj["children"] = serde_json::json!(children);
     }
+    if !s.section_patterns.is_empty() {
+        let patterns: Vec<serde_json::Value> =
+            s.section_patterns.iter().map(section_pattern_to_json).collect();
+        obj["patterns"] = serde_json::json!(patterns);
+    }
+    obj
+}
+
+fn section_pattern_to_json(p: &md_db::schema::SectionPatternDef) -> serde_json::Value {
+    let mut obj = section_to_json(&p.section);
+    if let Some(map) = obj.as_object_mut() {
+        map.remove("name");
+        map.remove("required");
+    }
+    obj["pattern"] = serde_json::Value::String(p.pattern.clone());
+    obj["min"] = serde_json::json!(p.min);
+    obj["max"] = serde_json::json!(p.max);
     obj
 }
 
@@ -515,17 +684,34 @@ fn export_schema_json(schema: &Schema) -> serde_json::Value {
         })
         .collect();
 
+    let coverage: Vec<serde_json::Value> = schema
+        .coverage
+        .iter()
+        .map(|c| {
+            serde_json::json!({
+                "type": c.doc_type,
+                "relation": c.relation,
+                "to_type": c.to_type,
+                "status": c.statuses,
+                "severity": c.severity,
+            })
+        })
+        .collect();
+
     serde_json::json!({
         "types": types,
         "relations": relations_to_json(schema),
+        "relation_groups": relation_groups_to_json(schema),
         "ref_formats": ref_formats,
+        "coverage": coverage,
     })
 }
 
 fn relations_to_json(schema: &Schema) -> serde_json::Value {
     let rels: Vec<serde_json::Value> = schema
-        .relations
-        .iter()
+        .grouped_relations()
+        .into_iter()
+        .flat_map(|g| g.relations)
         .map(|r| {
             let mut obj = serde_json::json!({
                 "name": r.name,
@@ -538,8 +724,29 @@ fn relations_to_json(schema: &Schema) -> serde_json::Value {
             if let Some(ref desc) = r.description {
                 obj["description"] = serde_json::Value::String(desc.clone());
             }
+            if !r.target_types.is_empty() {
+                obj["target_types"] = serde_json::json!(r.target_types);
+            }
+            if let Some(ref group) = r.group {
+                obj["group"] = serde_json::Value::String(group.clone());
+            }
             obj
         })
         .collect();
     serde_json::json!(rels)
 }
+
+fn relation_groups_to_json(schema: &Schema) -> serde_json::Value {
+    let groups: Vec<serde_json::Value> = schema
+        .grouped_relations()
+        .iter()
+        .filter_map(|g| {
+            Some(serde_json::json!({
+                "name": g.name?,
+                "description": g.description,
+                "relations": g.relations.iter().map(|r| r.name.as_str()).collect::<Vec<_>>(),
+            }))
+        })
+        .collect();
+    serde_json::json!(groups)
+}
@@ -1,30 +1,44 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use clap::Args;
 use md_db::diff::{self, FieldChangeKind, SectionChangeKind};
 use md_db::document::Document;
+use md_db::migrate::{self, SchemaDiff};
 use md_db::output::OutputFormat;
+use md_db::schema::Schema;
+use md_db::terminal::{paint, Style};
 
 #[derive(Debug, Args)]
 pub struct DiffArgs {
-    /// Old version of the markdown file
+    /// Old version of the markdown file, or of a `.kdl` schema for a schema diff
     pub old: PathBuf,
 
-    /// New version of the markdown file (omit to read from stdin)
+    /// New version of the file (omit to read from stdin)
     pub new: Option<PathBuf>,
 
     /// Read new version from stdin instead of a file
     #[arg(long)]
     pub stdin: bool,
 
+    /// Path to KDL schema file, or `auto`; status changes its `workflow` forbids are labeled
+    #[arg(long, default_value = "auto")]
+    pub schema: PathBuf,
+
     /// Output format: text, json
     #[arg(long, default_value = "text")]
     pub format: String,
+
+    /// Schema diff: only show changes that can invalidate existing documents,
+    /// and exit 1 if there are any
+    #[arg(long)]
+    pub breaking_only: bool,
+
+    /// Schema diff: count the documents under DIR each breaking change would invalidate
+    #[arg(long)]
+    pub dir: Option<PathBuf>,
 }
 
 pub fn run(args: &DiffArgs) -> Result<(), Box<dyn std::error::Error>> {
-    let old_doc = Document::from_file(&args.old)?;
-
     let new_content = if args.stdin {
         let mut buf = String::new();
         std::io::Read::read_to_string(&mut std::io::stdin(), &mut buf)?;
@@ -37,11 +51,31 @@ pub fn run(args: &DiffArgs) -> Result<(), Box<dyn std::error::Error>> {
         std::fs::read_to_string(new_path)?
     };
 
+    if args.old.extension().is_some_and(|e| e == "kdl") {
+        return run_schema(args, &new_content);
+    }
+    if args.breaking_only || args.dir.is_some() {
+        return Err("--breaking-only and --dir apply to schema diffs (two .kdl files)".into());
+    }
+
+    let old_doc = Document::from_file(&args.old)?;
+
     let mut result = diff::diff_documents(&old_doc.raw, &new_content)?;
 
     // Attach path from old file
     result.path = Some(args.old.display().to_string());
 
+    if let Some(schema_path) = md_db::project::resolve_optional_schema(&args.schema, &args.old)? {
+        let schema = Schema::from_file(schema_path)?;
+        let new_doc = Document::from_str(&new_content)?;
+        let doc_type = [&new_doc, &old_doc]
+            .iter()
+            .find_map(|d| d.frontmatter.as_ref().and_then(|fm| fm.get_display("type")));
+        if let Some(doc_type) = doc_type {
+            result.mark_transitions(&schema, &doc_type);
+        }
+    }
+
     let format = OutputFormat::from_str(&args.format).unwrap_or(OutputFormat::Text);
 
     match format {
@@ -56,6 +90,82 @@ pub fn run(args: &DiffArgs) -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
+/// Semantic diff of two schema files, with the breaking changes flagged.
+fn run_schema(args: &DiffArgs, new_content: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let old = Schema::from_file(&args.old)?;
+    let new = Schema::from_str(new_content)?;
+    let diff = migrate::diff_schemas(&old, &new);
+    let mut breaking = diff.breaking_changes();
+    if let Some(ref dir) = args.dir {
+        migrate::estimate_breakage(&mut breaking, &new, dir);
+        // With a corpus, only changes that invalidate a document count
+        breaking.retain(|c| c.affected_docs.as_ref().is_some_and(|docs| !docs.is_empty()));
+    }
+
+    match OutputFormat::from_str(&args.format).unwrap_or(OutputFormat::Text) {
+        OutputFormat::Json => {
+            let mut out = serde_json::Map::new();
+            if !args.breaking_only {
+                out.insert("diff".into(), diff.to_json());
+            }
+            out.insert("breaking".into(), breaking.iter().map(|c| c.to_json()).collect());
+            println!("{}", serde_json::to_string_pretty(&serde_json::Value::Object(out))?);
+        }
+        _ => print_schema_text(&diff, &breaking, args),
+    }
+
+    if args.breaking_only && !breaking.is_empty() {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+fn print_schema_text(diff: &SchemaDiff, breaking: &[migrate::BreakingChange], args: &DiffArgs) {
+    if !args.breaking_only {
+        if diff.is_empty() {
+            println!("Schemas are identical.");
+            return;
+        }
+        for line in diff.to_string().lines() {
+            let style = match line.trim_start().chars().next() {
+                Some('+') => Some(Style::Success),
+                Some('-') => Some(Style::Error),
+                Some('~') => Some(Style::Warning),
+                _ => Some(Style::Heading).filter(|_| !line.starts_with(' ')),
+            };
+            match style {
+                Some(style) => println!("{}", paint(line, style)),
+                None => println!("{line}"),
+            }
+        }
+        if breaking.is_empty() {
+            return;
+        }
+        println!();
+    }
+
+    if breaking.is_empty() {
+        println!("No breaking changes.");
+        return;
+    }
+    println!("{}", paint("Breaking changes:", Style::Heading));
+    for change in breaking {
+        let count = change
+            .affected_docs
+            .as_ref()
+            .map(|docs| format!(" ({} doc(s))", docs.len()))
+            .unwrap_or_default();
+        println!("  {} {change}{}", paint("!", Style::Error), paint(count, Style::Dim));
+        for path in change.affected_docs.iter().flatten() {
+            println!("      {}", paint(display_path(path, args.dir.as_deref()), Style::Dim));
+        }
+    }
+}
+
+fn display_path(path: &Path, dir: Option<&Path>) -> String {
+    dir.and_then(|d| path.strip_prefix(d).ok()).unwrap_or(path).display().to_string()
+}
+
 fn print_text(diff: &diff::DocDiff) {
     // Header line
     let header = match (&diff.path, &diff.id) {
@@ -89,10 +199,11 @@ fn print_text(diff: &diff::DocDiff) {
             }
             FieldChangeKind::Changed => {
                 println!(
-                    "  ~ field changed: {}: {} \u{2192} {}",
+                    "  ~ field changed: {}: {} \u{2192} {}{}",
                     fc.field,
                     fc.old.as_deref().unwrap_or("null"),
-                    fc.new.as_deref().unwrap_or("null")
+                    fc.new.as_deref().unwrap_or("null"),
+                    if fc.illegal_transition { " (illegal transition)" } else { "" }
                 );
             }
         }
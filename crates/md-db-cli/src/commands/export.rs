@@ -1,40 +1,509 @@
-use std::path::PathBuf;
+use std::collections::HashSet;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{mpsc, Arc};
+use std::time::Duration;
 
 use clap::Args;
-use md_db::export;
+use md_db::discovery::Filter;
+use md_db::document::Document;
+use md_db::export::confluence::{self, Auth, ConfluenceState, PageAction, RestClient};
+use md_db::export::{self, PdfOptions, SiteOptions};
+use md_db::graph::path_to_id;
+use md_db::locale::Locale;
+use md_db::output::problem::{self, Problem, ProblemKind};
 use md_db::schema::Schema;
+use md_db::theme::Theme;
+use notify::{RecursiveMode, Watcher};
+
+use super::watch::{collect_paths, timestamp};
+
+/// Pages poll this path and reload when the number it returns changes.
+const VERSION_PATH: &str = "/__md-db/version";
 
 #[derive(Debug, Args)]
 pub struct ExportArgs {
-    /// Directory containing markdown files
+    /// Directory containing markdown files, or `pdf`/`confluence` followed by the documents
     pub dir: PathBuf,
 
-    /// Path to KDL schema file (enables backlinks)
+    /// Documents or directories to print or publish with pdf and confluence [default: .]
+    pub files: Vec<PathBuf>,
+
+    /// Path to KDL schema file, or `auto` to use the closest one above the directory (enables backlinks)
     #[arg(long)]
     pub schema: Option<PathBuf>,
 
-    /// Output directory for generated site
-    #[arg(long, default_value = "site")]
-    pub output: PathBuf,
+    /// Output directory for the site, or file for sqlite and pdf [default: site, md-db.sqlite, <name>.pdf]
+    #[arg(long)]
+    pub output: Option<PathBuf>,
 
-    /// Output format (only "html" supported currently)
-    #[arg(long, default_value = "html")]
+    /// Output format: html (static site), sqlite (read-only database mirror, refreshed incrementally),
+    /// pdf (one document, or a bundle with --bundle), confluence (pages via the REST API)
+    #[arg(long, visible_alias = "target", default_value = "html")]
     pub format: String,
+
+    /// Language for labels, dates, and numbers (e.g. fi, sv-SE); overrides the schema's `locale`
+    #[arg(long)]
+    pub locale: Option<String>,
+
+    /// Serve the site on localhost, with each page's validation diagnostics shown over it
+    #[arg(long)]
+    pub serve: bool,
+
+    /// Port for --serve
+    #[arg(long, default_value = "8000")]
+    pub port: u16,
+
+    /// Rebuild changed pages when documents or the schema change; served pages reload themselves
+    #[arg(long)]
+    pub watch: bool,
+
+    /// Debounce interval for --watch in milliseconds
+    #[arg(long, default_value = "300")]
+    pub debounce: u64,
+
+    /// Keep raw HTML in documents as written instead of sanitizing it (only for trusted content)
+    #[arg(long)]
+    pub unsafe_html: bool,
+
+    /// Mermaid module loaded by pages with diagrams, e.g. a pinned build such as
+    /// .../mermaid@11.4.1/dist/mermaid.esm.min.mjs; without it diagrams show as source
+    #[arg(long)]
+    pub mermaid_url: Option<String>,
+
+    /// Subresource Integrity hash of --mermaid-url (sha384-...), checked by the browser
+    #[arg(long, requires = "mermaid_url")]
+    pub mermaid_integrity: Option<String>,
+
+    /// Theme directory: page.html or <type>.html templates for document pages, style.css, and static files
+    #[arg(long)]
+    pub theme: Option<PathBuf>,
+
+    /// Print every document under the given directories into one PDF, with a table of contents
+    #[arg(long)]
+    pub bundle: bool,
+
+    /// Only bundle (pdf) or publish (confluence) documents of this type
+    #[arg(long = "type")]
+    pub doc_type: Option<String>,
+
+    /// Title page heading for the PDF [default: the document title, or "Documents"]
+    #[arg(long)]
+    pub title: Option<String>,
+
+    /// PDF engine program or path [default: the first found of chromium, google-chrome, weasyprint, wkhtmltopdf]
+    #[arg(long)]
+    pub pdf_engine: Option<String>,
+
+    /// Confluence space key to publish to
+    #[arg(long)]
+    pub space: Option<String>,
+
+    /// ID of the Confluence page the documents' pages go under
+    #[arg(long)]
+    pub parent: Option<String>,
+
+    /// Confluence base URL, e.g. https://example.atlassian.net/wiki [default: $CONFLUENCE_URL]
+    #[arg(long)]
+    pub confluence_url: Option<String>,
+
+    /// Show which Confluence pages would be created or updated without publishing
+    #[arg(long)]
+    pub dry_run: bool,
 }
 
 pub fn run(args: &ExportArgs) -> Result<(), Box<dyn std::error::Error>> {
-    if args.format != "html" {
-        return Err(format!("unsupported format \"{}\", only html is supported", args.format).into());
+    // `export pdf FILE` and `export confluence DIR` name the format in place of the directory
+    let format = match args.dir.to_str() {
+        Some(keyword @ ("pdf" | "confluence")) => keyword,
+        _ => args.format.as_str(),
+    };
+    if !["html", "sqlite", "pdf", "confluence"].contains(&format) {
+        return Err(format!("unsupported format \"{format}\" (expected: html, sqlite, pdf, confluence)").into());
+    }
+
+    if (args.serve || args.watch) && format != "html" {
+        return Err("--serve and --watch need --format html".into());
+    }
+    match format {
+        "pdf" => return run_pdf(args),
+        "confluence" => return run_confluence(args),
+        _ => {}
+    }
+    if !args.files.is_empty() {
+        return Err("extra paths are only accepted with pdf and confluence".into());
+    }
+    if args.doc_type.is_some() {
+        return Err("--type needs pdf --bundle or confluence".into());
+    }
+    let schema_path = match &args.schema {
+        Some(path) => Some(md_db::project::resolve_schema(path, &args.dir)?),
+        None => None,
+    };
+    let schema = schema_path.as_ref().map(Schema::from_file).transpose()?;
+
+    if args.format == "sqlite" {
+        let db = args.output.clone().unwrap_or_else(|| PathBuf::from("md-db.sqlite"));
+        let refresh = md_db::sqlite::export(&args.dir, schema.as_ref(), &db)?;
+        eprintln!(
+            "{} {}: {} added, {} updated, {} removed, {} unchanged",
+            if refresh.rebuilt { "built" } else { "refreshed" },
+            db.display(),
+            refresh.added.len(),
+            refresh.updated.len(),
+            refresh.removed.len(),
+            refresh.unchanged
+        );
+        return Ok(());
+    }
+    let output = args.output.clone().unwrap_or_else(|| PathBuf::from("site"));
+    let locale = resolve_locale(args.locale.as_deref(), schema.as_ref())?;
+
+    let options = SiteOptions {
+        overlay: args.serve,
+        live_reload: (args.serve && args.watch).then(|| VERSION_PATH.to_string()),
+        unsafe_html: args.unsafe_html,
+        mermaid_url: args.mermaid_url.clone(),
+        mermaid_integrity: args.mermaid_integrity.clone(),
+        theme: args.theme.as_ref().map(Theme::load).transpose()?,
+    };
+    let report = export::export_site_with(&args.dir, schema.as_ref(), &output, &locale, &options)?;
+
+    eprintln!("exported {} documents to {}", report.documents, output.display());
+    for (old, new) in &report.redirects {
+        eprintln!("redirect {old} -> {new}");
+    }
+
+    let version = Arc::new(AtomicU64::new(0));
+    let server = if args.serve {
+        Some(serve(&output, args.port, Arc::clone(&version))?)
+    } else {
+        None
+    };
+    if args.watch {
+        let site = Site {
+            output,
+            locale,
+            options,
+        };
+        return watch(args, schema_path, schema, &site, &version);
+    }
+    if let Some(server) = server {
+        let _ = server.join();
+    }
+    Ok(())
+}
+
+/// The `--locale` language, or the schema's `locale`, or English.
+fn resolve_locale(lang: Option<&str>, schema: Option<&Schema>) -> Result<Locale, String> {
+    let project_locale = schema.and_then(|s| s.locale.clone());
+    match lang {
+        // The schema's own block for the same language keeps its overrides
+        Some(lang) => match project_locale.filter(|l| l.lang.eq_ignore_ascii_case(lang)) {
+            Some(locale) => Ok(locale),
+            None => Locale::builtin(lang).ok_or_else(|| {
+                format!(
+                    "unknown locale: {lang} (built-in: {}; define others in the schema's locale block)",
+                    md_db::locale::BUILTIN.join(", ")
+                )
+            }),
+        },
+        None => Ok(project_locale.unwrap_or_default()),
+    }
+}
+
+/// `export pdf FILE` or `export pdf --bundle [DIR...]`: one PDF of one
+/// document, or of every document (of `--type`) under the directories.
+fn run_pdf(args: &ExportArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let inputs = inputs(args, "pdf");
+    if !args.bundle && (inputs.len() > 1 || inputs[0].is_dir()) {
+        return Err("printing more than one document needs --bundle".into());
+    }
+    if !args.bundle && args.doc_type.is_some() {
+        return Err("--type needs --bundle".into());
     }
 
     let schema = match &args.schema {
-        Some(path) => Some(Schema::from_file(path)?),
+        Some(path) => Some(Schema::from_file(md_db::project::resolve_schema(path, &inputs[0])?)?),
         None => None,
     };
+    let files = documents(&inputs, args.doc_type.as_deref())?;
+    if files.is_empty() {
+        return Err("no documents to print".into());
+    }
+
+    let output = args.output.clone().unwrap_or_else(|| {
+        let name = match (args.bundle, &args.doc_type) {
+            (false, _) => inputs[0].file_stem().unwrap_or_default().to_string_lossy().into_owned(),
+            (true, Some(doc_type)) => doc_type.clone(),
+            (true, None) => "documents".into(),
+        };
+        PathBuf::from(format!("{name}.pdf"))
+    });
+    let locale = resolve_locale(args.locale.as_deref(), schema.as_ref())?;
+    let options = PdfOptions {
+        title: args.title.clone(),
+        engine: args.pdf_engine.clone(),
+        unsafe_html: args.unsafe_html,
+        mermaid_url: args.mermaid_url.clone(),
+        mermaid_integrity: args.mermaid_integrity.clone(),
+    };
+    let report = export::export_pdf(&files, schema.as_ref(), &locale, &output, &options)?;
+
+    if report.diagrams_as_source && args.mermaid_url.is_none() {
+        eprintln!("warning: Mermaid diagrams are printed as source (pass --mermaid-url to draw them)");
+    } else if report.diagrams_as_source {
+        eprintln!(
+            "warning: {} does not run scripts; Mermaid diagrams are printed as source (use a Chromium-based engine)",
+            report.engine.program.display()
+        );
+    }
+    eprintln!(
+        "printed {} document(s) to {} with {}",
+        report.documents,
+        output.display(),
+        report.engine.program.display()
+    );
+    Ok(())
+}
 
-    let count = export::export_site(&args.dir, schema.as_ref(), &args.output)?;
+/// `export confluence [DIR...] --space KEY`: publish the documents (of
+/// `--type`) as Confluence pages, pushing only pages whose content changed.
+/// The page of each document is recorded under the first directory.
+fn run_confluence(args: &ExportArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let space = args.space.clone().ok_or("confluence needs --space")?;
+    let inputs = inputs(args, "confluence");
+    let root = inputs.iter().find(|p| p.is_dir()).cloned().unwrap_or_else(|| PathBuf::from("."));
+    let schema = match &args.schema {
+        Some(path) => Some(Schema::from_file(md_db::project::resolve_schema(path, &inputs[0])?)?),
+        None => None,
+    };
+    let locale = resolve_locale(args.locale.as_deref(), schema.as_ref())?;
+    let docs = documents(&inputs, args.doc_type.as_deref())?
+        .iter()
+        .map(|path| Ok((path_to_id(path), Document::from_file(path)?)))
+        .collect::<Result<Vec<_>, md_db::error::Error>>()?;
 
-    eprintln!("exported {count} documents to {}", args.output.display());
+    let mut state = ConfluenceState::open(&root)?;
+    let pages = confluence::render_pages(&docs, &space, &state, &locale);
+    let mut items = confluence::plan(pages, &space, &state);
+    if !args.dry_run {
+        let base_url = match args.confluence_url {
+            Some(ref url) => url.clone(),
+            None => std::env::var("CONFLUENCE_URL").map_err(|_| "pass --confluence-url or set CONFLUENCE_URL")?,
+        };
+        let mut client = RestClient {
+            base_url,
+            space: space.clone(),
+            parent: args.parent.clone(),
+            auth: Auth::from_env()?,
+        };
+        // Record what was published even when a later page fails
+        let applied = confluence::apply(&mut items, &mut client, &mut state);
+        state.save()?;
+        applied?;
+    }
 
+    for item in &items {
+        let url = item.record.as_ref().map(|r| r.url.as_str()).unwrap_or("");
+        let verb = match (item.action, args.dry_run) {
+            (PageAction::Create, true) => "would create",
+            (PageAction::Update, true) => "would update",
+            (PageAction::Create, false) => "created",
+            (PageAction::Update, false) => "updated",
+            (PageAction::Unchanged, _) => "unchanged",
+        };
+        println!("{verb:<12} {}  {url}", item.page.title);
+    }
     Ok(())
 }
+
+/// The paths after the `keyword` standing for the directory (or the directory
+/// and the paths after it), `.` when there are none.
+fn inputs(args: &ExportArgs, keyword: &str) -> Vec<PathBuf> {
+    let mut inputs = args.files.clone();
+    if args.dir.as_os_str() != keyword {
+        inputs.insert(0, args.dir.clone());
+    }
+    if inputs.is_empty() {
+        inputs.push(PathBuf::from("."));
+    }
+    inputs
+}
+
+/// Files among `inputs` as given, and the documents (of `doc_type`) under directories.
+fn documents(inputs: &[PathBuf], doc_type: Option<&str>) -> Result<Vec<PathBuf>, md_db::error::Error> {
+    let filters: Vec<Filter> = doc_type
+        .map(|t| Filter::FieldEquals {
+            key: "type".into(),
+            value: t.to_string(),
+        })
+        .into_iter()
+        .collect();
+    let mut files = Vec::new();
+    for input in inputs {
+        if input.is_dir() {
+            files.extend(md_db::discovery::discover_files(input, None, &filters, false)?);
+        } else {
+            files.push(input.clone());
+        }
+    }
+    Ok(files)
+}
+
+/// Where and how `--watch` re-exports.
+struct Site {
+    output: PathBuf,
+    locale: Locale,
+    options: SiteOptions,
+}
+
+/// Re-export on every debounced batch of changes to documents or the schema.
+/// Only pages whose HTML changed are rewritten; a rebuild that writes anything
+/// bumps `version` so served pages reload.
+fn watch(
+    args: &ExportArgs,
+    schema_path: Option<PathBuf>,
+    mut schema: Option<Schema>,
+    site: &Site,
+    version: &AtomicU64,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let debounce = Duration::from_millis(args.debounce);
+    let (tx, rx) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(move |res: notify::Result<notify::Event>| {
+        if let Ok(event) = res {
+            let _ = tx.send(event);
+        }
+    })?;
+    watcher.watch(&args.dir, RecursiveMode::Recursive)?;
+    let schema_path = schema_path.map(|p| p.canonicalize().unwrap_or(p));
+    if let Some(parent) = schema_path.as_ref().and_then(|p| p.parent()) {
+        let _ = watcher.watch(parent, RecursiveMode::NonRecursive);
+    }
+    // The site may be written inside the watched directory
+    let output = site.output.canonicalize().unwrap_or_else(|_| site.output.clone());
+    eprintln!("Watching {} for changes...", args.dir.display());
+
+    loop {
+        let mut changed: HashSet<PathBuf> = collect_paths(&rx.recv()?);
+        loop {
+            match rx.recv_timeout(debounce) {
+                Ok(event) => changed.extend(collect_paths(&event)),
+                Err(mpsc::RecvTimeoutError::Timeout) => break,
+                Err(mpsc::RecvTimeoutError::Disconnected) => return Err("file watcher disconnected".into()),
+            }
+        }
+        let schema_changed = changed
+            .iter()
+            .any(|p| Some(p.canonicalize().unwrap_or_else(|_| p.clone())) == schema_path);
+        let docs_changed = changed
+            .iter()
+            .any(|p| p.extension().is_some_and(|ext| ext == "md") && !p.starts_with(&output));
+        if !schema_changed && !docs_changed {
+            continue;
+        }
+
+        if schema_changed {
+            if let Some(ref path) = schema_path {
+                match Schema::from_file(path) {
+                    Ok(s) => schema = Some(s),
+                    Err(e) => {
+                        eprintln!("[{}] schema reload error: {e}", timestamp());
+                        continue;
+                    }
+                }
+            }
+        }
+        match export::export_site_with(&args.dir, schema.as_ref(), &site.output, &site.locale, &site.options) {
+            Ok(report) if report.written.is_empty() => {}
+            Ok(report) => {
+                version.fetch_add(1, Ordering::SeqCst);
+                eprintln!("[{}] rebuilt {} page(s)", timestamp(), report.written.len());
+                for (old, new) in &report.redirects {
+                    eprintln!("[{}] redirect {old} -> {new}", timestamp());
+                }
+            }
+            Err(e) => eprintln!("[{}] export error: {e}", timestamp()),
+        }
+    }
+}
+
+/// Serve `root` on 127.0.0.1 from a background thread.
+fn serve(
+    root: &Path,
+    port: u16,
+    version: Arc<AtomicU64>,
+) -> Result<std::thread::JoinHandle<()>, Box<dyn std::error::Error>> {
+    let listener = TcpListener::bind(("127.0.0.1", port))?;
+    eprintln!("serving {} at http://127.0.0.1:{port}/", root.display());
+    let root = root.to_path_buf();
+    Ok(std::thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            let _ = respond(stream, &root, &version);
+        }
+    }))
+}
+
+/// Answer one GET: the live-reload version, or a file under `root`.
+fn respond(mut stream: TcpStream, root: &Path, version: &AtomicU64) -> std::io::Result<()> {
+    let mut request = String::new();
+    let mut accept = None;
+    {
+        let mut reader = BufReader::new(&stream);
+        reader.read_line(&mut request)?;
+        // Read the rest of the headers so the connection closes cleanly
+        let mut header = String::new();
+        while reader.read_line(&mut header)? > 2 {
+            if let Some((name, value)) = header.split_once(':') {
+                if name.trim().eq_ignore_ascii_case("accept") {
+                    accept = Some(value.trim().to_string());
+                }
+            }
+            header.clear();
+        }
+    }
+
+    let target = request.split_whitespace().nth(1).unwrap_or("/");
+    let path = target.split(['?', '#']).next().unwrap_or("/");
+    let (status, content_type, body) = if path == VERSION_PATH {
+        ("200 OK".to_string(), "text/plain", version.load(Ordering::SeqCst).to_string().into_bytes())
+    } else {
+        let rel = path.trim_start_matches('/');
+        let rel = if rel.is_empty() || rel.ends_with('/') {
+            format!("{rel}index.html")
+        } else {
+            rel.to_string()
+        };
+        let file = root.join(&rel);
+        match std::fs::read(&file) {
+            Ok(bytes) if !rel.split('/').any(|part| part == "..") => ("200 OK".to_string(), content_type(&file), bytes),
+            _ => {
+                let problem = Problem::new(ProblemKind::NotFound, format!("no page at {path}")).with_instance(path);
+                let (status, content_type, body) = problem.render(accept.as_deref());
+                (format!("{status} {}", problem::reason(status)), content_type, body.into_bytes())
+            }
+        }
+    };
+    write!(
+        stream,
+        "HTTP/1.1 {status}\r\nContent-Type: {content_type}\r\nContent-Length: {}\r\n\
+         Cache-Control: no-store\r\nConnection: close\r\n\r\n",
+        body.len()
+    )?;
+    stream.write_all(&body)
+}
+
+fn content_type(path: &Path) -> &'static str {
+    match path.extension().and_then(|e| e.to_str()).unwrap_or_default() {
+        "html" => "text/html; charset=utf-8",
+        "css" => "text/css",
+        "js" => "text/javascript",
+        "json" => "application/json",
+        "svg" => "image/svg+xml",
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        _ => "application/octet-stream",
+    }
+}
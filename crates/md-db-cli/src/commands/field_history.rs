@@ -0,0 +1,39 @@
+use std::path::PathBuf;
+
+use clap::Args;
+use md_db::history;
+
+#[derive(Debug, Args)]
+pub struct FieldHistoryArgs {
+    /// Path to the markdown file
+    pub file: PathBuf,
+
+    /// Frontmatter field (dotted path for nested values, e.g. `review.state`)
+    pub field: String,
+
+    /// Output format: text, json
+    #[arg(long, default_value = "text")]
+    pub format: String,
+}
+
+pub fn run(args: &FieldHistoryArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let changes = history::field_history(&args.file, &args.field)?;
+
+    if args.format == "json" {
+        println!("{}", serde_json::to_string_pretty(&changes)?);
+        return Ok(());
+    }
+    if changes.is_empty() {
+        eprintln!("{}: no committed value for \"{}\"", args.file.display(), args.field);
+        return Ok(());
+    }
+
+    let author_width = changes.iter().map(|c| c.author.chars().count()).max().unwrap_or(0).max(6);
+    println!("{:<10}  {:<7}  {:<author_width$}  VALUE", "DATE", "COMMIT", "AUTHOR");
+    for c in &changes {
+        let value = c.value.as_deref().unwrap_or("(removed)");
+        let short = &c.commit[..c.commit.len().min(7)];
+        println!("{:<10}  {short:<7}  {:<author_width$}  {value}", c.date, c.author);
+    }
+    Ok(())
+}
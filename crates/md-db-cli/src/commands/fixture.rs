@@ -0,0 +1,137 @@
+use std::path::{Path, PathBuf};
+
+use clap::Args;
+use md_db::fixture::{self, FixtureOptions};
+use md_db::schema::Schema;
+use md_db::users::UserConfig;
+
+#[derive(Debug, Args)]
+pub struct FixtureArgs {
+    /// Action: generate
+    pub action: String,
+
+    /// Path to KDL schema file, or `auto` to use the closest one above the current directory
+    #[arg(long, default_value = "auto")]
+    pub schema: PathBuf,
+
+    /// Folder to write `valid/` and `invalid/` into
+    #[arg(long, default_value = "tests/fixtures")]
+    pub out: PathBuf,
+
+    /// Documents per type (singletons get one, max_count types at most their limit)
+    #[arg(long, default_value = "2")]
+    pub per_type: usize,
+
+    /// Fill optional refs too and link documents through every relation and its inverse
+    #[arg(long)]
+    pub link: bool,
+
+    /// Also write invalid variants, one per diagnostic code, listed in invalid/expected.json
+    #[arg(long)]
+    pub with_errors: bool,
+
+    /// Path to user/team config YAML file (documents use its handles; enables U011/U012 variants)
+    #[arg(long)]
+    pub users: Option<PathBuf>,
+
+    /// Replace existing valid/ and invalid/ folders
+    #[arg(long)]
+    pub force: bool,
+
+    /// Output format: text, json
+    #[arg(long, default_value = "text")]
+    pub format: String,
+}
+
+pub fn run(args: &FixtureArgs) -> Result<(), Box<dyn std::error::Error>> {
+    match args.action.as_str() {
+        "generate" => generate(args),
+        _ => Err(format!("unknown action: {} (expected: generate)", args.action).into()),
+    }
+}
+
+fn generate(args: &FixtureArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let schema = Schema::from_file(md_db::project::resolve_schema(&args.schema, Path::new("."))?)?;
+    let user_config = match &args.users {
+        Some(path) => Some(UserConfig::from_file(path)?),
+        None => None,
+    };
+    if args.per_type == 0 {
+        return Err("--per-type must be at least 1".into());
+    }
+
+    if args.force {
+        for dir in [fixture::VALID_DIR, fixture::INVALID_DIR] {
+            let dir = args.out.join(dir);
+            if dir.is_dir() {
+                std::fs::remove_dir_all(&dir)?;
+            }
+        }
+    }
+
+    let opts = FixtureOptions {
+        per_type: args.per_type,
+        link: args.link,
+        with_errors: args.with_errors,
+    };
+    let corpus = fixture::generate(&schema, user_config.as_ref(), &opts);
+    let report = match fixture::write(&corpus, &args.out, &schema, user_config.as_ref()) {
+        Err(md_db::error::Error::Fixture(msg)) => return Err(format!("{msg} (pass --force to replace it)").into()),
+        result => result?,
+    };
+    md_db::durability::sync_pending()?;
+
+    let errors = report.valid.total_errors();
+    if args.format == "json" {
+        let problems: Vec<serde_json::Value> = report
+            .valid
+            .file_results
+            .iter()
+            .flat_map(|f| {
+                f.diagnostics
+                    .iter()
+                    .filter(|d| d.severity == md_db::validation::Severity::Error)
+                    .map(|d| serde_json::json!({ "path": f.path, "code": d.code, "message": d.message }))
+            })
+            .collect();
+        let result = serde_json::json!({
+            "out": args.out.display().to_string(),
+            "valid": report.valid_files,
+            "valid_errors": problems,
+            "invalid": report.expected,
+            "missed": report.missed,
+        });
+        println!("{}", serde_json::to_string_pretty(&result)?);
+    } else {
+        println!(
+            "Wrote {} valid document(s) to {}",
+            report.valid_files,
+            args.out.join(fixture::VALID_DIR).display()
+        );
+        if args.with_errors {
+            println!(
+                "Wrote {} invalid file(s) to {}",
+                report.expected.len(),
+                args.out.join(fixture::INVALID_DIR).display()
+            );
+            for (file, code) in &report.expected {
+                println!("  {code}  {file}");
+            }
+            if !report.missed.is_empty() {
+                println!("Not exercised by this schema: {}", report.missed.join(", "));
+            }
+        }
+        for f in &report.valid.file_results {
+            for d in f.diagnostics.iter().filter(|d| d.severity == md_db::validation::Severity::Error) {
+                eprintln!("{}: {} {}", f.path, d.code, d.message);
+            }
+        }
+    }
+
+    // The valid corpus should validate; if not, the schema has constraints the generator can't meet
+    if errors > 0 {
+        eprintln!("{errors} error(s) in the valid corpus; edit those files by hand");
+        std::process::exit(1);
+    }
+    Ok(())
+}
@@ -0,0 +1,117 @@
+use std::path::PathBuf;
+
+use clap::Args;
+use md_db::error::Error;
+use md_db::frontmatter::{self, Frontmatter};
+use md_db::undo::Recorder;
+
+#[derive(Debug, Args)]
+pub struct FmArgs {
+    /// Action: get, set, add, strip
+    pub action: String,
+
+    /// Any markdown file; no schema is needed
+    pub file: PathBuf,
+
+    /// get: an optional dotted key. set / add: key=value pairs
+    pub values: Vec<String>,
+
+    /// Output format for get: yaml, json
+    #[arg(long, default_value = "yaml")]
+    pub format: String,
+
+    /// Print the result to stdout instead of writing the file
+    #[arg(long)]
+    pub dry_run: bool,
+}
+
+pub fn run(args: &FmArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let content = std::fs::read_to_string(&args.file).map_err(|_| Error::FileNotFound(args.file.clone()))?;
+    let (fm, _) = Frontmatter::try_parse(&content)?;
+
+    let updated = match args.action.as_str() {
+        "get" => return get(args, &content, fm),
+        "set" => frontmatter::set_frontmatter_lines(&content, &pairs(&args.values)?),
+        "add" => {
+            let pairs = pairs(&args.values)?;
+            if let Some((key, _)) = pairs.iter().find(|(k, _)| fm.as_ref().is_some_and(|fm| fm.has_field(k))) {
+                let file = args.file.display();
+                return Err(format!("'{key}' is already set in {file}; use `fm set` to change it").into());
+            }
+            frontmatter::set_frontmatter_lines(&content, &pairs)
+        }
+        "strip" => frontmatter::strip_frontmatter(&content).to_string(),
+        other => return Err(format!("unknown action: {other} (expected: get, set, add, strip)").into()),
+    };
+    // Line edits must still leave YAML that parses
+    Frontmatter::try_parse(&updated)?;
+
+    if args.dry_run {
+        print!("{updated}");
+        return Ok(());
+    }
+    if updated != content {
+        let mut undo = Recorder::new("fm");
+        undo.track(&args.file);
+        md_db::durability::write(&args.file, &updated)?;
+        undo.finish()?;
+    }
+    Ok(())
+}
+
+fn get(args: &FmArgs, content: &str, fm: Option<Frontmatter>) -> Result<(), Box<dyn std::error::Error>> {
+    let json = args.format == "json";
+    if !json && args.format != "yaml" {
+        return Err(format!("unknown format: {} (expected: yaml, json)", args.format).into());
+    }
+    match args.values.as_slice() {
+        [] => {
+            let Some(fm) = fm else {
+                return Err(Error::NoFrontmatter.into());
+            };
+            if json {
+                println!("{}", serde_json::to_string_pretty(&fm.to_json())?);
+            } else {
+                // The block as written, comments and key order included
+                let block = &content[..content.len() - frontmatter::strip_frontmatter(content).len()];
+                let lines: Vec<&str> = block.lines().collect();
+                for line in lines.get(1..lines.len().saturating_sub(1)).unwrap_or_default() {
+                    println!("{line}");
+                }
+            }
+        }
+        [key] => {
+            let value = fm
+                .as_ref()
+                .and_then(|fm| fm.get(key))
+                .ok_or_else(|| Error::FieldNotFound(key.clone()))?;
+            if json {
+                println!("{}", serde_json::to_string_pretty(&frontmatter::yaml_to_json(value))?);
+            } else {
+                println!("{}", frontmatter::yaml_value_to_string(value));
+            }
+        }
+        _ => return Err("get takes at most one key".into()),
+    }
+    Ok(())
+}
+
+/// `key=value` arguments as inline YAML, typed like `set --field`.
+fn pairs(values: &[String]) -> Result<Vec<(&str, String)>, Box<dyn std::error::Error>> {
+    if values.is_empty() {
+        return Err("expected one or more key=value pairs".into());
+    }
+    values
+        .iter()
+        .map(|s| -> Result<(&str, String), Box<dyn std::error::Error>> {
+            let (key, value) = s
+                .split_once('=')
+                .ok_or_else(|| format!("invalid argument '{s}', expected key=value"))?;
+            let key = key.trim();
+            if key.is_empty() || key.contains(['.', ':']) {
+                return Err(format!("invalid key '{key}': only top-level keys can be set").into());
+            }
+            Ok((key, frontmatter::yaml_inline(&frontmatter::parse_yaml_value(value))))
+        })
+        .collect()
+}
@@ -0,0 +1,98 @@
+use std::path::PathBuf;
+
+use clap::Args;
+use md_db::document::Document;
+use md_db::normalize::NormalizeOptions;
+use md_db::output::OutputFormat;
+use md_db::schema::Schema;
+use md_db::undo::Recorder;
+
+#[derive(Debug, Args)]
+pub struct FmtArgs {
+    /// Directory or file to format
+    pub dir: PathBuf,
+
+    /// Path to KDL schema file, or `auto` to use the closest one above the target
+    #[arg(long, default_value = "auto")]
+    pub schema: PathBuf,
+
+    /// List files that aren't formatted and exit 1 instead of writing (for CI)
+    #[arg(long)]
+    pub check: bool,
+
+    /// Also normalize: coerce field values, rename alias headings, and fill missing `auto` fields
+    #[arg(long)]
+    pub normalize: bool,
+
+    /// Output format: text, json, auto
+    #[arg(long, default_value = "auto")]
+    pub format: String,
+}
+
+pub fn run(args: &FmtArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let schema = Schema::from_file(md_db::project::resolve_schema(&args.schema, &args.dir)?)?;
+    let files = if args.dir.is_file() {
+        vec![args.dir.clone()]
+    } else {
+        md_db::discovery::discover_files(&args.dir, None, &[], false)?
+    };
+    let format = OutputFormat::from_str(&args.format).unwrap_or(OutputFormat::Text);
+
+    let mut undo = Recorder::new("fmt");
+    let mut changed = 0usize;
+    let mut reports: Vec<serde_json::Value> = Vec::new();
+    for path in &files {
+        let doc = match Document::from_file(path) {
+            Ok(d) => d,
+            Err(_) => continue,
+        };
+        let result = if args.normalize {
+            md_db::format::normalize_and_format(&doc, &schema, &NormalizeOptions::default())
+        } else {
+            md_db::format::format(&doc, &schema)
+        };
+        if !result.changed() {
+            continue;
+        }
+        changed += 1;
+        if !args.check {
+            undo.track(path);
+            md_db::durability::write(path, &result.content)?;
+        }
+
+        match format {
+            OutputFormat::Json => reports.push(serde_json::json!({
+                "path": path.display().to_string(),
+                "changes": result.changes,
+            })),
+            _ if args.check => println!("{}", path.display()),
+            _ => {
+                println!("{}:", path.display());
+                for change in &result.changes {
+                    println!("  {}", change.description);
+                }
+            }
+        }
+    }
+    undo.finish()?;
+
+    match format {
+        OutputFormat::Json => {
+            let report = serde_json::json!({
+                "files": reports,
+                "checked": files.len(),
+                "check": args.check,
+            });
+            println!("{}", serde_json::to_string_pretty(&report)?);
+        }
+        _ if args.check => {
+            eprintln!("{changed} of {} file(s) need formatting", files.len());
+        }
+        _ => println!("formatted {changed} of {} file(s)", files.len()),
+    }
+
+    if args.check && changed > 0 {
+        std::process::exit(1);
+    }
+    Ok(())
+}
@@ -1,9 +1,10 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use clap::Args;
 use md_db::document::Document;
 use md_db::error::Error;
 use md_db::output::{self, OutputFormat};
+use md_db::schema::Schema;
 
 #[derive(Debug, Args)]
 pub struct GetArgs {
@@ -26,6 +27,10 @@ pub struct GetArgs {
     #[arg(long)]
     pub section: Option<String>,
 
+    /// Treat --section as a heading pattern (e.g. "Day \d+") and use the Nth match (0-based)
+    #[arg(long)]
+    pub nth: Option<usize>,
+
     /// Get a table by index within the section (0-based)
     #[arg(long)]
     pub table: Option<usize>,
@@ -37,10 +42,15 @@ pub struct GetArgs {
     /// Output format: text, markdown, json
     #[arg(long, default_value = "markdown")]
     pub format: String,
+
+    /// KDL schema whose field aliases map renamed fields to their current name
+    /// (`auto` uses the closest one above the file, if any)
+    #[arg(long, default_value = "auto")]
+    pub schema: PathBuf,
 }
 
 pub fn run(args: &GetArgs) -> Result<(), Box<dyn std::error::Error>> {
-    let doc = if args.stdin {
+    let mut doc = if args.stdin {
         let mut content = String::new();
         std::io::Read::read_to_string(&mut std::io::stdin(), &mut content)?;
         Document::from_str(&content)?
@@ -53,6 +63,14 @@ pub fn run(args: &GetArgs) -> Result<(), Box<dyn std::error::Error>> {
     };
     let format = OutputFormat::from_str(&args.format).unwrap_or(OutputFormat::Markdown);
 
+    // Documents written before a field rename read under the field's current name
+    let target = args.file.as_deref().unwrap_or(Path::new("."));
+    if let Some(path) = md_db::project::resolve_optional_schema(&args.schema, target)? {
+        if let Some(fm) = doc.frontmatter.as_mut() {
+            Schema::from_file(path)?.apply_aliases(fm);
+        }
+    }
+
     // --field: return bare frontmatter value
     if let Some(ref field) = args.field {
         let fm = doc.frontmatter()?;
@@ -77,7 +95,11 @@ pub fn run(args: &GetArgs) -> Result<(), Box<dyn std::error::Error>> {
 
     // --section: get section content
     if let Some(ref heading) = args.section {
-        let section = doc.get_section(heading)?;
+        let heading = match args.nth {
+            Some(n) => doc.nth_matching_heading(heading, n)?,
+            None => heading.clone(),
+        };
+        let section = doc.get_section(&heading)?;
 
         // --table within section
         if let Some(table_idx) = args.table {
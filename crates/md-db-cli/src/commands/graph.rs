@@ -9,8 +9,8 @@ pub struct GraphArgs {
     /// Directory containing markdown files
     pub dir: PathBuf,
 
-    /// Path to KDL schema file
-    #[arg(long)]
+    /// Path to KDL schema file, or `auto` to use the closest one above the target
+    #[arg(long, default_value = "auto")]
     pub schema: PathBuf,
 
     /// Output format: mermaid, dot, json
@@ -24,11 +24,26 @@ pub struct GraphArgs {
     /// Run structural health checks instead of rendering the graph
     #[arg(long)]
     pub check: bool,
+
+    /// Rebuild the graph from scratch instead of using the .md-db/graph.bin snapshot
+    #[arg(long)]
+    pub no_cache: bool,
+
+    /// Build the graph from the documents at a git revision (tag, branch, commit) instead of the working tree;
+    /// the schema still comes from the working tree
+    #[arg(long, value_name = "REV")]
+    pub at: Option<String>,
 }
 
 pub fn run(args: &GraphArgs) -> Result<(), Box<dyn std::error::Error>> {
-    let schema = Schema::from_file(&args.schema)?;
-    let graph = DocGraph::build(&args.dir, &schema)?;
+    let schema = Schema::from_file(md_db::project::resolve_schema(&args.schema, &args.dir)?)?;
+    let graph = if let Some(ref rev) = args.at {
+        DocGraph::from_contents(&md_db::vcs::read_tree_at(rev, &args.dir)?, &schema)
+    } else if args.no_cache {
+        DocGraph::build(&args.dir, &schema)?
+    } else {
+        DocGraph::build_cached(&args.dir, &schema)?
+    };
 
     if args.check {
         return run_check(&graph, &schema, &args.format);
@@ -38,10 +53,10 @@ pub fn run(args: &GraphArgs) -> Result<(), Box<dyn std::error::Error>> {
 
     match args.format.as_str() {
         "mermaid" => {
-            print!("{}", graph.to_mermaid(filter_type));
+            print!("{}", graph.to_mermaid(filter_type, &schema));
         }
         "dot" => {
-            print!("{}", graph.to_dot(filter_type));
+            print!("{}", graph.to_dot(filter_type, &schema));
         }
         "json" => {
             let nodes: Vec<serde_json::Value> = graph
@@ -58,6 +73,7 @@ pub fn run(args: &GraphArgs) -> Result<(), Box<dyn std::error::Error>> {
                         "type": n.doc_type,
                         "title": n.title,
                         "status": n.status,
+                        "uid": n.uid,
                         "path": n.path.display().to_string(),
                     })
                 })
@@ -14,15 +14,19 @@ pub struct HookArgs {
     /// Schema file path relative to repo root
     #[arg(long, default_value = "schema.kdl")]
     pub schema: String,
+
+    /// Lowest severity that blocks the commit: error, warning, never
+    #[arg(long, default_value = "error")]
+    pub fail_on: String,
 }
 
 const HOOK_TEMPLATE: &str = r#"#!/usr/bin/env bash
-# md-db pre-commit hook — validates changed markdown files
+# md-db pre-commit hook — validates staged markdown files
+# Diagnostics at or above '{FAIL_ON}' block the commit; others are reported only.
 set -euo pipefail
 
-changed=$(git diff --cached --name-only --diff-filter=ACM -- '*.md')
-if [ -n "$changed" ]; then
-    echo "$changed" | md-db validate --stdin-list --schema '{SCHEMA}'
+if [ -n "$(git diff --cached --name-only --diff-filter=ACM -- '*.md')" ]; then
+    md-db validate --staged --summary --fail-on {FAIL_ON} --schema '{SCHEMA}'
 fi
 "#;
 
@@ -40,6 +44,14 @@ fn install(args: &HookArgs) -> Result<(), Box<dyn std::error::Error>> {
         return Err("schema path contains unsafe characters (single quote or null byte)".into());
     }
 
+    if !["error", "warning", "never"].contains(&args.fail_on.as_str()) {
+        return Err(format!(
+            "unknown --fail-on \"{}\", expected error, warning, or never",
+            args.fail_on
+        )
+        .into());
+    }
+
     let hooks_dir = args.dir.join(".git/hooks");
     if !hooks_dir.exists() {
         return Err("not a git repository (no .git/hooks directory)".into());
@@ -50,8 +62,10 @@ fn install(args: &HookArgs) -> Result<(), Box<dyn std::error::Error>> {
         return Err("pre-commit hook already exists — remove it first or use 'uninstall'".into());
     }
 
-    let hook_content = HOOK_TEMPLATE.replace("{SCHEMA}", &args.schema);
-    fs::write(&hook_path, hook_content)?;
+    let hook_content = HOOK_TEMPLATE
+        .replace("{SCHEMA}", &args.schema)
+        .replace("{FAIL_ON}", &args.fail_on);
+    md_db::durability::write(&hook_path, hook_content)?;
 
     #[cfg(unix)]
     {
@@ -0,0 +1,91 @@
+use std::path::{Path, PathBuf};
+
+use clap::Args;
+use md_db::error::Error;
+use md_db::import::{self, ImportOptions};
+use md_db::schema::Schema;
+use md_db::undo::Recorder;
+
+#[derive(Debug, Args)]
+pub struct ImportArgs {
+    /// Source format: csv
+    pub format: String,
+
+    /// File to import
+    pub file: PathBuf,
+
+    /// Document type to create, one per row
+    #[arg(long = "type")]
+    pub doc_type: String,
+
+    /// Column-to-field mapping: "Title=title,Pri=priority" (columns named like a field map on their own)
+    #[arg(long)]
+    pub map: Option<String>,
+
+    /// Project directory; IDs continue from its documents and files go in the type's folder
+    #[arg(long, default_value = ".")]
+    pub dir: PathBuf,
+
+    /// Path to KDL schema file, or `auto` to use the closest one above the target
+    #[arg(long, default_value = "auto")]
+    pub schema: PathBuf,
+
+    /// List the files that would be created without writing them
+    #[arg(long)]
+    pub dry_run: bool,
+
+    /// Expand all template variables and date placeholders to real values
+    #[arg(long)]
+    pub fill: bool,
+
+    /// Don't add a stable `uid` (UUIDv7) to the frontmatter
+    #[arg(long)]
+    pub no_uid: bool,
+}
+
+pub fn run(args: &ImportArgs) -> Result<(), Box<dyn std::error::Error>> {
+    if args.format != "csv" {
+        return Err(format!("unknown format: {} (expected: csv)", args.format).into());
+    }
+    let schema_path = md_db::project::resolve_schema(&args.schema, &args.dir)?;
+    let schema = Schema::from_file(&schema_path)?;
+    let type_def = schema
+        .get_type(&args.doc_type)
+        .ok_or(Error::TypeNotFound(args.doc_type.clone()))?;
+
+    let csv = std::fs::read_to_string(&args.file).map_err(|_| Error::FileNotFound(args.file.clone()))?;
+    let opts = ImportOptions {
+        map: args.map.as_deref().map(import::parse_map).transpose()?.unwrap_or_default(),
+        fill: args.fill,
+        uid: !args.no_uid,
+    };
+    let schema_dir = schema_path.parent().unwrap_or(Path::new("."));
+    let (docs, ignored) = import::plan(&csv, type_def, &schema, schema_dir, &args.dir, &opts)?;
+    if !ignored.is_empty() {
+        eprintln!("ignoring column(s) with no matching field: {}", ignored.join(", "));
+    }
+
+    if args.dry_run {
+        for doc in &docs {
+            println!("{}  {}", doc.id, doc.path.display());
+        }
+        eprintln!("would create {} document(s)", docs.len());
+        return Ok(());
+    }
+
+    let mut undo = Recorder::new("import");
+    for doc in &docs {
+        if let Some(parent) = doc.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        undo.track(&doc.path);
+        if let Err(e) = md_db::durability::write(&doc.path, &doc.content) {
+            undo.finish()?;
+            return Err(e.into());
+        }
+        println!("{}  {}", doc.id, doc.path.display());
+    }
+    undo.finish()?;
+    eprintln!("created {} document(s)", docs.len());
+    Ok(())
+}
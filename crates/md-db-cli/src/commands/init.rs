@@ -30,10 +30,10 @@ pub fn run(args: &InitArgs) -> Result<(), Box<dyn std::error::Error>> {
         other => return Err(format!("unknown preset '{other}', expected: minimal, adr, full").into()),
     };
 
-    fs::write(&schema_path, schema)?;
+    md_db::durability::write(&schema_path, schema)?;
 
     let users_path = dir.join("users.yaml");
-    fs::write(&users_path, users_template())?;
+    md_db::durability::write(&users_path, users_template())?;
 
     // Create directories based on preset
     match args.preset.as_str() {
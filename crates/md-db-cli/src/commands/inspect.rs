@@ -1,8 +1,9 @@
 use std::collections::HashSet;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use clap::Args;
 use md_db::document::Document;
+use md_db::graph::DocGraph;
 use md_db::output::{self, OutputFormat};
 use md_db::schema::Schema;
 use md_db::users::UserConfig;
@@ -13,8 +14,8 @@ pub struct InspectArgs {
     /// Path to the markdown file (omit when using --stdin)
     pub file: Option<PathBuf>,
 
-    /// Path to KDL schema file
-    #[arg(long)]
+    /// Path to KDL schema file, or `auto` to use the closest one above the target
+    #[arg(long, default_value = "auto")]
     pub schema: PathBuf,
 
     /// Read document from stdin
@@ -25,13 +26,19 @@ pub struct InspectArgs {
     #[arg(long)]
     pub users: Option<PathBuf>,
 
+    /// Docs root to collect backlinks from (default: the schema's folder)
+    #[arg(long)]
+    pub dir: Option<PathBuf>,
+
     /// Output format: json, compact, text, auto (auto=json when piped)
     #[arg(long, default_value = "auto")]
     pub format: String,
 }
 
 pub fn run(args: &InspectArgs) -> Result<(), Box<dyn std::error::Error>> {
-    let schema = Schema::from_file(&args.schema)?;
+    let target = args.file.as_deref().unwrap_or(Path::new("."));
+    let schema_path = md_db::project::resolve_schema(&args.schema, target)?;
+    let schema = Schema::from_file(&schema_path)?;
     let user_config = match &args.users {
         Some(path) => Some(UserConfig::from_file(path)?),
         None => None,
@@ -49,6 +56,24 @@ pub fn run(args: &InspectArgs) -> Result<(), Box<dyn std::error::Error>> {
         Document::from_file(file)?
     };
 
+    // Backlinks come from the document's neighborhood in the graph snapshot, not a full build
+    let backlinks = match (&args.file, args.stdin) {
+        (Some(file), false) => {
+            let root = match &args.dir {
+                Some(dir) => dir.clone(),
+                None => schema_path
+                    .parent()
+                    .filter(|p| !p.as_os_str().is_empty())
+                    .unwrap_or(Path::new("."))
+                    .to_path_buf(),
+            };
+            let id = md_db::graph::path_to_id(file);
+            let graph = DocGraph::neighborhood(&root, &schema, &id, 1)?;
+            collect_backlinks(&graph, &id)
+        }
+        _ => Vec::new(),
+    };
+
     let format = OutputFormat::from_str(&args.format).unwrap_or(OutputFormat::auto());
 
     // Validate
@@ -62,7 +87,12 @@ pub fn run(args: &InspectArgs) -> Result<(), Box<dyn std::error::Error>> {
 
     match format {
         OutputFormat::Json => {
-            println!("{}", serde_json::to_string_pretty(&to_json(&doc, &file_result, &schema))?);
+            let mut json = to_json(&doc, &file_result, &schema);
+            json["backlinks"] = backlinks
+                .iter()
+                .map(|b| serde_json::json!({ "id": b.id, "relation": b.relation, "title": b.title }))
+                .collect();
+            println!("{}", serde_json::to_string_pretty(&json)?);
         }
         OutputFormat::Compact => {
             // Compact: frontmatter fields as key=value, then diagnostics
@@ -76,6 +106,17 @@ pub fn run(args: &InspectArgs) -> Result<(), Box<dyn std::error::Error>> {
             for s in doc.sections() {
                 println!("section:{}:level={}", s.heading.trim(), s.level);
             }
+            for r in relation_panel(&doc, &schema) {
+                println!(
+                    "relation:{}:{}={}",
+                    r.group.unwrap_or("-"),
+                    r.field,
+                    r.targets
+                );
+            }
+            for b in &backlinks {
+                println!("backlink:{}:{}", b.id, b.relation);
+            }
             for d in &file_result.diagnostics {
                 println!("diag:{}", d.to_compact());
             }
@@ -95,6 +136,29 @@ pub fn run(args: &InspectArgs) -> Result<(), Box<dyn std::error::Error>> {
                 let hashes = "#".repeat(s.level as usize);
                 println!("  {hashes} {}", s.heading.trim());
             }
+            let relations = relation_panel(&doc, &schema);
+            if !relations.is_empty() {
+                println!("\nRelations:");
+                let grouped = schema.has_relation_groups();
+                let mut current: Option<Option<&str>> = None;
+                for r in &relations {
+                    if grouped && current != Some(r.group) {
+                        println!("  [{}]", r.group.unwrap_or("other"));
+                        current = Some(r.group);
+                    }
+                    let indent = if grouped { "    " } else { "  " };
+                    println!("{indent}{}: {}", r.field, r.targets);
+                }
+            }
+            if !backlinks.is_empty() {
+                println!("\nBacklinks:");
+                for b in &backlinks {
+                    match &b.title {
+                        Some(title) => println!("  {} ({}) — {title}", b.id, b.relation),
+                        None => println!("  {} ({})", b.id, b.relation),
+                    }
+                }
+            }
             if !file_result.diagnostics.is_empty() {
                 println!("\nDiagnostics:");
                 for d in &file_result.diagnostics {
@@ -109,6 +173,54 @@ pub fn run(args: &InspectArgs) -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
+/// A document that refers to the inspected one.
+struct Backlink {
+    id: String,
+    relation: String,
+    title: Option<String>,
+}
+
+fn collect_backlinks(graph: &DocGraph, id: &str) -> Vec<Backlink> {
+    graph
+        .refs_to(id)
+        .into_iter()
+        .map(|e| Backlink {
+            id: e.from.clone(),
+            relation: e.relation.clone(),
+            title: graph.nodes.get(&e.from).and_then(|n| n.title.clone()),
+        })
+        .collect()
+}
+
+/// A relation field set on the document, for the relation panel.
+struct RelationEntry<'a> {
+    group: Option<&'a str>,
+    field: &'a str,
+    targets: String,
+}
+
+/// Relation fields present in the frontmatter, in the schema's grouped order.
+fn relation_panel<'a>(doc: &Document, schema: &'a Schema) -> Vec<RelationEntry<'a>> {
+    let Some(ref fm) = doc.frontmatter else {
+        return Vec::new();
+    };
+    let mut entries = Vec::new();
+    for group in schema.grouped_relations() {
+        for r in group.relations {
+            for field in std::iter::once(r.name.as_str()).chain(r.inverse.as_deref()) {
+                if let Some(val) = fm.get(field) {
+                    entries.push(RelationEntry {
+                        group: group.name,
+                        field,
+                        targets: output::yaml_value_display(val),
+                    });
+                }
+            }
+        }
+    }
+    entries
+}
+
 fn to_json(
     doc: &Document,
     file_result: &validation::FileResult,
@@ -142,6 +254,7 @@ fn to_json(
                 "message": d.message,
                 "location": d.location,
                 "hint": d.hint,
+                "span": d.span,
             })
         })
         .collect();
@@ -180,10 +293,22 @@ fn to_json(
             })
         });
 
+    let relations: Vec<serde_json::Value> = relation_panel(doc, schema)
+        .iter()
+        .map(|r| {
+            serde_json::json!({
+                "group": r.group,
+                "relation": r.field,
+                "targets": r.targets,
+            })
+        })
+        .collect();
+
     serde_json::json!({
         "path": doc.path.as_ref().map(|p| p.display().to_string()),
         "frontmatter": frontmatter,
         "sections": sections,
+        "relations": relations,
         "diagnostics": diagnostics,
         "errors": file_result.errors(),
         "warnings": file_result.warnings(),
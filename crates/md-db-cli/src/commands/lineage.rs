@@ -0,0 +1,76 @@
+use std::path::PathBuf;
+
+use clap::Args;
+use md_db::graph::DocGraph;
+use md_db::lineage;
+use md_db::schema::Schema;
+
+#[derive(Debug, Args)]
+pub struct LineageArgs {
+    /// Document ID (or uid) anywhere in the chain
+    pub id: String,
+
+    /// Directory containing markdown files
+    #[arg(long, default_value = ".")]
+    pub dir: PathBuf,
+
+    /// Path to KDL schema file, or `auto` to use the closest one above the target
+    #[arg(long, default_value = "auto")]
+    pub schema: PathBuf,
+
+    /// Output format: text, json, mermaid (timeline)
+    #[arg(long, default_value = "text")]
+    pub format: String,
+
+    /// Rebuild the graph from scratch instead of using the .md-db/graph.bin snapshot
+    #[arg(long)]
+    pub no_cache: bool,
+}
+
+pub fn run(args: &LineageArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let schema = Schema::from_file(md_db::project::resolve_schema(&args.schema, &args.dir)?)?;
+    let graph = if args.no_cache {
+        DocGraph::build(&args.dir, &schema)?
+    } else {
+        DocGraph::build_cached(&args.dir, &schema)?
+    };
+    let id = graph
+        .find_by_uid(&args.id)
+        .map(|n| n.id.clone())
+        .unwrap_or_else(|| args.id.to_uppercase());
+
+    let lineage = lineage::trace(&graph, &id, &schema)?;
+
+    match args.format.as_str() {
+        "json" => println!("{}", serde_json::to_string_pretty(&lineage)?),
+        "mermaid" => print!("{}", lineage.to_mermaid()),
+        _ => {
+            for (i, g) in lineage.generations.iter().enumerate() {
+                let marker = if g.id == lineage.id { "*" } else { " " };
+                let mut line = format!("{marker} {}. {}", i + 1, g.id);
+                if let Some(ref title) = g.title {
+                    line.push_str(&format!(" — {title}"));
+                }
+                if let Some(ref status) = g.status {
+                    line.push_str(&format!(" [{status}]"));
+                }
+                println!("{line}");
+
+                let mut meta = Vec::new();
+                if let Some(ref date) = g.date {
+                    meta.push(date.clone());
+                }
+                if !g.authors.is_empty() {
+                    meta.push(g.authors.join(", "));
+                }
+                meta.push(g.path.clone());
+                println!("     {}", meta.join(" · "));
+                if let Some(ref diff) = g.diff {
+                    println!("     {}", lineage::summarize_diff(diff));
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,82 @@
+use std::path::{Path, PathBuf};
+
+use clap::Args;
+use md_db::document::Document;
+use md_db::lint;
+use md_db::output::OutputFormat;
+use md_db::schema::Schema;
+use md_db::validation::ValidationResult;
+
+#[derive(Debug, Args)]
+pub struct LintArgs {
+    /// Directory or file to lint (omit when using --stdin)
+    pub dir: Option<PathBuf>,
+
+    /// Path to KDL schema file or `auto` (rules come from its `lint { }` block)
+    #[arg(long, default_value = "auto")]
+    pub schema: PathBuf,
+
+    /// Read document from stdin instead of file
+    #[arg(long)]
+    pub stdin: bool,
+
+    /// Glob pattern for filenames (default: "*.md")
+    #[arg(long)]
+    pub pattern: Option<String>,
+
+    /// Lowest severity that fails the run: error, warning, never
+    #[arg(long, default_value = "error")]
+    pub fail_on: String,
+
+    /// Output format: text, json, compact, auto (auto=json when piped)
+    #[arg(long, default_value = "auto")]
+    pub format: String,
+}
+
+pub fn run(args: &LintArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let target = args.dir.as_deref().unwrap_or(Path::new("."));
+    let schema_path = md_db::project::resolve_schema(&args.schema, target)?;
+    let schema = Schema::from_file(&schema_path)?;
+    if schema.lint.is_none() {
+        eprintln!("{}: no lint {{ }} block; nothing to check", schema_path.display());
+        return Ok(());
+    }
+
+    let result = if args.stdin {
+        let mut content = String::new();
+        std::io::Read::read_to_string(&mut std::io::stdin(), &mut content)?;
+        let doc = Document::from_str(&content)?;
+        ValidationResult {
+            file_results: vec![lint::lint_document(&doc, &schema)],
+        }
+    } else {
+        let dir = args
+            .dir
+            .as_ref()
+            .ok_or("directory argument required when not using --stdin")?;
+        lint::lint_directory(dir, &schema, args.pattern.as_deref())?
+    };
+
+    let blocked = match args.fail_on.as_str() {
+        "error" => !result.is_ok(),
+        "warning" => result.total_errors() + result.total_warnings() > 0,
+        "never" => false,
+        other => {
+            return Err(format!("unknown --fail-on \"{other}\", expected error, warning, or never").into())
+        }
+    };
+
+    match OutputFormat::from_str(&args.format).unwrap_or(OutputFormat::Text) {
+        OutputFormat::Json => {
+            let json = super::validate::result_to_json(&result);
+            println!("{}", serde_json::to_string_pretty(&json)?);
+        }
+        OutputFormat::Compact => print!("{}", result.to_compact_report()),
+        _ => print!("{}", result.to_report()),
+    }
+
+    if blocked {
+        std::process::exit(1);
+    }
+    Ok(())
+}
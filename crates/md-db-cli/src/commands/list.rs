@@ -1,9 +1,19 @@
-use std::path::PathBuf;
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
 
 use clap::Args;
 use md_db::discovery::{self, Filter};
+use md_db::document::Document;
 use md_db::frontmatter::Frontmatter;
+use md_db::graph::path_to_id;
+use md_db::histogram::{self, HistogramOptions};
+use md_db::ordering;
+use md_db::output::projection::{self, Column};
 use md_db::output::{self, ListEntry, OutputFormat};
+use md_db::schema::Schema;
+use md_db::users::UserConfig;
+use md_db::views::{self, ViewDef};
 
 #[derive(Debug, Args)]
 pub struct ListArgs {
@@ -38,17 +48,65 @@ pub struct ListArgs {
     #[arg(long = "not-has-field", value_name = "KEY")]
     pub not_has_fields: Vec<String>,
 
+    /// Filter by comparison: key<value, key<=value, key>value, key>=value (repeatable).
+    /// Dates compare chronologically, numbers numerically; $TODAY and $NOW expand
+    #[arg(long = "query", value_name = "KEY>=VALUE")]
+    pub queries: Vec<String>,
+
     /// Sort by frontmatter field (prefix with - for descending, e.g. -date)
     #[arg(long)]
     pub sort: Option<String>,
 
-    /// Output format: text, json
+    /// Path to KDL schema file (`auto` finds the closest one above the directory); without --sort, documents follow each type's `order-by`
+    #[arg(long)]
+    pub schema: Option<PathBuf>,
+
+    /// Output format: text, json, ndjson, csv, tsv
     #[arg(long, default_value = "text")]
     pub format: String,
 
-    /// Fields to include in JSON output (comma-separated)
+    /// Fields to include in output (comma-separated); text output prints them as tab-separated columns
     #[arg(long = "fields", value_name = "FIELDS")]
     pub output_fields: Option<String>,
+
+    /// Columns to add (comma-separated): id, path, frontmatter fields, table cells as "Section.Row.Column",
+    /// or computed words, reading_time (minutes), completeness (% of required sections filled; needs --schema)
+    #[arg(long, value_name = "COLUMNS")]
+    pub columns: Option<String>,
+
+    /// Return at most N documents
+    #[arg(long)]
+    pub limit: Option<usize>,
+
+    /// Skip the first N documents (after sorting)
+    #[arg(long, default_value = "0")]
+    pub offset: usize,
+
+    /// Print value counts for this field across the matching documents instead of listing them
+    #[arg(long, value_name = "FIELD")]
+    pub histogram: Option<String>,
+
+    /// With --histogram: keep the N most frequent values, bucket the rest as "other"
+    #[arg(long, default_value = "10")]
+    pub top: usize,
+
+    /// With --histogram: count @user values as their teams (needs --users)
+    #[arg(long, requires = "users")]
+    pub by_team: bool,
+
+    /// Path to user/team config YAML file
+    #[arg(long)]
+    pub users: Option<PathBuf>,
+
+    /// List the documents as they were at a git revision (tag, branch, commit) instead of the working tree;
+    /// the schema still comes from the working tree
+    #[arg(long, value_name = "REV")]
+    pub at: Option<String>,
+
+    /// Run a saved view from the schema or views.kdl (type, where, sort, columns, limit);
+    /// --sort, --columns, --fields, and --limit override its own
+    #[arg(long, value_name = "NAME")]
+    pub view: Option<String>,
 }
 
 pub fn run(args: &ListArgs) -> Result<(), Box<dyn std::error::Error>> {
@@ -105,12 +163,46 @@ pub fn run(args: &ListArgs) -> Result<(), Box<dyn std::error::Error>> {
     for f in &args.not_has_fields {
         filters.push(Filter::NotHasField(f.clone()));
     }
+    for q in &args.queries {
+        let filter = Filter::parse_comparison(q)
+            .ok_or_else(|| format!("invalid --query \"{q}\" (expected KEY<VALUE, KEY<=VALUE, KEY>VALUE, or KEY>=VALUE)"))?;
+        filters.push(filter);
+    }
+    let view = args.view.as_deref().map(|name| find_view(args, name)).transpose()?;
+    if let Some(ref view) = view {
+        filters.extend(view.filters());
+    }
 
     let pattern = args.pattern.as_deref();
-    let mut files = discovery::discover_files(&args.dir, pattern, &filters, false)?;
+    let snapshot: Option<BTreeMap<PathBuf, String>> = match &args.at {
+        Some(rev) => {
+            let tree = md_db::vcs::read_tree_at(rev, &args.dir)?;
+            Some(discovery::filter_contents(tree, pattern, &filters).into_iter().collect())
+        }
+        None => None,
+    };
+    let mut files = match &snapshot {
+        Some(tree) => tree.keys().cloned().collect(),
+        None => discovery::discover_files(&args.dir, pattern, &filters, false)?,
+    };
+
+    // Without --schema, the closest one (if any) still maps field aliases to current names
+    let schema = match &args.schema {
+        Some(path) => Some(Schema::from_file(md_db::project::resolve_schema(path, &args.dir)?)?),
+        None => md_db::project::find_schema(&args.dir).ok().and_then(|p| Schema::from_file(p).ok()),
+    };
+    let aliases = schema.as_ref();
+    let reader = Reader {
+        aliases,
+        snapshot: snapshot.as_ref(),
+    };
+
+    if let Some(ref field) = args.histogram {
+        return print_histogram(&files, field, args, format, reader);
+    }
 
     // Sort by frontmatter field if requested
-    if let Some(ref sort_spec) = args.sort {
+    if let Some(sort_spec) = args.sort.as_ref().or(view.as_ref().and_then(|v| v.sort.as_ref())) {
         let (sort_key, descending) = if let Some(key) = sort_spec.strip_prefix('-') {
             (key, true)
         } else {
@@ -121,50 +213,268 @@ pub fn run(args: &ListArgs) -> Result<(), Box<dyn std::error::Error>> {
         let mut file_vals: Vec<(PathBuf, Option<String>)> = files
             .into_iter()
             .map(|path| {
-                let val = std::fs::read_to_string(&path)
-                    .ok()
-                    .and_then(|content| Frontmatter::try_parse(&content).ok())
-                    .and_then(|(fm, _)| fm)
-                    .and_then(|fm| fm.get_display(sort_key));
+                let val = reader.frontmatter(&path).and_then(|fm| fm.get_display(sort_key));
                 (path, val)
             })
             .collect();
 
         file_vals.sort_by(|a, b| {
-            let cmp = a.1.as_deref().unwrap_or("").cmp(b.1.as_deref().unwrap_or(""));
+            let cmp = ordering::compare_text(a.1.as_deref().unwrap_or(""), b.1.as_deref().unwrap_or(""));
             if descending { cmp.reverse() } else { cmp }
         });
 
         files = file_vals.into_iter().map(|(path, _)| path).collect();
+    } else if let Some(schema) = aliases.filter(|_| args.schema.is_some()) {
+        let mut docs: Vec<(PathBuf, String, Option<Frontmatter>)> = files
+            .into_iter()
+            .map(|path| {
+                let fm = reader.frontmatter(&path);
+                let id = path_to_id(&path);
+                (path, id, fm)
+            })
+            .collect();
+        ordering::sort_documents(&mut docs, Some(schema), |(_, id, fm)| (id.as_str(), fm.as_ref()));
+        files = docs.into_iter().map(|(path, _, _)| path).collect();
     }
 
-    let selected_fields: Option<Vec<String>> = args
+    let limit = args.limit.or(view.as_ref().and_then(|v| v.limit));
+    let (files, page) = output::paginate(files, args.offset, limit);
+
+    let mut selected_fields: Option<Vec<String>> = args
         .output_fields
         .as_ref()
         .map(|s| s.split(',').map(|f| f.trim().to_string()).collect());
 
-    let entries: Vec<ListEntry> = files
-        .iter()
-        .map(|path| {
-            let fm_json = if format == OutputFormat::Json {
-                std::fs::read_to_string(path)
-                    .ok()
-                    .and_then(|content| Frontmatter::try_parse(&content).ok())
-                    .and_then(|(fm, _)| fm.map(|f| f.to_json()))
-            } else {
-                None
+    let view_columns = view.as_ref().filter(|_| args.output_fields.is_none()).and_then(|v| v.columns.as_deref());
+    let columns = args.columns.as_deref().or(view_columns).map(projection::parse_columns).unwrap_or_default();
+    let column_schema = aliases.filter(|_| {
+        args.schema.is_some() && columns.contains(&Column::Computed("completeness".into()))
+    });
+
+    if let Some(delimiter) = match format {
+        OutputFormat::Csv => Some(','),
+        OutputFormat::Tsv => Some('\t'),
+        _ => None,
+    } {
+        let fields = selected_fields.as_deref();
+        print!("{}", delimited(&files, fields, &columns, column_schema, reader, delimiter)?);
+        return Ok(());
+    }
+
+    // Text output needs the columns selected; JSON output includes them alongside frontmatter
+    let column_names: Vec<String> = columns.iter().map(Column::name).collect();
+    match selected_fields {
+        Some(ref mut fields) => fields.extend(column_names),
+        None if !columns.is_empty() && !matches!(format, OutputFormat::Json | OutputFormat::Ndjson) => {
+            selected_fields = Some(column_names);
+        }
+        None => {}
+    }
+
+    let json = matches!(format, OutputFormat::Json | OutputFormat::Ndjson);
+    let entry = |path: &PathBuf| {
+        let mut fm_json = if json || selected_fields.is_some() {
+            reader.frontmatter(path).map(|f| f.to_json())
+        } else {
+            None
+        };
+        if !columns.is_empty() {
+            add_columns(&mut fm_json, path, &columns, column_schema, reader);
+        }
+        ListEntry {
+            path: path.display().to_string(),
+            frontmatter_json: fm_json,
+        }
+    };
+
+    if format == OutputFormat::Ndjson {
+        // One line per document as it is read, so large listings never sit in memory as JSON
+        let mut out = std::io::stdout().lock();
+        for path in &files {
+            writeln!(out, "{}", output::list_entry_json(&entry(path), &selected_fields))?;
+        }
+    } else {
+        let entries: Vec<ListEntry> = files.iter().map(entry).collect();
+        println!(
+            "{}",
+            output::format_list(&entries, format, &selected_fields)
+        );
+    }
+
+    // Keep stdout parseable; report truncation on stderr
+    if let Some(next) = page.next_offset() {
+        eprintln!(
+            "showing {} of {} document(s); use --offset {next} for more",
+            page.returned, page.total
+        );
+    }
+
+    Ok(())
+}
+
+/// The view `name` from the schema (`--schema`, else the closest one) and its `views.kdl`.
+fn find_view(args: &ListArgs, name: &str) -> Result<ViewDef, Box<dyn std::error::Error>> {
+    let path = match &args.schema {
+        Some(path) => md_db::project::resolve_schema(path, &args.dir)?,
+        None => md_db::project::find_schema(&args.dir)?,
+    };
+    let views = views::load(&Schema::from_file(&path)?, &path)?;
+    if let Some(view) = views.iter().find(|v| v.name == name) {
+        return Ok(view.clone());
+    }
+    let known: Vec<&str> = views.iter().map(|v| v.name.as_str()).collect();
+    let known = if known.is_empty() { "none".to_string() } else { known.join(", ") };
+    Err(format!("unknown view '{name}' (defined: {known})").into())
+}
+
+/// Reads listed documents from the working tree, or from the files read at `--at`.
+#[derive(Clone, Copy)]
+struct Reader<'a> {
+    /// Schema whose field aliases are applied.
+    aliases: Option<&'a Schema>,
+    snapshot: Option<&'a BTreeMap<PathBuf, String>>,
+}
+
+impl Reader<'_> {
+    /// A file's frontmatter, with aliased fields under their current names.
+    fn frontmatter(&self, path: &Path) -> Option<Frontmatter> {
+        let mut fm = match self.snapshot {
+            Some(tree) => Frontmatter::try_parse(tree.get(path)?).ok()?.0?,
+            None => Frontmatter::read_from_file(path).ok().flatten()?,
+        };
+        if let Some(schema) = self.aliases {
+            schema.apply_aliases(&mut fm);
+        }
+        Some(fm)
+    }
+
+    /// A parsed document, with aliased fields under their current names.
+    fn document(&self, path: &Path) -> Option<Document> {
+        let mut doc = match self.snapshot {
+            Some(tree) => {
+                let mut doc = Document::from_str(tree.get(path)?).ok()?;
+                doc.path = Some(path.to_path_buf());
+                doc
+            }
+            None => Document::from_file(path).ok()?,
+        };
+        if let (Some(schema), Some(fm)) = (self.aliases, doc.frontmatter.as_mut()) {
+            schema.apply_aliases(fm);
+        }
+        Some(doc)
+    }
+}
+
+/// Add projected column values to a document's output object.
+fn add_columns(
+    fm_json: &mut Option<serde_json::Value>,
+    path: &Path,
+    columns: &[Column],
+    schema: Option<&Schema>,
+    reader: Reader,
+) {
+    let Some(doc) = reader.document(path) else {
+        return;
+    };
+    let values = projection::project(&doc, path, columns, schema);
+    let obj = fm_json.get_or_insert_with(|| serde_json::json!({}));
+    let Some(map) = obj.as_object_mut() else {
+        return;
+    };
+    for (column, value) in columns.iter().zip(values) {
+        map.insert(column.name(), value);
+    }
+}
+
+/// CSV/TSV rows: `--fields` then `--columns`, or with neither, the path and every
+/// frontmatter key seen across the results.
+fn delimited(
+    files: &[PathBuf],
+    fields: Option<&[String]>,
+    columns: &[Column],
+    schema: Option<&Schema>,
+    reader: Reader,
+    delimiter: char,
+) -> Result<String, Box<dyn std::error::Error>> {
+    let docs: Vec<(&PathBuf, Option<Document>)> = files.iter().map(|p| (p, reader.document(p))).collect();
+
+    let mut selected: Vec<Column> = fields.unwrap_or_default().iter().map(|f| Column::parse(f)).collect();
+    selected.extend(columns.iter().cloned());
+    if selected.is_empty() {
+        selected.push(Column::Path);
+        for (_, doc) in &docs {
+            let Some(fm) = doc.as_ref().and_then(|d| d.frontmatter.as_ref()) else {
+                continue;
             };
-            ListEntry {
-                path: path.display().to_string(),
-                frontmatter_json: fm_json,
+            for key in fm.keys() {
+                let column = Column::Field(key.clone());
+                if !selected.contains(&column) {
+                    selected.push(column);
+                }
             }
-        })
+        }
+    }
+
+    // Files that don't parse still get a row (path and id only)
+    let empty = Document::from_str("")?;
+    let headers: Vec<String> = selected.iter().map(Column::name).collect();
+    let rows: Vec<Vec<serde_json::Value>> = docs
+        .iter()
+        .map(|(path, doc)| projection::project(doc.as_ref().unwrap_or(&empty), path, &selected, schema))
         .collect();
+    Ok(projection::to_delimited(&headers, &rows, delimiter))
+}
 
-    println!(
-        "{}",
-        output::format_list(&entries, format, &selected_fields)
-    );
+fn print_histogram(
+    files: &[PathBuf],
+    field: &str,
+    args: &ListArgs,
+    format: OutputFormat,
+    reader: Reader,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let user_config = match (&args.users, args.by_team) {
+        (Some(path), true) => Some(UserConfig::from_file(path)?),
+        _ => None,
+    };
+    let docs: Vec<Frontmatter> = files
+        .iter()
+        .filter_map(|path| reader.frontmatter(path))
+        .collect();
+    let opts = HistogramOptions {
+        top: Some(args.top),
+        team_rollup: user_config.as_ref(),
+    };
+    let hist = histogram::histogram(&docs, field, &opts);
+
+    match format {
+        OutputFormat::Json => {
+            println!("{}", serde_json::to_string_pretty(&hist)?);
+            return Ok(());
+        }
+        OutputFormat::Ndjson => {
+            println!("{}", serde_json::to_string(&hist)?);
+            return Ok(());
+        }
+        _ => {}
+    }
 
+    println!("{field} ({} documents)", hist.documents);
+    let width = hist
+        .buckets
+        .iter()
+        .map(|b| b.value.chars().count())
+        .max()
+        .unwrap_or(0)
+        .max(9);
+    for b in &hist.buckets {
+        println!("  {:<width$}  {:>5}  {:>5.1}%", b.value, b.count, b.percent);
+    }
+    if let Some(ref other) = hist.other {
+        let label = format!("({} other)", other.values);
+        println!("  {label:<width$}  {:>5}  {:>5.1}%", other.count, other.percent);
+    }
+    if hist.missing > 0 {
+        println!("  {:<width$}  {:>5}", "(missing)", hist.missing);
+    }
     Ok(())
 }
@@ -0,0 +1,818 @@
+//! Language Server Protocol server over stdio.
+//!
+//! Publishes validation diagnostics when a document is opened or saved, completes
+//! enum values, relation targets, and users in frontmatter, and resolves document
+//! IDs (`ADR-001`) for go-to-definition and hover. Documents come from the graph
+//! snapshot (`.md-db/graph.bin`), refreshed at startup by file stamps; a hover
+//! loads only the neighborhood of the document under the cursor, never the full graph.
+//! Messages use the LSP base protocol (`Content-Length` framing); JSON-RPC
+//! responses are built the same way as the MCP server's.
+
+use std::collections::{HashMap, HashSet};
+use std::io::{self, BufRead, Read, Write};
+use std::path::{Path, PathBuf};
+
+use clap::Args;
+use md_db::document::Document;
+use md_db::graph::{DocGraph, DocNode};
+use md_db::graph_cache::GraphSnapshot;
+use md_db::normalize::NormalizeOptions;
+use md_db::schema::{FieldType, Schema};
+use md_db::span::Span;
+use md_db::users::UserConfig;
+use md_db::validation::{self, Severity};
+use serde_json::{json, Value};
+
+use super::mcp::{jsonrpc_error, jsonrpc_ok};
+
+#[derive(Debug, Args)]
+pub struct LspArgs {
+    /// Path to KDL schema file, or `auto` to use the closest one above the workspace root
+    #[arg(long, default_value = "auto")]
+    pub schema: PathBuf,
+
+    /// Path to user/team config YAML file
+    #[arg(long)]
+    pub users: Option<PathBuf>,
+
+    /// Workspace root (default: the root the editor sends, else the current directory)
+    #[arg(long)]
+    pub dir: Option<PathBuf>,
+
+    /// Accepted for editors that always pass it; stdio is the only transport
+    #[arg(long, hide = true)]
+    pub stdio: bool,
+}
+
+// LSP enum values
+const SEVERITY_ERROR: u8 = 1;
+const SEVERITY_WARNING: u8 = 2;
+const KIND_VALUE: u8 = 12;
+const KIND_REFERENCE: u8 = 18;
+const KIND_ENUM_MEMBER: u8 = 20;
+const TEXT_SYNC_FULL: u8 = 1;
+
+/// What a position's `character` counts: UTF-16 code units, the LSP default, or
+/// characters (`utf-32`) when the client offers that.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PositionEncoding {
+    Utf16,
+    Utf32,
+}
+
+impl PositionEncoding {
+    /// `utf-32` if the client lists it in `general.positionEncodings`, else UTF-16.
+    fn negotiate(params: &Value) -> Self {
+        let offered = params["capabilities"]["general"]["positionEncodings"].as_array();
+        if offered.is_some_and(|e| e.iter().any(|e| e == "utf-32")) {
+            Self::Utf32
+        } else {
+            Self::Utf16
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Utf16 => "utf-16",
+            Self::Utf32 => "utf-32",
+        }
+    }
+
+    /// Character index in `line` of a position's `character`.
+    fn to_char(self, line: &str, character: usize) -> usize {
+        match self {
+            Self::Utf32 => character,
+            Self::Utf16 => {
+                let mut units = 0;
+                for (i, c) in line.chars().enumerate() {
+                    if units >= character {
+                        return i;
+                    }
+                    units += c.len_utf16();
+                }
+                line.chars().count()
+            }
+        }
+    }
+
+    /// A position's `character` for character index `ch` in `line`.
+    fn from_char(self, line: &str, ch: usize) -> usize {
+        match self {
+            Self::Utf32 => ch,
+            Self::Utf16 => line.chars().take(ch).map(char::len_utf16).sum(),
+        }
+    }
+}
+
+struct Server {
+    schema: Schema,
+    users: Option<UserConfig>,
+    root: PathBuf,
+    snapshot: GraphSnapshot,
+    known_files: HashSet<PathBuf>,
+    known_ids: HashSet<String>,
+    /// Text of open documents by URI, kept current by `didChange`.
+    open: HashMap<String, String>,
+    encoding: PositionEncoding,
+}
+
+pub fn run(args: &LspArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let stdin = io::stdin();
+    let stdout = io::stdout();
+    let mut reader = stdin.lock();
+    let mut writer = stdout.lock();
+
+    let mut server: Option<Server> = None;
+    let mut shutdown = false;
+
+    while let Some(body) = read_message(&mut reader)? {
+        let msg: Value = match serde_json::from_slice(&body) {
+            Ok(v) => v,
+            Err(e) => {
+                write_message(&mut writer, &jsonrpc_error(&Value::Null, -32700, &format!("parse error: {e}")))?;
+                continue;
+            }
+        };
+        let id = msg.get("id").cloned();
+        let method = msg.get("method").and_then(|m| m.as_str()).unwrap_or("");
+        let params = msg.get("params").cloned().unwrap_or(Value::Null);
+
+        match (method, &id) {
+            ("initialize", Some(id)) => {
+                let root = args
+                    .dir
+                    .clone()
+                    .or_else(|| client_root(&params))
+                    .unwrap_or_else(|| PathBuf::from("."));
+                let encoding = PositionEncoding::negotiate(&params);
+                let response = match Server::new(args, &root, encoding) {
+                    Ok(s) => {
+                        server = Some(s);
+                        jsonrpc_ok(id, capabilities(encoding))
+                    }
+                    Err(e) => jsonrpc_error(id, -32603, &e),
+                };
+                write_message(&mut writer, &response)?;
+            }
+            ("shutdown", Some(id)) => {
+                shutdown = true;
+                write_message(&mut writer, &jsonrpc_ok(id, Value::Null))?;
+            }
+            ("exit", _) => std::process::exit(if shutdown { 0 } else { 1 }),
+            (_, id) => {
+                let Some(server) = server.as_mut() else {
+                    if let Some(id) = id {
+                        write_message(&mut writer, &jsonrpc_error(id, -32002, "server not initialized"))?;
+                    }
+                    continue;
+                };
+                match id {
+                    Some(id) => {
+                        let response = match server.request(method, &params) {
+                            Some(Ok(result)) => jsonrpc_ok(id, result),
+                            Some(Err(e)) => jsonrpc_error(id, -32602, &e),
+                            None => jsonrpc_error(id, -32601, &format!("method not found: {method}")),
+                        };
+                        write_message(&mut writer, &response)?;
+                    }
+                    None => {
+                        for notification in server.notify(method, &params) {
+                            write_message(&mut writer, &notification)?;
+                        }
+                    }
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+fn capabilities(encoding: PositionEncoding) -> Value {
+    json!({
+        "capabilities": {
+            "positionEncoding": encoding.name(),
+            "textDocumentSync": {
+                "openClose": true,
+                "change": TEXT_SYNC_FULL,
+                "save": { "includeText": false },
+            },
+            "completionProvider": { "triggerCharacters": [":", " ", "-", "\"", "[", ","] },
+            "definitionProvider": true,
+            "hoverProvider": true,
+            "documentFormattingProvider": true,
+        },
+        "serverInfo": {
+            "name": "md-db",
+            "version": env!("CARGO_PKG_VERSION"),
+        }
+    })
+}
+
+/// `rootUri`, falling back to the deprecated `rootPath`.
+fn client_root(params: &Value) -> Option<PathBuf> {
+    params
+        .get("rootUri")
+        .and_then(|u| u.as_str())
+        .and_then(uri_to_path)
+        .or_else(|| params.get("rootPath").and_then(|p| p.as_str()).map(PathBuf::from))
+}
+
+impl Server {
+    fn new(args: &LspArgs, root: &Path, encoding: PositionEncoding) -> Result<Self, String> {
+        let schema = md_db::project::resolve_schema(&args.schema, root)
+            .and_then(Schema::from_file)
+            .map_err(|e| e.to_string())?;
+        let users = match &args.users {
+            Some(path) => Some(UserConfig::from_file(path).map_err(|e| e.to_string())?),
+            None => None,
+        };
+        let root = root.canonicalize().unwrap_or_else(|_| root.to_path_buf());
+        let files = md_db::discovery::discover_files(&root, None, &[], false).map_err(|e| e.to_string())?;
+        let mut snapshot = GraphSnapshot::load(&root, &schema);
+        snapshot.refresh_modified(&files, &schema);
+        if snapshot.is_dirty() {
+            let _ = snapshot.save();
+        }
+        let mut server = Self {
+            schema,
+            users,
+            root,
+            snapshot,
+            known_files: HashSet::new(),
+            known_ids: HashSet::new(),
+            open: HashMap::new(),
+            encoding,
+        };
+        server.rescan();
+        Ok(server)
+    }
+
+    /// Refresh the file and ID sets that reference checks resolve against.
+    fn rescan(&mut self) {
+        let files = md_db::discovery::discover_files(&self.root, None, &[], false).unwrap_or_default();
+        self.known_files = files
+            .iter()
+            .map(|p| p.canonicalize().unwrap_or_else(|_| p.clone()))
+            .collect();
+        self.known_ids = validation::collect_known_ids(&files);
+    }
+
+    fn request(&self, method: &str, params: &Value) -> Option<Result<Value, String>> {
+        let result = match method {
+            "textDocument/completion" => self.position(params).map(|(text, line, ch)| {
+                let items = self.completion(&text, line, ch);
+                json!({ "isIncomplete": false, "items": items })
+            }),
+            "textDocument/definition" => self.position(params).map(|(text, line, ch)| {
+                self.node_at(&text, line, ch)
+                    .map(|(node, _)| {
+                        json!({
+                            "uri": path_to_uri(&node.path),
+                            "range": { "start": { "line": 0, "character": 0 }, "end": { "line": 0, "character": 0 } },
+                        })
+                    })
+                    .unwrap_or(Value::Null)
+            }),
+            "textDocument/formatting" => self.formatting(params),
+            "textDocument/hover" => self.position(params).map(|(text, line, ch)| {
+                self.node_at(&text, line, ch)
+                    .map(|(node, graph)| {
+                        json!({ "contents": { "kind": "markdown", "value": hover_text(&node, &graph) } })
+                    })
+                    .unwrap_or(Value::Null)
+            }),
+            _ => return None,
+        };
+        Some(result)
+    }
+
+    /// Handle a notification; returns notifications to send back.
+    fn notify(&mut self, method: &str, params: &Value) -> Vec<Value> {
+        let doc = &params["textDocument"];
+        let Some(uri) = doc.get("uri").and_then(|u| u.as_str()).map(str::to_string) else {
+            return Vec::new();
+        };
+        match method {
+            "textDocument/didOpen" => {
+                let text = doc.get("text").and_then(|t| t.as_str()).unwrap_or("").to_string();
+                let published = self.publish(&uri, &text);
+                self.open.insert(uri, text);
+                published
+            }
+            "textDocument/didChange" => {
+                // Full sync: the last change holds the whole document
+                if let Some(text) = params["contentChanges"]
+                    .as_array()
+                    .and_then(|c| c.last())
+                    .and_then(|c| c.get("text"))
+                    .and_then(|t| t.as_str())
+                {
+                    self.open.insert(uri, text.to_string());
+                }
+                Vec::new()
+            }
+            "textDocument/didSave" => {
+                let Some(path) = uri_to_path(&uri) else {
+                    return Vec::new();
+                };
+                let text = params
+                    .get("text")
+                    .and_then(|t| t.as_str())
+                    .map(str::to_string)
+                    .or_else(|| std::fs::read_to_string(&path).ok())
+                    .unwrap_or_default();
+                self.snapshot.update_file(&path, &self.schema);
+                self.rescan();
+                let published = self.publish(&uri, &text);
+                self.open.insert(uri, text);
+                published
+            }
+            "textDocument/didClose" => {
+                self.open.remove(&uri);
+                Vec::new()
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    /// Text and 0-based position (line, character index) of a `textDocument/position` request.
+    fn position(&self, params: &Value) -> Result<(String, usize, usize), String> {
+        let uri = params["textDocument"]["uri"].as_str().ok_or("missing textDocument.uri")?;
+        let line = params["position"]["line"].as_u64().ok_or("missing position.line")? as usize;
+        let character = params["position"]["character"].as_u64().ok_or("missing position.character")? as usize;
+        let text = self.text(uri)?;
+        let ch = self.encoding.to_char(text.lines().nth(line).unwrap_or(""), character);
+        Ok((text, line, ch))
+    }
+
+    /// The open document's text, else the file's.
+    fn text(&self, uri: &str) -> Result<String, String> {
+        match self.open.get(uri) {
+            Some(t) => Ok(t.clone()),
+            None => uri_to_path(uri)
+                .and_then(|p| std::fs::read_to_string(p).ok())
+                .ok_or_else(|| format!("cannot read {uri}")),
+        }
+    }
+
+    /// Edits for `textDocument/formatting`: the document normalized and laid out
+    /// as `fmt --normalize` would, as one edit replacing the whole text.
+    fn formatting(&self, params: &Value) -> Result<Value, String> {
+        let uri = params["textDocument"]["uri"].as_str().ok_or("missing textDocument.uri")?;
+        let text = self.text(uri)?;
+        // Unparseable frontmatter is reported as a diagnostic; leave the text alone
+        let Ok(mut doc) = Document::from_str(&text) else {
+            return Ok(json!([]));
+        };
+        doc.path = uri_to_path(uri);
+        let result = md_db::format::normalize_and_format(&doc, &self.schema, &NormalizeOptions::default());
+        if result.content == text {
+            return Ok(json!([]));
+        }
+        let last = text.rsplit('\n').next().unwrap_or("");
+        let character = self.encoding.from_char(last, last.chars().count());
+        let end = json!({ "line": text.matches('\n').count(), "character": character });
+        Ok(json!([{
+            "range": { "start": { "line": 0, "character": 0 }, "end": end },
+            "newText": result.content,
+        }]))
+    }
+
+    fn publish(&self, uri: &str, text: &str) -> Vec<Value> {
+        let Some(path) = uri_to_path(uri) else {
+            return Vec::new();
+        };
+        let lines: Vec<&str> = text.lines().collect();
+        let diagnostics: Vec<Value> = self
+            .diagnostics(&path, text)
+            .iter()
+            .map(|d| lsp_diagnostic(d, &lines, self.encoding))
+            .collect();
+        vec![json!({
+            "jsonrpc": "2.0",
+            "method": "textDocument/publishDiagnostics",
+            "params": { "uri": uri, "diagnostics": diagnostics },
+        })]
+    }
+
+    /// Validate `text` as the document at `path`, the way `md-db validate` would.
+    fn diagnostics(&self, path: &Path, text: &str) -> Vec<validation::Diagnostic> {
+        let mut doc = match Document::from_str(text) {
+            Ok(d) => d,
+            Err(e) => {
+                return vec![validation::Diagnostic {
+                    severity: Severity::Error,
+                    code: "E000".into(),
+                    message: format!("failed to parse: {e}"),
+                    location: "file".into(),
+                    hint: None,
+                    span: md_db::span::yaml_error_span(text),
+                }];
+            }
+        };
+        doc.path = Some(path.to_path_buf());
+        let result = if let Some(type_def) = self.schema.singleton_for(path) {
+            validation::validate_singleton(&doc, type_def, self.users.as_ref())
+        } else if doc.frontmatter.as_ref().is_some_and(|fm| fm.get("type").is_some()) {
+            validation::validate_document(&doc, &self.schema, &self.known_files, &self.known_ids, self.users.as_ref())
+        } else {
+            return Vec::new();
+        };
+        result.diagnostics
+    }
+
+    /// Completion items for a frontmatter value at the cursor.
+    fn completion(&self, text: &str, line: usize, ch: usize) -> Vec<Value> {
+        let lines: Vec<&str> = text.lines().collect();
+        let Some(end) = frontmatter_end(&lines) else {
+            return Vec::new();
+        };
+        if line == 0 || line >= end {
+            return Vec::new();
+        }
+        let current: String = lines[line].chars().take(ch).collect();
+        let Some(key) = key_for_value(&lines, line, &current) else {
+            return Vec::new();
+        };
+
+        if key == "type" {
+            return self
+                .schema
+                .types
+                .iter()
+                .filter(|t| !t.singleton)
+                .map(|t| item(&t.name, KIND_ENUM_MEMBER, t.description.as_deref()))
+                .collect();
+        }
+
+        let doc_type = lines[1..end].iter().find_map(|l| l.strip_prefix("type:")).map(unquote);
+        let targets = doc_type
+            .as_deref()
+            .map(|t| self.schema.ref_target_types(t, &key))
+            .unwrap_or_default();
+        let field_type = doc_type
+            .and_then(|t| self.schema.get_type(&t))
+            .and_then(|t| t.fields.iter().find(|f| f.name == key))
+            .map(|f| &f.field_type);
+        let is_relation = self.schema.find_relation(&key).is_some();
+
+        match field_type {
+            Some(FieldType::Enum(values)) => values.iter().map(|v| item(v, KIND_ENUM_MEMBER, None)).collect(),
+            Some(FieldType::User | FieldType::UserArray) => {
+                let Some(ref users) = self.users else {
+                    return Vec::new();
+                };
+                let mut handles = users.all_user_handles();
+                handles.extend(users.all_team_names());
+                handles.sort();
+                handles.iter().map(|h| item(h, KIND_VALUE, None)).collect()
+            }
+            Some(FieldType::Ref | FieldType::RefArray) => self.ref_items(targets),
+            _ if is_relation => self.ref_items(targets),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Document IDs, limited to `targets` types when there are any.
+    fn ref_items(&self, targets: &[String]) -> Vec<Value> {
+        self.snapshot
+            .nodes()
+            .filter(|n| targets.is_empty() || n.doc_type.as_ref().is_some_and(|t| targets.contains(t)))
+            .map(|n| {
+                let detail = match (&n.title, &n.status) {
+                    (Some(t), Some(s)) => format!("{t} ({s})"),
+                    (Some(t), None) => t.clone(),
+                    (None, Some(s)) => s.clone(),
+                    (None, None) => String::new(),
+                };
+                item(&n.id, KIND_REFERENCE, Some(&detail).filter(|d| !d.is_empty()).map(String::as_str))
+            })
+            .collect()
+    }
+
+    /// The document named by the ID (or uid) under the cursor, and its direct neighborhood.
+    fn node_at(&self, text: &str, line: usize, ch: usize) -> Option<(DocNode, DocGraph)> {
+        let word = word_at(text.lines().nth(line)?, ch)?;
+        let graph = self.snapshot.neighborhood(word, 1);
+        let node = graph
+            .nodes
+            .get(&word.to_uppercase())
+            .or_else(|| graph.find_by_uid(word))?
+            .clone();
+        Some((node, graph))
+    }
+}
+
+fn item(label: &str, kind: u8, detail: Option<&str>) -> Value {
+    let mut item = json!({ "label": label, "kind": kind });
+    if let Some(d) = detail {
+        item["detail"] = json!(d);
+    }
+    item
+}
+
+fn hover_text(node: &DocNode, graph: &DocGraph) -> String {
+    let mut out = format!("**{}**", node.id);
+    if let Some(ref title) = node.title {
+        out.push_str(&format!(" — {title}"));
+    }
+    let mut facts = Vec::new();
+    if let Some(ref t) = node.doc_type {
+        facts.push(format!("type: {t}"));
+    }
+    if let Some(ref s) = node.status {
+        facts.push(format!("status: {s}"));
+    }
+    if !facts.is_empty() {
+        out.push_str(&format!("\n\n{}", facts.join(" · ")));
+    }
+    let backlinks: Vec<&str> = graph.refs_to(&node.id).iter().map(|e| e.from.as_str()).collect();
+    if !backlinks.is_empty() {
+        out.push_str(&format!("\n\nReferenced by {}", backlinks.join(", ")));
+    }
+    out.push_str(&format!("\n\n`{}`", node.path.display()));
+    out
+}
+
+fn lsp_diagnostic(d: &validation::Diagnostic, lines: &[&str], encoding: PositionEncoding) -> Value {
+    let mut message = d.message.clone();
+    if let Some(ref hint) = d.hint {
+        message.push_str(&format!("\nhint: {hint}"));
+    }
+    json!({
+        "range": range(d.span, lines, encoding),
+        "severity": match d.severity {
+            Severity::Error => SEVERITY_ERROR,
+            Severity::Warning => SEVERITY_WARNING,
+        },
+        "code": d.code,
+        "source": "md-db",
+        "message": message,
+    })
+}
+
+/// LSP range (0-based, in the negotiated encoding) for a span of `lines`; the
+/// start of the file when there is none.
+fn range(span: Option<Span>, lines: &[&str], encoding: PositionEncoding) -> Value {
+    let (l, c, el, ec) = span.map_or((0, 0, 0, 0), |s| {
+        (s.line - 1, s.column - 1, s.end_line - 1, s.end_column.saturating_sub(1))
+    });
+    let character = |line: usize, ch: usize| encoding.from_char(lines.get(line).copied().unwrap_or(""), ch);
+    let (c, ec) = (character(l, c), character(el, ec));
+    json!({
+        "start": { "line": l, "character": c },
+        "end": { "line": el, "character": ec },
+    })
+}
+
+/// Index of the closing `---` line, if the text starts with frontmatter.
+fn frontmatter_end(lines: &[&str]) -> Option<usize> {
+    if lines.first()?.trim_end() != "---" {
+        return None;
+    }
+    lines.iter().skip(1).position(|l| l.trim_end() == "---").map(|i| i + 1)
+}
+
+/// The frontmatter key whose value is being typed: `key: ...` on the line, or
+/// the key above a `- item` line.
+fn key_for_value(lines: &[&str], line: usize, before_cursor: &str) -> Option<String> {
+    let trimmed = before_cursor.trim_start();
+    if trimmed.starts_with('-') {
+        let indent = before_cursor.len() - trimmed.len();
+        return lines[1..line].iter().rev().find_map(|l| {
+            let t = l.trim_start();
+            let key = t.strip_suffix(':').filter(|_| l.len() - t.len() <= indent)?;
+            Some(key.trim().to_string())
+        });
+    }
+    let (key, _) = trimmed.split_once(':')?;
+    Some(key.trim().to_string())
+}
+
+fn unquote(s: &str) -> String {
+    s.trim().trim_matches(|c| c == '"' || c == '\'').to_string()
+}
+
+/// The ID-like word (letters, digits, `-`, `_`) around character `ch`.
+fn word_at(line: &str, ch: usize) -> Option<&str> {
+    let is_word = |c: char| c.is_alphanumeric() || c == '-' || c == '_';
+    let chars: Vec<(usize, char)> = line.char_indices().collect();
+    let ch = ch.min(chars.len());
+    let start = chars[..ch].iter().rev().take_while(|(_, c)| is_word(*c)).count();
+    let start = ch - start;
+    let end = ch + chars[ch..].iter().take_while(|(_, c)| is_word(*c)).count();
+    if start == end {
+        return None;
+    }
+    let from = chars[start].0;
+    let to = chars.get(end).map_or(line.len(), |(i, _)| *i);
+    Some(line[from..to].trim_matches('-'))
+}
+
+/// Path of a `file://` URI, percent-decoded.
+fn uri_to_path(uri: &str) -> Option<PathBuf> {
+    let rest = uri.strip_prefix("file://")?;
+    let bytes = rest.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        // `%XX` needs the two bytes after the `%`, also at the very end
+        if bytes[i] == b'%' && i + 3 <= bytes.len() {
+            let hex = std::str::from_utf8(&bytes[i + 1..i + 3]).ok();
+            if let Some(b) = hex.and_then(|h| u8::from_str_radix(h, 16).ok()) {
+                out.push(b);
+                i += 3;
+                continue;
+            }
+        }
+        out.push(bytes[i]);
+        i += 1;
+    }
+    let path = String::from_utf8(out).ok()?;
+    // file:///C:/x on Windows
+    let path = match path.as_bytes() {
+        [b'/', _, b':', ..] if cfg!(windows) => path[1..].to_string(),
+        _ => path,
+    };
+    Some(PathBuf::from(path))
+}
+
+/// `file://` URI for a path, percent-encoding everything but unreserved characters and `/`.
+fn path_to_uri(path: &Path) -> String {
+    let abs = if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        std::env::current_dir().map(|d| d.join(path)).unwrap_or_else(|_| path.to_path_buf())
+    };
+    let s = abs.to_string_lossy().replace('\\', "/");
+    let mut uri = String::from("file://");
+    if !s.starts_with('/') {
+        uri.push('/');
+    }
+    for b in s.bytes() {
+        match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' | b'/' | b':' => uri.push(b as char),
+            _ => uri.push_str(&format!("%{b:02X}")),
+        }
+    }
+    uri
+}
+
+/// Read one `Content-Length`-framed message body. `None` at end of input.
+fn read_message(reader: &mut impl BufRead) -> io::Result<Option<Vec<u8>>> {
+    let mut length = None;
+    loop {
+        let mut header = String::new();
+        if reader.read_line(&mut header)? == 0 {
+            return Ok(None);
+        }
+        let header = header.trim_end();
+        if header.is_empty() {
+            if length.is_some() {
+                break;
+            }
+            continue;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.eq_ignore_ascii_case("Content-Length") {
+                length = value.trim().parse::<usize>().ok();
+            }
+        }
+    }
+    let mut body = vec![0; length.unwrap_or(0)];
+    reader.read_exact(&mut body)?;
+    Ok(Some(body))
+}
+
+fn write_message(writer: &mut impl Write, msg: &Value) -> io::Result<()> {
+    let body = msg.to_string();
+    write!(writer, "Content-Length: {}\r\n\r\n{body}", body.len())?;
+    writer.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn server() -> (tempfile::TempDir, Server) {
+        let schema = Schema::from_str(
+            r#"
+relation "supersedes" inverse="superseded_by" cardinality="one"
+type "adr" {
+    field "title" type="string" required=#true
+    field "status" type="enum" required=#true {
+        values "proposed" "accepted" "rejected"
+    }
+    field "related" type="ref[]"
+}
+"#,
+        )
+        .unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("adr-001.md");
+        std::fs::write(&path, "---\ntype: adr\ntitle: Use PostgreSQL\nstatus: accepted\n---\n").unwrap();
+        let mut snapshot = GraphSnapshot::new(dir.path(), &schema);
+        snapshot.update_file(&path, &schema);
+        let server = Server {
+            schema,
+            users: None,
+            root: dir.path().to_path_buf(),
+            snapshot,
+            known_files: HashSet::new(),
+            known_ids: HashSet::new(),
+            open: HashMap::new(),
+            encoding: PositionEncoding::Utf16,
+        };
+        (dir, server)
+    }
+
+    #[test]
+    fn test_framing() {
+        let mut out = Vec::new();
+        write_message(&mut out, &json!({ "id": 1 })).unwrap();
+        assert_eq!(out, b"Content-Length: 8\r\n\r\n{\"id\":1}");
+        let mut input = io::Cursor::new([out.clone(), out].concat());
+        assert_eq!(read_message(&mut input).unwrap().unwrap(), b"{\"id\":1}");
+        assert!(read_message(&mut input).unwrap().is_some());
+        assert!(read_message(&mut input).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_completion() {
+        let (_dir, server) = server();
+        let text = "---\ntype: adr\nstatus: \nsupersedes: \nrelated:\n  - \n---\n\nBody\n";
+        let labels = |line, ch| -> Vec<String> {
+            server
+                .completion(text, line, ch)
+                .iter()
+                .map(|i| i["label"].as_str().unwrap().to_string())
+                .collect()
+        };
+        assert_eq!(labels(2, 8), vec!["proposed", "accepted", "rejected"]);
+        assert_eq!(labels(3, 12), vec!["ADR-001"]);
+        assert_eq!(labels(5, 4), vec!["ADR-001"]);
+        assert_eq!(labels(1, 6), vec!["adr"]);
+        // Not in frontmatter
+        assert!(labels(8, 2).is_empty());
+    }
+
+    #[test]
+    fn test_definition_and_hover() {
+        let (_dir, server) = server();
+        let text = "---\ntype: adr\nsupersedes: ADR-001\n---\n\nSee adr-001.\n";
+        let (node, graph) = server.node_at(text, 2, 15).unwrap();
+        assert_eq!(node.id, "ADR-001");
+        assert_eq!(server.node_at(text, 5, 6).unwrap().0.id, "ADR-001");
+        assert!(server.node_at(text, 5, 1).is_none());
+        let hover = hover_text(&node, &graph);
+        assert!(hover.starts_with("**ADR-001** — Use PostgreSQL"));
+        assert!(hover.contains("status: accepted"));
+    }
+
+    #[test]
+    fn test_formatting() {
+        let (dir, mut server) = server();
+        let uri = path_to_uri(&dir.path().join("adr-002.md"));
+        server.open.insert(uri.clone(), "---\nstatus: accepted\ntitle: T\ntype: adr\n---\n\nBody   \n".into());
+        let edits = server.formatting(&json!({ "textDocument": { "uri": uri } })).unwrap();
+        assert_eq!(edits[0]["newText"], "---\ntype: adr\ntitle: T\nstatus: accepted\n---\n\nBody\n");
+        assert_eq!(edits[0]["range"]["end"], json!({ "line": 7, "character": 0 }));
+
+        let formatted = edits[0]["newText"].as_str().unwrap().to_string();
+        server.open.insert(uri.clone(), formatted);
+        assert_eq!(server.formatting(&json!({ "textDocument": { "uri": uri } })).unwrap(), json!([]));
+    }
+
+    #[test]
+    fn test_uri_roundtrip() {
+        let uri = path_to_uri(Path::new("/work/my docs/adr-001.md"));
+        assert_eq!(uri, "file:///work/my%20docs/adr-001.md");
+        assert_eq!(uri_to_path(&uri).unwrap(), PathBuf::from("/work/my docs/adr-001.md"));
+        assert_eq!(word_at("supersedes: ADR-001", 14), Some("ADR-001"));
+        assert_eq!(word_at("a  b", 2), None);
+        assert_eq!(uri_to_path("file:///work/adr-%41").unwrap(), PathBuf::from("/work/adr-A"));
+        assert_eq!(uri_to_path("file:///work/100%").unwrap(), PathBuf::from("/work/100%"));
+    }
+
+    #[test]
+    fn test_position_encoding() {
+        // 🚀 is two UTF-16 code units but one character
+        let line = "See 🚀 ADR-001";
+        let utf16 = PositionEncoding::Utf16;
+        assert_eq!(utf16.to_char(line, 8), 7);
+        assert_eq!(utf16.from_char(line, 7), 8);
+        assert_eq!(PositionEncoding::Utf32.to_char(line, 8), 8);
+
+        // Just after "ADR-001": UTF-16 unit 14 is character 13
+        let (_dir, mut server) = server();
+        let uri = "file:///nonexistent/adr-009.md";
+        server.open.insert(uri.into(), "---\ntype: adr\n---\n\nSee 🚀 ADR-001.\n".into());
+        let params = json!({ "textDocument": { "uri": uri }, "position": { "line": 4, "character": 14 } });
+        let (text, line, ch) = server.position(&params).unwrap();
+        assert_eq!(server.node_at(&text, line, ch).unwrap().0.id, "ADR-001");
+
+        let offered = json!({ "capabilities": { "general": { "positionEncodings": ["utf-8", "utf-32"] } } });
+        assert_eq!(PositionEncoding::negotiate(&offered), PositionEncoding::Utf32);
+        assert_eq!(PositionEncoding::negotiate(&json!({})), PositionEncoding::Utf16);
+    }
+}
@@ -2,23 +2,69 @@
 //!
 //! Reads JSON-RPC 2.0 requests line-by-line from stdin, dispatches to md-db
 //! library functions, and writes JSON-RPC responses to stdout.
+//!
+//! With `--http ADDR` it speaks the Streamable HTTP transport instead: clients
+//! POST messages to `/mcp`, each `initialize` opens a session (with its own
+//! directory, schema, and users file from the query string), and connections are
+//! served concurrently. `--tokens` requires a bearer token on every request;
+//! without it, only loopback addresses may be bound.
+//!
+//! An `mcp` capability block (see [`McpConfig`]) limits the tools offered, the
+//! paths they may touch, and the size of their results.
 
-use std::collections::HashSet;
+use std::collections::hash_map::RandomState;
+use std::collections::{HashMap, HashSet};
+use std::hash::{BuildHasher, Hasher};
 use std::io::{self, BufRead, Write};
-use std::path::PathBuf;
-
+use std::net::{IpAddr, TcpListener, TcpStream};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use md_db::auth::ApiTokens;
+use md_db::context::{self, ContextOptions};
 use md_db::discovery::{self, Filter};
 use md_db::document::Document;
+use md_db::fix::FixEngine;
 use md_db::frontmatter::Frontmatter;
 use md_db::graph::{DocGraph, path_to_id};
+use md_db::mcp_config::McpConfig;
+use md_db::migrate;
+use md_db::project::MANIFEST;
 use md_db::output;
+use md_db::output::problem;
+use md_db::output::projection::{self, Column};
+use md_db::promote;
 use md_db::schema::Schema;
+use md_db::search;
+use md_db::sync;
 use md_db::template;
+use md_db::undo::Recorder;
 use md_db::users::UserConfig;
 use md_db::validation;
+use md_db::views::{self, ViewDef};
 
+use clap::Args;
 use serde_json::{json, Value};
 
+use super::serve;
+
+#[derive(Debug, Args)]
+pub struct McpArgs {
+    /// KDL file with an `mcp` capability block (default: the md-db.kdl manifest in the current directory, if any)
+    #[arg(long)]
+    pub config: Option<PathBuf>,
+
+    /// Serve the Streamable HTTP transport at this address (e.g. 127.0.0.1:8808) instead of stdio
+    #[arg(long, value_name = "ADDR")]
+    pub http: Option<String>,
+
+    /// With --http: YAML file of `user: token` lines; every request then needs a bearer token
+    #[arg(long, requires = "http")]
+    pub tokens: Option<PathBuf>,
+}
+
 // ── Tool descriptors ────────────────────────────────────────────────────────
 
 fn tool_list() -> Value {
@@ -63,7 +109,10 @@ fn tool_list() -> Value {
                     "dir":     { "type": "string", "description": "Directory to search" },
                     "pattern": { "type": "string", "description": "Glob pattern (default *.md)" },
                     "fields":  { "type": "array",  "items": { "type": "string" }, "description": "Filters: key=value" },
-                    "sort":    { "type": "string", "description": "Sort by field (prefix - for descending)" }
+                    "sort":    { "type": "string", "description": "Sort by field (prefix - for descending)" },
+                    "select":  { "type": "array",  "items": { "type": "string" }, "description": "Frontmatter fields to return (default: all)" },
+                    "limit":   { "type": "integer", "description": "Return at most N documents" },
+                    "offset":  { "type": "integer", "description": "Skip the first N documents" }
                 },
                 "required": ["dir"]
             }
@@ -91,7 +140,9 @@ fn tool_list() -> Value {
                     "type":      { "type": "string",  "description": "Show details for a specific type" },
                     "field":     { "type": "string",  "description": "Show details for a field (requires type)" },
                     "relations": { "type": "boolean", "description": "Show all relations" },
-                    "export":    { "type": "boolean", "description": "Export full schema as JSON" }
+                    "export":    { "type": "boolean", "description": "Export full schema as JSON" },
+                    "example":   { "type": "boolean", "description": "Return a filled-in example document for the type (requires type)" },
+                    "users":     { "type": "string",  "description": "Path to users.yaml (example uses real handles)" }
                 },
                 "required": ["schema"]
             }
@@ -104,6 +155,7 @@ fn tool_list() -> Value {
                 "properties": {
                     "file":         { "type": "string",  "description": "Path to the markdown file" },
                     "fields":       { "type": "array",   "items": { "type": "string" }, "description": "Field updates: key=value" },
+                    "patch":        { "type": "object",  "description": "JSON Merge Patch for frontmatter (null deletes a key, objects merge); applied before fields" },
                     "section":      { "type": "string",  "description": "Target section heading" },
                     "content":      { "type": "string",  "description": "Replace section content" },
                     "append":       { "type": "string",  "description": "Append to section" },
@@ -112,7 +164,9 @@ fn tool_list() -> Value {
                     "value":        { "type": "string",  "description": "Value for --cell" },
                     "add_row":      { "type": "string",  "description": "Add row (comma-separated)" },
                     "section_sets": { "type": "array",   "items": { "type": "string" }, "description": "Batch: Heading=content" },
-                    "dry_run":      { "type": "boolean", "description": "Return result without writing" }
+                    "dry_run":      { "type": "boolean", "description": "Return result without writing" },
+                    "schema":       { "type": "string",  "description": "Schema whose workflow status changes must follow (default: auto)" },
+                    "force":        { "type": "boolean", "description": "Allow a status change the workflow forbids" }
                 },
                 "required": ["file"]
             }
@@ -126,7 +180,7 @@ fn tool_list() -> Value {
                     "type":    { "type": "string",  "description": "Document type name" },
                     "schema":  { "type": "string",  "description": "Path to KDL schema file" },
                     "output":  { "type": "string",  "description": "Output file path" },
-                    "dir":     { "type": "string",  "description": "Directory for auto-ID" },
+                    "dir":     { "type": "string",  "description": "Directory for auto-ID and {{NEXT_ID}} in templates" },
                     "fields":  { "type": "array",   "items": { "type": "string" }, "description": "Pre-fill: key=value" },
                     "fill":    { "type": "boolean", "description": "Expand template variables" },
                     "auto_id": { "type": "boolean", "description": "Auto-generate path using next ID" }
@@ -157,11 +211,32 @@ fn tool_list() -> Value {
                 "properties": {
                     "dir":    { "type": "string", "description": "Directory containing markdown files" },
                     "schema": { "type": "string", "description": "Path to KDL schema file" },
-                    "type":   { "type": "string", "description": "Filter by document type" }
+                    "type":   { "type": "string", "description": "Filter by document type" },
+                    "select": { "type": "array",  "items": { "type": "string" }, "description": "Node attributes to return: type, title, status, uid, path (id is always included)" },
+                    "limit":  { "type": "integer", "description": "Return at most N nodes (edges are limited to those leaving returned nodes)" },
+                    "offset": { "type": "integer", "description": "Skip the first N nodes" }
                 },
                 "required": ["dir", "schema"]
             }
         },
+        {
+            "name": "md-db-search",
+            "description": "Full-text search across document content and frontmatter.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "dir":            { "type": "string",  "description": "Directory to search" },
+                    "query":          { "type": "string",  "description": "Search query (substring match)" },
+                    "section":        { "type": "string",  "description": "Only search within this section heading" },
+                    "field":          { "type": "string",  "description": "Only search within this frontmatter field" },
+                    "case_sensitive": { "type": "boolean", "description": "Case-sensitive search" },
+                    "select":         { "type": "array",   "items": { "type": "string" }, "description": "Frontmatter fields to return with each hit" },
+                    "limit":          { "type": "integer", "description": "Return at most N documents" },
+                    "offset":         { "type": "integer", "description": "Skip the first N documents" }
+                },
+                "required": ["dir", "query"]
+            }
+        },
         {
             "name": "md-db-deprecate",
             "description": "Mark a document as deprecated or superseded.",
@@ -176,13 +251,154 @@ fn tool_list() -> Value {
                 },
                 "required": ["file", "schema"]
             }
+        },
+        {
+            "name": "md-db-fix",
+            "description": "Propose and apply automatic fixes (missing defaults, enum typos, missing sections). Returns the patches.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "schema":  { "type": "string",  "description": "Path to KDL schema file" },
+                    "file":    { "type": "string",  "description": "Single file to fix (instead of dir)" },
+                    "dir":     { "type": "string",  "description": "Directory to fix" },
+                    "users":   { "type": "string",  "description": "Path to user/team config YAML" },
+                    "dry_run": { "type": "boolean", "description": "Return patches without writing" }
+                },
+                "required": ["schema"]
+            }
+        },
+        {
+            "name": "md-db-context",
+            "description": "Token-budgeted context pack for a document or query: frontmatter, key sections, one-hop related summaries, and schema excerpts.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "dir":        { "type": "string",  "description": "Directory containing markdown files" },
+                    "schema":     { "type": "string",  "description": "Path to KDL schema file" },
+                    "id":         { "type": "string",  "description": "Focus document ID, uid, or path" },
+                    "query":      { "type": "string",  "description": "Focus on the top full-text matches instead" },
+                    "limit":      { "type": "integer", "description": "Focus documents taken from query (default 3)" },
+                    "max_tokens": { "type": "integer", "description": "Token budget (default 4000)" },
+                    "sections":   { "type": "array",   "items": { "type": "string" }, "description": "Sections to include (default: required sections)" },
+                    "format":     { "type": "string",  "description": "markdown (default) or json" }
+                },
+                "required": ["dir", "schema"]
+            }
+        },
+        {
+            "name": "md-db-batch",
+            "description": "Set frontmatter fields on every document matching the filters. At least one filter is required.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "dir":        { "type": "string",  "description": "Directory to scan" },
+                    "fields":     { "type": "array",   "items": { "type": "string" }, "description": "Equals filters: key=value" },
+                    "not_fields": { "type": "array",   "items": { "type": "string" }, "description": "Not-equal filters: key!=value" },
+                    "has_fields": { "type": "array",   "items": { "type": "string" }, "description": "Field names that must be present" },
+                    "contains":   { "type": "array",   "items": { "type": "string" }, "description": "Contains filters: key~=value" },
+                    "set":        { "type": "array",   "items": { "type": "string" }, "description": "Field updates: key=value" },
+                    "pattern":    { "type": "string",  "description": "Glob pattern for filenames" },
+                    "schema":     { "type": "string",  "description": "Schema whose workflow status changes must follow (default: auto)" },
+                    "force":      { "type": "boolean", "description": "Allow status changes the workflow forbids" },
+                    "dry_run":    { "type": "boolean", "description": "Return the matching files without writing" }
+                },
+                "required": ["dir", "set"]
+            }
+        },
+        {
+            "name": "md-db-sync",
+            "description": "Add missing inverse relation refs (and with prune, remove stale ones). Returns the planned changes.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "dir":     { "type": "string",  "description": "Directory containing markdown files" },
+                    "schema":  { "type": "string",  "description": "Path to KDL schema file" },
+                    "prune":   { "type": "boolean", "description": "Remove inverse refs whose document no longer links back" },
+                    "promote": { "type": "boolean", "description": "Also copy schema promote values from the body into frontmatter" },
+                    "dry_run": { "type": "boolean", "description": "Return the plan without writing" }
+                },
+                "required": ["dir", "schema"]
+            }
+        },
+        {
+            "name": "md-db-rename",
+            "description": "Change a document's ID: rename its file and rewrite refs to it in other documents.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "file":    { "type": "string",  "description": "Path to the markdown file" },
+                    "new_id":  { "type": "string",  "description": "New document ID, e.g. ADR-010" },
+                    "dir":     { "type": "string",  "description": "Directory to scan for references" },
+                    "schema":  { "type": "string",  "description": "Path to KDL schema file" },
+                    "dry_run": { "type": "boolean", "description": "Return the changes without writing" }
+                },
+                "required": ["file", "new_id", "dir", "schema"]
+            }
+        },
+        {
+            "name": "md-db-migrate",
+            "description": "Compare two schema versions: the diff, its breaking changes, and with dir the migration plan and affected documents. Never writes.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "old_schema": { "type": "string", "description": "Path to the current KDL schema" },
+                    "new_schema": { "type": "string", "description": "Path to the target KDL schema" },
+                    "dir":        { "type": "string", "description": "Directory of documents to plan the migration for" }
+                },
+                "required": ["old_schema", "new_schema"]
+            }
+        },
+        {
+            "name": "md-db-stats",
+            "description": "Corpus dashboard: documents by type and status, validation counts, graph and staleness summary.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "dir":    { "type": "string", "description": "Directory containing markdown files" },
+                    "schema": { "type": "string", "description": "Path to KDL schema file" },
+                    "users":  { "type": "string", "description": "Path to user/team config YAML" }
+                },
+                "required": ["dir", "schema"]
+            }
         }
     ])
 }
 
+/// Name prefix of the tools generated from saved views.
+const VIEW_TOOL_PREFIX: &str = "md-db-view-";
+
+/// One tool per saved view, named `md-db-view-<name>`.
+fn view_tools(views: &[ViewDef]) -> Vec<Value> {
+    views
+        .iter()
+        .map(|view| {
+            let mut query: Vec<String> = view.doc_type.iter().map(|t| format!("type={t}")).collect();
+            query.extend(view.conditions.iter().cloned());
+            query.extend(view.sort.iter().map(|s| format!("sort {s}")));
+            let description = match view.description {
+                Some(ref d) => format!("{d} (saved view: {})", query.join("; ")),
+                None => format!("Saved view: {}", query.join("; ")),
+            };
+            json!({
+                "name": format!("{VIEW_TOOL_PREFIX}{}", view.name),
+                "description": description,
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "dir":    { "type": "string",  "description": "Directory to search" },
+                        "limit":  { "type": "integer", "description": "Return at most N documents (default: the view's limit)" },
+                        "offset": { "type": "integer", "description": "Skip the first N documents" }
+                    },
+                    "required": ["dir"]
+                }
+            })
+        })
+        .collect()
+}
+
 // ── JSON-RPC helpers ────────────────────────────────────────────────────────
 
-fn jsonrpc_ok(id: &Value, result: Value) -> Value {
+pub(crate) fn jsonrpc_ok(id: &Value, result: Value) -> Value {
     json!({
         "jsonrpc": "2.0",
         "id": id,
@@ -190,7 +406,7 @@ fn jsonrpc_ok(id: &Value, result: Value) -> Value {
     })
 }
 
-fn jsonrpc_error(id: &Value, code: i64, message: &str) -> Value {
+pub(crate) fn jsonrpc_error(id: &Value, code: i64, message: &str) -> Value {
     json!({
         "jsonrpc": "2.0",
         "id": id,
@@ -215,11 +431,95 @@ fn handle_tool_call(name: &str, args: &Value) -> Result<Value, String> {
         "md-db-new" => tool_new(args),
         "md-db-refs" => tool_refs(args),
         "md-db-graph" => tool_graph(args),
+        "md-db-search" => tool_search(args),
         "md-db-deprecate" => tool_deprecate(args),
+        "md-db-fix" => tool_fix(args),
+        "md-db-context" => tool_context(args),
+        "md-db-batch" => tool_batch(args),
+        "md-db-sync" => tool_sync(args),
+        "md-db-rename" => tool_rename(args),
+        "md-db-migrate" => tool_migrate(args),
+        "md-db-stats" => tool_stats(args),
         _ => Err(format!("unknown tool: {name}")),
     }
 }
 
+/// Path-valued arguments the capability root applies to.
+const PATH_ARGS: &[&str] = &["file", "dir", "schema", "users", "output", "old_schema", "new_schema"];
+
+/// Run a tool within the capability config and return its pretty-printed result.
+/// With a root, a missing `dir` defaults to it and every path argument must stay
+/// inside it.
+pub(super) fn guarded_call(config: &McpConfig, views: &[ViewDef], name: &str, args: &Value) -> Result<String, String> {
+    if !config.tool_allowed(name) {
+        return Err(format!("tool {name} is not enabled on this server"));
+    }
+    let view = name
+        .strip_prefix(VIEW_TOOL_PREFIX)
+        .and_then(|view| views.iter().find(|v| v.name == view));
+    let mut args = args.clone();
+    if let Some(ref root) = config.root {
+        if let Some(obj) = args.as_object_mut().filter(|_| view.is_some() || tool_accepts(name, "dir")) {
+            obj.entry("dir").or_insert_with(|| json!(root.display().to_string()));
+        }
+        for key in PATH_ARGS {
+            if let Some(path) = str_arg(&args, key) {
+                config.check_path(Path::new(&path)).map_err(|e| e.to_string())?;
+            }
+        }
+    }
+    let result = match view {
+        Some(view) => tool_view(view, &args)?,
+        None => handle_tool_call(name, &args)?,
+    };
+    // Under `--durability on-batch-end`, each call is a batch
+    md_db::durability::sync_pending().map_err(|e| e.to_string())?;
+    let text = serde_json::to_string_pretty(&result).unwrap_or_else(|_| result.to_string());
+    config.check_result_size(text.len()).map_err(|e| e.to_string())?;
+    Ok(text)
+}
+
+/// Whether the tool's input schema has this argument.
+fn tool_accepts(name: &str, arg: &str) -> bool {
+    tool_list()
+        .as_array()
+        .and_then(|tools| tools.iter().find(|t| t["name"] == name))
+        .is_some_and(|t| t["inputSchema"]["properties"].get(arg).is_some())
+}
+
+/// The capability config: `--config`, else an `md-db.kdl` in the current directory.
+fn load_config(args: &McpArgs) -> Result<McpConfig, Box<dyn std::error::Error>> {
+    let config = match &args.config {
+        Some(path) => McpConfig::from_file(path)?,
+        None if Path::new(MANIFEST).is_file() => McpConfig::from_file(MANIFEST)?,
+        None => McpConfig::default(),
+    };
+    let known = tool_list();
+    let known: Vec<&str> = known
+        .as_array()
+        .map(|tools| tools.iter().filter_map(|t| t["name"].as_str()).collect())
+        .unwrap_or_default();
+    // View tools depend on the schema found under the root, so any of their names is accepted
+    let unknown = config
+        .allow
+        .iter()
+        .chain(&config.deny)
+        .find(|t| !known.contains(&t.as_str()) && !t.starts_with(VIEW_TOOL_PREFIX));
+    if let Some(unknown) = unknown {
+        return Err(format!("mcp config names unknown tool '{unknown}' (known: {})", known.join(", ")).into());
+    }
+    Ok(config)
+}
+
+/// Saved views of the schema found from the root (or the current directory), if any.
+fn load_views(config: &McpConfig) -> Result<Vec<ViewDef>, Box<dyn std::error::Error>> {
+    let start = config.root.clone().unwrap_or_else(|| PathBuf::from("."));
+    let Ok(path) = md_db::project::find_schema(&start) else {
+        return Ok(Vec::new());
+    };
+    Ok(views::load(&Schema::from_file(&path)?, &path)?)
+}
+
 fn str_arg(args: &Value, key: &str) -> Option<String> {
     args.get(key).and_then(|v| v.as_str()).map(|s| s.to_string())
 }
@@ -288,7 +588,7 @@ fn tool_validate(args: &Value) -> Result<Value, String> {
     Ok(json!(out))
 }
 
-fn validate_result_to_json(result: &validation::ValidationResult) -> Value {
+pub(super) fn validate_result_to_json(result: &validation::ValidationResult) -> Value {
     let files: Vec<Value> = result
         .file_results
         .iter()
@@ -304,6 +604,7 @@ fn validate_result_to_json(result: &validation::ValidationResult) -> Value {
                         "message": d.message,
                         "location": d.location,
                         "hint": d.hint,
+                        "span": d.span,
                     })
                 })
                 .collect();
@@ -403,10 +704,9 @@ fn tool_list_docs(args: &Value) -> Result<Value, String> {
         let mut file_vals: Vec<(PathBuf, Option<String>)> = files
             .into_iter()
             .map(|path| {
-                let val = std::fs::read_to_string(&path)
+                let val = Frontmatter::read_from_file(&path)
                     .ok()
-                    .and_then(|content| Frontmatter::try_parse(&content).ok())
-                    .and_then(|(fm, _)| fm)
+                    .flatten()
                     .and_then(|fm| fm.get_display(&sort_key));
                 (path, val)
             })
@@ -428,13 +728,13 @@ fn tool_list_docs(args: &Value) -> Result<Value, String> {
         files = file_vals.into_iter().map(|(path, _)| path).collect();
     }
 
+    let (files, page) = output::paginate(files, int_arg(args, "offset").unwrap_or(0), int_arg(args, "limit"));
+    let select = str_array_arg(args, "select");
+
     let entries: Vec<Value> = files
         .iter()
         .map(|path| {
-            let fm_json = std::fs::read_to_string(path)
-                .ok()
-                .and_then(|content| Frontmatter::try_parse(&content).ok())
-                .and_then(|(fm, _)| fm.map(|f| f.to_json()));
+            let fm_json = read_frontmatter_json(path, &select);
             json!({
                 "path": path.display().to_string(),
                 "frontmatter": fm_json,
@@ -442,7 +742,56 @@ fn tool_list_docs(args: &Value) -> Result<Value, String> {
         })
         .collect();
 
-    Ok(json!({ "files": entries, "count": entries.len() }))
+    let mut result = page.to_json();
+    result["files"] = json!(entries);
+    Ok(result)
+}
+
+fn tool_view(view: &ViewDef, args: &Value) -> Result<Value, String> {
+    let dir = require_str(args, "dir")?;
+    let files = view.files(Path::new(&dir)).map_err(|e| e.to_string())?;
+    let limit = int_arg(args, "limit").or(view.limit);
+    let (files, page) = output::paginate(files, int_arg(args, "offset").unwrap_or(0), limit);
+    let columns = view.columns.as_deref().map(projection::parse_columns).unwrap_or_default();
+
+    let entries: Vec<Value> = files
+        .iter()
+        .map(|path| {
+            if columns.is_empty() {
+                return json!({
+                    "path": path.display().to_string(),
+                    "frontmatter": read_frontmatter_json(path, &[]),
+                });
+            }
+            let values = match Document::from_file(path) {
+                Ok(doc) => projection::project(&doc, path, &columns, None),
+                Err(_) => vec![Value::Null; columns.len()],
+            };
+            let row: serde_json::Map<String, Value> =
+                columns.iter().map(Column::name).zip(values).collect();
+            json!({ "path": path.display().to_string(), "columns": row })
+        })
+        .collect();
+
+    let mut result = page.to_json();
+    result["view"] = json!(view.name);
+    result["files"] = json!(entries);
+    Ok(result)
+}
+
+/// Frontmatter of a file as JSON, projected to `select` when non-empty.
+fn read_frontmatter_json(path: &std::path::Path, select: &[String]) -> Option<Value> {
+    Frontmatter::read_from_file(path)
+        .ok()
+        .flatten()
+        .map(|f| f.to_json())
+        .map(|fm| {
+            if select.is_empty() {
+                fm
+            } else {
+                output::project_fields(&fm, select)
+            }
+        })
 }
 
 fn tool_inspect(args: &Value) -> Result<Value, String> {
@@ -492,6 +841,7 @@ fn tool_inspect(args: &Value) -> Result<Value, String> {
                 "message": d.message,
                 "location": d.location,
                 "hint": d.hint,
+                "span": d.span,
             })
         })
         .collect();
@@ -524,6 +874,15 @@ fn tool_describe(args: &Value) -> Result<Value, String> {
             .get_type(&type_name)
             .ok_or_else(|| format!("unknown type: {type_name}"))?;
 
+        if bool_arg(args, "example") {
+            let user_config = match str_arg(args, "users") {
+                Some(path) => Some(UserConfig::from_file(&path).map_err(|e| e.to_string())?),
+                None => None,
+            };
+            let example = template::generate_example(type_def, &schema, user_config.as_ref());
+            return Ok(json!({ "type": type_def.name, "example": example }));
+        }
+
         if let Some(field_name) = str_arg(args, "field") {
             let field_def = type_def
                 .fields
@@ -562,6 +921,11 @@ fn tool_set(args: &Value) -> Result<Value, String> {
     let file = require_str(args, "file")?;
     let dry_run = bool_arg(args, "dry_run");
     let mut doc = Document::from_file(&PathBuf::from(&file)).map_err(|e| e.to_string())?;
+    let before = doc.frontmatter.clone();
+
+    if let Some(patch) = args.get("patch") {
+        doc.merge_patch(patch).map_err(|e| e.to_string())?;
+    }
 
     for field_str in str_array_arg(args, "fields") {
         let (key, value) = field_str
@@ -602,10 +966,27 @@ fn tool_set(args: &Value) -> Result<Value, String> {
         }
     }
 
+    if let (Some(before), Some(after)) = (&before, &doc.frontmatter) {
+        let schema_arg = PathBuf::from(str_arg(args, "schema").unwrap_or_else(|| "auto".into()));
+        let schema_path =
+            md_db::project::resolve_optional_schema(&schema_arg, Path::new(&file)).map_err(|e| e.to_string())?;
+        if let Some(schema_path) = schema_path {
+            let schema = Schema::from_file(&schema_path).map_err(|e| e.to_string())?;
+            if let Some(msg) = schema.transition_violation(before, after) {
+                if !bool_arg(args, "force") {
+                    return Err(format!("{msg}; pass force to override"));
+                }
+            }
+        }
+    }
+
     if dry_run {
         Ok(json!({ "content": doc.raw, "written": false }))
     } else {
+        let mut undo = Recorder::new("set");
+        undo.track(&file);
         doc.save().map_err(|e| e.to_string())?;
+        undo.finish().map_err(|e| e.to_string())?;
         Ok(json!({ "path": file, "written": true }))
     }
 }
@@ -620,7 +1001,7 @@ fn tool_new(args: &Value) -> Result<Value, String> {
         .ok_or_else(|| format!("unknown type: {doc_type}"))?;
 
     let field_strs = str_array_arg(args, "fields");
-    let fields: Vec<(String, String)> = field_strs
+    let mut fields: Vec<(String, String)> = field_strs
         .iter()
         .map(|s| {
             s.split_once('=')
@@ -628,15 +1009,24 @@ fn tool_new(args: &Value) -> Result<Value, String> {
                 .ok_or_else(|| format!("invalid field: {s}"))
         })
         .collect::<Result<_, _>>()?;
+    if !fields.iter().any(|(k, _)| k == md_db::uid::UID_FIELD) {
+        fields.push((md_db::uid::UID_FIELD.to_string(), md_db::uid::generate()));
+    }
 
     let fill = bool_arg(args, "fill");
     let auto_id = bool_arg(args, "auto_id");
 
+    let next_id = match str_arg(args, "dir") {
+        Some(dir) => Some(
+            DocGraph::build_cached(&PathBuf::from(&dir), &schema)
+                .map_err(|e| e.to_string())?
+                .next_id(&doc_type),
+        ),
+        None => None,
+    };
     let output_path = if auto_id {
         let dir = require_str(args, "dir")?;
-        let graph =
-            DocGraph::build(&PathBuf::from(&dir), &schema).map_err(|e| e.to_string())?;
-        let next_id = graph.next_id(&doc_type);
+        let next_id = next_id.as_deref().unwrap_or_default();
         let folder = type_def.folder.as_deref().unwrap_or(".");
         let filename = format!("{}.md", next_id.to_lowercase());
         Some(PathBuf::from(&dir).join(folder).join(filename))
@@ -644,7 +1034,9 @@ fn tool_new(args: &Value) -> Result<Value, String> {
         str_arg(args, "output").map(PathBuf::from)
     };
 
-    let content = template::generate_document_opts(type_def, &schema, &fields, fill);
+    let schema_dir = Path::new(&schema_path).parent().unwrap_or(Path::new("."));
+    let content = template::new_document(type_def, &schema, schema_dir, &fields, next_id.as_deref(), fill)
+        .map_err(|e| e.to_string())?;
 
     if let Some(ref path) = output_path {
         if let Some(parent) = path.parent() {
@@ -652,7 +1044,10 @@ fn tool_new(args: &Value) -> Result<Value, String> {
                 std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
             }
         }
-        std::fs::write(path, &content).map_err(|e| e.to_string())?;
+        let mut undo = Recorder::new("new");
+        undo.track(path);
+        md_db::durability::write(path, &content).map_err(|e| e.to_string())?;
+        undo.finish().map_err(|e| e.to_string())?;
         Ok(json!({ "path": path.display().to_string(), "content": content }))
     } else {
         Ok(json!({ "content": content }))
@@ -664,7 +1059,7 @@ fn tool_refs(args: &Value) -> Result<Value, String> {
     let schema_path = require_str(args, "schema")?;
     let schema = Schema::from_file(&PathBuf::from(&schema_path)).map_err(|e| e.to_string())?;
     let graph =
-        DocGraph::build(&PathBuf::from(&dir), &schema).map_err(|e| e.to_string())?;
+        DocGraph::build_cached(&PathBuf::from(&dir), &schema).map_err(|e| e.to_string())?;
     let depth = int_arg(args, "depth").unwrap_or(1);
 
     if let Some(target) = str_arg(args, "to") {
@@ -729,10 +1124,10 @@ fn tool_graph(args: &Value) -> Result<Value, String> {
     let schema_path = require_str(args, "schema")?;
     let schema = Schema::from_file(&PathBuf::from(&schema_path)).map_err(|e| e.to_string())?;
     let graph =
-        DocGraph::build(&PathBuf::from(&dir), &schema).map_err(|e| e.to_string())?;
+        DocGraph::build_cached(&PathBuf::from(&dir), &schema).map_err(|e| e.to_string())?;
     let filter_type = str_arg(args, "type");
 
-    let nodes: Vec<Value> = graph
+    let matching: Vec<&md_db::graph::DocNode> = graph
         .nodes
         .values()
         .filter(|n| {
@@ -741,20 +1136,39 @@ fn tool_graph(args: &Value) -> Result<Value, String> {
                 .map(|ft| n.doc_type.as_deref() == Some(ft))
                 .unwrap_or(true)
         })
+        .collect();
+    let (page_nodes, page) =
+        output::paginate(matching, int_arg(args, "offset").unwrap_or(0), int_arg(args, "limit"));
+
+    let select = str_array_arg(args, "select");
+    let nodes: Vec<Value> = page_nodes
+        .iter()
         .map(|n| {
-            json!({
+            let node = json!({
                 "id": n.id,
                 "type": n.doc_type,
                 "title": n.title,
                 "status": n.status,
+                "uid": n.uid,
                 "path": n.path.display().to_string(),
-            })
+            });
+            if select.is_empty() {
+                node
+            } else {
+                let mut keys = vec!["id".to_string()];
+                keys.extend(select.iter().cloned());
+                output::project_fields(&node, &keys)
+            }
         })
         .collect();
 
+    // When nodes are paged, only return edges leaving nodes in this page
+    let paged = page.offset > 0 || page.truncated();
+    let page_ids: HashSet<&str> = page_nodes.iter().map(|n| n.id.as_str()).collect();
     let edges: Vec<Value> = graph
         .edges
         .iter()
+        .filter(|e| !paged || page_ids.contains(e.from.as_str()))
         .map(|e| json!({ "from": e.from, "to": e.to, "relation": e.relation }))
         .collect();
 
@@ -763,9 +1177,46 @@ fn tool_graph(args: &Value) -> Result<Value, String> {
         "edges": edges,
         "node_count": nodes.len(),
         "edge_count": edges.len(),
+        "total_nodes": page.total,
+        "offset": page.offset,
+        "truncated": page.truncated(),
+        "next_offset": page.next_offset(),
     }))
 }
 
+fn tool_search(args: &Value) -> Result<Value, String> {
+    let dir = require_str(args, "dir")?;
+    let query = require_str(args, "query")?;
+    let options = search::SearchOptions {
+        case_sensitive: bool_arg(args, "case_sensitive"),
+        section_filter: str_arg(args, "section"),
+        field_filter: str_arg(args, "field"),
+        max_results: None,
+    };
+
+    let results = search::search_documents(&PathBuf::from(&dir), &query, &options)
+        .map_err(|e| e.to_string())?;
+    let (results, page) =
+        output::paginate(results, int_arg(args, "offset").unwrap_or(0), int_arg(args, "limit"));
+
+    let select = str_array_arg(args, "select");
+    let items: Vec<Value> = results
+        .iter()
+        .map(|r| {
+            let mut item = json!({ "path": r.path, "id": r.id, "matches": r.matches });
+            if !select.is_empty() {
+                item["frontmatter"] =
+                    json!(read_frontmatter_json(std::path::Path::new(&r.path), &select));
+            }
+            item
+        })
+        .collect();
+
+    let mut result = page.to_json();
+    result["results"] = json!(items);
+    Ok(result)
+}
+
 fn tool_deprecate(args: &Value) -> Result<Value, String> {
     let file = require_str(args, "file")?;
     let schema_path = require_str(args, "schema")?;
@@ -775,23 +1226,28 @@ fn tool_deprecate(args: &Value) -> Result<Value, String> {
     let mut doc = Document::from_file(&PathBuf::from(&file)).map_err(|e| e.to_string())?;
     let doc_id = path_to_id(std::path::Path::new(&file));
 
+    let doc_type = doc.frontmatter.as_ref().and_then(|fm| fm.get_display("type")).unwrap_or_default();
+    let status = schema.status_field(&doc_type);
     if let Some(replacement) = str_arg(args, "superseded_by") {
-        doc.set_field_from_str("status", "superseded");
+        doc.set_field_from_str(&status.field, status.superseded_value());
         doc.set_field_from_str("superseded_by", &replacement);
     } else {
-        doc.set_field_from_str("status", "deprecated");
+        doc.set_field_from_str(&status.field, status.deprecated_value());
     }
 
     if dry_run {
         return Ok(json!({ "id": doc_id, "content": doc.raw, "written": false }));
     }
 
+    let mut undo = Recorder::new("deprecate");
+    undo.track(&file);
     doc.save().map_err(|e| e.to_string())?;
+    undo.finish().map_err(|e| e.to_string())?;
 
     let mut backlinks = Vec::new();
     if let Some(dir) = str_arg(args, "dir") {
         let graph =
-            DocGraph::build(&PathBuf::from(&dir), &schema).map_err(|e| e.to_string())?;
+            DocGraph::build_cached(&PathBuf::from(&dir), &schema).map_err(|e| e.to_string())?;
         for edge in graph.refs_to(&doc_id) {
             if edge.from != doc_id {
                 backlinks.push(json!({ "from": edge.from, "relation": edge.relation }));
@@ -806,6 +1262,240 @@ fn tool_deprecate(args: &Value) -> Result<Value, String> {
     }))
 }
 
+fn tool_fix(args: &Value) -> Result<Value, String> {
+    let schema_path = require_str(args, "schema")?;
+    let schema = Schema::from_file(&PathBuf::from(&schema_path)).map_err(|e| e.to_string())?;
+    let user_config = str_arg(args, "users")
+        .map(|p| UserConfig::from_file(&PathBuf::from(p)))
+        .transpose()
+        .map_err(|e| e.to_string())?;
+    let dry_run = bool_arg(args, "dry_run");
+
+    let result = if let Some(file) = str_arg(args, "file") {
+        let doc = Document::from_file(&PathBuf::from(&file)).map_err(|e| e.to_string())?;
+        let fr = validation::validate_document(
+            &doc,
+            &schema,
+            &HashSet::new(),
+            &HashSet::new(),
+            user_config.as_ref(),
+        );
+        validation::ValidationResult {
+            file_results: vec![fr],
+        }
+    } else if let Some(dir) = str_arg(args, "dir") {
+        validation::validate_directory(&PathBuf::from(&dir), &schema, None, user_config.as_ref())
+            .map_err(|e| e.to_string())?
+    } else {
+        return Err("provide 'dir' or 'file'".into());
+    };
+
+    let engine = FixEngine::new();
+    let mut undo = Recorder::new("fix");
+    let mut files = Vec::new();
+    for fr in &result.file_results {
+        if fr.diagnostics.is_empty() {
+            continue;
+        }
+        let Ok(mut doc) = Document::from_file(&PathBuf::from(&fr.path)) else {
+            continue;
+        };
+        let patches = engine.fix(&mut doc, &fr.diagnostics, &schema);
+        if patches.is_empty() {
+            continue;
+        }
+        let written = !dry_run && patches.iter().any(|p| p.is_applicable());
+        if written {
+            undo.track(&fr.path);
+            doc.save().map_err(|e| e.to_string())?;
+        }
+        files.push(json!({
+            "path": fr.path,
+            "patches": patches,
+            "written": written,
+        }));
+    }
+    undo.finish().map_err(|e| e.to_string())?;
+
+    Ok(json!({ "files": files, "dry_run": dry_run }))
+}
+
+fn tool_batch(args: &Value) -> Result<Value, String> {
+    let dir = PathBuf::from(require_str(args, "dir")?);
+    let fields = str_array_arg(args, "fields");
+    let not_fields = str_array_arg(args, "not_fields");
+    let contains = str_array_arg(args, "contains");
+    let has_fields = str_array_arg(args, "has_fields");
+    if fields.is_empty() && not_fields.is_empty() && contains.is_empty() && has_fields.is_empty() {
+        return Err("at least one frontmatter filter is required (fields, not_fields, has_fields, or contains)".into());
+    }
+    let set_fields = str_array_arg(args, "set");
+    if set_fields.is_empty() {
+        return Err("missing required argument: set".into());
+    }
+    let set_pairs = set_fields
+        .iter()
+        .map(|s| s.split_once('=').ok_or_else(|| format!("invalid set format: {s}")))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let filters = super::batch::build_filters(&fields, &not_fields, &contains, &has_fields);
+    let files = discovery::discover_files(&dir, str_arg(args, "pattern").as_deref(), &filters, false)
+        .map_err(|e| e.to_string())?;
+
+    let schema_arg = PathBuf::from(str_arg(args, "schema").unwrap_or_else(|| "auto".into()));
+    let schema_path = md_db::project::resolve_optional_schema(&schema_arg, &dir).map_err(|e| e.to_string())?;
+    if let Some(schema_path) = schema_path {
+        let schema = Schema::from_file(&schema_path).map_err(|e| e.to_string())?;
+        let violations = super::batch::workflow_violations(&files, &set_pairs, &schema).map_err(|e| e.to_string())?;
+        if !violations.is_empty() && !bool_arg(args, "force") {
+            return Err(format!(
+                "{} document(s) would break their workflow; pass force to override: {}",
+                violations.len(),
+                violations.join("; ")
+            ));
+        }
+    }
+
+    let dry_run = bool_arg(args, "dry_run");
+    if !dry_run {
+        let mut undo = Recorder::new("batch");
+        for path in &files {
+            let mut doc = Document::from_file(path).map_err(|e| e.to_string())?;
+            for &(key, value) in &set_pairs {
+                doc.set_field_from_str(key, value);
+            }
+            undo.track(path);
+            if let Err(e) = doc.save() {
+                // Keep what was already written undoable
+                undo.finish().map_err(|e| e.to_string())?;
+                return Err(e.to_string());
+            }
+        }
+        undo.finish().map_err(|e| e.to_string())?;
+    }
+
+    let paths: Vec<String> = files.iter().map(|p| p.display().to_string()).collect();
+    Ok(json!({ "files": paths, "count": paths.len(), "written": !dry_run }))
+}
+
+fn tool_sync(args: &Value) -> Result<Value, String> {
+    let dir = PathBuf::from(require_str(args, "dir")?);
+    let schema_path = require_str(args, "schema")?;
+    let schema = Schema::from_file(&PathBuf::from(&schema_path)).map_err(|e| e.to_string())?;
+    let graph = DocGraph::build_cached(&dir, &schema).map_err(|e| e.to_string())?;
+    let plan = sync::plan_from_graph_with(&graph, &schema, bool_arg(args, "prune"));
+    let promotions = if bool_arg(args, "promote") {
+        let files = discovery::discover_files(&dir, None, &[], false).map_err(|e| e.to_string())?;
+        promote::plan_promotions(&files, &schema)
+    } else {
+        Vec::new()
+    };
+
+    let mut result = plan.to_json();
+    if bool_arg(args, "promote") {
+        let items: Vec<Value> = promotions.iter().map(|p| p.to_json()).collect();
+        result["promotion_count"] = json!(items.len());
+        result["promotions"] = json!(items);
+    }
+    let written = !bool_arg(args, "dry_run") && (!plan.is_empty() || !promotions.is_empty());
+    if written {
+        let mut undo = Recorder::new("sync");
+        undo.track_all(plan.actions.iter().map(|a| &a.path));
+        undo.track_all(promotions.iter().map(|p| &p.path));
+        sync::apply_sync_plan(&plan).map_err(|e| e.to_string())?;
+        promote::apply_promotions(&promotions).map_err(|e| e.to_string())?;
+        undo.finish().map_err(|e| e.to_string())?;
+    }
+    result["written"] = json!(written);
+    Ok(result)
+}
+
+fn tool_rename(args: &Value) -> Result<Value, String> {
+    let file = PathBuf::from(require_str(args, "file")?);
+    let new_id = require_str(args, "new_id")?;
+    let dir = PathBuf::from(require_str(args, "dir")?);
+    let schema_path = require_str(args, "schema")?;
+    let schema = Schema::from_file(&PathBuf::from(&schema_path)).map_err(|e| e.to_string())?;
+    let dry_run = bool_arg(args, "dry_run");
+
+    let renamed = super::rename::rename(&file, &new_id, &schema, &dir, dry_run).map_err(|e| e.to_string())?;
+    let updated: Vec<Value> = renamed
+        .updated
+        .iter()
+        .map(|(path, id)| json!({ "path": path.display().to_string(), "id": id }))
+        .collect();
+    Ok(json!({
+        "old_id": renamed.old_id,
+        "new_id": renamed.new_id,
+        "path": renamed.new_path.display().to_string(),
+        "updated": updated,
+        "written": !dry_run,
+    }))
+}
+
+fn tool_migrate(args: &Value) -> Result<Value, String> {
+    let old = Schema::from_file(&PathBuf::from(require_str(args, "old_schema")?)).map_err(|e| e.to_string())?;
+    let new = Schema::from_file(&PathBuf::from(require_str(args, "new_schema")?)).map_err(|e| e.to_string())?;
+    let diff = migrate::diff_schemas(&old, &new);
+    let mut breaking = diff.breaking_changes();
+
+    let mut result = json!({ "identical": diff.is_empty(), "diff": diff.to_json() });
+    if let Some(dir) = str_arg(args, "dir") {
+        let dir = PathBuf::from(dir);
+        migrate::estimate_breakage(&mut breaking, &new, &dir);
+        result["plan"] = migrate::compute_migration(&diff, &dir).to_json();
+    }
+    result["breaking"] = breaking.iter().map(|c| c.to_json()).collect();
+    Ok(result)
+}
+
+fn tool_stats(args: &Value) -> Result<Value, String> {
+    let dir = PathBuf::from(require_str(args, "dir")?);
+    let schema_path = require_str(args, "schema")?;
+    let schema = Schema::from_file(&PathBuf::from(&schema_path)).map_err(|e| e.to_string())?;
+    let user_config = str_arg(args, "users")
+        .map(|p| UserConfig::from_file(&PathBuf::from(p)))
+        .transpose()
+        .map_err(|e| e.to_string())?;
+    let dashboard =
+        super::stats::Dashboard::build(&dir, &schema, user_config.as_ref()).map_err(|e| e.to_string())?;
+    Ok(dashboard.to_json())
+}
+
+fn tool_context(args: &Value) -> Result<Value, String> {
+    let dir = PathBuf::from(require_str(args, "dir")?);
+    let schema_path = require_str(args, "schema")?;
+    let schema = Schema::from_file(&PathBuf::from(&schema_path)).map_err(|e| e.to_string())?;
+    let graph = DocGraph::build_cached(&dir, &schema).map_err(|e| e.to_string())?;
+
+    let focus = if let Some(id) = str_arg(args, "id") {
+        vec![context::resolve_id(&graph, &id)]
+    } else if let Some(query) = str_arg(args, "query") {
+        let limit = int_arg(args, "limit").unwrap_or(3);
+        context::focus_from_query(&graph, &dir, &query, limit).map_err(|e| e.to_string())?
+    } else {
+        return Err("provide 'id' or 'query'".into());
+    };
+    if focus.is_empty() {
+        return Err("no documents match the query".into());
+    }
+
+    let opts = ContextOptions {
+        max_tokens: int_arg(args, "max_tokens").unwrap_or(ContextOptions::default().max_tokens),
+        sections: str_array_arg(args, "sections"),
+    };
+    let pack = context::build(&graph, &schema, &focus, &opts).map_err(|e| e.to_string())?;
+
+    if str_arg(args, "format").as_deref() == Some("json") {
+        return serde_json::to_value(&pack).map_err(|e| e.to_string());
+    }
+    Ok(json!({
+        "focus": focus,
+        "estimated_tokens": pack.estimated_tokens,
+        "markdown": pack.to_markdown(),
+    }))
+}
+
 // ── Schema JSON helpers ─────────────────────────────────────────────────────
 
 fn field_type_short(ft: &md_db::schema::FieldType) -> &'static str {
@@ -820,6 +1510,8 @@ fn field_type_short(ft: &md_db::schema::FieldType) -> &'static str {
         FieldType::RefArray => "ref[]",
         FieldType::User => "user",
         FieldType::UserArray => "user[]",
+        FieldType::Date => "date",
+        FieldType::DateTime => "datetime",
     }
 }
 
@@ -841,6 +1533,18 @@ fn field_to_json(f: &md_db::schema::FieldDef) -> Value {
     if let md_db::schema::FieldType::Enum(ref vals) = f.field_type {
         obj["values"] = json!(vals);
     }
+    if let Some(ref min) = f.min {
+        obj["min"] = Value::String(min.clone());
+    }
+    if let Some(ref max) = f.max {
+        obj["max"] = Value::String(max.clone());
+    }
+    if f.not_future {
+        obj["not_future"] = Value::Bool(true);
+    }
+    if !f.target_types.is_empty() {
+        obj["target_types"] = json!(f.target_types);
+    }
     obj
 }
 
@@ -853,12 +1557,25 @@ fn section_to_json(s: &md_db::schema::SectionDef) -> Value {
         let children: Vec<Value> = s.children.iter().map(|c| section_to_json(c)).collect();
         obj["children"] = json!(children);
     }
+    if !s.section_patterns.is_empty() {
+        obj["patterns"] = json!(s.section_patterns.iter().map(section_pattern_to_json).collect::<Vec<_>>());
+    }
     obj
 }
 
+fn section_pattern_to_json(p: &md_db::schema::SectionPatternDef) -> Value {
+    json!({
+        "pattern": p.pattern,
+        "min": p.min,
+        "max": p.max,
+        "description": p.section.description,
+    })
+}
+
 fn type_to_json(type_def: &md_db::schema::TypeDef) -> Value {
     let fields: Vec<Value> = type_def.fields.iter().map(|f| field_to_json(f)).collect();
     let sections: Vec<Value> = type_def.sections.iter().map(|s| section_to_json(s)).collect();
+    let patterns: Vec<Value> = type_def.section_patterns.iter().map(section_pattern_to_json).collect();
     json!({
         "name": type_def.name,
         "description": type_def.description,
@@ -866,6 +1583,7 @@ fn type_to_json(type_def: &md_db::schema::TypeDef) -> Value {
         "max_count": type_def.max_count,
         "fields": fields,
         "sections": sections,
+        "section_patterns": patterns,
     })
 }
 
@@ -888,6 +1606,7 @@ fn relations_to_json(schema: &Schema) -> Value {
                 },
                 "description": r.description,
                 "acyclic": r.acyclic,
+                "target_types": r.target_types,
             })
         })
         .collect();
@@ -904,15 +1623,148 @@ fn parse_cell_spec(spec: &str) -> Result<(String, usize), String> {
     Ok((col, row))
 }
 
+// ── Sessions ────────────────────────────────────────────────────────────────
+
+/// Protocol versions this server speaks, oldest first.
+const PROTOCOL_VERSIONS: &[&str] = &["2024-11-05", "2025-03-26"];
+
+/// What one client has set up: the stdio connection, or an HTTP session.
+#[derive(Debug, Clone, Default)]
+struct Session {
+    initialized: bool,
+    /// Working directory: relative path arguments resolve against it, and it
+    /// is the default `dir`.
+    dir: Option<PathBuf>,
+    /// Default `schema` and `users` for tools that take them.
+    schema: Option<PathBuf>,
+    users: Option<PathBuf>,
+    /// Token user that opened an HTTP session; only they can use it.
+    user: Option<String>,
+}
+
+impl Session {
+    /// Tool arguments with this session's directory and defaults applied.
+    fn apply(&self, name: &str, args: &Value) -> Value {
+        let mut args = args.clone();
+        let Some(obj) = args.as_object_mut() else {
+            return args;
+        };
+        if let Some(ref dir) = self.dir {
+            for key in PATH_ARGS {
+                let joined = obj.get(*key).and_then(Value::as_str).map(Path::new).filter(|p| p.is_relative());
+                if let Some(path) = joined.map(|p| dir.join(p)) {
+                    obj.insert(key.to_string(), json!(path.display().to_string()));
+                }
+            }
+        }
+        let view = name.starts_with(VIEW_TOOL_PREFIX);
+        for (key, default) in [("dir", &self.dir), ("schema", &self.schema), ("users", &self.users)] {
+            if let Some(default) = default.as_ref().filter(|_| tool_accepts(name, key) || (view && key == "dir")) {
+                obj.entry(key).or_insert_with(|| json!(default.display().to_string()));
+            }
+        }
+        args
+    }
+}
+
+/// The response to one JSON-RPC message, or `None` for a notification (or a
+/// client's response), which gets none.
+fn handle_message(config: &McpConfig, views: &[ViewDef], session: &mut Session, request: &Value) -> Option<Value> {
+    let id = request.get("id").filter(|_| request.get("method").is_some()).cloned()?;
+    let method = request
+        .get("method")
+        .and_then(|m| m.as_str())
+        .unwrap_or("");
+    let params = request.get("params").cloned().unwrap_or(json!({}));
+
+    let response = match method {
+        "initialize" => {
+            session.initialized = true;
+            // The client's version if we speak it, else our latest
+            let requested = params.get("protocolVersion").and_then(Value::as_str);
+            let version = requested
+                .filter(|v| PROTOCOL_VERSIONS.contains(v))
+                .unwrap_or(PROTOCOL_VERSIONS[PROTOCOL_VERSIONS.len() - 1]);
+            jsonrpc_ok(
+                &id,
+                json!({
+                    "protocolVersion": version,
+                    "capabilities": {
+                        "tools": { "listChanged": false }
+                    },
+                    "serverInfo": {
+                        "name": "md-db",
+                        "version": env!("CARGO_PKG_VERSION"),
+                    }
+                }),
+            )
+        }
+        "tools/list" => {
+            if !session.initialized {
+                jsonrpc_error(&id, -32600, "not initialized")
+            } else {
+                let tools: Vec<Value> = tool_list()
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .cloned()
+                    .chain(view_tools(views))
+                    .filter(|t| t["name"].as_str().is_some_and(|name| config.tool_allowed(name)))
+                    .collect();
+                jsonrpc_ok(&id, json!({ "tools": tools }))
+            }
+        }
+        "tools/call" => {
+            if !session.initialized {
+                jsonrpc_error(&id, -32600, "not initialized")
+            } else {
+                let tool_name = params
+                    .get("name")
+                    .and_then(|n| n.as_str())
+                    .unwrap_or("");
+                let tool_args = session.apply(tool_name, &params.get("arguments").cloned().unwrap_or(json!({})));
+
+                match guarded_call(config, views, tool_name, &tool_args) {
+                    Ok(text) => {
+                        jsonrpc_ok(
+                            &id,
+                            json!({
+                                "content": text_content(&text),
+                                "isError": false,
+                            }),
+                        )
+                    }
+                    Err(e) => jsonrpc_ok(
+                        &id,
+                        json!({
+                            "content": text_content(&e),
+                            "isError": true,
+                        }),
+                    ),
+                }
+            }
+        }
+        "ping" => jsonrpc_ok(&id, json!({})),
+        _ => jsonrpc_error(&id, -32601, &format!("unknown method: {method}")),
+    };
+    Some(response)
+}
+
 // ── Main loop ───────────────────────────────────────────────────────────────
 
-pub fn run() -> Result<(), Box<dyn std::error::Error>> {
+pub fn run(args: &McpArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let config = load_config(args)?;
+    let views = load_views(&config)?;
+    if let Some(ref addr) = args.http {
+        let tokens = args.tokens.as_ref().map(ApiTokens::from_file).transpose()?;
+        return run_http(addr, config, views, tokens);
+    }
     let stdin = io::stdin();
     let stdout = io::stdout();
     let mut reader = stdin.lock();
     let mut writer = stdout.lock();
 
-    let mut initialized = false;
+    let mut session = Session::default();
 
     loop {
         let mut line = String::new();
@@ -926,90 +1778,364 @@ pub fn run() -> Result<(), Box<dyn std::error::Error>> {
             continue;
         }
 
-        let request: Value = match serde_json::from_str(line) {
-            Ok(v) => v,
-            Err(e) => {
-                let resp = jsonrpc_error(&Value::Null, -32700, &format!("parse error: {e}"));
-                writeln!(writer, "{}", resp)?;
-                writer.flush()?;
-                continue;
-            }
+        let response = match serde_json::from_str(line) {
+            Ok(request) => handle_message(&config, &views, &mut session, &request),
+            Err(e) => Some(jsonrpc_error(&Value::Null, -32700, &format!("parse error: {e}"))),
         };
+        if let Some(response) = response {
+            writeln!(writer, "{}", response)?;
+            writer.flush()?;
+        }
+    }
 
-        let id = request.get("id").cloned().unwrap_or(Value::Null);
-        let method = request
-            .get("method")
-            .and_then(|m| m.as_str())
-            .unwrap_or("");
-        let params = request.get("params").cloned().unwrap_or(json!({}));
-
-        let response = match method {
-            "initialize" => {
-                initialized = true;
-                jsonrpc_ok(
-                    &id,
-                    json!({
-                        "protocolVersion": "2024-11-05",
-                        "capabilities": {
-                            "tools": { "listChanged": false }
-                        },
-                        "serverInfo": {
-                            "name": "md-db",
-                            "version": env!("CARGO_PKG_VERSION"),
-                        }
-                    }),
-                )
-            }
-            "notifications/initialized" => {
-                // Client acknowledgement — no response needed for notifications
-                continue;
-            }
-            "tools/list" => {
-                if !initialized {
-                    jsonrpc_error(&id, -32600, "not initialized")
-                } else {
-                    jsonrpc_ok(&id, json!({ "tools": tool_list() }))
+    Ok(())
+}
+
+// ── Streamable HTTP transport ───────────────────────────────────────────────
+
+/// Path of the MCP endpoint.
+const HTTP_ENDPOINT: &str = "/mcp";
+
+/// Header carrying the session ID.
+const SESSION_HEADER: &str = "mcp-session-id";
+
+/// State shared by every HTTP connection.
+struct HttpServer {
+    config: McpConfig,
+    views: Vec<ViewDef>,
+    /// Open sessions by ID; each is set up by its `initialize` and ended by `DELETE`.
+    sessions: Mutex<HashMap<String, Session>>,
+    /// Set when every request must carry a bearer token.
+    tokens: Option<ApiTokens>,
+}
+
+/// Serve the Streamable HTTP transport at `addr`, each connection on its own
+/// thread. Without tokens any client that can connect may call every tool (a
+/// capability root limits paths, not callers), so only a loopback address is allowed.
+fn run_http(
+    addr: &str,
+    config: McpConfig,
+    views: Vec<ViewDef>,
+    tokens: Option<ApiTokens>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let listener = TcpListener::bind(addr)?;
+    let local = listener.local_addr()?;
+    if !local.ip().is_loopback() && tokens.is_none() {
+        return Err(format!("refusing to serve {local} without --tokens; bind a loopback address or pass --tokens").into());
+    }
+    let mode = if tokens.is_some() { "token auth" } else { "no auth" };
+    eprintln!("MCP server ({mode}) at http://{local}{HTTP_ENDPOINT}");
+    let server = HttpServer {
+        config,
+        views,
+        sessions: Mutex::new(HashMap::new()),
+        tokens,
+    };
+    serve::accept_loop(&listener, server, respond_http);
+    Ok(())
+}
+
+/// An HTTP response: status, extra header lines, content type, and body.
+struct HttpResponse {
+    status: u16,
+    headers: String,
+    content_type: &'static str,
+    body: String,
+}
+
+impl HttpResponse {
+    fn json(status: u16, body: &Value) -> Self {
+        Self {
+            status,
+            headers: String::new(),
+            content_type: "application/json",
+            body: body.to_string(),
+        }
+    }
+
+    /// A status with a JSON-RPC error body, for requests rejected before any message is handled.
+    fn error(status: u16, code: i64, message: &str) -> Self {
+        Self::json(status, &jsonrpc_error(&Value::Null, code, message))
+    }
+}
+
+fn respond_http(mut stream: TcpStream, server: &HttpServer) -> io::Result<()> {
+    let local = stream.local_addr().ok().map(|a| a.ip());
+    let response = match serve::read_request(&stream) {
+        Ok(request) => handle_http(server, &request, local),
+        Err(problem) => HttpResponse::error(400, -32700, &problem.detail),
+    };
+    write!(
+        stream,
+        "HTTP/1.1 {} {}\r\nContent-Type: {}\r\nContent-Length: {}\r\n{}\
+         Cache-Control: no-store\r\nConnection: close\r\n\r\n",
+        response.status,
+        problem::reason(response.status),
+        response.content_type,
+        response.body.len(),
+        response.headers
+    )?;
+    stream.write_all(response.body.as_bytes())
+}
+
+/// `local` is the address the connection came in on.
+fn handle_http(server: &HttpServer, request: &serve::Request, local: Option<IpAddr>) -> HttpResponse {
+    if request.path.trim_end_matches('/') != HTTP_ENDPOINT {
+        let message = format!("no route {} (the endpoint is {HTTP_ENDPOINT})", request.path);
+        return HttpResponse::error(404, -32600, &message);
+    }
+    if !host_allowed(&server.config, request.header("host"), local) {
+        return HttpResponse::error(403, -32600, "requests for other hosts are refused");
+    }
+    if !origin_allowed(&server.config, request.header("origin")) {
+        return HttpResponse::error(403, -32600, "requests from other origins are refused");
+    }
+    let user = match server.tokens {
+        Some(ref tokens) => match tokens.authenticate(request.authorization.as_deref()) {
+            Some(user) => Some(user),
+            None => {
+                return HttpResponse {
+                    headers: "WWW-Authenticate: Bearer\r\n".to_string(),
+                    ..HttpResponse::error(401, -32600, "a valid `Authorization: Bearer` token is required")
                 }
             }
-            "tools/call" => {
-                if !initialized {
-                    jsonrpc_error(&id, -32600, "not initialized")
-                } else {
-                    let tool_name = params
-                        .get("name")
-                        .and_then(|n| n.as_str())
-                        .unwrap_or("");
-                    let tool_args = params.get("arguments").cloned().unwrap_or(json!({}));
-
-                    match handle_tool_call(tool_name, &tool_args) {
-                        Ok(result) => {
-                            let text = serde_json::to_string_pretty(&result)
-                                .unwrap_or_else(|_| result.to_string());
-                            jsonrpc_ok(
-                                &id,
-                                json!({
-                                    "content": text_content(&text),
-                                    "isError": false,
-                                }),
-                            )
-                        }
-                        Err(e) => jsonrpc_ok(
-                            &id,
-                            json!({
-                                "content": text_content(&e),
-                                "isError": true,
-                            }),
-                        ),
-                    }
-                }
+        },
+        None => None,
+    };
+    let session_id = request.header(SESSION_HEADER);
+    match request.method.as_str() {
+        "POST" => post_messages(server, request, session_id, user),
+        "DELETE" => {
+            let removed = session_id.and_then(|id| {
+                let mut sessions = server.sessions.lock().ok()?;
+                sessions.get(id).filter(|s| s.user.as_deref() == user)?;
+                sessions.remove(id)
+            });
+            match removed {
+                Some(_) => HttpResponse::json(200, &json!({})),
+                None => HttpResponse::error(404, -32600, "unknown session"),
             }
-            "ping" => jsonrpc_ok(&id, json!({})),
-            _ => jsonrpc_error(&id, -32601, &format!("unknown method: {method}")),
+        }
+        // No server-initiated messages, so no stream to open with GET
+        _ => HttpResponse {
+            headers: "Allow: POST, DELETE\r\n".to_string(),
+            ..HttpResponse::error(405, -32600, &format!("{HTTP_ENDPOINT} only accepts POST and DELETE"))
+        },
+    }
+}
+
+/// `POST /mcp`: one JSON-RPC message or a batch. An `initialize` opens a new
+/// session, configured by the query (`dir`, `schema`, `users`); every other
+/// request names its session in the `Mcp-Session-Id` header, and must come
+/// from the user who opened it.
+fn post_messages(
+    server: &HttpServer,
+    request: &serve::Request,
+    session_id: Option<&str>,
+    user: Option<&str>,
+) -> HttpResponse {
+    let body: Value = match serde_json::from_slice(&request.body) {
+        Ok(body) => body,
+        Err(e) => return HttpResponse::error(400, -32700, &format!("parse error: {e}")),
+    };
+    let batch = body.is_array();
+    let messages = match body {
+        Value::Array(messages) => messages,
+        message => vec![message],
+    };
+
+    let initialize = messages.iter().any(|m| m["method"] == "initialize");
+    let (mut session, session_id) = if initialize {
+        match new_session(&server.config, &request.query, user) {
+            Ok(session) => (session, new_session_id()),
+            Err(e) => return HttpResponse::error(400, -32602, &e),
+        }
+    } else {
+        let Some(id) = session_id else {
+            return HttpResponse::error(400, -32600, "missing Mcp-Session-Id header (send initialize first)");
         };
+        let session = server.sessions.lock().ok().and_then(|s| s.get(id).cloned());
+        match session.filter(|s| s.user.as_deref() == user) {
+            Some(session) => (session, id.to_string()),
+            None => return HttpResponse::error(404, -32600, "unknown session (initialize a new one)"),
+        }
+    };
+
+    let responses: Vec<Value> = messages
+        .iter()
+        .filter_map(|message| handle_message(&server.config, &server.views, &mut session, message))
+        .collect();
+    let mut headers = String::new();
+    if initialize && session.initialized {
+        headers = format!("Mcp-Session-Id: {session_id}\r\n");
+        if let Ok(mut sessions) = server.sessions.lock() {
+            sessions.insert(session_id, session);
+        }
+    }
 
-        writeln!(writer, "{}", response)?;
-        writer.flush()?;
+    if responses.is_empty() {
+        return HttpResponse {
+            status: 202,
+            headers,
+            content_type: "application/json",
+            body: String::new(),
+        };
+    }
+    // JSON unless the client only takes an event stream
+    let accept = request.accept.as_deref().unwrap_or("application/json");
+    if accept.contains("text/event-stream") && !accept.contains("application/json") {
+        let body = responses.iter().map(|r| format!("event: message\ndata: {r}\n\n")).collect();
+        return HttpResponse {
+            status: 200,
+            headers,
+            content_type: "text/event-stream",
+            body,
+        };
+    }
+    let body = if batch { Value::Array(responses) } else { responses.into_iter().next().unwrap_or_default() };
+    HttpResponse {
+        headers,
+        ..HttpResponse::json(200, &body)
     }
+}
 
-    Ok(())
+/// A session from the `initialize` request's query. Its paths are relative to
+/// the server's working directory and must be inside the capability root.
+fn new_session(config: &McpConfig, query: &[(String, String)], user: Option<&str>) -> Result<Session, String> {
+    let param = |key: &str| query.iter().find(|(k, _)| k == key).map(|(_, v)| PathBuf::from(v));
+    let dir = param("dir");
+    if let Some(dir) = dir.as_ref().filter(|d| !d.is_dir()) {
+        return Err(format!("{} is not a directory", dir.display()));
+    }
+    // Without a schema, the closest one above the directory
+    let schema = param("schema").or_else(|| dir.as_deref().and_then(|d| md_db::project::find_schema(d).ok()));
+    let users = param("users");
+    for path in dir.iter().chain(&schema).chain(&users) {
+        config.check_path(path).map_err(|e| e.to_string())?;
+    }
+    Ok(Session {
+        initialized: false,
+        dir,
+        schema,
+        users,
+        user: user.map(str::to_string),
+    })
+}
+
+/// A fresh session ID: 32 hex digits from the standard library's randomly
+/// seeded hasher. It tells sessions apart; requests are authenticated by their
+/// token, and a session only answers the user who opened it.
+fn new_session_id() -> String {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+    let nanos = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_nanos();
+    (0..2)
+        .map(|_| {
+            let mut hasher = RandomState::new().build_hasher();
+            hasher.write_u64(COUNTER.fetch_add(1, Ordering::Relaxed));
+            hasher.write_u128(nanos);
+            format!("{:016x}", hasher.finish())
+        })
+        .collect()
+}
+
+/// A page that reaches the server by DNS rebinding sends its own name as `Host`,
+/// so only loopback names, the address the connection came in on, and the
+/// config's `allow-host` names are accepted.
+fn host_allowed(config: &McpConfig, host: Option<&str>, local: Option<IpAddr>) -> bool {
+    let Some(host) = host else {
+        return false;
+    };
+    let name = host_name(host);
+    is_loopback_name(name)
+        || local.is_some_and(|ip| parse_ip(name) == Some(ip))
+        || config.allow_hosts.iter().any(|h| h.eq_ignore_ascii_case(name))
+}
+
+/// Browsers send `Origin`; only pages from a loopback address or an
+/// `allow-origin` of the config are let through.
+fn origin_allowed(config: &McpConfig, origin: Option<&str>) -> bool {
+    let Some(origin) = origin else {
+        return true;
+    };
+    let host = origin.split_once("://").map_or(origin, |(_, host)| host);
+    is_loopback_name(host_name(host)) || config.allow_origins.iter().any(|o| o.eq_ignore_ascii_case(origin))
+}
+
+/// `host[:port]` without the port; IPv6 literals keep their brackets.
+fn host_name(host: &str) -> &str {
+    match host.find(']') {
+        Some(end) => &host[..=end],
+        None => host.split(':').next().unwrap_or(host),
+    }
+}
+
+fn parse_ip(name: &str) -> Option<IpAddr> {
+    name.trim_start_matches('[').trim_end_matches(']').parse().ok()
+}
+
+fn is_loopback_name(name: &str) -> bool {
+    name.eq_ignore_ascii_case("localhost") || parse_ip(name).is_some_and(|ip| ip.is_loopback())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_session() {
+        let config = McpConfig::default();
+        let mut session = Session {
+            dir: Some(PathBuf::from("docs")),
+            schema: Some(PathBuf::from("schema.kdl")),
+            ..Session::default()
+        };
+        let list = json!({ "jsonrpc": "2.0", "id": 2, "method": "tools/list" });
+        let response = handle_message(&config, &[], &mut session, &list).unwrap();
+        assert_eq!(response["error"]["message"], "not initialized");
+
+        let init = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "initialize",
+            "params": { "protocolVersion": "2024-11-05" },
+        });
+        let response = handle_message(&config, &[], &mut session, &init).unwrap();
+        assert_eq!(response["result"]["protocolVersion"], "2024-11-05");
+        let notification = json!({ "jsonrpc": "2.0", "method": "notifications/initialized" });
+        assert!(handle_message(&config, &[], &mut session, &notification).is_none());
+        let response = handle_message(&config, &[], &mut session, &list).unwrap();
+        assert!(response["result"]["tools"].as_array().is_some_and(|t| !t.is_empty()));
+
+        // Relative paths resolve against the session directory; missing ones get its defaults
+        let args = session.apply("md-db-inspect", &json!({ "file": "adr-001.md", "users": "/etc/users.yaml" }));
+        let file = Path::new("docs").join("adr-001.md");
+        assert_eq!(args["file"], file.display().to_string());
+        assert_eq!(args["schema"], "schema.kdl");
+        assert_eq!(args["users"], "/etc/users.yaml");
+        assert!(args.get("dir").is_none());
+        assert_eq!(session.apply("md-db-list", &json!({}))["dir"], "docs");
+    }
+
+    #[test]
+    fn test_host_and_origin() {
+        let config = McpConfig {
+            allow_origins: vec!["https://agents.example.com".into()],
+            allow_hosts: vec!["mcp.example.com".into()],
+            ..McpConfig::default()
+        };
+        let local = "10.0.0.5".parse().ok();
+        assert!(host_allowed(&config, Some("localhost:8808"), local));
+        assert!(host_allowed(&config, Some("[::1]:8808"), local));
+        assert!(host_allowed(&config, Some("10.0.0.5:8808"), local));
+        assert!(host_allowed(&config, Some("MCP.example.com"), local));
+        // A rebinding page names its own host
+        assert!(!host_allowed(&config, Some("evil.example:8808"), local));
+        assert!(!host_allowed(&config, None, local));
+
+        assert!(origin_allowed(&config, None));
+        assert!(origin_allowed(&config, Some("http://127.0.0.1:3000")));
+        assert!(origin_allowed(&config, Some("https://agents.example.com")));
+        assert!(!origin_allowed(&config, Some("http://10.0.0.5:8808")));
+        assert!(!origin_allowed(&config, Some("https://evil.example")));
+    }
 }
@@ -0,0 +1,99 @@
+use std::path::PathBuf;
+
+use clap::Args;
+use md_db::graph::path_to_id;
+use md_db::merge::{self, MergeOptions};
+use md_db::schema::Schema;
+use md_db::undo::Recorder;
+
+#[derive(Debug, Args)]
+pub struct MergeArgs {
+    /// The two documents to merge
+    #[arg(num_args = 2, required = true)]
+    pub files: Vec<PathBuf>,
+
+    /// Document that survives, by path or ID (defaults to the first); the other is merged into it
+    #[arg(long)]
+    pub into: Option<String>,
+
+    /// Append a section to a differently named one (repeatable): "FROM HEADING=INTO HEADING"
+    #[arg(long)]
+    pub map: Vec<String>,
+
+    /// Add every section at the end instead of appending to sections with the same heading
+    #[arg(long)]
+    pub concat: bool,
+
+    /// Directory whose references to the merged-away document are updated
+    #[arg(long, default_value = ".")]
+    pub dir: PathBuf,
+
+    /// Path to the KDL schema file, or `auto` to use the closest one above the files
+    #[arg(long, default_value = "auto")]
+    pub schema: PathBuf,
+
+    /// Show what would change without writing
+    #[arg(long)]
+    pub dry_run: bool,
+}
+
+pub fn run(args: &MergeArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let (into_path, from_path) = match args.into {
+        None => (&args.files[0], &args.files[1]),
+        Some(ref into) => {
+            let is = |path: &PathBuf| path.as_os_str() == into.as_str() || path_to_id(path).eq_ignore_ascii_case(into);
+            match (is(&args.files[0]), is(&args.files[1])) {
+                (true, false) => (&args.files[0], &args.files[1]),
+                (false, true) => (&args.files[1], &args.files[0]),
+                _ => return Err(format!("--into {into} must name exactly one of the two documents").into()),
+            }
+        }
+    };
+    let sections: Vec<(String, String)> = args
+        .map
+        .iter()
+        .map(|m| match m.split_once('=') {
+            Some((from, into)) => Ok((from.trim().to_string(), into.trim().to_string())),
+            None => Err(format!("invalid --map (expected FROM=INTO): {m}")),
+        })
+        .collect::<Result<_, _>>()?;
+
+    let schema = Schema::from_file(md_db::project::resolve_schema(&args.schema, into_path)?)?;
+    let opts = MergeOptions {
+        sections: &sections,
+        concat: args.concat,
+    };
+    let merge = merge::plan_merge(into_path, from_path, &args.dir, &schema, &opts)?;
+
+    for conflict in &merge.conflicts {
+        eprintln!("warning: {conflict}");
+    }
+    if args.dry_run {
+        eprintln!("  would update: {} ({})", from_path.display(), merge.from_id);
+        for doc in &merge.referrers {
+            eprintln!("  would update: {}", doc.path.as_deref().unwrap_or(from_path).display());
+        }
+        print!("{}", merge.into.raw);
+        return Ok(());
+    }
+
+    let mut undo = Recorder::new("merge");
+    let docs: Vec<_> = [&merge.into, &merge.from].into_iter().chain(&merge.referrers).collect();
+    for doc in &docs {
+        if let Some(ref path) = doc.path {
+            undo.track(path);
+        }
+    }
+    let result = docs.iter().try_for_each(|doc| doc.save());
+    undo.finish()?;
+    result?;
+
+    eprintln!(
+        "merge {} -> {}: {} referencing file(s) updated, {} left as a superseded stub",
+        merge.from_id,
+        merge.into_id,
+        merge.referrers.len(),
+        from_path.display()
+    );
+    Ok(())
+}
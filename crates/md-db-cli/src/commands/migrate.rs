@@ -3,6 +3,7 @@ use std::path::PathBuf;
 use clap::Args;
 use md_db::migrate;
 use md_db::schema::Schema;
+use md_db::undo::Recorder;
 
 #[derive(Debug, Args)]
 pub struct MigrateArgs {
@@ -51,7 +52,10 @@ pub fn run(args: &MigrateArgs) -> Result<(), Box<dyn std::error::Error>> {
                 println!();
                 print!("{plan}");
                 if !args.dry_run && !plan.actions.is_empty() {
+                    let mut undo = Recorder::new("migrate");
+                    undo.track_all(plan.actions.iter().flat_map(|a| &a.affected_docs));
                     let result = migrate::apply_migration(&plan)?;
+                    undo.finish()?;
                     println!();
                     println!("{result}");
                 }
@@ -70,108 +74,12 @@ fn print_json(
 ) -> Result<(), Box<dyn std::error::Error>> {
     let mut obj = serde_json::Map::new();
 
-    // Diff section
-    let mut diff_obj = serde_json::Map::new();
-    diff_obj.insert(
-        "added_types".into(),
-        serde_json::Value::Array(
-            diff.added_types
-                .iter()
-                .map(|s| serde_json::Value::String(s.clone()))
-                .collect(),
-        ),
-    );
-    diff_obj.insert(
-        "removed_types".into(),
-        serde_json::Value::Array(
-            diff.removed_types
-                .iter()
-                .map(|s| serde_json::Value::String(s.clone()))
-                .collect(),
-        ),
-    );
-
-    let type_changes: Vec<serde_json::Value> = diff
-        .type_changes
-        .iter()
-        .map(|tc| {
-            serde_json::json!({
-                "type": tc.type_name,
-                "added_fields": tc.added_fields.iter().map(|f| &f.name).collect::<Vec<_>>(),
-                "removed_fields": tc.removed_fields.iter().map(|f| &f.name).collect::<Vec<_>>(),
-                "changed_fields": tc.changed_fields.iter().map(|c| {
-                    serde_json::json!({
-                        "name": c.name,
-                        "removed_enum_values": c.removed_enum_values,
-                        "added_enum_values": c.added_enum_values,
-                    })
-                }).collect::<Vec<_>>(),
-                "added_sections": tc.added_sections,
-                "removed_sections": tc.removed_sections,
-            })
-        })
-        .collect();
-    diff_obj.insert("type_changes".into(), serde_json::Value::Array(type_changes));
-    obj.insert("diff".into(), serde_json::Value::Object(diff_obj));
+    obj.insert("diff".into(), diff.to_json());
 
     // Plan section (if dir provided)
     if let Some(ref dir) = args.dir {
         let plan = migrate::compute_migration(diff, dir);
-        let actions: Vec<serde_json::Value> = plan
-            .actions
-            .iter()
-            .map(|a| {
-                let kind = match &a.kind {
-                    migrate::ActionKind::AddField {
-                        type_name,
-                        field_name,
-                        default_value,
-                    } => serde_json::json!({
-                        "action": "add_field",
-                        "type": type_name,
-                        "field": field_name,
-                        "default": default_value,
-                    }),
-                    migrate::ActionKind::RemoveField {
-                        type_name,
-                        field_name,
-                    } => serde_json::json!({
-                        "action": "remove_field",
-                        "type": type_name,
-                        "field": field_name,
-                    }),
-                    migrate::ActionKind::RemovedEnumValue {
-                        type_name,
-                        field_name,
-                        value,
-                    } => serde_json::json!({
-                        "action": "removed_enum_value",
-                        "type": type_name,
-                        "field": field_name,
-                        "value": value,
-                    }),
-                    migrate::ActionKind::AddSection {
-                        type_name,
-                        section_name,
-                    } => serde_json::json!({
-                        "action": "add_section",
-                        "type": type_name,
-                        "section": section_name,
-                    }),
-                };
-                let docs: Vec<String> = a
-                    .affected_docs
-                    .iter()
-                    .map(|p| p.display().to_string())
-                    .collect();
-                serde_json::json!({
-                    "kind": kind,
-                    "affected_docs": docs,
-                    "count": a.affected_docs.len(),
-                })
-            })
-            .collect();
-        obj.insert("plan".into(), serde_json::Value::Array(actions));
+        obj.insert("plan".into(), plan.to_json());
         obj.insert("dry_run".into(), serde_json::Value::Bool(args.dry_run));
     }
 
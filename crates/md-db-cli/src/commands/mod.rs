@@ -1,104 +1,386 @@
+//! Every CLI command, one module each. The default `full` feature builds all of
+//! them; without it (`--no-default-features`) only the schema-less readers are
+//! compiled: `get`, `list`, `search`, and `fm`. Both builds share the same
+//! argument structs and output formatting, so the thin binary can't drift.
+
 use clap::Subcommand;
 
+#[cfg(feature = "full")]
+pub mod assets;
+#[cfg(feature = "full")]
+pub mod badges;
+#[cfg(feature = "full")]
 pub mod batch;
+#[cfg(feature = "full")]
+pub mod blame;
+#[cfg(feature = "full")]
+pub mod cache;
+#[cfg(feature = "full")]
+pub mod changelog;
+#[cfg(feature = "full")]
+pub mod conflicts;
+#[cfg(feature = "full")]
+pub mod context;
+#[cfg(feature = "full")]
 pub mod deprecate;
+#[cfg(feature = "full")]
 pub mod diff;
+#[cfg(feature = "full")]
 pub mod describe;
+#[cfg(feature = "full")]
 pub mod export;
+#[cfg(feature = "full")]
+pub mod field_history;
+#[cfg(feature = "full")]
 pub mod fix;
+#[cfg(feature = "full")]
+pub mod fixture;
+pub mod fm;
+#[cfg(feature = "full")]
+pub mod fmt;
 pub mod get;
+#[cfg(feature = "full")]
 pub mod graph;
+#[cfg(feature = "full")]
 pub mod hook;
+#[cfg(feature = "full")]
+pub mod import;
+#[cfg(feature = "full")]
 pub mod init;
+#[cfg(feature = "full")]
 pub mod inspect;
+#[cfg(feature = "full")]
+pub mod lineage;
+#[cfg(feature = "full")]
+pub mod lint;
 pub mod list;
+#[cfg(feature = "full")]
+pub mod lsp;
+#[cfg(feature = "full")]
 pub mod mcp;
+#[cfg(feature = "full")]
+pub mod merge;
+#[cfg(feature = "full")]
 pub mod migrate;
+#[cfg(feature = "full")]
 pub mod new;
+#[cfg(feature = "full")]
+pub mod outline;
+#[cfg(feature = "full")]
+pub mod owners;
+#[cfg(feature = "full")]
+pub mod project;
+#[cfg(feature = "full")]
+pub mod reassign;
+#[cfg(feature = "full")]
 pub mod refs;
+#[cfg(feature = "full")]
 pub mod rename;
+#[cfg(feature = "full")]
+pub mod report;
+#[cfg(feature = "full")]
+pub mod schema;
 pub mod search;
+#[cfg(feature = "full")]
+pub mod selftest;
+#[cfg(feature = "full")]
+pub mod serve;
+#[cfg(feature = "full")]
 pub mod set;
+#[cfg(feature = "full")]
+pub mod split;
+#[cfg(feature = "full")]
 pub mod stats;
+#[cfg(feature = "full")]
 pub mod sync;
+#[cfg(feature = "full")]
+pub mod tables;
+#[cfg(feature = "full")]
+pub mod triage;
+#[cfg(feature = "full")]
+pub mod uid;
+#[cfg(feature = "full")]
+pub mod undo;
+#[cfg(feature = "full")]
 pub mod validate;
+#[cfg(feature = "full")]
 pub mod watch;
 
 #[derive(Debug, Subcommand)]
 pub enum Commands {
+    #[cfg(feature = "full")]
+    /// Store attachments content-addressed and garbage-collect unreferenced ones
+    Assets(assets::AssetsArgs),
+    #[cfg(feature = "full")]
+    /// Print project badges or keep the `<!-- md-db:badges -->` block in a README current
+    Badges(badges::BadgesArgs),
+    #[cfg(feature = "full")]
     /// Apply field mutations to all docs matching a filter
     Batch(batch::BatchArgs),
+    #[cfg(feature = "full")]
+    /// Show the commit, author, and date that last changed each frontmatter field
+    Blame(blame::BlameArgs),
+    #[cfg(feature = "full")]
+    /// Show or clear the .md-db/ caches (graph snapshot); they rebuild on next use
+    Cache(cache::CacheArgs),
+    #[cfg(feature = "full")]
+    /// Add entries to a Keep-a-Changelog CHANGELOG.md and cut releases
+    Changelog(changelog::ChangelogArgs),
+    #[cfg(feature = "full")]
+    /// Report semantic merge conflicts between two branches (duplicate IDs, status clashes)
+    Conflicts(conflicts::ConflictsArgs),
+    #[cfg(feature = "full")]
+    /// Print a token-budgeted context pack (doc, key sections, related summaries, schema) for agents
+    Context(context::ContextArgs),
+    #[cfg(feature = "full")]
     /// Deprecate a document (set status, optionally mark superseded)
     Deprecate(deprecate::DeprecateArgs),
+    #[cfg(feature = "full")]
     /// Show structural diff between two versions of a document
     Diff(diff::DiffArgs),
+    #[cfg(feature = "full")]
     /// Describe schema types, fields, sections, and relations
     Describe(describe::DescribeArgs),
-    /// Export documents to a static HTML site
+    #[cfg(feature = "full")]
+    /// Export documents to a static HTML site or a SQLite mirror
     Export(export::ExportArgs),
+    #[cfg(feature = "full")]
+    /// Show every value a frontmatter field took across git history, with dates and authors
+    FieldHistory(field_history::FieldHistoryArgs),
+    #[cfg(feature = "full")]
     /// Auto-fix common validation errors
     Fix(fix::FixArgs),
+    #[cfg(feature = "full")]
+    /// Generate a valid fixture corpus from the schema, plus invalid variants per diagnostic code
+    Fixture(fixture::FixtureArgs),
+    /// Read or edit the frontmatter of any markdown file, no schema needed (get, set, add, strip)
+    Fm(fm::FmArgs),
+    #[cfg(feature = "full")]
+    /// Format managed documents canonically: frontmatter key order, quoting, tables, headings, whitespace
+    Fmt(fmt::FmtArgs),
     /// Read fields, sections, or table cells from a markdown file
     Get(get::GetArgs),
+    #[cfg(feature = "full")]
     /// Export the document link graph as mermaid, DOT, or JSON
     Graph(graph::GraphArgs),
+    #[cfg(feature = "full")]
     /// Install or uninstall a git pre-commit hook
     Hook(hook::HookArgs),
+    #[cfg(feature = "full")]
+    /// Create documents in bulk from a CSV file, one per row, numbered from the next free ID
+    Import(import::ImportArgs),
+    #[cfg(feature = "full")]
     /// Scaffold a new md-db project with schema.kdl and directory structure
     Init(init::InitArgs),
+    #[cfg(feature = "full")]
     /// Inspect a document: frontmatter + sections + validation in one call
     Inspect(inspect::InspectArgs),
+    #[cfg(feature = "full")]
+    /// Show a document's supersedes chain with what changed between generations
+    Lineage(lineage::LineageArgs),
+    #[cfg(feature = "full")]
+    /// Check writing style against the schema's lint rules (heading case, line length, empty sections, ...)
+    Lint(lint::LintArgs),
     /// List and filter markdown files by frontmatter
     List(list::ListArgs),
+    #[cfg(feature = "full")]
+    /// Start a Language Server (LSP) over stdio for editor diagnostics, completion, and navigation
+    Lsp(lsp::LspArgs),
+    #[cfg(feature = "full")]
     /// Start MCP (Model Context Protocol) server over stdio
-    Mcp,
+    Mcp(mcp::McpArgs),
+    #[cfg(feature = "full")]
+    /// Merge one document into another: sections, relations, and every reference to it
+    Merge(merge::MergeArgs),
+    #[cfg(feature = "full")]
     /// Detect schema changes and migrate documents
     Migrate(migrate::MigrateArgs),
+    #[cfg(feature = "full")]
     /// Validate markdown files against a KDL schema
     Validate(validate::ValidateArgs),
+    #[cfg(feature = "full")]
     /// Create a new document from a schema type definition
     New(new::NewArgs),
+    #[cfg(feature = "full")]
+    /// Print a document's heading tree with word counts, tables, diagrams, and required-section marks
+    Outline(outline::OutlineArgs),
+    #[cfg(feature = "full")]
+    /// List documents a user owns (user fields) or mentions in prose (--mentions @alice)
+    Owners(owners::OwnersArgs),
+    #[cfg(feature = "full")]
+    /// Generate the schema's projections: documents of tables queried from the corpus (--write, --check)
+    Project(project::ProjectArgs),
+    #[cfg(feature = "full")]
+    /// Hand over ownership: replace a user in user fields and Owner columns (--from @bob --to @alice)
+    Reassign(reassign::ReassignArgs),
+    #[cfg(feature = "full")]
+    /// Re-apply operations reverted by `undo`
+    Redo(undo::UndoArgs),
+    #[cfg(feature = "full")]
     /// Show forward refs or backlinks for a document
     Refs(refs::RefsArgs),
+    #[cfg(feature = "full")]
     /// Rename a document ID and cascade-update all references
     Rename(rename::RenameArgs),
+    #[cfg(feature = "full")]
+    /// Generate a report document: `board` groups documents into one section per status (--group-by)
+    Report(report::ReportArgs),
+    #[cfg(feature = "full")]
+    /// Check the schema's own examples and counter-examples (`schema test`)
+    Schema(schema::SchemaArgs),
     /// Full-text search across document content and frontmatter
     Search(search::SearchArgs),
+    #[cfg(feature = "full")]
+    /// Check the project end to end: schema round-trip, graph determinism, cache, sync and fix idempotency
+    Selftest(selftest::SelftestArgs),
+    #[cfg(feature = "full")]
+    /// Serve documents, refs, the graph, and validation as a read-only JSON HTTP API
+    Serve(serve::ServeArgs),
+    #[cfg(feature = "full")]
     /// Update fields, sections, or table cells in a markdown file
     Set(set::SetArgs),
+    #[cfg(feature = "full")]
+    /// Move a section into a new document of another type, leaving a link and a relation behind
+    Split(split::SplitArgs),
+    #[cfg(feature = "full")]
     /// Show document set health overview (counts, validation, graph stats)
     Stats(stats::StatsArgs),
+    #[cfg(feature = "full")]
     /// Sync bidirectional relations (add missing inverse refs)
     Sync(sync::SyncArgs),
+    #[cfg(feature = "full")]
+    /// Export one table section from every matching document as a single CSV or JSON dataset
+    Tables(tables::TablesArgs),
+    #[cfg(feature = "full")]
+    /// Open or update tracking tickets for findings `fix` can't repair
+    Triage(triage::TriageArgs),
+    #[cfg(feature = "full")]
+    /// Generate stable document uids or backfill them into existing docs
+    Uid(uid::UidArgs),
+    #[cfg(feature = "full")]
+    /// Revert the last md-db operations (file changes made by set, batch, fix, ...)
+    Undo(undo::UndoArgs),
+    #[cfg(feature = "full")]
     /// Watch directory and re-validate on file changes
     Watch(watch::WatchArgs),
 }
 
+impl Commands {
+    /// Commands whose human-readable output can run to many screens go through the pager.
+    pub fn pages_output(&self) -> bool {
+        match self {
+            Commands::List(_) => true,
+            #[cfg(feature = "full")]
+            Commands::Describe(_) | Commands::Validate(_) => true,
+            _ => false,
+        }
+    }
+}
+
 /// Run the given command.
 pub fn run(command: &Commands) -> Result<(), Box<dyn std::error::Error>> {
     match command {
+        #[cfg(feature = "full")]
+        Commands::Assets(args) => assets::run(args),
+        #[cfg(feature = "full")]
+        Commands::Badges(args) => badges::run(args),
+        #[cfg(feature = "full")]
         Commands::Batch(args) => batch::run(args),
+        #[cfg(feature = "full")]
+        Commands::Blame(args) => blame::run(args),
+        #[cfg(feature = "full")]
+        Commands::Cache(args) => cache::run(args),
+        #[cfg(feature = "full")]
+        Commands::Changelog(args) => changelog::run(args),
+        #[cfg(feature = "full")]
+        Commands::Conflicts(args) => conflicts::run(args),
+        #[cfg(feature = "full")]
+        Commands::Context(args) => context::run(args),
+        #[cfg(feature = "full")]
         Commands::Deprecate(args) => deprecate::run(args),
+        #[cfg(feature = "full")]
         Commands::Diff(args) => diff::run(args),
+        #[cfg(feature = "full")]
         Commands::Describe(args) => describe::run(args),
+        #[cfg(feature = "full")]
         Commands::Export(args) => export::run(args),
+        #[cfg(feature = "full")]
+        Commands::FieldHistory(args) => field_history::run(args),
+        #[cfg(feature = "full")]
         Commands::Fix(args) => fix::run(args),
+        #[cfg(feature = "full")]
+        Commands::Fixture(args) => fixture::run(args),
+        Commands::Fm(args) => fm::run(args),
+        #[cfg(feature = "full")]
+        Commands::Fmt(args) => fmt::run(args),
         Commands::Get(args) => get::run(args),
+        #[cfg(feature = "full")]
         Commands::Graph(args) => graph::run(args),
+        #[cfg(feature = "full")]
         Commands::Hook(args) => hook::run(args),
+        #[cfg(feature = "full")]
+        Commands::Import(args) => import::run(args),
+        #[cfg(feature = "full")]
         Commands::Init(args) => init::run(args),
+        #[cfg(feature = "full")]
         Commands::Inspect(args) => inspect::run(args),
+        #[cfg(feature = "full")]
+        Commands::Lineage(args) => lineage::run(args),
+        #[cfg(feature = "full")]
+        Commands::Lint(args) => lint::run(args),
         Commands::List(args) => list::run(args),
-        Commands::Mcp => mcp::run(),
+        #[cfg(feature = "full")]
+        Commands::Lsp(args) => lsp::run(args),
+        #[cfg(feature = "full")]
+        Commands::Mcp(args) => mcp::run(args),
+        #[cfg(feature = "full")]
+        Commands::Merge(args) => merge::run(args),
+        #[cfg(feature = "full")]
         Commands::Migrate(args) => migrate::run(args),
+        #[cfg(feature = "full")]
         Commands::Validate(args) => validate::run(args),
+        #[cfg(feature = "full")]
         Commands::New(args) => new::run(args),
+        #[cfg(feature = "full")]
+        Commands::Outline(args) => outline::run(args),
+        #[cfg(feature = "full")]
+        Commands::Owners(args) => owners::run(args),
+        #[cfg(feature = "full")]
+        Commands::Project(args) => project::run(args),
+        #[cfg(feature = "full")]
+        Commands::Reassign(args) => reassign::run(args),
+        #[cfg(feature = "full")]
+        Commands::Redo(args) => undo::run_redo(args),
+        #[cfg(feature = "full")]
         Commands::Refs(args) => refs::run(args),
+        #[cfg(feature = "full")]
         Commands::Rename(args) => rename::run(args),
+        #[cfg(feature = "full")]
+        Commands::Report(args) => report::run(args),
+        #[cfg(feature = "full")]
+        Commands::Schema(args) => schema::run(args),
         Commands::Search(args) => search::run(args),
+        #[cfg(feature = "full")]
+        Commands::Selftest(args) => selftest::run(args),
+        #[cfg(feature = "full")]
+        Commands::Serve(args) => serve::run(args),
+        #[cfg(feature = "full")]
         Commands::Set(args) => set::run(args),
+        #[cfg(feature = "full")]
+        Commands::Split(args) => split::run(args),
+        #[cfg(feature = "full")]
         Commands::Stats(args) => stats::run(args),
+        #[cfg(feature = "full")]
         Commands::Sync(args) => sync::run(args),
+        #[cfg(feature = "full")]
+        Commands::Tables(args) => tables::run(args),
+        #[cfg(feature = "full")]
+        Commands::Triage(args) => triage::run(args),
+        #[cfg(feature = "full")]
+        Commands::Uid(args) => uid::run(args),
+        #[cfg(feature = "full")]
+        Commands::Undo(args) => undo::run(args),
+        #[cfg(feature = "full")]
         Commands::Watch(args) => watch::run(args),
     }
 }
@@ -1,10 +1,14 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::process::Command;
 
 use clap::Args;
 use md_db::error::Error;
 use md_db::graph::DocGraph;
+use md_db::pipeline::{self, NewStep};
 use md_db::schema::Schema;
 use md_db::template;
+use md_db::uid;
+use md_db::undo::Recorder;
 
 #[derive(Debug, Args)]
 pub struct NewArgs {
@@ -12,8 +16,8 @@ pub struct NewArgs {
     #[arg(long = "type")]
     pub doc_type: String,
 
-    /// Path to the KDL schema file
-    #[arg(long)]
+    /// Path to the KDL schema file, or `auto` to use the closest one above the target
+    #[arg(long, default_value = "auto")]
     pub schema: PathBuf,
 
     /// Output file path (prints to stdout if omitted; use --auto-id to generate path automatically)
@@ -35,42 +39,69 @@ pub struct NewArgs {
     /// Auto-generate output path using next ID + type folder (requires --dir)
     #[arg(long)]
     pub auto_id: bool,
+    /// Don't add a stable `uid` (UUIDv7) to the frontmatter
+    #[arg(long)]
+    pub no_uid: bool,
+    /// Skip the `on-new` steps declared in the project manifest
+    #[arg(long)]
+    pub no_steps: bool,
 }
 
 pub fn run(args: &NewArgs) -> Result<(), Box<dyn std::error::Error>> {
-    let schema = Schema::from_file(&args.schema)?;
+    let target = args.output.as_deref().or(args.dir.as_deref()).unwrap_or(Path::new("."));
+    let schema_path = md_db::project::resolve_schema(&args.schema, target)?;
+    let schema = Schema::from_file(&schema_path)?;
 
     let type_def = schema
         .get_type(&args.doc_type)
         .ok_or(Error::TypeNotFound(args.doc_type.clone()))?;
 
-    let fields: Vec<(String, String)> = args
+    let mut fields: Vec<(String, String)> = args
         .fields
         .iter()
         .map(|s| parse_field_arg(s))
         .collect::<Result<_, _>>()?;
+    if !args.no_uid && !fields.iter().any(|(k, _)| k == uid::UID_FIELD) {
+        fields.push((uid::UID_FIELD.to_string(), uid::generate()));
+    }
 
     // Auto-ID: scan dir, compute next ID, generate output path
+    let mut next_id = None;
     let output_path = if args.auto_id {
         let dir = args.dir.as_ref().ok_or("--auto-id requires --dir")?;
         let graph = DocGraph::build(dir, &schema)?;
-        let next_id = graph.next_id(&args.doc_type);
+        let id = graph.next_id(&args.doc_type);
         let folder = type_def.folder.as_deref().unwrap_or(".");
-        let filename = format!("{}.md", next_id.to_lowercase());
+        let filename = format!("{}.md", id.to_lowercase());
         let path = PathBuf::from(dir).join(folder).join(&filename);
-        eprintln!("auto-id: {next_id} → {}", path.display());
+        eprintln!("auto-id: {id} → {}", path.display());
+        next_id = Some(id);
         Some(path)
     } else if let Some(ref dir) = args.dir {
         // --dir without --auto-id: just print next available ID
         let graph = DocGraph::build(dir, &schema)?;
-        let next_id = graph.next_id(&args.doc_type);
-        eprintln!("next-id: {next_id}");
+        let id = graph.next_id(&args.doc_type);
+        eprintln!("next-id: {id}");
+        next_id = Some(id);
         args.output.clone()
     } else {
         args.output.clone()
     };
 
-    let content = template::generate_document_opts(type_def, &schema, &fields, args.fill);
+    // A type's `template` file is relative to the schema
+    let schema_dir = schema_path.parent().unwrap_or(Path::new("."));
+    let content =
+        template::new_document(type_def, &schema, schema_dir, &fields, next_id.as_deref(), args.fill)?;
+
+    let steps = if args.no_steps {
+        Vec::new()
+    } else {
+        md_db::project::Project::find(target)?.map(|p| p.on_new).unwrap_or_default()
+    };
+    let (content, notes) = pipeline::apply_content_steps(&content, &steps, &schema)?;
+    for note in &notes {
+        eprintln!("on-new {note}");
+    }
 
     if let Some(ref path) = output_path {
         // Create parent directories if needed
@@ -79,10 +110,21 @@ pub fn run(args: &NewArgs) -> Result<(), Box<dyn std::error::Error>> {
                 std::fs::create_dir_all(parent)?;
             }
         }
-        std::fs::write(path, &content)?;
+        let mut undo = Recorder::new("new");
+        undo.track(path);
+        md_db::durability::write(path, &content)?;
         eprintln!("wrote {}", path.display());
+        // Log what was written even when a later step fails
+        let dir = args.dir.as_deref().or(path.parent()).unwrap_or(Path::new("."));
+        let result = run_file_steps(&steps, path, dir, &schema, &mut undo);
+        undo.finish()?;
+        result?;
     } else {
         print!("{content}");
+        let skipped: Vec<&str> = steps.iter().filter(|s| !s.edits_content()).map(|s| s.name()).collect();
+        if !skipped.is_empty() {
+            eprintln!("on-new: skipped {} (no output file)", skipped.join(", "));
+        }
         if let Some(ref folder) = type_def.folder {
             eprintln!("hint: default folder for type \"{}\" is \"{folder}\"", type_def.name);
         }
@@ -91,6 +133,52 @@ pub fn run(args: &NewArgs) -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
+/// Run the `on-new` steps that work on the written file, in declared order.
+fn run_file_steps(
+    steps: &[NewStep],
+    path: &Path,
+    dir: &Path,
+    schema: &Schema,
+    undo: &mut Recorder,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut written = vec![path.to_path_buf()];
+    for step in steps.iter().filter(|s| !s.edits_content()) {
+        match step {
+            NewStep::Inverse => {
+                let graph = DocGraph::build(dir, schema)?;
+                let plan = pipeline::inverse_plan(&graph, schema, path);
+                for w in &plan.warnings {
+                    eprintln!("on-new inverse: warning: {w}");
+                }
+                undo.track_all(plan.actions.iter().map(|a| &a.path));
+                md_db::sync::apply_sync_plan(&plan)?;
+                for a in &plan.actions {
+                    eprintln!("on-new inverse: added {} to {} \"{}\"", a.add_refs.join(", "), a.doc_id, a.field_name);
+                    written.push(a.path.clone());
+                }
+            }
+            NewStep::GitAdd => {
+                let paths: Vec<&Path> = written.iter().map(PathBuf::as_path).collect();
+                md_db::vcs::stage(&paths)?;
+                eprintln!("on-new git-add: staged {} file(s)", paths.len());
+            }
+            NewStep::Editor => {
+                let cmd = md_db::terminal::editor_command();
+                let status = Command::new(&cmd[0])
+                    .args(&cmd[1..])
+                    .arg(path)
+                    .status()
+                    .map_err(|e| format!("failed to start editor {}: {e}", cmd[0]))?;
+                if !status.success() {
+                    return Err(format!("editor {} exited with {status}", cmd[0]).into());
+                }
+            }
+            NewStep::Defaults | NewStep::Fix | NewStep::Toc => {}
+        }
+    }
+    Ok(())
+}
+
 fn parse_field_arg(s: &str) -> Result<(String, String), Box<dyn std::error::Error>> {
     let (key, value) = s
         .split_once('=')
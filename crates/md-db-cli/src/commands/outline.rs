@@ -0,0 +1,54 @@
+use std::path::PathBuf;
+
+use clap::Args;
+use md_db::outline::{self, Outline};
+use md_db::output::OutputFormat;
+use md_db::schema::Schema;
+
+#[derive(Debug, Args)]
+pub struct OutlineArgs {
+    /// Path to the markdown file (omit with --dir)
+    pub file: Option<PathBuf>,
+
+    /// Outline every document under this directory instead
+    #[arg(long, conflicts_with = "file")]
+    pub dir: Option<PathBuf>,
+
+    /// KDL schema that marks required sections (`auto` uses the closest one above the target, if any)
+    #[arg(long, default_value = "auto")]
+    pub schema: PathBuf,
+
+    /// Output format: text, json
+    #[arg(long, default_value = "text")]
+    pub format: String,
+}
+
+pub fn run(args: &OutlineArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let target = match (&args.file, &args.dir) {
+        (Some(file), _) => file.as_path(),
+        (None, Some(dir)) => dir.as_path(),
+        (None, None) => return Err("give a file or --dir".into()),
+    };
+    let schema = match md_db::project::resolve_optional_schema(&args.schema, target)? {
+        Some(path) => Some(Schema::from_file(path)?),
+        None => None,
+    };
+
+    let outlines: Vec<Outline> = match args.dir {
+        Some(ref dir) => md_db::discovery::discover_files(dir, None, &[], false)?
+            .iter()
+            .filter_map(|path| outline::outline_file(path, schema.as_ref()).ok())
+            .collect(),
+        None => vec![outline::outline_file(target, schema.as_ref())?],
+    };
+
+    match OutputFormat::from_str(&args.format).unwrap_or(OutputFormat::Text) {
+        OutputFormat::Json if args.dir.is_some() => println!("{}", serde_json::to_string_pretty(&outlines)?),
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&outlines[0])?),
+        _ => {
+            let texts: Vec<String> = outlines.iter().map(outline::to_text).collect();
+            print!("{}", texts.join("\n"));
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,79 @@
+use std::path::PathBuf;
+
+use clap::Args;
+use md_db::owners::OwnersIndex;
+use md_db::schema::Schema;
+
+#[derive(Debug, Args)]
+pub struct OwnersArgs {
+    /// List documents whose user fields (author, owner, reviewers, ...) name this handle
+    pub handle: Option<String>,
+
+    /// List documents whose body mentions this handle (e.g. @alice)
+    #[arg(long)]
+    pub mentions: Option<String>,
+
+    /// Directory containing markdown files
+    #[arg(long, default_value = ".")]
+    pub dir: PathBuf,
+
+    /// Path to KDL schema file or `auto` (declares which fields hold users)
+    #[arg(long, default_value = "auto")]
+    pub schema: PathBuf,
+
+    /// Output format: text, json
+    #[arg(long, default_value = "text")]
+    pub format: String,
+}
+
+pub fn run(args: &OwnersArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let schema = Schema::from_file(md_db::project::resolve_schema(&args.schema, &args.dir)?)?;
+    let index = OwnersIndex::build(&args.dir, &schema)?;
+    let json = args.format == "json";
+
+    if let Some(ref handle) = args.mentions {
+        let mentions = index.mentioning(handle);
+        if json {
+            println!("{}", serde_json::to_string_pretty(&mentions)?);
+        } else if mentions.is_empty() {
+            println!("No documents mention {handle}.");
+        } else {
+            for m in &mentions {
+                println!("{}  {}:{}", m.id, m.path.display(), m.line);
+            }
+        }
+        return Ok(());
+    }
+
+    if let Some(ref handle) = args.handle {
+        let owned = index.owned_by(handle);
+        if json {
+            println!("{}", serde_json::to_string_pretty(&owned)?);
+        } else if owned.is_empty() {
+            println!("No documents list {handle} in a user field.");
+        } else {
+            for o in &owned {
+                println!("{}  {}  ({})", o.id, o.path.display(), o.field);
+            }
+        }
+        return Ok(());
+    }
+
+    let summary = index.summary();
+    if json {
+        let rows: Vec<serde_json::Value> = summary
+            .iter()
+            .map(|(handle, owned, mentioned)| {
+                serde_json::json!({ "handle": handle, "owned": owned, "mentions": mentioned })
+            })
+            .collect();
+        println!("{}", serde_json::to_string_pretty(&rows)?);
+    } else {
+        let width = summary.iter().map(|(h, _, _)| h.len()).max().unwrap_or(6).max(6);
+        println!("{:<width$}  {:>5}  {:>8}", "handle", "owned", "mentions");
+        for (handle, owned, mentioned) in &summary {
+            println!("{handle:<width$}  {owned:>5}  {mentioned:>8}");
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,80 @@
+use std::path::PathBuf;
+
+use clap::Args;
+use md_db::projection::{self, ProjectionStatus};
+use md_db::schema::Schema;
+use md_db::undo::Recorder;
+
+#[derive(Debug, Args)]
+pub struct ProjectArgs {
+    /// Docs directory the projections query; their paths are relative to it
+    #[arg(default_value = ".")]
+    pub dir: PathBuf,
+
+    /// Path to KDL schema file, or `auto` to use the closest one above the target
+    #[arg(long, default_value = "auto")]
+    pub schema: PathBuf,
+
+    /// Only this projection
+    #[arg(long)]
+    pub name: Option<String>,
+
+    /// Write projections that are missing or out of date
+    #[arg(long)]
+    pub write: bool,
+
+    /// Fail if any projection is missing or out of date instead of writing it (for CI)
+    #[arg(long, conflicts_with = "write")]
+    pub check: bool,
+}
+
+pub fn run(args: &ProjectArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let schema = Schema::from_file(md_db::project::resolve_schema(&args.schema, &args.dir)?)?;
+    if let Some(ref name) = args.name {
+        if !schema.projections.iter().any(|p| &p.name == name) {
+            let known: Vec<&str> = schema.projections.iter().map(|p| p.name.as_str()).collect();
+            return Err(format!("unknown projection \"{name}\" (schema declares: {})", known.join(", ")).into());
+        }
+    }
+    let projections: Vec<_> = projection::project(&args.dir, &schema)?
+        .into_iter()
+        .filter(|p| args.name.as_ref().is_none_or(|name| &p.name == name))
+        .collect();
+    if projections.is_empty() {
+        eprintln!("the schema declares no projections");
+        return Ok(());
+    }
+
+    let outdated: Vec<_> = projections.iter().filter(|p| p.status != ProjectionStatus::Fresh).collect();
+    if args.write && !outdated.is_empty() {
+        let mut undo = Recorder::new("project");
+        for p in &outdated {
+            undo.track(&p.path);
+        }
+        let result = outdated.iter().try_for_each(|p| -> Result<(), Box<dyn std::error::Error>> {
+            if let Some(parent) = p.path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            Ok(md_db::durability::write(&p.path, &p.content)?)
+        });
+        undo.finish()?;
+        result?;
+    }
+
+    for p in &projections {
+        let state = match (p.status, args.write) {
+            (ProjectionStatus::Fresh, _) => "up to date".to_string(),
+            (ProjectionStatus::Missing, true) => "created".to_string(),
+            (ProjectionStatus::Stale, true) => "updated".to_string(),
+            (status, false) => status.to_string(),
+        };
+        eprintln!("{}: {} ({state})", p.name, p.path.display());
+    }
+    if !args.write && !outdated.is_empty() {
+        if args.check {
+            return Err(format!("{} projection(s) out of date; run `md-db project --write`", outdated.len()).into());
+        }
+        eprintln!("run `md-db project --write` to update them");
+    }
+    Ok(())
+}
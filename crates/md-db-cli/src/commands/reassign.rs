@@ -0,0 +1,137 @@
+use std::path::PathBuf;
+
+use clap::Args;
+use md_db::discovery::Filter;
+use md_db::notify::{self, Notifier};
+use md_db::reassign::{self, ReassignOptions};
+use md_db::schema::Schema;
+use md_db::undo::Recorder;
+
+#[derive(Debug, Args)]
+pub struct ReassignArgs {
+    /// Directory containing markdown files
+    #[arg(default_value = ".")]
+    pub dir: PathBuf,
+
+    /// User or team handing over (with or without @)
+    #[arg(long)]
+    pub from: String,
+
+    /// User or team taking over
+    #[arg(long)]
+    pub to: String,
+
+    /// Frontmatter fields to reassign (comma-separated; default: every user/user[] field of the type)
+    #[arg(long)]
+    pub fields: Option<String>,
+
+    /// Table columns to reassign (comma-separated)
+    #[arg(long, default_value = "Owner")]
+    pub columns: String,
+
+    /// Only documents of this type
+    #[arg(long = "type")]
+    pub doc_type: Option<String>,
+
+    /// Only documents with this status: a value, a comma-separated list, or !value to exclude one
+    #[arg(long)]
+    pub status: Option<String>,
+
+    /// Path to KDL schema file, or `auto` to use the closest one above the directory
+    #[arg(long, default_value = "auto")]
+    pub schema: PathBuf,
+
+    /// Show what would change without writing files
+    #[arg(long)]
+    pub dry_run: bool,
+
+    /// Output format: text, json
+    #[arg(long, default_value = "text")]
+    pub format: String,
+
+    /// POST a `reassign` event (JSON) to this webhook URL after writing
+    #[arg(long)]
+    pub notify: Option<String>,
+
+    /// Run this shell command with the `reassign` event (JSON) on stdin after writing
+    #[arg(long)]
+    pub notify_cmd: Option<String>,
+
+    /// Seconds to wait for the --notify webhook or --notify-cmd command before giving up
+    #[arg(long, default_value_t = md_db::notify::DEFAULT_TIMEOUT_SECS)]
+    pub notify_timeout: u64,
+}
+
+pub fn run(args: &ReassignArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let schema = Schema::from_file(md_db::project::resolve_schema(&args.schema, &args.dir)?)?;
+    let mut filters = Vec::new();
+    if let Some(ref doc_type) = args.doc_type {
+        filters.push(Filter::FieldEquals { key: "type".into(), value: doc_type.clone() });
+    }
+    if let Some(ref status) = args.status {
+        filters.push(match status.strip_prefix('!') {
+            Some(value) => Filter::FieldNotEquals { key: "status".into(), value: value.to_string() },
+            None => Filter::FieldIn { key: "status".into(), values: split_list(status) },
+        });
+    }
+    let files = md_db::discovery::discover_files(&args.dir, None, &filters, false)?;
+
+    let fields = args.fields.as_deref().map(split_list).unwrap_or_default();
+    let columns = split_list(&args.columns);
+    let opts = ReassignOptions {
+        from: &args.from,
+        to: &args.to,
+        fields: &fields,
+        columns: &columns,
+    };
+    let plan = reassign::plan_reassign(&files, &schema, &opts)?;
+    let places: usize = plan.iter().map(|r| r.locations.len()).sum();
+    let documents: Vec<serde_json::Value> = plan
+        .iter()
+        .map(|r| serde_json::json!({"id": r.doc_id, "path": r.path.display().to_string(), "locations": r.locations}))
+        .collect();
+
+    if args.format == "json" {
+        let result = serde_json::json!({
+            "from": args.from,
+            "to": args.to,
+            "documents": documents,
+            "places": places,
+            "dry_run": args.dry_run,
+        });
+        println!("{}", serde_json::to_string_pretty(&result)?);
+    } else {
+        for r in &plan {
+            println!("{}: {}", r.doc_id, r.locations.join(", "));
+        }
+        let verb = if args.dry_run { "Would reassign" } else { "Reassigned" };
+        println!("{verb} {} to {} in {} document(s), {places} place(s).", args.from, args.to, plan.len());
+    }
+    if args.dry_run || plan.is_empty() {
+        return Ok(());
+    }
+
+    let mut undo = Recorder::new("reassign");
+    undo.track_all(plan.iter().map(|r| &r.path));
+    let result = reassign::apply(&plan);
+    undo.finish()?;
+    result?;
+
+    let notifiers = Notifier::from_args(
+        args.notify.as_deref(),
+        args.notify_cmd.as_deref(),
+        args.notify_timeout,
+    );
+    if !notifiers.is_empty() {
+        let text = format!("{} handed over to {}: {} document(s) reassigned", args.from, args.to, plan.len());
+        let fields = serde_json::json!({"from": args.from, "to": args.to, "documents": documents});
+        for error in notify::send_all(&notifiers, &notify::event("reassign", &text, fields)) {
+            eprintln!("warning: {error}");
+        }
+    }
+    Ok(())
+}
+
+fn split_list(list: &str) -> Vec<String> {
+    list.split(',').map(|s| s.trim().to_string()).filter(|s| !s.is_empty()).collect()
+}
@@ -10,8 +10,8 @@ pub struct RefsArgs {
     /// Directory containing markdown files
     pub dir: PathBuf,
 
-    /// Path to KDL schema file
-    #[arg(long)]
+    /// Path to KDL schema file, or `auto` to use the closest one above the target
+    #[arg(long, default_value = "auto")]
     pub schema: PathBuf,
 
     /// Show outgoing refs from this file or ID
@@ -29,16 +29,31 @@ pub struct RefsArgs {
     /// Output format: text, json, compact, auto
     #[arg(long, default_value = "auto")]
     pub format: String,
+
+    /// Build the whole graph from scratch instead of loading the document's neighborhood
+    /// from the .md-db/graph.bin snapshot
+    #[arg(long)]
+    pub no_cache: bool,
 }
 
 pub fn run(args: &RefsArgs) -> Result<(), Box<dyn std::error::Error>> {
-    let schema = Schema::from_file(&args.schema)?;
-    let graph = DocGraph::build(&args.dir, &schema)?;
+    let schema = Schema::from_file(md_db::project::resolve_schema(&args.schema, &args.dir)?)?;
+    let focus = match (&args.to, &args.from) {
+        (Some(target), _) => normalize_id(target),
+        (None, Some(source)) => resolve_id(source),
+        (None, None) => return Err("specify --from or --to".into()),
+    };
+    // Only the documents within --depth links are loaded from the snapshot
+    let graph = if args.no_cache {
+        DocGraph::build(&args.dir, &schema)?
+    } else {
+        DocGraph::neighborhood(&args.dir, &schema, &focus, args.depth)?
+    };
     let format = OutputFormat::from_str(&args.format).unwrap_or(OutputFormat::auto());
 
-    if let Some(ref target) = args.to {
+    let id = graph.find_by_uid(&focus).map(|n| n.id.clone()).unwrap_or(focus);
+    if args.to.is_some() {
         // Backlinks to a document
-        let id = normalize_id(target);
         let edges = if args.depth > 1 {
             graph.refs_to_transitive(&id, args.depth)
         } else {
@@ -46,9 +61,8 @@ pub fn run(args: &RefsArgs) -> Result<(), Box<dyn std::error::Error>> {
         };
 
         output_edges(&edges, &graph, &id, "backlinks", format);
-    } else if let Some(ref source) = args.from {
+    } else {
         // Forward refs from a document
-        let id = resolve_id(source);
         let edges = if args.depth > 1 {
             graph.refs_from_transitive(&id, args.depth)
         } else {
@@ -56,8 +70,6 @@ pub fn run(args: &RefsArgs) -> Result<(), Box<dyn std::error::Error>> {
         };
 
         output_edges(&edges, &graph, &id, "refs", format);
-    } else {
-        return Err("specify --from or --to".into());
     }
 
     Ok(())
@@ -1,10 +1,11 @@
 use std::collections::HashSet;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use clap::Args;
 use md_db::document::Document;
 use md_db::graph::{path_to_id, DocGraph};
 use md_db::schema::{FieldType, Schema};
+use md_db::undo::Recorder;
 
 #[derive(Debug, Args)]
 pub struct RenameArgs {
@@ -14,8 +15,8 @@ pub struct RenameArgs {
     /// New document ID (e.g. ADR-010)
     pub new_id: String,
 
-    /// Path to KDL schema file
-    #[arg(long)]
+    /// Path to KDL schema file, or `auto` to use the closest one above the target
+    #[arg(long, default_value = "auto")]
     pub schema: PathBuf,
 
     /// Directory to scan for references
@@ -28,28 +29,65 @@ pub struct RenameArgs {
 }
 
 pub fn run(args: &RenameArgs) -> Result<(), Box<dyn std::error::Error>> {
-    let schema = Schema::from_file(&args.schema)?;
-    let old_id = path_to_id(&args.file);
-    let new_id = args.new_id.to_uppercase();
+    let schema = Schema::from_file(md_db::project::resolve_schema(&args.schema, &args.file)?)?;
+    let renamed = rename(&args.file, &args.new_id, &schema, &args.dir, args.dry_run)?;
+
+    let (update_verb, rename_verb) = if args.dry_run {
+        ("would update", "would rename")
+    } else {
+        ("updated", "renamed")
+    };
+    for (path, ref_id) in &renamed.updated {
+        eprintln!("  {update_verb}: {} ({ref_id})", path.display());
+    }
+    eprintln!("  {rename_verb}: {} -> {}", args.file.display(), renamed.new_path.display());
+
+    // Summary
+    eprintln!(
+        "rename {} -> {}: {} file(s) updated, 1 file renamed",
+        renamed.old_id,
+        renamed.new_id,
+        renamed.updated.len()
+    );
+
+    Ok(())
+}
+
+/// What [`rename`] changed, or would change on a dry run.
+pub(super) struct Renamed {
+    pub old_id: String,
+    pub new_id: String,
+    pub new_path: PathBuf,
+    /// Documents whose refs were rewritten, with their IDs.
+    pub updated: Vec<(PathBuf, String)>,
+}
+
+/// Give `file` the ID `new_id`: rename it (keeping any slug) and rewrite the refs
+/// to it in the documents under `dir`. Shared with the MCP `md-db-rename` tool.
+pub(super) fn rename(
+    file: &Path,
+    new_id: &str,
+    schema: &Schema,
+    dir: &Path,
+    dry_run: bool,
+) -> Result<Renamed, Box<dyn std::error::Error>> {
+    let old_id = path_to_id(file);
+    let new_id = new_id.to_uppercase();
 
     if old_id == new_id {
         return Err(format!("old ID and new ID are the same: {old_id}").into());
     }
 
     // Compute new filename: lowercase new_id + preserve slug if any + .md
-    let new_filename = compute_new_filename(&args.file, &old_id, &new_id);
-    let new_path = args
-        .file
-        .parent()
-        .unwrap_or_else(|| std::path::Path::new("."))
-        .join(&new_filename);
-
-    if new_path.exists() && new_path != args.file {
+    let new_filename = compute_new_filename(file, &old_id, &new_id);
+    let new_path = file.parent().unwrap_or_else(|| Path::new(".")).join(&new_filename);
+
+    if new_path.exists() && new_path != file {
         return Err(format!("target file already exists: {}", new_path.display()).into());
     }
 
     // Build graph to find all docs referencing old_id
-    let graph = DocGraph::build(&args.dir, &schema)?;
+    let graph = DocGraph::build(dir, schema)?;
     let backlinks = graph.refs_to(&old_id);
 
     // Collect unique referencing doc IDs (skip self)
@@ -72,7 +110,8 @@ pub fn run(args: &RenameArgs) -> Result<(), Box<dyn std::error::Error>> {
         }
     }
 
-    let mut updated_files = Vec::new();
+    let mut undo = Recorder::new("rename");
+    let mut updated = Vec::new();
 
     // Update each referencing document
     for ref_id in &referencing_ids {
@@ -114,35 +153,28 @@ pub fn run(args: &RenameArgs) -> Result<(), Box<dyn std::error::Error>> {
             raw.push_str(&doc.body);
             doc.raw = raw;
 
-            if args.dry_run {
-                eprintln!("  would update: {} ({})", node.path.display(), ref_id);
-            } else {
+            if !dry_run {
+                undo.track(&node.path);
                 doc.save()?;
-                eprintln!("  updated: {} ({})", node.path.display(), ref_id);
             }
-            updated_files.push(node.path.clone());
+            updated.push((node.path.clone(), ref_id.to_string()));
         }
     }
 
     // Rename the source file
-    if args.dry_run {
-        eprintln!(
-            "  would rename: {} -> {}",
-            args.file.display(),
-            new_path.display()
-        );
-    } else {
-        std::fs::rename(&args.file, &new_path)?;
-        eprintln!("  renamed: {} -> {}", args.file.display(), new_path.display());
+    if !dry_run {
+        undo.track(file);
+        undo.track(&new_path);
+        std::fs::rename(file, &new_path)?;
     }
-
-    // Summary
-    eprintln!(
-        "rename {old_id} -> {new_id}: {} file(s) updated, 1 file renamed",
-        updated_files.len()
-    );
-
-    Ok(())
+    undo.finish()?;
+
+    Ok(Renamed {
+        old_id,
+        new_id,
+        new_path,
+        updated,
+    })
 }
 
 /// Compute the new filename preserving any slug suffix.
@@ -151,7 +183,7 @@ pub fn run(args: &RenameArgs) -> Result<(), Box<dyn std::error::Error>> {
 ///   -> `adr-010-use-postgresql.md`
 ///
 /// Example: `adr-001.md` with new_id=`ADR-010` -> `adr-010.md`
-fn compute_new_filename(old_path: &std::path::Path, old_id: &str, new_id: &str) -> String {
+fn compute_new_filename(old_path: &Path, old_id: &str, new_id: &str) -> String {
     let stem = old_path
         .file_stem()
         .and_then(|s| s.to_str())
@@ -199,7 +231,6 @@ fn replace_ref_in_value(val: &mut serde_yaml::Value, old_id: &str, new_id: &str)
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::path::Path;
 
     #[test]
     fn test_compute_new_filename_simple() {
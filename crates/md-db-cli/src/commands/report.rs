@@ -0,0 +1,98 @@
+use std::path::{Path, PathBuf};
+
+use clap::Args;
+use md_db::board;
+use md_db::marker;
+use md_db::schema::Schema;
+use md_db::undo::Recorder;
+
+#[derive(Debug, Args)]
+pub struct ReportArgs {
+    /// Report: board
+    pub report: String,
+
+    /// Directory containing markdown files
+    #[arg(default_value = ".")]
+    pub dir: PathBuf,
+
+    /// Field whose values become the board's sections
+    #[arg(long, default_value = "status")]
+    pub group_by: String,
+
+    /// Only documents of this type
+    #[arg(long = "type")]
+    pub doc_type: Option<String>,
+
+    /// Keep the board in the `<!-- md-db:board -->` block of this file, creating the file if it doesn't exist
+    #[arg(long)]
+    pub output: Option<PathBuf>,
+
+    /// With --output: fail if the board is out of date instead of rewriting it (for CI)
+    #[arg(long, requires = "output")]
+    pub check: bool,
+
+    /// KDL schema whose enum values order the sections (`auto` uses the closest one above the directory, if any)
+    #[arg(long, default_value = "auto")]
+    pub schema: PathBuf,
+
+    /// Output format when printing: markdown, json
+    #[arg(long, default_value = "markdown")]
+    pub format: String,
+}
+
+pub fn run(args: &ReportArgs) -> Result<(), Box<dyn std::error::Error>> {
+    match args.report.as_str() {
+        "board" => run_board(args),
+        _ => Err(format!("unknown report: {} (expected: board)", args.report).into()),
+    }
+}
+
+fn run_board(args: &ReportArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let schema = match md_db::project::resolve_optional_schema(&args.schema, &args.dir)? {
+        Some(path) => Some(Schema::from_file(path)?),
+        None => None,
+    };
+    // Links are relative to the board file, or to the working directory when printed
+    let link_from = match args.output.as_ref().and_then(|f| f.parent()) {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    let board = board::board(&args.dir, args.doc_type.as_deref(), &args.group_by, schema.as_ref(), link_from)?;
+
+    let Some(ref file) = args.output else {
+        match args.format.as_str() {
+            "json" => println!("{}", serde_json::to_string_pretty(&board)?),
+            _ => print!("{}", board.to_markdown()),
+        }
+        return Ok(());
+    };
+
+    let text = std::fs::read_to_string(file).ok();
+    let updated = board.update(text.as_deref())?.ok_or_else(|| {
+        format!(
+            "{} has no board block; add the lines {} and {} where the board should go",
+            file.display(),
+            marker::start_marker(board::BLOCK_NAME),
+            marker::end_marker(board::BLOCK_NAME)
+        )
+    })?;
+
+    if text.as_deref() == Some(updated.as_str()) {
+        eprintln!("{}: board up to date", file.display());
+    } else if args.check {
+        return Err(format!(
+            "{}: board is out of date; run `md-db report board --output {}`",
+            file.display(),
+            file.display()
+        )
+        .into());
+    } else {
+        let mut undo = Recorder::new("report board");
+        undo.track(file);
+        md_db::durability::write(file, updated)?;
+        undo.finish()?;
+        let verb = if text.is_some() { "updated" } else { "created" };
+        eprintln!("{}: board {verb}", file.display());
+    }
+    Ok(())
+}
@@ -0,0 +1,63 @@
+use std::path::PathBuf;
+
+use clap::Args;
+use md_db::schema::Schema;
+use md_db::schema_test;
+
+#[derive(Debug, Args)]
+pub struct SchemaArgs {
+    /// Action: test
+    pub action: String,
+
+    /// Docs directory, used to find the schema with `--schema auto`
+    #[arg(default_value = ".")]
+    pub dir: PathBuf,
+
+    /// Path to KDL schema file, or `auto` to use the closest one above the directory
+    #[arg(long, default_value = "auto")]
+    pub schema: PathBuf,
+
+    /// Output format: text, json
+    #[arg(long, default_value = "text")]
+    pub format: String,
+}
+
+pub fn run(args: &SchemaArgs) -> Result<(), Box<dyn std::error::Error>> {
+    match args.action.as_str() {
+        "test" => test(args),
+        _ => Err(format!("unknown action: {} (expected: test)", args.action).into()),
+    }
+}
+
+fn test(args: &SchemaArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let schema = Schema::from_file(md_db::project::resolve_schema(&args.schema, &args.dir)?)?;
+    let report = schema_test::run(&schema);
+
+    if args.format == "json" {
+        let result = serde_json::json!({
+            "passed": report.passed(),
+            "results": report.results,
+        });
+        println!("{}", serde_json::to_string_pretty(&result)?);
+    } else {
+        for r in &report.results {
+            let mark = if r.passed { "ok  " } else { "FAIL" };
+            let kind = if r.counter { "counter-example" } else { "example" };
+            println!("{mark} {} {kind} {:?}", r.target, r.snippet);
+            if let Some(ref reason) = r.reason {
+                println!("     {reason}");
+            }
+        }
+        if report.results.is_empty() {
+            eprintln!("no examples in the schema; add `example` or `counter-example` to types, fields, or sections");
+        } else {
+            println!();
+            println!("{} example(s), {} failed", report.results.len(), report.failed());
+        }
+    }
+
+    if !report.passed() {
+        std::process::exit(1);
+    }
+    Ok(())
+}
@@ -1,3 +1,4 @@
+use std::io::Write;
 use std::path::PathBuf;
 
 use clap::Args;
@@ -28,7 +29,7 @@ pub struct SearchArgs {
     #[arg(long)]
     pub max_results: Option<usize>,
 
-    /// Output format: text, json
+    /// Output format: text, json, ndjson
     #[arg(long, default_value = "text")]
     pub format: String,
 }
@@ -43,6 +44,16 @@ pub fn run(args: &SearchArgs) -> Result<(), Box<dyn std::error::Error>> {
         max_results: args.max_results,
     };
 
+    if format == OutputFormat::Ndjson {
+        // Print each document's matches as soon as it is searched
+        let mut out = std::io::stdout().lock();
+        search::search_documents_with(&args.dir, &args.query, &options, |result| {
+            writeln!(out, "{}", serde_json::to_string(&result)?)?;
+            Ok(())
+        })?;
+        return Ok(());
+    }
+
     let results = search::search_documents(&args.dir, &args.query, &options)?;
 
     match format {
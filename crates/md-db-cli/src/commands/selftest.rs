@@ -0,0 +1,57 @@
+use std::path::PathBuf;
+
+use clap::Args;
+use md_db::selftest;
+use md_db::users::UserConfig;
+
+#[derive(Debug, Args)]
+pub struct SelftestArgs {
+    /// Docs directory
+    #[arg(default_value = ".")]
+    pub dir: PathBuf,
+
+    /// Path to KDL schema file, or `auto` to use the closest one above the target
+    #[arg(long, default_value = "auto")]
+    pub schema: PathBuf,
+
+    /// Path to user/team config YAML file
+    #[arg(long)]
+    pub users: Option<PathBuf>,
+
+    /// Output format: text, json
+    #[arg(long, default_value = "text")]
+    pub format: String,
+}
+
+pub fn run(args: &SelftestArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let user_config = match &args.users {
+        Some(path) => Some(UserConfig::from_file(path)?),
+        None => None,
+    };
+    let schema_path = md_db::project::resolve_schema(&args.schema, &args.dir)?;
+    let report = selftest::run(&args.dir, &schema_path, user_config.as_ref())?;
+
+    if args.format == "json" {
+        let result = serde_json::json!({
+            "passed": report.passed(),
+            "checks": report.checks,
+        });
+        println!("{}", serde_json::to_string_pretty(&result)?);
+    } else {
+        for check in &report.checks {
+            let mark = if check.passed { "ok  " } else { "FAIL" };
+            println!("{mark} {}", check.name);
+            for line in &check.details {
+                println!("     {line}");
+            }
+        }
+        let failed = report.checks.iter().filter(|c| !c.passed).count();
+        println!();
+        println!("{} check(s), {failed} failed", report.checks.len());
+    }
+
+    if !report.passed() {
+        std::process::exit(1);
+    }
+    Ok(())
+}
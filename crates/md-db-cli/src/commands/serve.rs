@@ -0,0 +1,525 @@
+//! HTTP API over a docs directory.
+//!
+//! Each route runs the MCP tool of the same name, so a response body is exactly
+//! what the tool returns. Paths in requests are confined to the served
+//! directory, and errors are RFC 7807 problem details.
+//!
+//! The API is read-only unless started with a token file. Then every request
+//! needs a bearer token, the write routes are open, and each write is recorded
+//! in the audit log under the token's user.
+
+use std::collections::HashSet;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use clap::Args;
+use md_db::assets::percent_decode;
+use md_db::audit::{self, AuditEntry};
+use md_db::auth::ApiTokens;
+use md_db::context::resolve_id;
+use md_db::document::Document;
+use md_db::error::Error;
+use md_db::frontmatter::Frontmatter;
+use md_db::graph::{path_to_id, DocGraph};
+use md_db::mcp_config::McpConfig;
+use md_db::output::problem::{self, Problem, ProblemKind};
+use md_db::schema::Schema;
+use md_db::users::UserConfig;
+use md_db::validation::{self, ValidationResult};
+use serde_json::{json, Map, Value};
+
+use super::mcp;
+
+/// Largest request body accepted (`POST /validate`).
+const MAX_BODY: usize = 4 * 1024 * 1024;
+
+/// Largest request line plus headers accepted.
+const MAX_HEAD: u64 = 64 * 1024;
+
+/// How long a connection may stall while being read from or written to.
+const IO_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Connections handled at once; further ones are closed until one finishes.
+const MAX_CONNECTIONS: usize = 64;
+
+#[derive(Debug, Args)]
+pub struct ServeArgs {
+    /// Directory containing markdown files
+    #[arg(default_value = ".")]
+    pub dir: PathBuf,
+
+    /// Path to KDL schema file, or `auto` to use the closest one above the directory
+    #[arg(long, default_value = "auto")]
+    pub schema: PathBuf,
+
+    /// Path to user/team config YAML
+    #[arg(long)]
+    pub users: Option<PathBuf>,
+
+    /// Port to listen on
+    #[arg(long, default_value_t = 8080)]
+    pub port: u16,
+
+    /// Address to bind (use 0.0.0.0 to serve other machines)
+    #[arg(long, default_value = "127.0.0.1")]
+    pub bind: String,
+
+    /// YAML file of `user: token` lines; requires a token on every request and enables the write routes
+    #[arg(long)]
+    pub tokens: Option<PathBuf>,
+}
+
+/// What every request is answered from.
+struct Server {
+    dir: PathBuf,
+    schema: PathBuf,
+    users: Option<PathBuf>,
+    config: McpConfig,
+    /// Set when writes are enabled.
+    tokens: Option<ApiTokens>,
+    /// Where the undo log lives; writes hold its [`md_db::undo::write_lock`].
+    log_root: PathBuf,
+}
+
+/// A parsed request.
+pub(super) struct Request {
+    pub(super) method: String,
+    pub(super) path: String,
+    pub(super) query: Vec<(String, String)>,
+    pub(super) accept: Option<String>,
+    pub(super) authorization: Option<String>,
+    content_type: Option<String>,
+    /// The headers without a field above, names lowercased.
+    headers: Vec<(String, String)>,
+    pub(super) body: Vec<u8>,
+}
+
+impl Request {
+    /// The value of a header (`name` in lowercase).
+    pub(super) fn header(&self, name: &str) -> Option<&str> {
+        self.headers.iter().find(|(n, _)| n == name).map(|(_, v)| v.as_str())
+    }
+}
+
+pub fn run(args: &ServeArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let schema = md_db::project::resolve_schema(&args.schema, &args.dir)?;
+    // Fail at startup, not on the first request
+    Schema::from_file(&schema)?;
+    if let Some(ref users) = args.users {
+        UserConfig::from_file(users)?;
+    }
+    let root = args
+        .dir
+        .canonicalize()
+        .map_err(|_| format!("{} is not a directory", args.dir.display()))?;
+    let log_root = md_db::undo::log_root(&root)?;
+    let config = McpConfig {
+        extra_reads: std::iter::once(&schema)
+            .chain(&args.users)
+            .filter_map(|p| p.canonicalize().ok())
+            .collect(),
+        root: Some(root),
+        ..McpConfig::default()
+    };
+    let server = Server {
+        dir: args.dir.clone(),
+        schema,
+        users: args.users.clone(),
+        config,
+        tokens: args.tokens.as_ref().map(ApiTokens::from_file).transpose()?,
+        log_root,
+    };
+
+    let listener = TcpListener::bind((args.bind.as_str(), args.port))?;
+    let mode = if server.tokens.is_some() { "read-write" } else { "read-only" };
+    eprintln!("serving {} ({mode}) at http://{}:{}/", args.dir.display(), args.bind, args.port);
+    accept_loop(&listener, server, respond);
+    Ok(())
+}
+
+/// Handle each connection on its own thread, with read and write timeouts, at
+/// most `MAX_CONNECTIONS` at a time, so a slow or idle client holds up no one else.
+pub(super) fn accept_loop<S: Send + Sync + 'static>(
+    listener: &TcpListener,
+    state: S,
+    respond: fn(TcpStream, &S) -> std::io::Result<()>,
+) {
+    let state = Arc::new(state);
+    let active = Arc::new(AtomicUsize::new(0));
+    for stream in listener.incoming().flatten() {
+        if active.load(Ordering::Acquire) >= MAX_CONNECTIONS {
+            continue; // dropping the stream closes it
+        }
+        let timeouts = stream
+            .set_read_timeout(Some(IO_TIMEOUT))
+            .and_then(|_| stream.set_write_timeout(Some(IO_TIMEOUT)));
+        if let Err(e) = timeouts {
+            eprintln!("warning: {e}");
+            continue;
+        }
+        active.fetch_add(1, Ordering::AcqRel);
+        let (state, active) = (Arc::clone(&state), Arc::clone(&active));
+        std::thread::spawn(move || {
+            if let Err(e) = respond(stream, &state) {
+                eprintln!("warning: {e}");
+            }
+            active.fetch_sub(1, Ordering::AcqRel);
+        });
+    }
+}
+
+fn respond(mut stream: TcpStream, server: &Server) -> std::io::Result<()> {
+    let result = match read_request(&stream) {
+        Ok(request) => handle(server, &request).map_err(|p| (p, request.accept)),
+        Err(problem) => Err((problem, None)),
+    };
+    let (status, content_type, body, extra) = match result {
+        Ok((status, body)) => (status, "application/json", body, String::new()),
+        Err((problem, accept)) => {
+            let extra = match problem.kind {
+                ProblemKind::MethodNotAllowed => {
+                    let allow = problem.extensions.get("allow").and_then(Value::as_str).unwrap_or_default();
+                    format!("Allow: {allow}\r\n")
+                }
+                ProblemKind::Unauthorized => "WWW-Authenticate: Bearer\r\n".to_string(),
+                _ => String::new(),
+            };
+            let (status, content_type, body) = problem.render(accept.as_deref());
+            (status, content_type, body, extra)
+        }
+    };
+    write!(
+        stream,
+        "HTTP/1.1 {status} {}\r\nContent-Type: {content_type}\r\nContent-Length: {}\r\n{extra}\
+         Cache-Control: no-store\r\nConnection: close\r\n\r\n",
+        problem::reason(status),
+        body.len()
+    )?;
+    stream.write_all(body.as_bytes())
+}
+
+pub(super) fn read_request(stream: &TcpStream) -> Result<Request, Problem> {
+    let io_error = |e: std::io::Error| Problem::new(ProblemKind::BadRequest, format!("cannot read request: {e}"));
+    let mut reader = BufReader::new(stream);
+    let mut head_left = MAX_HEAD;
+    let mut line = String::new();
+    read_head_line(&mut reader, &mut line, &mut head_left)?;
+    let mut parts = line.split_whitespace();
+    let (Some(method), Some(target)) = (parts.next(), parts.next()) else {
+        return Err(Problem::new(ProblemKind::BadRequest, "malformed request line"));
+    };
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let mut request = Request {
+        method: method.to_string(),
+        path: percent_decode(path),
+        query: query
+            .split('&')
+            .filter(|pair| !pair.is_empty())
+            .map(|pair| {
+                let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+                let decode = |s: &str| percent_decode(&s.replace('+', " "));
+                (decode(key), decode(value))
+            })
+            .collect(),
+        accept: None,
+        authorization: None,
+        content_type: None,
+        headers: Vec::new(),
+        body: Vec::new(),
+    };
+
+    let mut length = 0;
+    let mut header = String::new();
+    while read_head_line(&mut reader, &mut header, &mut head_left)? > 2 {
+        if let Some((name, value)) = header.split_once(':') {
+            let (name, value) = (name.trim().to_ascii_lowercase(), value.trim().to_string());
+            match name.as_str() {
+                "accept" => request.accept = Some(value),
+                "authorization" => request.authorization = Some(value),
+                "content-type" => request.content_type = Some(value),
+                "content-length" => {
+                    length = value
+                        .parse()
+                        .map_err(|_| Problem::new(ProblemKind::BadRequest, "invalid Content-Length"))?;
+                }
+                _ => request.headers.push((name, value)),
+            }
+        }
+        header.clear();
+    }
+    if length > MAX_BODY {
+        return Err(Problem::new(ProblemKind::BadRequest, format!("body over {MAX_BODY} bytes")));
+    }
+    request.body = vec![0; length];
+    reader.read_exact(&mut request.body).map_err(io_error)?;
+    Ok(request)
+}
+
+/// Read one line of the request line or headers, out of the `left` bytes the
+/// head may still take. A line cut off by that limit is an error, not a header.
+fn read_head_line(reader: &mut BufReader<&TcpStream>, line: &mut String, left: &mut u64) -> Result<usize, Problem> {
+    let read = reader
+        .by_ref()
+        .take(*left)
+        .read_line(line)
+        .map_err(|e| Problem::new(ProblemKind::BadRequest, format!("cannot read request: {e}")))?;
+    *left -= read as u64;
+    if read > 0 && !line.ends_with('\n') && *left == 0 {
+        return Err(Problem::new(ProblemKind::BadRequest, format!("request headers over {MAX_HEAD} bytes")));
+    }
+    Ok(read)
+}
+
+/// The status and JSON body for a request.
+fn handle(server: &Server, request: &Request) -> Result<(u16, String), Problem> {
+    let user = match server.tokens {
+        Some(ref tokens) => match tokens.authenticate(request.authorization.as_deref()) {
+            Some(user) => Some(user),
+            None => {
+                let detail = "a valid `Authorization: Bearer` token is required";
+                return Err(Problem::new(ProblemKind::Unauthorized, detail).with_instance(&request.path));
+            }
+        },
+        None => None,
+    };
+    let path = request.path.trim_end_matches('/');
+    let segments: Vec<&str> = path.split('/').skip(1).collect();
+    let allowed = match (segments.as_slice(), user) {
+        (["docs"], Some(_)) => "GET, POST",
+        (["docs", _, "fields"], Some(_)) => "PATCH",
+        (["docs", _, "fields"], None) => "",
+        (["docs"] | ["docs", _] | ["docs", _, "refs"] | ["graph"], _) => "GET",
+        (["validate"], _) => "POST",
+        _ => {
+            let problem = Problem::new(ProblemKind::NotFound, format!("no route {path}"));
+            return Err(problem.with_instance(&request.path));
+        }
+    };
+    if !allowed.split(", ").any(|m| m == request.method) {
+        let detail = if allowed.is_empty() {
+            format!("{path} needs writes, and the server is read-only (start it with --tokens)")
+        } else {
+            format!("{path} only accepts {allowed}")
+        };
+        let problem = Problem::new(ProblemKind::MethodNotAllowed, detail).with_extension("allow", json!(allowed));
+        return Err(problem.with_instance(&request.path));
+    }
+    let ok = |body: String| (200, body);
+    let result = match (segments.as_slice(), user) {
+        (["docs"], Some(user)) if request.method == "POST" => create_doc(server, request, user).map(|b| (201, b)),
+        (["docs", id, "fields"], Some(user)) => update_fields(server, request, id, user).map(ok),
+        (["docs"], _) => list_docs(server, request).map(ok),
+        (["docs", id], _) => get_doc(server, request, id).map(ok),
+        (["docs", id, "refs"], _) => doc_refs(server, request, id).map(ok),
+        (["graph"], _) => graph(server, request).map(ok),
+        _ => validate(server, request).map(ok),
+    };
+    result.map_err(|p| p.with_instance(&request.path))
+}
+
+/// `GET /docs`: `md-db-list`. Query parameters other than `pattern`, `sort`,
+/// `select`, `limit`, and `offset` filter on frontmatter fields.
+fn list_docs(server: &Server, request: &Request) -> Result<String, Problem> {
+    let mut args = query_args(request, &["pattern", "sort"], &["select"], &["limit", "offset"]);
+    let fields: Vec<String> = request
+        .query
+        .iter()
+        .filter(|(key, _)| !["pattern", "sort", "select", "limit", "offset"].contains(&key.as_str()))
+        .map(|(key, value)| format!("{key}={value}"))
+        .collect();
+    args.insert("fields".into(), json!(fields));
+    call(server, "md-db-list", args, ProblemKind::BadRequest)
+}
+
+/// `GET /docs/{id}`: `md-db-get` on the document with this ID, uid, or path.
+fn get_doc(server: &Server, request: &Request, id: &str) -> Result<String, Problem> {
+    let (_, path) = find_doc(server, id)?;
+    let mut args = query_args(request, &["field", "section", "cell"], &[], &["table"]);
+    if request.query.iter().any(|(key, value)| key == "frontmatter" && value != "false") {
+        args.insert("frontmatter".into(), json!(true));
+    }
+    args.insert("file".into(), json!(path.display().to_string()));
+    call(server, "md-db-get", args, ProblemKind::NotFound)
+}
+
+/// `GET /docs/{id}/refs`: `md-db-refs`, outgoing unless `mode=backlinks`.
+fn doc_refs(server: &Server, request: &Request, id: &str) -> Result<String, Problem> {
+    let (id, _) = find_doc(server, id)?;
+    let mut args = query_args(request, &[], &[], &["depth"]);
+    let backlinks = request.query.iter().any(|(key, value)| key == "mode" && value == "backlinks");
+    args.insert(if backlinks { "to" } else { "from" }.into(), json!(id));
+    call(server, "md-db-refs", args, ProblemKind::BadRequest)
+}
+
+/// `GET /graph`: `md-db-graph`.
+fn graph(server: &Server, request: &Request) -> Result<String, Problem> {
+    let args = query_args(request, &["type"], &["select"], &["limit", "offset"]);
+    call(server, "md-db-graph", args, ProblemKind::BadRequest)
+}
+
+/// `POST /validate`: `md-db-validate` with a JSON body of its arguments (`file`,
+/// `pattern`; the whole directory when empty), or one `text/markdown` document.
+fn validate(server: &Server, request: &Request) -> Result<String, Problem> {
+    let body = body_text(request)?;
+    let markdown = request
+        .content_type
+        .as_deref()
+        .is_some_and(|t| t.starts_with("text/markdown") || t.starts_with("text/plain"));
+    if markdown {
+        let schema = Schema::from_file(&server.schema).map_err(Problem::from)?;
+        let users = server.users.as_ref().map(UserConfig::from_file).transpose().map_err(Problem::from)?;
+        let doc = Document::from_str(body).map_err(|e| Problem::new(ProblemKind::BadRequest, e.to_string()))?;
+        let result = ValidationResult {
+            file_results: vec![validation::validate_document(
+                &doc,
+                &schema,
+                &HashSet::new(),
+                &HashSet::new(),
+                users.as_ref(),
+            )],
+        };
+        return Ok(serde_json::to_string_pretty(&mcp::validate_result_to_json(&result)).unwrap_or_default());
+    }
+
+    let mut args = json_body(body)?;
+    // The served schema and users always apply
+    args.retain(|key, _| key == "file" || key == "pattern");
+    if let Some(file) = args.get("file").and_then(Value::as_str).map(Path::new) {
+        let file = server.dir.join(file);
+        args.insert("file".into(), json!(file.display().to_string()));
+    }
+    call(server, "md-db-validate", args, ProblemKind::BadRequest)
+}
+
+/// `POST /docs`: `md-db-new` with `{"type": ..., "fields": {...}}`, the next
+/// ID of the type, and the template filled in. Writes are serialized so two
+/// requests can't take the same ID.
+fn create_doc(server: &Server, request: &Request, user: &str) -> Result<String, Problem> {
+    let _lock = md_db::undo::write_lock(&server.log_root);
+    let body = json_body(body_text(request)?)?;
+    let Some(doc_type) = body.get("type").and_then(Value::as_str) else {
+        return Err(Problem::new(ProblemKind::BadRequest, "missing \"type\""));
+    };
+    let fields: Vec<String> = match body.get("fields") {
+        None => Vec::new(),
+        Some(Value::Object(fields)) => fields
+            .iter()
+            .map(|(key, value)| match value {
+                Value::String(s) => format!("{key}={s}"),
+                other => format!("{key}={other}"),
+            })
+            .collect(),
+        Some(_) => return Err(Problem::new(ProblemKind::BadRequest, "\"fields\" must be an object")),
+    };
+    let mut args = Map::new();
+    args.insert("type".into(), json!(doc_type));
+    args.insert("fields".into(), json!(fields));
+    args.insert("fill".into(), json!(true));
+    args.insert("auto_id".into(), json!(true));
+    let out = call(server, "md-db-new", args, ProblemKind::BadRequest)?;
+
+    let created: Value = serde_json::from_str(&out).unwrap_or_default();
+    if let Some(path) = created["path"].as_str().map(PathBuf::from) {
+        let changes = audit::field_changes(&Value::Null, &frontmatter_json(&path));
+        record(server, AuditEntry::new(user, "create", &path_to_id(&path), &path, changes))?;
+    }
+    Ok(out)
+}
+
+/// `PATCH /docs/{id}/fields`: `md-db-set` with the body as a JSON Merge Patch
+/// of the frontmatter (`null` deletes a field). The workflow applies unless
+/// `force=true`.
+fn update_fields(server: &Server, request: &Request, id: &str, user: &str) -> Result<String, Problem> {
+    let _lock = md_db::undo::write_lock(&server.log_root);
+    let (id, path) = find_doc(server, id)?;
+    let patch = json_body(body_text(request)?)?;
+    let before = frontmatter_json(&path);
+    let mut args = Map::new();
+    args.insert("file".into(), json!(path.display().to_string()));
+    args.insert("patch".into(), Value::Object(patch));
+    if request.query.iter().any(|(key, value)| key == "force" && value != "false") {
+        args.insert("force".into(), json!(true));
+    }
+    let out = call(server, "md-db-set", args, ProblemKind::BadRequest)?;
+
+    let changes = audit::field_changes(&before, &frontmatter_json(&path));
+    if !changes.is_empty() {
+        record(server, AuditEntry::new(user, "update-fields", &id, &path, changes))?;
+    }
+    Ok(out)
+}
+
+fn body_text(request: &Request) -> Result<&str, Problem> {
+    std::str::from_utf8(&request.body).map_err(|_| Problem::new(ProblemKind::BadRequest, "body is not UTF-8"))
+}
+
+/// A JSON object body; an empty body is an empty object.
+fn json_body(body: &str) -> Result<Map<String, Value>, Problem> {
+    match serde_json::from_str::<Value>(body) {
+        _ if body.trim().is_empty() => Ok(Map::new()),
+        Ok(Value::Object(map)) => Ok(map),
+        Ok(_) => Err(Problem::new(ProblemKind::BadRequest, "body must be a JSON object")),
+        Err(e) => Err(Problem::new(ProblemKind::BadRequest, format!("invalid JSON: {e}"))),
+    }
+}
+
+fn frontmatter_json(path: &Path) -> Value {
+    Frontmatter::read_from_file(path).ok().flatten().map_or(Value::Null, |fm| fm.to_json())
+}
+
+/// Append to the audit log of the served directory. The change is already
+/// written, so a failure here is reported rather than hidden.
+fn record(server: &Server, entry: AuditEntry) -> Result<(), Problem> {
+    audit::append(&server.dir, &entry)
+        .and_then(|_| md_db::durability::sync_pending())
+        .map_err(|e| {
+            Problem::new(ProblemKind::Internal, format!("{} written, but the audit log failed: {e}", entry.id))
+        })
+}
+
+/// The display ID and path of a document in the served directory.
+fn find_doc(server: &Server, id: &str) -> Result<(String, PathBuf), Problem> {
+    let schema = Schema::from_file(&server.schema).map_err(Problem::from)?;
+    let graph = DocGraph::build_cached(&server.dir, &schema).map_err(Problem::from)?;
+    let id = resolve_id(&graph, id);
+    match graph.nodes.get(&id) {
+        Some(node) => Ok((id, node.path.clone())),
+        None => Err(Problem::from(Error::DocumentNotFound(id))),
+    }
+}
+
+/// Tool arguments from the query: strings, comma-separated lists, and integers.
+fn query_args(request: &Request, strings: &[&str], lists: &[&str], ints: &[&str]) -> Map<String, Value> {
+    let mut args = Map::new();
+    for (key, value) in &request.query {
+        let key = key.as_str();
+        let value = if strings.contains(&key) {
+            json!(value)
+        } else if lists.contains(&key) {
+            json!(value.split(',').map(str::trim).filter(|s| !s.is_empty()).collect::<Vec<_>>())
+        } else if ints.contains(&key) {
+            match value.parse::<u64>() {
+                Ok(n) => json!(n),
+                Err(_) => continue,
+            }
+        } else {
+            continue;
+        };
+        args.insert(key.to_string(), value);
+    }
+    args
+}
+
+/// Run an MCP tool on the served directory and schema; its error becomes a problem of `kind`.
+fn call(server: &Server, tool: &str, mut args: Map<String, Value>, kind: ProblemKind) -> Result<String, Problem> {
+    args.insert("dir".into(), json!(server.dir.display().to_string()));
+    args.insert("schema".into(), json!(server.schema.display().to_string()));
+    if let Some(ref users) = server.users {
+        args.insert("users".into(), json!(users.display().to_string()));
+    }
+    mcp::guarded_call(&server.config, &[], tool, &Value::Object(args)).map_err(|e| Problem::new(kind, e))
+}
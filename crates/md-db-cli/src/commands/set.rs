@@ -2,6 +2,8 @@ use std::path::PathBuf;
 
 use clap::Args;
 use md_db::document::Document;
+use md_db::schema::Schema;
+use md_db::undo::Recorder;
 
 #[derive(Debug, Args)]
 pub struct SetArgs {
@@ -12,10 +14,19 @@ pub struct SetArgs {
     #[arg(long = "field")]
     pub fields: Vec<String>,
 
+    /// JSON Merge Patch (RFC 7386) for the frontmatter: null deletes a key, objects merge.
+    /// Applied before --field. Use @path to read the patch from a file, - for stdin
+    #[arg(long, value_name = "JSON")]
+    pub patch: Option<String>,
+
     /// Target section heading
     #[arg(long)]
     pub section: Option<String>,
 
+    /// Treat --section as a heading pattern (e.g. "Day \d+") and use the Nth match (0-based)
+    #[arg(long)]
+    pub nth: Option<usize>,
+
     /// Replace section content with this text
     #[arg(long)]
     pub content: Option<String>,
@@ -47,10 +58,35 @@ pub struct SetArgs {
     /// Print result to stdout instead of writing file
     #[arg(long)]
     pub dry_run: bool,
+
+    /// Path to KDL schema file, or `auto`; status changes are checked against its `workflow`
+    #[arg(long, default_value = "auto")]
+    pub schema: PathBuf,
+
+    /// Allow a status change the type's workflow forbids
+    #[arg(long)]
+    pub force: bool,
 }
 
 pub fn run(args: &SetArgs) -> Result<(), Box<dyn std::error::Error>> {
     let mut doc = Document::from_file(&args.file)?;
+    let before = doc.frontmatter.clone();
+
+    // --patch '{"status":"accepted"}'
+    if let Some(ref patch) = args.patch {
+        let json = if patch == "-" {
+            let mut buf = String::new();
+            std::io::Read::read_to_string(&mut std::io::stdin(), &mut buf)?;
+            buf
+        } else if let Some(path) = patch.strip_prefix('@') {
+            std::fs::read_to_string(path)?
+        } else {
+            patch.clone()
+        };
+        let value: serde_json::Value =
+            serde_json::from_str(&json).map_err(|e| format!("invalid --patch JSON: {e}"))?;
+        doc.merge_patch(&value)?;
+    }
 
     // --field key=value
     for field_str in &args.fields {
@@ -69,7 +105,12 @@ pub fn run(args: &SetArgs) -> Result<(), Box<dyn std::error::Error>> {
     }
 
     // --section operations
-    if let Some(ref heading) = args.section {
+    if let Some(ref section) = args.section {
+        let heading = &match args.nth {
+            Some(n) => doc.nth_matching_heading(section, n)?,
+            None => section.clone(),
+        };
+
         // --content: replace section content
         if let Some(ref content) = args.content {
             doc.replace_section_content(heading, &format!("{content}\n"))?;
@@ -100,10 +141,24 @@ pub fn run(args: &SetArgs) -> Result<(), Box<dyn std::error::Error>> {
         }
     }
 
+    if let (Some(before), Some(after)) = (&before, &doc.frontmatter) {
+        if let Some(schema_path) = md_db::project::resolve_optional_schema(&args.schema, &args.file)? {
+            if let Some(msg) = Schema::from_file(schema_path)?.transition_violation(before, after) {
+                if !args.force {
+                    return Err(format!("{}: {msg}; pass --force to override", args.file.display()).into());
+                }
+                eprintln!("warning: {}: {msg} (forced)", args.file.display());
+            }
+        }
+    }
+
     if args.dry_run {
         print!("{}", doc.raw);
     } else {
+        let mut undo = Recorder::new("set");
+        undo.track(&args.file);
         doc.save()?;
+        undo.finish()?;
     }
 
     Ok(())
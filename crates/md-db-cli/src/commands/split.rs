@@ -0,0 +1,83 @@
+use std::path::{Path, PathBuf};
+
+use clap::Args;
+use md_db::schema::Schema;
+use md_db::split::{self, SplitOptions};
+use md_db::undo::Recorder;
+
+#[derive(Debug, Args)]
+pub struct SplitArgs {
+    /// Document to take the section from
+    pub file: PathBuf,
+
+    /// Heading of the section to move (its subsections go with it)
+    #[arg(long)]
+    pub section: String,
+
+    /// Document type of the new document
+    #[arg(long = "type")]
+    pub doc_type: String,
+
+    /// Relation field on the new document that points back at the original
+    #[arg(long, default_value = "parent")]
+    pub relation: String,
+
+    /// Title of the new document (defaults to the section heading)
+    #[arg(long)]
+    pub title: Option<String>,
+
+    /// Directory scanned for the next free ID; the type's folder is relative to it
+    #[arg(long, default_value = ".")]
+    pub dir: PathBuf,
+
+    /// Path to the KDL schema file, or `auto` to use the closest one above the file
+    #[arg(long, default_value = "auto")]
+    pub schema: PathBuf,
+
+    /// Print both documents instead of writing them
+    #[arg(long)]
+    pub dry_run: bool,
+}
+
+pub fn run(args: &SplitArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let schema_path = md_db::project::resolve_schema(&args.schema, &args.file)?;
+    let schema = Schema::from_file(&schema_path)?;
+    let schema_dir = schema_path.parent().unwrap_or(Path::new("."));
+    let opts = SplitOptions {
+        section: &args.section,
+        doc_type: &args.doc_type,
+        relation: &args.relation,
+        title: args.title.as_deref(),
+    };
+    let split = split::plan_split(&args.file, &args.dir, &schema, schema_dir, &opts)?;
+
+    if args.dry_run {
+        println!("--- {}", args.file.display());
+        print!("{}", split.parent.raw);
+        println!("--- {} (new)", split.child_path.display());
+        print!("{}", split.child.raw);
+        return Ok(());
+    }
+
+    if let Some(parent) = split.child_path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut undo = Recorder::new("split");
+    undo.track(&args.file);
+    undo.track(&split.child_path);
+    let result = split.child.save().and_then(|()| split.parent.save());
+    undo.finish()?;
+    result?;
+
+    eprintln!(
+        "{}: moved \"{}\" to {} ({})",
+        split.parent_id,
+        args.section,
+        split.child_id,
+        split.child_path.display()
+    );
+    if !split.copied.is_empty() {
+        eprintln!("copied fields: {}", split.copied.join(", "));
+    }
+    Ok(())
+}
@@ -1,9 +1,14 @@
 use std::collections::{BTreeMap, HashMap};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
 
 use clap::Args;
+use md_db::aggregate;
+use md_db::capacity;
 use md_db::document::Document;
+use md_db::frontmatter::Frontmatter;
 use md_db::graph::DocGraph;
+use md_db::output::OutputFormat;
 use md_db::schema::Schema;
 use md_db::users::UserConfig;
 use md_db::validation;
@@ -13,21 +18,44 @@ pub struct StatsArgs {
     /// Directory containing markdown files
     pub dir: PathBuf,
 
-    /// Path to KDL schema file
-    #[arg(long)]
+    /// Path to KDL schema file, or `auto` to use the closest one above the target
+    #[arg(long, default_value = "auto")]
     pub schema: PathBuf,
 
     /// Path to user/team config YAML file
     #[arg(long)]
     pub users: Option<PathBuf>,
 
-    /// Output format: text, json, auto (auto=json when piped)
+    /// Output format: text, json, auto (auto=json when piped); markdown with --group-by or --metrics
     #[arg(long, default_value = "auto")]
     pub format: String,
+
+    /// Count documents per value of these frontmatter fields (comma-separated, e.g. type,severity)
+    /// instead of printing the dashboard
+    #[arg(long, value_name = "FIELDS")]
+    pub group_by: Option<String>,
+
+    /// Numeric fields to summarize per group with min, avg, and max (comma-separated)
+    #[arg(long, value_name = "FIELDS")]
+    pub metrics: Option<String>,
+
+    /// Report ID numbering and max_count headroom per type, with growth forecasts and warnings
+    #[arg(long, conflicts_with_all = ["group_by", "metrics"])]
+    pub capacity: bool,
+
+    /// With --capacity: warn about limits expected within this many months
+    #[arg(long, default_value = "12", requires = "capacity")]
+    pub horizon: u32,
 }
 
 pub fn run(args: &StatsArgs) -> Result<(), Box<dyn std::error::Error>> {
-    let schema = Schema::from_file(&args.schema)?;
+    if args.group_by.is_some() || args.metrics.is_some() {
+        return run_aggregate(args);
+    }
+    if args.capacity {
+        return run_capacity(args);
+    }
+    let schema = Schema::from_file(md_db::project::resolve_schema(&args.schema, &args.dir)?)?;
     let user_config = match &args.users {
         Some(path) => Some(UserConfig::from_file(path)?),
         None => None,
@@ -36,232 +64,379 @@ pub fn run(args: &StatsArgs) -> Result<(), Box<dyn std::error::Error>> {
     let format = md_db::output::OutputFormat::from_str(&args.format)
         .unwrap_or(md_db::output::OutputFormat::Text);
 
-    // Build graph
-    let graph = DocGraph::build(&args.dir, &schema)?;
-
-    // Run validation
-    let validation_result =
-        validation::validate_directory(&args.dir, &schema, None, user_config.as_ref())?;
-
-    // Aggregate by_type: { type_name -> { total, by_status: { status -> count } } }
-    let mut by_type: BTreeMap<String, TypeStats> = BTreeMap::new();
-    let files = md_db::discovery::discover_files(&args.dir, None, &[], false)?;
-    for path in &files {
-        let doc = match Document::from_file(path) {
-            Ok(d) => d,
-            Err(_) => continue,
-        };
-        let fm = match &doc.frontmatter {
-            Some(fm) => fm,
-            None => continue,
-        };
-        let type_name = match fm.get_display("type") {
-            Some(t) => t,
-            None => continue,
-        };
-        let entry = by_type.entry(type_name).or_insert_with(TypeStats::default);
-        entry.total += 1;
-        if let Some(status) = fm.get_display("status") {
-            *entry.by_status.entry(status).or_insert(0) += 1;
+    let dashboard = Dashboard::build(&args.dir, &schema, user_config.as_ref())?;
+    match format {
+        md_db::output::OutputFormat::Json => {
+            println!("{}", serde_json::to_string_pretty(&dashboard.to_json())?);
         }
+        _ => dashboard.print_text(),
     }
 
-    let total_docs = by_type.values().map(|t| t.total).sum::<usize>();
+    Ok(())
+}
 
-    // Validation summary
-    let ok_count = validation_result
-        .file_results
-        .iter()
-        .filter(|fr| fr.errors() == 0)
-        .count();
-    let error_file_count = validation_result
-        .file_results
-        .iter()
-        .filter(|fr| fr.errors() > 0)
-        .count();
+/// The numbers behind the dashboard, shared by `md-db stats` and the MCP `md-db-stats` tool.
+pub(super) struct Dashboard {
+    by_type: BTreeMap<String, TypeStats>,
+    total_docs: usize,
+    ok_count: usize,
+    error_file_count: usize,
+    by_code: BTreeMap<String, usize>,
+    /// Only with a schema `prose` block.
+    prose: Option<md_db::prose::ProseStats>,
+    prose_warnings: usize,
+    node_count: usize,
+    edge_count: usize,
+    orphans: usize,
+    /// ID and backlink count.
+    most_referenced: Option<(String, usize)>,
+    /// ID and outgoing ref count.
+    most_referencing: Option<(String, usize)>,
+    oldest: Option<(String, SystemTime)>,
+    newest: Option<(String, SystemTime)>,
+}
 
-    let mut by_code: BTreeMap<String, usize> = BTreeMap::new();
-    for fr in &validation_result.file_results {
-        for d in &fr.diagnostics {
-            *by_code.entry(d.code.clone()).or_insert(0) += 1;
+impl Dashboard {
+    pub(super) fn build(
+        dir: &Path,
+        schema: &Schema,
+        user_config: Option<&UserConfig>,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        let graph = DocGraph::build(dir, schema)?;
+        let validation_result = validation::validate_directory(dir, schema, None, user_config)?;
+
+        // Aggregate by_type: { type_name -> { total, by_status: { status -> count } } }
+        let mut by_type: BTreeMap<String, TypeStats> = BTreeMap::new();
+        let mut prose = md_db::prose::ProseStats::default();
+        let files = md_db::discovery::discover_files(dir, None, &[], false)?;
+        for path in &files {
+            let doc = match Document::from_file(path) {
+                Ok(d) => d,
+                Err(_) => continue,
+            };
+            let fm = match &doc.frontmatter {
+                Some(fm) => fm,
+                None => continue,
+            };
+            let type_name = match fm.get_display("type") {
+                Some(t) => t,
+                None => continue,
+            };
+            if schema.prose.is_some() {
+                if let Some(type_def) = schema.get_type(&type_name) {
+                    for (_, text) in md_db::prose::required_section_texts(&doc, type_def) {
+                        prose.add(&md_db::prose::analyze(&text));
+                    }
+                }
+            }
+            let status_field = schema.status_field(&type_name);
+            let entry = by_type.entry(type_name).or_insert_with(TypeStats::default);
+            entry.total += 1;
+            if let Some(status) = fm.get_display(&status_field.field) {
+                if status_field.is_active(&status) {
+                    entry.active += 1;
+                } else if status_field.is_terminal(&status) {
+                    entry.terminal += 1;
+                } else {
+                    entry.open += 1;
+                }
+                *entry.by_status.entry(status).or_insert(0) += 1;
+            }
         }
-    }
 
-    // Graph stats
-    let node_count = graph.nodes.len();
-    let edge_count = graph.edges.len();
-
-    // Orphans: nodes with 0 in + 0 out edges
-    let mut in_degree: HashMap<&str, usize> = HashMap::new();
-    let mut out_degree: HashMap<&str, usize> = HashMap::new();
-    for edge in &graph.edges {
-        *out_degree.entry(edge.from.as_str()).or_insert(0) += 1;
-        *in_degree.entry(edge.to.as_str()).or_insert(0) += 1;
-    }
-    let orphans: Vec<&str> = graph
-        .nodes
-        .keys()
-        .filter(|id| {
-            in_degree.get(id.as_str()).copied().unwrap_or(0) == 0
-                && out_degree.get(id.as_str()).copied().unwrap_or(0) == 0
-        })
-        .map(|s| s.as_str())
-        .collect();
+        let total_docs = by_type.values().map(|t| t.total).sum::<usize>();
+
+        // Validation summary
+        let ok_count = validation_result
+            .file_results
+            .iter()
+            .filter(|fr| fr.errors() == 0)
+            .count();
+        let error_file_count = validation_result
+            .file_results
+            .iter()
+            .filter(|fr| fr.errors() > 0)
+            .count();
+
+        let mut by_code: BTreeMap<String, usize> = BTreeMap::new();
+        for fr in &validation_result.file_results {
+            for d in &fr.diagnostics {
+                *by_code.entry(d.code.clone()).or_insert(0) += 1;
+            }
+        }
 
-    // Most referenced (highest in-degree)
-    let most_referenced = graph
-        .nodes
-        .keys()
-        .max_by_key(|id| in_degree.get(id.as_str()).copied().unwrap_or(0))
-        .filter(|id| in_degree.get(id.as_str()).copied().unwrap_or(0) > 0);
-
-    // Most referencing (highest out-degree)
-    let most_referencing = graph
-        .nodes
-        .keys()
-        .max_by_key(|id| out_degree.get(id.as_str()).copied().unwrap_or(0))
-        .filter(|id| out_degree.get(id.as_str()).copied().unwrap_or(0) > 0);
-
-    // Staleness: oldest and newest by file mtime
-    let mut file_times: Vec<(&str, std::time::SystemTime, &PathBuf)> = Vec::new();
-    for (id, node) in &graph.nodes {
-        if let Ok(meta) = std::fs::metadata(&node.path) {
-            if let Ok(mtime) = meta.modified() {
-                file_times.push((id.as_str(), mtime, &node.path));
+        let prose_warnings = by_code
+            .iter()
+            .filter(|(code, _)| code.starts_with('P'))
+            .map(|(_, count)| count)
+            .sum::<usize>();
+
+        // Orphans: nodes with 0 in + 0 out edges
+        let mut in_degree: HashMap<&str, usize> = HashMap::new();
+        let mut out_degree: HashMap<&str, usize> = HashMap::new();
+        for edge in &graph.edges {
+            *out_degree.entry(edge.from.as_str()).or_insert(0) += 1;
+            *in_degree.entry(edge.to.as_str()).or_insert(0) += 1;
+        }
+        let orphans = graph
+            .nodes
+            .keys()
+            .filter(|id| {
+                in_degree.get(id.as_str()).copied().unwrap_or(0) == 0
+                    && out_degree.get(id.as_str()).copied().unwrap_or(0) == 0
+            })
+            .count();
+
+        // Highest in-degree and out-degree
+        let busiest = |degree: &HashMap<&str, usize>| {
+            graph
+                .nodes
+                .keys()
+                .map(|id| (id.clone(), degree.get(id.as_str()).copied().unwrap_or(0)))
+                .max_by_key(|(_, count)| *count)
+                .filter(|(_, count)| *count > 0)
+        };
+        let most_referenced = busiest(&in_degree);
+        let most_referencing = busiest(&out_degree);
+
+        // Staleness: oldest and newest by file mtime. Retired documents (terminal
+        // status) are expected to stay unchanged, so they don't count.
+        let mut file_times: Vec<(String, SystemTime)> = Vec::new();
+        for (id, node) in &graph.nodes {
+            let retired = match (&node.doc_type, &node.status) {
+                (Some(t), Some(s)) => schema.status_field(t).is_terminal(s),
+                _ => false,
+            };
+            if retired {
+                continue;
+            }
+            if let Ok(meta) = std::fs::metadata(&node.path) {
+                if let Ok(mtime) = meta.modified() {
+                    file_times.push((id.clone(), mtime));
+                }
             }
         }
+        file_times.sort_by_key(|(_, t)| *t);
+
+        Ok(Self {
+            by_type,
+            total_docs,
+            ok_count,
+            error_file_count,
+            by_code,
+            prose: schema.prose.is_some().then_some(prose),
+            prose_warnings,
+            node_count: graph.nodes.len(),
+            edge_count: graph.edges.len(),
+            orphans,
+            most_referenced,
+            most_referencing,
+            oldest: file_times.first().cloned(),
+            newest: file_times.last().cloned(),
+        })
     }
-    file_times.sort_by_key(|(_, t, _)| *t);
-
-    let oldest = file_times.first();
-    let newest = file_times.last();
 
-    match format {
-        md_db::output::OutputFormat::Json => {
-            let mut json = serde_json::Map::new();
-            json.insert("total_docs".into(), serde_json::json!(total_docs));
-
-            // by_type
-            let bt: serde_json::Map<String, serde_json::Value> = by_type
-                .iter()
-                .map(|(name, stats)| {
-                    (
-                        name.clone(),
-                        serde_json::json!({
-                            "total": stats.total,
-                            "by_status": stats.by_status,
-                        }),
-                    )
-                })
-                .collect();
-            json.insert("by_type".into(), serde_json::Value::Object(bt));
-
-            // validation
+    pub(super) fn to_json(&self) -> serde_json::Value {
+        let mut json = serde_json::Map::new();
+        json.insert("total_docs".into(), serde_json::json!(self.total_docs));
+
+        // by_type
+        let bt: serde_json::Map<String, serde_json::Value> = self
+            .by_type
+            .iter()
+            .map(|(name, stats)| {
+                (
+                    name.clone(),
+                    serde_json::json!({
+                        "total": stats.total,
+                        "by_status": stats.by_status,
+                        "open": stats.open,
+                        "active": stats.active,
+                        "terminal": stats.terminal,
+                    }),
+                )
+            })
+            .collect();
+        json.insert("by_type".into(), serde_json::Value::Object(bt));
+
+        // validation
+        json.insert(
+            "validation".into(),
+            serde_json::json!({
+                "ok": self.ok_count,
+                "errors": self.error_file_count,
+                "by_code": self.by_code,
+            }),
+        );
+
+        // prose
+        if let Some(ref prose) = self.prose {
             json.insert(
-                "validation".into(),
+                "prose".into(),
                 serde_json::json!({
-                    "ok": ok_count,
-                    "errors": error_file_count,
-                    "by_code": by_code,
+                    "sentences": prose.sentences,
+                    "words": prose.words,
+                    "passive_ratio": prose.passive_ratio(),
+                    "reading_ease": prose.reading_ease(),
+                    "longest_sentence": prose.longest,
+                    "warnings": self.prose_warnings,
                 }),
             );
+        }
+
+        // graph
+        let mut graph_obj = serde_json::json!({
+            "nodes": self.node_count,
+            "edges": self.edge_count,
+            "orphans": self.orphans,
+        });
+        if let Some((ref id, count)) = self.most_referenced {
+            graph_obj["most_referenced"] = serde_json::json!({ "id": id, "backlinks": count });
+        }
+        if let Some((ref id, count)) = self.most_referencing {
+            graph_obj["most_referencing"] = serde_json::json!({ "id": id, "outgoing": count });
+        }
+        json.insert("graph".into(), graph_obj);
 
-            // graph
-            let mut graph_obj = serde_json::json!({
-                "nodes": node_count,
-                "edges": edge_count,
-                "orphans": orphans.len(),
-            });
-            if let Some(id) = most_referenced {
-                graph_obj["most_referenced"] = serde_json::json!({
+        // staleness
+        let mut staleness = serde_json::Map::new();
+        if let Some((ref id, ref time)) = self.oldest {
+            staleness.insert(
+                "oldest".into(),
+                serde_json::json!({
                     "id": id,
-                    "backlinks": in_degree.get(id.as_str()).copied().unwrap_or(0),
-                });
-            }
-            if let Some(id) = most_referencing {
-                graph_obj["most_referencing"] = serde_json::json!({
+                    "date": format_system_time(time),
+                }),
+            );
+        }
+        if let Some((ref id, ref time)) = self.newest {
+            staleness.insert(
+                "newest".into(),
+                serde_json::json!({
                     "id": id,
-                    "outgoing": out_degree.get(id.as_str()).copied().unwrap_or(0),
-                });
-            }
-            json.insert("graph".into(), graph_obj);
+                    "date": format_system_time(time),
+                }),
+            );
+        }
+        json.insert("staleness".into(), serde_json::Value::Object(staleness));
+        serde_json::Value::Object(json)
+    }
 
-            // staleness
-            let mut staleness = serde_json::Map::new();
-            if let Some((id, time, _)) = oldest {
-                staleness.insert(
-                    "oldest".into(),
-                    serde_json::json!({
-                        "id": id,
-                        "date": format_system_time(time),
-                    }),
-                );
-            }
-            if let Some((id, time, _)) = newest {
-                staleness.insert(
-                    "newest".into(),
-                    serde_json::json!({
-                        "id": id,
-                        "date": format_system_time(time),
-                    }),
+    fn print_text(&self) {
+        println!("Documents: {}", self.total_docs);
+        for (name, stats) in &self.by_type {
+            let status_parts: Vec<String> = stats
+                .by_status
+                .iter()
+                .map(|(s, c)| format!("{c} {s}"))
+                .collect();
+            if status_parts.is_empty() {
+                println!("  {name}: {}", stats.total);
+            } else {
+                println!(
+                    "  {name}: {} ({}) — {} open, {} active, {} terminal",
+                    stats.total,
+                    status_parts.join(", "),
+                    stats.open,
+                    stats.active,
+                    stats.terminal
                 );
             }
-            json.insert("staleness".into(), serde_json::Value::Object(staleness));
+        }
 
-            println!(
-                "{}",
-                serde_json::to_string_pretty(&serde_json::Value::Object(json))?
-            );
+        println!();
+        println!("Validation: {} ok, {} with errors", self.ok_count, self.error_file_count);
+        for (code, count) in &self.by_code {
+            println!("  {code}: {count}");
         }
-        _ => {
-            // Text dashboard
-            println!("Documents: {total_docs}");
-            for (name, stats) in &by_type {
-                let status_parts: Vec<String> = stats
-                    .by_status
-                    .iter()
-                    .map(|(s, c)| format!("{c} {s}"))
-                    .collect();
-                if status_parts.is_empty() {
-                    println!("  {name}: {}", stats.total);
-                } else {
-                    println!(
-                        "  {name}: {} ({})",
-                        stats.total,
-                        status_parts.join(", ")
-                    );
-                }
-            }
 
+        if let Some(ref prose) = self.prose {
             println!();
-            println!("Validation: {ok_count} ok, {error_file_count} with errors");
-            for (code, count) in &by_code {
-                println!("  {code}: {count}");
+            println!(
+                "Prose: {} sentences, {:.0}% passive, longest {} words, {} warnings",
+                prose.sentences,
+                prose.passive_ratio() * 100.0,
+                prose.longest,
+                self.prose_warnings
+            );
+            if let Some(score) = prose.reading_ease() {
+                println!("  Reading ease: {score:.0}");
             }
+        }
 
-            println!();
-            println!("Graph: {node_count} nodes, {edge_count} edges");
-            println!("  Orphans (no refs in or out): {}", orphans.len());
-            if let Some(id) = most_referenced {
-                let count = in_degree.get(id.as_str()).copied().unwrap_or(0);
-                println!("  Most referenced: {id} ({count} backlinks)");
-            }
-            if let Some(id) = most_referencing {
-                let count = out_degree.get(id.as_str()).copied().unwrap_or(0);
-                println!("  Most referencing: {id} ({count} outgoing)");
-            }
+        println!();
+        println!("Graph: {} nodes, {} edges", self.node_count, self.edge_count);
+        println!("  Orphans (no refs in or out): {}", self.orphans);
+        if let Some((ref id, count)) = self.most_referenced {
+            println!("  Most referenced: {id} ({count} backlinks)");
+        }
+        if let Some((ref id, count)) = self.most_referencing {
+            println!("  Most referencing: {id} ({count} outgoing)");
+        }
 
-            println!();
-            println!("Staleness:");
-            if let Some((id, time, _)) = oldest {
-                println!("  Oldest unchanged: {id} ({})", format_system_time(time));
+        println!();
+        println!("Staleness:");
+        if let Some((ref id, ref time)) = self.oldest {
+            println!("  Oldest unchanged (not retired): {id} ({})", format_system_time(time));
+        }
+        if let Some((ref id, ref time)) = self.newest {
+            println!("  Newest: {id} ({})", format_system_time(time));
+        }
+    }
+}
+
+/// `--group-by` / `--metrics`: a table of groups instead of the dashboard. Needs no schema.
+fn run_aggregate(args: &StatsArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let list = |spec: &Option<String>| -> Vec<String> {
+        spec.iter()
+            .flat_map(|s| s.split(','))
+            .map(|f| f.trim().to_string())
+            .filter(|f| !f.is_empty())
+            .collect()
+    };
+    let docs: Vec<Frontmatter> = md_db::discovery::discover_files(&args.dir, None, &[], false)?
+        .iter()
+        .filter_map(|path| Frontmatter::read_from_file(path).ok().flatten())
+        .collect();
+    let aggregation = aggregate::aggregate(&docs, &list(&args.group_by), &list(&args.metrics));
+
+    match OutputFormat::from_str(&args.format).unwrap_or(OutputFormat::Text) {
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&aggregation)?),
+        OutputFormat::Markdown => print!("{}", aggregation.to_table().to_markdown()),
+        _ => print!("{}", aggregation.to_table().to_text()),
+    }
+    Ok(())
+}
+
+fn run_capacity(args: &StatsArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let schema = Schema::from_file(md_db::project::resolve_schema(&args.schema, &args.dir)?)?;
+    let docs: Vec<(String, Frontmatter)> = md_db::discovery::discover_files(&args.dir, None, &[], false)?
+        .iter()
+        .filter_map(|path| {
+            let fm = Frontmatter::read_from_file(path).ok().flatten()?;
+            Some((md_db::graph::path_to_id(path), fm))
+        })
+        .collect();
+    let report = capacity::capacity(&docs, &schema, md_db::health::today(), f64::from(args.horizon));
+
+    match OutputFormat::from_str(&args.format).unwrap_or(OutputFormat::Text) {
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&report)?),
+        format => {
+            let table = capacity::to_table(&report);
+            if format == OutputFormat::Markdown {
+                print!("{}", table.to_markdown());
+            } else {
+                print!("{}", table.to_text());
+            }
+            let warnings: Vec<&String> = report.iter().flat_map(|c| &c.warnings).collect();
+            if !warnings.is_empty() {
+                println!();
             }
-            if let Some((id, time, _)) = newest {
-                println!("  Newest: {id} ({})", format_system_time(time));
+            for warning in warnings {
+                println!("warning: {warning}");
             }
         }
     }
-
     Ok(())
 }
 
@@ -269,6 +444,10 @@ pub fn run(args: &StatsArgs) -> Result<(), Box<dyn std::error::Error>> {
 struct TypeStats {
     total: usize,
     by_status: BTreeMap<String, usize>,
+    /// Counts by the type's status-field meaning: neither active nor terminal, active, terminal.
+    open: usize,
+    active: usize,
+    terminal: usize,
 }
 
 fn format_system_time(time: &std::time::SystemTime) -> String {
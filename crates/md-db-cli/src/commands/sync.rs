@@ -1,48 +1,263 @@
 use std::path::PathBuf;
 
 use clap::Args;
+use md_db::document::Document;
+use md_db::frontmatter::yaml_value_to_string;
+use md_db::graph::{path_to_id, DocGraph};
+use md_db::issue_sync::{self, GitHubIssues, IssueState, IssueSyncOptions};
+use md_db::promote;
 use md_db::schema::Schema;
 use md_db::sync;
+use md_db::undo::Recorder;
 
 #[derive(Debug, Args)]
 pub struct SyncArgs {
-    /// Directory containing markdown files
+    /// Directory containing markdown files, or `github-issues` to sync action items with issues
     pub dir: PathBuf,
 
-    /// Path to KDL schema file
-    #[arg(long)]
+    /// Directory containing markdown files (after `github-issues`)
+    pub target: Option<PathBuf>,
+
+    /// Path to KDL schema file, or `auto` to use the closest one above the target
+    #[arg(long, default_value = "auto")]
     pub schema: PathBuf,
 
     /// Show what would change without writing files
     #[arg(long)]
     pub dry_run: bool,
 
+    /// Report missing inverse refs (R030) and, with --prune, stale ones (R031) as
+    /// diagnostics without writing; exits 1 if any
+    #[arg(long)]
+    pub check: bool,
+
+    /// Remove inverse refs whose document no longer links back, instead of adding the forward ref
+    #[arg(long)]
+    pub prune: bool,
+
     /// Output format: text, json
     #[arg(long, default_value = "text")]
     pub format: String,
+
+    /// Rebuild the graph from scratch instead of using the .md-db/graph.bin snapshot
+    #[arg(long)]
+    pub no_cache: bool,
+    /// Also copy schema `promote` values from the body (table cells, yaml blocks) into frontmatter
+    #[arg(long)]
+    pub promote: bool,
+
+    /// github-issues: repository as owner/name (defaults to the repository of the current directory)
+    #[arg(long)]
+    pub repo: Option<String>,
+
+    /// github-issues: section holding the action item table
+    #[arg(long, default_value = "Action Items")]
+    pub section: String,
+
+    /// github-issues: only documents of this type
+    #[arg(long = "type")]
+    pub doc_type: Option<String>,
+
+    /// github-issues: column with the action (defaults to the first column)
+    #[arg(long)]
+    pub title_column: Option<String>,
+
+    /// github-issues: column the issue number is written to, added when missing
+    #[arg(long, default_value = "Issue")]
+    pub issue_column: String,
+
+    /// github-issues: Status values that mean done (comma-separated); the first is written for closed issues
+    #[arg(long, default_value = "done,closed")]
+    pub done: String,
+
+    /// github-issues: Status written when an issue is reopened
+    #[arg(long, default_value = "open")]
+    pub open_value: String,
+
+    /// github-issues: label for created issues (repeatable)
+    #[arg(long)]
+    pub label: Vec<String>,
+
+    /// github-issues: URL prefix for the document link in issue bodies, e.g. https://github.com/org/repo/blob/main
+    #[arg(long)]
+    pub link_base: Option<String>,
 }
 
 pub fn run(args: &SyncArgs) -> Result<(), Box<dyn std::error::Error>> {
-    let schema = Schema::from_file(&args.schema)?;
-    let plan = sync::compute_sync_plan(&args.dir, &schema)?;
+    if args.dir.as_os_str() == "github-issues" {
+        return run_github_issues(args);
+    }
+    if args.target.is_some() {
+        return Err("a second directory is only taken after `github-issues`".into());
+    }
+    let schema = Schema::from_file(md_db::project::resolve_schema(&args.schema, &args.dir)?)?;
+    let graph = if args.no_cache {
+        DocGraph::build(&args.dir, &schema)?
+    } else {
+        DocGraph::build_cached(&args.dir, &schema)?
+    };
+    let plan = sync::plan_from_graph_with(&graph, &schema, args.prune);
+    if args.check {
+        return check(&plan, &args.format);
+    }
+    let promotions = if args.promote {
+        let files = md_db::discovery::discover_files(&args.dir, None, &[], false)?;
+        promote::plan_promotions(&files, &schema)
+    } else {
+        Vec::new()
+    };
 
     match args.format.as_str() {
         "json" => {
-            println!("{}", serde_json::to_string_pretty(&plan.to_json())?);
+            let mut result = plan.to_json();
+            if args.promote {
+                let items: Vec<serde_json::Value> = promotions.iter().map(|p| p.to_json()).collect();
+                result["promotions"] = serde_json::json!(items);
+                result["promotion_count"] = serde_json::json!(items.len());
+            }
+            println!("{}", serde_json::to_string_pretty(&result)?);
         }
         _ => {
             print!("{}", plan.to_report());
+            if args.promote {
+                print!("{}", promotion_report(&promotions));
+            }
         }
     }
 
-    if !args.dry_run && !plan.is_empty() {
+    let changed = !plan.is_empty() || !promotions.is_empty();
+    if !args.dry_run && changed {
+        let mut undo = Recorder::new("sync");
+        undo.track_all(plan.actions.iter().map(|a| &a.path));
+        undo.track_all(promotions.iter().map(|p| &p.path));
         sync::apply_sync_plan(&plan)?;
+        // Relation sync may have rewritten the same files; promotions re-read them
+        promote::apply_promotions(&promotions)?;
+        undo.finish()?;
         if args.format != "json" {
             println!("Done.");
         }
-    } else if args.dry_run && !plan.is_empty() && args.format != "json" {
+    } else if args.dry_run && changed && args.format != "json" {
         println!("Dry run — no files modified.");
     }
 
     Ok(())
 }
+
+fn run_github_issues(args: &SyncArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let dir = args.target.clone().unwrap_or_else(|| PathBuf::from("."));
+    let done: Vec<String> = args.done.split(',').map(|v| v.trim().to_string()).filter(|v| !v.is_empty()).collect();
+    let opts = IssueSyncOptions {
+        section: &args.section,
+        title_column: args.title_column.as_deref(),
+        issue_column: &args.issue_column,
+        done_values: &done,
+        open_value: &args.open_value,
+        link_base: args.link_base.as_deref(),
+        dry_run: args.dry_run,
+    };
+    let mut tracker = GitHubIssues {
+        repo: args.repo.clone(),
+        labels: args.label.clone(),
+    };
+    let mut state = IssueState::open(&dir)?;
+
+    let mut changed = Vec::new();
+    let mut items = Vec::new();
+    for path in md_db::discovery::discover_files(&dir, None, &[], false)? {
+        let Ok(mut doc) = Document::from_file(&path) else { continue };
+        let doc_type = doc.frontmatter.as_ref().and_then(|fm| fm.get_display("type"));
+        if args.doc_type.as_ref().is_some_and(|t| doc_type.as_ref() != Some(t)) {
+            continue;
+        }
+        let before = doc.raw.clone();
+        let result = issue_sync::sync_document(&mut doc, &path_to_id(&path), &mut tracker, &mut state, &opts);
+        // Keep what was synced before a failure so the next run does not create duplicates
+        if !args.dry_run && doc.raw != before {
+            changed.push(doc);
+        }
+        match result {
+            Ok(changes) => items.extend(changes),
+            Err(e) => {
+                save_issue_sync(&changed, &state)?;
+                return Err(format!("{}: {e}", path.display()).into());
+            }
+        }
+    }
+    if !args.dry_run {
+        save_issue_sync(&changed, &state)?;
+    }
+
+    if args.format == "json" {
+        let items: Vec<serde_json::Value> = items
+            .iter()
+            .map(|item| {
+                let actions: Vec<String> = item.actions.iter().map(|a| a.to_string()).collect();
+                serde_json::json!({"doc": item.doc_id, "title": item.title, "issue": item.issue, "actions": actions})
+            })
+            .collect();
+        println!("{}", serde_json::to_string_pretty(&serde_json::json!({"items": items}))?);
+        return Ok(());
+    }
+    let mut updated = 0;
+    for item in items.iter().filter(|item| !item.actions.is_empty()) {
+        let issue = item.issue.map_or_else(|| "new".to_string(), |n| format!("#{n}"));
+        let actions: Vec<String> = item.actions.iter().map(|a| a.to_string()).collect();
+        println!("{issue} {}: {}", item.title, actions.join(", "));
+        updated += 1;
+    }
+    println!("\n{} action item(s), {updated} changed.", items.len());
+    if args.dry_run && updated > 0 {
+        println!("Dry run — no issues or files modified.");
+    }
+    Ok(())
+}
+
+fn save_issue_sync(docs: &[Document], state: &IssueState) -> Result<(), Box<dyn std::error::Error>> {
+    if !docs.is_empty() {
+        let mut undo = Recorder::new("sync");
+        undo.track_all(docs.iter().filter_map(|doc| doc.path.as_ref()));
+        let result = docs.iter().try_for_each(|doc| doc.save());
+        undo.finish()?;
+        result?;
+    }
+    state.save()?;
+    Ok(())
+}
+
+fn check(plan: &sync::SyncPlan, format: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let result = plan.to_diagnostics();
+    for w in &plan.warnings {
+        eprintln!("warning: {w}");
+    }
+    if format == "json" {
+        println!("{}", serde_json::to_string_pretty(&super::validate::result_to_json(&result))?);
+    } else {
+        print!("{}", result.to_report());
+    }
+    if !result.is_ok() {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+fn promotion_report(promotions: &[promote::Promotion]) -> String {
+    if promotions.is_empty() {
+        return "All promoted fields match the body. Nothing to promote.\n".into();
+    }
+    let mut out = String::new();
+    for p in promotions {
+        let value = yaml_value_to_string(&p.value);
+        match p.current {
+            Some(ref current) => out.push_str(&format!(
+                "{}: \"{}\" drifted: {} → {value}\n",
+                p.doc_id,
+                p.field,
+                yaml_value_to_string(current)
+            )),
+            None => out.push_str(&format!("{}: set \"{}\" to {value}\n", p.doc_id, p.field)),
+        }
+    }
+    out.push_str(&format!("\n{} field(s) to promote.\n", promotions.len()));
+    out
+}
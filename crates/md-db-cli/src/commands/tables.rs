@@ -0,0 +1,84 @@
+use std::path::PathBuf;
+
+use clap::Args;
+use md_db::schema::Schema;
+use md_db::tables::{self, TableQuery};
+
+#[derive(Debug, Args)]
+pub struct TablesArgs {
+    /// Action: export
+    pub action: String,
+
+    /// Directory to scan
+    #[arg(long, default_value = ".")]
+    pub dir: PathBuf,
+
+    /// Path to KDL schema file, or `auto` to use the closest one above the target
+    #[arg(long, default_value = "auto")]
+    pub schema: PathBuf,
+
+    /// Section heading holding the table (e.g. "Action Items")
+    #[arg(long)]
+    pub section: String,
+
+    /// Only documents of this type; its table definition sets column order and types
+    #[arg(long = "type")]
+    pub doc_type: Option<String>,
+
+    /// Which table in the section, 0-based
+    #[arg(long, default_value_t = 0)]
+    pub table: usize,
+
+    /// Write to this file instead of stdout
+    #[arg(long)]
+    pub out: Option<PathBuf>,
+
+    /// Output format: csv, json (default: from the --out extension, else csv)
+    #[arg(long)]
+    pub format: Option<String>,
+}
+
+pub fn run(args: &TablesArgs) -> Result<(), Box<dyn std::error::Error>> {
+    match args.action.as_str() {
+        "export" => export(args),
+        _ => Err(format!("unknown action: {} (expected: export)", args.action).into()),
+    }
+}
+
+fn export(args: &TablesArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let schema = Schema::from_file(md_db::project::resolve_schema(&args.schema, &args.dir)?)?;
+    if let Some(ref doc_type) = args.doc_type {
+        if schema.get_type(doc_type).is_none() {
+            return Err(format!("unknown type: {doc_type}").into());
+        }
+    }
+
+    let files = md_db::discovery::discover_files(&args.dir, None, &[], false)?;
+    let query = TableQuery {
+        section: args.section.clone(),
+        doc_type: args.doc_type.clone(),
+        index: args.table,
+    };
+    let data = tables::collect(&files, &schema, &query)?;
+
+    let from_ext = args
+        .out
+        .as_ref()
+        .and_then(|p| p.extension())
+        .and_then(|e| e.to_str())
+        .filter(|e| matches!(*e, "csv" | "json"));
+    let output = match args.format.as_deref().or(from_ext).unwrap_or("csv") {
+        "csv" => data.to_csv(),
+        "json" => format!("{}\n", serde_json::to_string_pretty(&data.to_json())?),
+        other => return Err(format!("unknown format: {other} (expected: csv, json)").into()),
+    };
+
+    match args.out {
+        Some(ref path) => {
+            md_db::durability::write(path, output)?;
+            eprintln!("wrote {} row(s) to {}", data.rows.len(), path.display());
+        }
+        None => print!("{output}"),
+    }
+    Ok(())
+}
@@ -0,0 +1,109 @@
+use std::path::PathBuf;
+
+use clap::Args;
+use md_db::schema::Schema;
+use md_db::triage::{self, GitHubProvider, GroupBy, TriageAction, TriageState};
+use md_db::users::UserConfig;
+use md_db::validation;
+
+#[derive(Debug, Args)]
+pub struct TriageArgs {
+    /// Directory to validate
+    pub dir: PathBuf,
+
+    /// Path to KDL schema file, or `auto` to use the closest one above the target
+    #[arg(long, default_value = "auto")]
+    pub schema: PathBuf,
+
+    /// Path to user/team config YAML file
+    #[arg(long)]
+    pub users: Option<PathBuf>,
+
+    /// GitHub repository (owner/name); defaults to the current repository
+    #[arg(long)]
+    pub repo: Option<String>,
+
+    /// Label added to created issues (repeatable)
+    #[arg(long)]
+    pub label: Vec<String>,
+
+    /// One ticket per: code, file
+    #[arg(long, default_value = "code")]
+    pub group_by: String,
+
+    /// URL prefix for document links in ticket bodies (e.g. https://github.com/o/r/blob/main)
+    #[arg(long)]
+    pub link_base: Option<String>,
+
+    /// Close tickets whose findings are all fixed
+    #[arg(long)]
+    pub close_resolved: bool,
+
+    /// Show the plan without touching any tickets
+    #[arg(long)]
+    pub dry_run: bool,
+
+    /// Output format: text, json
+    #[arg(long, default_value = "text")]
+    pub format: String,
+}
+
+pub fn run(args: &TriageArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let schema = Schema::from_file(md_db::project::resolve_schema(&args.schema, &args.dir)?)?;
+    let user_config = match &args.users {
+        Some(path) => Some(UserConfig::from_file(path)?),
+        None => None,
+    };
+    let group_by = GroupBy::from_str(&args.group_by)
+        .ok_or_else(|| format!("unknown --group-by \"{}\", expected code or file", args.group_by))?;
+
+    let result = validation::validate_directory(&args.dir, &schema, None, user_config.as_ref())?;
+    let groups = triage::group_findings(&result, group_by, args.link_base.as_deref());
+
+    let mut state = TriageState::open(&args.dir)?;
+    let mut items = triage::plan(groups, &state);
+
+    if !args.dry_run {
+        let mut provider = GitHubProvider {
+            repo: args.repo.clone(),
+            labels: args.label.clone(),
+        };
+        triage::apply(&mut items, &mut provider, &mut state, args.close_resolved)?;
+        state.save()?;
+    }
+
+    if args.format == "json" {
+        let out: Vec<serde_json::Value> = items.iter().map(|i| i.to_json()).collect();
+        let result = serde_json::json!({
+            "tickets": out,
+            "dry_run": args.dry_run,
+        });
+        println!("{}", serde_json::to_string_pretty(&result)?);
+        return Ok(());
+    }
+
+    if items.is_empty() {
+        println!("No findings need a ticket.");
+        return Ok(());
+    }
+    for item in &items {
+        let title = item
+            .group
+            .as_ref()
+            .map(|g| g.title.as_str())
+            .unwrap_or("(no findings left)");
+        let url = item.ticket.as_ref().map(|t| t.url.as_str()).unwrap_or("");
+        let verb = match (item.action, args.dry_run) {
+            (TriageAction::Resolve, _) if !args.close_resolved => "resolved (pass --close-resolved to close)",
+            (TriageAction::Create, true) => "would create",
+            (TriageAction::Update, true) => "would update",
+            (TriageAction::Resolve, true) => "would close",
+            (TriageAction::Create, false) => "created",
+            (TriageAction::Update, false) => "updated",
+            (TriageAction::Resolve, false) => "closed",
+            (TriageAction::Unchanged, _) => "unchanged",
+        };
+        println!("{verb:<10} {title}  {url}");
+    }
+    Ok(())
+}
@@ -0,0 +1,78 @@
+use std::path::PathBuf;
+
+use clap::Args;
+use md_db::uid;
+use md_db::undo::Recorder;
+
+#[derive(Debug, Args)]
+pub struct UidArgs {
+    /// Action: backfill, generate
+    pub action: String,
+
+    /// Directory containing markdown files (for `backfill`)
+    #[arg(default_value = ".")]
+    pub dir: PathBuf,
+
+    /// Show which files would get a uid without writing them
+    #[arg(long)]
+    pub dry_run: bool,
+
+    /// Output format: text, json
+    #[arg(long, default_value = "text")]
+    pub format: String,
+}
+
+pub fn run(args: &UidArgs) -> Result<(), Box<dyn std::error::Error>> {
+    match args.action.as_str() {
+        "backfill" => backfill(args),
+        "generate" => {
+            println!("{}", uid::generate());
+            Ok(())
+        }
+        _ => Err(format!(
+            "unknown action: {} (expected: backfill, generate)",
+            args.action
+        )
+        .into()),
+    }
+}
+
+fn backfill(args: &UidArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let files = md_db::discovery::discover_files(&args.dir, None, &[], false)?;
+    let mut undo = Recorder::new("uid backfill");
+    if !args.dry_run {
+        undo.track_all(&files);
+    }
+    let assigned = uid::backfill(&files, args.dry_run)?;
+    undo.finish()?;
+
+    if args.format == "json" {
+        let items: Vec<serde_json::Value> = assigned
+            .iter()
+            .map(|a| {
+                serde_json::json!({
+                    "path": a.path.display().to_string(),
+                    "uid": a.uid,
+                })
+            })
+            .collect();
+        let result = serde_json::json!({
+            "assigned": items,
+            "count": items.len(),
+            "dry_run": args.dry_run,
+        });
+        println!("{}", serde_json::to_string_pretty(&result)?);
+        return Ok(());
+    }
+
+    if assigned.is_empty() {
+        println!("All documents already have a uid.");
+        return Ok(());
+    }
+    let verb = if args.dry_run { "Would assign" } else { "Assigned" };
+    for a in &assigned {
+        println!("{verb} {} → {}", a.uid, a.path.display());
+    }
+    println!("\n{} document(s) updated.", assigned.len());
+    Ok(())
+}
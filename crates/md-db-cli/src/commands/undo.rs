@@ -0,0 +1,41 @@
+use std::path::PathBuf;
+
+use clap::Args;
+use md_db::undo::Operation;
+
+#[derive(Debug, Args)]
+pub struct UndoArgs {
+    /// Number of operations to revert (or re-apply)
+    #[arg(long, default_value_t = 1)]
+    pub steps: usize,
+
+    /// Directory inside the project whose undo log to use
+    #[arg(long, default_value = ".")]
+    pub dir: PathBuf,
+}
+
+/// Revert the last md-db operations.
+pub fn run(args: &UndoArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let root = md_db::undo::log_root(&args.dir)?;
+    report(&md_db::undo::undo(&root, args.steps)?, "undid", "nothing to undo");
+    Ok(())
+}
+
+/// Re-apply operations reverted by `undo`.
+pub fn run_redo(args: &UndoArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let root = md_db::undo::log_root(&args.dir)?;
+    report(&md_db::undo::redo(&root, args.steps)?, "redid", "nothing to redo");
+    Ok(())
+}
+
+fn report(ops: &[Operation], verb: &str, empty: &str) {
+    if ops.is_empty() {
+        eprintln!("{empty}");
+    }
+    for op in ops {
+        eprintln!("{verb} '{}' from {} ({} file(s))", op.command, op.time, op.files.len());
+        for file in &op.files {
+            eprintln!("  {}", file.path.display());
+        }
+    }
+}
@@ -1,17 +1,20 @@
-use std::path::PathBuf;
+use std::collections::HashSet;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::Command;
 
 use clap::Args;
 use md_db::schema::Schema;
 use md_db::users::UserConfig;
-use md_db::validation;
+use md_db::validation::{self, Severity};
 
 #[derive(Debug, Args)]
 pub struct ValidateArgs {
     /// Directory or file to validate (omit when using --stdin)
     pub dir: Option<PathBuf>,
 
-    /// Path to KDL schema file
-    #[arg(long)]
+    /// Path to KDL schema file, or `auto` to use the closest one above the target
+    #[arg(long, default_value = "auto")]
     pub schema: PathBuf,
 
     /// Read document from stdin instead of file
@@ -30,28 +33,60 @@ pub struct ValidateArgs {
     #[arg(long)]
     pub pattern: Option<String>,
 
-    /// Output format: text, json, compact, auto (auto=json when piped)
+    /// Validate only markdown files staged in git (used by the pre-commit hook)
+    #[arg(long)]
+    pub staged: bool,
+
+    /// Validate only markdown files changed since --base (committed, uncommitted, or untracked),
+    /// plus documents that link to a document deleted since then
+    #[arg(long, conflicts_with = "staged")]
+    pub changed: bool,
+
+    /// Revision --changed compares against, via its merge base with HEAD
+    #[arg(long, default_value = "HEAD", requires = "changed")]
+    pub base: String,
+
+    /// Lowest severity that fails the run: error, warning, never
+    #[arg(long, default_value = "error")]
+    pub fail_on: String,
+
+    /// Print a compact blocker summary with `md-db fix` suggestions instead of the full report
+    #[arg(long)]
+    pub summary: bool,
+
+    /// Output format: text, json, ndjson, compact, sarif, auto (auto=json when piped)
     #[arg(long, default_value = "auto")]
     pub format: String,
+
+    /// Run only these phases (repeatable or comma-separated): frontmatter, sections, tables, refs, graph
+    #[arg(long)]
+    pub only: Vec<String>,
+
+    /// Leave out these phases (repeatable or comma-separated)
+    #[arg(long)]
+    pub skip: Vec<String>,
 }
 
 pub fn run(args: &ValidateArgs) -> Result<(), Box<dyn std::error::Error>> {
-    let schema = Schema::from_file(&args.schema)?;
+    let target = args.dir.as_deref().unwrap_or(Path::new("."));
+    let schema = Schema::from_file(md_db::project::resolve_schema(&args.schema, target)?)?;
     let user_config = match &args.users {
         Some(path) => Some(UserConfig::from_file(path)?),
         None => None,
     };
+    let opts = validation::ValidationOptions::from_names(&args.only, &args.skip)?;
 
     let result = if args.stdin {
         let mut content = String::new();
         std::io::Read::read_to_string(&mut std::io::stdin(), &mut content)?;
         let doc = md_db::document::Document::from_str(&content)?;
-        let fr = validation::validate_document(
+        let fr = validation::validate_document_with(
             &doc,
             &schema,
             &std::collections::HashSet::new(),
             &std::collections::HashSet::new(),
             user_config.as_ref(),
+            &opts,
         );
         validation::ValidationResult {
             file_results: vec![fr],
@@ -67,107 +102,354 @@ pub fn run(args: &ValidateArgs) -> Result<(), Box<dyn std::error::Error>> {
             .filter(|p| p.exists())
             .collect();
 
-        let known_files: std::collections::HashSet<PathBuf> = paths
+        let known_files: HashSet<PathBuf> = paths
             .iter()
             .filter_map(|p| p.canonicalize().ok())
             .collect();
-        let known_ids: std::collections::HashSet<String> = paths
+        let known_ids = md_db::validation::collect_known_ids(&paths);
+
+        validate_paths(&paths, &schema, &known_files, &known_ids, user_config.as_ref(), &opts)
+    } else if args.staged {
+        let staged = staged_markdown_files()?;
+        let paths: Vec<PathBuf> = staged.iter().map(|(path, _)| path.clone()).collect();
+
+        // Resolve refs against the whole tree, not just the staged subset
+        let root = args.dir.clone().unwrap_or_else(|| PathBuf::from("."));
+        let all_files = md_db::discovery::discover_files(&root, None, &[], false)?;
+        let known_files: HashSet<PathBuf> = all_files
             .iter()
-            .map(|p| md_db::graph::path_to_id(p))
+            .chain(paths.iter())
+            .filter_map(|p| p.canonicalize().ok())
             .collect();
+        let known_ids = md_db::validation::collect_known_ids(all_files.iter().chain(paths.iter()));
 
-        let mut file_results = Vec::new();
-        for path in &paths {
-            let doc = match md_db::document::Document::from_file(path) {
-                Ok(d) => d,
-                Err(e) => {
-                    file_results.push(validation::FileResult {
-                        path: path.display().to_string(),
-                        diagnostics: vec![validation::Diagnostic {
-                            severity: validation::Severity::Error,
-                            code: "E000".into(),
-                            message: format!("failed to parse: {e}"),
-                            location: "file".into(),
-                            hint: None,
-                        }],
-                    });
-                    continue;
-                }
-            };
-            // Skip files without frontmatter type
-            if doc.frontmatter.is_none() {
-                continue;
-            }
-            if let Some(ref fm) = doc.frontmatter {
-                if fm.get("type").is_none() {
-                    continue;
-                }
-            }
-            file_results.push(validation::validate_document(
-                &doc,
-                &schema,
-                &known_files,
-                &known_ids,
-                user_config.as_ref(),
-            ));
-        }
-        validation::ValidationResult { file_results }
+        // The staged blobs, not the working tree: that is what the commit will contain
+        let sources = staged.into_iter().map(|(path, content)| (path, Ok(content)));
+        validate_sources(sources, &schema, &known_files, &known_ids, user_config.as_ref(), &opts)
+    } else if args.changed {
+        let root = args.dir.clone().unwrap_or_else(|| PathBuf::from("."));
+        let paths = changed_markdown_files(&root, &args.base, &schema)?;
+        let all_files = md_db::discovery::discover_files(&root, None, &[], false)?;
+        let known_files: HashSet<PathBuf> = all_files.iter().filter_map(|p| p.canonicalize().ok()).collect();
+        let known_ids = md_db::validation::collect_known_ids(&all_files);
+
+        validate_paths(&paths, &schema, &known_files, &known_ids, user_config.as_ref(), &opts)
     } else {
         let dir = args
             .dir
             .as_ref()
             .ok_or("directory argument required when not using --stdin or --stdin-list")?;
         let pattern = args.pattern.as_deref();
-        validation::validate_directory(dir, &schema, pattern, user_config.as_ref())?
+        validation::validate_directory_with(dir, &schema, pattern, user_config.as_ref(), &opts)?
+    };
+
+    let fail_on = match args.fail_on.as_str() {
+        "error" => Some(Severity::Error),
+        "warning" => Some(Severity::Warning),
+        "never" => None,
+        other => {
+            return Err(format!("unknown --fail-on \"{other}\", expected error, warning, or never").into())
+        }
+    };
+    let blocked = match fail_on {
+        Some(Severity::Error) => !result.is_ok(),
+        Some(Severity::Warning) => result.total_errors() + result.total_warnings() > 0,
+        None => false,
     };
 
     let format = md_db::output::OutputFormat::from_str(&args.format)
         .unwrap_or(md_db::output::OutputFormat::Text);
 
+    if args.summary
+        && !matches!(
+            format,
+            md_db::output::OutputFormat::Json | md_db::output::OutputFormat::Ndjson | md_db::output::OutputFormat::Sarif
+        )
+    {
+        print!("{}", blocker_summary(&result, fail_on, args));
+        if blocked {
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
     match format {
         md_db::output::OutputFormat::Json => {
             let json = result_to_json(&result);
             println!("{}", serde_json::to_string_pretty(&json)?);
         }
+        md_db::output::OutputFormat::Ndjson => {
+            // One line per file with diagnostics; the exit status carries the verdict
+            let mut out = std::io::stdout().lock();
+            for f in result.file_results.iter().filter(|f| !f.diagnostics.is_empty()) {
+                writeln!(out, "{}", file_result_to_json(f))?;
+            }
+        }
         md_db::output::OutputFormat::Compact => {
             print!("{}", result.to_compact_report());
         }
+        md_db::output::OutputFormat::Sarif => {
+            let root = std::env::current_dir().ok();
+            let sarif = md_db::output::sarif::to_sarif(&result, root.as_deref());
+            println!("{}", serde_json::to_string_pretty(&sarif)?);
+        }
         _ => {
             print!("{}", result.to_report());
         }
     }
 
-    if result.is_ok() {
-        Ok(())
-    } else {
+    if blocked {
         std::process::exit(1);
     }
+    Ok(())
+}
+
+/// Validate an explicit list of files, skipping files without a frontmatter `type`.
+fn validate_paths(
+    paths: &[PathBuf],
+    schema: &Schema,
+    known_files: &HashSet<PathBuf>,
+    known_ids: &HashSet<String>,
+    user_config: Option<&UserConfig>,
+    opts: &validation::ValidationOptions,
+) -> validation::ValidationResult {
+    let sources = paths
+        .iter()
+        .map(|path| (path.clone(), std::fs::read_to_string(path).map_err(md_db::error::Error::from)));
+    validate_sources(sources, schema, known_files, known_ids, user_config, opts)
+}
+
+/// Validate documents given as a path and its content (or the error reading it),
+/// skipping documents without a frontmatter `type`.
+fn validate_sources(
+    sources: impl IntoIterator<Item = (PathBuf, md_db::error::Result<String>)>,
+    schema: &Schema,
+    known_files: &HashSet<PathBuf>,
+    known_ids: &HashSet<String>,
+    user_config: Option<&UserConfig>,
+    opts: &validation::ValidationOptions,
+) -> validation::ValidationResult {
+    let mut file_results = Vec::new();
+    for (path, raw) in sources {
+        let parsed = match raw {
+            Ok(raw) => md_db::document::Document::from_str(&raw).map_err(|e| (e, Some(raw))),
+            Err(e) => Err((e, None)),
+        };
+        let mut doc = match parsed {
+            Ok(d) => d,
+            Err((e, raw)) => {
+                file_results.push(validation::FileResult {
+                    path: path.display().to_string(),
+                    diagnostics: vec![validation::Diagnostic {
+                        severity: validation::Severity::Error,
+                        code: "E000".into(),
+                        message: format!("failed to parse: {e}"),
+                        location: "file".into(),
+                        hint: None,
+                        span: raw.as_deref().and_then(md_db::span::yaml_error_span),
+                    }],
+                });
+                continue;
+            }
+        };
+        doc.path = Some(path);
+        // Skip files without frontmatter type
+        if doc.frontmatter.is_none() {
+            continue;
+        }
+        if let Some(ref fm) = doc.frontmatter {
+            if fm.get("type").is_none() {
+                continue;
+            }
+        }
+        file_results.push(validation::validate_document_with(
+            &doc,
+            schema,
+            known_files,
+            known_ids,
+            user_config,
+            opts,
+        ));
+    }
+    validation::ValidationResult { file_results }
 }
 
-fn result_to_json(result: &validation::ValidationResult) -> serde_json::Value {
+/// Staged (added, copied, modified, renamed) markdown files with their staged contents,
+/// which differ from the working tree when a file is partially staged.
+/// Paths are made relative to the current directory when it is inside the repository.
+fn staged_markdown_files() -> Result<Vec<(PathBuf, String)>, Box<dyn std::error::Error>> {
+    let toplevel = Command::new("git")
+        .args(["rev-parse", "--show-toplevel"])
+        .output()?;
+    if !toplevel.status.success() {
+        return Err("--staged requires a git repository".into());
+    }
+    let root = PathBuf::from(String::from_utf8_lossy(&toplevel.stdout).trim());
+    let cwd = std::env::current_dir()?;
+
+    let listing = git(&["diff", "--cached", "--name-only", "-z", "--diff-filter=ACMR", "--", "*.md"])?;
+    let mut staged = Vec::new();
+    for name in listing.split(|b| *b == 0).filter(|name| !name.is_empty()) {
+        let name = String::from_utf8_lossy(name).into_owned();
+        let blob = git(&["show", &format!(":{name}")])?;
+        let content = String::from_utf8(blob).map_err(|_| format!("staged {name} is not valid UTF-8"))?;
+        staged.push((relative_to(&root.join(&name), &cwd), content));
+    }
+    Ok(staged)
+}
+
+/// Run git and return its stdout, or its stderr as the error.
+fn git(args: &[&str]) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    let output = Command::new("git").args(args).output()?;
+    if !output.status.success() {
+        return Err(format!("git {} failed: {}", args[0], String::from_utf8_lossy(&output.stderr).trim()).into());
+    }
+    Ok(output.stdout)
+}
+
+/// Markdown files under `root` changed since the merge base of `base` and HEAD,
+/// followed by the documents whose refs point at a document deleted since then
+/// (by ID, or by the uid it had at the base).
+fn changed_markdown_files(
+    root: &Path,
+    base: &str,
+    schema: &Schema,
+) -> Result<Vec<PathBuf>, Box<dyn std::error::Error>> {
+    let changes = md_db::vcs::changed_markdown_files(base)?;
+    let cwd = std::env::current_dir()?;
+    let root_abs = root.canonicalize()?;
+    let under_root = |p: &Path| p.canonicalize().is_ok_and(|p| p.starts_with(&root_abs));
+    let mut paths: Vec<PathBuf> = changes.changed.iter().filter(|p| under_root(p)).cloned().collect();
+
+    let mut gone = HashSet::new();
+    for path in &changes.deleted {
+        gone.insert(md_db::graph::path_to_id(path).to_lowercase());
+        let uid = md_db::vcs::read_file_at(&changes.base, path)
+            .ok()
+            .and_then(|raw| md_db::document::Document::from_str(&raw).ok())
+            .and_then(|doc| doc.frontmatter?.get_display(md_db::uid::UID_FIELD));
+        gone.extend(uid.map(|u| u.to_lowercase()));
+    }
+    let mut dependents = 0;
+    if !gone.is_empty() {
+        let graph = md_db::graph::DocGraph::build_cached(root, schema)?;
+        let seen: HashSet<PathBuf> = paths.iter().filter_map(|p| p.canonicalize().ok()).collect();
+        let mut extra: Vec<PathBuf> = graph
+            .edges
+            .iter()
+            .filter(|e| gone.contains(&e.to.to_lowercase()))
+            .filter_map(|e| graph.nodes.get(&e.from))
+            .map(|node| relative_to(&node.path, &cwd))
+            .filter(|p| p.canonicalize().is_ok_and(|c| !seen.contains(&c)))
+            .collect();
+        extra.sort();
+        extra.dedup();
+        dependents = extra.len();
+        paths.extend(extra);
+    }
+    eprintln!(
+        "md-db: {} changed file(s) since {}, {dependents} linking to {} deleted document(s)",
+        paths.len() - dependents,
+        &changes.base[..changes.base.len().min(12)],
+        changes.deleted.len()
+    );
+    Ok(paths)
+}
+
+fn relative_to(path: &Path, base: &Path) -> PathBuf {
+    let base = base.canonicalize().unwrap_or_else(|_| base.to_path_buf());
+    let path_c = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    path_c
+        .strip_prefix(&base)
+        .map(|p| p.to_path_buf())
+        .unwrap_or_else(|_| path.to_path_buf())
+}
+
+/// Compact pre-commit summary: one line per diagnostic, tagged BLOCK or warn depending on
+/// the `--fail-on` threshold, followed by `md-db fix` commands for auto-fixable blockers.
+fn blocker_summary(
+    result: &validation::ValidationResult,
+    fail_on: Option<Severity>,
+    args: &ValidateArgs,
+) -> String {
+    let blocks = |sev: Severity| match fail_on {
+        Some(Severity::Error) => sev == Severity::Error,
+        Some(Severity::Warning) => true,
+        None => false,
+    };
+
+    let mut out = String::new();
+    let mut blocked_files = 0usize;
+    let mut fix_paths: Vec<&str> = Vec::new();
+
+    for fr in &result.file_results {
+        let mut file_blocked = false;
+        let mut fixable = false;
+        for d in &fr.diagnostics {
+            let tag = if blocks(d.severity) {
+                file_blocked = true;
+                fixable |= validation::FIXABLE_CODES.contains(&d.code.as_str());
+                "BLOCK"
+            } else {
+                "warn "
+            };
+            out.push_str(&format!("  {tag} {}  {} {}\n", fr.path, d.code, d.message));
+        }
+        if file_blocked {
+            blocked_files += 1;
+        }
+        if fixable {
+            fix_paths.push(&fr.path);
+        }
+    }
+
+    if !fix_paths.is_empty() {
+        out.push_str("\nAuto-fixable blockers — run:\n");
+        for path in &fix_paths {
+            let mut cmd = format!("  md-db fix {}", shell_quote(path));
+            if !md_db::project::is_auto(&args.schema) {
+                cmd.push_str(&format!(" --schema {}", shell_quote(&args.schema.display().to_string())));
+            }
+            if let Some(ref users) = args.users {
+                cmd.push_str(&format!(" --users {}", shell_quote(&users.display().to_string())));
+            }
+            out.push_str(&cmd);
+            out.push('\n');
+        }
+    }
+
+    out.push_str(&format!(
+        "\nmd-db: {} file(s) checked — {} error(s), {} warning(s)",
+        result.file_results.len(),
+        result.total_errors(),
+        result.total_warnings()
+    ));
+    if blocked_files > 0 {
+        out.push_str(&format!(" — blocked by {blocked_files} file(s)\n"));
+    } else {
+        out.push_str(" — ok\n");
+    }
+    out
+}
+
+/// Quote a path for copy-pasting into a POSIX shell.
+fn shell_quote(s: &str) -> String {
+    if s
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || "/._-".contains(c))
+    {
+        s.to_string()
+    } else {
+        format!("'{}'", s.replace('\'', "'\\''"))
+    }
+}
+
+pub(crate) fn result_to_json(result: &validation::ValidationResult) -> serde_json::Value {
     let files: Vec<serde_json::Value> = result
         .file_results
         .iter()
         .filter(|f| !f.diagnostics.is_empty())
-        .map(|f| {
-            let diags: Vec<serde_json::Value> = f
-                .diagnostics
-                .iter()
-                .map(|d| {
-                    serde_json::json!({
-                        "severity": d.severity.to_string(),
-                        "code": d.code,
-                        "message": d.message,
-                        "location": d.location,
-                        "hint": d.hint,
-                    })
-                })
-                .collect();
-            serde_json::json!({
-                "path": f.path,
-                "diagnostics": diags,
-            })
-        })
+        .map(file_result_to_json)
         .collect();
 
     serde_json::json!({
@@ -177,3 +459,24 @@ fn result_to_json(result: &validation::ValidationResult) -> serde_json::Value {
         "ok": result.is_ok(),
     })
 }
+
+fn file_result_to_json(f: &validation::FileResult) -> serde_json::Value {
+    let diags: Vec<serde_json::Value> = f
+        .diagnostics
+        .iter()
+        .map(|d| {
+            serde_json::json!({
+                "severity": d.severity.to_string(),
+                "code": d.code,
+                "message": d.message,
+                "location": d.location,
+                "hint": d.hint,
+                "span": d.span,
+            })
+        })
+        .collect();
+    serde_json::json!({
+        "path": f.path,
+        "diagnostics": diags,
+    })
+}
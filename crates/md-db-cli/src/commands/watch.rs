@@ -1,14 +1,17 @@
-use std::collections::HashSet;
-use std::path::PathBuf;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
 use std::sync::mpsc;
 use std::time::Duration;
 
 use clap::Args;
 use md_db::document::Document;
+use md_db::frontmatter::Frontmatter;
+use md_db::graph::{path_to_id, DocGraph, GraphDelta, GraphDiagnostic};
+use md_db::notify::Notifier;
 use md_db::output::OutputFormat;
 use md_db::schema::Schema;
 use md_db::users::UserConfig;
-use md_db::validation::{self, FileResult, Severity, ValidationResult};
+use md_db::validation::{self, DiagnosticDelta, DiagnosticState, FileResult, Severity, ValidationResult};
 use notify::{EventKind, RecursiveMode, Watcher};
 
 #[derive(Debug, Args)]
@@ -16,8 +19,8 @@ pub struct WatchArgs {
     /// Directory to watch
     pub dir: PathBuf,
 
-    /// Path to KDL schema file
-    #[arg(long)]
+    /// Path to KDL schema file, or `auto` to use the closest one above the target
+    #[arg(long, default_value = "auto")]
     pub schema: PathBuf,
 
     /// Path to user/team config YAML file
@@ -31,10 +34,27 @@ pub struct WatchArgs {
     /// Debounce interval in milliseconds
     #[arg(long, default_value = "300")]
     pub debounce: u64,
+
+    /// Don't maintain the document graph or report graph health (G0xx) changes
+    #[arg(long)]
+    pub no_graph: bool,
+
+    /// POST validation failures, new documents, and status changes (JSON events) to this webhook URL
+    #[arg(long)]
+    pub notify: Option<String>,
+
+    /// Run this shell command for each event, with the JSON payload on stdin
+    #[arg(long)]
+    pub notify_cmd: Option<String>,
+
+    /// Seconds to wait for the --notify webhook or --notify-cmd command before giving up
+    #[arg(long, default_value_t = md_db::notify::DEFAULT_TIMEOUT_SECS)]
+    pub notify_timeout: u64,
 }
 
 pub fn run(args: &WatchArgs) -> Result<(), Box<dyn std::error::Error>> {
-    let schema = Schema::from_file(&args.schema)?;
+    let schema_path = md_db::project::resolve_schema(&args.schema, &args.dir)?;
+    let schema = Schema::from_file(&schema_path)?;
     let user_config = match &args.users {
         Some(path) => Some(UserConfig::from_file(path)?),
         None => None,
@@ -45,7 +65,46 @@ pub fn run(args: &WatchArgs) -> Result<(), Box<dyn std::error::Error>> {
     // Initial full validation
     eprintln!("Watching {} for changes...", args.dir.display());
     let result = validation::validate_directory(&args.dir, &schema, None, user_config.as_ref())?;
-    print_result(&result, format, None);
+    print_result(&result, format);
+    // Later events print only what changed against this state
+    let mut state = DiagnosticState::default();
+    state.replace(&result);
+
+    // The graph is built once and then patched per changed file
+    let mut graph = if args.no_graph {
+        None
+    } else {
+        Some(DocGraph::build(&args.dir, &schema)?)
+    };
+    let mut health: HashSet<GraphDiagnostic> = graph
+        .as_ref()
+        .map(|g| g.check_health(&schema).into_iter().collect())
+        .unwrap_or_default();
+    if let Some(ref g) = graph {
+        eprintln!(
+            "[{}] graph: {} document(s), {} edge(s), {} issue(s)",
+            timestamp(),
+            g.nodes.len(),
+            g.edges.len(),
+            health.len()
+        );
+    }
+    let root = args.dir.canonicalize().unwrap_or_else(|_| args.dir.clone());
+
+    // Events compare each changed document with what it was before
+    let notifiers = Notifier::from_args(
+        args.notify.as_deref(),
+        args.notify_cmd.as_deref(),
+        args.notify_timeout,
+    );
+    let mut watched: HashMap<String, Watched> = HashMap::new();
+    if !notifiers.is_empty() {
+        for path in md_db::discovery::discover_files(&args.dir, None, &[], false)? {
+            if let Some(w) = Watched::read(&path, &schema) {
+                watched.insert(path.display().to_string(), w);
+            }
+        }
+    }
 
     // Set up file watcher
     let (tx, rx) = mpsc::channel();
@@ -58,7 +117,7 @@ pub fn run(args: &WatchArgs) -> Result<(), Box<dyn std::error::Error>> {
     watcher.watch(&args.dir, RecursiveMode::Recursive)?;
 
     // Also watch schema file for changes
-    let schema_path = args.schema.canonicalize().unwrap_or_else(|_| args.schema.clone());
+    let schema_path = schema_path.canonicalize().unwrap_or(schema_path);
     if let Some(schema_parent) = schema_path.parent() {
         let _ = watcher.watch(schema_parent, RecursiveMode::NonRecursive);
     }
@@ -102,7 +161,7 @@ pub fn run(args: &WatchArgs) -> Result<(), Box<dyn std::error::Error>> {
 
         // Reload schema/users if changed
         let current_schema = if schema_changed {
-            match Schema::from_file(&args.schema) {
+            match Schema::from_file(&schema_path) {
                 Ok(s) => s,
                 Err(e) => {
                     eprintln!("[{}] schema reload error: {e}", timestamp());
@@ -135,20 +194,40 @@ pub fn run(args: &WatchArgs) -> Result<(), Box<dyn std::error::Error>> {
                 None,
                 current_users.as_ref(),
             ) {
-                Ok(result) => print_result(&result, format, None),
+                Ok(result) => {
+                    let delta = state.replace(&result);
+                    print_delta(&delta, &state, format);
+                    send_events(&notifiers, &[], &delta, &mut watched, &current_schema);
+                }
                 Err(e) => eprintln!("[{}] validation error: {e}", timestamp()),
             }
+            if schema_changed && graph.is_some() {
+                match DocGraph::build(&args.dir, &current_schema) {
+                    Ok(g) => {
+                        let after: HashSet<GraphDiagnostic> =
+                            g.check_health(&current_schema).into_iter().collect();
+                        print_graph_changes(&GraphDelta::default(), &health, &after, format);
+                        health = after;
+                        graph = Some(g);
+                    }
+                    Err(e) => eprintln!("[{}] graph rebuild error: {e}", timestamp()),
+                }
+            }
         } else {
-            // Incremental: validate only changed .md files
-            let md_files: Vec<PathBuf> = changed_paths
+            // Changed .md files, including deleted ones (the graph drops their nodes),
+            // spelled the way the initial run reported them
+            let md_changed: Vec<PathBuf> = changed_paths
                 .into_iter()
-                .filter(|p| p.extension().map_or(false, |ext| ext == "md") && p.is_file())
+                .filter(|p| p.extension().map_or(false, |ext| ext == "md"))
+                .map(|p| discovery_path(&p, &root, &args.dir))
                 .collect();
-
-            if md_files.is_empty() {
+            if md_changed.is_empty() {
                 continue;
             }
 
+            // Incremental: validate only changed .md files that still exist
+            let md_files: Vec<PathBuf> = md_changed.iter().filter(|p| p.is_file()).cloned().collect();
+
             // Build known files/IDs from the whole directory for cross-ref validation
             let all_files =
                 md_db::discovery::discover_files(&args.dir, None, &[], false).unwrap_or_default();
@@ -156,8 +235,7 @@ pub fn run(args: &WatchArgs) -> Result<(), Box<dyn std::error::Error>> {
                 .iter()
                 .map(|p| p.canonicalize().unwrap_or_else(|_| p.clone()))
                 .collect();
-            let known_ids: HashSet<String> =
-                all_files.iter().map(|p| md_db::graph::path_to_id(p)).collect();
+            let known_ids = validation::collect_known_ids(&all_files);
 
             let mut file_results = Vec::new();
             for path in &md_files {
@@ -189,23 +267,173 @@ pub fn run(args: &WatchArgs) -> Result<(), Box<dyn std::error::Error>> {
                                 message: format!("failed to parse: {e}"),
                                 location: "file".into(),
                                 hint: None,
+                                span: std::fs::read_to_string(path)
+                                    .ok()
+                                    .and_then(|raw| md_db::span::yaml_error_span(&raw)),
                             }],
                         });
                     }
                 }
             }
 
-            if !file_results.is_empty() {
-                let result = ValidationResult { file_results };
-                let changed_display: Vec<String> =
-                    md_files.iter().map(|p| p.display().to_string()).collect();
-                print_result(&result, format, Some(&changed_display));
+            let result = ValidationResult { file_results };
+            let changed_display: Vec<String> =
+                md_changed.iter().map(|p| p.display().to_string()).collect();
+            let delta = state.update(&changed_display, &result);
+            print_delta(&delta, &state, format);
+            send_events(&notifiers, &md_changed, &delta, &mut watched, &current_schema);
+
+            if let Some(ref mut g) = graph {
+                let delta = g.apply_changes(&md_changed, &current_schema);
+                let after: HashSet<GraphDiagnostic> =
+                    g.check_health(&current_schema).into_iter().collect();
+                print_graph_changes(&delta, &health, &after, format);
+                health = after;
+            }
+        }
+    }
+}
+
+/// The frontmatter events are detected from, by path.
+#[derive(Debug, Clone, PartialEq)]
+struct Watched {
+    doc_type: String,
+    status: Option<String>,
+}
+
+impl Watched {
+    /// `None` for files that are gone or have no `type`.
+    fn read(path: &Path, schema: &Schema) -> Option<Self> {
+        let fm = Frontmatter::read_from_file(path).ok()??;
+        let doc_type = fm.get_display("type")?;
+        let status = fm.get_display(&schema.status_field(&doc_type).field);
+        Some(Self { doc_type, status })
+    }
+}
+
+/// Notify `document-created` and `status-changed` for the `changed` files, then
+/// `validation-failed` for each document with new errors.
+fn send_events(
+    notifiers: &[Notifier],
+    changed: &[PathBuf],
+    delta: &DiagnosticDelta,
+    watched: &mut HashMap<String, Watched>,
+    schema: &Schema,
+) {
+    if notifiers.is_empty() {
+        return;
+    }
+    let mut events = Vec::new();
+    for path in changed {
+        let key = path.display().to_string();
+        let id = path_to_id(path);
+        let now = Watched::read(path, schema);
+        match (watched.get(&key), &now) {
+            (None, Some(now)) => events.push(md_db::notify::event(
+                "document-created",
+                &format!("{id} ({}) created", now.doc_type),
+                serde_json::json!({"doc": id, "type": now.doc_type, "path": key, "status": now.status}),
+            )),
+            (Some(before), Some(now)) if before.status != now.status => {
+                let show = |s: &Option<String>| s.clone().unwrap_or_else(|| "(none)".into());
+                events.push(md_db::notify::event(
+                    "status-changed",
+                    &format!("{id} status: {} → {}", show(&before.status), show(&now.status)),
+                    serde_json::json!({
+                        "doc": id, "type": now.doc_type, "path": key, "from": before.status, "to": now.status,
+                    }),
+                ));
+            }
+            _ => {}
+        }
+        match now {
+            Some(now) => watched.insert(key, now),
+            None => watched.remove(&key),
+        };
+    }
+
+    let mut failures: BTreeMap<&str, Vec<serde_json::Value>> = BTreeMap::new();
+    for (path, d) in delta.new.iter().filter(|(_, d)| d.severity == Severity::Error) {
+        failures.entry(path.as_str()).or_default().push(diagnostic_json(path, d));
+    }
+    for (path, diagnostics) in failures {
+        let id = path_to_id(Path::new(path));
+        let doc_type = watched.get(path).map(|w| w.doc_type.clone());
+        events.push(md_db::notify::event(
+            "validation-failed",
+            &format!("{id} fails validation: {} new error(s)", diagnostics.len()),
+            serde_json::json!({"doc": id, "type": doc_type, "path": path, "diagnostics": diagnostics}),
+        ));
+    }
+
+    for event in &events {
+        for error in md_db::notify::send_all(notifiers, event) {
+            eprintln!("[{}] notify: {error}", timestamp());
+        }
+    }
+}
+
+/// Watcher events carry absolute paths; the graph keys files the way discovery
+/// spells them, relative to the `dir` argument.
+fn discovery_path(path: &Path, root: &Path, dir: &Path) -> PathBuf {
+    match path.strip_prefix(root) {
+        Ok(rel) => dir.join(rel),
+        Err(_) => path.to_path_buf(),
+    }
+}
+
+/// Report edge changes and graph diagnostics that appeared or cleared since the last event.
+fn print_graph_changes(
+    delta: &GraphDelta,
+    before: &HashSet<GraphDiagnostic>,
+    after: &HashSet<GraphDiagnostic>,
+    format: OutputFormat,
+) {
+    let mut new: Vec<&GraphDiagnostic> = after.difference(before).collect();
+    let mut resolved: Vec<&GraphDiagnostic> = before.difference(after).collect();
+    if delta.is_empty() && new.is_empty() && resolved.is_empty() {
+        return;
+    }
+    new.sort_by(|a, b| (&a.code, &a.message).cmp(&(&b.code, &b.message)));
+    resolved.sort_by(|a, b| (&a.code, &a.message).cmp(&(&b.code, &b.message)));
+
+    let diag_json = |d: &&GraphDiagnostic| {
+        serde_json::json!({ "code": d.code, "severity": d.severity, "message": d.message })
+    };
+    match format {
+        OutputFormat::Json => {
+            let json = serde_json::json!({
+                "timestamp": timestamp(),
+                "graph": {
+                    "edges_added": delta.added.len(),
+                    "edges_removed": delta.removed.len(),
+                    "nodes_removed": delta.removed_nodes,
+                    "new": new.iter().map(diag_json).collect::<Vec<_>>(),
+                    "resolved": resolved.iter().map(diag_json).collect::<Vec<_>>(),
+                },
+            });
+            println!("{}", serde_json::to_string(&json).unwrap_or_default());
+        }
+        _ => {
+            eprintln!(
+                "[{}] graph: +{} / -{} edge(s), {} new issue(s), {} resolved",
+                timestamp(),
+                delta.added.len(),
+                delta.removed.len(),
+                new.len(),
+                resolved.len()
+            );
+            for d in &new {
+                println!("  {} {}: {}", d.code, d.severity, d.message);
+            }
+            for d in &resolved {
+                println!("  resolved {}: {}", d.code, d.message);
             }
         }
     }
 }
 
-fn collect_paths(event: &notify::Event) -> HashSet<PathBuf> {
+pub(super) fn collect_paths(event: &notify::Event) -> HashSet<PathBuf> {
     match event.kind {
         EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_) => {
             event.paths.iter().cloned().collect()
@@ -214,7 +442,7 @@ fn collect_paths(event: &notify::Event) -> HashSet<PathBuf> {
     }
 }
 
-fn timestamp() -> String {
+pub(super) fn timestamp() -> String {
     let now = std::time::SystemTime::now();
     let since_midnight = now
         .duration_since(std::time::UNIX_EPOCH)
@@ -227,7 +455,7 @@ fn timestamp() -> String {
     format!("{h:02}:{m:02}:{s:02}")
 }
 
-fn print_result(result: &ValidationResult, format: OutputFormat, changed: Option<&[String]>) {
+fn print_result(result: &ValidationResult, format: OutputFormat) {
     match format {
         OutputFormat::Json => {
             let files: Vec<serde_json::Value> = result
@@ -237,15 +465,7 @@ fn print_result(result: &ValidationResult, format: OutputFormat, changed: Option
                     let diags: Vec<serde_json::Value> = f
                         .diagnostics
                         .iter()
-                        .map(|d| {
-                            serde_json::json!({
-                                "severity": d.severity.to_string(),
-                                "code": d.code,
-                                "message": d.message,
-                                "location": d.location,
-                                "hint": d.hint,
-                            })
-                        })
+                        .map(|d| diagnostic_json(&f.path, d))
                         .collect();
                     serde_json::json!({
                         "path": f.path,
@@ -265,75 +485,14 @@ fn print_result(result: &ValidationResult, format: OutputFormat, changed: Option
             println!("{}", serde_json::to_string(&json).unwrap_or_default());
         }
         OutputFormat::Compact => {
-            if let Some(paths) = changed {
-                for path in paths {
-                    let fr = result.file_results.iter().find(|f| f.path == *path);
-                    match fr {
-                        Some(f) if f.diagnostics.is_empty() => {
-                            println!("[{}] {} — 0 errors, 0 warnings ✓", timestamp(), path);
-                        }
-                        Some(f) => {
-                            println!(
-                                "[{}] {} — {} error(s), {} warning(s)",
-                                timestamp(),
-                                path,
-                                f.errors(),
-                                f.warnings()
-                            );
-                            for d in &f.diagnostics {
-                                println!("  {}", d.to_compact());
-                            }
-                        }
-                        None => {
-                            println!("[{}] {} — 0 errors, 0 warnings ✓", timestamp(), path);
-                        }
-                    }
-                }
-            } else {
-                print!("{}", result.to_compact_report());
-                println!(
-                    "result: {} error(s), {} warning(s)",
-                    result.total_errors(),
-                    result.total_warnings()
-                );
-            }
+            print!("{}", result.to_compact_report());
+            println!(
+                "result: {} error(s), {} warning(s)",
+                result.total_errors(),
+                result.total_warnings()
+            );
         }
         _ => {
-            // Text format: clear screen + show current state
-            if changed.is_some() {
-                // Clear screen for incremental updates
-                print!("\x1B[2J\x1B[H");
-            }
-            if let Some(paths) = changed {
-                for path in paths {
-                    let fr = result.file_results.iter().find(|f| f.path == *path);
-                    match fr {
-                        Some(f) if f.diagnostics.is_empty() => {
-                            eprintln!(
-                                "[{}] {} changed — 0 errors, 0 warnings ✓",
-                                timestamp(),
-                                path
-                            );
-                        }
-                        Some(f) => {
-                            eprintln!(
-                                "[{}] {} changed — {} error(s)",
-                                timestamp(),
-                                path,
-                                f.errors()
-                            );
-                            for d in &f.diagnostics { println!("{d}"); }
-                        }
-                        None => {
-                            eprintln!(
-                                "[{}] {} changed — 0 errors, 0 warnings ✓",
-                                timestamp(),
-                                path
-                            );
-                        }
-                    }
-                }
-            }
             if result.total_errors() > 0 || result.total_warnings() > 0 {
                 print!("{}", result.to_report());
             } else {
@@ -347,3 +506,71 @@ fn print_result(result: &ValidationResult, format: OutputFormat, changed: Option
         }
     }
 }
+
+/// Report diagnostics that appeared or cleared since the last event, with the
+/// running totals.
+fn print_delta(delta: &DiagnosticDelta, state: &DiagnosticState, format: OutputFormat) {
+    let (errors, warnings) = (state.errors(), state.warnings());
+    match format {
+        OutputFormat::Json => {
+            let list = |v: &[(String, validation::Diagnostic)]| -> Vec<serde_json::Value> {
+                v.iter().map(|(path, d)| diagnostic_json(path, d)).collect()
+            };
+            let json = serde_json::json!({
+                "timestamp": timestamp(),
+                "new": list(&delta.new),
+                "resolved": list(&delta.resolved),
+                "errors": errors,
+                "warnings": warnings,
+                "ok": errors == 0,
+            });
+            println!("{}", serde_json::to_string(&json).unwrap_or_default());
+        }
+        OutputFormat::Compact => {
+            for (path, d) in &delta.new {
+                println!("+{path}:{}", d.to_compact());
+            }
+            for (path, d) in &delta.resolved {
+                println!("-{path}:{}", d.to_compact());
+            }
+            println!("result: {errors} error(s), {warnings} warning(s)");
+        }
+        _ => {
+            let summary = format!("{errors} error(s), {warnings} warning(s)");
+            if delta.is_empty() {
+                eprintln!("[{}] no new or resolved issues ({summary})", timestamp());
+                return;
+            }
+            eprintln!(
+                "[{}] {} new, {} resolved ({summary})",
+                timestamp(),
+                delta.new.len(),
+                delta.resolved.len()
+            );
+            let mut last = None;
+            for (path, d) in &delta.new {
+                if last != Some(path) {
+                    println!("{path}:");
+                    last = Some(path);
+                }
+                println!("{d}");
+            }
+            for (path, d) in &delta.resolved {
+                println!("  resolved {}[{}]: {} ({path}, {})", d.severity, d.code, d.message, d.location);
+            }
+        }
+    }
+}
+
+fn diagnostic_json(path: &str, d: &validation::Diagnostic) -> serde_json::Value {
+    serde_json::json!({
+        "path": path,
+        "severity": d.severity.to_string(),
+        "code": d.code,
+        "message": d.message,
+        "location": d.location,
+        "hint": d.hint,
+        "span": d.span,
+        "fingerprint": d.fingerprint(path),
+    })
+}
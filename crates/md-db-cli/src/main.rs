@@ -1,5 +1,9 @@
+use std::process::{Command, Stdio};
+
 use clap::{CommandFactory, Parser};
 use clap_complete::{generate, Shell};
+use md_db::durability::{self, Durability};
+use md_db::terminal::{self, ColorChoice};
 
 mod commands;
 
@@ -8,6 +12,18 @@ mod commands;
 struct Cli {
     #[command(subcommand)]
     command: CliCommand,
+
+    /// Colorize output: auto (terminal only, honors NO_COLOR), always, never
+    #[arg(long, global = true, default_value = "auto", value_parser = ["auto", "always", "never"])]
+    color: String,
+
+    /// Print long output directly instead of through $PAGER
+    #[arg(long, global = true)]
+    no_pager: bool,
+
+    /// When written files are fsynced: always (each file), on-batch-end (once the command is done), never
+    #[arg(long, global = true, default_value = "always", value_parser = ["always", "on-batch-end", "never"])]
+    durability: String,
 }
 
 #[derive(Debug, clap::Subcommand)]
@@ -23,6 +39,16 @@ enum CliCommand {
 
 fn main() {
     let cli = Cli::parse();
+    terminal::set_color(ColorChoice::from_str(&cli.color).unwrap_or(ColorChoice::Auto));
+    durability::set_policy(Durability::from_name(&cli.durability).unwrap_or(Durability::Always));
+
+    if let CliCommand::App(ref cmd) = cli.command {
+        if cmd.pages_output() && !cli.no_pager {
+            if let Some(code) = run_in_pager() {
+                std::process::exit(code);
+            }
+        }
+    }
 
     match cli.command {
         CliCommand::Completions { shell } => {
@@ -30,10 +56,46 @@ fn main() {
             generate(shell, &mut cmd, "md-db", &mut std::io::stdout());
         }
         CliCommand::App(ref cmd) => {
-            if let Err(e) = commands::run(cmd) {
+            let result = commands::run(cmd);
+            // Files written before a failure are synced too
+            let synced = durability::sync_pending();
+            if let Err(e) = result {
+                eprintln!("error: {e}");
+                std::process::exit(1);
+            }
+            if let Err(e) = synced {
                 eprintln!("error: {e}");
                 std::process::exit(1);
             }
         }
     }
 }
+
+/// Re-run this command with stdout piped into the pager. Returns the exit code, or
+/// `None` when output should go straight to stdout (not a terminal, already paged,
+/// paging disabled, or the pager could not be started).
+fn run_in_pager() -> Option<i32> {
+    if std::env::var_os(terminal::PAGED_ENV).is_some() || !std::io::IsTerminal::is_terminal(&std::io::stdout()) {
+        return None;
+    }
+    let pager_cmd = terminal::pager_command()?;
+    let exe = std::env::current_exe().ok()?;
+
+    let mut pager = Command::new(&pager_cmd[0]);
+    pager.args(&pager_cmd[1..]).stdin(Stdio::piped());
+    if std::env::var_os("LESS").is_none() {
+        // Quit if one screen, keep colors, don't clear the screen on exit
+        pager.env("LESS", "FRX");
+    }
+    let mut pager = pager.spawn().ok()?;
+    let pipe = pager.stdin.take()?;
+
+    // The child sees PAGED_ENV, so it treats the pipe as a terminal and doesn't page again
+    let status = Command::new(exe)
+        .args(std::env::args_os().skip(1))
+        .env(terminal::PAGED_ENV, "1")
+        .stdout(Stdio::from(pipe))
+        .status();
+    let _ = pager.wait();
+    Some(status.map_or(1, |s| s.code().unwrap_or(1)))
+}
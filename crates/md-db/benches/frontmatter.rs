@@ -0,0 +1,70 @@
+//! Frontmatter-only reads vs full parses, as `list` and discovery use them.
+//!
+//! Run with `cargo bench -p md-db --bench frontmatter`. Generates documents
+//! with long bodies in a temp dir and times reading every file's frontmatter
+//! both ways.
+
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+use md_db::frontmatter::Frontmatter;
+
+const DOCS: usize = 500;
+const BODY_PARAGRAPHS: usize = 200;
+const ROUNDS: usize = 5;
+
+fn main() {
+    let dir = tempfile::tempdir().unwrap();
+    let paragraph = "The quick brown fox jumps over the lazy dog, again and again. ".repeat(4);
+    let paths: Vec<PathBuf> = (0..DOCS)
+        .map(|i| {
+            let mut content = format!(
+                "---\ntype: adr\ntitle: Decision {i}\nstatus: accepted\ntags: [a, b]\n---\n\n# Context\n\n"
+            );
+            for p in 0..BODY_PARAGRAPHS {
+                content.push_str(&format!("{paragraph}\n\n"));
+                if p % 20 == 0 {
+                    content.push_str(&format!("## Part {p}\n\n| A | B |\n|---|---|\n| 1 | 2 |\n\n"));
+                }
+            }
+            let path = dir.path().join(format!("adr-{i:03}.md"));
+            std::fs::write(&path, content).unwrap();
+            path
+        })
+        .collect();
+
+    let full = time(|| {
+        paths
+            .iter()
+            .filter_map(|p| {
+                let content = std::fs::read_to_string(p).ok()?;
+                Frontmatter::try_parse(&content).ok()?.0
+            })
+            .filter(|fm| fm.get_display("status").as_deref() == Some("accepted"))
+            .count()
+    });
+    let fast = time(|| {
+        paths
+            .iter()
+            .filter_map(|p| Frontmatter::read_from_file(p).ok().flatten())
+            .filter(|fm| fm.get_display("status").as_deref() == Some("accepted"))
+            .count()
+    });
+
+    println!("{DOCS} documents, best of {ROUNDS} rounds");
+    println!("  full parse        {:>8.2} ms", full.as_secs_f64() * 1000.0);
+    println!("  frontmatter only  {:>8.2} ms", fast.as_secs_f64() * 1000.0);
+    println!("  speedup           {:>8.1}x", full.as_secs_f64() / fast.as_secs_f64());
+}
+
+/// Best wall time over `ROUNDS` runs; every run must see all documents.
+fn time(f: impl Fn() -> usize) -> Duration {
+    (0..ROUNDS)
+        .map(|_| {
+            let start = Instant::now();
+            assert_eq!(f(), DOCS);
+            start.elapsed()
+        })
+        .min()
+        .unwrap()
+}
@@ -0,0 +1,215 @@
+//! Group-by counts and numeric summaries over frontmatter (`md-db stats --group-by`).
+//!
+//! Documents are grouped by the values of one or more fields; a document with
+//! an array field joins one group per element, and a missing field groups under
+//! [`NONE`]. Each group reports its document count and, per metric field, the
+//! min, max, and average of the values that parse as numbers.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde::Serialize;
+use serde_yaml::Value;
+
+use crate::frontmatter::{yaml_value_to_string, Frontmatter};
+use crate::table::Table;
+
+/// Group value of documents without the field.
+pub const NONE: &str = "(none)";
+
+/// Min, max, and average of one numeric field within a group.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct NumericSummary {
+    /// Documents in the group with a numeric value.
+    pub count: usize,
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+    pub avg: Option<f64>,
+    pub sum: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Group {
+    /// The group's value of each `group_by` field, in order.
+    pub values: Vec<String>,
+    pub count: usize,
+    pub metrics: BTreeMap<String, NumericSummary>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Aggregation {
+    pub group_by: Vec<String>,
+    pub metrics: Vec<String>,
+    pub documents: usize,
+    /// Largest first; ties by key.
+    pub groups: Vec<Group>,
+}
+
+/// Group `docs` by the `group_by` fields and summarize the `metrics` fields in
+/// each group. Without `group_by` all documents form one group.
+pub fn aggregate(docs: &[Frontmatter], group_by: &[String], metrics: &[String]) -> Aggregation {
+    let mut groups: BTreeMap<Vec<String>, Vec<&Frontmatter>> = BTreeMap::new();
+    for fm in docs {
+        let mut keys: Vec<Vec<String>> = vec![Vec::new()];
+        for field in group_by {
+            let values = group_values(fm.get(field));
+            keys = keys
+                .into_iter()
+                .flat_map(|key| {
+                    values.iter().map(move |v| {
+                        let mut key = key.clone();
+                        key.push(v.clone());
+                        key
+                    })
+                })
+                .collect();
+        }
+        for key in keys {
+            groups.entry(key).or_default().push(fm);
+        }
+    }
+
+    let mut groups: Vec<Group> = groups
+        .into_iter()
+        .map(|(values, members)| Group {
+            values,
+            count: members.len(),
+            metrics: metrics
+                .iter()
+                .map(|m| (m.clone(), summarize(members.iter().filter_map(|fm| number(fm.get(m))))))
+                .collect(),
+        })
+        .collect();
+    // The BTreeMap left groups in key order, so a stable sort keeps ties that way
+    groups.sort_by(|a, b| b.count.cmp(&a.count));
+
+    Aggregation {
+        group_by: group_by.to_vec(),
+        metrics: metrics.to_vec(),
+        documents: docs.len(),
+        groups,
+    }
+}
+
+impl Aggregation {
+    /// One row per group: the group values, the count, then min, avg, and max per metric.
+    pub fn to_table(&self) -> Table {
+        let mut headers: Vec<String> = self.group_by.clone();
+        headers.push("count".into());
+        for m in &self.metrics {
+            headers.extend(["min", "avg", "max"].map(|stat| format!("{m} {stat}")));
+        }
+        let rows = self
+            .groups
+            .iter()
+            .map(|g| {
+                let mut row = g.values.clone();
+                row.push(g.count.to_string());
+                for m in &self.metrics {
+                    let s = &g.metrics[m];
+                    row.extend([s.min, s.avg, s.max].map(|v| v.map(format_number).unwrap_or_default()));
+                }
+                row
+            })
+            .collect();
+        Table::new(headers, rows)
+    }
+}
+
+fn group_values(value: Option<&Value>) -> Vec<String> {
+    let values: BTreeSet<String> = match value {
+        Some(Value::Sequence(items)) => items.iter().filter(|v| !v.is_null()).map(yaml_value_to_string).collect(),
+        Some(Value::Null) | None => BTreeSet::new(),
+        Some(v) => BTreeSet::from([yaml_value_to_string(v)]),
+    };
+    if values.is_empty() {
+        vec![NONE.to_string()]
+    } else {
+        values.into_iter().collect()
+    }
+}
+
+fn number(value: Option<&Value>) -> Option<f64> {
+    match value? {
+        Value::Number(n) => n.as_f64(),
+        Value::String(s) => s.trim().parse().ok(),
+        _ => None,
+    }
+    .filter(|n: &f64| n.is_finite())
+}
+
+fn summarize(values: impl Iterator<Item = f64>) -> NumericSummary {
+    let mut summary = NumericSummary {
+        count: 0,
+        min: None,
+        max: None,
+        avg: None,
+        sum: 0.0,
+    };
+    for v in values {
+        summary.count += 1;
+        summary.sum += v;
+        summary.min = Some(summary.min.map_or(v, |m| m.min(v)));
+        summary.max = Some(summary.max.map_or(v, |m| m.max(v)));
+    }
+    if summary.count > 0 {
+        summary.avg = Some(summary.sum / summary.count as f64);
+    }
+    summary
+}
+
+/// At most two decimals, without trailing zeros.
+fn format_number(n: f64) -> String {
+    let s = format!("{n:.2}");
+    s.trim_end_matches('0').trim_end_matches('.').to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn docs(yamls: &[&str]) -> Vec<Frontmatter> {
+        yamls
+            .iter()
+            .map(|y| Frontmatter::try_parse(&format!("---\n{y}\n---\n")).unwrap().0.unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_group_by_and_metrics() {
+        let docs = docs(&[
+            "type: inc\nseverity: sev1\nduration_minutes: 30",
+            "type: inc\nseverity: sev1\nduration_minutes: 45",
+            "type: inc\nseverity: sev2\nduration_minutes: \"10\"",
+            "type: inc\nduration_minutes: unknown",
+            "type: adr",
+        ]);
+        let group_by = vec!["type".to_string(), "severity".to_string()];
+        let agg = aggregate(&docs, &group_by, &["duration_minutes".to_string()]);
+        let keys: Vec<(String, usize)> = agg.groups.iter().map(|g| (g.values.join("/"), g.count)).collect();
+        assert_eq!(
+            keys,
+            vec![("inc/sev1".into(), 2), ("adr/(none)".into(), 1), ("inc/(none)".into(), 1), ("inc/sev2".into(), 1)]
+        );
+        let sev1 = &agg.groups[0].metrics["duration_minutes"];
+        assert_eq!((sev1.count, sev1.min, sev1.max, sev1.avg), (2, Some(30.0), Some(45.0), Some(37.5)));
+        assert_eq!(agg.groups[2].metrics["duration_minutes"].count, 0);
+
+        let table = agg.to_table();
+        assert_eq!(
+            table.headers(),
+            ["type", "severity", "count", "duration_minutes min", "duration_minutes avg", "duration_minutes max"]
+        );
+        assert_eq!(table.rows()[0], ["inc", "sev1", "2", "30", "37.5", "45"]);
+    }
+
+    #[test]
+    fn test_array_fields_and_no_grouping() {
+        let docs = docs(&["tags: [db, infra]", "tags: [db]", "title: untagged"]);
+        let agg = aggregate(&docs, &["tags".to_string()], &[]);
+        let counts: Vec<(&str, usize)> = agg.groups.iter().map(|g| (g.values[0].as_str(), g.count)).collect();
+        assert_eq!(counts, vec![("db", 2), (NONE, 1), ("infra", 1)]);
+
+        let all = aggregate(&docs, &[], &[]);
+        assert_eq!((all.groups.len(), all.groups[0].count), (1, 3));
+    }
+}
@@ -0,0 +1,567 @@
+//! Content-addressable attachment store.
+//!
+//! Binary attachments are stored once under `<root>/.md-db/assets/<hash>.<ext>`, where
+//! `<hash>` is derived from the file content, so attaching the same file to several
+//! documents never duplicates it. `<root>/.md-db/assets.json` records each blob's
+//! original name and referrers; `gc` re-scans the documents before deleting anything,
+//! so a blob is only removed when no markdown link or image points at it.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Component, Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::document::Document;
+use crate::error::{Error, Result};
+use crate::graph_cache::CACHE_DIR;
+
+/// Subdirectory of `CACHE_DIR` holding asset blobs.
+const ASSETS_DIR: &str = "assets";
+
+/// Index file (inside `CACHE_DIR`) describing stored assets.
+const INDEX_FILE: &str = "assets.json";
+
+/// Metadata tracked for a stored asset blob.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AssetRecord {
+    /// File name the asset was first added under (used as link text).
+    pub original_name: String,
+    /// Blob size in bytes.
+    pub size: u64,
+    /// Documents linking to the asset, relative to the store root.
+    pub referrers: BTreeSet<PathBuf>,
+}
+
+/// Result of adding a file to the store.
+#[derive(Debug, Clone)]
+pub struct AddedAsset {
+    /// Blob file name (`<hash>.<ext>`).
+    pub name: String,
+    /// Full path of the stored blob.
+    pub path: PathBuf,
+    /// True if an identical blob was already stored.
+    pub deduplicated: bool,
+}
+
+/// Attachment store rooted at a docs directory.
+#[derive(Debug)]
+pub struct AssetStore {
+    root: PathBuf,
+    records: BTreeMap<String, AssetRecord>,
+}
+
+impl AssetStore {
+    /// Open the store for a docs directory, loading the index if present.
+    pub fn open(root: impl AsRef<Path>) -> Result<Self> {
+        let root = root.as_ref().to_path_buf();
+        let index_path = root.join(CACHE_DIR).join(INDEX_FILE);
+        let records = if index_path.exists() {
+            let data = std::fs::read_to_string(&index_path)?;
+            serde_json::from_str(&data)?
+        } else {
+            BTreeMap::new()
+        };
+        Ok(Self { root, records })
+    }
+
+    /// Directory holding the blobs.
+    pub fn assets_dir(&self) -> PathBuf {
+        self.root.join(CACHE_DIR).join(ASSETS_DIR)
+    }
+
+    /// All tracked assets keyed by blob name.
+    pub fn records(&self) -> &BTreeMap<String, AssetRecord> {
+        &self.records
+    }
+
+    /// Write the index to `<root>/.md-db/assets.json`.
+    pub fn save(&self) -> Result<()> {
+        let dir = self.root.join(CACHE_DIR);
+        std::fs::create_dir_all(&dir).map_err(|_| Error::WriteFailed(dir.clone()))?;
+        let path = dir.join(INDEX_FILE);
+        let data = serde_json::to_string_pretty(&self.records)?;
+        crate::durability::write(&path, data)?;
+        Ok(())
+    }
+
+    /// Copy a file into the store. Identical content maps to the same blob.
+    pub fn add(&mut self, file: &Path) -> Result<AddedAsset> {
+        if !file.is_file() {
+            return Err(Error::FileNotFound(file.to_path_buf()));
+        }
+        let bytes = std::fs::read(file)?;
+        let digest = sha256_hex(&bytes);
+        let hash = &digest[..32];
+        let name = match file.extension().and_then(|e| e.to_str()) {
+            Some(ext) => format!("{hash}.{}", ext.to_lowercase()),
+            None => hash.to_string(),
+        };
+
+        let dir = self.assets_dir();
+        let path = dir.join(&name);
+        let deduplicated = path.exists();
+        if !deduplicated {
+            std::fs::create_dir_all(&dir).map_err(|_| Error::WriteFailed(dir.clone()))?;
+            crate::durability::write(&path, &bytes)?;
+        }
+
+        let original_name = file
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or(&name)
+            .to_string();
+        self.records.entry(name.clone()).or_insert_with(|| AssetRecord {
+            original_name,
+            size: bytes.len() as u64,
+            referrers: BTreeSet::new(),
+        });
+
+        Ok(AddedAsset {
+            name,
+            path,
+            deduplicated,
+        })
+    }
+
+    /// Insert a managed link to a stored asset into a document, appending a list item
+    /// to `section` (created if missing). Returns the document with the link added;
+    /// the link is not inserted twice.
+    pub fn link(&mut self, name: &str, doc_path: &Path, section: &str) -> Result<Document> {
+        let record = self
+            .records
+            .get(name)
+            .ok_or_else(|| Error::FileNotFound(self.assets_dir().join(name)))?;
+        let mut doc = Document::from_file(doc_path)?;
+
+        let doc_dir = absolute(doc_path.parent().unwrap_or(Path::new(".")));
+        let blob = absolute(&self.assets_dir().join(name));
+        let url = relative_path(&doc_dir, &blob)
+            .to_string_lossy()
+            .replace('\\', "/");
+
+        if !doc.body.contains(&format!("]({url})")) {
+            let line = format!("- [{}]({url})", record.original_name.replace(']', "\\]"));
+            doc.append_or_create_section(section, &line)?;
+        }
+
+        let referrer = self.relative_to_root(doc_path);
+        if let Some(record) = self.records.get_mut(name) {
+            record.referrers.insert(referrer);
+        }
+        Ok(doc)
+    }
+
+    /// Scan all markdown files under the root for links and images pointing into the
+    /// assets directory. Returns blob name → referring documents (relative to root).
+    pub fn scan_referrers(&self) -> Result<BTreeMap<String, BTreeSet<PathBuf>>> {
+        let assets_dir = absolute(&self.assets_dir());
+        let mut found: BTreeMap<String, BTreeSet<PathBuf>> = BTreeMap::new();
+
+        for path in crate::discovery::discover_files(&self.root, None, &[], false)? {
+            let Ok(content) = std::fs::read_to_string(&path) else {
+                continue;
+            };
+            let doc_dir = absolute(path.parent().unwrap_or(Path::new(".")));
+            let body = Document::from_str(&content)
+                .map(|d| d.body)
+                .unwrap_or(content);
+
+            for url in crate::ast_util::extract_link_and_image_urls(&body) {
+                if url.contains("://") || url.starts_with('#') {
+                    continue;
+                }
+                let target = normalize(&doc_dir.join(url.split('#').next().unwrap_or("")));
+                if target.parent() == Some(assets_dir.as_path()) {
+                    if let Some(name) = target.file_name().and_then(|n| n.to_str()) {
+                        found
+                            .entry(name.to_string())
+                            .or_default()
+                            .insert(self.relative_to_root(&path));
+                    }
+                }
+            }
+        }
+
+        Ok(found)
+    }
+
+    /// Remove blobs no document links to. The index is refreshed from a full scan
+    /// first, so stale referrer lists never keep or drop a blob incorrectly.
+    /// Returns the names of removed (or, with `dry_run`, removable) blobs.
+    pub fn gc(&mut self, dry_run: bool) -> Result<Vec<String>> {
+        let referrers = self.scan_referrers()?;
+        let dir = self.assets_dir();
+
+        let mut blobs: Vec<String> = Vec::new();
+        if dir.is_dir() {
+            for entry in std::fs::read_dir(&dir)?.flatten() {
+                if entry.path().is_file() {
+                    if let Some(name) = entry.file_name().to_str() {
+                        blobs.push(name.to_string());
+                    }
+                }
+            }
+        }
+        blobs.sort();
+
+        let mut removed = Vec::new();
+        for name in blobs {
+            match referrers.get(&name) {
+                Some(refs) => {
+                    let size = std::fs::metadata(dir.join(&name)).map(|m| m.len()).unwrap_or(0);
+                    let record = self.records.entry(name.clone()).or_insert_with(|| AssetRecord {
+                        original_name: name.clone(),
+                        size,
+                        referrers: BTreeSet::new(),
+                    });
+                    record.referrers = refs.clone();
+                }
+                None => {
+                    if !dry_run {
+                        let path = dir.join(&name);
+                        std::fs::remove_file(&path).map_err(|_| Error::WriteFailed(path))?;
+                        self.records.remove(&name);
+                    }
+                    removed.push(name);
+                }
+            }
+        }
+
+        if !dry_run {
+            // Drop index entries whose blob vanished outside of md-db.
+            self.records.retain(|name, _| dir.join(name).exists());
+        }
+
+        Ok(removed)
+    }
+
+    fn relative_to_root(&self, path: &Path) -> PathBuf {
+        let root = absolute(&self.root);
+        let path = absolute(path);
+        path.strip_prefix(&root)
+            .map(|p| p.to_path_buf())
+            .unwrap_or(path)
+    }
+}
+
+/// A relative link or image whose target file does not exist.
+#[derive(Debug, Clone)]
+pub struct BrokenLink {
+    /// The URL as written in the document.
+    pub url: String,
+    /// 1-based line within the document body.
+    pub line: usize,
+}
+
+/// The local file path a link URL points at, percent-decoded and without `#fragment`
+/// or `?query`. `None` for external URLs (`https:`, `mailto:`, ...), absolute paths,
+/// and in-page anchors.
+pub fn local_target(url: &str) -> Option<String> {
+    let url = url.trim();
+    if url.is_empty() || url.starts_with('#') || url.starts_with('/') {
+        return None;
+    }
+    if let Some(i) = url.find(':') {
+        let scheme = &url[..i];
+        if !scheme.is_empty() && scheme.chars().all(|c| c.is_ascii_alphanumeric() || "+.-".contains(c)) {
+            return None;
+        }
+    }
+    let path = url.split(['#', '?']).next().unwrap_or("");
+    if path.is_empty() {
+        return None;
+    }
+    Some(percent_decode(path))
+}
+
+/// Relative links and images in the body of the document at `doc_path` whose target
+/// does not exist on disk.
+pub fn broken_links(doc_path: &Path, body: &str) -> Vec<BrokenLink> {
+    let doc_dir = doc_path.parent().unwrap_or(Path::new("."));
+    crate::ast_util::find_link_and_image_urls(body)
+        .into_iter()
+        .filter(|(_, url)| local_target(url).is_some_and(|target| !doc_dir.join(target).exists()))
+        .map(|(line, url)| BrokenLink { url, line })
+        .collect()
+}
+
+/// Files under `folder` (recursively) that no document in `docs` links to, sorted.
+pub fn orphans(folder: &Path, docs: &[PathBuf]) -> Result<Vec<PathBuf>> {
+    if !folder.is_dir() {
+        return Ok(Vec::new());
+    }
+    let mut linked = BTreeSet::new();
+    for path in docs {
+        let Ok(content) = std::fs::read_to_string(path) else {
+            continue;
+        };
+        let doc_dir = absolute(path.parent().unwrap_or(Path::new(".")));
+        let body = Document::from_str(&content).map(|d| d.body).unwrap_or(content);
+        for url in crate::ast_util::extract_link_and_image_urls(&body) {
+            if let Some(target) = local_target(&url) {
+                linked.insert(absolute(&doc_dir.join(target)));
+            }
+        }
+    }
+
+    let mut found = Vec::new();
+    for entry in walkdir::WalkDir::new(folder).into_iter().flatten() {
+        if entry.file_type().is_file() && !linked.contains(&absolute(entry.path())) {
+            found.push(entry.path().to_path_buf());
+        }
+    }
+    found.sort();
+    Ok(found)
+}
+
+/// Decode `%XX` escapes (e.g. `my%20diagram.png`); malformed escapes are kept as written.
+pub fn percent_decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' && i + 2 < bytes.len() {
+            let hex = std::str::from_utf8(&bytes[i + 1..i + 3]).ok();
+            if let Some(b) = hex.and_then(|h| u8::from_str_radix(h, 16).ok()) {
+                out.push(b);
+                i += 3;
+                continue;
+            }
+        }
+        out.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+/// Canonicalize if possible, otherwise lexically normalize against the current directory.
+pub(crate) fn absolute(path: &Path) -> PathBuf {
+    if let Ok(p) = path.canonicalize() {
+        return p;
+    }
+    let joined = if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        std::env::current_dir().unwrap_or_default().join(path)
+    };
+    normalize(&joined)
+}
+
+/// Lexically resolve `.` and `..` components.
+pub(crate) fn normalize(path: &Path) -> PathBuf {
+    let mut out = PathBuf::new();
+    for comp in path.components() {
+        match comp {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                out.pop();
+            }
+            other => out.push(other.as_os_str()),
+        }
+    }
+    out
+}
+
+/// A relative path as written in a Markdown link: `/` separators, spaces escaped.
+pub(crate) fn url_path(path: &Path) -> String {
+    path.to_string_lossy().replace('\\', "/").replace(' ', "%20")
+}
+
+/// Relative path from directory `from` to `to` (both absolute).
+pub(crate) fn relative_path(from: &Path, to: &Path) -> PathBuf {
+    let from: Vec<Component> = from.components().collect();
+    let to: Vec<Component> = to.components().collect();
+    let common = from.iter().zip(&to).take_while(|(a, b)| a == b).count();
+
+    let mut out = PathBuf::new();
+    for _ in common..from.len() {
+        out.push("..");
+    }
+    for comp in &to[common..] {
+        out.push(comp.as_os_str());
+    }
+    out
+}
+
+/// SHA-256 digest as lowercase hex, implemented here to avoid pulling in a crypto crate
+/// for content addressing.
+pub(crate) fn sha256_hex(data: &[u8]) -> String {
+    const K: [u32; 64] = [
+        0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4,
+        0xab1c5ed5, 0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe,
+        0x9bdc06a7, 0xc19bf174, 0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc, 0x2de92c6f,
+        0x4a7484aa, 0x5cb0a9dc, 0x76f988da, 0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7,
+        0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967, 0x27b70a85, 0x2e1b2138, 0x4d2c6dfc,
+        0x53380d13, 0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85, 0xa2bfe8a1, 0xa81a664b,
+        0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070, 0x19a4c116,
+        0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f, 0x682e6ff3,
+        0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7,
+        0xc67178f2,
+    ];
+    let mut h: [u32; 8] = [
+        0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab,
+        0x5be0cd19,
+    ];
+
+    let mut msg = data.to_vec();
+    let bit_len = (data.len() as u64).wrapping_mul(8);
+    msg.push(0x80);
+    while msg.len() % 64 != 56 {
+        msg.push(0);
+    }
+    msg.extend_from_slice(&bit_len.to_be_bytes());
+
+    for chunk in msg.chunks(64) {
+        let mut w = [0u32; 64];
+        for (i, word) in chunk.chunks(4).enumerate() {
+            w[i] = u32::from_be_bytes([word[0], word[1], word[2], word[3]]);
+        }
+        for i in 16..64 {
+            let s0 = w[i - 15].rotate_right(7) ^ w[i - 15].rotate_right(18) ^ (w[i - 15] >> 3);
+            let s1 = w[i - 2].rotate_right(17) ^ w[i - 2].rotate_right(19) ^ (w[i - 2] >> 10);
+            w[i] = w[i - 16]
+                .wrapping_add(s0)
+                .wrapping_add(w[i - 7])
+                .wrapping_add(s1);
+        }
+
+        let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut hh] = h;
+        for i in 0..64 {
+            let s1 = e.rotate_right(6) ^ e.rotate_right(11) ^ e.rotate_right(25);
+            let ch = (e & f) ^ (!e & g);
+            let t1 = hh
+                .wrapping_add(s1)
+                .wrapping_add(ch)
+                .wrapping_add(K[i])
+                .wrapping_add(w[i]);
+            let s0 = a.rotate_right(2) ^ a.rotate_right(13) ^ a.rotate_right(22);
+            let maj = (a & b) ^ (a & c) ^ (b & c);
+            let t2 = s0.wrapping_add(maj);
+            hh = g;
+            g = f;
+            f = e;
+            e = d.wrapping_add(t1);
+            d = c;
+            c = b;
+            b = a;
+            a = t1.wrapping_add(t2);
+        }
+
+        for (slot, v) in h.iter_mut().zip([a, b, c, d, e, f, g, hh]) {
+            *slot = slot.wrapping_add(v);
+        }
+    }
+
+    h.iter().map(|v| format!("{v:08x}")).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sha256_known_vectors() {
+        assert_eq!(
+            sha256_hex(b""),
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+        assert_eq!(
+            sha256_hex(b"abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+    }
+
+    #[test]
+    fn test_relative_path() {
+        assert_eq!(
+            relative_path(Path::new("/repo/docs/adr"), Path::new("/repo/docs/.md-db/assets/x.pdf")),
+            PathBuf::from("../.md-db/assets/x.pdf")
+        );
+    }
+
+    #[test]
+    fn test_local_target() {
+        assert_eq!(local_target("./img/my%20diagram.png#frag").as_deref(), Some("./img/my diagram.png"));
+        assert_eq!(local_target("../adr-002.md?plain=1").as_deref(), Some("../adr-002.md"));
+        assert_eq!(local_target("https://example.com/x.png"), None);
+        assert_eq!(local_target("mailto:a@example.com"), None);
+        assert_eq!(local_target("#decision"), None);
+        assert_eq!(local_target("/abs/path.png"), None);
+    }
+
+    #[test]
+    fn test_broken_links_and_orphans() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        std::fs::create_dir_all(root.join("img")).unwrap();
+        std::fs::write(root.join("img/used.png"), b"png").unwrap();
+        std::fs::write(root.join("img/unused.png"), b"png").unwrap();
+        let doc = root.join("adr-001.md");
+        let body = "![](img/used.png)\n\n![](img/missing.png) and [site](https://example.com)\n";
+        std::fs::write(&doc, body).unwrap();
+
+        let broken = broken_links(&doc, body);
+        assert_eq!(broken.len(), 1);
+        assert_eq!(broken[0].url, "img/missing.png");
+        assert_eq!(broken[0].line, 3);
+
+        let orphans = orphans(&root.join("img"), &[doc]).unwrap();
+        assert_eq!(orphans, vec![root.join("img/unused.png")]);
+    }
+
+    #[test]
+    fn test_add_dedupes_and_links() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        std::fs::write(root.join("a.pdf"), b"%PDF same bytes").unwrap();
+        std::fs::write(root.join("b.pdf"), b"%PDF same bytes").unwrap();
+        let doc_path = root.join("adr-001.md");
+        std::fs::write(&doc_path, "---\ntitle: A\n---\n\n# Decision\n\nText.\n").unwrap();
+
+        let mut store = AssetStore::open(root).unwrap();
+        let first = store.add(&root.join("a.pdf")).unwrap();
+        let second = store.add(&root.join("b.pdf")).unwrap();
+        assert!(!first.deduplicated);
+        assert!(second.deduplicated);
+        assert_eq!(first.name, second.name);
+        assert!(first.name.ends_with(".pdf"));
+
+        let doc = store.link(&first.name, &doc_path, "Attachments").unwrap();
+        doc.save().unwrap();
+        assert!(doc.body.contains("# Attachments"));
+        assert!(doc.body.contains(&format!("[a.pdf](.md-db/assets/{})", first.name)));
+
+        // Linking again does not duplicate the list item
+        let again = store.link(&first.name, &doc_path, "Attachments").unwrap();
+        assert_eq!(again.body.matches(&first.name).count(), 1);
+
+        let refs = store.scan_referrers().unwrap();
+        assert_eq!(refs[&first.name].len(), 1);
+    }
+
+    #[test]
+    fn test_gc_removes_only_unreferenced() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        std::fs::write(root.join("keep.png"), b"keep").unwrap();
+        std::fs::write(root.join("drop.png"), b"drop").unwrap();
+        let doc_path = root.join("adr-001.md");
+        std::fs::write(&doc_path, "---\ntitle: A\n---\n\n# Decision\n\nText.\n").unwrap();
+
+        let mut store = AssetStore::open(root).unwrap();
+        let keep = store.add(&root.join("keep.png")).unwrap();
+        let drop = store.add(&root.join("drop.png")).unwrap();
+        store.link(&keep.name, &doc_path, "Attachments").unwrap().save().unwrap();
+
+        let removable = store.gc(true).unwrap();
+        assert_eq!(removable, vec![drop.name.clone()]);
+        assert!(drop.path.exists(), "dry run must not delete");
+
+        let removed = store.gc(false).unwrap();
+        assert_eq!(removed, vec![drop.name.clone()]);
+        assert!(!drop.path.exists());
+        assert!(keep.path.exists());
+        assert!(store.records().contains_key(&keep.name));
+        assert!(!store.records().contains_key(&drop.name));
+    }
+}
@@ -59,6 +59,19 @@ pub fn find_headings<'a>(
     headings
 }
 
+/// GitHub-style anchor for a heading: lowercased, punctuation dropped, spaces as `-`.
+pub fn heading_anchor(text: &str) -> String {
+    text.trim()
+        .to_lowercase()
+        .chars()
+        .filter_map(|c| match c {
+            ' ' => Some('-'),
+            c if c.is_alphanumeric() || c == '-' || c == '_' => Some(c),
+            _ => None,
+        })
+        .collect()
+}
+
 /// Find a heading node by exact text match (case-insensitive).
 pub fn find_heading_by_text<'a>(
     root: &'a AstNode<'a>,
@@ -223,6 +236,92 @@ pub fn extract_links(body: &str) -> Vec<String> {
     links
 }
 
+/// Extract URLs of all links and images from a markdown body.
+pub fn extract_link_and_image_urls(body: &str) -> Vec<String> {
+    let arena = Arena::new();
+    let opts = comrak_opts();
+    let root = comrak::parse_document(&arena, body, &opts);
+    let mut urls = Vec::new();
+    for node in root.descendants() {
+        match node.data.borrow().value {
+            NodeValue::Link(ref link) | NodeValue::Image(ref link) => urls.push(link.url.clone()),
+            _ => {}
+        }
+    }
+    urls
+}
+
+/// Like [`extract_link_and_image_urls`], paired with the 1-based body line of each link.
+pub fn find_link_and_image_urls(body: &str) -> Vec<(usize, String)> {
+    let arena = Arena::new();
+    let opts = comrak_opts();
+    let root = comrak::parse_document(&arena, body, &opts);
+    let mut urls = Vec::new();
+    for node in root.descendants() {
+        let data = node.data.borrow();
+        match data.value {
+            NodeValue::Link(ref link) | NodeValue::Image(ref link) => {
+                urls.push((data.sourcepos.start.line, link.url.clone()))
+            }
+            _ => {}
+        }
+    }
+    urls
+}
+
+/// `text` with the link and image URLs that `map` returns a replacement for
+/// rewritten, inline (`](url)`) and in reference definitions (`[x]: url`).
+pub fn rewrite_urls(text: &str, map: impl Fn(&str) -> Option<String>) -> String {
+    let mut replacements: std::collections::BTreeMap<String, String> = std::collections::BTreeMap::new();
+    for (_, url) in find_link_and_image_urls(text) {
+        if !replacements.contains_key(&url) {
+            if let Some(new) = map(&url) {
+                replacements.insert(url, new);
+            }
+        }
+    }
+    if replacements.is_empty() {
+        return text.to_string();
+    }
+    // Longest first, so a URL isn't matched by one of its prefixes
+    let mut urls: Vec<&String> = replacements.keys().collect();
+    urls.sort_by_key(|u| std::cmp::Reverse(u.len()));
+    let alternatives: Vec<String> = urls.iter().map(|u| regex::escape(u)).collect();
+    let re = regex::Regex::new(&format!(r"(\]\(<?|\]:[ \t]*<?)({})([>)\s]|$)", alternatives.join("|")))
+        .expect("escaped URLs form a valid pattern");
+    re.replace_all(text, |c: &regex::Captures| format!("{}{}{}", &c[1], replacements[&c[2]], &c[3]))
+        .into_owned()
+}
+
+/// `content` with every ATX heading moved `by` levels (negative raises),
+/// kept within levels 1 to 6.
+pub fn shift_headings(content: &str, by: i32) -> String {
+    if by == 0 {
+        return content.to_string();
+    }
+    let heading_lines: std::collections::HashSet<usize> = {
+        let arena = Arena::new();
+        let root = comrak::parse_document(&arena, content, &comrak_opts());
+        find_headings(root, None)
+            .into_iter()
+            .map(|node| node.data.borrow().sourcepos.start.line)
+            .collect()
+    };
+    content
+        .split_inclusive('\n')
+        .enumerate()
+        .map(|(i, line)| {
+            let trimmed = line.trim_start();
+            let hashes = trimmed.bytes().take_while(|b| *b == b'#').count();
+            if hashes == 0 || !heading_lines.contains(&(i + 1)) {
+                return line.to_string();
+            }
+            let level = (hashes as i32 + by).clamp(1, 6) as usize;
+            format!("{}{}", "#".repeat(level), &trimmed[hashes..])
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use comrak::{Arena, Options};
@@ -322,4 +421,10 @@ mod tests {
         let links = super::extract_links(md);
         assert!(links.is_empty());
     }
+
+    #[test]
+    fn test_heading_anchor() {
+        assert_eq!(heading_anchor("Pros & Cons"), "pros--cons");
+        assert_eq!(heading_anchor(" Step 2: Roll-out_plan "), "step-2-roll-out_plan");
+    }
 }
@@ -0,0 +1,117 @@
+//! Audit log of changes made through `md-db serve`: who changed what, and when.
+//!
+//! Each write appends one JSON object per line to `<root>/.md-db/audit.jsonl`.
+//! Unlike the undo log, entries are never dropped or rewritten.
+
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+
+use crate::error::{Error, Result};
+use crate::graph_cache::CACHE_DIR;
+
+/// Log file name inside `CACHE_DIR`.
+pub const LOG_FILE: &str = "audit.jsonl";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuditEntry {
+    /// As YYYY-MM-DDTHH:MM:SSZ.
+    pub time: String,
+    pub user: String,
+    /// What was done, e.g. "create" or "update-fields".
+    pub action: String,
+    pub id: String,
+    pub path: PathBuf,
+    /// Changed frontmatter fields: `{"status": {"from": "proposed", "to": "accepted"}}`.
+    pub changes: Map<String, Value>,
+}
+
+impl AuditEntry {
+    pub fn new(user: &str, action: &str, id: &str, path: impl Into<PathBuf>, changes: Map<String, Value>) -> Self {
+        Self {
+            time: crate::template::format_now(),
+            user: user.to_string(),
+            action: action.to_string(),
+            id: id.to_string(),
+            path: path.into(),
+            changes,
+        }
+    }
+}
+
+/// The top-level fields that differ between two frontmatter objects (JSON),
+/// with their old and new values; a missing field is `null`.
+pub fn field_changes(before: &Value, after: &Value) -> Map<String, Value> {
+    let empty = Map::new();
+    let before = before.as_object().unwrap_or(&empty);
+    let after = after.as_object().unwrap_or(&empty);
+    let mut changes = Map::new();
+    for key in before.keys().chain(after.keys()) {
+        let (from, to) = (before.get(key), after.get(key));
+        if from != to && !changes.contains_key(key) {
+            changes.insert(key.clone(), json!({ "from": from, "to": to }));
+        }
+    }
+    changes
+}
+
+/// Append an entry to the log under `root`.
+pub fn append(root: &Path, entry: &AuditEntry) -> Result<()> {
+    let dir = root.join(CACHE_DIR);
+    std::fs::create_dir_all(&dir).map_err(|_| Error::WriteFailed(dir.clone()))?;
+    let path = dir.join(LOG_FILE);
+    let line = serde_json::to_string(entry)?;
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .map_err(|_| Error::WriteFailed(path.clone()))?;
+    writeln!(file, "{line}").map_err(|_| Error::WriteFailed(path.clone()))?;
+    crate::durability::sync_file(&file, &path)
+}
+
+/// Every entry of the log under `root`, oldest first.
+pub fn read(root: &Path) -> Result<Vec<AuditEntry>> {
+    let path = root.join(CACHE_DIR).join(LOG_FILE);
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let content = std::fs::read_to_string(&path)?;
+    content
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| serde_json::from_str(line).map_err(Error::from))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_audit_log() {
+        let dir = tempfile::tempdir().unwrap();
+        let changes = field_changes(
+            &json!({ "title": "Use Kafka", "status": "proposed", "draft": true }),
+            &json!({ "title": "Use Kafka", "status": "accepted", "owner": "@ana" }),
+        );
+        assert_eq!(
+            Value::Object(changes.clone()),
+            json!({
+                "status": { "from": "proposed", "to": "accepted" },
+                "draft": { "from": true, "to": null },
+                "owner": { "from": null, "to": "@ana" },
+            })
+        );
+
+        append(dir.path(), &AuditEntry::new("alice", "update-fields", "ADR-001", "adr-001.md", changes)).unwrap();
+        append(dir.path(), &AuditEntry::new("ci-bot", "create", "ADR-002", "adr-002.md", Map::new())).unwrap();
+        let entries = read(dir.path()).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!((entries[0].user.as_str(), entries[0].id.as_str()), ("alice", "ADR-001"));
+        assert_eq!(entries[0].changes["status"]["to"], "accepted");
+        assert_eq!(entries[1].action, "create");
+    }
+}
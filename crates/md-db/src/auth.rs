@@ -0,0 +1,98 @@
+//! Bearer tokens for `md-db serve`.
+//!
+//! The token file is YAML mapping each user to their token:
+//!
+//! ```yaml
+//! alice: 3f9c0d2e8b7a41c6a5e0
+//! ci-bot: 71ab5f04d9e2c3b86a1f
+//! ```
+//!
+//! A request carries `Authorization: Bearer <token>` and acts as that user,
+//! whose name goes into the audit log.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use crate::error::{Error, Result};
+
+/// Tokens shorter than this are refused when the file is loaded.
+pub const MIN_TOKEN_LEN: usize = 16;
+
+#[derive(Debug, Clone)]
+pub struct ApiTokens {
+    /// User by token.
+    users: BTreeMap<String, String>,
+}
+
+impl ApiTokens {
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path).map_err(|_| Error::FileNotFound(path.to_path_buf()))?;
+        Self::from_str(&content).map_err(|e| match e {
+            Error::Auth(msg) => Error::Auth(format!("{}: {msg}", path.display())),
+            other => other,
+        })
+    }
+
+    pub fn from_str(content: &str) -> Result<Self> {
+        let tokens: BTreeMap<String, String> =
+            serde_yaml::from_str(content).map_err(|e| Error::Auth(format!("expected `user: token` lines: {e}")))?;
+        let mut users = BTreeMap::new();
+        for (user, token) in tokens {
+            let token = token.trim().to_string();
+            if token.len() < MIN_TOKEN_LEN {
+                return Err(Error::Auth(format!("token of {user} is shorter than {MIN_TOKEN_LEN} characters")));
+            }
+            if let Some(other) = users.insert(token, user.clone()) {
+                return Err(Error::Auth(format!("{other} and {user} share a token")));
+            }
+        }
+        if users.is_empty() {
+            return Err(Error::Auth("no tokens".into()));
+        }
+        Ok(Self { users })
+    }
+
+    /// The user an `Authorization` header value authenticates, if any.
+    pub fn authenticate(&self, authorization: Option<&str>) -> Option<&str> {
+        let (scheme, token) = authorization?.trim().split_once(' ')?;
+        if !scheme.eq_ignore_ascii_case("bearer") {
+            return None;
+        }
+        let token = token.trim().as_bytes();
+        // Compare against every token, each in constant time, so timing reveals nothing
+        let mut found = None;
+        for (known, user) in &self.users {
+            if constant_time_eq(known.as_bytes(), token) {
+                found = Some(user.as_str());
+            }
+        }
+        found
+    }
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_api_tokens() {
+        let tokens = ApiTokens::from_str("alice: 3f9c0d2e8b7a41c6a5e0\nci-bot: 71ab5f04d9e2c3b86a1f\n").unwrap();
+        assert_eq!(tokens.authenticate(Some("Bearer 3f9c0d2e8b7a41c6a5e0")), Some("alice"));
+        assert_eq!(tokens.authenticate(Some("bearer  71ab5f04d9e2c3b86a1f ")), Some("ci-bot"));
+        assert_eq!(tokens.authenticate(Some("Bearer 3f9c0d2e8b7a41c6a5e1")), None);
+        assert_eq!(tokens.authenticate(Some("Basic 3f9c0d2e8b7a41c6a5e0")), None);
+        assert_eq!(tokens.authenticate(None), None);
+
+        assert!(ApiTokens::from_str("alice: short\n").is_err());
+        assert!(ApiTokens::from_str("a: 3f9c0d2e8b7a41c6a5e0\nb: 3f9c0d2e8b7a41c6a5e0\n").is_err());
+        assert!(ApiTokens::from_str("{}").is_err());
+    }
+}
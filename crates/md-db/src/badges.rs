@@ -0,0 +1,325 @@
+//! Per-document reading time and completeness.
+//!
+//! Reading time counts the words of paragraphs and list items (code and tables
+//! are skipped). Completeness is the share of the type's required sections that
+//! exist and hold more than the scaffold `md-db new` generates.
+//!
+//! Project badges summarize the whole directory (counts per type, validation
+//! status) for the `<!-- md-db:badges -->` block `md-db badges --write` maintains.
+
+use std::path::Path;
+
+use serde::Serialize;
+
+use crate::document::Document;
+use crate::error::Result;
+use crate::frontmatter::Frontmatter;
+use crate::schema::{FieldType, Schema};
+use crate::validation::ValidationResult;
+
+/// Average adult reading speed for technical prose.
+pub const WORDS_PER_MINUTE: usize = 200;
+
+/// Badge values for one document.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DocBadges {
+    pub words: usize,
+    /// Rounded up; at least 1 for any non-empty document.
+    pub reading_minutes: usize,
+    /// `None` without a schema or for documents whose type has no required sections.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub completeness: Option<Completeness>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Completeness {
+    pub filled: usize,
+    pub required: usize,
+    /// Required sections that are missing or still scaffold.
+    pub unfilled: Vec<String>,
+}
+
+impl Completeness {
+    /// Filled share, 0–100.
+    pub fn percent(&self) -> usize {
+        if self.required == 0 {
+            100
+        } else {
+            self.filled * 100 / self.required
+        }
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.filled == self.required
+    }
+}
+
+impl DocBadges {
+    /// `"4 min read"`
+    pub fn reading_time_label(&self) -> String {
+        format!("{} min read", self.reading_minutes)
+    }
+
+    /// `"2/3 sections"`
+    pub fn completeness_label(&self) -> Option<String> {
+        self.completeness
+            .as_ref()
+            .map(|c| format!("{}/{} sections", c.filled, c.required))
+    }
+}
+
+/// Minutes to read `words`, rounded up.
+pub fn reading_minutes(words: usize) -> usize {
+    words.div_ceil(WORDS_PER_MINUTE)
+}
+
+/// Compute badges for a document. Completeness needs the schema to know which
+/// sections the document's type requires.
+pub fn compute(doc: &Document, schema: Option<&Schema>) -> DocBadges {
+    let words = crate::prose::word_count(&crate::prose::prose_text(&doc.body));
+
+    let type_def = schema.and_then(|schema| {
+        let type_name = doc.frontmatter.as_ref()?.get_display("type")?;
+        schema.get_type(&type_name)
+    });
+    let completeness = type_def.and_then(|type_def| {
+        let required: Vec<&str> = type_def
+            .sections
+            .iter()
+            .filter(|s| s.required)
+            .map(|s| s.name.as_str())
+            .collect();
+        if required.is_empty() {
+            return None;
+        }
+        let unfilled: Vec<String> = required
+            .iter()
+            .filter(|name| match doc.get_section(name) {
+                Ok(section) => crate::template::is_scaffold(&section.content),
+                Err(_) => true,
+            })
+            .map(|name| name.to_string())
+            .collect();
+        Some(Completeness {
+            filled: required.len() - unfilled.len(),
+            required: required.len(),
+            unfilled,
+        })
+    });
+
+    DocBadges {
+        words,
+        reading_minutes: reading_minutes(words),
+        completeness,
+    }
+}
+
+/// Marker block name maintained by `md-db badges --write`, see [`crate::marker`].
+pub const BLOCK_NAME: &str = "badges";
+
+/// Project-wide counts rendered into a README badge block.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ProjectBadges {
+    /// Quarter the `accepted` counts refer to, e.g. "2026-Q4".
+    pub quarter: String,
+    pub types: Vec<TypeCounts>,
+    pub errors: usize,
+    pub warnings: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TypeCounts {
+    pub doc_type: String,
+    pub total: usize,
+    /// An active status (`accepted` unless the type declares a `status-field`) with a
+    /// date in the current quarter. The date is the type's first `date`/`datetime`
+    /// field, or a field named `date`.
+    pub accepted_this_quarter: usize,
+    /// Documents whose status is neither active nor terminal.
+    pub open: usize,
+}
+
+/// Count the documents under `dir` per schema type. `today` is `YYYY-MM-DD` and picks
+/// the quarter; `validation` supplies the error and warning totals for the shield.
+pub fn project(
+    dir: impl AsRef<Path>,
+    schema: &Schema,
+    today: &str,
+    validation: &ValidationResult,
+) -> Result<ProjectBadges> {
+    let files = crate::discovery::discover_files(&dir, None, &[], false)?;
+    let frontmatters: Vec<Frontmatter> = files
+        .iter()
+        .filter_map(|p| Frontmatter::read_from_file(p).ok().flatten())
+        .collect();
+    Ok(tally(&frontmatters, schema, today, validation))
+}
+
+fn tally(
+    frontmatters: &[Frontmatter],
+    schema: &Schema,
+    today: &str,
+    validation: &ValidationResult,
+) -> ProjectBadges {
+    let year: i64 = today.get(..4).and_then(|y| y.parse().ok()).unwrap_or(1970);
+    let month: i64 = today.get(5..7).and_then(|m| m.parse().ok()).unwrap_or(1);
+    let q = (month - 1) / 3;
+    let quarter_start = crate::validation::parse_date(&format!("{year:04}-{:02}-01", q * 3 + 1));
+    let quarter_end = if q == 3 {
+        crate::validation::parse_date(&format!("{:04}-01-01", year + 1))
+    } else {
+        crate::validation::parse_date(&format!("{year:04}-{:02}-01", q * 3 + 4))
+    };
+
+    let types = schema
+        .types
+        .iter()
+        .filter(|t| !t.singleton)
+        .map(|type_def| {
+            let date_field = type_def
+                .fields
+                .iter()
+                .find(|f| matches!(f.field_type, FieldType::Date | FieldType::DateTime))
+                .map_or("date", |f| f.name.as_str());
+            let status_field = schema.status_field(&type_def.name);
+            let status = |fm: &Frontmatter| fm.get_display(&status_field.field);
+            let docs: Vec<&Frontmatter> = frontmatters
+                .iter()
+                .filter(|fm| fm.get_display("type").as_deref() == Some(type_def.name.as_str()))
+                .collect();
+            let accepted_this_quarter = docs
+                .iter()
+                .filter(|fm| status(fm).is_some_and(|s| status_field.is_active(&s)))
+                .filter_map(|fm| fm.get_display(date_field))
+                .filter_map(|d| crate::validation::parse_date(d.get(..10).unwrap_or(&d)))
+                .filter(|d| quarter_start.is_some_and(|s| *d >= s) && quarter_end.is_some_and(|e| *d < e))
+                .count();
+            let open = docs
+                .iter()
+                .filter_map(|fm| status(fm))
+                .filter(|s| status_field.is_open(s))
+                .count();
+            TypeCounts {
+                doc_type: type_def.name.clone(),
+                total: docs.len(),
+                accepted_this_quarter,
+                open,
+            }
+        })
+        .collect();
+
+    ProjectBadges {
+        quarter: format!("{year:04}-Q{}", q + 1),
+        types,
+        errors: validation.total_errors(),
+        warnings: validation.total_warnings(),
+    }
+}
+
+impl ProjectBadges {
+    /// shields.io badge for the validation status.
+    pub fn validation_shield(&self) -> String {
+        let (label, color) = if self.errors > 0 {
+            (format!("{}%20errors", self.errors), "red")
+        } else if self.warnings > 0 {
+            (format!("{}%20warnings", self.warnings), "yellow")
+        } else {
+            ("passing".to_string(), "brightgreen")
+        };
+        format!("![validation](https://img.shields.io/badge/validation-{label}-{color})")
+    }
+
+    /// Markdown for the badge block: the validation shield and a table of counts.
+    /// Contains no timestamps, so regenerating an unchanged project is a no-op.
+    pub fn to_markdown(&self) -> String {
+        let mut out = format!("{}\n\n", self.validation_shield());
+        out.push_str(&format!("| Type | Total | Accepted in {} | Open |\n", self.quarter));
+        out.push_str("|------|------:|------:|------:|\n");
+        for t in &self.types {
+            out.push_str(&format!(
+                "| {} | {} | {} | {} |\n",
+                t.doc_type, t.total, t.accepted_this_quarter, t.open
+            ));
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compute() {
+        let schema = Schema::from_str(
+            r#"
+type "adr" {
+    section "Context" required=#true
+    section "Decision" required=#true
+    section "Consequences" required=#true {
+        section "Positive"
+    }
+    section "Notes"
+}
+"#,
+        )
+        .unwrap();
+        let body = "We need a database. ".repeat(100);
+        let doc = Document::from_str(&format!(
+            "---\ntype: adr\n---\n\n# Context\n\n{body}\n\n# Decision\n\nTODO\n\n# Consequences\n\n## Positive\n\n```sh\nmake\n```\n"
+        ))
+        .unwrap();
+
+        let badges = compute(&doc, Some(&schema));
+        // 400 words of context plus the "TODO" placeholder
+        assert_eq!(badges.words, 401);
+        assert_eq!(badges.reading_time_label(), "3 min read");
+        let c = badges.completeness.as_ref().unwrap();
+        assert_eq!((c.filled, c.required, c.percent()), (2, 3, 66));
+        assert_eq!(c.unfilled, vec!["Decision"]);
+        assert_eq!(badges.completeness_label().as_deref(), Some("2/3 sections"));
+
+        assert!(compute(&doc, None).completeness.is_none());
+        assert_eq!(reading_minutes(0), 0);
+        assert_eq!(reading_minutes(1), 1);
+    }
+
+    #[test]
+    fn test_project_badges() {
+        let schema = Schema::from_str(
+            r#"
+type "adr" {
+    field "decided" type="date"
+}
+type "inc" {
+}
+type "readme" singleton=#true {
+    match "README.md"
+}
+"#,
+        )
+        .unwrap();
+        let fm = |yaml: &str| Frontmatter::try_parse(&format!("---\n{yaml}\n---\n")).unwrap().0.unwrap();
+        let docs = vec![
+            fm("type: adr\nstatus: accepted\ndecided: 2026-10-02"),
+            fm("type: adr\nstatus: accepted\ndecided: 2026-09-30"),
+            fm("type: adr\nstatus: proposed"),
+            fm("type: inc\nstatus: open\ndate: 2026-11-01"),
+            fm("type: inc\nstatus: resolved"),
+        ];
+        let badges = tally(&docs, &schema, "2026-10-16", &ValidationResult { file_results: vec![] });
+
+        assert_eq!(badges.quarter, "2026-Q4");
+        assert_eq!(badges.types.len(), 2);
+        assert_eq!(
+            badges.types[0],
+            TypeCounts { doc_type: "adr".into(), total: 3, accepted_this_quarter: 1, open: 1 }
+        );
+        assert_eq!((badges.types[1].total, badges.types[1].open), (2, 1));
+
+        let md = badges.to_markdown();
+        assert!(md.starts_with("![validation](https://img.shields.io/badge/validation-passing-brightgreen)"));
+        assert!(md.contains("| Type | Total | Accepted in 2026-Q4 | Open |"));
+        assert!(md.contains("| adr | 3 | 1 | 1 |"));
+    }
+}
@@ -0,0 +1,204 @@
+//! Kanban-style board of documents grouped by a field (`md-db report board`).
+//!
+//! Each value of the field (usually `status`) becomes a `##` section listing
+//! links to its documents. Values the schema declares for an enum field come
+//! first, in schema order and even when empty; other values follow, and
+//! documents without the field go last under [`NONE`]. Written to a file, the
+//! board lives in a `<!-- md-db:board -->` block (see [`crate::marker`]), so
+//! re-runs leave the text around it alone.
+
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+
+use crate::aggregate::NONE;
+use crate::discovery::{self, Filter};
+use crate::error::Result;
+use crate::frontmatter::Frontmatter;
+use crate::graph::path_to_id;
+use crate::schema::{FieldType, Schema};
+
+/// Marker block name maintained by `md-db report board --output`.
+pub const BLOCK_NAME: &str = "board";
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Card {
+    pub id: String,
+    pub title: Option<String>,
+    /// Path to the document, relative to the board's directory.
+    pub link: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Column {
+    pub value: String,
+    pub cards: Vec<Card>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Board {
+    pub group_by: String,
+    pub doc_type: Option<String>,
+    pub columns: Vec<Column>,
+}
+
+/// Group the documents under `dir` (of `doc_type`, if given) by `group_by`.
+/// Links are relative to the directory `link_from`; cards follow the schema's
+/// document order.
+pub fn board(
+    dir: &Path,
+    doc_type: Option<&str>,
+    group_by: &str,
+    schema: Option<&Schema>,
+    link_from: &Path,
+) -> Result<Board> {
+    let filters: Vec<Filter> = doc_type
+        .map(|t| Filter::FieldEquals {
+            key: "type".into(),
+            value: t.into(),
+        })
+        .into_iter()
+        .collect();
+    let mut docs: Vec<(String, PathBuf, Frontmatter)> = discovery::discover_files(dir, None, &filters, false)?
+        .into_iter()
+        .filter_map(|path| {
+            let fm = Frontmatter::read_from_file(&path).ok().flatten()?;
+            Some((path_to_id(&path), path, fm))
+        })
+        .collect();
+    crate::ordering::sort_documents(&mut docs, schema, |(id, _, fm)| (id.as_str(), Some(fm)));
+
+    let mut columns: Vec<Column> = declared_values(schema, doc_type, group_by)
+        .into_iter()
+        .map(|value| Column { value, cards: Vec::new() })
+        .collect();
+    let declared = columns.len();
+    let from = link_from.canonicalize().unwrap_or_else(|_| link_from.to_path_buf());
+    for (id, path, fm) in docs {
+        let value = fm
+            .get_display(group_by)
+            .filter(|v| !v.trim().is_empty())
+            .unwrap_or_else(|| NONE.to_string());
+        let target = path.canonicalize().unwrap_or(path);
+        let card = Card {
+            id,
+            title: fm.get_display("title"),
+            link: crate::assets::relative_path(&from, &target).to_string_lossy().replace('\\', "/"),
+        };
+        match columns.iter_mut().find(|c| c.value == value) {
+            Some(column) => column.cards.push(card),
+            None => columns.push(Column { value, cards: vec![card] }),
+        }
+    }
+    columns[declared..].sort_by(|a, b| {
+        (a.value == NONE)
+            .cmp(&(b.value == NONE))
+            .then_with(|| crate::ordering::compare_text(&a.value, &b.value))
+    });
+
+    Ok(Board {
+        group_by: group_by.to_string(),
+        doc_type: doc_type.map(str::to_string),
+        columns,
+    })
+}
+
+/// Enum values the schema declares for `field`, in order, across `doc_type` or every type.
+fn declared_values(schema: Option<&Schema>, doc_type: Option<&str>, field: &str) -> Vec<String> {
+    let mut values: Vec<String> = Vec::new();
+    let types = schema.iter().flat_map(|s| &s.types);
+    for type_def in types.filter(|t| doc_type.is_none_or(|d| t.name == d)) {
+        for field_def in type_def.fields.iter().filter(|f| f.name == field) {
+            if let FieldType::Enum(ref declared) = field_def.field_type {
+                for value in declared {
+                    if !values.contains(value) {
+                        values.push(value.clone());
+                    }
+                }
+            }
+        }
+    }
+    values
+}
+
+impl Board {
+    /// One `##` section per column with its document count, and a link list.
+    /// Contains no timestamps, so regenerating an unchanged board is a no-op.
+    pub fn to_markdown(&self) -> String {
+        let mut out = String::new();
+        for (i, column) in self.columns.iter().enumerate() {
+            if i > 0 {
+                out.push('\n');
+            }
+            out.push_str(&format!("## {} ({})\n\n", column.value, column.cards.len()));
+            if column.cards.is_empty() {
+                out.push_str("_No documents._\n");
+            }
+            for card in &column.cards {
+                let link = card.link.replace(' ', "%20");
+                match card.title {
+                    Some(ref title) => out.push_str(&format!("- [{}]({link}) {title}\n", card.id)),
+                    None => out.push_str(&format!("- [{}]({link})\n", card.id)),
+                }
+            }
+        }
+        out
+    }
+
+    /// `text` with its board block replaced, `None` when it has no block. A new
+    /// file (`text` of `None`) gets a heading and the block.
+    pub fn update(&self, text: Option<&str>) -> Result<Option<String>> {
+        let Some(text) = text else {
+            let heading = match self.doc_type {
+                Some(ref t) => format!("{t} by {}", self.group_by),
+                None => format!("Documents by {}", self.group_by),
+            };
+            return Ok(Some(format!(
+                "# {heading}\n\n{}\n{}{}\n",
+                crate::marker::start_marker(BLOCK_NAME),
+                self.to_markdown(),
+                crate::marker::end_marker(BLOCK_NAME)
+            )));
+        };
+        crate::marker::replace_block(text, BLOCK_NAME, &self.to_markdown())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_board() {
+        let dir = tempfile::tempdir().unwrap();
+        let docs = dir.path().join("docs");
+        std::fs::create_dir(&docs).unwrap();
+        let doc = |name: &str, fm: &str| std::fs::write(docs.join(name), format!("---\n{fm}\n---\n")).unwrap();
+        doc("opp-001.md", "type: opp\ntitle: Faster builds\nstatus: open");
+        doc("opp-002.md", "type: opp\ntitle: Cache deps\nstatus: parked");
+        doc("opp-003.md", "type: opp\ntitle: Drop CI\nstatus: open");
+        doc("opp-004.md", "type: opp\ntitle: Unsorted");
+        doc("adr-001.md", "type: adr\ntitle: Use Kafka\nstatus: open");
+        let schema = Schema::from_str(
+            "type \"opp\" {\n    field \"status\" type=\"enum\" {\n        values \"open\" \"won\" \"lost\"\n    }\n}\n\
+             type \"adr\"\n",
+        )
+        .unwrap();
+
+        let board = board(&docs, Some("opp"), "status", Some(&schema), dir.path()).unwrap();
+        let columns: Vec<(&str, usize)> = board.columns.iter().map(|c| (c.value.as_str(), c.cards.len())).collect();
+        assert_eq!(columns, vec![("open", 2), ("won", 0), ("lost", 0), ("parked", 1), (NONE, 1)]);
+        assert_eq!(board.columns[0].cards[1].link, "docs/opp-003.md");
+
+        let markdown = board.to_markdown();
+        assert!(markdown.starts_with("## open (2)\n\n- [OPP-001](docs/opp-001.md) Faster builds\n"), "{markdown}");
+        assert!(markdown.contains("## won (0)\n\n_No documents._\n"), "{markdown}");
+
+        // A new file gets a heading; later runs replace only the block
+        let created = board.update(None).unwrap().unwrap();
+        assert!(created.starts_with("# opp by status\n\n<!-- md-db:board -->\n## open (2)"), "{created}");
+        let edited = created.replace("# opp by status\n", "# Pipeline\n\nNotes.\n");
+        assert_eq!(board.update(Some(&edited)).unwrap().unwrap(), edited);
+        assert!(board.update(Some("# Pipeline\n")).unwrap().is_none());
+    }
+}
@@ -4,7 +4,8 @@ use std::time::SystemTime;
 
 use serde::{Deserialize, Serialize};
 
-use crate::error::{Error, Result};
+use crate::cache_file;
+use crate::error::Result;
 use crate::frontmatter::Frontmatter;
 
 /// Cached metadata for a single document file.
@@ -20,7 +21,7 @@ pub struct CacheEntry {
     pub size: u64,
 }
 
-/// In-memory document cache with file-based JSON persistence.
+/// In-memory document cache, persisted through [`cache_file`].
 #[derive(Debug)]
 pub struct DocCache {
     entries: HashMap<PathBuf, CacheEntry>,
@@ -36,25 +37,21 @@ impl DocCache {
         }
     }
 
-    /// Load cache from a JSON file. Returns empty cache if file doesn't exist or is invalid.
+    /// Load a cache file written by [`save`](Self::save). Returns an empty cache if
+    /// the file doesn't exist, is from another md-db version, or is corrupt; a
+    /// corrupt cache is marked dirty so the next save replaces it.
     pub fn load(path: &Path) -> Result<Self> {
-        if !path.exists() {
-            return Ok(Self::new());
-        }
-        let data = std::fs::read_to_string(path)?;
-        let entries: HashMap<PathBuf, CacheEntry> =
-            serde_json::from_str(&data).map_err(Error::Json)?;
-        Ok(Self {
-            entries,
-            dirty: false,
-        })
+        let (entries, dirty) = match cache_file::load(path, &cache_file::DOCS, 0) {
+            cache_file::Loaded::Hit(entries) => (entries, false),
+            cache_file::Loaded::Missing => (HashMap::new(), false),
+            cache_file::Loaded::Outdated | cache_file::Loaded::Corrupt(_) => (HashMap::new(), true),
+        };
+        Ok(Self { entries, dirty })
     }
 
-    /// Save cache to a JSON file.
+    /// Save the cache in md-db's versioned cache format.
     pub fn save(&self, path: &Path) -> Result<()> {
-        let data = serde_json::to_string_pretty(&self.entries)?;
-        std::fs::write(path, data)?;
-        Ok(())
+        cache_file::save(path, &cache_file::DOCS, 0, &self.entries)
     }
 
     /// Get a cached entry for a path.
@@ -169,9 +166,14 @@ fn mtime_secs(meta: &std::fs::Metadata) -> u64 {
 
 /// Simple non-cryptographic hash for content change detection.
 /// Uses FNV-1a for speed.
-fn simple_hash(data: &str) -> u64 {
+pub(crate) fn simple_hash(data: &str) -> u64 {
+    hash_bytes(data.as_bytes())
+}
+
+/// FNV-1a over raw bytes.
+pub(crate) fn hash_bytes(data: &[u8]) -> u64 {
     let mut hash: u64 = 0xcbf29ce484222325;
-    for byte in data.bytes() {
+    for &byte in data {
         hash ^= byte as u64;
         hash = hash.wrapping_mul(0x100000001b3);
     }
@@ -257,7 +259,7 @@ mod tests {
     fn test_save_and_load_roundtrip() {
         let dir = tempfile::tempdir().unwrap();
         let md_path = create_temp_md(dir.path(), "doc.md", "---\ntitle: Cached\n---\nbody\n");
-        let cache_path = dir.path().join(".md-db-cache.json");
+        let cache_path = dir.path().join(".md-db").join("docs.bin");
 
         let mut cache = DocCache::new();
         cache.refresh(&md_path).unwrap();
@@ -272,6 +274,17 @@ mod tests {
         assert_eq!(fm.get("title").unwrap(), "Cached");
     }
 
+    #[test]
+    fn test_load_corrupt_returns_empty_dirty() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache_path = dir.path().join("docs.bin");
+        std::fs::write(&cache_path, "{\"legacy\": \"json\"}").unwrap();
+
+        let loaded = DocCache::load(&cache_path).unwrap();
+        assert!(loaded.is_empty());
+        assert!(loaded.is_dirty());
+    }
+
     #[test]
     fn test_load_nonexistent_returns_empty() {
         let cache = DocCache::load(Path::new("/tmp/nonexistent-cache-file.json")).unwrap();
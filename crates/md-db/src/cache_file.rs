@@ -0,0 +1,339 @@
+//! Versioned container for md-db's on-disk caches.
+//!
+//! Every cache file md-db writes (the graph snapshot under `.md-db/`, a
+//! [`DocCache`](crate::cache::DocCache) wherever it is saved) is a fixed header
+//! followed by a compact JSON payload:
+//!
+//! | Bytes  | Field                                              |
+//! |--------|----------------------------------------------------|
+//! | 0..8   | magic `MDDBCACH`                                   |
+//! | 8..10  | container format version (u16, little-endian)      |
+//! | 10..14 | cache kind tag, e.g. `GRPH`                        |
+//! | 14..18 | payload version of that kind (u32)                 |
+//! | 18..26 | schema key the payload was built against (u64)     |
+//! | 26..34 | FNV-1a checksum of the payload (u64)               |
+//! | 34..42 | payload length in bytes (u64)                      |
+//!
+//! [`load`] treats a missing, truncated, mismatched, or corrupt file as a cache
+//! miss, so callers rebuild and overwrite it; nothing is ever read from a file
+//! whose checksum does not match. [`status`] and [`clear`] back `md-db cache`.
+
+use std::path::{Path, PathBuf};
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::cache::hash_bytes;
+use crate::error::{Error, Result};
+use crate::graph_cache::CACHE_DIR;
+use crate::schema::Schema;
+
+/// First eight bytes of every cache file.
+pub const MAGIC: &[u8; 8] = b"MDDBCACH";
+
+/// Bump when the header layout changes.
+pub const FORMAT_VERSION: u16 = 1;
+
+/// Header size in bytes.
+pub const HEADER_LEN: usize = 42;
+
+/// Cache files from before the versioned format, removed by [`clear`].
+const LEGACY_FILES: &[&str] = &["graph.json"];
+
+/// One kind of cache: its tag, payload version, and name for `cache status`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CacheKind {
+    pub tag: [u8; 4],
+    /// Bump whenever the payload's shape or the logic that fills it changes.
+    pub version: u32,
+    pub name: &'static str,
+}
+
+/// The graph snapshot, `.md-db/graph.bin`.
+pub const GRAPH: CacheKind = CacheKind {
+    tag: *b"GRPH",
+    version: 5,
+    name: "graph snapshot",
+};
+
+/// A [`DocCache`](crate::cache::DocCache) of parsed frontmatter.
+pub const DOCS: CacheKind = CacheKind {
+    tag: *b"DOCS",
+    version: 1,
+    name: "document cache",
+};
+
+/// Every kind md-db writes.
+pub const KINDS: &[CacheKind] = &[GRAPH, DOCS];
+
+/// A cache file's header.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Header {
+    pub format: u16,
+    pub tag: [u8; 4],
+    pub version: u32,
+    pub schema_key: u64,
+    pub checksum: u64,
+    pub len: u64,
+}
+
+impl Header {
+    /// The registered kind this header names, if any.
+    pub fn kind(&self) -> Option<&'static CacheKind> {
+        KINDS.iter().find(|k| k.tag == self.tag)
+    }
+
+    fn to_bytes(self) -> [u8; HEADER_LEN] {
+        let mut out = [0u8; HEADER_LEN];
+        out[..8].copy_from_slice(MAGIC);
+        out[8..10].copy_from_slice(&self.format.to_le_bytes());
+        out[10..14].copy_from_slice(&self.tag);
+        out[14..18].copy_from_slice(&self.version.to_le_bytes());
+        out[18..26].copy_from_slice(&self.schema_key.to_le_bytes());
+        out[26..34].copy_from_slice(&self.checksum.to_le_bytes());
+        out[34..42].copy_from_slice(&self.len.to_le_bytes());
+        out
+    }
+}
+
+/// Split a cache file into its header and payload, checking the magic, length,
+/// and checksum. The error says what is wrong.
+pub fn parse(bytes: &[u8]) -> std::result::Result<(Header, &[u8]), String> {
+    if bytes.len() < HEADER_LEN || &bytes[..8] != MAGIC {
+        return Err("not an md-db cache file".into());
+    }
+    let u64_at = |at: usize| u64::from_le_bytes(bytes[at..at + 8].try_into().unwrap());
+    let header = Header {
+        format: u16::from_le_bytes([bytes[8], bytes[9]]),
+        tag: bytes[10..14].try_into().unwrap(),
+        version: u32::from_le_bytes(bytes[14..18].try_into().unwrap()),
+        schema_key: u64_at(18),
+        checksum: u64_at(26),
+        len: u64_at(34),
+    };
+    let payload = &bytes[HEADER_LEN..];
+    if payload.len() as u64 != header.len {
+        return Err(format!("truncated: {} of {} payload bytes", payload.len(), header.len));
+    }
+    if hash_bytes(payload) != header.checksum {
+        return Err("checksum mismatch".into());
+    }
+    Ok((header, payload))
+}
+
+/// Outcome of [`load`].
+#[derive(Debug)]
+pub enum Loaded<T> {
+    Hit(T),
+    /// No file at the path.
+    Missing,
+    /// A valid file for another format, payload version, or schema.
+    Outdated,
+    /// Unreadable, truncated, checksum mismatch, or a payload that fails to decode.
+    Corrupt(String),
+}
+
+/// Read a cache file of `kind` built against `schema_key`.
+pub fn load<T: DeserializeOwned>(path: &Path, kind: &CacheKind, schema_key: u64) -> Loaded<T> {
+    let bytes = match std::fs::read(path) {
+        Ok(bytes) => bytes,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Loaded::Missing,
+        Err(e) => return Loaded::Corrupt(e.to_string()),
+    };
+    let (header, payload) = match parse(&bytes) {
+        Ok(parsed) => parsed,
+        Err(reason) => return Loaded::Corrupt(reason),
+    };
+    if header.format != FORMAT_VERSION
+        || header.tag != kind.tag
+        || header.version != kind.version
+        || header.schema_key != schema_key
+    {
+        return Loaded::Outdated;
+    }
+    match serde_json::from_slice(payload) {
+        Ok(value) => Loaded::Hit(value),
+        Err(e) => Loaded::Corrupt(e.to_string()),
+    }
+}
+
+/// Write `value` as a cache file of `kind`. Writes to a temporary file first and
+/// renames it, so concurrent readers never see a partial file.
+pub fn save<T: Serialize>(path: &Path, kind: &CacheKind, schema_key: u64, value: &T) -> Result<()> {
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent).map_err(|_| Error::WriteFailed(parent.to_path_buf()))?;
+    }
+    let payload = serde_json::to_vec(value)?;
+    let header = Header {
+        format: FORMAT_VERSION,
+        tag: kind.tag,
+        version: kind.version,
+        schema_key,
+        checksum: hash_bytes(&payload),
+        len: payload.len() as u64,
+    };
+    let mut data = header.to_bytes().to_vec();
+    data.extend_from_slice(&payload);
+
+    crate::durability::write(path, data)
+}
+
+/// State of one file reported by [`status`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CacheState {
+    Ok,
+    /// Valid but will be rebuilt on next use; the reason says why.
+    Outdated(String),
+    Corrupt(String),
+    /// A pre-versioned cache file md-db no longer reads.
+    Legacy,
+}
+
+/// One file under `.md-db/` that holds cached data.
+#[derive(Debug, Clone)]
+pub struct CacheFile {
+    pub path: PathBuf,
+    pub header: Option<Header>,
+    pub size: u64,
+    pub state: CacheState,
+}
+
+impl CacheFile {
+    /// The kind's name, `legacy`, or `unknown`.
+    pub fn kind_name(&self) -> &'static str {
+        match (self.header.and_then(|h| h.kind()), &self.state) {
+            (Some(kind), _) => kind.name,
+            (None, CacheState::Legacy) => "legacy",
+            (None, _) => "unknown",
+        }
+    }
+}
+
+/// The cache files in `<dir>/.md-db/`, sorted by path. With a schema, the graph
+/// snapshot is also checked against it. Undo logs, asset stores, and other state
+/// that is not a cache are never listed.
+pub fn status(dir: &Path, schema: Option<&Schema>) -> Result<Vec<CacheFile>> {
+    let cache_dir = dir.join(CACHE_DIR);
+    let Ok(entries) = std::fs::read_dir(&cache_dir) else {
+        return Ok(Vec::new());
+    };
+    let mut files = Vec::new();
+    for entry in entries {
+        let path = entry?.path();
+        if !path.is_file() {
+            continue;
+        }
+        let name = path.file_name().and_then(|n| n.to_str()).unwrap_or_default();
+        let legacy = LEGACY_FILES.contains(&name);
+        // Leftovers of an interrupted save
+        let partial = name.ends_with(".tmp");
+        let mut head = [0u8; 8];
+        let is_cache = std::fs::File::open(&path)
+            .and_then(|mut f| std::io::Read::read_exact(&mut f, &mut head))
+            .is_ok_and(|_| &head == MAGIC);
+        if !(legacy || partial || is_cache) {
+            continue;
+        }
+
+        let bytes = std::fs::read(&path)?;
+        let (header, state) = if legacy {
+            (None, CacheState::Legacy)
+        } else if partial {
+            (None, CacheState::Corrupt("left over from an interrupted write".into()))
+        } else {
+            match parse(&bytes) {
+                Ok((header, _)) => (Some(header), check(&header, schema)),
+                Err(reason) => (None, CacheState::Corrupt(reason)),
+            }
+        };
+        files.push(CacheFile {
+            path,
+            header,
+            size: bytes.len() as u64,
+            state,
+        });
+    }
+    files.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(files)
+}
+
+fn check(header: &Header, schema: Option<&Schema>) -> CacheState {
+    let Some(kind) = header.kind() else {
+        return CacheState::Outdated(format!("unknown kind {}", String::from_utf8_lossy(&header.tag)));
+    };
+    if header.format != FORMAT_VERSION {
+        return CacheState::Outdated(format!("format v{}, this md-db writes v{FORMAT_VERSION}", header.format));
+    }
+    if header.version != kind.version {
+        return CacheState::Outdated(format!("{} v{}, this md-db writes v{}", kind.name, header.version, kind.version));
+    }
+    match schema {
+        Some(schema) if kind.tag == GRAPH.tag && header.schema_key != crate::graph_cache::schema_key(schema) => {
+            CacheState::Outdated("built against a different schema".into())
+        }
+        _ => CacheState::Ok,
+    }
+}
+
+/// Remove every file [`status`] lists. Returns the removed paths. Caches are
+/// rebuilt on next use.
+pub fn clear(dir: &Path) -> Result<Vec<PathBuf>> {
+    let files = status(dir, None)?;
+    let mut removed = Vec::new();
+    for file in files {
+        std::fs::remove_file(&file.path).map_err(|_| Error::WriteFailed(file.path.clone()))?;
+        removed.push(file.path);
+    }
+    Ok(removed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip_and_corruption() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(CACHE_DIR).join("graph.bin");
+        save(&path, &GRAPH, 7, &vec!["a", "b"]).unwrap();
+
+        assert!(matches!(load::<Vec<String>>(&path, &GRAPH, 7), Loaded::Hit(v) if v == ["a", "b"]));
+        assert!(matches!(load::<Vec<String>>(&path, &GRAPH, 8), Loaded::Outdated));
+        assert!(matches!(load::<Vec<String>>(&path, &DOCS, 7), Loaded::Outdated));
+
+        let mut bytes = std::fs::read(&path).unwrap();
+        *bytes.last_mut().unwrap() ^= 1;
+        std::fs::write(&path, &bytes).unwrap();
+        assert!(matches!(load::<Vec<String>>(&path, &GRAPH, 7), Loaded::Corrupt(r) if r == "checksum mismatch"));
+
+        std::fs::write(&path, &bytes[..HEADER_LEN + 2]).unwrap();
+        assert!(matches!(load::<Vec<String>>(&path, &GRAPH, 7), Loaded::Corrupt(r) if r.starts_with("truncated")));
+        assert!(matches!(load::<Vec<String>>(&dir.path().join("none"), &GRAPH, 7), Loaded::Missing));
+    }
+
+    #[test]
+    fn test_status_and_clear() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache_dir = dir.path().join(CACHE_DIR);
+        save(&cache_dir.join("graph.bin"), &GRAPH, 1, &()).unwrap();
+        let docs = CacheKind { version: 0, ..DOCS };
+        save(&cache_dir.join("docs.bin"), &docs, 0, &()).unwrap();
+        std::fs::write(cache_dir.join("graph.json"), "{}").unwrap();
+        std::fs::write(cache_dir.join("undo-log.json"), "[]").unwrap();
+
+        let files = status(dir.path(), None).unwrap();
+        let states: Vec<(&str, &CacheState)> = files.iter().map(|f| (f.kind_name(), &f.state)).collect();
+        assert_eq!(states.len(), 3);
+        assert!(matches!(states[0], ("document cache", CacheState::Outdated(_))));
+        assert_eq!(states[1], ("graph snapshot", &CacheState::Ok));
+        assert_eq!(states[2], ("legacy", &CacheState::Legacy));
+
+        let schema = Schema::from_str("relation \"enables\"").unwrap();
+        let files = status(dir.path(), Some(&schema)).unwrap();
+        assert!(matches!(files[1].state, CacheState::Outdated(ref r) if r.contains("schema")));
+
+        assert_eq!(clear(dir.path()).unwrap().len(), 3);
+        assert!(status(dir.path(), None).unwrap().is_empty());
+        assert!(cache_dir.join("undo-log.json").is_file());
+    }
+}
@@ -0,0 +1,230 @@
+//! ID and document-count headroom per type (`md-db stats --capacity`).
+//!
+//! Generated IDs are `PREFIX-NNN`, padded to [`ID_WIDTH`] digits or the widest
+//! existing ID, so three-digit numbering ends at 999. For each type the report
+//! shows how much of its numbering and of its `max_count` is used, how many
+//! documents a month it gains (from its date field over the last year), and
+//! when either limit will be reached at that rate. Types close to a limit, or
+//! expected to reach one within the horizon, get warnings, so IDs can be widened
+//! (`md-db rename` updates references) before numbers run out.
+
+use serde::Serialize;
+
+use crate::frontmatter::Frontmatter;
+use crate::graph::ID_WIDTH;
+use crate::schema::{FieldType, Schema, TypeDef};
+use crate::table::Table;
+
+/// Share of a limit in use from which it is reported.
+pub const WARN_RATIO: f64 = 0.8;
+
+/// Days of history the growth rate is measured over.
+const WINDOW_DAYS: i64 = 365;
+
+const DAYS_PER_MONTH: f64 = 30.44;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TypeCapacity {
+    pub doc_type: String,
+    pub documents: usize,
+    pub max_count: Option<usize>,
+    /// Highest number among the type's `PREFIX-N` IDs.
+    pub highest_id: Option<u64>,
+    /// Digits in the type's IDs.
+    pub id_width: usize,
+    /// Highest number the width holds (999 for three digits).
+    pub id_limit: u64,
+    /// New documents per month, from the type's dated documents.
+    pub per_month: Option<f64>,
+    /// Months until the numbering runs out at that rate.
+    pub months_to_id_limit: Option<f64>,
+    /// Months until `max_count` is reached at that rate.
+    pub months_to_max_count: Option<f64>,
+    pub warnings: Vec<String>,
+}
+
+/// Capacity of every non-singleton type. `docs` are (ID, frontmatter) pairs;
+/// `today` is days since the epoch; limits expected within `horizon_months` warn.
+pub fn capacity(docs: &[(String, Frontmatter)], schema: &Schema, today: i64, horizon_months: f64) -> Vec<TypeCapacity> {
+    schema
+        .types
+        .iter()
+        .filter(|t| !t.singleton)
+        .map(|type_def| {
+            let of_type: Vec<&(String, Frontmatter)> = docs
+                .iter()
+                .filter(|(_, fm)| fm.get_display("type").as_deref() == Some(type_def.name.as_str()))
+                .collect();
+            type_capacity(type_def, &of_type, today, horizon_months)
+        })
+        .collect()
+}
+
+fn type_capacity(type_def: &TypeDef, docs: &[&(String, Frontmatter)], today: i64, horizon: f64) -> TypeCapacity {
+    let prefix = format!("{}-", type_def.name.to_uppercase());
+    let numbers: Vec<(u64, usize)> = docs
+        .iter()
+        .filter_map(|(id, _)| id.strip_prefix(&prefix))
+        .filter_map(|n| n.parse::<u64>().ok().map(|parsed| (parsed, n.len())))
+        .collect();
+    let highest_id = numbers.iter().map(|(n, _)| *n).max();
+    let id_width = numbers.iter().map(|(_, len)| *len).fold(ID_WIDTH, usize::max);
+    let id_limit = 10u64.saturating_pow(id_width as u32) - 1;
+    let per_month = growth(type_def, docs, today);
+
+    let months_left = |remaining: f64| per_month.filter(|r| *r > 0.0).map(|r| (remaining / r).max(0.0));
+    let months_to_id_limit = highest_id.and_then(|h| months_left(id_limit.saturating_sub(h) as f64));
+    let months_to_max_count = type_def
+        .max_count
+        .and_then(|max| months_left(max.saturating_sub(docs.len()) as f64));
+
+    let name = &type_def.name;
+    let mut warnings = Vec::new();
+    if let Some(highest) = highest_id {
+        let ratio = highest as f64 / id_limit as f64;
+        if ratio >= WARN_RATIO {
+            warnings.push(format!(
+                "{name} numbering is {}% used ({prefix}{highest:0id_width$} of {prefix}{id_limit})",
+                (ratio * 100.0).floor()
+            ));
+        }
+    }
+    if let Some(months) = months_to_id_limit.filter(|m| *m <= horizon) {
+        warnings.push(format!(
+            "{name} numbering runs out in {} at {}",
+            format_months(months),
+            format_rate(per_month.unwrap_or_default())
+        ));
+    }
+    if let Some(max) = type_def.max_count {
+        if docs.len() as f64 >= max as f64 * WARN_RATIO {
+            warnings.push(format!("{name} has {} of {max} documents allowed (max_count)", docs.len()));
+        }
+    }
+    if let Some(months) = months_to_max_count.filter(|m| *m <= horizon) {
+        warnings.push(format!(
+            "{name} reaches max_count in {} at {}",
+            format_months(months),
+            format_rate(per_month.unwrap_or_default())
+        ));
+    }
+
+    TypeCapacity {
+        doc_type: name.clone(),
+        documents: docs.len(),
+        max_count: type_def.max_count,
+        highest_id,
+        id_width,
+        id_limit,
+        per_month,
+        months_to_id_limit,
+        months_to_max_count,
+        warnings,
+    }
+}
+
+/// Documents per month dated within the last [`WINDOW_DAYS`], measured from the
+/// first of them when the type is younger than that. `None` without dates.
+fn growth(type_def: &TypeDef, docs: &[&(String, Frontmatter)], today: i64) -> Option<f64> {
+    let date_field = type_def
+        .fields
+        .iter()
+        .find(|f| matches!(f.field_type, FieldType::Date | FieldType::DateTime))
+        .map_or("date", |f| f.name.as_str());
+    let dates: Vec<i64> = docs
+        .iter()
+        .filter_map(|(_, fm)| fm.get_display(date_field))
+        .filter_map(|d| crate::validation::parse_date(d.get(..10).unwrap_or(&d)))
+        .filter(|d| *d <= today)
+        .collect();
+    let first = *dates.iter().min()?;
+    let since = first.max(today - WINDOW_DAYS);
+    let recent = dates.iter().filter(|d| **d >= since).count();
+    // At least a month, so a burst of documents on one day doesn't read as a huge rate
+    let days = (today - since).max(DAYS_PER_MONTH as i64);
+    Some(recent as f64 / (days as f64 / DAYS_PER_MONTH))
+}
+
+fn format_months(months: f64) -> String {
+    if months < 1.0 {
+        "under a month".to_string()
+    } else {
+        format!("~{} months", months.round())
+    }
+}
+
+fn format_rate(per_month: f64) -> String {
+    format!("{per_month:.1} new documents a month")
+}
+
+/// One row per type: documents, max_count, IDs used of the limit, monthly
+/// growth, and months to each limit.
+pub fn to_table(report: &[TypeCapacity]) -> Table {
+    let headers = ["type", "documents", "max_count", "ids used", "per month", "ids full in", "max_count in"];
+    let months = |m: Option<f64>| m.map(|m| format!("{m:.0} mo")).unwrap_or_else(|| "-".into());
+    let rows = report
+        .iter()
+        .map(|c| {
+            vec![
+                c.doc_type.clone(),
+                c.documents.to_string(),
+                c.max_count.map_or_else(|| "-".into(), |m| m.to_string()),
+                format!("{}/{}", c.highest_id.unwrap_or_default(), c.id_limit),
+                c.per_month.map_or_else(|| "-".into(), |r| format!("{r:.1}")),
+                months(c.months_to_id_limit),
+                months(c.months_to_max_count),
+            ]
+        })
+        .collect();
+    Table::new(headers.map(String::from).to_vec(), rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn doc(id: &str, yaml: &str) -> (String, Frontmatter) {
+        let fm = Frontmatter::try_parse(&format!("---\n{yaml}\n---\n")).unwrap().0.unwrap();
+        (id.to_string(), fm)
+    }
+
+    #[test]
+    fn test_capacity() {
+        let schema = Schema::from_str(
+            "type \"inc\" {\n    field \"started\" type=\"date\"\n}\ntype \"adr\" max_count=5\n\
+             type \"readme\" singleton=#true {\n    match \"README.md\"\n}\n",
+        )
+        .unwrap();
+        let today = crate::validation::parse_date("2024-12-31").unwrap();
+        let mut docs = vec![
+            doc("INC-950", "type: inc\nstarted: 2024-07-01"),
+            doc("INC-960", "type: inc\nstarted: 2024-10-01"),
+            doc("INC-990", "type: inc\nstarted: 2024-12-01"),
+        ];
+        docs.extend((1..=4).map(|n| doc(&format!("ADR-{n:03}"), "type: adr")));
+
+        let report = capacity(&docs, &schema, today, 12.0);
+        assert_eq!(report.len(), 2);
+        let inc = &report[0];
+        assert_eq!((inc.highest_id, inc.id_width, inc.id_limit), (Some(990), 3, 999));
+        // Three documents since July 1st: 183 days, about six months
+        assert!((inc.per_month.unwrap() - 0.5).abs() < 0.01, "{:?}", inc.per_month);
+        assert_eq!(inc.months_to_id_limit.map(f64::round), Some(18.0));
+        assert_eq!(inc.warnings, vec!["inc numbering is 99% used (INC-990 of INC-999)"]);
+
+        let adr = &report[1];
+        assert_eq!((adr.per_month, adr.max_count), (None, Some(5)));
+        assert_eq!(adr.warnings, vec!["adr has 4 of 5 documents allowed (max_count)"]);
+
+        // A faster-growing type is warned about before it is nearly full
+        let burst: Vec<_> = (100..130).map(|n| doc(&format!("INC-{n:04}"), "type: inc\nstarted: 2024-12-15")).collect();
+        let report = capacity(&burst, &schema, today, 12.0);
+        assert_eq!(report[0].id_limit, 9999);
+        assert!(report[0].warnings.is_empty(), "{:?}", report[0].warnings);
+        let report = capacity(&burst, &schema, today, 400.0);
+        assert!(report[0].warnings[0].starts_with("inc numbering runs out in ~324 months"), "{:?}", report[0].warnings);
+
+        let table = to_table(&capacity(&docs, &schema, today, 12.0));
+        assert_eq!(table.rows()[0], ["inc", "3", "-", "990/999", "0.5", "18 mo", "-"]);
+    }
+}
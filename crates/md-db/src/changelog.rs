@@ -0,0 +1,319 @@
+//! Keep-a-Changelog maintenance for a `CHANGELOG.md` singleton.
+//!
+//! Releases are `##` headings (`## [1.4.0] - 2024-05-01`, `## [Unreleased]`;
+//! brackets optional), change groups are `###` headings named after a kind
+//! (`### Added`), and entries are list items under them. Edits splice the
+//! document body, so hand-written text elsewhere is left untouched.
+
+use std::ops::Range;
+
+use comrak::Arena;
+
+use crate::ast_util;
+use crate::document::Document;
+use crate::error::{Error, Result};
+
+/// Release that collects changes until the next version is cut.
+pub const UNRELEASED: &str = "Unreleased";
+
+/// Change kinds, in the order groups appear within a release.
+pub const KINDS: [&str; 6] = ["Added", "Changed", "Deprecated", "Removed", "Fixed", "Security"];
+
+/// Body of a freshly created changelog.
+const PREAMBLE: &str = "# Changelog\n\nAll notable changes to this project are documented in this file.\n\nThe format is based on [Keep a Changelog](https://keepachangelog.com/en/1.1.0/).\n\n## [Unreleased]\n";
+
+/// One `##` or `###` heading with its byte ranges in the body.
+struct Heading {
+    level: u8,
+    text: String,
+    /// The heading line itself, including its newline.
+    line: Range<usize>,
+    /// From the heading line to the next heading of the same or higher level.
+    section: Range<usize>,
+}
+
+/// A new, empty changelog with an `Unreleased` section.
+pub fn new_changelog() -> Document {
+    Document::from_str(PREAMBLE).expect("preamble has no frontmatter")
+}
+
+/// Canonical spelling of a change kind (`fixed` -> `Fixed`).
+pub fn normalize_kind(kind: &str) -> Result<&'static str> {
+    KINDS
+        .iter()
+        .find(|k| k.eq_ignore_ascii_case(kind.trim()))
+        .copied()
+        .ok_or_else(|| {
+            Error::InvalidFieldValue(format!(
+                "unknown change kind '{kind}' (expected one of: {})",
+                KINDS.join(", ").to_lowercase()
+            ))
+        })
+}
+
+/// Release name and date from a heading: `[1.4.0] - 2024-05-01` -> (`1.4.0`, `2024-05-01`).
+pub fn parse_release_heading(text: &str) -> (String, Option<String>) {
+    let (name, date) = match text.split_once(" - ") {
+        Some((name, date)) => (name, Some(date.trim().to_string())),
+        None => (text, None),
+    };
+    let name = name.trim().trim_start_matches('[').trim_end_matches(']').trim();
+    (name.to_string(), date)
+}
+
+/// Add `text` as a list item under the `kind` group of `release`. The group is
+/// created in canonical order if missing; `Unreleased` is created if missing.
+pub fn add_entry(doc: &mut Document, release: &str, kind: &str, text: &str) -> Result<()> {
+    let kind = normalize_kind(kind)?;
+    let text = text.trim();
+    if text.is_empty() {
+        return Err(Error::InvalidFieldValue("changelog entry is empty".into()));
+    }
+
+    let mut headings = scan(&doc.body);
+    if find_release(&headings, release).is_none() {
+        if !release.eq_ignore_ascii_case(UNRELEASED) {
+            return Err(Error::SectionNotFound(release.to_string()));
+        }
+        insert_unreleased(doc, &headings);
+        headings = scan(&doc.body);
+    }
+    let rel = find_release(&headings, release).expect("release exists");
+    let groups: Vec<&Heading> = headings
+        .iter()
+        .filter(|h| h.level == 3 && within(&h.section, &rel.section))
+        .collect();
+
+    let item = format!("- {text}");
+    let mut body = doc.body.clone();
+    match groups.iter().find(|g| g.text.eq_ignore_ascii_case(kind)) {
+        Some(group) => {
+            let has_items = !body[group.line.end..group.section.end].trim().is_empty();
+            splice(&mut body, group.section.end, &item, if has_items { "\n" } else { "\n\n" });
+        }
+        None => {
+            let rank = |name: &str| KINDS.iter().position(|k| k.eq_ignore_ascii_case(name));
+            let pos = groups
+                .iter()
+                .find(|g| rank(&g.text) > rank(kind))
+                .map(|g| g.section.start)
+                .unwrap_or(rel.section.end);
+            splice(&mut body, pos, &format!("### {kind}\n\n{item}"), "\n\n");
+        }
+    }
+    doc.set_body(&body);
+    Ok(())
+}
+
+/// Cut a release: the `Unreleased` entries become `version` dated `date`, and an
+/// empty `Unreleased` section is opened above it. Compare links at the bottom
+/// (`[unreleased]: .../compare/v1.3.0...HEAD`) are moved along.
+pub fn release(doc: &mut Document, version: &str, date: &str) -> Result<()> {
+    let version = version.trim().trim_start_matches('[').trim_end_matches(']');
+    if !is_iso_date(date) {
+        return Err(Error::InvalidFieldValue(format!("release date '{date}' is not YYYY-MM-DD")));
+    }
+    let headings = scan(&doc.body);
+    if find_release(&headings, version).is_some() {
+        return Err(Error::InvalidFieldValue(format!("version {version} is already in the changelog")));
+    }
+    let rel = find_release(&headings, UNRELEASED)
+        .ok_or_else(|| Error::SectionNotFound(UNRELEASED.to_string()))?;
+    let has_entries = doc.body[rel.line.end..rel.section.end]
+        .lines()
+        .any(|l| l.trim_start().starts_with("- ") || l.trim_start().starts_with("* "));
+    if !has_entries {
+        return Err(Error::InvalidFieldValue("nothing to release: Unreleased has no entries".into()));
+    }
+
+    let unreleased_line = doc.body[rel.line.clone()].trim_end().to_string();
+    let title = if unreleased_line.contains('[') {
+        format!("[{version}]")
+    } else {
+        version.to_string()
+    };
+    let mut body = doc.body.clone();
+    body.replace_range(rel.line.clone(), &format!("{unreleased_line}\n\n## {title} - {date}\n"));
+    doc.set_body(&update_compare_links(&body, version));
+    Ok(())
+}
+
+/// Rewrite `[unreleased]: <base>/compare/<prev>...HEAD` for a new version, adding its own link.
+fn update_compare_links(body: &str, version: &str) -> String {
+    let mut out = String::with_capacity(body.len());
+    for line in body.split_inclusive('\n') {
+        let rewritten = line.trim_end().split_once("]:").and_then(|(label, url)| {
+            if !label.trim_start_matches('[').eq_ignore_ascii_case(UNRELEASED) {
+                return None;
+            }
+            let (base, prev) = url.trim().strip_suffix("...HEAD")?.rsplit_once("/compare/")?;
+            let tag = if prev.starts_with('v') { format!("v{version}") } else { version.to_string() };
+            Some(format!(
+                "{label}]: {base}/compare/{tag}...HEAD\n[{version}]: {base}/compare/{prev}...{tag}\n"
+            ))
+        });
+        out.push_str(rewritten.as_deref().unwrap_or(line));
+    }
+    out
+}
+
+/// Open an `Unreleased` section before the first release (or at the end).
+fn insert_unreleased(doc: &mut Document, headings: &[Heading]) {
+    let first = headings.iter().find(|h| h.level == 2);
+    let bracketed = first.is_none_or(|h| doc.body[h.line.clone()].contains('['));
+    let title = if bracketed { format!("[{UNRELEASED}]") } else { UNRELEASED.to_string() };
+    let pos = first.map(|h| h.section.start).unwrap_or(doc.body.len());
+    let mut body = doc.body.clone();
+    splice(&mut body, pos, &format!("## {title}"), "\n\n");
+    doc.set_body(&body);
+}
+
+/// Insert `block` at `pos` (a line start), separated from the text before it by
+/// `sep` and from a following heading by a blank line.
+fn splice(body: &mut String, pos: usize, block: &str, sep: &str) {
+    let head = body[..pos].trim_end();
+    let tail = body[pos..].trim_start();
+    let mut out = String::with_capacity(body.len() + block.len() + 4);
+    out.push_str(head);
+    if !head.is_empty() {
+        out.push_str(sep);
+    }
+    out.push_str(block.trim_end());
+    out.push('\n');
+    if !tail.trim().is_empty() {
+        out.push('\n');
+        out.push_str(tail);
+    }
+    *body = out;
+}
+
+fn find_release<'a>(headings: &'a [Heading], name: &str) -> Option<&'a Heading> {
+    let name = name.trim().trim_start_matches('[').trim_end_matches(']');
+    headings
+        .iter()
+        .find(|h| h.level == 2 && parse_release_heading(&h.text).0.eq_ignore_ascii_case(name))
+}
+
+fn within(inner: &Range<usize>, outer: &Range<usize>) -> bool {
+    inner.start >= outer.start && inner.end <= outer.end
+}
+
+fn is_iso_date(s: &str) -> bool {
+    let b = s.as_bytes();
+    b.len() == 10
+        && b[4] == b'-'
+        && b[7] == b'-'
+        && b.iter().enumerate().all(|(i, c)| i == 4 || i == 7 || c.is_ascii_digit())
+}
+
+/// Where section content ends: before trailing link reference definitions.
+fn content_end(body: &str) -> usize {
+    let mut end = body.len();
+    let mut start = body.len();
+    for line in body.split_inclusive('\n').rev() {
+        start -= line.len();
+        let l = line.trim();
+        let is_link_def = l.starts_with('[') && l.split_once("]:").is_some_and(|(label, _)| !label.contains(']'));
+        if !l.is_empty() && !is_link_def {
+            break;
+        }
+        end = start;
+    }
+    end
+}
+
+/// All `##` and `###` headings in the body. Sections stop before trailing link definitions.
+fn scan(body: &str) -> Vec<Heading> {
+    let limit = content_end(body);
+    let arena = Arena::new();
+    let opts = ast_util::comrak_opts();
+    let root = comrak::parse_document(&arena, body, &opts);
+    ast_util::find_headings(root, None)
+        .into_iter()
+        .filter_map(|node| {
+            let level = ast_util::heading_level(node)?;
+            if level != 2 && level != 3 {
+                return None;
+            }
+            let mut section = ast_util::section_byte_range(node, body);
+            let content = ast_util::section_content_byte_range(node, body);
+            section.end = section.end.min(limit).max(content.start);
+            Some(Heading {
+                level,
+                text: ast_util::collect_text(node).trim().to_string(),
+                line: section.start..content.start,
+                section,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = "\
+# Changelog
+
+## [Unreleased]
+
+### Fixed
+
+- Broken footer link
+
+## [1.3.0] - 2024-01-10
+
+### Added
+
+- Cookie banner
+
+[unreleased]: https://github.com/acme/site/compare/v1.3.0...HEAD
+[1.3.0]: https://github.com/acme/site/compare/v1.2.0...v1.3.0
+";
+
+    #[test]
+    fn test_add_entry_orders_groups() {
+        let mut doc = Document::from_str(SAMPLE).unwrap();
+        add_entry(&mut doc, "Unreleased", "added", "New privacy policy").unwrap();
+        add_entry(&mut doc, "unreleased", "fixed", "Typo on pricing page").unwrap();
+        let unreleased = &doc.body[..doc.body.find("## [1.3.0]").unwrap()];
+        assert_eq!(
+            unreleased,
+            "# Changelog\n\n## [Unreleased]\n\n### Added\n\n- New privacy policy\n\n### Fixed\n\n- Broken footer link\n- Typo on pricing page\n\n"
+        );
+
+        add_entry(&mut doc, "1.3.0", "Security", "Patched XSS").unwrap();
+        assert!(doc.body.contains("- Cookie banner\n\n### Security\n\n- Patched XSS\n\n[unreleased]:"));
+
+        assert!(matches!(add_entry(&mut doc, "9.9.9", "added", "x"), Err(Error::SectionNotFound(_))));
+        assert!(matches!(add_entry(&mut doc, "Unreleased", "tweaked", "x"), Err(Error::InvalidFieldValue(_))));
+    }
+
+    #[test]
+    fn test_release_moves_entries_and_links() {
+        let mut doc = Document::from_str(SAMPLE).unwrap();
+        release(&mut doc, "1.4.0", "2024-05-01").unwrap();
+        assert!(doc.body.contains("## [Unreleased]\n\n## [1.4.0] - 2024-05-01\n\n### Fixed\n\n- Broken footer link"));
+        assert!(doc.body.contains(
+            "[unreleased]: https://github.com/acme/site/compare/v1.4.0...HEAD\n[1.4.0]: https://github.com/acme/site/compare/v1.3.0...v1.4.0\n"
+        ));
+
+        // Unreleased is now empty, and the version exists
+        assert!(release(&mut doc, "1.5.0", "2024-06-01").is_err());
+        add_entry(&mut doc, UNRELEASED, "changed", "Faster builds").unwrap();
+        assert!(release(&mut doc, "1.4.0", "2024-06-01").is_err());
+        assert!(release(&mut doc, "1.5.0", "June").is_err());
+    }
+
+    #[test]
+    fn test_new_changelog() {
+        let mut doc = new_changelog();
+        add_entry(&mut doc, UNRELEASED, "added", "First entry").unwrap();
+        assert!(doc.raw.ends_with("## [Unreleased]\n\n### Added\n\n- First entry\n"));
+
+        let mut bare = Document::from_str("# Changelog\n\n## 1.0.0 - 2023-01-01\n\n- Initial\n").unwrap();
+        add_entry(&mut bare, UNRELEASED, "removed", "Legacy API").unwrap();
+        assert!(bare.body.starts_with("# Changelog\n\n## Unreleased\n\n### Removed\n\n- Legacy API\n\n## 1.0.0"));
+        assert_eq!(parse_release_heading("[1.0.0] - 2023-01-01"), ("1.0.0".into(), Some("2023-01-01".into())));
+    }
+}
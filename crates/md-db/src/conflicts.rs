@@ -0,0 +1,292 @@
+//! Semantic merge conflicts between two branches of a docs tree.
+//!
+//! A textual merge happily combines two branches that each add `adr-005.md`
+//! under different slugs, or that move the same document to different statuses.
+//! Both sides are compared against their merge base to find those cases.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+
+use crate::assets::sha256_hex;
+use crate::error::Result;
+use crate::graph::DocGraph;
+use crate::schema::Schema;
+use crate::validation::Severity;
+
+/// A semantic conflict between the two branches.
+#[derive(Debug, Clone)]
+pub struct Conflict {
+    pub code: String,
+    pub severity: Severity,
+    /// Document the conflict is about.
+    pub id: String,
+    pub message: String,
+}
+
+impl Conflict {
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "code": self.code,
+            "severity": self.severity.to_string(),
+            "id": self.id,
+            "message": self.message,
+        })
+    }
+}
+
+/// Documents of one revision: the link graph plus a content digest per document.
+#[derive(Debug)]
+pub struct RevisionDocs {
+    pub graph: DocGraph,
+    digests: BTreeMap<String, String>,
+}
+
+impl RevisionDocs {
+    pub fn from_contents(files: &[(PathBuf, String)], schema: &Schema) -> Self {
+        let graph = DocGraph::from_contents(files, schema);
+        let digests = files
+            .iter()
+            .map(|(path, content)| (crate::graph::path_to_id(path), sha256_hex(content.as_bytes())))
+            .collect();
+        Self { graph, digests }
+    }
+
+    /// Read every markdown file under `dir` at a git revision.
+    pub fn at_revision(rev: &str, dir: &Path, schema: &Schema) -> Result<Self> {
+        let files = crate::vcs::read_tree_at(rev, dir)?;
+        Ok(Self::from_contents(&files, schema))
+    }
+
+    fn has(&self, id: &str) -> bool {
+        self.graph.nodes.contains_key(id)
+    }
+
+    fn status(&self, id: &str) -> Option<&str> {
+        self.graph.nodes.get(id).and_then(|n| n.status.as_deref())
+    }
+
+    fn path(&self, id: &str) -> String {
+        self.graph
+            .nodes
+            .get(id)
+            .map(|n| n.path.display().to_string())
+            .unwrap_or_default()
+    }
+
+    /// Frontmatter relations as `(from, relation, to)`, with inverse fields turned
+    /// around so `supersedes` on one doc and `superseded_by` on the other compare equal.
+    fn relations(&self, schema: &Schema) -> BTreeSet<(String, String, String)> {
+        self.graph
+            .edges
+            .iter()
+            .filter_map(|e| {
+                let (rel, is_inverse) = schema.find_relation(&e.relation)?;
+                Some(if is_inverse {
+                    (e.to.clone(), rel.name.clone(), e.from.clone())
+                } else {
+                    (e.from.clone(), rel.name.clone(), e.to.clone())
+                })
+            })
+            .collect()
+    }
+
+    /// Every outgoing reference (relations and inline links) as `(from, to)`.
+    fn refs(&self) -> BTreeSet<(String, String)> {
+        self.graph
+            .edges
+            .iter()
+            .map(|e| (e.from.clone(), e.to.clone()))
+            .collect()
+    }
+}
+
+/// Compare `base` and `head` against their common `ancestor`.
+///
+/// - C001: both branches add a document with the same ID (different content)
+/// - C002: both branches change a document's status to different values
+/// - C003: both branches add the same relation (warning)
+/// - C004: one branch deletes a document the other newly references
+pub fn detect(
+    ancestor: &RevisionDocs,
+    base: &RevisionDocs,
+    head: &RevisionDocs,
+    schema: &Schema,
+) -> Vec<Conflict> {
+    let mut out = Vec::new();
+
+    // Same new ID on both sides
+    for id in base.graph.nodes.keys() {
+        if ancestor.has(id) || !head.has(id) || base.digests.get(id) == head.digests.get(id) {
+            continue;
+        }
+        out.push(Conflict {
+            code: "C001".into(),
+            severity: Severity::Error,
+            id: id.clone(),
+            message: format!(
+                "{id} is added on both branches ({} on base, {} on head); renumber one of them",
+                base.path(id),
+                head.path(id)
+            ),
+        });
+    }
+
+    // Diverging status transitions
+    for id in ancestor.graph.nodes.keys() {
+        let (Some(b), Some(h)) = (base.status(id), head.status(id)) else {
+            continue;
+        };
+        let was = ancestor.status(id);
+        if b != h && was != Some(b) && was != Some(h) {
+            out.push(Conflict {
+                code: "C002".into(),
+                severity: Severity::Error,
+                id: id.clone(),
+                message: format!(
+                    "status of {id} changed to \"{b}\" on base and \"{h}\" on head (was {})",
+                    was.map(|w| format!("\"{w}\"")).unwrap_or_else(|| "unset".into())
+                ),
+            });
+        }
+    }
+
+    // Same relation added twice
+    let old_rels = ancestor.relations(schema);
+    let head_rels = head.relations(schema);
+    for rel in base.relations(schema) {
+        if old_rels.contains(&rel) || !head_rels.contains(&rel) {
+            continue;
+        }
+        let (from, name, to) = rel;
+        out.push(Conflict {
+            code: "C003".into(),
+            severity: Severity::Warning,
+            id: from.clone(),
+            message: format!("both branches add {from} {name} {to}; check the merged document for duplicates"),
+        });
+    }
+
+    // Deleted on one side, newly referenced on the other
+    let old_refs = ancestor.refs();
+    for (deleter, referrer, deleter_name, referrer_name) in
+        [(base, head, "base", "head"), (head, base, "head", "base")]
+    {
+        for (from, to) in referrer.refs() {
+            if old_refs.contains(&(from.clone(), to.clone())) {
+                continue;
+            }
+            if ancestor.has(&to) && !deleter.has(&to) {
+                out.push(Conflict {
+                    code: "C004".into(),
+                    severity: Severity::Error,
+                    id: to.clone(),
+                    message: format!(
+                        "{to} is deleted on {deleter_name} but {from} on {referrer_name} adds a reference to it"
+                    ),
+                });
+            }
+        }
+    }
+
+    out
+}
+
+/// Detect conflicts between two git revisions for the docs under `dir`.
+pub fn detect_between(base: &str, head: &str, dir: &Path, schema: &Schema) -> Result<Vec<Conflict>> {
+    let base = crate::vcs::resolve(base)?;
+    let head = crate::vcs::resolve(head)?;
+    let ancestor = crate::vcs::merge_base(&base, &head)?;
+
+    let ancestor = RevisionDocs::at_revision(&ancestor, dir, schema)?;
+    let base = RevisionDocs::at_revision(&base, dir, schema)?;
+    let head = RevisionDocs::at_revision(&head, dir, schema)?;
+    Ok(detect(&ancestor, &base, &head, schema))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn schema() -> Schema {
+        Schema::from_str(
+            r#"
+type "adr" {
+    field "title" type="string"
+    field "status" type="string"
+}
+relation "supersedes" inverse="superseded_by" cardinality="one"
+relation "related" cardinality="many"
+"#,
+        )
+        .unwrap()
+    }
+
+    fn rev(files: &[(&str, &str)]) -> RevisionDocs {
+        let files: Vec<(PathBuf, String)> = files
+            .iter()
+            .map(|(p, c)| (PathBuf::from(p), c.to_string()))
+            .collect();
+        RevisionDocs::from_contents(&files, &schema())
+    }
+
+    fn codes(conflicts: &[Conflict]) -> Vec<&str> {
+        conflicts.iter().map(|c| c.code.as_str()).collect()
+    }
+
+    const ADR1: &str = "---\ntype: adr\ntitle: One\nstatus: proposed\n---\n";
+    const ADR2: &str = "---\ntype: adr\ntitle: Two\nstatus: accepted\n---\n";
+
+    #[test]
+    fn test_no_conflicts_for_independent_changes() {
+        let schema = schema();
+        let ancestor = rev(&[("adr-001.md", ADR1)]);
+        let base = rev(&[("adr-001.md", ADR1), ("adr-002-a.md", ADR2)]);
+        let head = rev(&[("adr-001.md", &ADR1.replace("proposed", "accepted"))]);
+        assert!(detect(&ancestor, &base, &head, &schema).is_empty());
+
+        // Identical additions on both sides merge cleanly
+        let head = rev(&[("adr-001.md", ADR1), ("adr-002-a.md", ADR2)]);
+        assert!(detect(&ancestor, &base, &head, &schema).is_empty());
+    }
+
+    #[test]
+    fn test_same_new_id_and_status_conflict() {
+        let schema = schema();
+        let ancestor = rev(&[("adr-001.md", ADR1)]);
+        let base = rev(&[
+            ("adr-001.md", &ADR1.replace("proposed", "accepted")),
+            ("adr-002-use-kafka.md", ADR2),
+        ]);
+        let head = rev(&[
+            ("adr-001.md", &ADR1.replace("proposed", "rejected")),
+            ("adr-002-use-nats.md", &ADR2.replace("Two", "Other")),
+        ]);
+        let conflicts = detect(&ancestor, &base, &head, &schema);
+        assert_eq!(codes(&conflicts), vec!["C001", "C002"]);
+        assert!(conflicts[0].message.contains("adr-002-use-kafka.md on base"));
+        assert!(conflicts[1].message.contains("\"accepted\" on base and \"rejected\" on head (was \"proposed\")"));
+    }
+
+    #[test]
+    fn test_duplicate_relation_and_deleted_reference() {
+        let schema = schema();
+        let ancestor = rev(&[("adr-001.md", ADR1), ("adr-002.md", ADR2), ("adr-003.md", ADR2)]);
+        // base: ADR-002 supersedes ADR-001, and ADR-003 is deleted
+        let base = rev(&[
+            ("adr-001.md", ADR1),
+            ("adr-002.md", &ADR2.replace("status: accepted\n", "status: accepted\nsupersedes: ADR-001\n")),
+        ]);
+        // head: same relation declared from the other side, plus a new ref to ADR-003
+        let head = rev(&[
+            ("adr-001.md", &ADR1.replace("status: proposed\n", "status: proposed\nsuperseded_by: ADR-002\nrelated: [ADR-003]\n")),
+            ("adr-002.md", ADR2),
+            ("adr-003.md", ADR2),
+        ]);
+        let conflicts = detect(&ancestor, &base, &head, &schema);
+        assert_eq!(codes(&conflicts), vec!["C003", "C004"]);
+        assert_eq!(conflicts[0].severity, Severity::Warning);
+        assert!(conflicts[0].message.contains("ADR-002 supersedes ADR-001"));
+        assert_eq!(conflicts[1].id, "ADR-003");
+        assert!(conflicts[1].message.contains("deleted on base but ADR-001 on head"));
+    }
+}
@@ -0,0 +1,516 @@
+//! Token-budgeted context packs for LLM agents.
+//!
+//! A pack holds the focus documents' frontmatter and key sections, one-line
+//! summaries of the documents one hop away in the graph, and the schema
+//! excerpt for the focus documents' types. Pieces are added in priority order
+//! (frontmatter, schema, sections, related) until `max_tokens` is reached; a
+//! section that only partly fits is cut short. Tokens are estimated as four
+//! characters each, measured on the Markdown rendering.
+
+use std::collections::BTreeSet;
+use std::path::Path;
+
+use serde::Serialize;
+
+use crate::document::Document;
+use crate::error::{Error, Result};
+use crate::graph::DocGraph;
+use crate::schema::{Schema, TypeDef};
+use crate::search::{self, SearchOptions};
+
+/// Longest related-document summary, in characters.
+const SUMMARY_CHARS: usize = 160;
+
+/// Options for [`build`].
+#[derive(Debug, Clone)]
+pub struct ContextOptions {
+    pub max_tokens: usize,
+    /// Sections to include from each focus document. Empty means the type's
+    /// required sections, or every top-level section without a type.
+    pub sections: Vec<String>,
+}
+
+impl Default for ContextOptions {
+    fn default() -> Self {
+        Self {
+            max_tokens: 4000,
+            sections: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ContextPack {
+    pub focus: Vec<FocusDoc>,
+    pub schema: Vec<TypeExcerpt>,
+    pub related: Vec<RelatedDoc>,
+    pub max_tokens: usize,
+    pub estimated_tokens: usize,
+    /// What was left out to stay within the budget.
+    pub omitted: Omitted,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FocusDoc {
+    pub id: String,
+    pub path: String,
+    pub frontmatter: serde_json::Value,
+    pub sections: Vec<SectionExcerpt>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SectionExcerpt {
+    pub heading: String,
+    pub content: String,
+    /// Content was cut to fit the budget.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub truncated: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RelatedDoc {
+    pub id: String,
+    pub relation: String,
+    /// `out` for refs from a focus document, `in` for backlinks.
+    pub direction: &'static str,
+    pub title: Option<String>,
+    pub status: Option<String>,
+    pub summary: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TypeExcerpt {
+    pub name: String,
+    pub description: Option<String>,
+    /// `name: type` with ` (required)` where applicable.
+    pub fields: Vec<String>,
+    pub required_sections: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Omitted {
+    pub sections: usize,
+    pub related: usize,
+    pub schema_types: usize,
+}
+
+impl Omitted {
+    pub fn is_empty(&self) -> bool {
+        self.sections == 0 && self.related == 0 && self.schema_types == 0
+    }
+}
+
+/// Rough token count: four characters per token, rounded up.
+pub fn estimate_tokens(text: &str) -> usize {
+    text.chars().count().div_ceil(4)
+}
+
+/// Resolve a focus argument to a display ID: a `uid`, a file path, or an ID in any case.
+pub fn resolve_id(graph: &DocGraph, s: &str) -> String {
+    if let Some(node) = graph.find_by_uid(s) {
+        return node.id.clone();
+    }
+    if s.contains('/') || s.ends_with(".md") {
+        return crate::graph::path_to_id(Path::new(s));
+    }
+    s.to_uppercase().replace('_', "-")
+}
+
+/// Display IDs of the first `limit` graph documents matching a full-text `query`.
+pub fn focus_from_query(graph: &DocGraph, dir: &Path, query: &str, limit: usize) -> Result<Vec<String>> {
+    let results = search::search_documents(dir, query, &SearchOptions::default())?;
+    Ok(results
+        .iter()
+        .map(|r| crate::graph::path_to_id(Path::new(&r.path)))
+        .filter(|id| graph.nodes.contains_key(id))
+        .take(limit)
+        .collect())
+}
+
+/// Build a context pack around `focus_ids` (display IDs present in the graph).
+pub fn build(
+    graph: &DocGraph,
+    schema: &Schema,
+    focus_ids: &[String],
+    opts: &ContextOptions,
+) -> Result<ContextPack> {
+    let mut docs = Vec::new();
+    for id in focus_ids {
+        let node = graph
+            .nodes
+            .get(id)
+            .ok_or_else(|| Error::DocumentNotFound(id.clone()))?;
+        docs.push((node, Document::from_file(&node.path)?));
+    }
+
+    let mut pack = ContextPack {
+        focus: Vec::new(),
+        schema: Vec::new(),
+        related: Vec::new(),
+        max_tokens: opts.max_tokens,
+        estimated_tokens: 0,
+        omitted: Omitted::default(),
+    };
+    let mut used = 0;
+
+    // 1. Frontmatter of every focus document, always included
+    for (node, doc) in &docs {
+        let focus = FocusDoc {
+            id: node.id.clone(),
+            path: node.path.display().to_string(),
+            frontmatter: doc.frontmatter.as_ref().map(|fm| fm.to_json()).unwrap_or_default(),
+            sections: Vec::new(),
+        };
+        used += estimate_tokens(&focus_header_markdown(&focus));
+        pack.focus.push(focus);
+    }
+
+    // 2. Schema excerpts for the focus types
+    let type_names: BTreeSet<String> = docs
+        .iter()
+        .filter_map(|(node, _)| node.doc_type.clone())
+        .collect();
+    for name in type_names {
+        let Some(type_def) = schema.get_type(&name) else {
+            continue;
+        };
+        let excerpt = type_excerpt(type_def);
+        let cost = estimate_tokens(&type_markdown(&excerpt));
+        if used + cost > opts.max_tokens {
+            pack.omitted.schema_types += 1;
+            continue;
+        }
+        used += cost;
+        pack.schema.push(excerpt);
+    }
+
+    // 3. Related documents, gathered now so sections can leave room for them
+    let focus_set: BTreeSet<&str> = focus_ids.iter().map(|s| s.as_str()).collect();
+    let related = related_docs(graph, &focus_set);
+    let related_cost: usize = related.iter().map(|r| estimate_tokens(&related_markdown(r))).sum();
+    let reserve = related_cost.min(opts.max_tokens / 4);
+
+    // 4. Key sections, cut to what's left after the related reserve
+    let section_budget = opts.max_tokens.saturating_sub(reserve);
+    let mut full = used >= section_budget;
+    for (i, (node, doc)) in docs.iter().enumerate() {
+        let type_def = node.doc_type.as_deref().and_then(|t| schema.get_type(t));
+        for heading in key_sections(doc, type_def, &opts.sections) {
+            let Ok(section) = doc.get_section(&heading) else {
+                continue;
+            };
+            if full {
+                pack.omitted.sections += 1;
+                continue;
+            }
+            let mut excerpt = SectionExcerpt {
+                heading: section.heading.trim().to_string(),
+                content: section.content.trim().to_string(),
+                truncated: false,
+            };
+            let cost = estimate_tokens(&section_markdown(&excerpt));
+            if used + cost > section_budget {
+                full = true;
+                excerpt.truncated = true;
+                let content = std::mem::take(&mut excerpt.content);
+                let overhead = estimate_tokens(&section_markdown(&excerpt));
+                let room = section_budget.saturating_sub(used + overhead);
+                if room == 0 {
+                    pack.omitted.sections += 1;
+                    continue;
+                }
+                excerpt.content = truncate_chars(&content, room * 4);
+            }
+            used += estimate_tokens(&section_markdown(&excerpt));
+            pack.focus[i].sections.push(excerpt);
+        }
+    }
+
+    // 5. Related summaries until the budget runs out
+    for doc in related {
+        let cost = estimate_tokens(&related_markdown(&doc));
+        if used + cost > opts.max_tokens {
+            pack.omitted.related += 1;
+            continue;
+        }
+        used += cost;
+        pack.related.push(doc);
+    }
+
+    pack.estimated_tokens = used;
+    Ok(pack)
+}
+
+impl ContextPack {
+    /// Render the pack as Markdown.
+    pub fn to_markdown(&self) -> String {
+        let mut out = String::new();
+        for focus in &self.focus {
+            out.push_str(&focus_header_markdown(focus));
+            for section in &focus.sections {
+                out.push_str(&section_markdown(section));
+            }
+        }
+        if !self.related.is_empty() {
+            out.push_str("## Related\n\n");
+            for doc in &self.related {
+                out.push_str(&related_markdown(doc));
+            }
+            out.push('\n');
+        }
+        for excerpt in &self.schema {
+            out.push_str(&type_markdown(excerpt));
+        }
+        if !self.omitted.is_empty() {
+            out.push_str(&format!(
+                "<!-- trimmed to ~{} tokens: omitted {} section(s), {} related doc(s), {} schema type(s) -->\n",
+                self.max_tokens, self.omitted.sections, self.omitted.related, self.omitted.schema_types
+            ));
+        }
+        out
+    }
+}
+
+fn focus_header_markdown(focus: &FocusDoc) -> String {
+    let title = focus.frontmatter.get("title").and_then(|v| v.as_str());
+    let mut out = match title {
+        Some(title) => format!("# {} — {title}\n\n", focus.id),
+        None => format!("# {}\n\n", focus.id),
+    };
+    out.push_str(&format!("Path: {}\n\n", focus.path));
+    if let Some(map) = focus.frontmatter.as_object() {
+        for (key, value) in map {
+            let value = match value {
+                serde_json::Value::String(s) => s.clone(),
+                other => other.to_string(),
+            };
+            out.push_str(&format!("- {key}: {value}\n"));
+        }
+        out.push('\n');
+    }
+    out
+}
+
+fn section_markdown(section: &SectionExcerpt) -> String {
+    let marker = if section.truncated { " …" } else { "" };
+    format!("## {}\n\n{}{marker}\n\n", section.heading, section.content)
+}
+
+fn related_markdown(doc: &RelatedDoc) -> String {
+    let arrow = if doc.direction == "out" { "→" } else { "←" };
+    let mut line = format!("- {arrow} {} {}", doc.relation, doc.id);
+    if let Some(ref title) = doc.title {
+        line.push_str(&format!(" — {title}"));
+    }
+    if let Some(ref status) = doc.status {
+        line.push_str(&format!(" [{status}]"));
+    }
+    if let Some(ref summary) = doc.summary {
+        line.push_str(&format!(": {summary}"));
+    }
+    line.push('\n');
+    line
+}
+
+fn type_markdown(excerpt: &TypeExcerpt) -> String {
+    let mut out = format!("## Schema: {}\n\n", excerpt.name);
+    if let Some(ref desc) = excerpt.description {
+        out.push_str(&format!("{desc}\n\n"));
+    }
+    for field in &excerpt.fields {
+        out.push_str(&format!("- {field}\n"));
+    }
+    if !excerpt.required_sections.is_empty() {
+        out.push_str(&format!("- required sections: {}\n", excerpt.required_sections.join(", ")));
+    }
+    out.push('\n');
+    out
+}
+
+fn type_excerpt(type_def: &TypeDef) -> TypeExcerpt {
+    TypeExcerpt {
+        name: type_def.name.clone(),
+        description: type_def.description.clone(),
+        fields: type_def
+            .fields
+            .iter()
+            .map(|f| {
+                let required = if f.required { " (required)" } else { "" };
+                format!("{}: {}{required}", f.name, f.field_type)
+            })
+            .collect(),
+        required_sections: type_def
+            .sections
+            .iter()
+            .filter(|s| s.required)
+            .map(|s| s.name.clone())
+            .collect(),
+    }
+}
+
+/// Sections to include: explicit names, else the type's required ones, else all top-level.
+fn key_sections(doc: &Document, type_def: Option<&TypeDef>, explicit: &[String]) -> Vec<String> {
+    if !explicit.is_empty() {
+        return explicit.to_vec();
+    }
+    let required: Vec<String> = type_def
+        .map(|t| t.sections.iter().filter(|s| s.required).map(|s| s.name.clone()).collect())
+        .unwrap_or_default();
+    if !required.is_empty() {
+        return required;
+    }
+    doc.sections().into_iter().map(|s| s.heading.trim().to_string()).collect()
+}
+
+/// Documents one hop from any focus document, outgoing refs first, each once.
+fn related_docs(graph: &DocGraph, focus: &BTreeSet<&str>) -> Vec<RelatedDoc> {
+    let mut seen = BTreeSet::new();
+    let mut out = Vec::new();
+    let outgoing = graph
+        .edges
+        .iter()
+        .filter(|e| focus.contains(e.from.as_str()))
+        .map(|e| (e, e.to.as_str(), "out"));
+    let incoming = graph
+        .edges
+        .iter()
+        .filter(|e| focus.contains(e.to.as_str()))
+        .map(|e| (e, e.from.as_str(), "in"));
+    for (edge, other, direction) in outgoing.chain(incoming) {
+        if focus.contains(other) || !seen.insert(other.to_string()) {
+            continue;
+        }
+        let node = graph.nodes.get(other);
+        out.push(RelatedDoc {
+            id: other.to_string(),
+            relation: edge.relation.clone(),
+            direction,
+            title: node.and_then(|n| n.title.clone()),
+            status: node.and_then(|n| n.status.clone()),
+            summary: node.and_then(|n| summarize(&n.path)),
+        });
+    }
+    out
+}
+
+/// A `summary`/`description` field, else the first sentence of the body.
+fn summarize(path: &Path) -> Option<String> {
+    let doc = Document::from_file(path).ok()?;
+    let from_field = doc
+        .frontmatter
+        .as_ref()
+        .and_then(|fm| fm.get_display("summary").or_else(|| fm.get_display("description")));
+    let text = match from_field {
+        Some(text) => text,
+        None => crate::prose::sentences(&crate::prose::prose_text(&doc.body))
+            .into_iter()
+            .next()?,
+    };
+    Some(truncate_chars(text.trim(), SUMMARY_CHARS))
+}
+
+/// First `max` characters, cut at a word boundary where possible, with `…` when shortened.
+fn truncate_chars(text: &str, max: usize) -> String {
+    if text.chars().count() <= max {
+        return text.to_string();
+    }
+    let cut: String = text.chars().take(max.saturating_sub(1)).collect();
+    let cut = match cut.rfind(char::is_whitespace) {
+        Some(i) if i > cut.len() / 2 => &cut[..i],
+        _ => &cut,
+    };
+    format!("{}…", cut.trim_end())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn setup() -> (tempfile::TempDir, Schema) {
+        let dir = tempfile::tempdir().unwrap();
+        let long = "The database choice affects every service we run. ".repeat(40);
+        let files = [
+            (
+                "adr-001.md".to_string(),
+                "---\ntype: adr\ntitle: Use MySQL\nstatus: superseded\n---\n\n# Context\n\nWe needed a database quickly. It had to be cheap.\n\n# Decision\n\nMySQL.\n".to_string(),
+            ),
+            (
+                "adr-002.md".to_string(),
+                format!("---\ntype: adr\ntitle: Use Postgres\nstatus: accepted\nsupersedes: ADR-001\n---\n\n# Context\n\n{long}\n\n# Decision\n\nPostgres.\n\n# Notes\n\nNot required.\n"),
+            ),
+            (
+                "inc-001.md".to_string(),
+                "---\ntype: inc\ntitle: Outage\nsummary: Primary ran out of disk.\ncaused_by: [ADR-002]\n---\n".to_string(),
+            ),
+        ];
+        for (name, content) in files {
+            std::fs::write(dir.path().join(name), content).unwrap();
+        }
+        let schema = Schema::from_str(
+            r#"
+relation "supersedes" inverse="superseded_by" cardinality="one"
+relation "caused_by" inverse="causes"
+
+type "adr" description="Architecture decision" {
+    field "title" type="string" required=#true
+    field "status" type="enum" {
+        values "proposed" "accepted" "superseded"
+    }
+    section "Context" required=#true
+    section "Decision" required=#true
+    section "Notes"
+}
+"#,
+        )
+        .unwrap();
+        (dir, schema)
+    }
+
+    #[test]
+    fn test_pack_with_room_to_spare() {
+        let (dir, schema) = setup();
+        let graph = DocGraph::build(dir.path(), &schema).unwrap();
+        let pack = build(&graph, &schema, &["ADR-002".into()], &ContextOptions::default()).unwrap();
+
+        let headings: Vec<&str> = pack.focus[0].sections.iter().map(|s| s.heading.as_str()).collect();
+        assert_eq!(headings, vec!["Context", "Decision"]);
+        assert!(pack.omitted.is_empty());
+        assert_eq!(pack.schema[0].fields, vec!["title: string (required)", "status: enum(proposed, accepted, superseded)"]);
+
+        let related: Vec<(&str, &str, &str)> = pack
+            .related
+            .iter()
+            .map(|r| (r.id.as_str(), r.relation.as_str(), r.direction))
+            .collect();
+        assert_eq!(related, vec![("ADR-001", "supersedes", "out"), ("INC-001", "caused_by", "in")]);
+        assert_eq!(pack.related[0].summary.as_deref(), Some("We needed a database quickly."));
+        assert_eq!(pack.related[1].summary.as_deref(), Some("Primary ran out of disk."));
+
+        let md = pack.to_markdown();
+        assert!(md.starts_with("# ADR-002 — Use Postgres\n"));
+        assert!(md.contains("- → supersedes ADR-001 — Use MySQL [superseded]: We needed a database quickly.\n"));
+        assert!(md.contains("## Schema: adr\n\nArchitecture decision\n"));
+
+        assert_eq!(resolve_id(&graph, "adr_002"), "ADR-002");
+        assert_eq!(focus_from_query(&graph, dir.path(), "database quickly", 3).unwrap(), vec!["ADR-001"]);
+    }
+
+    #[test]
+    fn test_pack_trimmed_to_budget() {
+        let (dir, schema) = setup();
+        let graph = DocGraph::build(dir.path(), &schema).unwrap();
+        let opts = ContextOptions { max_tokens: 300, sections: Vec::new() };
+        let pack = build(&graph, &schema, &["ADR-002".into()], &opts).unwrap();
+
+        assert!(pack.estimated_tokens <= 300, "{}", pack.estimated_tokens);
+        assert!(pack.focus[0].sections[0].truncated);
+        assert_eq!(pack.omitted.sections, 1, "Decision no longer fits");
+        assert_eq!(pack.related.len(), 2, "related docs keep their reserve");
+        assert!(pack.to_markdown().contains("<!-- trimmed to ~300 tokens"));
+
+        assert!(matches!(
+            build(&graph, &schema, &["ADR-404".into()], &opts),
+            Err(Error::DocumentNotFound(_))
+        ));
+    }
+}
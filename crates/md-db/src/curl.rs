@@ -0,0 +1,25 @@
+//! Helpers for running `curl` with the request in a config file on stdin, so
+//! URLs and credentials never show in process lists. Used by the Confluence
+//! exporter and webhook notifications.
+
+/// A double-quoted string for a curl config file.
+pub(crate) fn quote(s: &str) -> String {
+    let escaped = s
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+        .replace('\r', "\\r")
+        .replace('\t', "\\t");
+    format!("\"{escaped}\"")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quote() {
+        assert_eq!(quote("https://x/y"), "\"https://x/y\"");
+        assert_eq!(quote("a \"b\"\\\nc"), "\"a \\\"b\\\"\\\\\\nc\"");
+    }
+}
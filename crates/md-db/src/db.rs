@@ -0,0 +1,343 @@
+//! One object for embedding md-db in another program.
+//!
+//! [`Db::open`] does the wiring the CLI does per command: it resolves the schema
+//! (and a `users.yaml` next to it), builds the document graph from its on-disk
+//! snapshot, and then answers discovery, validation, search, queries, refs,
+//! lookups by ID, and field edits against that state. The file list and the
+//! full validation result are computed once and kept until a change made through
+//! the `Db`, a [`Db::watch`] event, or [`Db::refresh`].
+//!
+//! ```no_run
+//! use md_db::Db;
+//! use md_db::discovery::Filter;
+//!
+//! let mut db = Db::open("docs", "auto")?;
+//! let proposed = db.query(&[Filter::FieldEquals { key: "status".into(), value: "proposed".into() }])?;
+//! db.set("ADR-003", &[("status", "accepted")])?;
+//! let errors = db.validate()?.total_errors();
+//! # Ok::<(), md_db::error::Error>(())
+//! ```
+
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::time::{Duration, SystemTime};
+
+use crate::discovery::Filter;
+use crate::document::Document;
+use crate::error::{Error, Result};
+use crate::graph::{DocEdge, DocGraph};
+use crate::schema::Schema;
+use crate::search::{SearchOptions, SearchResult};
+use crate::undo::Recorder;
+use crate::users::UserConfig;
+use crate::validation::{FileResult, ValidationOptions, ValidationResult};
+
+/// User/team config file looked for next to the schema, then in the root.
+pub const USERS_FILE: &str = "users.yaml";
+
+/// A document directory with its schema, user config, and graph.
+#[derive(Debug, Clone)]
+pub struct Db {
+    root: PathBuf,
+    schema_path: PathBuf,
+    schema: Schema,
+    users: Option<UserConfig>,
+    graph: DocGraph,
+    files: OnceLock<Vec<PathBuf>>,
+    validation: OnceLock<ValidationResult>,
+}
+
+impl Db {
+    /// Open `root` with a schema file, or with `auto` the closest schema at or
+    /// above it (as `--schema auto` does).
+    pub fn open(root: impl AsRef<Path>, schema: impl AsRef<Path>) -> Result<Self> {
+        let root = root.as_ref().to_path_buf();
+        let schema_path = crate::project::resolve_schema(schema.as_ref(), &root)?;
+        let schema = Schema::from_file(&schema_path)?;
+        let users = [schema_path.parent().unwrap_or(Path::new(".")), root.as_path()]
+            .iter()
+            .map(|dir| dir.join(USERS_FILE))
+            .find(|p| p.is_file())
+            .map(UserConfig::from_file)
+            .transpose()?;
+        let graph = DocGraph::build_cached(&root, &schema)?;
+        Ok(Self {
+            root,
+            schema_path,
+            schema,
+            users,
+            graph,
+            files: OnceLock::new(),
+            validation: OnceLock::new(),
+        })
+    }
+
+    /// Use this user/team config instead of the discovered one.
+    pub fn with_users(mut self, users: UserConfig) -> Self {
+        self.users = Some(users);
+        self.validation = OnceLock::new();
+        self
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    pub fn schema_path(&self) -> &Path {
+        &self.schema_path
+    }
+
+    pub fn schema(&self) -> &Schema {
+        &self.schema
+    }
+
+    pub fn users(&self) -> Option<&UserConfig> {
+        self.users.as_ref()
+    }
+
+    /// The document graph as of the last open, edit, refresh, or watch event.
+    pub fn graph(&self) -> &DocGraph {
+        &self.graph
+    }
+
+    /// Pick up changes made outside this `Db`: the graph is brought up to date
+    /// from its snapshot and the cached file list and validation are dropped.
+    pub fn refresh(&mut self) -> Result<()> {
+        self.graph = DocGraph::build_cached(&self.root, &self.schema)?;
+        self.invalidate();
+        Ok(())
+    }
+
+    /// Markdown files under the root, sorted.
+    pub fn files(&self) -> Result<&[PathBuf]> {
+        if let Some(files) = self.files.get() {
+            return Ok(files);
+        }
+        let files = crate::discovery::discover_files(&self.root, None, &[], false)?;
+        Ok(self.files.get_or_init(|| files))
+    }
+
+    /// Validate every document, like `md-db validate ROOT`.
+    pub fn validate(&self) -> Result<&ValidationResult> {
+        if let Some(result) = self.validation.get() {
+            return Ok(result);
+        }
+        let result = crate::validation::validate_directory(&self.root, &self.schema, None, self.users.as_ref())?;
+        Ok(self.validation.get_or_init(|| result))
+    }
+
+    /// Validate every document, running only the phases `opts` selects. Not cached.
+    pub fn validate_with(&self, opts: &ValidationOptions) -> Result<ValidationResult> {
+        crate::validation::validate_directory_with(&self.root, &self.schema, None, self.users.as_ref(), opts)
+    }
+
+    /// Validate one document, such as an unsaved draft, with refs resolved
+    /// against the documents of the root.
+    pub fn validate_document(&self, doc: &Document) -> Result<FileResult> {
+        let known_files: HashSet<PathBuf> = self
+            .files()?
+            .iter()
+            .map(|p| p.canonicalize().unwrap_or_else(|_| p.clone()))
+            .collect();
+        let known_ids = crate::validation::collect_known_ids(self.files()?);
+        Ok(crate::validation::validate_document(
+            doc,
+            &self.schema,
+            &known_files,
+            &known_ids,
+            self.users.as_ref(),
+        ))
+    }
+
+    /// Full-text search over the documents, like `md-db search`.
+    pub fn search(&self, query: &str, options: &SearchOptions) -> Result<Vec<SearchResult>> {
+        crate::search::search_documents(&self.root, query, options)
+    }
+
+    /// Outgoing refs of the document with this ID or uid.
+    pub fn refs_from(&self, id: &str) -> Vec<&DocEdge> {
+        self.graph.refs_from(&self.resolve_id(id))
+    }
+
+    /// Backlinks to the document with this ID or uid.
+    pub fn refs_to(&self, id: &str) -> Vec<&DocEdge> {
+        self.graph.refs_to(&self.resolve_id(id))
+    }
+
+    fn resolve_id(&self, id: &str) -> String {
+        crate::context::resolve_id(&self.graph, id)
+    }
+
+    /// Documents whose frontmatter matches all `filters`, in path order.
+    pub fn query(&self, filters: &[Filter]) -> Result<Vec<Document>> {
+        crate::discovery::discover_files(&self.root, None, filters, false)?
+            .iter()
+            .map(Document::from_file)
+            .collect()
+    }
+
+    /// Path of the document with this ID, uid, or path.
+    pub fn path_of(&self, id: &str) -> Option<&Path> {
+        let node = self.graph.nodes.get(&self.resolve_id(id))?;
+        Some(node.path.as_path())
+    }
+
+    /// The document with this ID or uid.
+    pub fn get(&self, id: &str) -> Result<Document> {
+        let path = self.path_of(id).ok_or_else(|| Error::DocumentNotFound(id.to_string()))?;
+        Document::from_file(path)
+    }
+
+    /// Set frontmatter fields (values typed like `--field`) and save the document.
+    /// The change is recorded in the undo log and the graph is updated. A status
+    /// change the type's workflow doesn't allow fails with [`Error::Workflow`], as
+    /// `set` without `--force` does.
+    pub fn set(&mut self, id: &str, fields: &[(&str, &str)]) -> Result<Document> {
+        let mut doc = self.get(id)?;
+        let before = doc.frontmatter.clone();
+        for (key, value) in fields {
+            doc.set_field_from_str(key, value);
+        }
+        if let (Some(before), Some(after)) = (&before, &doc.frontmatter) {
+            if let Some(msg) = self.schema.transition_violation(before, after) {
+                return Err(Error::Workflow(msg));
+            }
+        }
+        let path = doc.path.clone().ok_or(Error::NoPath)?;
+        let mut undo = Recorder::new("set");
+        undo.track(&path);
+        doc.save()?;
+        undo.finish()?;
+        self.graph.update_file(&path, &self.schema);
+        self.validation = OnceLock::new();
+        Ok(doc)
+    }
+
+    /// Poll the root every `interval` and call `on_change` with the files created,
+    /// modified, or deleted since the last look; the graph is updated first. Returns
+    /// when `on_change` returns `false`.
+    pub fn watch<F>(&mut self, interval: Duration, mut on_change: F) -> Result<()>
+    where
+        F: FnMut(&Db, &[PathBuf]) -> bool,
+    {
+        let mut seen = self.fingerprints()?;
+        loop {
+            std::thread::sleep(interval);
+            let now = self.fingerprints()?;
+            let mut changed: Vec<PathBuf> = now
+                .iter()
+                .filter(|(path, stamp)| seen.get(*path) != Some(*stamp))
+                .map(|(path, _)| path.clone())
+                .collect();
+            changed.extend(seen.keys().filter(|path| !now.contains_key(*path)).cloned());
+            seen = now;
+            if changed.is_empty() {
+                continue;
+            }
+            changed.sort();
+            self.graph.apply_changes(&changed, &self.schema);
+            self.invalidate();
+            if !on_change(self, &changed) {
+                return Ok(());
+            }
+        }
+    }
+
+    fn invalidate(&mut self) {
+        self.files = OnceLock::new();
+        self.validation = OnceLock::new();
+    }
+
+    fn fingerprints(&self) -> Result<BTreeMap<PathBuf, (Option<SystemTime>, u64)>> {
+        Ok(crate::discovery::discover_files(&self.root, None, &[], false)?
+            .into_iter()
+            .filter_map(|path| {
+                let meta = std::fs::metadata(&path).ok()?;
+                Some((path, (meta.modified().ok(), meta.len())))
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCHEMA: &str = "type \"adr\" {\n    field \"title\" type=\"string\" required=#true\n    field \"status\" type=\"enum\" required=#true {\n        values \"proposed\" \"accepted\"\n    }\n}\n";
+
+    #[test]
+    fn test_open_query_get_set() {
+        let dir = tempfile::tempdir().unwrap();
+        // The manifest also keeps the undo log inside the temp dir
+        std::fs::write(dir.path().join("md-db.kdl"), "root \".\"\n").unwrap();
+        std::fs::write(dir.path().join("schema.kdl"), SCHEMA).unwrap();
+        std::fs::write(dir.path().join("adr-001.md"), "---\ntype: adr\ntitle: One\nstatus: proposed\n---\n").unwrap();
+        std::fs::write(dir.path().join("adr-002.md"), "---\ntype: adr\ntitle: Two\nstatus: maybe\n---\n").unwrap();
+
+        let mut db = Db::open(dir.path(), "auto").unwrap();
+        assert_eq!(db.graph().nodes.len(), 2);
+        assert_eq!(db.validate().unwrap().total_errors(), 1);
+        assert_eq!(db.files().unwrap().len(), 2);
+        assert_eq!(db.search("Two", &SearchOptions::default()).unwrap().len(), 1);
+        let draft = Document::from_str("---\ntype: adr\ntitle: Draft\n---\n").unwrap();
+        assert!(db.validate_document(&draft).unwrap().diagnostics.iter().any(|d| d.message.contains("status")));
+
+        let proposed = db
+            .query(&[Filter::FieldEquals {
+                key: "status".into(),
+                value: "proposed".into(),
+            }])
+            .unwrap();
+        assert_eq!(proposed.len(), 1);
+        assert_eq!(db.get("ADR-002").unwrap().frontmatter().unwrap().get_display("title").as_deref(), Some("Two"));
+        assert!(matches!(db.get("ADR-009"), Err(Error::DocumentNotFound(_))));
+
+        // The cached validation is dropped by the edit
+        db.set("ADR-002", &[("status", "accepted")]).unwrap();
+        assert_eq!(db.validate().unwrap().total_errors(), 0);
+        assert!(dir.path().join(".md-db/undo-log.json").is_file());
+    }
+
+    #[test]
+    fn test_set_follows_workflow() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("md-db.kdl"), "root \".\"\n").unwrap();
+        let schema = SCHEMA.replace("\n}\n", "\n    workflow {\n        transition \"proposed\" to=\"accepted\"\n    }\n}\n");
+        std::fs::write(dir.path().join("schema.kdl"), schema).unwrap();
+        let adr = dir.path().join("adr-001.md");
+        std::fs::write(&adr, "---\ntype: adr\ntitle: One\nstatus: accepted\n---\n").unwrap();
+
+        let mut db = Db::open(dir.path(), "auto").unwrap();
+        assert!(matches!(db.set("ADR-001", &[("status", "proposed")]), Err(Error::Workflow(_))));
+        assert!(std::fs::read_to_string(&adr).unwrap().contains("status: accepted"));
+        db.set("ADR-001", &[("title", "Uno")]).unwrap();
+    }
+
+    #[test]
+    fn test_watch_reports_changes() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("schema.kdl"), SCHEMA).unwrap();
+        std::fs::write(dir.path().join("adr-001.md"), "---\ntype: adr\ntitle: One\nstatus: proposed\n---\n").unwrap();
+        let mut db = Db::open(dir.path(), dir.path().join("schema.kdl")).unwrap();
+
+        let new_file = dir.path().join("adr-002.md");
+        let writer = {
+            let new_file = new_file.clone();
+            std::thread::spawn(move || {
+                std::thread::sleep(Duration::from_millis(50));
+                std::fs::write(new_file, "---\ntype: adr\ntitle: Two\nstatus: proposed\n---\n").unwrap();
+            })
+        };
+        let mut events = Vec::new();
+        db.watch(Duration::from_millis(20), |db, changed| {
+            events.extend(changed.iter().cloned());
+            db.graph().nodes.len() < 2
+        })
+        .unwrap();
+        writer.join().unwrap();
+
+        assert!(!events.is_empty() && events.iter().all(|p| p == &new_file));
+        assert!(db.graph().nodes.contains_key("ADR-002"));
+    }
+}
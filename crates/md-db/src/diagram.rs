@@ -0,0 +1,394 @@
+//! Syntax checks for diagram sources in fenced code blocks.
+//!
+//! This is not a full mermaid or D2 parser. It covers the mistakes that
+//! actually break rendering: unknown diagram headers, unbalanced blocks
+//! (`subgraph`/`end`, `loop`/`end`, `{`/`}`), unclosed brackets and quotes,
+//! and edges missing an endpoint. Each error carries the 1-based line within
+//! the diagram source.
+
+/// A syntax problem in a diagram source.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DiagramError {
+    /// 1-based line within the diagram source.
+    pub line: usize,
+    pub message: String,
+}
+
+/// Languages [`check`] understands.
+pub const CHECKED_LANGUAGES: &[&str] = &["mermaid", "d2"];
+
+/// Check `source` written in `lang`. Unknown languages are not checked.
+pub fn check(lang: &str, source: &str) -> Vec<DiagramError> {
+    match lang.to_ascii_lowercase().as_str() {
+        "mermaid" => check_mermaid(source),
+        "d2" => check_d2(source),
+        _ => Vec::new(),
+    }
+}
+
+const MERMAID_HEADERS: &[&str] = &[
+    "graph",
+    "flowchart",
+    "sequenceDiagram",
+    "classDiagram",
+    "classDiagram-v2",
+    "stateDiagram",
+    "stateDiagram-v2",
+    "erDiagram",
+    "journey",
+    "gantt",
+    "pie",
+    "gitGraph",
+    "mindmap",
+    "timeline",
+    "quadrantChart",
+    "requirementDiagram",
+    "C4Context",
+    "C4Container",
+    "C4Component",
+    "C4Dynamic",
+    "C4Deployment",
+    "sankey-beta",
+    "xychart-beta",
+    "block-beta",
+    "packet-beta",
+    "architecture-beta",
+    "kanban",
+];
+
+const FLOWCHART_DIRECTIONS: &[&str] = &["TD", "TB", "BT", "RL", "LR"];
+
+const FLOWCHART_EDGES: &[&str] = &["-->", "---", "-.->", "==>", "-.-", "===", "--o", "--x"];
+
+/// Sequence diagram keywords that open a block closed by `end`.
+const SEQUENCE_BLOCKS: &[&str] = &["loop", "alt", "opt", "par", "critical", "break", "rect", "box"];
+
+fn err(line: usize, message: impl Into<String>) -> DiagramError {
+    DiagramError {
+        line,
+        message: message.into(),
+    }
+}
+
+fn check_mermaid(source: &str) -> Vec<DiagramError> {
+    let mut errors = Vec::new();
+    let lines: Vec<&str> = source.lines().collect();
+
+    // Skip an optional `---` config block and comments to find the header
+    let mut i = 0;
+    if lines.first().is_some_and(|l| l.trim() == "---") {
+        i = match lines.iter().skip(1).position(|l| l.trim() == "---") {
+            Some(end) => end + 2,
+            None => {
+                errors.push(err(1, "config block opened with --- is never closed"));
+                return errors;
+            }
+        };
+    }
+    while i < lines.len() && (lines[i].trim().is_empty() || lines[i].trim().starts_with("%%")) {
+        i += 1;
+    }
+    let Some(header_line) = lines.get(i) else {
+        errors.push(err(1, "diagram is empty"));
+        return errors;
+    };
+    let mut words = header_line.split_whitespace();
+    let kind = words.next().unwrap_or_default();
+    if !MERMAID_HEADERS.contains(&kind) {
+        errors.push(err(
+            i + 1,
+            format!("unknown diagram type \"{kind}\" (expected e.g. flowchart, sequenceDiagram, classDiagram)"),
+        ));
+        return errors;
+    }
+    let flowchart = kind == "graph" || kind == "flowchart";
+    if flowchart {
+        if let Some(dir) = words.next().filter(|d| !d.starts_with("%%")) {
+            let dir = dir.trim_end_matches(';');
+            if !FLOWCHART_DIRECTIONS.contains(&dir) {
+                errors.push(err(i + 1, format!("unknown flowchart direction \"{dir}\" (expected TD, TB, BT, RL, or LR)")));
+            }
+        }
+    }
+
+    // Open blocks: (keyword, line)
+    let mut blocks: Vec<(&str, usize)> = Vec::new();
+    let mut braces: Vec<usize> = Vec::new();
+    for (n, raw) in lines.iter().enumerate().skip(i + 1) {
+        let line_no = n + 1;
+        let line = strip_mermaid_comment(raw).trim();
+        if line.is_empty() {
+            continue;
+        }
+        let first = line.split_whitespace().next().unwrap_or_default();
+
+        match kind {
+            "graph" | "flowchart" => {
+                if let Some(msg) = unbalanced_brackets(line) {
+                    errors.push(err(line_no, msg));
+                }
+                if first == "subgraph" {
+                    blocks.push(("subgraph", line_no));
+                } else if line == "end" {
+                    if blocks.pop().is_none() {
+                        errors.push(err(line_no, "\"end\" without a matching subgraph"));
+                    }
+                } else if let Some(msg) = dangling_edge(line, FLOWCHART_EDGES) {
+                    errors.push(err(line_no, msg));
+                }
+            }
+            "sequenceDiagram" => {
+                if SEQUENCE_BLOCKS.contains(&first) {
+                    blocks.push((first, line_no));
+                } else if line == "end" {
+                    if blocks.pop().is_none() {
+                        errors.push(err(line_no, "\"end\" without a matching loop/alt/opt/par block"));
+                    }
+                } else if first == "else" {
+                    if !blocks.iter().any(|(k, _)| *k == "alt" || *k == "critical") {
+                        errors.push(err(line_no, "\"else\" outside an alt block"));
+                    }
+                } else if first == "and" {
+                    if !blocks.iter().any(|(k, _)| *k == "par") {
+                        errors.push(err(line_no, "\"and\" outside a par block"));
+                    }
+                } else if is_sequence_message(line) && !line.contains(':') {
+                    errors.push(err(line_no, "message is missing \": text\""));
+                }
+            }
+            "classDiagram" | "classDiagram-v2" | "stateDiagram" | "stateDiagram-v2" | "erDiagram" => {
+                if let Some(msg) = unbalanced_brackets(line) {
+                    errors.push(err(line_no, msg));
+                }
+                // Body blocks open at the end of a line and close at the start of
+                // one; braces elsewhere are ER cardinality markers like `||--o{`
+                if line.starts_with('}') && braces.pop().is_none() {
+                    errors.push(err(line_no, "\"}\" without a matching \"{\""));
+                }
+                if line.ends_with('{') {
+                    braces.push(line_no);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    for (keyword, line_no) in blocks {
+        errors.push(err(line_no, format!("\"{keyword}\" is never closed with \"end\"")));
+    }
+    for line_no in braces {
+        errors.push(err(line_no, "\"{\" is never closed"));
+    }
+    errors.sort_by_key(|e| e.line);
+    errors
+}
+
+fn strip_mermaid_comment(line: &str) -> &str {
+    match line.find("%%") {
+        Some(pos) => &line[..pos],
+        None => line,
+    }
+}
+
+/// Bracket/quote problems on a single line, ignoring text inside quotes.
+/// Braces are only paired when both ends are on the line, since diagram
+/// bodies and ER cardinality markers use them unpaired.
+fn unbalanced_brackets(line: &str) -> Option<String> {
+    let mut stack: Vec<char> = Vec::new();
+    let mut in_quote = false;
+    let mut prev = ' ';
+    for (i, c) in line.char_indices() {
+        let before = prev;
+        prev = c;
+        if c == '"' {
+            in_quote = !in_quote;
+            continue;
+        }
+        if in_quote {
+            continue;
+        }
+        match c {
+            '(' | '[' | '{' => stack.push(c),
+            // Asymmetric flowchart node: `A>label]`
+            '>' if before.is_alphanumeric() && line[i..].contains(']') => stack.push(c),
+            ')' | ']' | '}' => {
+                let matches = match c {
+                    ')' => stack.last() == Some(&'('),
+                    ']' => matches!(stack.last(), Some('[') | Some('>')),
+                    _ => stack.last() == Some(&'{'),
+                };
+                if matches {
+                    stack.pop();
+                } else if c != '}' {
+                    return Some(format!("unexpected \"{c}\""));
+                }
+            }
+            _ => {}
+        }
+    }
+    if in_quote {
+        return Some("unclosed quote".into());
+    }
+    match stack.iter().rev().find(|c| **c != '{') {
+        Some(c) => Some(format!("\"{c}\" is never closed")),
+        None => None,
+    }
+}
+
+/// An edge operator at the very start or end of a line has a missing endpoint.
+fn dangling_edge(line: &str, ops: &[&str]) -> Option<String> {
+    let line = line.trim_end_matches(';').trim();
+    for op in ops {
+        if line.ends_with(op) {
+            return Some(format!("edge \"{op}\" has no target"));
+        }
+        if line.starts_with(op) {
+            return Some(format!("edge \"{op}\" has no source"));
+        }
+    }
+    None
+}
+
+fn is_sequence_message(line: &str) -> bool {
+    const ARROWS: &[&str] = &["->>", "-->>", "-x", "--x", "-)", "--)", "->", "-->"];
+    let head = line.split(':').next().unwrap_or_default();
+    let lowered = head.to_ascii_lowercase();
+    let keyword = [
+        "participant", "actor", "note", "activate", "deactivate", "autonumber", "title", "create", "destroy",
+        "link",
+    ]
+    .iter()
+    .any(|k| lowered.starts_with(k));
+    !keyword && ARROWS.iter().any(|a| head.contains(a))
+}
+
+fn check_d2(source: &str) -> Vec<DiagramError> {
+    let mut errors = Vec::new();
+    let mut braces: Vec<usize> = Vec::new();
+    // Closing pipes of an open block string (`|md ... |`)
+    let mut block_string: Option<String> = None;
+
+    for (n, raw) in source.lines().enumerate() {
+        let line_no = n + 1;
+        let trimmed = raw.trim();
+        if let Some(ref pipes) = block_string {
+            if trimmed.starts_with(pipes.as_str()) {
+                block_string = None;
+            }
+            continue;
+        }
+        let line = strip_d2_comment(trimmed).trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        if let Some(pipes) = opens_block_string(line) {
+            block_string = Some(pipes);
+            continue;
+        }
+        if line.matches('"').count() % 2 == 1 {
+            errors.push(err(line_no, "unclosed quote"));
+            continue;
+        }
+
+        let mut in_quote = false;
+        for c in line.chars() {
+            match c {
+                '"' => in_quote = !in_quote,
+                '{' if !in_quote => braces.push(line_no),
+                '}' if !in_quote && braces.pop().is_none() => {
+                    errors.push(err(line_no, "\"}\" without a matching \"{\""));
+                }
+                _ => {}
+            }
+        }
+
+        // The part before a label (`a -> b: label`) holds the connection
+        let head = line.split(':').next().unwrap_or_default().trim();
+        if let Some(msg) = dangling_edge(head, &["<->", "->", "<-", "--"]) {
+            errors.push(err(line_no, msg));
+        }
+    }
+
+    if block_string.is_some() {
+        errors.push(err(source.lines().count(), "block string is never closed with \"|\""));
+    }
+    for line_no in braces {
+        errors.push(err(line_no, "\"{\" is never closed"));
+    }
+    errors.sort_by_key(|e| e.line);
+    errors
+}
+
+fn strip_d2_comment(line: &str) -> &str {
+    let mut in_quote = false;
+    for (i, c) in line.char_indices() {
+        match c {
+            '"' => in_quote = !in_quote,
+            '#' if !in_quote => return &line[..i],
+            _ => {}
+        }
+    }
+    line
+}
+
+/// `key: |md` (or `|||`, `|`) opens a block string; returns the closing pipes.
+fn opens_block_string(line: &str) -> Option<String> {
+    let value = line.split_once(':')?.1.trim();
+    let pipes: String = value.chars().take_while(|c| *c == '|').collect();
+    let rest = &value[pipes.len()..];
+    (!pipes.is_empty() && !rest.contains('|') && rest.chars().all(|c| c.is_ascii_alphanumeric()))
+        .then_some(pipes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(errors: &[DiagramError]) -> Vec<usize> {
+        errors.iter().map(|e| e.line).collect()
+    }
+
+    #[test]
+    fn test_mermaid_valid() {
+        let src = "%% overview\nflowchart LR\n  A[Start] --> B{Ok?}\n  subgraph Infra\n    B -->|yes| C(\"Done (really)\")\n  end\n";
+        assert_eq!(check("mermaid", src), vec![]);
+        let seq = "sequenceDiagram\n  participant A\n  loop Every minute\n    A->>B: ping\n  end\n  alt ok\n    B-->>A: pong\n  else down\n    B--xA: timeout\n  end\n";
+        assert_eq!(check("mermaid", seq), vec![]);
+        let class = "classDiagram\n  class Animal {\n    +String name\n  }\n";
+        assert_eq!(check("mermaid", class), vec![]);
+    }
+
+    #[test]
+    fn test_mermaid_errors() {
+        let errors = check("mermaid", "graph XY\n  A[Start --> B\n  B -->\n  subgraph S\n");
+        assert_eq!(lines(&errors), vec![1, 2, 3, 4]);
+        assert!(errors[0].message.contains("direction"));
+        assert!(errors[1].message.contains("\"[\" is never closed"));
+        assert!(errors[2].message.contains("no target"));
+        assert!(errors[3].message.contains("subgraph"));
+
+        let errors = check("mermaid", "\nflowchrt TD\n  A --> B\n");
+        assert_eq!(lines(&errors), vec![2]);
+        assert!(errors[0].message.contains("unknown diagram type \"flowchrt\""));
+
+        let errors = check("mermaid", "sequenceDiagram\n  A->>B\n  else\n  end\n");
+        assert_eq!(lines(&errors), vec![2, 3, 4]);
+    }
+
+    #[test]
+    fn test_d2() {
+        let ok = "# servers\nweb: Web Server {\n  shape: rectangle\n}\nweb -> db: \"queries # not a comment\"\nexplain: |md\n  # Heading {\n|\n";
+        assert_eq!(check("d2", ok), vec![]);
+
+        let errors = check("d2", "a -> \nb: {\n  label: \"oops\n}\n}\n");
+        assert_eq!(lines(&errors), vec![1, 3, 5]);
+        assert!(errors[0].message.contains("no target"));
+        assert!(errors[2].message.contains("without a matching"));
+    }
+
+    #[test]
+    fn test_unchecked_language() {
+        assert!(check("plantuml", "@startuml\nwhatever(\n").is_empty());
+    }
+}
@@ -5,6 +5,7 @@ use serde::Serialize;
 use crate::document::Document;
 use crate::error::Result;
 use crate::frontmatter::yaml_value_to_string as yaml_value_display;
+use crate::schema::Schema;
 
 /// Kind of change for a frontmatter field.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize)]
@@ -22,6 +23,9 @@ pub struct FieldChange {
     pub kind: FieldChangeKind,
     pub old: Option<String>,
     pub new: Option<String>,
+    /// The change breaks the type's `workflow`; set by [`DocDiff::mark_transitions`].
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub illegal_transition: bool,
 }
 
 /// Kind of change for a section.
@@ -60,6 +64,18 @@ impl DocDiff {
     pub fn is_empty(&self) -> bool {
         self.field_changes.is_empty() && self.section_changes.is_empty()
     }
+
+    /// Flag changes of the workflow field that `doc_type`'s workflow does not allow.
+    pub fn mark_transitions(&mut self, schema: &Schema, doc_type: &str) {
+        let Some((field, workflow)) = schema.workflow(doc_type) else {
+            return;
+        };
+        for change in self.field_changes.iter_mut().filter(|c| c.field == field) {
+            if let (Some(from), Some(to)) = (&change.old, &change.new) {
+                change.illegal_transition = !workflow.allows(from, to);
+            }
+        }
+    }
 }
 
 /// Compare two markdown document strings and return structured diff.
@@ -120,6 +136,7 @@ fn diff_frontmatter(old_doc: &Document, new_doc: &Document) -> Vec<FieldChange>
                     kind: FieldChangeKind::Added,
                     old: None,
                     new: Some(yaml_value_display(v)),
+                    illegal_transition: false,
                 });
             }
             (Some(v), None) => {
@@ -128,6 +145,7 @@ fn diff_frontmatter(old_doc: &Document, new_doc: &Document) -> Vec<FieldChange>
                     kind: FieldChangeKind::Removed,
                     old: Some(yaml_value_display(v)),
                     new: None,
+                    illegal_transition: false,
                 });
             }
             (Some(old_v), Some(new_v)) => {
@@ -137,6 +155,7 @@ fn diff_frontmatter(old_doc: &Document, new_doc: &Document) -> Vec<FieldChange>
                         kind: FieldChangeKind::Changed,
                         old: Some(yaml_value_display(old_v)),
                         new: Some(yaml_value_display(new_v)),
+                        illegal_transition: false,
                     });
                 }
             }
@@ -349,6 +368,23 @@ Bad things.
         assert_eq!(reviewers.kind, FieldChangeKind::Changed);
     }
 
+    #[test]
+    fn test_mark_transitions() {
+        let schema = Schema::from_str(
+            "type \"adr\" {\n    workflow {\n        transition \"proposed\" to=\"accepted\"\n    }\n}\n",
+        )
+        .unwrap();
+        let old = "---\ntype: adr\nstatus: accepted\ntitle: A\n---\n";
+        let mut diff = diff_documents(old, &old.replace("accepted", "proposed").replace(": A", ": B")).unwrap();
+        diff.mark_transitions(&schema, "adr");
+        let flagged: Vec<(&str, bool)> =
+            diff.field_changes.iter().map(|c| (c.field.as_str(), c.illegal_transition)).collect();
+        assert_eq!(flagged, vec![("status", true), ("title", false)]);
+        let json = serde_json::to_value(&diff).unwrap();
+        assert_eq!(json["field_changes"][0]["illegal_transition"], true);
+        assert!(json["field_changes"][1].get("illegal_transition").is_none());
+    }
+
     #[test]
     fn test_field_removed() {
         let old = "---\ntitle: Test\nstatus: ok\n---\n# Body\n";
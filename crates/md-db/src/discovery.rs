@@ -1,3 +1,4 @@
+use std::cmp::Ordering;
 use std::path::{Path, PathBuf};
 
 use ignore::WalkBuilder;
@@ -21,9 +22,68 @@ pub enum Filter {
     HasField(String),
     /// Field must NOT exist.
     NotHasField(String),
+    /// Field must compare to value with `op`. Dates and datetimes compare
+    /// chronologically, numbers numerically, anything else as text.
+    FieldCompare {
+        key: String,
+        op: CompareOp,
+        value: String,
+    },
 }
 
-/// Discover markdown files in a directory with optional filtering.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompareOp {
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl CompareOp {
+    fn accepts(self, ord: Ordering) -> bool {
+        match self {
+            CompareOp::Lt => ord == Ordering::Less,
+            CompareOp::Le => ord != Ordering::Greater,
+            CompareOp::Gt => ord == Ordering::Greater,
+            CompareOp::Ge => ord != Ordering::Less,
+        }
+    }
+}
+
+impl Filter {
+    /// Parse `key<value`, `key<=value`, `key>value`, or `key>=value`.
+    /// `$TODAY` and `$NOW` in the value expand to the current date / datetime.
+    pub fn parse_comparison(expr: &str) -> Option<Filter> {
+        let pos = expr.find(['<', '>'])?;
+        let (key, rest) = expr.split_at(pos);
+        let (op, value) = if let Some(v) = rest.strip_prefix("<=") {
+            (CompareOp::Le, v)
+        } else if let Some(v) = rest.strip_prefix(">=") {
+            (CompareOp::Ge, v)
+        } else if let Some(v) = rest.strip_prefix('<') {
+            (CompareOp::Lt, v)
+        } else {
+            (CompareOp::Gt, &rest[1..])
+        };
+        let (key, value) = (key.trim(), value.trim());
+        if key.is_empty() || value.is_empty() {
+            return None;
+        }
+        let value = match value {
+            "$TODAY" => crate::template::format_today(),
+            "$NOW" => crate::template::format_now(),
+            other => other.to_string(),
+        };
+        Some(Filter::FieldCompare {
+            key: key.to_string(),
+            op,
+            value,
+        })
+    }
+}
+
+/// Discover markdown files in a directory with optional filtering. A directory
+/// holding an `md-db.kdl` manifest stands for its project: every root is walked.
 pub fn discover_files(
     dir: impl AsRef<Path>,
     pattern: Option<&str>,
@@ -31,6 +91,19 @@ pub fn discover_files(
     no_ignore: bool,
 ) -> Result<Vec<PathBuf>> {
     let dir = dir.as_ref();
+    if let Some(project) = crate::project::Project::at(dir)? {
+        return project.files(pattern, filters, no_ignore);
+    }
+    walk_files(dir, pattern, filters, no_ignore)
+}
+
+/// Markdown files under one directory, ignoring manifests.
+pub(crate) fn walk_files(
+    dir: &Path,
+    pattern: Option<&str>,
+    filters: &[Filter],
+    no_ignore: bool,
+) -> Result<Vec<PathBuf>> {
     let glob_pattern = pattern.unwrap_or("*.md");
 
     let mut results = Vec::new();
@@ -55,15 +128,10 @@ pub fn discover_files(
             continue;
         }
 
-        // If there are filters, parse frontmatter and check
+        // If there are filters, read just the frontmatter and check
         if !filters.is_empty() {
-            let content = match std::fs::read_to_string(path) {
-                Ok(c) => c,
-                Err(_) => continue,
-            };
-
-            let fm = match Frontmatter::try_parse(&content) {
-                Ok((Some(fm), _)) => fm,
+            let fm = match Frontmatter::read_from_file(path) {
+                Ok(Some(fm)) => fm,
                 _ => continue,
             };
 
@@ -79,6 +147,24 @@ pub fn discover_files(
     Ok(results)
 }
 
+/// [`discover_files`] over files already read, e.g. at a git revision: keeps
+/// those whose name matches `pattern` and whose frontmatter passes `filters`.
+pub fn filter_contents(
+    files: Vec<(PathBuf, String)>,
+    pattern: Option<&str>,
+    filters: &[Filter],
+) -> Vec<(PathBuf, String)> {
+    let glob_pattern = pattern.unwrap_or("*.md");
+    files
+        .into_iter()
+        .filter(|(path, content)| {
+            matches_glob(path, glob_pattern)
+                && (filters.is_empty()
+                    || matches!(Frontmatter::try_parse(content), Ok((Some(fm), _)) if check_filters(&fm, filters)))
+        })
+        .collect()
+}
+
 fn matches_glob(path: &Path, pattern: &str) -> bool {
     let file_name = match path.file_name().and_then(|n| n.to_str()) {
         Some(n) => n,
@@ -92,7 +178,7 @@ fn matches_glob(path: &Path, pattern: &str) -> bool {
     }
 }
 
-fn check_filters(fm: &Frontmatter, filters: &[Filter]) -> bool {
+pub(crate) fn check_filters(fm: &Frontmatter, filters: &[Filter]) -> bool {
     for filter in filters {
         match filter {
             Filter::FieldEquals { key, value } => {
@@ -130,6 +216,12 @@ fn check_filters(fm: &Frontmatter, filters: &[Filter]) -> bool {
                     return false;
                 }
             }
+            Filter::FieldCompare { key, op, value } => {
+                match fm.get_display(key) {
+                    Some(v) if op.accepts(crate::ordering::compare_text(&v, value)) => {}
+                    _ => return false,
+                }
+            }
         }
     }
     true
@@ -180,4 +272,35 @@ mod tests {
         assert!(matches_glob(path, "adr-*.md"));
         assert!(!matches_glob(path, "*.txt"));
     }
+
+    #[test]
+    fn test_field_compare() {
+        let fm = Frontmatter::try_parse("---\ndate: 2024-03-01\nat: 2024-03-01T10:00:00+02:00\nscore: 9\n---\n")
+            .unwrap()
+            .0
+            .unwrap();
+        let passes = |expr: &str| check_filters(&fm, &[Filter::parse_comparison(expr).unwrap()]);
+        assert!(passes("date>=2024-03-01"));
+        assert!(passes("date<2024-10-01"));
+        assert!(!passes("date>2024-03-01"));
+        assert!(passes("at<2024-03-01T09:00:00Z"), "offsets are applied");
+        assert!(!passes("score>10") && passes("score<10"), "numeric, not textual");
+        assert!(!passes("missing>1"));
+        assert!(Filter::parse_comparison("date=2024").is_none());
+    }
+
+    #[test]
+    fn test_filter_contents() {
+        let files = vec![
+            (PathBuf::from("docs/adr-001.md"), "---\nstatus: accepted\n---\n".to_string()),
+            (PathBuf::from("docs/adr-002.md"), "---\nstatus: proposed\n---\n".to_string()),
+            (PathBuf::from("docs/inc-001.md"), "---\nstatus: accepted\n---\n".to_string()),
+            (PathBuf::from("docs/notes.md"), "no frontmatter".to_string()),
+        ];
+        let accepted = [Filter::FieldEquals { key: "status".into(), value: "accepted".into() }];
+        let kept = filter_contents(files.clone(), Some("adr-*.md"), &accepted);
+        assert_eq!(kept.len(), 1);
+        assert_eq!(kept[0].0, Path::new("docs/adr-001.md"));
+        assert_eq!(filter_contents(files, None, &[]).len(), 4);
+    }
 }
@@ -47,6 +47,18 @@ impl Document {
         self.frontmatter.as_ref().ok_or(Error::NoFrontmatter)
     }
 
+    /// Number of lines in `raw` before the first line of `body`, for turning body
+    /// line numbers into file line numbers.
+    pub fn body_line_offset(&self) -> usize {
+        let body = self.body.trim_end();
+        let offset = if body.is_empty() {
+            self.raw.len()
+        } else {
+            self.raw.rfind(body).unwrap_or(0)
+        };
+        self.raw[..offset].matches('\n').count()
+    }
+
     /// Get a section by heading text (case-insensitive exact match).
     pub fn get_section(&self, heading: &str) -> Result<Section> {
         let arena = Arena::new();
@@ -88,6 +100,24 @@ impl Document {
         Ok(section)
     }
 
+    /// Heading text of the nth (0-based) heading, at any level, that fully matches
+    /// a section pattern such as `Day \d+`. Use with `get_section` and the
+    /// section mutation methods to address repeated sections.
+    pub fn nth_matching_heading(&self, pattern: &str, n: usize) -> Result<String> {
+        let re = crate::schema::section_pattern_regex(pattern)
+            .map_err(|e| Error::InvalidFieldValue(format!("invalid section pattern '{pattern}': {e}")))?;
+        let arena = Arena::new();
+        let opts = ast_util::comrak_opts();
+        let root = comrak::parse_document(&arena, &self.body, &opts);
+
+        ast_util::find_headings(root, None)
+            .into_iter()
+            .map(|node| ast_util::collect_text(node).trim().to_string())
+            .filter(|text| re.is_match(text))
+            .nth(n)
+            .ok_or_else(|| Error::SectionNotFound(format!("{pattern} #{n}")))
+    }
+
     /// Get all top-level sections (headings at the minimum level found in the doc).
     pub fn sections(&self) -> Vec<Section> {
         let arena = Arena::new();
@@ -184,6 +214,18 @@ impl Document {
         self.set_field(key, value);
     }
 
+    /// Apply a JSON Merge Patch (RFC 7386) to the frontmatter, creating it if absent.
+    pub fn merge_patch(&mut self, patch: &serde_json::Value) -> Result<()> {
+        let mut fm = self
+            .frontmatter
+            .clone()
+            .unwrap_or_else(|| Frontmatter::from_data(std::collections::BTreeMap::new()));
+        fm.merge_patch(patch)?;
+        self.frontmatter = Some(fm);
+        self.rebuild_raw();
+        Ok(())
+    }
+
     /// Remove a frontmatter field and rebuild raw content.
     pub fn remove_field(&mut self, key: &str) -> Option<Value> {
         let removed = self.frontmatter.as_mut().and_then(|fm| fm.remove(key));
@@ -228,6 +270,37 @@ impl Document {
         Ok(())
     }
 
+    /// Append content to a section, creating it as a new top-level section at the end
+    /// of the body if it doesn't exist yet.
+    pub fn append_or_create_section(&mut self, heading: &str, content: &str) -> Result<()> {
+        match self.append_to_section(heading, content) {
+            Err(Error::SectionNotFound(_)) => {
+                let mut body = self.body.trim_end().to_string();
+                if !body.is_empty() {
+                    body.push_str("\n\n");
+                }
+                body.push_str(&format!("# {heading}\n\n{content}\n"));
+                self.body = body;
+                self.rebuild_raw();
+                Ok(())
+            }
+            other => other,
+        }
+    }
+
+    /// Append an empty heading at the end of the body.
+    pub fn append_heading(&mut self, heading: &str, level: u8) {
+        let hashes = "#".repeat(level as usize);
+        self.body.push_str(&format!("\n{hashes} {heading}\n\n"));
+        self.rebuild_raw();
+    }
+
+    /// Replace the whole body, keeping the frontmatter.
+    pub fn set_body(&mut self, body: &str) {
+        self.body = body.to_string();
+        self.rebuild_raw();
+    }
+
     /// Update a table cell within a section.
     pub fn set_table_cell(
         &mut self,
@@ -256,17 +329,44 @@ impl Document {
         Ok(())
     }
 
+    /// Add an empty column to a table within a section.
+    pub fn add_table_column(&mut self, heading: &str, table_idx: usize, col: &str) -> Result<()> {
+        let (range, mut table) = self.find_table_byte_range(heading, table_idx)?;
+        table.add_column(col);
+        self.replace_body_range(range, &table.to_markdown());
+        Ok(())
+    }
+
+    /// Sort a table's rows by a column (see [`Table::sort_by_column`]).
+    /// Returns whether the order changed.
+    pub fn sort_table(
+        &mut self,
+        heading: &str,
+        table_idx: usize,
+        col: &str,
+        ascending: bool,
+    ) -> Result<bool> {
+        let (range, mut table) = self.find_table_byte_range(heading, table_idx)?;
+        let before = table.rows().to_vec();
+        table.sort_by_column(col, ascending)?;
+        if table.rows() == before.as_slice() {
+            return Ok(false);
+        }
+        self.replace_body_range(range, &table.to_markdown());
+        Ok(true)
+    }
+
     /// Save to the document's path (errors if no path set).
     pub fn save(&self) -> Result<()> {
         let path = self.path.as_ref().ok_or(Error::NoPath)?;
-        std::fs::write(path, &self.raw).map_err(|_| Error::WriteFailed(path.clone()))?;
+        crate::durability::write(path, &self.raw)?;
         Ok(())
     }
 
     /// Save to an explicit path.
     pub fn save_to(&self, path: impl AsRef<Path>) -> Result<()> {
         let path = path.as_ref();
-        std::fs::write(path, &self.raw).map_err(|_| Error::WriteFailed(path.to_path_buf()))?;
+        crate::durability::write(path, &self.raw)?;
         Ok(())
     }
 
@@ -387,6 +487,23 @@ Bad things.
         assert_eq!(sections.len(), 2); // Decision, Consequences (top-level = h1)
     }
 
+    #[test]
+    fn test_nth_matching_heading() {
+        let mut doc = Document::from_str(
+            "# Runbook\n\n## Day 1\n\nSetup.\n\n## Day 2\n\nDeploy.\n\n## Notes\n\nMisc.\n",
+        )
+        .unwrap();
+        assert_eq!(doc.nth_matching_heading(r"Day \d+", 1).unwrap(), "Day 2");
+        assert!(doc.nth_matching_heading(r"Day \d+", 2).is_err());
+        // Anchored: "Day" alone doesn't match "Day 1"
+        assert!(doc.nth_matching_heading("Day", 0).is_err());
+
+        let heading = doc.nth_matching_heading(r"Day \d+", 1).unwrap();
+        doc.replace_section_content(&heading, "Rollback.\n").unwrap();
+        assert!(doc.get_section("Day 2").unwrap().content.contains("Rollback"));
+        assert!(doc.get_section("Day 1").unwrap().content.contains("Setup"));
+    }
+
     #[test]
     fn test_to_json() {
         let doc = Document::from_str(SAMPLE).unwrap();
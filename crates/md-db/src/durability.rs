@@ -0,0 +1,226 @@
+//! Crash-safe file writes shared by every command that changes files.
+//!
+//! A write goes to a temporary file next to the target, streamed in
+//! [`CHUNK_SIZE`] chunks, and is renamed over the target once complete. A
+//! reader, or a machine that sleeps mid-write on a network filesystem, sees
+//! the old content or the new, never a truncated file. When the data is forced
+//! to disk follows the [`Durability`] policy, set once per process by the CLI's
+//! `--durability` flag:
+//!
+//! - `always` (the default) fsyncs each file before the rename, and its directory after.
+//! - `on-batch-end` records the files and fsyncs them in [`sync_pending`], once the
+//!   command (or a server request) is done.
+//! - `never` leaves flushing to the operating system.
+
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, AtomicU8, Ordering};
+use std::sync::Mutex;
+
+use crate::error::{Error, Result};
+
+/// Bytes handed to the filesystem per write call.
+pub const CHUNK_SIZE: usize = 64 * 1024;
+
+static POLICY: AtomicU8 = AtomicU8::new(0);
+
+/// Files written under `on-batch-end` and not synced yet.
+static PENDING: Mutex<Vec<PathBuf>> = Mutex::new(Vec::new());
+
+static TMP_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// `--durability always|on-batch-end|never`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Durability {
+    Always,
+    OnBatchEnd,
+    Never,
+}
+
+impl Durability {
+    pub const ALL: [Durability; 3] = [Durability::Always, Durability::OnBatchEnd, Durability::Never];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Durability::Always => "always",
+            Durability::OnBatchEnd => "on-batch-end",
+            Durability::Never => "never",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|d| d.name() == name)
+    }
+}
+
+pub fn set_policy(policy: Durability) {
+    POLICY.store(policy as u8, Ordering::Relaxed);
+}
+
+pub fn policy() -> Durability {
+    Durability::ALL[POLICY.load(Ordering::Relaxed) as usize]
+}
+
+/// A file being written. Nothing at the target changes until [`commit`](Self::commit);
+/// dropped uncommitted, the partial file is removed.
+#[derive(Debug)]
+pub struct AtomicFile {
+    target: PathBuf,
+    tmp: PathBuf,
+    writer: Option<BufWriter<File>>,
+}
+
+impl AtomicFile {
+    /// Start writing `path`. A symlink is followed, so the file it points to is
+    /// replaced rather than the link.
+    pub fn create(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let target = match std::fs::symlink_metadata(path) {
+            Ok(meta) if meta.file_type().is_symlink() => path.canonicalize().unwrap_or_else(|_| path.to_path_buf()),
+            _ => path.to_path_buf(),
+        };
+        let name = target.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+        let n = TMP_COUNTER.fetch_add(1, Ordering::Relaxed);
+        let tmp = target.with_file_name(format!(".{name}.{}-{n}.tmp", std::process::id()));
+        let file = File::create(&tmp).map_err(|_| Error::WriteFailed(target.clone()))?;
+        Ok(Self {
+            target,
+            tmp,
+            writer: Some(BufWriter::with_capacity(CHUNK_SIZE, file)),
+        })
+    }
+
+    /// Flush, sync per the policy, and rename over the target, keeping its permissions.
+    pub fn commit(mut self) -> Result<()> {
+        let writer = self.writer.take().expect("AtomicFile committed twice");
+        let result = self.replace(writer);
+        if result.is_err() {
+            let _ = std::fs::remove_file(&self.tmp);
+        }
+        result
+    }
+
+    fn replace(&self, writer: BufWriter<File>) -> Result<()> {
+        let failed = |_: std::io::Error| Error::WriteFailed(self.target.clone());
+        let file = writer.into_inner().map_err(|e| e.into_error()).map_err(failed)?;
+        if let Ok(meta) = std::fs::metadata(&self.target) {
+            std::fs::set_permissions(&self.tmp, meta.permissions()).map_err(failed)?;
+        }
+        if policy() == Durability::Always {
+            file.sync_all().map_err(failed)?;
+        }
+        drop(file);
+        std::fs::rename(&self.tmp, &self.target).map_err(failed)?;
+        match policy() {
+            Durability::Always => sync_dir(parent(&self.target)),
+            Durability::OnBatchEnd => PENDING.lock().unwrap_or_else(|e| e.into_inner()).push(self.target.clone()),
+            Durability::Never => {}
+        }
+        Ok(())
+    }
+}
+
+impl Write for AtomicFile {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.writer.as_mut().expect("write after commit").write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.writer.as_mut().expect("flush after commit").flush()
+    }
+}
+
+impl Drop for AtomicFile {
+    fn drop(&mut self) {
+        if self.writer.take().is_some() {
+            let _ = std::fs::remove_file(&self.tmp);
+        }
+    }
+}
+
+/// Replace `path` with `contents` atomically; the replacement for `std::fs::write`.
+pub fn write(path: impl AsRef<Path>, contents: impl AsRef<[u8]>) -> Result<()> {
+    let mut file = AtomicFile::create(&path)?;
+    file.write_all(contents.as_ref())
+        .map_err(|_| Error::WriteFailed(path.as_ref().to_path_buf()))?;
+    file.commit()
+}
+
+/// Apply the policy to a file written in place (an appended log), already flushed.
+pub fn sync_file(file: &File, path: &Path) -> Result<()> {
+    match policy() {
+        Durability::Always => file.sync_all().map_err(|_| Error::WriteFailed(path.to_path_buf())),
+        Durability::OnBatchEnd => {
+            PENDING.lock().unwrap_or_else(|e| e.into_inner()).push(path.to_path_buf());
+            Ok(())
+        }
+        Durability::Never => Ok(()),
+    }
+}
+
+/// Fsync every file written since the last call under `on-batch-end`, then
+/// their directories. Files removed since are skipped.
+pub fn sync_pending() -> Result<()> {
+    let mut paths = std::mem::take(&mut *PENDING.lock().unwrap_or_else(|e| e.into_inner()));
+    paths.sort();
+    paths.dedup();
+    for path in &paths {
+        match File::open(path) {
+            Ok(file) => file.sync_all().map_err(|_| Error::WriteFailed(path.clone()))?,
+            Err(_) => continue,
+        }
+    }
+    let mut dirs: Vec<&Path> = paths.iter().map(|p| parent(p)).collect();
+    dirs.dedup();
+    dirs.into_iter().for_each(sync_dir);
+    Ok(())
+}
+
+fn parent(path: &Path) -> &Path {
+    path.parent().filter(|d| !d.as_os_str().is_empty()).unwrap_or(Path::new("."))
+}
+
+/// Fsync a directory, so a rename into it survives a crash. Not every
+/// filesystem supports this, so failures are ignored.
+fn sync_dir(dir: &Path) {
+    #[cfg(unix)]
+    if let Ok(dir) = File::open(dir) {
+        let _ = dir.sync_all();
+    }
+    #[cfg(not(unix))]
+    let _ = dir;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_atomic_write() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("adr-001.md");
+        write(&path, "---\ntitle: Old\n---\n").unwrap();
+        write(&path, "---\ntitle: New\n---\n").unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "---\ntitle: New\n---\n");
+
+        // Streamed across several chunks; nothing changes until the commit
+        let mut file = AtomicFile::create(&path).unwrap();
+        let big = "x".repeat(CHUNK_SIZE * 3 + 5);
+        file.write_all(big.as_bytes()).unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "---\ntitle: New\n---\n");
+        file.commit().unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap().len(), big.len());
+
+        // Dropped uncommitted: the target is untouched and no temporary file is left
+        let mut file = AtomicFile::create(&path).unwrap();
+        file.write_all(b"partial").unwrap();
+        drop(file);
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 1);
+        assert_eq!(std::fs::read_to_string(&path).unwrap().len(), big.len());
+
+        assert_eq!(Durability::from_name("on-batch-end"), Some(Durability::OnBatchEnd));
+        assert_eq!(Durability::from_name("sometimes"), None);
+        assert!(sync_pending().is_ok());
+    }
+}
@@ -35,6 +35,12 @@ pub enum Error {
     #[error("schema parse error: {0}")]
     SchemaParse(String),
 
+    #[error("schema discovery: {0}")]
+    SchemaDiscovery(String),
+
+    #[error("project manifest error: {0}")]
+    Manifest(String),
+
     #[error("failed to write file: {0}")]
     WriteFailed(PathBuf),
 
@@ -52,6 +58,51 @@ pub enum Error {
 
     #[error("row {row} out of bounds (max {max})")]
     RowOutOfBounds { row: usize, max: usize },
+
+    #[error("document not found: {0}")]
+    DocumentNotFound(String),
+
+    #[error("ticket provider error: {0}")]
+    Ticket(String),
+
+    #[error("git error: {0}")]
+    Vcs(String),
+
+    #[error("marker block error: {0}")]
+    Marker(String),
+
+    #[error("template error: {0}")]
+    Template(String),
+
+    #[error("undo error: {0}")]
+    Undo(String),
+
+    #[error("import error: {0}")]
+    Import(String),
+
+    #[error("MCP capability error: {0}")]
+    Capability(String),
+
+    #[error("export error: {0}")]
+    Export(String),
+
+    #[error("split error: {0}")]
+    Split(String),
+
+    #[error("merge error: {0}")]
+    Merge(String),
+
+    #[error("notification error: {0}")]
+    Notify(String),
+
+    #[error("API token error: {0}")]
+    Auth(String),
+
+    #[error("fixture error: {0}")]
+    Fixture(String),
+
+    #[error("workflow violation: {0}")]
+    Workflow(String),
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
@@ -1,12 +1,24 @@
 use std::collections::BTreeMap;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 use comrak::{Arena, Options};
 use regex::Regex;
 
+use crate::badges::DocBadges;
 use crate::document::Document;
 use crate::graph::{path_to_id, DocGraph};
-use crate::schema::Schema;
+use crate::health::DocHealth;
+use crate::locale::Locale;
+use crate::permalink::{self, Manifest};
+use crate::schema::{IndexKey, IndexRules, Schema};
+use crate::theme::Theme;
+use crate::validation::{Diagnostic, Severity};
+
+pub mod confluence;
+mod pdf;
+mod site;
+
+pub use pdf::{export_pdf, print_html, Engine, EngineKind, PdfOptions, PdfReport, ENGINES};
 
 /// Encode a string for safe use in HTML double-quoted attributes (href, class, etc.).
 /// Uses encode_minimal which escapes &, <, >, ", and ' — sufficient for attribute values
@@ -22,33 +34,41 @@ fn encode_text(s: &str) -> String {
 }
 
 /// Render a Document's markdown body to HTML using comrak.
-/// Raw HTML blocks in markdown are stripped (unsafe_ = false) to prevent XSS.
-fn render_markdown_to_html(body: &str) -> String {
+/// Raw HTML in markdown goes through the allowlist sanitizer to prevent XSS,
+/// unless `unsafe_html` passes it through as written.
+fn render_markdown_to_html(body: &str, unsafe_html: bool) -> String {
     let arena = Arena::new();
     let mut opts = Options::default();
     opts.extension.table = true;
     opts.extension.strikethrough = true;
     opts.extension.autolink = true;
-    opts.render.unsafe_ = false;
+    opts.render.unsafe_ = true;
     let root = comrak::parse_document(&arena, body, &opts);
     let mut html = Vec::new();
     comrak::format_html(root, &opts, &mut html).unwrap_or_default();
-    String::from_utf8_lossy(&html).to_string()
+    let html = String::from_utf8_lossy(&html);
+    if unsafe_html {
+        html.to_string()
+    } else {
+        crate::sanitize::sanitize(&html)
+    }
 }
 
-/// Build a frontmatter metadata HTML table.
-fn frontmatter_table(doc: &Document) -> String {
+/// Build a frontmatter metadata HTML table, with dates and numbers in the locale's format.
+fn frontmatter_table(doc: &Document, locale: &Locale) -> String {
     let fm = match &doc.frontmatter {
         Some(fm) => fm,
         None => return String::new(),
     };
 
-    let mut html = String::from(
-        "<table class=\"metadata\">\n<thead><tr><th>Field</th><th>Value</th></tr></thead>\n<tbody>\n",
+    let mut html = format!(
+        "<table class=\"metadata\">\n<thead><tr><th>{}</th><th>{}</th></tr></thead>\n<tbody>\n",
+        encode_text(&locale.label("Field")),
+        encode_text(&locale.label("Value")),
     );
 
     for (key, val) in fm.data() {
-        let display = crate::frontmatter::yaml_value_to_string(val);
+        let display = locale.format_value(val);
         html.push_str(&format!(
             "<tr><td>{}</td><td>{}</td></tr>\n",
             htmlescape::encode_minimal(key),
@@ -59,8 +79,85 @@ fn frontmatter_table(doc: &Document) -> String {
     html
 }
 
+/// Where exported pages live, for links between them; see [`crate::permalink`].
+#[derive(Debug, Clone, Copy)]
+struct Links<'a> {
+    /// Document ID to URL; IDs not listed use [`permalink::default_url`].
+    urls: Option<&'a BTreeMap<String, String>>,
+    /// URL of the page being rendered.
+    page: &'a str,
+    /// Document types with a listing page, for the navigation bar.
+    types: &'a [String],
+    /// Whether the site has schema and graph pages.
+    schema: bool,
+}
+
+impl<'a> Links<'a> {
+    /// The flat `adr-001.html` layout, seen from a top-level page.
+    fn flat() -> Self {
+        Links {
+            urls: None,
+            page: "/index.html",
+            types: &[],
+            schema: false,
+        }
+    }
+
+    fn from_page(self, page: &'a str) -> Self {
+        Links { page, ..self }
+    }
+
+    fn doc(&self, id: &str) -> String {
+        match self.urls.and_then(|urls| urls.get(id)) {
+            Some(url) => permalink::relative_href(self.page, url),
+            None => permalink::relative_href(self.page, &permalink::default_url(id)),
+        }
+    }
+
+    fn page(&self, url: &str) -> String {
+        permalink::relative_href(self.page, url)
+    }
+
+    /// Navigation bar: index, search, graph, and schema pages, then one link per
+    /// type's listing page. Outside a site export, only the index.
+    fn nav(&self, locale: &Locale) -> String {
+        let link = |url: &str, label: &str| {
+            format!("<a href=\"{}\">{}</a>", encode_attr(&self.page(url)), encode_text(label))
+        };
+        let mut items = vec![link("/index.html", &locale.label("Index"))];
+        if self.urls.is_some() {
+            items.push(link(site::SEARCH_PAGE, &locale.label("Search")));
+            if self.schema {
+                items.push(link(site::GRAPH_PAGE, &locale.label("Graph")));
+                items.push(link("/schema.html", &locale.label("Schema")));
+            }
+        }
+        let mut nav = format!("<nav>{}", items.join(" · "));
+        if !self.types.is_empty() {
+            let types: Vec<String> = self.types.iter().map(|t| link(&site::type_url(t), &t.to_uppercase())).collect();
+            nav.push_str(&format!(" | <span class=\"nav-types\">{}</span>", types.join(" ")));
+        }
+        nav.push_str("</nav>");
+        nav
+    }
+}
+
+/// Point links to other documents' `.md` files at their exported pages.
+fn rewrite_md_links(html: &str, known_ids: &[String], links: Links) -> String {
+    let re = Regex::new(r##"href="([^"#:]+\.md)(#[^"]*)?""##).unwrap();
+    re.replace_all(html, |caps: &regex::Captures| {
+        let id = path_to_id(Path::new(&caps[1]));
+        if !known_ids.contains(&id) {
+            return caps[0].to_string();
+        }
+        let anchor = caps.get(2).map_or("", |m| m.as_str());
+        format!("href=\"{}{anchor}\"", encode_attr(&links.doc(&id)))
+    })
+    .to_string()
+}
+
 /// Convert cross-document refs (e.g. ADR-001) in HTML to clickable links.
-fn linkify_refs(html: &str, known_ids: &[String]) -> String {
+fn linkify_refs(html: &str, known_ids: &[String], links: Links) -> String {
     if known_ids.is_empty() {
         return html.to_string();
     }
@@ -71,12 +168,7 @@ fn linkify_refs(html: &str, known_ids: &[String]) -> String {
 
     re.replace_all(html, |caps: &regex::Captures| {
         let id = &caps[0];
-        let lower = id.to_lowercase();
-        format!(
-            "<a href=\"{}\">{}</a>",
-            encode_attr(&format!("{lower}.html")),
-            encode_text(id),
-        )
+        format!("<a href=\"{}\">{}</a>", encode_attr(&links.doc(id)), encode_text(id))
     })
     .to_string()
 }
@@ -93,20 +185,147 @@ table.metadata th { background: #e8e8e8; }
 .status-deprecated, .status-superseded { background: #fecaca; color: #991b1b; }
 .status-accepted, .status-active, .status-resolved { background: #bbf7d0; color: #166534; }
 .status-proposed, .status-draft { background: #fef3c7; color: #92400e; }
+.doc-badges { margin: 0.25rem 0 1rem; }
+.badge { display: inline-block; padding: 0.1rem 0.45rem; border-radius: 3px; font-size: 0.8rem; background: #eef2ff; color: #3730a3; }
+.badge-complete { background: #dcfce7; color: #166534; }
+.badge-incomplete { background: #ffedd5; color: #9a3412; }
 .backlinks { margin-top: 2rem; padding: 1rem; background: #f9fafb; border: 1px solid #e5e7eb; border-radius: 4px; }
 .backlinks h2 { margin-top: 0; font-size: 1rem; }
+.backlinks h3 { margin: 0.75rem 0 0.25rem; font-size: 0.9rem; color: #4b5563; }
 a { color: #2563eb; }
 nav { margin-bottom: 1rem; font-size: 0.9rem; }
 h1 { border-bottom: 1px solid #e5e7eb; padding-bottom: 0.3rem; }
+details.index-group summary { cursor: pointer; }
+details.index-group summary h2 { display: inline; font-size: 1.25rem; }
+.attention { margin: 1rem 0; padding: 0.75rem 1rem; background: #fff7ed; border: 1px solid #fed7aa; border-radius: 4px; }
+.attention h2 { margin-top: 0; font-size: 1rem; }
+.reasons { color: #9a3412; font-size: 0.85rem; }
+.updated { color: #6b7280; font-size: 0.85rem; }
+.health { display: inline-block; padding: 0 0.4rem; border-radius: 3px; font-size: 0.8rem; }
+.health-low { background: #fecaca; color: #991b1b; }
+.health-fair { background: #fef3c7; color: #92400e; }
+.health-good { background: #dcfce7; color: #166534; }
+.nav-types a { margin-right: 0.4rem; }
+.search-input { width: 100%; padding: 0.5rem; font-size: 1rem; box-sizing: border-box; }
+.search-results li { margin-bottom: 0.75rem; }
+.snippet { margin: 0.2rem 0 0; color: #4b5563; font-size: 0.85rem; }
+#graph { width: 100%; height: auto; border: 1px solid #e5e7eb; border-radius: 4px; }
+.graph-edge { stroke: #cbd5e1; stroke-width: 1.5; }
+.graph-label { font-size: 11px; fill: #1f2937; }
+.graph-type { display: inline-block; margin-right: 0.4rem; padding: 0 0.4rem; border-radius: 3px; color: #fff; }
+pre.mermaid { background: none; text-align: center; }
 "#;
 
 /// Export a single document to a full HTML page.
 pub fn export_html(doc: &Document, known_ids: &[String], backlinks: &[(String, String)]) -> String {
+    let badges = crate::badges::compute(doc, None);
+    let page = PageContext {
+        known_ids,
+        schema: None,
+        locale: &Locale::default(),
+        links: Links::flat(),
+        unsafe_html: false,
+        theme: None,
+    };
+    render_document(doc, backlinks, &badges, page)
+}
+
+/// Reading-time and completeness badges shown under the page heading.
+fn badges_html(badges: &DocBadges, locale: &Locale) -> String {
+    let minutes = badges.reading_minutes.to_string();
+    let mut out = format!(
+        "<p class=\"doc-badges\"><span class=\"badge\">{}</span>",
+        encode_text(&locale.label_with("{n} min read", &[("n", &minutes)]))
+    );
+    if let Some(c) = &badges.completeness {
+        let class = if c.is_complete() { "complete" } else { "incomplete" };
+        let (filled, required) = (c.filled.to_string(), c.required.to_string());
+        let label = locale.label_with("{filled}/{required} sections", &[("filled", &filled), ("required", &required)]);
+        let title = if c.unfilled.is_empty() {
+            String::new()
+        } else {
+            let to_fill = locale.label_with("To fill: {sections}", &[("sections", &c.unfilled.join(", "))]);
+            format!(" title=\"{}\"", encode_attr(&to_fill))
+        };
+        out.push_str(&format!(
+            " <span class=\"badge badge-{class}\"{title}>{}</span>",
+            encode_text(&label)
+        ));
+    }
+    out.push_str("</p>");
+    out
+}
+
+/// "Referenced by" panel. With a schema, backlinks follow the schema's relation
+/// order, and get one sub-heading per group when relations are grouped.
+fn backlinks_html(backlinks: &[(String, String)], schema: Option<&Schema>, locale: &Locale, links: Links) -> String {
+    if backlinks.is_empty() {
+        return String::new();
+    }
+    let mut sorted: Vec<&(String, String)> = backlinks.iter().collect();
+    if let Some(schema) = schema {
+        sorted.sort_by_key(|(_, relation)| schema.relation_rank(relation));
+    }
+    let grouped = schema.is_some_and(|s| s.has_relation_groups());
+
+    let mut bl = format!(
+        "<div class=\"backlinks\"><h2>{}</h2>",
+        encode_text(&locale.label("Referenced by"))
+    );
+    let mut current: Option<Option<&str>> = None;
+    for (ref_id, ref_relation) in sorted {
+        let group = schema.and_then(|s| s.relation_group(ref_relation));
+        if current != Some(group) {
+            if current.is_some() {
+                bl.push_str("</ul>");
+            }
+            if grouped {
+                let heading = group.map_or_else(|| locale.label("other"), str::to_string);
+                bl.push_str(&format!("\n<h3>{}</h3>", encode_text(&heading)));
+            }
+            bl.push_str("<ul>\n");
+            current = Some(group);
+        }
+        bl.push_str(&format!(
+            "<li><a href=\"{}\">{}</a> ({})</li>\n",
+            encode_attr(&links.doc(ref_id)),
+            encode_text(ref_id),
+            encode_text(ref_relation),
+        ));
+    }
+    bl.push_str("</ul></div>\n");
+    bl
+}
+
+/// What the document pages of one export share.
+#[derive(Debug, Clone, Copy)]
+struct PageContext<'a> {
+    known_ids: &'a [String],
+    schema: Option<&'a Schema>,
+    locale: &'a Locale,
+    links: Links<'a>,
+    unsafe_html: bool,
+    theme: Option<&'a Theme>,
+}
+
+impl PageContext<'_> {
+    /// Markdown to HTML with links to other documents pointing at their pages.
+    fn markdown(&self, markdown: &str) -> String {
+        let html = render_markdown_to_html(markdown, self.unsafe_html);
+        let html = rewrite_md_links(&html, self.known_ids, self.links);
+        linkify_refs(&html, self.known_ids, self.links)
+    }
+}
+
+/// Render a document page with precomputed badges, through the theme's
+/// template for its type when there is one.
+fn render_document(doc: &Document, backlinks: &[(String, String)], badges: &DocBadges, page: PageContext) -> String {
+    let PageContext { schema, locale, links, .. } = page;
     let title = doc
         .frontmatter
         .as_ref()
         .and_then(|fm| fm.get_display("title"))
-        .unwrap_or_else(|| "Untitled".to_string());
+        .unwrap_or_else(|| locale.label("Untitled"));
 
     let status = doc
         .frontmatter
@@ -119,9 +338,8 @@ pub fn export_html(doc: &Document, known_ids: &[String], backlinks: &[(String, S
         .map(|p| path_to_id(p))
         .unwrap_or_default();
 
-    let fm_html = frontmatter_table(doc);
-    let body_html = render_markdown_to_html(&doc.body);
-    let body_linked = linkify_refs(&body_html, known_ids);
+    let fm_html = frontmatter_table(doc, locale);
+    let body_linked = page.markdown(&doc.body);
 
     let status_badge = status
         .as_ref()
@@ -135,28 +353,51 @@ pub fn export_html(doc: &Document, known_ids: &[String], backlinks: &[(String, S
         })
         .unwrap_or_default();
 
-    let backlinks_html = if backlinks.is_empty() {
-        String::new()
-    } else {
-        let mut bl = String::from("<div class=\"backlinks\"><h2>Referenced by</h2><ul>\n");
-        for (ref_id, ref_relation) in backlinks {
-            let lower = ref_id.to_lowercase();
-            bl.push_str(&format!(
-                "<li><a href=\"{}\">{}</a> ({})</li>\n",
-                encode_attr(&format!("{lower}.html")),
-                encode_text(ref_id),
-                encode_text(ref_relation),
-            ));
-        }
-        bl.push_str("</ul></div>\n");
-        bl
-    };
+    let backlinks_html = backlinks_html(backlinks, schema, locale, links);
+    let badges_html = badges_html(badges, locale);
+    let nav = links.nav(locale);
+
+    let doc_type = doc.frontmatter.as_ref().and_then(|fm| fm.get_display("type"));
+    if let Some(template) = page.theme.and_then(|t| t.template_for(doc_type.as_deref())) {
+        let fields: Vec<serde_json::Value> = doc
+            .frontmatter
+            .iter()
+            .flat_map(|fm| fm.data())
+            .map(|(name, value)| serde_json::json!({ "name": name, "value": locale.format_value(value) }))
+            .collect();
+        let backlinks: Vec<serde_json::Value> = backlinks
+            .iter()
+            .map(|(id, relation)| serde_json::json!({ "id": id, "relation": relation, "href": links.doc(id) }))
+            .collect();
+        let (sections, section) = theme_sections(&doc.body, &page);
+        let context = serde_json::json!({
+            "id": doc_id,
+            "title": title,
+            "type": doc_type,
+            "status": status,
+            "lang": locale.lang,
+            "root": links.page("/"),
+            "css": CSS,
+            "nav": nav,
+            "badges": badges_html,
+            "metadata": fm_html,
+            "body": body_linked,
+            "backlinks_html": backlinks_html,
+            "frontmatter": doc.frontmatter.as_ref().map(|fm| fm.to_json()),
+            "fields": fields,
+            "sections": sections,
+            "section": section,
+            "backlinks": backlinks,
+        });
+        return template.render(&context);
+    }
 
     let encoded_title = encode_text(&title);
     let encoded_doc_id = encode_text(&doc_id);
+    let lang = encode_attr(&locale.lang);
     format!(
         r#"<!DOCTYPE html>
-<html lang="en">
+<html lang="{lang}">
 <head>
 <meta charset="utf-8">
 <meta name="viewport" content="width=device-width, initial-scale=1">
@@ -164,8 +405,9 @@ pub fn export_html(doc: &Document, known_ids: &[String], backlinks: &[(String, S
 <style>{CSS}</style>
 </head>
 <body>
-<nav><a href="index.html">Index</a></nav>
+{nav}
 <h1>{encoded_doc_id}{status_badge}</h1>
+{badges_html}
 {fm_html}
 {body_linked}
 {backlinks_html}
@@ -175,62 +417,315 @@ pub fn export_html(doc: &Document, known_ids: &[String], backlinks: &[(String, S
     )
 }
 
-/// Export an index page listing all documents grouped by type.
+/// Sections for theme templates: every heading in order with the HTML up to
+/// the next heading, and each heading's full content (subsections included)
+/// by heading text.
+fn theme_sections(
+    body: &str,
+    page: &PageContext,
+) -> (Vec<serde_json::Value>, serde_json::Map<String, serde_json::Value>) {
+    let arena = Arena::new();
+    let root = comrak::parse_document(&arena, body, &crate::ast_util::comrak_opts());
+    let headings = crate::ast_util::find_headings(root, None);
+    let starts: Vec<usize> = headings
+        .iter()
+        .map(|h| crate::ast_util::section_byte_range(h, body).start)
+        .collect();
+    let mut sections = Vec::new();
+    let mut by_heading = serde_json::Map::new();
+    for (i, node) in headings.iter().enumerate() {
+        let heading = crate::ast_util::collect_text(node).trim().to_string();
+        let content = crate::ast_util::section_content_byte_range(node, body);
+        let own_end = starts.get(i + 1).copied().unwrap_or(body.len()).clamp(content.start, content.end);
+        sections.push(serde_json::json!({
+            "heading": heading,
+            "level": crate::ast_util::heading_level(node).unwrap_or(1),
+            "html": page.markdown(&body[content.start..own_end]),
+        }));
+        by_heading
+            .entry(heading)
+            .or_insert_with(|| serde_json::Value::String(page.markdown(&body[content])));
+    }
+    (sections, by_heading)
+}
+
+/// Export an index page listing all documents grouped by type, ordered by ID.
 pub fn export_index(docs: &[(String, &Document)]) -> String {
-    // Group by type
-    let mut by_type: BTreeMap<String, Vec<(String, String)>> = BTreeMap::new();
+    render_index(docs, None, "", &Locale::default(), &IndexRules::default(), &BTreeMap::new(), Links::flat())
+}
 
-    for (id, doc) in docs {
-        let doc_type = doc
-            .frontmatter
+/// Render the index page with an optional `<nav>` block above the heading.
+/// Documents are split into collapsible groups by `rules.group_by` and follow
+/// `rules.order_by` within them, falling back to each type's `order-by` keys, then ID.
+/// Documents whose health needs attention are listed first.
+fn render_index(
+    docs: &[(String, &Document)],
+    schema: Option<&Schema>,
+    nav: &str,
+    locale: &Locale,
+    rules: &IndexRules,
+    health: &BTreeMap<String, DocHealth>,
+    links: Links,
+) -> String {
+    let mut docs: Vec<&(String, &Document)> = docs.iter().collect();
+    crate::ordering::sort_documents(&mut docs, schema, |(id, doc)| {
+        (id.as_str(), doc.frontmatter.as_ref())
+    });
+    let unscored = DocHealth {
+        score: 100,
+        ..DocHealth::default()
+    };
+    let health_of = |id: &str| health.get(id).unwrap_or(&unscored);
+    let doc_type = |doc: &Document| {
+        doc.frontmatter
             .as_ref()
             .and_then(|fm| fm.get_display("type"))
-            .unwrap_or_else(|| "other".to_string());
+            .unwrap_or_else(|| locale.label("other"))
+    };
+    let status = |doc: &Document| {
+        let fm = doc.frontmatter.as_ref()?;
+        let field = match (schema, fm.get_display("type")) {
+            (Some(schema), Some(t)) => schema.status_field(&t).field,
+            _ => "status".to_string(),
+        };
+        fm.get_display(&field)
+    };
+
+    // Stable sorts, so ties keep the type order
+    match rules.order_by {
+        Some(IndexKey::Type) => docs.sort_by_key(|(_, doc)| doc_type(doc)),
+        Some(IndexKey::Status) => docs.sort_by_key(|(_, doc)| {
+            let s = status(doc);
+            (s.is_none(), s)
+        }),
+        Some(IndexKey::Recency) => docs.sort_by_key(|(id, _)| health_of(id).age_days.unwrap_or(i64::MAX)),
+        Some(IndexKey::Health) => docs.sort_by_key(|(id, _)| health_of(id).score),
+        None => {}
+    }
+
+    // Groups sort by rank, then label
+    let mut groups: BTreeMap<(usize, String), Vec<&(String, &Document)>> = BTreeMap::new();
+    for entry in &docs {
+        let (id, doc) = entry;
+        let key = match rules.group_by {
+            IndexKey::Type => (0, doc_type(doc).to_uppercase()),
+            IndexKey::Status => match status(doc) {
+                Some(s) => (0, s),
+                None => (1, locale.label("other")),
+            },
+            IndexKey::Recency => match health_of(id).age_days {
+                Some(age) if age <= 7 => (0, locale.label("Last 7 days")),
+                Some(age) if age <= 30 => (1, locale.label("Last 30 days")),
+                Some(age) if age <= 90 => (2, locale.label("Last 90 days")),
+                Some(_) => (3, locale.label("Older")),
+                None => (4, locale.label("Undated")),
+            },
+            IndexKey::Health => match health_of(id).score {
+                0..50 => (0, locale.label("Needs attention")),
+                50..80 => (1, locale.label("Fair")),
+                _ => (2, locale.label("Healthy")),
+            },
+        };
+        groups.entry(key).or_default().push(*entry);
+    }
+
+    let show_health = rules.group_by == IndexKey::Health || rules.order_by == Some(IndexKey::Health);
+    let show_updated = rules.group_by == IndexKey::Recency || rules.order_by == Some(IndexKey::Recency);
+    let item = |id: &str, doc: &Document, extra: &str| {
         let title = doc
             .frontmatter
             .as_ref()
             .and_then(|fm| fm.get_display("title"))
-            .unwrap_or_else(|| id.clone());
-        by_type
-            .entry(doc_type)
-            .or_default()
-            .push((id.clone(), title));
-    }
+            .unwrap_or_else(|| id.to_string());
+        format!(
+            "<li><a href=\"{}\">{}</a> — {}{extra}</li>\n",
+            encode_attr(&links.doc(id)),
+            encode_text(id),
+            encode_text(&title),
+        )
+    };
+    let date = |d: &str| locale.format_date(d).unwrap_or_else(|| d.to_string());
 
     let mut body = String::new();
     let total = docs.len();
-    body.push_str(&format!("<p>{total} documents</p>\n"));
+    let count = locale.label_with("{n} documents", &[("n", &total.to_string())]);
+    body.push_str(&format!("<p>{}</p>\n", encode_text(&count)));
 
-    for (doc_type, entries) in &by_type {
-        let upper_type = doc_type.to_uppercase();
+    let mut attention: Vec<&&(String, &Document)> =
+        docs.iter().filter(|(id, _)| health_of(id).needs_attention()).collect();
+    attention.sort_by_key(|(id, _)| health_of(id).score);
+    if !attention.is_empty() {
         body.push_str(&format!(
-            "<h2>{} ({})</h2>\n<ul>\n",
-            encode_text(&upper_type),
+            "<section class=\"attention\">\n<h2>{}</h2>\n<ul>\n",
+            encode_text(&locale.label("Needs attention"))
+        ));
+        for (id, doc) in attention {
+            let h = health_of(id);
+            let mut reasons = Vec::new();
+            if h.errors > 0 {
+                reasons.push(locale.label_with("{n} errors", &[("n", &h.errors.to_string())]));
+            }
+            if let Some(ref review) = h.overdue {
+                reasons.push(locale.label_with("review overdue since {date}", &[("date", &date(review))]));
+            }
+            if let Some(ref expiry) = h.expiring {
+                let key = if h.expired { "expired {date}" } else { "expires {date}" };
+                reasons.push(locale.label_with(key, &[("date", &date(expiry))]));
+            }
+            let extra = format!(" <span class=\"reasons\">{}</span>", encode_text(&reasons.join(", ")));
+            body.push_str(&item(id, doc, &extra));
+        }
+        body.push_str("</ul>\n</section>\n");
+    }
+
+    for ((_, label), entries) in &groups {
+        body.push_str(&format!(
+            "<details class=\"index-group\" open>\n<summary><h2>{} ({})</h2></summary>\n<ul>\n",
+            encode_text(label),
             entries.len()
         ));
-        for (id, title) in entries {
-            let lower = id.to_lowercase();
+        for (id, doc) in entries {
+            let h = health_of(id);
+            let mut extra = String::new();
+            if show_updated {
+                if let Some(ref updated) = h.updated {
+                    extra.push_str(&format!(" <span class=\"updated\">{}</span>", encode_text(&date(updated))));
+                }
+            }
+            if show_health {
+                let class = match h.score {
+                    0..50 => "low",
+                    50..80 => "fair",
+                    _ => "good",
+                };
+                extra.push_str(&format!(" <span class=\"health health-{class}\">{}</span>", h.score));
+            }
+            body.push_str(&item(id, doc, &extra));
+        }
+        body.push_str("</ul>\n</details>\n");
+    }
+
+    let lang = encode_attr(&locale.lang);
+    let heading = encode_text(&locale.label("Document Index"));
+    format!(
+        r#"<!DOCTYPE html>
+<html lang="{lang}">
+<head>
+<meta charset="utf-8">
+<meta name="viewport" content="width=device-width, initial-scale=1">
+<title>{heading}</title>
+<style>{CSS}</style>
+</head>
+<body>
+{nav}<h1>{heading}</h1>
+{body}
+</body>
+</html>
+"#
+    )
+}
+
+/// Export a schema reference page: one section per type with its fields and a
+/// generated example document writers can copy from.
+pub fn export_schema_page(schema: &Schema) -> String {
+    render_schema_page(schema, &Locale::default(), Links::flat())
+}
+
+fn render_schema_page(schema: &Schema, locale: &Locale, links: Links) -> String {
+    let th = |key: &str| format!("<th>{}</th>", encode_text(&locale.label(key)));
+    let mut body = String::new();
+
+    for type_def in &schema.types {
+        body.push_str(&format!(
+            "<h2 id=\"{}\">{}</h2>\n",
+            encode_attr(&type_def.name),
+            encode_text(&type_def.name.to_uppercase()),
+        ));
+        if let Some(ref desc) = type_def.description {
+            body.push_str(&format!("<p>{}</p>\n", encode_text(desc)));
+        }
+
+        if !type_def.fields.is_empty() {
+            body.push_str(&format!(
+                "<table>\n<thead><tr>{}{}{}{}</tr></thead>\n<tbody>\n",
+                th("Field"),
+                th("Type"),
+                th("Required"),
+                th("Description"),
+            ));
+            for f in &type_def.fields {
+                body.push_str(&format!(
+                    "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>\n",
+                    encode_text(&f.name),
+                    encode_text(&f.field_type.to_string()),
+                    if f.required { encode_text(&locale.label("yes")) } else { String::new() },
+                    encode_text(f.description.as_deref().unwrap_or("")),
+                ));
+            }
+            body.push_str("</tbody>\n</table>\n");
+        }
+
+        let example = crate::template::generate_example(type_def, schema, None);
+        body.push_str(&format!(
+            "<details><summary>{}</summary>\n<pre><code>",
+            encode_text(&locale.label("Example document"))
+        ));
+        body.push_str(&encode_text(&example));
+        body.push_str("</code></pre>\n</details>\n");
+    }
+
+    if !schema.relations.is_empty() {
+        body.push_str(&format!(
+            "<h2 id=\"relations\">{}</h2>\n",
+            encode_text(&locale.label("Relations"))
+        ));
+        let grouped = schema.has_relation_groups();
+        for group in schema.grouped_relations() {
+            if grouped {
+                let heading = group.name.map_or_else(|| locale.label("other"), str::to_string);
+                body.push_str(&format!("<h3>{}</h3>\n", encode_text(&heading)));
+                if let Some(desc) = group.description {
+                    body.push_str(&format!("<p>{}</p>\n", encode_text(desc)));
+                }
+            }
             body.push_str(&format!(
-                "<li><a href=\"{}\">{}</a> — {}</li>\n",
-                encode_attr(&format!("{lower}.html")),
-                encode_text(id),
-                encode_text(title),
+                "<table>\n<thead><tr>{}{}{}{}</tr></thead>\n<tbody>\n",
+                th("Relation"),
+                th("Inverse"),
+                th("Cardinality"),
+                th("Description"),
             ));
+            for r in &group.relations {
+                body.push_str(&format!(
+                    "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>\n",
+                    encode_text(&r.name),
+                    encode_text(r.inverse.as_deref().unwrap_or("")),
+                    encode_text(&locale.label(match r.cardinality {
+                        crate::schema::Cardinality::One => "one",
+                        crate::schema::Cardinality::Many => "many",
+                    })),
+                    encode_text(r.description.as_deref().unwrap_or("")),
+                ));
+            }
+            body.push_str("</tbody>\n</table>\n");
         }
-        body.push_str("</ul>\n");
     }
 
+    let lang = encode_attr(&locale.lang);
+    let heading = encode_text(&locale.label("Schema Reference"));
+    let nav = links.from_page("/schema.html").nav(locale);
     format!(
         r#"<!DOCTYPE html>
-<html lang="en">
+<html lang="{lang}">
 <head>
 <meta charset="utf-8">
 <meta name="viewport" content="width=device-width, initial-scale=1">
-<title>Document Index</title>
+<title>{heading}</title>
 <style>{CSS}</style>
 </head>
 <body>
-<h1>Document Index</h1>
+{nav}
+<h1>{heading}</h1>
 {body}
 </body>
 </html>
@@ -238,13 +733,63 @@ pub fn export_index(docs: &[(String, &Document)]) -> String {
     )
 }
 
-/// Export all documents in a directory to HTML files in output_dir.
-/// Returns the number of documents exported.
+/// Export all documents in a directory to HTML files in output_dir, in the schema's
+/// locale (English without one). Returns the number of documents exported.
 pub fn export_site(
     dir: impl AsRef<Path>,
     schema: Option<&Schema>,
     output_dir: impl AsRef<Path>,
 ) -> crate::error::Result<usize> {
+    let locale = schema.and_then(|s| s.locale.clone()).unwrap_or_default();
+    export_site_localized(dir, schema, output_dir, &locale)
+}
+
+/// [`export_site`] with an explicit locale for labels, dates, and numbers.
+pub fn export_site_localized(
+    dir: impl AsRef<Path>,
+    schema: Option<&Schema>,
+    output_dir: impl AsRef<Path>,
+    locale: &Locale,
+) -> crate::error::Result<usize> {
+    export_site_with(dir, schema, output_dir, locale, &SiteOptions::default()).map(|report| report.documents)
+}
+
+/// Extras for the `export --serve` preview, the raw HTML opt-out, where
+/// Mermaid is loaded from, and the theme.
+#[derive(Debug, Clone, Default)]
+pub struct SiteOptions {
+    /// Show each page's validation diagnostics in a panel over the page (needs a schema).
+    pub overlay: bool,
+    /// URL path the pages poll; they reload when its response changes.
+    pub live_reload: Option<String>,
+    /// Keep raw HTML in documents as written instead of sanitizing it.
+    pub unsafe_html: bool,
+    /// Mermaid module loaded by pages with diagrams; they show the diagram source when `None`.
+    pub mermaid_url: Option<String>,
+    /// Subresource Integrity hash of `mermaid_url` (`sha384-...`), checked by the browser.
+    pub mermaid_integrity: Option<String>,
+    /// Templates and stylesheet replacing the built-in document layout.
+    pub theme: Option<Theme>,
+}
+
+/// What [`export_site_with`] did.
+#[derive(Debug, Clone, Default)]
+pub struct SiteReport {
+    pub documents: usize,
+    /// Pages whose content changed. Unchanged pages are not rewritten.
+    pub written: Vec<PathBuf>,
+    /// Redirects added because a page moved, old URL first.
+    pub redirects: Vec<(String, String)>,
+}
+
+/// [`export_site_localized`] with preview options, reporting which pages changed.
+pub fn export_site_with(
+    dir: impl AsRef<Path>,
+    schema: Option<&Schema>,
+    output_dir: impl AsRef<Path>,
+    locale: &Locale,
+    options: &SiteOptions,
+) -> crate::error::Result<SiteReport> {
     let dir = dir.as_ref();
     let output_dir = output_dir.as_ref();
     std::fs::create_dir_all(output_dir)
@@ -265,37 +810,235 @@ pub fn export_site(
 
     let known_ids: Vec<String> = docs.iter().map(|(id, _)| id.clone()).collect();
 
-    // Build backlinks map if schema provided
+    // Page URLs from permalink templates, else the flat `adr-001.html` layout
+    let urls: BTreeMap<String, String> = docs
+        .iter()
+        .map(|(id, doc)| (id.clone(), permalink::permalink(id, doc, schema)))
+        .collect();
+    permalink::check_unique(&urls)?;
+    let doc_refs: Vec<(String, &Document)> = docs.iter().map(|(id, d)| (id.clone(), d)).collect();
+    let types = site::doc_types(&doc_refs, schema);
+    let site_links = Links {
+        urls: Some(&urls),
+        page: "/index.html",
+        types: &types,
+        schema: schema.is_some(),
+    };
+
+    // Build backlinks map if schema provided; the graph also feeds the graph page
     let mut backlinks_map: BTreeMap<String, Vec<(String, String)>> = BTreeMap::new();
+    let graph = schema.and_then(|schema| DocGraph::build(dir, schema).ok());
+    for edge in graph.iter().flat_map(|g| &g.edges) {
+        backlinks_map
+            .entry(edge.to.clone())
+            .or_default()
+            .push((edge.from.clone(), edge.relation.clone()));
+    }
+
+    // Validation counts per file feed the index's health scores
+    let rules = schema.and_then(|s| s.index.clone()).unwrap_or_default();
+    let mut counts: BTreeMap<String, (usize, usize)> = BTreeMap::new();
+    let mut diagnostics: BTreeMap<String, Vec<Diagnostic>> = BTreeMap::new();
     if let Some(schema) = schema {
-        if let Ok(graph) = DocGraph::build(dir, schema) {
-            for edge in &graph.edges {
-                backlinks_map
-                    .entry(edge.to.clone())
-                    .or_default()
-                    .push((edge.from.clone(), edge.relation.clone()));
+        if let Ok(result) = crate::validation::validate_directory(dir, schema, None, None) {
+            for fr in result.file_results {
+                counts.insert(fr.path.clone(), (fr.errors(), fr.warnings()));
+                if options.overlay {
+                    diagnostics.insert(fr.path, fr.diagnostics);
+                }
             }
         }
     }
+    let reload = options.live_reload.as_deref().map(live_reload_script).unwrap_or_default();
+    // Every page gets the theme's stylesheet and, when previewing, the reload script
+    let theme = options.theme.as_ref();
+    let finish = |html: &str, url: &str| {
+        let html = match theme.filter(|t| t.stylesheet) {
+            Some(_) => {
+                let stylesheet = format!("/{}/{}", crate::theme::OUTPUT_DIR, crate::theme::STYLESHEET);
+                let href = encode_attr(&permalink::relative_href(url, &stylesheet));
+                inject_before_head_end(html, &format!("<link rel=\"stylesheet\" href=\"{href}\">\n"))
+            }
+            None => html.to_string(),
+        };
+        inject_before_body_end(&html, &reload)
+    };
+    let mut report = SiteReport {
+        documents: docs.len(),
+        written: Vec::new(),
+        redirects: Vec::new(),
+    };
+    let today = crate::health::today();
+    let mut health: BTreeMap<String, DocHealth> = BTreeMap::new();
 
-    // Export each document
+    // Export each document, rendering md-db-query blocks against the full set
     for (id, doc) in &docs {
         let backlinks = backlinks_map.get(id).cloned().unwrap_or_default();
-        let html = export_html(doc, &known_ids, &backlinks);
-        let filename = format!("{}.html", id.to_lowercase());
-        let out_path = output_dir.join(&filename);
-        std::fs::write(&out_path, &html)
-            .map_err(|_| crate::error::Error::WriteFailed(out_path.clone()))?;
+        // Badges describe what the author wrote, not expanded query tables
+        let badges = crate::badges::compute(doc, schema);
+        let path = doc.path.as_ref().map(|p| p.display().to_string()).unwrap_or_default();
+        health.insert(
+            id.clone(),
+            crate::health::assess(
+                doc.frontmatter.as_ref(),
+                schema,
+                &rules,
+                counts.get(&path).copied().unwrap_or_default(),
+                badges.completeness.as_ref().map(|c| c.percent()),
+                today,
+            ),
+        );
+        let url = &urls[id];
+        let links = site_links.from_page(url);
+        let page = PageContext {
+            known_ids: &known_ids,
+            schema,
+            locale,
+            links,
+            unsafe_html: options.unsafe_html,
+            theme,
+        };
+        let html = if crate::query::find_query_blocks(&doc.body).is_empty() {
+            render_document(doc, &backlinks, &badges, page)
+        } else {
+            let mut expanded = doc.clone();
+            expanded.body = crate::query::expand_queries(&doc.body, id, &doc_refs, schema);
+            render_document(&expanded, &backlinks, &badges, page)
+        };
+        let html = site::render_mermaid(&html, options.mermaid_url.as_deref(), options.mermaid_integrity.as_deref());
+        let overlay = diagnostics.get(&path).map(|d| overlay_html(d)).unwrap_or_default();
+        let html = finish(&inject_before_body_end(&html, &overlay), url);
+        write_page(output_dir.join(permalink::output_file(url)), &html, &mut report.written)?;
+    }
+
+    // Export schema reference and graph pages
+    if let Some(schema) = schema {
+        let html = finish(&render_schema_page(schema, locale, site_links), "/schema.html");
+        write_page(output_dir.join("schema.html"), &html, &mut report.written)?;
+    }
+    if let Some(ref graph) = graph {
+        let html = finish(&site::render_graph_page(graph, locale, site_links), site::GRAPH_PAGE);
+        write_page(output_dir.join(permalink::output_file(site::GRAPH_PAGE)), &html, &mut report.written)?;
+    }
+
+    // Search page and index, and one listing page per type
+    let js = site::search_index_js(&doc_refs, site_links);
+    write_page(output_dir.join(permalink::output_file(site::SEARCH_INDEX)), &js, &mut report.written)?;
+    let html = finish(&site::render_search_page(locale, site_links), site::SEARCH_PAGE);
+    write_page(output_dir.join(permalink::output_file(site::SEARCH_PAGE)), &html, &mut report.written)?;
+    for doc_type in &types {
+        let url = site::type_url(doc_type);
+        let html = finish(&site::render_type_page(doc_type, &doc_refs, schema, locale, site_links), &url);
+        write_page(output_dir.join(permalink::output_file(&url)), &html, &mut report.written)?;
     }
 
     // Export index
-    let doc_refs: Vec<(String, &Document)> = docs.iter().map(|(id, d)| (id.clone(), d)).collect();
-    let index_html = export_index(&doc_refs);
-    let index_path = output_dir.join("index.html");
-    std::fs::write(&index_path, &index_html)
-        .map_err(|_| crate::error::Error::WriteFailed(index_path))?;
+    let nav = format!("{}\n", site_links.nav(locale));
+    let index_html = render_index(&doc_refs, schema, &nav, locale, &rules, &health, site_links);
+    let index_html = finish(&index_html, "/index.html");
+    write_page(output_dir.join("index.html"), &index_html, &mut report.written)?;
 
-    Ok(docs.len())
+    // The theme's stylesheet, images, and fonts
+    if let Some(theme) = theme {
+        for file in theme.static_files() {
+            let bytes = std::fs::read(theme.dir.join(&file))?;
+            write_page(output_dir.join(crate::theme::OUTPUT_DIR).join(&file), &bytes, &mut report.written)?;
+        }
+    }
+
+    // Pages that moved since the last export leave a redirect at their old URL
+    let mut manifest = Manifest::load(output_dir);
+    let published = docs
+        .iter()
+        .map(|(id, doc)| (permalink::manifest_key(id, doc), urls[id].clone()))
+        .collect();
+    report.redirects = manifest.update(published);
+    for (old, new) in &manifest.redirects {
+        let html = permalink::redirect_page(&permalink::relative_href(old, new));
+        write_page(output_dir.join(permalink::output_file(old)), &html, &mut report.written)?;
+    }
+    manifest.save(output_dir)?;
+
+    Ok(report)
+}
+
+/// Write a page unless the file already holds exactly `contents`.
+fn write_page(path: PathBuf, contents: impl AsRef<[u8]>, written: &mut Vec<PathBuf>) -> crate::error::Result<()> {
+    let contents = contents.as_ref();
+    if std::fs::read(&path).is_ok_and(|old| old == contents) {
+        return Ok(());
+    }
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|_| crate::error::Error::WriteFailed(parent.to_path_buf()))?;
+    }
+    crate::durability::write(&path, contents)?;
+    written.push(path);
+    Ok(())
+}
+
+fn inject_before_body_end(html: &str, extra: &str) -> String {
+    match html.rfind("</body>") {
+        Some(at) if !extra.is_empty() => format!("{}{extra}{}", &html[..at], &html[at..]),
+        _ => html.to_string(),
+    }
+}
+
+fn inject_before_head_end(html: &str, extra: &str) -> String {
+    match html.find("</head>") {
+        Some(at) => format!("{}{extra}{}", &html[..at], &html[at..]),
+        None => html.to_string(),
+    }
+}
+
+const OVERLAY_CSS: &str = ".md-db-overlay { position: fixed; right: 1rem; bottom: 1rem; max-width: 32rem; \
+max-height: 50vh; overflow: auto; background: #fff; border: 1px solid #d0d7de; border-left: 4px solid #dc2626; \
+border-radius: 6px; box-shadow: 0 4px 12px rgba(0,0,0,.15); font-size: .85rem; padding: .5rem .75rem; z-index: 1000; }
+.md-db-overlay.md-db-warnings-only { border-left-color: #d97706; }
+.md-db-overlay summary { font-weight: 600; cursor: pointer; }
+.md-db-overlay ul { padding-left: 1rem; margin: .5rem 0 0; }
+.md-db-overlay .md-db-error code { color: #dc2626; }
+.md-db-overlay .md-db-warning code { color: #d97706; }
+.md-db-overlay .md-db-where, .md-db-overlay .md-db-hint { display: block; color: #57606a; }";
+
+/// Panel listing a page's validation diagnostics; empty when there are none.
+fn overlay_html(diags: &[Diagnostic]) -> String {
+    if diags.is_empty() {
+        return String::new();
+    }
+    let errors = diags.iter().filter(|d| d.severity == Severity::Error).count();
+    let warnings = diags.len() - errors;
+    let class = if errors == 0 { " md-db-warnings-only" } else { "" };
+    let mut out = format!(
+        "<style>{OVERLAY_CSS}</style>\n<aside class=\"md-db-overlay{class}\"><details open>\
+         <summary>{errors} error(s), {warnings} warning(s)</summary><ul>\n"
+    );
+    for d in diags {
+        let hint = d
+            .hint
+            .as_ref()
+            .map(|h| format!("<span class=\"md-db-hint\">hint: {}</span>", encode_text(h)))
+            .unwrap_or_default();
+        out.push_str(&format!(
+            "<li class=\"md-db-{}\"><code>{}</code> {}<span class=\"md-db-where\">{}</span>{hint}</li>\n",
+            d.severity,
+            encode_text(&d.code),
+            encode_text(&d.message),
+            encode_text(&d.location),
+        ));
+    }
+    out.push_str("</ul></details></aside>\n");
+    out
+}
+
+/// Script that polls `url` and reloads the page when the response changes.
+fn live_reload_script(url: &str) -> String {
+    let url = serde_json::to_string(url).unwrap_or_default().replace("</", "<\\/");
+    format!(
+        "<script>(function () {{ var seen = null; setInterval(function () {{ \
+         fetch({url}, {{ cache: \"no-store\" }}).then(function (r) {{ return r.text(); }}).then(function (v) {{ \
+         if (seen !== null && v !== seen) {{ location.reload(); }} seen = v; }}).catch(function () {{}}); }}, 1000); \
+         }})();</script>\n"
+    )
 }
 
 #[cfg(test)]
@@ -305,7 +1048,7 @@ mod tests {
     #[test]
     fn test_render_markdown_to_html() {
         let md = "# Hello\n\nWorld **bold**.\n";
-        let html = render_markdown_to_html(md);
+        let html = render_markdown_to_html(md, false);
         assert!(html.contains("<h1>"));
         assert!(html.contains("<strong>bold</strong>"));
     }
@@ -313,7 +1056,7 @@ mod tests {
     #[test]
     fn test_frontmatter_table() {
         let doc = Document::from_str("---\ntitle: Test\nstatus: accepted\n---\n\nBody\n").unwrap();
-        let html = frontmatter_table(&doc);
+        let html = frontmatter_table(&doc, &Locale::default());
         assert!(html.contains("title"));
         assert!(html.contains("Test"));
         assert!(html.contains("accepted"));
@@ -323,7 +1066,7 @@ mod tests {
     fn test_linkify_refs() {
         let html = "<p>See ADR-001 and OPP-002 for details.</p>";
         let ids = vec!["ADR-001".to_string(), "OPP-002".to_string()];
-        let result = linkify_refs(html, &ids);
+        let result = linkify_refs(html, &ids, Links::flat());
         assert!(result.contains("<a href=\"adr-001.html\">ADR-001</a>"));
         assert!(result.contains("<a href=\"opp-002.html\">OPP-002</a>"));
     }
@@ -367,14 +1110,56 @@ mod tests {
         assert!(!html.contains("<script>"), "raw <script> must be escaped in backlinks");
     }
 
+    #[test]
+    fn test_backlinks_grouped_by_relation_group() {
+        let schema = Schema::from_str(
+            r#"
+relation-group "lifecycle"
+relation-group "causality"
+relation "enables" cardinality="many" group="causality"
+relation "supersedes" cardinality="one" group="lifecycle"
+relation "related_to" cardinality="many"
+"#,
+        )
+        .unwrap();
+        let backlinks = vec![
+            ("OPP-001".to_string(), "enables".to_string()),
+            ("ADR-002".to_string(), "related_to".to_string()),
+            ("ADR-003".to_string(), "supersedes".to_string()),
+        ];
+        let html = backlinks_html(&backlinks, Some(&schema), &Locale::default(), Links::flat());
+        let pos = |s: &str| html.find(s).unwrap();
+        assert!(pos("<h3>lifecycle</h3>") < pos("ADR-003"));
+        assert!(pos("ADR-003") < pos("<h3>causality</h3>"));
+        assert!(pos("OPP-001") < pos("<h3>other</h3>"));
+        assert!(pos("<h3>other</h3>") < pos("ADR-002"));
+
+        let flat = backlinks_html(&backlinks, None, &Locale::default(), Links::flat());
+        assert!(!flat.contains("<h3>"));
+        assert_eq!(flat.matches("<ul>").count(), 1);
+    }
+
     #[test]
     fn test_raw_html_stripped_from_markdown() {
         let md = "# Hello\n\n<script>alert('xss')</script>\n\nSafe text.\n";
-        let html = render_markdown_to_html(md);
+        let html = render_markdown_to_html(md, false);
         assert!(!html.contains("<script>"), "raw HTML should be stripped");
         assert!(html.contains("Safe text."));
     }
 
+    #[test]
+    fn test_raw_html_sanitized_or_kept() {
+        let md = "<details><summary>More</summary>\n\nHidden <b onmouseover=\"x()\">text</b>\n\n</details>\n\n\
+                  [click](javascript:alert(1))\n";
+        let html = render_markdown_to_html(md, false);
+        assert!(html.contains("<details><summary>More</summary>"), "{html}");
+        assert!(html.contains("<b>text</b>") && !html.contains("onmouseover"), "{html}");
+        assert!(html.contains("<a>click</a>"), "{html}");
+
+        let raw = render_markdown_to_html(md, true);
+        assert!(raw.contains("onmouseover"), "{raw}");
+    }
+
     #[test]
     fn test_export_index() {
         let doc1 =
@@ -392,6 +1177,68 @@ mod tests {
         assert!(html.contains("2 documents"));
     }
 
+    #[test]
+    fn test_index_follows_order_by() {
+        let schema = Schema::from_str(
+            "type \"adr\" {\n    field \"date\" type=\"string\"\n    order-by \"date\" desc=#true\n}\n",
+        )
+        .unwrap();
+        let old = Document::from_str("---\ntitle: Old\ntype: adr\ndate: 2023-01-01\n---\n").unwrap();
+        let new = Document::from_str("---\ntitle: New\ntype: adr\ndate: 2025-01-01\n---\n").unwrap();
+        let docs = vec![("ADR-001".to_string(), &old), ("ADR-002".to_string(), &new)];
+
+        let rules = IndexRules::default();
+        let html = render_index(&docs, Some(&schema), "", &Locale::default(), &rules, &BTreeMap::new(), Links::flat());
+        assert!(html.find("ADR-002").unwrap() < html.find("ADR-001").unwrap());
+        let html = export_index(&docs);
+        assert!(html.find("ADR-001").unwrap() < html.find("ADR-002").unwrap());
+    }
+
+    #[test]
+    fn test_index_groups_and_attention() {
+        let broken = Document::from_str("---\ntitle: Broken\ntype: adr\nstatus: proposed\n---\n").unwrap();
+        let fine = Document::from_str("---\ntitle: Fine\ntype: adr\nstatus: accepted\n---\n").unwrap();
+        let docs = vec![("ADR-001".to_string(), &broken), ("ADR-002".to_string(), &fine)];
+        let rules = IndexRules {
+            group_by: IndexKey::Status,
+            order_by: Some(IndexKey::Health),
+            ..IndexRules::default()
+        };
+        let mut health = BTreeMap::new();
+        let overdue = DocHealth {
+            errors: 2,
+            overdue: Some("2024-01-31".into()),
+            score: 15,
+            ..DocHealth::default()
+        };
+        health.insert("ADR-001".to_string(), overdue);
+
+        let html = render_index(&docs, None, "", &Locale::default(), &rules, &health, Links::flat());
+        let pos = |s: &str| html.find(s).unwrap();
+        assert!(pos("<section class=\"attention\">") < pos("<details"));
+        assert!(html.contains("<span class=\"reasons\">2 errors, review overdue since 2024-01-31</span>"));
+        assert!(pos("<summary><h2>accepted (1)</h2></summary>") < pos("<summary><h2>proposed (1)</h2></summary>"));
+        assert!(html.contains("<span class=\"health health-low\">15</span>"));
+        assert!(html.contains("<span class=\"health health-good\">100</span>"));
+
+        let plain = export_index(&docs);
+        assert!(!plain.contains("<section class=\"attention\">"));
+        assert!(plain.contains("<summary><h2>ADR (2)</h2></summary>"));
+    }
+
+    #[test]
+    fn test_export_schema_page() {
+        let schema = Schema::from_str(
+            "type \"adr\" description=\"Decision\" {\n    field \"title\" type=\"string\" required=#true\n    section \"Decision\"\n}\n",
+        )
+        .unwrap();
+        let html = export_schema_page(&schema);
+        assert!(html.contains("Schema Reference"));
+        assert!(html.contains("<h2 id=\"adr\">ADR</h2>"));
+        assert!(html.contains("Example document"));
+        assert!(html.contains("# Decision"));
+    }
+
     #[test]
     fn test_export_site() {
         let dir = tempfile::tempdir().unwrap();
@@ -410,4 +1257,254 @@ mod tests {
         assert!(output.join("index.html").exists());
         assert!(output.join("adr-001.html").exists());
     }
+
+    #[test]
+    fn test_export_site_search_graph_and_types() {
+        let dir = tempfile::tempdir().unwrap();
+        let input = dir.path().join("input");
+        let output = dir.path().join("output");
+        std::fs::create_dir_all(&input).unwrap();
+        std::fs::write(input.join("adr-001.md"), "---\ntitle: Use Kafka\ntype: adr\n---\n\nQueues.\n").unwrap();
+        std::fs::write(
+            input.join("adr-002.md"),
+            "---\ntitle: Use NATS\ntype: adr\nsupersedes: ADR-001\n---\n\n```mermaid\ngraph LR\n  A --> B\n```\n",
+        )
+        .unwrap();
+        std::fs::write(input.join("inc-001.md"), "---\ntitle: </script>\ntype: inc\n---\n").unwrap();
+        let schema = Schema::from_str("type \"adr\"\ntype \"inc\"\nrelation \"supersedes\" cardinality=\"one\"\n").unwrap();
+
+        export_site(&input, Some(&schema), &output).unwrap();
+        let read = |name: &str| std::fs::read_to_string(output.join(name)).unwrap();
+        let adr = read("adr-002.html");
+        assert!(adr.contains("<a href=\"search.html\">Search</a> · <a href=\"graph.html\">Graph</a>"), "{adr}");
+        assert!(adr.contains("<span class=\"nav-types\"><a href=\"types/adr.html\">ADR</a> "), "{adr}");
+        assert!(adr.contains("<pre class=\"mermaid\">graph LR\n  A --&gt; B\n</pre>"), "{adr}");
+        assert!(!adr.contains("import mermaid"), "{adr}");
+        assert!(read("adr-001.html").contains("<div class=\"backlinks\">"));
+
+        assert!(read("search-index.js").contains("\"url\":\"adr-001.html\",\"text\":\"Queues.\""));
+        assert!(read("search.html").contains("<script src=\"search-index.js\"></script>"));
+        let graph = read("graph.html");
+        assert!(graph.contains("{\"from\":\"ADR-002\",\"to\":\"ADR-001\",\"relation\":\"supersedes\"}"), "{graph}");
+        assert!(graph.contains("\"title\":\"\\u003c/script>\""), "{graph}");
+        let types = read("types/inc.html");
+        assert!(types.contains("<a href=\"../inc-001.html\">INC-001</a> — &lt;/script&gt;"), "{types}");
+        assert!(types.contains("<a href=\"../index.html\">Index</a>"));
+
+        // Without a schema there is no graph page to link to
+        let plain = dir.path().join("plain");
+        export_site(&input, None, &plain).unwrap();
+        assert!(!plain.join("graph.html").exists());
+        let index = std::fs::read_to_string(plain.join("index.html")).unwrap();
+        assert!(index.contains("<a href=\"search.html\">Search</a> | ") && !index.contains("Graph"), "{index}");
+    }
+
+    #[test]
+    fn test_export_site_badges() {
+        let dir = tempfile::tempdir().unwrap();
+        let input = dir.path().join("input");
+        let output = dir.path().join("output");
+        std::fs::create_dir_all(&input).unwrap();
+        std::fs::write(
+            input.join("adr-001.md"),
+            "---\ntitle: Test ADR\ntype: adr\n---\n\n# Context\n\nWhy.\n\n# Decision\n\nTBD\n",
+        )
+        .unwrap();
+        let schema = Schema::from_str(
+            "type \"adr\" {\n    section \"Context\" required=#true\n    section \"Decision\" required=#true\n}\n",
+        )
+        .unwrap();
+
+        export_site(&input, Some(&schema), &output).unwrap();
+        let html = std::fs::read_to_string(output.join("adr-001.html")).unwrap();
+        assert!(html.contains("<span class=\"badge\">1 min read</span>"));
+        assert!(html.contains("<span class=\"badge badge-incomplete\" title=\"To fill: Decision\">1/2 sections</span>"));
+    }
+
+    #[test]
+    fn test_export_site_overlay_and_reload() {
+        let dir = tempfile::tempdir().unwrap();
+        let input = dir.path().join("input");
+        let output = dir.path().join("output");
+        std::fs::create_dir_all(&input).unwrap();
+        std::fs::create_dir_all(&output).unwrap();
+        std::fs::write(input.join("adr-001.md"), "---\ntitle: <Test>\ntype: adr\n---\n\n# Context\n\nWhy.\n").unwrap();
+        std::fs::write(input.join("adr-002.md"), "---\ntitle: Fine\ntype: adr\nstatus: accepted\n---\n").unwrap();
+        let schema = Schema::from_str(
+            "type \"adr\" {\n    field \"status\" type=\"string\" required=#true\n    section \"Context\"\n}\n",
+        )
+        .unwrap();
+        let options = SiteOptions {
+            overlay: true,
+            live_reload: Some("/__md-db/version".into()),
+            ..Default::default()
+        };
+
+        let report = export_site_with(&input, Some(&schema), &output, &Locale::default(), &options).unwrap();
+        // Two documents, schema, graph, search page and index, the type page, and the index
+        assert_eq!((report.documents, report.written.len()), (2, 8));
+        let html = std::fs::read_to_string(output.join("adr-001.html")).unwrap();
+        assert!(html.contains("<aside class=\"md-db-overlay\">"), "{html}");
+        assert!(html.contains("1 error(s), 0 warning(s)"), "{html}");
+        assert!(html.contains("fetch(\"/__md-db/version\""));
+        assert!(html.trim_end().ends_with("</body>\n</html>"));
+        let clean = std::fs::read_to_string(output.join("adr-002.html")).unwrap();
+        assert!(!clean.contains("md-db-overlay") && clean.contains("fetch("));
+
+        // Only pages whose output changed are rewritten
+        let again = export_site_with(&input, Some(&schema), &output, &Locale::default(), &options).unwrap();
+        assert!(again.written.is_empty(), "{:?}", again.written);
+        std::fs::write(input.join("adr-001.md"), "---\ntitle: Test\ntype: adr\nstatus: accepted\n---\n").unwrap();
+        let fixed = export_site_with(&input, Some(&schema), &output, &Locale::default(), &options).unwrap();
+        assert!(fixed.written.contains(&output.join("adr-001.html")));
+        assert!(!fixed.written.contains(&output.join("adr-002.html")));
+        let html = std::fs::read_to_string(output.join("adr-001.html")).unwrap();
+        assert!(!html.contains("md-db-overlay"));
+
+        // Without options the pages carry neither
+        export_site(&input, Some(&schema), &output).unwrap();
+        assert!(!std::fs::read_to_string(output.join("adr-002.html")).unwrap().contains("<script>"));
+    }
+
+    #[test]
+    fn test_export_site_permalinks() {
+        let dir = tempfile::tempdir().unwrap();
+        let input = dir.path().join("input");
+        let output = dir.path().join("output");
+        std::fs::create_dir_all(&input).unwrap();
+        std::fs::write(
+            input.join("adr-001.md"),
+            "---\ntitle: Use Kafka\ntype: adr\ndate: 2024-03-05\n---\n\nSee [the incident](inc-001.md#impact).\n",
+        )
+        .unwrap();
+        std::fs::write(input.join("inc-001.md"), "---\ntitle: Outage\ntype: inc\n---\n\nFixed by ADR-001.\n").unwrap();
+        let flat = Schema::from_str("type \"adr\"\ntype \"inc\"\n").unwrap();
+        let nested =
+            Schema::from_str("type \"adr\" {\n    permalink \"/decisions/{year}/{slug}/\"\n}\ntype \"inc\"\n").unwrap();
+
+        let report = export_site(&input, Some(&flat), &output).unwrap();
+        assert!(report.redirects.is_empty());
+        let inc = std::fs::read_to_string(output.join("inc-001.html")).unwrap();
+        assert!(inc.contains("<a href=\"adr-001.html\">ADR-001</a>"), "{inc}");
+
+        // Moving the ADRs leaves a redirect at the old URL, and links follow the move
+        let report = export_site(&input, Some(&nested), &output).unwrap();
+        let moved = ("/adr-001.html".to_string(), "/decisions/2024/use-kafka/".to_string());
+        assert_eq!(report.redirects, vec![moved]);
+        let adr = std::fs::read_to_string(output.join("decisions/2024/use-kafka/index.html")).unwrap();
+        assert!(adr.contains("href=\"../../../inc-001.html#impact\""), "{adr}");
+        assert!(adr.contains("href=\"../../../index.html\""), "{adr}");
+        let inc = std::fs::read_to_string(output.join("inc-001.html")).unwrap();
+        assert!(inc.contains("<a href=\"decisions/2024/use-kafka/\">ADR-001</a>"), "{inc}");
+        let stub = std::fs::read_to_string(output.join("adr-001.html")).unwrap();
+        assert!(stub.contains("url=decisions/2024/use-kafka/"), "{stub}");
+        let manifest = Manifest::load(&output);
+        assert_eq!(manifest.documents["ADR-001"], "/decisions/2024/use-kafka/");
+
+        // The redirect outlives the export that created it
+        assert!(export_site(&input, Some(&nested), &output).unwrap().redirects.is_empty());
+        assert!(std::fs::read_to_string(output.join("adr-001.html")).unwrap().contains("url=decisions/"));
+
+        // A permalink may not take the place of the index
+        let clash = "---\ntitle: Outage\ntype: inc\npermalink: /index.html\n---\n";
+        std::fs::write(input.join("inc-001.md"), clash).unwrap();
+        assert!(export_site(&input, Some(&nested), &output).is_err());
+    }
+
+    #[test]
+    fn test_export_site_theme() {
+        let dir = tempfile::tempdir().unwrap();
+        let input = dir.path().join("input");
+        let theme_dir = dir.path().join("theme");
+        let output = dir.path().join("output");
+        std::fs::create_dir_all(&input).unwrap();
+        std::fs::create_dir_all(theme_dir.join("img")).unwrap();
+        std::fs::write(
+            input.join("adr-001.md"),
+            "---\ntitle: Use Kafka\ntype: adr\nowner: ana\n---\n\n# Context\n\nSee INC-001.\n\n## Load\n\nHigh.\n",
+        )
+        .unwrap();
+        std::fs::write(input.join("inc-001.md"), "---\ntitle: Outage\ntype: inc\ncaused_by: ADR-001\n---\n").unwrap();
+        std::fs::write(
+            theme_dir.join("adr.html"),
+            "<html><head></head><body><h1>{{id}}: {{title}}</h1><p>{{frontmatter.owner}}</p>{{{section.Context}}}\
+             {{#each sections}}[{{level}} {{heading}}]{{/each}}\
+             {{#each backlinks}}<a href=\"{{href}}\">{{id}} {{relation}}</a>{{/each}}</body></html>",
+        )
+        .unwrap();
+        std::fs::write(theme_dir.join("style.css"), "h1 { color: teal; }").unwrap();
+        std::fs::write(theme_dir.join("img/logo.svg"), "<svg/>").unwrap();
+        let schema = Schema::from_str("type \"adr\"\ntype \"inc\"\nrelation \"caused_by\" cardinality=\"one\"\n").unwrap();
+        let options = SiteOptions {
+            theme: Some(Theme::load(&theme_dir).unwrap()),
+            ..Default::default()
+        };
+
+        export_site_with(&input, Some(&schema), &output, &Locale::default(), &options).unwrap();
+        let adr = std::fs::read_to_string(output.join("adr-001.html")).unwrap();
+        assert!(adr.starts_with("<html><head><link rel=\"stylesheet\" href=\"theme/style.css\">\n</head>"), "{adr}");
+        assert!(adr.contains("<h1>ADR-001: Use Kafka</h1><p>ana</p>"), "{adr}");
+        assert!(adr.contains("See <a href=\"inc-001.html\">INC-001</a>.</p>\n<h2>Load</h2>"), "{adr}");
+        assert!(adr.contains("[1 Context][2 Load]<a href=\"inc-001.html\">INC-001 caused_by</a>"), "{adr}");
+        // Types without a template keep the built-in layout, with the theme's stylesheet
+        let inc = std::fs::read_to_string(output.join("inc-001.html")).unwrap();
+        assert!(inc.contains("<h1>INC-001</h1>") && inc.contains("href=\"theme/style.css\""), "{inc}");
+        let index = std::fs::read_to_string(output.join("index.html")).unwrap();
+        assert!(index.contains("<link rel=\"stylesheet\" href=\"theme/style.css\">"));
+        assert_eq!(std::fs::read_to_string(output.join("theme/img/logo.svg")).unwrap(), "<svg/>");
+        assert!(output.join("theme/style.css").exists() && !output.join("theme/adr.html").exists());
+    }
+
+    #[test]
+    fn test_export_site_localized() {
+        let dir = tempfile::tempdir().unwrap();
+        let input = dir.path().join("input");
+        let output = dir.path().join("output");
+        std::fs::create_dir_all(&input).unwrap();
+        std::fs::write(
+            input.join("pol-001.md"),
+            "---\ntitle: Etätyö\ntype: pol\ndate: 2024-03-05\nbudget: 12500.5\n---\n\nTeksti.\n",
+        )
+        .unwrap();
+        let schema = Schema::from_str("locale \"fi\" {\n    label \"Value\" \"Sisältö\"\n}\ntype \"pol\" {}\n").unwrap();
+
+        export_site(&input, Some(&schema), &output).unwrap();
+        let html = std::fs::read_to_string(output.join("pol-001.html")).unwrap();
+        assert!(html.contains("<html lang=\"fi\">"));
+        assert!(html.contains("<th>Kenttä</th><th>Sisältö</th>"));
+        assert!(html.contains("<td>5.3.2024</td>"));
+        assert!(html.contains("<td>12\u{a0}500,5</td>"));
+        let index = std::fs::read_to_string(output.join("index.html")).unwrap();
+        assert!(index.contains("<p>1 dokumenttia</p>"));
+
+        export_site_localized(&input, Some(&schema), &output, &Locale::default()).unwrap();
+        let html = std::fs::read_to_string(output.join("pol-001.html")).unwrap();
+        assert!(html.contains("<td>2024-03-05</td>"));
+    }
+
+    #[test]
+    fn test_export_site_renders_queries() {
+        let dir = tempfile::tempdir().unwrap();
+        let input = dir.path().join("input");
+        let output = dir.path().join("output");
+        std::fs::create_dir_all(&input).unwrap();
+
+        std::fs::write(
+            input.join("adr-001.md"),
+            "---\ntitle: Test ADR\ntype: adr\n---\n\n# Incidents\n\n```md-db-query\ntype: inc\nlinks: caused_by\ncolumns: id, title\n```\n",
+        )
+        .unwrap();
+        std::fs::write(
+            input.join("inc-001.md"),
+            "---\ntitle: Outage\ntype: inc\ncaused_by: [ADR-001]\n---\n",
+        )
+        .unwrap();
+
+        export_site(&input, None, &output).unwrap();
+        let html = std::fs::read_to_string(output.join("adr-001.html")).unwrap();
+        assert!(html.contains("<table>"));
+        assert!(html.contains("href=\"inc-001.html\""));
+        assert!(html.contains("Outage"));
+        assert!(!html.contains("md-db-query"));
+    }
 }
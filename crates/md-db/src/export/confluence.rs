@@ -0,0 +1,499 @@
+//! Publish documents as Confluence pages (`md-db export confluence`).
+//!
+//! Each document becomes one page under a parent page, titled `ID Title`, with
+//! its metadata table and body in Confluence's storage format. Links to other
+//! documents, in the body and in relation fields, become Confluence page links.
+//! `<root>/.md-db/confluence.json` maps document IDs to page IDs, so later runs
+//! update the same pages, and only those whose content changed.
+
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use super::{encode_attr, encode_text, render_markdown_to_html};
+use crate::assets::sha256_hex;
+use crate::curl;
+use crate::document::Document;
+use crate::error::{Error, Result};
+use crate::graph::path_to_id;
+use crate::graph_cache::CACHE_DIR;
+use crate::locale::Locale;
+
+/// State file (inside `CACHE_DIR`) mapping document IDs to pages.
+const STATE_FILE: &str = "confluence.json";
+
+/// One document rendered as a Confluence page.
+#[derive(Debug, Clone)]
+pub struct ConfluencePage {
+    pub doc_id: String,
+    pub title: String,
+    /// Storage-format XHTML.
+    pub body: String,
+}
+
+impl ConfluencePage {
+    /// Digest of title and body, used to skip unchanged pages.
+    pub fn digest(&self) -> String {
+        sha256_hex(format!("{}\n{}", self.title, self.body).as_bytes())
+    }
+}
+
+/// A page recorded in the Confluence state.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PageRecord {
+    pub page_id: String,
+    pub space: String,
+    pub title: String,
+    pub url: String,
+    /// Digest of the last title and body pushed to the page.
+    pub digest: String,
+}
+
+/// A page as returned by Confluence.
+#[derive(Debug, Clone)]
+pub struct RemotePage {
+    pub id: String,
+    pub url: String,
+}
+
+/// Backend that creates and updates pages in one space.
+pub trait ConfluenceApi {
+    fn space(&self) -> &str;
+    fn create(&mut self, title: &str, body: &str) -> Result<RemotePage>;
+    fn update(&mut self, page_id: &str, title: &str, body: &str) -> Result<RemotePage>;
+}
+
+/// Pages published from a docs directory.
+#[derive(Debug)]
+pub struct ConfluenceState {
+    root: PathBuf,
+    pages: BTreeMap<String, PageRecord>,
+}
+
+impl ConfluenceState {
+    /// Load `<root>/.md-db/confluence.json`, or start empty.
+    pub fn open(root: impl AsRef<Path>) -> Result<Self> {
+        let root = root.as_ref().to_path_buf();
+        let path = root.join(CACHE_DIR).join(STATE_FILE);
+        let pages = if path.exists() {
+            serde_json::from_str(&std::fs::read_to_string(&path)?)?
+        } else {
+            BTreeMap::new()
+        };
+        Ok(Self { root, pages })
+    }
+
+    pub fn pages(&self) -> &BTreeMap<String, PageRecord> {
+        &self.pages
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let dir = self.root.join(CACHE_DIR);
+        std::fs::create_dir_all(&dir).map_err(|_| Error::WriteFailed(dir.clone()))?;
+        let path = dir.join(STATE_FILE);
+        let data = serde_json::to_string_pretty(&self.pages)?;
+        crate::durability::write(&path, data)?;
+        Ok(())
+    }
+}
+
+/// What a publish run does for one document.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PageAction {
+    Create,
+    Update,
+    Unchanged,
+}
+
+impl std::fmt::Display for PageAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PageAction::Create => write!(f, "create"),
+            PageAction::Update => write!(f, "update"),
+            PageAction::Unchanged => write!(f, "unchanged"),
+        }
+    }
+}
+
+/// One planned (or applied) page of a publish run.
+#[derive(Debug, Clone)]
+pub struct PagePlan {
+    pub action: PageAction,
+    pub page: ConfluencePage,
+    /// Existing page, or the new one once applied.
+    pub record: Option<PageRecord>,
+}
+
+/// Page title of a document: `ID Title`, unique within the space.
+pub fn page_title(id: &str, doc: &Document) -> String {
+    match doc.frontmatter.as_ref().and_then(|fm| fm.get_display("title")) {
+        Some(title) => format!("{id} {title}"),
+        None => id.to_string(),
+    }
+}
+
+/// Render `docs` (ID, document) as pages of `space`. Links to these documents
+/// and to pages already in `state` become page links; others stay as written.
+pub fn render_pages(
+    docs: &[(String, Document)],
+    space: &str,
+    state: &ConfluenceState,
+    locale: &Locale,
+) -> Vec<ConfluencePage> {
+    let mut titles: BTreeMap<String, String> = state
+        .pages
+        .iter()
+        .filter(|(_, record)| record.space == space)
+        .map(|(id, record)| (id.clone(), record.title.clone()))
+        .collect();
+    titles.extend(docs.iter().map(|(id, doc)| (id.clone(), page_title(id, doc))));
+    let links = PageLinks { titles: &titles, space };
+
+    docs.iter()
+        .map(|(id, doc)| {
+            let mut body = metadata_table(doc, locale, &links);
+            body.push_str(&storage_body(&render_markdown_to_html(&doc.body, false), &links));
+            ConfluencePage {
+                doc_id: id.clone(),
+                title: titles[id].clone(),
+                body,
+            }
+        })
+        .collect()
+}
+
+/// Compare rendered pages with the recorded ones. A page recorded for another
+/// space is created anew in this one.
+pub fn plan(pages: Vec<ConfluencePage>, space: &str, state: &ConfluenceState) -> Vec<PagePlan> {
+    pages
+        .into_iter()
+        .map(|page| {
+            let record = state.pages.get(&page.doc_id).filter(|r| r.space == space).cloned();
+            let action = match record {
+                None => PageAction::Create,
+                Some(ref r) if r.digest == page.digest() => PageAction::Unchanged,
+                Some(_) => PageAction::Update,
+            };
+            PagePlan { action, page, record }
+        })
+        .collect()
+}
+
+/// Execute a plan and record the pages in `state`. The state is updated page
+/// by page, so a failure part way keeps what was already published.
+pub fn apply(items: &mut [PagePlan], api: &mut dyn ConfluenceApi, state: &mut ConfluenceState) -> Result<()> {
+    for item in items.iter_mut() {
+        let remote = match (item.action, &item.record) {
+            (PageAction::Create, _) => api.create(&item.page.title, &item.page.body)?,
+            (PageAction::Update, Some(record)) => api.update(&record.page_id, &item.page.title, &item.page.body)?,
+            _ => continue,
+        };
+        let record = PageRecord {
+            page_id: remote.id,
+            space: api.space().to_string(),
+            title: item.page.title.clone(),
+            url: remote.url,
+            digest: item.page.digest(),
+        };
+        state.pages.insert(item.page.doc_id.clone(), record.clone());
+        item.record = Some(record);
+    }
+    Ok(())
+}
+
+/// Page titles of linkable documents, by ID.
+struct PageLinks<'a> {
+    titles: &'a BTreeMap<String, String>,
+    space: &'a str,
+}
+
+impl PageLinks<'_> {
+    /// An `ac:link` to the page of `id` with `body` (storage-format XHTML) as its text.
+    fn link(&self, id: &str, body: &str) -> Option<String> {
+        let title = self.titles.get(id)?;
+        Some(format!(
+            "<ac:link><ri:page ri:space-key=\"{}\" ri:content-title=\"{}\" />\
+             <ac:link-body>{body}</ac:link-body></ac:link>",
+            encode_attr(self.space),
+            encode_attr(title)
+        ))
+    }
+}
+
+/// The frontmatter as a table, with document IDs in values linked to their pages.
+fn metadata_table(doc: &Document, locale: &Locale, links: &PageLinks) -> String {
+    let Some(ref fm) = doc.frontmatter else {
+        return String::new();
+    };
+    let ids = Regex::new(r"[A-Za-z][A-Za-z0-9_]*-\d+").unwrap();
+    let mut html = format!(
+        "<table><tbody>\n<tr><th>{}</th><th>{}</th></tr>\n",
+        encode_text(&locale.label("Field")),
+        encode_text(&locale.label("Value"))
+    );
+    for (key, value) in fm.data() {
+        let value = encode_text(&locale.format_value(value));
+        let value = ids.replace_all(&value, |c: &regex::Captures| {
+            links.link(&c[0], &c[0]).unwrap_or_else(|| c[0].to_string())
+        });
+        html.push_str(&format!("<tr><td>{}</td><td>{value}</td></tr>\n", encode_text(key)));
+    }
+    html.push_str("</tbody></table>\n");
+    html
+}
+
+/// Turn rendered HTML into storage format: links to documents' `.md` files
+/// become page links and code blocks the code macro.
+fn storage_body(html: &str, links: &PageLinks) -> String {
+    let anchors = Regex::new(r##"(?s)<a href="([^"#:]+\.md)(?:#[^"]*)?"[^>]*>(.*?)</a>"##).unwrap();
+    let html = anchors.replace_all(html, |c: &regex::Captures| {
+        let id = path_to_id(Path::new(&c[1]));
+        links.link(&id, &c[2]).unwrap_or_else(|| c[0].to_string())
+    });
+
+    let code = Regex::new(r#"(?s)<pre><code(?: class="language-([^"]+)")?>(.*?)</code></pre>"#).unwrap();
+    code.replace_all(&html, |c: &regex::Captures| {
+        let text = htmlescape::decode_html(&c[2]).unwrap_or_else(|_| c[2].to_string());
+        let language = match c.get(1) {
+            // Confluence has no Mermaid macro; the diagram shows as source
+            Some(lang) if lang.as_str() != "mermaid" => {
+                format!("<ac:parameter ac:name=\"language\">{}</ac:parameter>", encode_text(lang.as_str()))
+            }
+            _ => String::new(),
+        };
+        format!(
+            "<ac:structured-macro ac:name=\"code\">{language}<ac:plain-text-body><![CDATA[{}]]></ac:plain-text-body>\
+             </ac:structured-macro>",
+            text.trim_end_matches('\n').replace("]]>", "]]]]><![CDATA[>")
+        )
+    })
+    .into_owned()
+}
+
+/// How requests authenticate.
+#[derive(Debug, Clone)]
+pub enum Auth {
+    /// User (e-mail on Confluence Cloud) and API token.
+    Basic { user: String, token: String },
+    /// Personal access token (Confluence Data Center).
+    Bearer(String),
+}
+
+impl Auth {
+    /// `CONFLUENCE_TOKEN`, with `CONFLUENCE_USER` for basic authentication.
+    pub fn from_env() -> Result<Self> {
+        let token = std::env::var("CONFLUENCE_TOKEN")
+            .map_err(|_| Error::Export("CONFLUENCE_TOKEN is not set".into()))?;
+        Ok(match std::env::var("CONFLUENCE_USER") {
+            Ok(user) if !user.is_empty() => Auth::Basic { user, token },
+            _ => Auth::Bearer(token),
+        })
+    }
+}
+
+/// The Confluence REST API through `curl`. The request, credentials included,
+/// goes to curl as a config file on stdin, so it never shows in process lists.
+#[derive(Debug, Clone)]
+pub struct RestClient {
+    /// Base URL of the site, e.g. `https://example.atlassian.net/wiki`.
+    pub base_url: String,
+    pub space: String,
+    /// Page the documents' pages are created under.
+    pub parent: Option<String>,
+    pub auth: Auth,
+}
+
+impl RestClient {
+    fn request(&self, method: &str, path: &str, body: Option<&serde_json::Value>) -> Result<serde_json::Value> {
+        let url = format!("{}/rest/api/content{path}", self.base_url.trim_end_matches('/'));
+        let mut config = format!(
+            "url = {}\nrequest = {}\nsilent\nshow-error\nheader = \"Accept: application/json\"\n\
+             write-out = \"\\n%{{http_code}}\"\n",
+            curl::quote(&url),
+            curl::quote(method)
+        );
+        match self.auth {
+            Auth::Basic { ref user, ref token } => {
+                config.push_str(&format!("user = {}\n", curl::quote(&format!("{user}:{token}"))));
+            }
+            Auth::Bearer(ref token) => {
+                config.push_str(&format!("header = {}\n", curl::quote(&format!("Authorization: Bearer {token}"))));
+            }
+        }
+        if let Some(body) = body {
+            config.push_str("header = \"Content-Type: application/json\"\n");
+            config.push_str(&format!("data-binary = {}\n", curl::quote(&body.to_string())));
+        }
+
+        let mut child = Command::new("curl")
+            .args(["--config", "-"])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| Error::Export(format!("failed to run curl: {e}")))?;
+        if let Some(mut pipe) = child.stdin.take() {
+            pipe.write_all(config.as_bytes())?;
+        }
+        let output = child.wait_with_output()?;
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let (response, status) = stdout.rsplit_once('\n').unwrap_or(("", &stdout));
+        if !output.status.success() || !status.starts_with('2') {
+            let message = serde_json::from_str::<serde_json::Value>(response)
+                .ok()
+                .and_then(|v| v["message"].as_str().map(str::to_string))
+                .unwrap_or_else(|| format!("{}{}", response.trim(), String::from_utf8_lossy(&output.stderr).trim()));
+            return Err(Error::Export(format!("Confluence {method} {path} failed ({status}): {message}")));
+        }
+        Ok(serde_json::from_str(response)?)
+    }
+
+    fn page_json(&self, title: &str, body: &str) -> serde_json::Value {
+        let mut page = json!({
+            "type": "page",
+            "title": title,
+            "space": { "key": self.space },
+            "body": { "storage": { "value": body, "representation": "storage" } },
+        });
+        if let Some(ref parent) = self.parent {
+            page["ancestors"] = json!([{ "id": parent }]);
+        }
+        page
+    }
+
+    fn remote_page(value: &serde_json::Value) -> Result<RemotePage> {
+        let id = match value["id"] {
+            serde_json::Value::String(ref id) => id.clone(),
+            serde_json::Value::Number(ref id) => id.to_string(),
+            _ => return Err(Error::Export(format!("unexpected Confluence response: {value}"))),
+        };
+        let links = &value["_links"];
+        let url = format!(
+            "{}{}",
+            links["base"].as_str().unwrap_or_default(),
+            links["webui"].as_str().unwrap_or_default()
+        );
+        Ok(RemotePage { id, url })
+    }
+}
+
+impl ConfluenceApi for RestClient {
+    fn space(&self) -> &str {
+        &self.space
+    }
+
+    fn create(&mut self, title: &str, body: &str) -> Result<RemotePage> {
+        let created = self.request("POST", "", Some(&self.page_json(title, body)))?;
+        Self::remote_page(&created)
+    }
+
+    fn update(&mut self, page_id: &str, title: &str, body: &str) -> Result<RemotePage> {
+        // Confluence takes an update only as the version after the current one
+        let current = self.request("GET", &format!("/{page_id}?expand=version"), None)?;
+        let version = current["version"]["number"].as_u64().unwrap_or(0);
+        let mut page = self.page_json(title, body);
+        page["id"] = json!(page_id);
+        page["version"] = json!({ "number": version + 1 });
+        let updated = self.request("PUT", &format!("/{page_id}"), Some(&page))?;
+        Self::remote_page(&updated)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Default)]
+    struct FakeApi {
+        next: u32,
+        calls: Vec<String>,
+    }
+
+    impl ConfluenceApi for FakeApi {
+        fn space(&self) -> &str {
+            "ENG"
+        }
+        fn create(&mut self, title: &str, _body: &str) -> Result<RemotePage> {
+            self.next += 1;
+            self.calls.push(format!("create {title}"));
+            Ok(RemotePage {
+                id: self.next.to_string(),
+                url: format!("https://example.test/wiki/pages/{}", self.next),
+            })
+        }
+        fn update(&mut self, page_id: &str, title: &str, _body: &str) -> Result<RemotePage> {
+            self.calls.push(format!("update {page_id} {title}"));
+            Ok(RemotePage {
+                id: page_id.to_string(),
+                url: format!("https://example.test/wiki/pages/{page_id}"),
+            })
+        }
+    }
+
+    fn docs(decision: &str) -> Vec<(String, Document)> {
+        vec![
+            (
+                "ADR-001".into(),
+                Document::from_str(&format!(
+                    "---\ntitle: Use Kafka\nsupersedes: ADR-002\n---\n\n# Decision\n\n{decision}\n\n\
+                     ```yaml\nkey: \"a<b\"\n```\n"
+                ))
+                .unwrap(),
+            ),
+            (
+                "ADR-002".into(),
+                Document::from_str("---\ntitle: Use RabbitMQ\n---\n\nSee [the *new* one](adr-001.md#decision).\n")
+                    .unwrap(),
+            ),
+        ]
+    }
+
+    #[test]
+    fn test_publish_updates_changed_pages_only() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut api = FakeApi::default();
+        let mut state = ConfluenceState::open(dir.path()).unwrap();
+
+        let pages = render_pages(&docs("Kafka."), "ENG", &state, &Locale::default());
+        assert_eq!(pages[0].title, "ADR-001 Use Kafka");
+        assert!(pages[0].body.contains(
+            "<td>supersedes</td><td><ac:link><ri:page ri:space-key=\"ENG\" ri:content-title=\"ADR-002 Use RabbitMQ\" />\
+             <ac:link-body>ADR-002</ac:link-body></ac:link></td>"
+        ));
+        assert!(pages[0].body.contains(
+            "<ac:structured-macro ac:name=\"code\"><ac:parameter ac:name=\"language\">yaml</ac:parameter>\
+             <ac:plain-text-body><![CDATA[key: \"a<b\"]]></ac:plain-text-body></ac:structured-macro>"
+        ), "{}", pages[0].body);
+        assert!(pages[1].body.contains(
+            "<ac:link><ri:page ri:space-key=\"ENG\" ri:content-title=\"ADR-001 Use Kafka\" />\
+             <ac:link-body>the <em>new</em> one</ac:link-body></ac:link>"
+        ), "{}", pages[1].body);
+
+        let mut items = plan(pages, "ENG", &state);
+        assert!(items.iter().all(|i| i.action == PageAction::Create));
+        apply(&mut items, &mut api, &mut state).unwrap();
+        state.save().unwrap();
+
+        // Only the edited document is pushed again, to the same page
+        let mut state = ConfluenceState::open(dir.path()).unwrap();
+        assert_eq!(state.pages()["ADR-002"].page_id, "2");
+        let pages = render_pages(&docs("Kafka, partitioned."), "ENG", &state, &Locale::default());
+        let mut items = plan(pages, "ENG", &state);
+        let actions: Vec<PageAction> = items.iter().map(|i| i.action).collect();
+        assert_eq!(actions, vec![PageAction::Update, PageAction::Unchanged]);
+        apply(&mut items, &mut api, &mut state).unwrap();
+        assert_eq!(
+            api.calls,
+            vec!["create ADR-001 Use Kafka", "create ADR-002 Use RabbitMQ", "update 1 ADR-001 Use Kafka"]
+        );
+
+        // Another space starts over
+        let items = plan(render_pages(&docs("Kafka."), "OPS", &state, &Locale::default()), "OPS", &state);
+        assert!(items.iter().all(|i| i.action == PageAction::Create));
+    }
+}
@@ -0,0 +1,402 @@
+//! PDF of one document or a bundle (`md-db export --format pdf`).
+//!
+//! The documents are laid out as one HTML file for print: a title page, a table
+//! of contents, then each document from a new page with its metadata table,
+//! body tables, and Mermaid diagrams. An installed PDF engine prints it:
+//! a Chromium-based browser, which also draws the diagrams, or WeasyPrint or
+//! wkhtmltopdf, which show diagrams as their source.
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use comrak::Arena;
+use regex::Regex;
+
+use super::{encode_attr, encode_text, frontmatter_table, render_markdown_to_html, site};
+use crate::assets::{absolute, local_target, normalize, url_path};
+use crate::ast_util;
+use crate::document::Document;
+use crate::error::{Error, Result};
+use crate::graph::path_to_id;
+use crate::locale::Locale;
+use crate::schema::Schema;
+
+/// Engines looked for on the `PATH`, in order.
+pub const ENGINES: &[&str] = &[
+    "chromium",
+    "chromium-browser",
+    "google-chrome",
+    "google-chrome-stable",
+    "microsoft-edge",
+    "weasyprint",
+    "wkhtmltopdf",
+];
+
+/// How long a Chromium engine may spend loading Mermaid and drawing diagrams.
+const SCRIPT_BUDGET_MS: u32 = 15_000;
+
+#[derive(Debug, Clone, Default)]
+pub struct PdfOptions {
+    /// Title page heading. Defaults to the document's title, or "Documents" for a bundle.
+    pub title: Option<String>,
+    /// Engine program name or path; the first of [`ENGINES`] found when `None`.
+    pub engine: Option<String>,
+    /// Keep raw HTML in documents as written instead of sanitizing it.
+    pub unsafe_html: bool,
+    /// Mermaid module for the diagrams; they are printed as source when `None`.
+    pub mermaid_url: Option<String>,
+    /// Subresource Integrity hash of `mermaid_url` (`sha384-...`).
+    pub mermaid_integrity: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EngineKind {
+    Chromium,
+    WeasyPrint,
+    Wkhtmltopdf,
+}
+
+/// A PDF engine program and how to drive it.
+#[derive(Debug, Clone)]
+pub struct Engine {
+    pub kind: EngineKind,
+    pub program: PathBuf,
+}
+
+impl Engine {
+    /// The engine `requested` (a program name or path), or the first of
+    /// [`ENGINES`] on the `PATH`. The kind follows the program's file name.
+    pub fn find(requested: Option<&str>) -> Result<Self> {
+        let program = match requested {
+            Some(name) if name.contains(['/', '\\']) => Some(PathBuf::from(name)),
+            Some(name) => on_path(name),
+            None => ENGINES.iter().find_map(|name| on_path(name)),
+        };
+        let Some(program) = program else {
+            return Err(Error::Export(match requested {
+                Some(name) => format!("PDF engine not found: {name}"),
+                None => format!("no PDF engine found; install one of {} or pass --pdf-engine", ENGINES.join(", ")),
+            }));
+        };
+        let name = program.file_name().unwrap_or_default().to_string_lossy().to_lowercase();
+        let kind = if name.contains("weasyprint") {
+            EngineKind::WeasyPrint
+        } else if name.contains("wkhtmltopdf") {
+            EngineKind::Wkhtmltopdf
+        } else {
+            EngineKind::Chromium
+        };
+        Ok(Engine { kind, program })
+    }
+
+    /// Whether the engine runs the page's scripts, so Mermaid diagrams are drawn.
+    pub fn runs_scripts(&self) -> bool {
+        self.kind == EngineKind::Chromium
+    }
+
+    /// Print the HTML file at `html` to `pdf`.
+    pub fn print(&self, html: &Path, pdf: &Path) -> Result<()> {
+        let html = absolute(html);
+        let pdf = absolute(pdf);
+        let mut command = Command::new(&self.program);
+        match self.kind {
+            EngineKind::Chromium => command
+                .args(["--headless", "--disable-gpu", "--no-pdf-header-footer"])
+                .arg(format!("--virtual-time-budget={SCRIPT_BUDGET_MS}"))
+                .arg(format!("--print-to-pdf={}", pdf.display()))
+                .arg(format!("file://{}", url_path(&html))),
+            EngineKind::WeasyPrint => command.arg(&html).arg(&pdf),
+            EngineKind::Wkhtmltopdf => command.args(["--quiet", "--enable-local-file-access"]).arg(&html).arg(&pdf),
+        };
+        let output = command
+            .output()
+            .map_err(|e| Error::Export(format!("{}: {e}", self.program.display())))?;
+        if !output.status.success() || !pdf.exists() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(Error::Export(format!("{} failed: {}", self.program.display(), stderr.trim())));
+        }
+        Ok(())
+    }
+}
+
+fn on_path(name: &str) -> Option<PathBuf> {
+    let file = format!("{name}{}", std::env::consts::EXE_SUFFIX);
+    std::env::split_paths(&std::env::var_os("PATH")?)
+        .map(|dir| dir.join(&file))
+        .find(|path| path.is_file())
+}
+
+#[derive(Debug, Clone)]
+pub struct PdfReport {
+    pub documents: usize,
+    pub engine: Engine,
+    /// Mermaid diagrams were printed as source, because the engine runs no scripts
+    /// or no Mermaid module was given.
+    pub diagrams_as_source: bool,
+}
+
+/// Print the documents at `files` to one PDF at `output`. More than one file
+/// makes a bundle, in the schema's document order.
+pub fn export_pdf(
+    files: &[PathBuf],
+    schema: Option<&Schema>,
+    locale: &Locale,
+    output: &Path,
+    options: &PdfOptions,
+) -> Result<PdfReport> {
+    if files.is_empty() {
+        return Err(Error::Export("no documents to export".into()));
+    }
+    let engine = Engine::find(options.engine.as_deref())?;
+    let mut docs: Vec<(String, Document)> = files
+        .iter()
+        .map(|path| Ok((path_to_id(path), Document::from_file(path)?)))
+        .collect::<Result<_>>()?;
+    crate::ordering::sort_documents(&mut docs, schema, |(id, doc)| (id.as_str(), doc.frontmatter.as_ref()));
+
+    let html = print_html(&docs, locale, options);
+    let diagrams_as_source =
+        html.contains("<pre class=\"mermaid\">") && (!engine.runs_scripts() || options.mermaid_url.is_none());
+    let page = std::env::temp_dir().join(format!("md-db-print-{}.html", std::process::id()));
+    std::fs::write(&page, html).map_err(|_| Error::WriteFailed(page.clone()))?;
+    if let Some(parent) = output.parent().filter(|p| !p.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent)?;
+    }
+    let printed = engine.print(&page, output);
+    let _ = std::fs::remove_file(&page);
+    printed?;
+
+    Ok(PdfReport {
+        documents: docs.len(),
+        engine,
+        diagrams_as_source,
+    })
+}
+
+/// The print layout of `docs` (ID, document with its path set): title page,
+/// table of contents, and one section per document.
+pub fn print_html(docs: &[(String, Document)], locale: &Locale, options: &PdfOptions) -> String {
+    let ids: Vec<String> = docs.iter().map(|(id, _)| id.clone()).collect();
+    let single = match docs {
+        [(id, doc)] => Some((id, doc)),
+        _ => None,
+    };
+    let field = |doc: &Document, key: &str| doc.frontmatter.as_ref().and_then(|fm| fm.get_display(key));
+
+    let (title, subtitle) = match single {
+        Some((id, doc)) => {
+            let title = options.title.clone().or_else(|| field(doc, "title")).unwrap_or_else(|| id.clone());
+            let parts: Vec<String> = [Some(id.clone()), field(doc, "type"), field(doc, "status")]
+                .into_iter()
+                .flatten()
+                .collect();
+            (title, parts.join(" · "))
+        }
+        None => (
+            options.title.clone().unwrap_or_else(|| locale.label("Documents")),
+            locale.label_with("{n} documents", &[("n", &docs.len().to_string())]),
+        ),
+    };
+    let today = crate::template::format_today();
+    let generated = locale.label_with("Generated {date}", &[("date", &locale.format_date(&today).unwrap_or(today))]);
+
+    let mut toc = String::from("<ol>\n");
+    let mut sections = String::new();
+    for (id, doc) in docs {
+        let anchor = doc_anchor(id);
+        let headings = headings(&doc.body);
+        let top = headings.iter().map(|(level, _)| *level).min().unwrap_or(1);
+        let heading_items: String = headings
+            .iter()
+            .filter(|(level, _)| *level <= top + u8::from(single.is_some()))
+            .map(|(_, text)| {
+                let href = format!("#{anchor}-{}", ast_util::heading_anchor(text));
+                format!("<li><a href=\"{}\">{}</a></li>\n", encode_attr(&href), encode_text(text))
+            })
+            .collect();
+        let doc_title = field(doc, "title").unwrap_or_else(|| locale.label("Untitled"));
+        if single.is_some() {
+            toc.push_str(&heading_items);
+        } else {
+            toc.push_str(&format!(
+                "<li><a href=\"#{anchor}\">{} {}</a>\n<ol>\n{heading_items}</ol></li>\n",
+                encode_text(id),
+                encode_text(&doc_title)
+            ));
+        }
+
+        sections.push_str(&format!("<section class=\"document\" id=\"{anchor}\">\n"));
+        if single.is_none() {
+            sections.push_str(&format!(
+                "<h1 class=\"doc-title\">{} {}</h1>\n",
+                encode_text(id),
+                encode_text(&doc_title)
+            ));
+        }
+        sections.push_str(&frontmatter_table(doc, locale));
+        sections.push_str(&document_body(doc, &anchor, &headings, &ids, options.unsafe_html));
+        sections.push_str("</section>\n");
+    }
+    toc.push_str("</ol>\n");
+
+    let html = format!(
+        r#"<!DOCTYPE html>
+<html lang="{lang}">
+<head>
+<meta charset="utf-8">
+<title>{title}</title>
+<style>{PRINT_CSS}</style>
+</head>
+<body>
+<section class="title-page">
+<h1>{title}</h1>
+<p class="subtitle">{subtitle}</p>
+<p class="generated">{generated}</p>
+</section>
+<nav class="toc">
+<h1>{contents}</h1>
+{toc}</nav>
+{sections}</body>
+</html>
+"#,
+        lang = encode_attr(&locale.lang),
+        title = encode_text(&title),
+        subtitle = encode_text(&subtitle),
+        generated = encode_text(&generated),
+        contents = encode_text(&locale.label("Contents")),
+    );
+    site::render_mermaid(&html, options.mermaid_url.as_deref(), options.mermaid_integrity.as_deref())
+}
+
+/// The document body as HTML: headings get IDs for the table of contents,
+/// images point at their files, and links to documents in the PDF at their section.
+fn document_body(doc: &Document, anchor: &str, headings: &[(u8, String)], ids: &[String], unsafe_html: bool) -> String {
+    let html = render_markdown_to_html(&doc.body, unsafe_html);
+
+    let mut heading_ids = headings.iter().map(|(_, text)| format!("{anchor}-{}", ast_util::heading_anchor(text)));
+    let html = Regex::new(r"<h([1-6])>").unwrap().replace_all(&html, |c: &regex::Captures| {
+        match heading_ids.next() {
+            Some(id) => format!("<h{} id=\"{}\">", &c[1], encode_attr(&id)),
+            None => c[0].to_string(),
+        }
+    });
+
+    let doc_dir = absolute(doc.path.as_deref().and_then(Path::parent).unwrap_or(Path::new(".")));
+    let html = Regex::new(r#"(<img[^>]*\ssrc=")([^"]+)""#).unwrap().replace_all(&html, |c: &regex::Captures| {
+        let src = htmlescape::decode_html(&c[2]).unwrap_or_else(|_| c[2].to_string());
+        match local_target(&src) {
+            Some(target) => {
+                let file = normalize(&doc_dir.join(target));
+                format!("{}file://{}\"", &c[1], encode_attr(&url_path(&file)))
+            }
+            None => c[0].to_string(),
+        }
+    });
+
+    Regex::new(r##"href="([^"#:]+\.md)(?:#([^"]*))?""##)
+        .unwrap()
+        .replace_all(&html, |c: &regex::Captures| {
+            let id = path_to_id(Path::new(&c[1]));
+            if !ids.contains(&id) {
+                return c[0].to_string();
+            }
+            match c.get(2) {
+                Some(section) => format!("href=\"#{}-{}\"", doc_anchor(&id), encode_attr(section.as_str())),
+                None => format!("href=\"#{}\"", doc_anchor(&id)),
+            }
+        })
+        .into_owned()
+}
+
+/// Heading levels and texts of a Markdown body, in order.
+fn headings(body: &str) -> Vec<(u8, String)> {
+    let arena = Arena::new();
+    let root = comrak::parse_document(&arena, body, &ast_util::comrak_opts());
+    ast_util::find_headings(root, None)
+        .into_iter()
+        .map(|node| {
+            let level = ast_util::heading_level(node).unwrap_or(1);
+            (level, ast_util::collect_text(node).trim().to_string())
+        })
+        .collect()
+}
+
+fn doc_anchor(id: &str) -> String {
+    format!("doc-{}", id.to_lowercase())
+}
+
+/// Print styles: A4 pages, a page break before each document, and table
+/// headers repeated across pages. `target-counter` adds page numbers to the
+/// contents in engines that support it (WeasyPrint).
+const PRINT_CSS: &str = r#"
+@page { size: A4; margin: 18mm 16mm; }
+body { font-family: system-ui, -apple-system, sans-serif; color: #1a1a1a; line-height: 1.5; font-size: 11pt; }
+.title-page { page-break-after: always; padding-top: 8cm; text-align: center; }
+.title-page h1 { font-size: 26pt; margin-bottom: 0.5rem; }
+.subtitle { font-size: 13pt; color: #4b5563; }
+.generated { margin-top: 3rem; color: #6b7280; }
+.toc { page-break-after: always; }
+.toc ol { list-style: none; padding-left: 1.2rem; }
+.toc > ol { padding-left: 0; }
+.toc a { color: inherit; text-decoration: none; }
+.toc a::after { content: leader('.') target-counter(attr(href), page); }
+.document + .document { page-break-before: always; }
+h1 { border-bottom: 1px solid #d1d5db; padding-bottom: 0.2rem; }
+h1, h2, h3, h4 { page-break-after: avoid; }
+table { border-collapse: collapse; width: 100%; margin: 0.75rem 0; }
+thead { display: table-header-group; }
+tr, img, pre { page-break-inside: avoid; }
+th, td { border: 1px solid #bbb; padding: 0.3rem 0.5rem; text-align: left; vertical-align: top; }
+th { background: #f0f0f0; }
+table.metadata { width: auto; min-width: 50%; font-size: 9.5pt; }
+pre { white-space: pre-wrap; background: #f6f8fa; padding: 0.5rem; font-size: 9pt; }
+pre.mermaid { background: none; text-align: center; }
+img { max-width: 100%; }
+a { color: #1d4ed8; }
+"#;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_print_html() {
+        let doc = |path: &str, content: &str| {
+            let mut doc = Document::from_str(content).unwrap();
+            doc.path = Some(PathBuf::from(path));
+            (path_to_id(Path::new(path)), doc)
+        };
+        let docs = vec![
+            doc(
+                "docs/adr-001.md",
+                "---\ntype: adr\ntitle: Use Kafka\n---\n\n# Context\n\nSee [ADR-002](adr-002.md#decision).\n\n\
+                 | a | b |\n|---|---|\n| 1 | 2 |\n\n![flow](img/flow.png)\n",
+            ),
+            doc(
+                "docs/adr-002.md",
+                "---\ntype: adr\ntitle: Use Avro\n---\n\n# Decision\n\n```mermaid\ngraph TD; A-->B\n```\n",
+            ),
+        ];
+        let options = PdfOptions {
+            title: Some("Architecture decisions".into()),
+            mermaid_url: Some("/assets/mermaid.esm.min.mjs".into()),
+            ..Default::default()
+        };
+
+        let html = print_html(&docs, &Locale::default(), &options);
+        assert!(html.contains("<h1>Architecture decisions</h1>\n<p class=\"subtitle\">2 documents</p>"), "{html}");
+        assert!(html.contains("<li><a href=\"#doc-adr-001\">ADR-001 Use Kafka</a>\n<ol>\n<li><a href=\"#doc-adr-001-context\">Context</a></li>"));
+        assert!(html.contains("<h1 id=\"doc-adr-002-decision\">Decision</h1>"), "{html}");
+        assert!(html.contains("href=\"#doc-adr-002-decision\""), "{html}");
+        assert!(html.contains("<td>2</td>"));
+        assert!(html.contains("/docs/img/flow.png\""), "{html}");
+        assert!(html.contains("<pre class=\"mermaid\">graph TD; A--&gt;B\n</pre>"), "{html}");
+        assert!(html.contains("import mermaid from \"/assets/mermaid.esm.min.mjs\""), "{html}");
+
+        // One document: its own title page, and its sections in the contents
+        let html = print_html(&docs[..1], &Locale::builtin("de").unwrap(), &PdfOptions::default());
+        assert!(html.contains("<h1>Use Kafka</h1>\n<p class=\"subtitle\">ADR-001 · adr</p>"), "{html}");
+        assert!(html.contains("<h1>Inhaltsverzeichnis</h1>\n<ol>\n<li><a href=\"#doc-adr-001-context\">"), "{html}");
+        assert!(!html.contains("doc-title"));
+    }
+}
@@ -0,0 +1,421 @@
+//! Site-wide pages of the HTML export: client-side search, the document graph,
+//! and one listing page per type, plus rendering of Mermaid diagrams.
+//!
+//! Everything works from `file://` as well as a web server: the search index is
+//! a script (`search-index.js`) rather than a fetched JSON file, and the graph
+//! page carries its data inline.
+
+use regex::Regex;
+use serde::Serialize;
+
+use super::{encode_attr, encode_text, Links, CSS};
+use crate::document::Document;
+use crate::graph::DocGraph;
+use crate::locale::Locale;
+use crate::permalink;
+
+pub(super) const SEARCH_PAGE: &str = "/search.html";
+pub(super) const SEARCH_INDEX: &str = "/search-index.js";
+pub(super) const GRAPH_PAGE: &str = "/graph.html";
+
+/// Characters of body text kept per document in the search index.
+const MAX_TEXT: usize = 5000;
+
+/// URL of the listing page of a document type.
+pub(super) fn type_url(doc_type: &str) -> String {
+    format!("{}{}.html", permalink::TYPE_PAGES, permalink::slugify(doc_type))
+}
+
+#[derive(Debug, Serialize)]
+struct SearchEntry<'a> {
+    id: &'a str,
+    title: String,
+    #[serde(rename = "type")]
+    doc_type: Option<String>,
+    status: Option<String>,
+    /// Link from the search page.
+    url: String,
+    text: String,
+}
+
+/// `search-index.js`: every document's ID, title, type, status, and prose,
+/// assigned to `window.MD_DB_SEARCH`.
+pub(super) fn search_index_js(docs: &[(String, &Document)], links: Links) -> String {
+    let links = links.from_page(SEARCH_PAGE);
+    let entries: Vec<SearchEntry> = docs
+        .iter()
+        .map(|(id, doc)| {
+            let fm = doc.frontmatter.as_ref();
+            let prose = crate::prose::prose_text(&doc.body);
+            let text = prose.split_whitespace().collect::<Vec<_>>().join(" ");
+            SearchEntry {
+                id,
+                title: fm.and_then(|fm| fm.get_display("title")).unwrap_or_default(),
+                doc_type: fm.and_then(|fm| fm.get_display("type")),
+                status: fm.and_then(|fm| fm.get_display("status")),
+                url: links.doc(id),
+                text: text.chars().take(MAX_TEXT).collect(),
+            }
+        })
+        .collect();
+    format!("window.MD_DB_SEARCH = {};\n", serde_json::to_string(&entries).unwrap_or_default())
+}
+
+/// Matches every query word against ID, title, type, and status (scored higher)
+/// or the text, and lists the best 50 with a snippet. Reads `?q=` on load.
+const SEARCH_JS: &str = r#"(function () {
+  var docs = window.MD_DB_SEARCH || [];
+  var input = document.getElementById("search-input");
+  var list = document.getElementById("search-results");
+  var summary = document.getElementById("search-summary");
+  function snippet(text, word) {
+    var at = text.toLowerCase().indexOf(word);
+    if (at < 0) return text.slice(0, 160);
+    var start = Math.max(0, at - 60);
+    return (start > 0 ? "…" : "") + text.slice(start, at + 100) + (at + 100 < text.length ? "…" : "");
+  }
+  function run() {
+    var words = input.value.toLowerCase().split(/\s+/).filter(Boolean);
+    list.textContent = "";
+    summary.textContent = "";
+    if (!words.length) return;
+    var hits = [];
+    docs.forEach(function (d) {
+      var head = [d.id, d.title, d.type, d.status].join(" ").toLowerCase();
+      var text = d.text.toLowerCase();
+      var score = 0;
+      for (var i = 0; i < words.length; i++) {
+        if (head.indexOf(words[i]) >= 0) score += 10;
+        else if (text.indexOf(words[i]) >= 0) score += 1;
+        else return;
+      }
+      hits.push({ doc: d, score: score });
+    });
+    hits.sort(function (a, b) { return b.score - a.score; });
+    if (!hits.length) summary.textContent = summary.getAttribute("data-none");
+    hits.slice(0, 50).forEach(function (hit) {
+      var d = hit.doc;
+      var li = document.createElement("li");
+      var a = document.createElement("a");
+      a.href = d.url;
+      a.textContent = d.id;
+      li.appendChild(a);
+      li.appendChild(document.createTextNode(d.title ? " — " + d.title : ""));
+      var meta = [d.type, d.status].filter(Boolean).join(", ");
+      if (meta) {
+        var span = document.createElement("span");
+        span.className = "updated";
+        span.textContent = " " + meta;
+        li.appendChild(span);
+      }
+      var p = document.createElement("p");
+      p.className = "snippet";
+      p.textContent = snippet(d.text, words[0]);
+      li.appendChild(p);
+      list.appendChild(li);
+    });
+  }
+  var q = new URLSearchParams(location.search).get("q");
+  if (q) input.value = q;
+  input.addEventListener("input", run);
+  run();
+})();"#;
+
+pub(super) fn render_search_page(locale: &Locale, links: Links) -> String {
+    let links = links.from_page(SEARCH_PAGE);
+    let heading = encode_text(&locale.label("Search"));
+    let none = encode_attr(&locale.label("No results"));
+    let index = encode_attr(&links.page(SEARCH_INDEX));
+    let body = format!(
+        "<input type=\"search\" id=\"search-input\" class=\"search-input\" placeholder=\"{heading}\" autofocus>\n\
+         <p id=\"search-summary\" data-none=\"{none}\"></p>\n<ol id=\"search-results\" class=\"search-results\"></ol>\n\
+         <script src=\"{index}\"></script>\n<script>{SEARCH_JS}</script>\n"
+    );
+    page(&heading, &links.nav(locale), &body, locale)
+}
+
+#[derive(Debug, Serialize)]
+struct GraphNode<'a> {
+    id: &'a str,
+    title: Option<&'a str>,
+    #[serde(rename = "type")]
+    doc_type: Option<&'a str>,
+    status: Option<&'a str>,
+    /// Link from the graph page; `None` for IDs that aren't exported.
+    url: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct GraphEdge<'a> {
+    from: &'a str,
+    to: &'a str,
+    relation: &'a str,
+}
+
+/// Lays the nodes out with a few hundred steps of a force simulation (nodes
+/// repel, edges pull, everything drifts to the centre) and draws them as SVG.
+/// Nodes link to their pages; colours follow the type.
+const GRAPH_JS: &str = r#"(function () {
+  var data = JSON.parse(document.getElementById("graph-data").textContent);
+  var svg = document.getElementById("graph");
+  var legend = document.getElementById("graph-legend");
+  var ns = "http://www.w3.org/2000/svg";
+  var W = 900, H = 600;
+  var nodes = data.nodes, byId = {}, degree = {};
+  nodes.forEach(function (n, i) {
+    var angle = 2 * Math.PI * i / nodes.length;
+    n.x = W / 2 + 250 * Math.cos(angle);
+    n.y = H / 2 + 200 * Math.sin(angle);
+    byId[n.id] = n;
+    degree[n.id] = 0;
+  });
+  var edges = data.edges.filter(function (e) { return byId[e.from] && byId[e.to]; });
+  edges.forEach(function (e) { degree[e.from]++; degree[e.to]++; });
+  for (var step = 0; step < 300; step++) {
+    var cool = 1 - step / 300;
+    nodes.forEach(function (n) { n.dx = (W / 2 - n.x) * 0.01; n.dy = (H / 2 - n.y) * 0.01; });
+    for (var i = 0; i < nodes.length; i++) {
+      for (var j = i + 1; j < nodes.length; j++) {
+        var a = nodes[i], b = nodes[j], x = a.x - b.x, y = a.y - b.y;
+        var d2 = Math.max(x * x + y * y, 1), f = 3000 / d2, d = Math.sqrt(d2);
+        a.dx += f * x / d; a.dy += f * y / d; b.dx -= f * x / d; b.dy -= f * y / d;
+      }
+    }
+    edges.forEach(function (e) {
+      var a = byId[e.from], b = byId[e.to], x = b.x - a.x, y = b.y - a.y;
+      a.dx += x * 0.05; a.dy += y * 0.05; b.dx -= x * 0.05; b.dy -= y * 0.05;
+    });
+    nodes.forEach(function (n) {
+      n.x = Math.min(W - 30, Math.max(30, n.x + Math.max(-20, Math.min(20, n.dx)) * cool));
+      n.y = Math.min(H - 20, Math.max(20, n.y + Math.max(-20, Math.min(20, n.dy)) * cool));
+    });
+  }
+  function color(type) {
+    var h = 0, s = type || "";
+    for (var i = 0; i < s.length; i++) h = (h * 31 + s.charCodeAt(i)) % 360;
+    return "hsl(" + h + ", 55%, 55%)";
+  }
+  function add(parent, name, attrs, text) {
+    var el = document.createElementNS(ns, name);
+    for (var k in attrs) el.setAttribute(k, attrs[k]);
+    if (text) el.textContent = text;
+    parent.appendChild(el);
+    return el;
+  }
+  edges.forEach(function (e) {
+    var a = byId[e.from], b = byId[e.to];
+    var line = add(svg, "line", { x1: a.x, y1: a.y, x2: b.x, y2: b.y, "class": "graph-edge" });
+    add(line, "title", {}, e.from + " " + e.relation + " " + e.to);
+  });
+  nodes.forEach(function (n) {
+    var g = n.url ? add(svg, "a", { href: n.url }) : add(svg, "g", {});
+    add(g, "circle", { cx: n.x, cy: n.y, r: 5 + Math.min(degree[n.id], 8), fill: color(n.type) });
+    add(g, "text", { x: n.x + 10, y: n.y + 4, "class": "graph-label" }, n.id);
+    add(g, "title", {}, n.id + (n.title ? " — " + n.title : "") + (n.status ? " (" + n.status + ")" : ""));
+  });
+  var types = {};
+  nodes.forEach(function (n) { types[n.type || ""] = true; });
+  Object.keys(types).sort().forEach(function (t) {
+    var span = document.createElement("span");
+    span.className = "graph-type";
+    span.style.background = color(t);
+    span.textContent = t || "?";
+    legend.appendChild(span);
+  });
+})();"#;
+
+/// The graph page, with the documents and their relations embedded as JSON.
+pub(super) fn render_graph_page(graph: &DocGraph, locale: &Locale, links: Links) -> String {
+    let links = links.from_page(GRAPH_PAGE);
+    let nodes: Vec<GraphNode> = graph
+        .nodes
+        .values()
+        .map(|n| GraphNode {
+            id: &n.id,
+            title: n.title.as_deref(),
+            doc_type: n.doc_type.as_deref(),
+            status: n.status.as_deref(),
+            url: links.urls.is_some_and(|urls| urls.contains_key(&n.id)).then(|| links.doc(&n.id)),
+        })
+        .collect();
+    let edges: Vec<GraphEdge> = graph
+        .edges
+        .iter()
+        .map(|e| GraphEdge {
+            from: &e.from,
+            to: &e.to,
+            relation: &e.relation,
+        })
+        .collect();
+    let data = serde_json::json!({ "nodes": nodes, "edges": edges });
+    // `<` only occurs inside JSON strings, where the escape keeps `</script>` out of the page
+    let data = serde_json::to_string(&data).unwrap_or_default().replace('<', "\\u003c");
+
+    let heading = encode_text(&locale.label("Graph"));
+    let body = format!(
+        "<p id=\"graph-legend\"></p>\n<svg id=\"graph\" viewBox=\"0 0 900 600\" role=\"img\"></svg>\n\
+         <script type=\"application/json\" id=\"graph-data\">{data}</script>\n<script>{GRAPH_JS}</script>\n"
+    );
+    page(&heading, &links.nav(locale), &body, locale)
+}
+
+/// Document types in `docs`, in schema order, then alphabetically.
+pub(super) fn doc_types(docs: &[(String, &Document)], schema: Option<&crate::schema::Schema>) -> Vec<String> {
+    let mut types: Vec<String> = Vec::new();
+    for (_, doc) in docs {
+        if let Some(t) = doc.frontmatter.as_ref().and_then(|fm| fm.get_display("type")) {
+            if !types.contains(&t) {
+                types.push(t);
+            }
+        }
+    }
+    let rank = |t: &str| schema.and_then(|s| s.types.iter().position(|d| d.name == t)).unwrap_or(usize::MAX);
+    types.sort_by(|a, b| rank(a).cmp(&rank(b)).then_with(|| a.cmp(b)));
+    types
+}
+
+/// Listing page of one type: every document with its title and status, in
+/// the schema's document order.
+pub(super) fn render_type_page(
+    doc_type: &str,
+    docs: &[(String, &Document)],
+    schema: Option<&crate::schema::Schema>,
+    locale: &Locale,
+    links: Links,
+) -> String {
+    let url = type_url(doc_type);
+    let links = links.from_page(&url);
+    let mut of_type: Vec<&(String, &Document)> = docs
+        .iter()
+        .filter(|(_, doc)| doc.frontmatter.as_ref().and_then(|fm| fm.get_display("type")).as_deref() == Some(doc_type))
+        .collect();
+    crate::ordering::sort_documents(&mut of_type, schema, |(id, doc)| (id.as_str(), doc.frontmatter.as_ref()));
+
+    let count = locale.label_with("{n} documents", &[("n", &of_type.len().to_string())]);
+    let mut body = format!("<p>{}</p>\n<ul>\n", encode_text(&count));
+    for (id, doc) in of_type {
+        let fm = doc.frontmatter.as_ref();
+        let title = fm.and_then(|fm| fm.get_display("title")).unwrap_or_else(|| id.clone());
+        let status = fm
+            .and_then(|fm| fm.get_display("status"))
+            .map(|s| {
+                let class = format!("status-{}", s.to_lowercase());
+                format!(" <span class=\"status-badge {}\">{}</span>", encode_attr(&class), encode_text(&s))
+            })
+            .unwrap_or_default();
+        body.push_str(&format!(
+            "<li><a href=\"{}\">{}</a> — {}{status}</li>\n",
+            encode_attr(&links.doc(id)),
+            encode_text(id),
+            encode_text(&title),
+        ));
+    }
+    body.push_str("</ul>\n");
+    page(&encode_text(&doc_type.to_uppercase()), &links.nav(locale), &body, locale)
+}
+
+/// Turn fenced `mermaid` code blocks into `<pre class="mermaid">`, and on pages
+/// that have any load Mermaid from `script_url` when one is given. With an
+/// `integrity` hash the module is first fetched by a `<script>` that checks it;
+/// the import then reuses that module.
+pub(super) fn render_mermaid(html: &str, script_url: Option<&str>, integrity: Option<&str>) -> String {
+    let re = Regex::new(r#"(?s)<pre><code class="language-mermaid">(.*?)</code></pre>"#).unwrap();
+    if !re.is_match(html) {
+        return html.to_string();
+    }
+    let html = re.replace_all(html, "<pre class=\"mermaid\">$1</pre>");
+    let Some(script_url) = script_url else {
+        return html.into_owned();
+    };
+    let checked = integrity.map_or(String::new(), |hash| {
+        format!(
+            "<script type=\"module\" src=\"{}\" integrity=\"{}\" crossorigin=\"anonymous\"></script>\n",
+            encode_attr(script_url),
+            encode_attr(hash)
+        )
+    });
+    let url = serde_json::to_string(script_url).unwrap_or_default().replace("</", "<\\/");
+    let script = format!(
+        "{checked}<script type=\"module\">import mermaid from {url}; mermaid.initialize({{ startOnLoad: true }});</script>\n"
+    );
+    super::inject_before_body_end(&html, &script)
+}
+
+fn page(heading: &str, nav: &str, body: &str, locale: &Locale) -> String {
+    let lang = encode_attr(&locale.lang);
+    format!(
+        r#"<!DOCTYPE html>
+<html lang="{lang}">
+<head>
+<meta charset="utf-8">
+<meta name="viewport" content="width=device-width, initial-scale=1">
+<title>{heading}</title>
+<style>{CSS}</style>
+</head>
+<body>
+{nav}
+<h1>{heading}</h1>
+{body}</body>
+</html>
+"#
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::*;
+
+    #[test]
+    fn test_search_index_and_type_pages() {
+        let adr =
+            Document::from_str("---\ntitle: Use Kafka\ntype: adr\nstatus: accepted\n---\n\nEvents go   through\nKafka.\n")
+                .unwrap();
+        let inc = Document::from_str("---\ntitle: Outage\ntype: inc\n---\n\n```sh\nexit 1\n```\n").unwrap();
+        let docs = vec![("ADR-001".to_string(), &adr), ("INC-001".to_string(), &inc)];
+        let urls: BTreeMap<String, String> =
+            docs.iter().map(|(id, _)| (id.clone(), permalink::default_url(id))).collect();
+        let types = doc_types(&docs, None);
+        let links = Links {
+            urls: Some(&urls),
+            page: "/index.html",
+            types: &types,
+            schema: false,
+        };
+
+        let js = search_index_js(&docs, links);
+        assert!(js.starts_with("window.MD_DB_SEARCH = [{\"id\":\"ADR-001\",\"title\":\"Use Kafka\""), "{js}");
+        assert!(js.contains("\"status\":\"accepted\",\"url\":\"adr-001.html\",\"text\":\"Events go through Kafka.\""));
+        assert!(js.contains("\"id\":\"INC-001\",\"title\":\"Outage\",\"type\":\"inc\",\"status\":null"), "{js}");
+
+        assert_eq!(types, vec!["adr", "inc"]);
+        let page = render_type_page("adr", &docs, None, &Locale::default(), links);
+        assert!(page.contains("<a href=\"../adr-001.html\">ADR-001</a> — Use Kafka <span class=\"status-badge"));
+        assert!(!page.contains("INC-001</a>"));
+        assert!(page.contains("<a href=\"../search.html\">Search</a>"), "{page}");
+        assert!(page.contains("<a href=\"../types/inc.html\">INC</a>"), "{page}");
+    }
+
+    #[test]
+    fn test_render_mermaid() {
+        let html = "<body>\n<pre><code class=\"language-mermaid\">graph TD\n  A --&gt; B\n</code></pre>\n</body>";
+        let url = "https://cdn.jsdelivr.net/npm/mermaid@11.4.1/dist/mermaid.esm.min.mjs";
+        let out = render_mermaid(html, Some(url), None);
+        assert!(out.contains("<pre class=\"mermaid\">graph TD\n  A --&gt; B\n</pre>"), "{out}");
+        assert!(out.contains(&format!("import mermaid from \"{url}\"")), "{out}");
+        assert!(!out.contains("integrity"), "{out}");
+
+        let out = render_mermaid(html, Some(url), Some("sha384-abc"));
+        assert!(
+            out.contains(&format!("<script type=\"module\" src=\"{url}\" integrity=\"sha384-abc\" crossorigin=\"anonymous\">")),
+            "{out}"
+        );
+
+        // No script unless asked for one
+        let out = render_mermaid(html, None, None);
+        assert!(out.contains("<pre class=\"mermaid\">") && !out.contains("<script"), "{out}");
+        let plain = "<body><pre><code class=\"language-sh\">ls</code></pre></body>";
+        assert_eq!(render_mermaid(plain, Some(url), None), plain);
+    }
+}
@@ -0,0 +1,435 @@
+//! Automatic repairs for validation diagnostics.
+//!
+//! A [`Fixer`] looks at one diagnostic and proposes a [`Patch`] without touching
+//! the document. Patches are plain data, so callers can show them (`fix --dry-run`,
+//! editor code actions) before [`Patch::apply`] writes them. [`FixEngine`] holds
+//! the registered fixers and is what the CLI and MCP server run.
+
+use serde::{Deserialize, Serialize};
+
+use crate::document::Document;
+use crate::schema::{FieldType, Schema, TypeDef};
+use crate::template;
+use crate::validation::Diagnostic;
+
+/// A single change to a document.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "kebab-case")]
+pub enum Edit {
+    /// Set a frontmatter field; `value` is parsed as YAML.
+    SetField { field: String, value: String },
+    /// Append an empty heading at the end of the body.
+    AppendSection { heading: String, level: u8 },
+    /// Sort the rows of the first table under `section` by `column`.
+    SortTable { section: String, column: String, ascending: bool },
+}
+
+impl Edit {
+    pub fn apply(&self, doc: &mut Document) {
+        match self {
+            Edit::SetField { field, value } => doc.set_field_from_str(field, value),
+            Edit::AppendSection { heading, level } => doc.append_heading(heading, *level),
+            Edit::SortTable { section, column, ascending } => {
+                // The table was found when the diagnostic was raised
+                let _ = doc.sort_table(section, 0, column, *ascending);
+            }
+        }
+    }
+}
+
+/// A proposed fix for one diagnostic.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Patch {
+    pub code: String,
+    pub description: String,
+    /// Empty when the fixer recognized the problem but it needs a manual fix.
+    pub edits: Vec<Edit>,
+}
+
+impl Patch {
+    pub fn new(code: &str, description: impl Into<String>, edits: Vec<Edit>) -> Self {
+        Self {
+            code: code.to_string(),
+            description: description.into(),
+            edits,
+        }
+    }
+
+    /// A patch that only explains why nothing can be done automatically.
+    pub fn manual(code: &str, description: impl Into<String>) -> Self {
+        Self::new(code, description, Vec::new())
+    }
+
+    pub fn is_applicable(&self) -> bool {
+        !self.edits.is_empty()
+    }
+
+    pub fn apply(&self, doc: &mut Document) {
+        for edit in &self.edits {
+            edit.apply(doc);
+        }
+    }
+}
+
+/// Proposes patches for the diagnostic codes it understands.
+pub trait Fixer {
+    fn applies_to(&self, code: &str) -> bool;
+
+    /// `None` when the diagnostic doesn't carry enough information to act on.
+    fn propose(&self, doc: &Document, diag: &Diagnostic, type_def: &TypeDef) -> Option<Patch>;
+}
+
+/// F010: add a missing required field with its schema default.
+pub struct MissingFieldFixer;
+
+impl Fixer for MissingFieldFixer {
+    fn applies_to(&self, code: &str) -> bool {
+        code == "F010"
+    }
+
+    fn propose(&self, _doc: &Document, diag: &Diagnostic, type_def: &TypeDef) -> Option<Patch> {
+        // `missing required field "NAME"`
+        let field_name = extract_quoted(&diag.message)?;
+        let field_def = type_def.fields.iter().find(|f| f.name == field_name)?;
+
+        Some(match template::field_default_string(field_def) {
+            Some(default_val) => Patch::new(
+                "F010",
+                format!(
+                    "added field {field_name}=\"{default_val}\"{}",
+                    field_def
+                        .default
+                        .as_ref()
+                        .map(|d| format!(" (schema default: {d})"))
+                        .unwrap_or_default()
+                ),
+                vec![Edit::SetField {
+                    field: field_name,
+                    value: default_val,
+                }],
+            ),
+            None => Patch::manual(
+                "F010",
+                format!("field \"{field_name}\" has no default — manual fix needed"),
+            ),
+        })
+    }
+}
+
+/// F021: replace an invalid enum value with the closest allowed one.
+pub struct EnumValueFixer;
+
+impl Fixer for EnumValueFixer {
+    fn applies_to(&self, code: &str) -> bool {
+        code == "F021"
+    }
+
+    fn propose(&self, _doc: &Document, diag: &Diagnostic, type_def: &TypeDef) -> Option<Patch> {
+        // `field "NAME" has invalid value "VALUE"`
+        let field_name = extract_quoted(&diag.message)?;
+        let invalid_value = extract_nth_quoted(&diag.message, 1)?;
+        let field_def = type_def.fields.iter().find(|f| f.name == field_name)?;
+
+        let FieldType::Enum(allowed) = &field_def.field_type else {
+            return None;
+        };
+        let candidates: Vec<&str> = allowed.iter().map(|s| s.as_str()).collect();
+        // Allow up to half the string length as max edit distance (reasonable threshold)
+        let max_dist = (invalid_value.len() / 2).max(2);
+
+        Some(match template::closest_match(&invalid_value, &candidates, max_dist) {
+            Some(closest) => Patch::new(
+                "F021",
+                format!("field \"{field_name}\": \"{invalid_value}\" → \"{closest}\""),
+                vec![Edit::SetField {
+                    field: field_name,
+                    value: closest.to_string(),
+                }],
+            ),
+            None => Patch::manual(
+                "F021",
+                format!(
+                    "field \"{field_name}\": no close match for \"{invalid_value}\" in [{}]",
+                    candidates.join(", ")
+                ),
+            ),
+        })
+    }
+}
+
+/// S010: append the missing section heading to the body.
+pub struct MissingSectionFixer;
+
+impl Fixer for MissingSectionFixer {
+    fn applies_to(&self, code: &str) -> bool {
+        code == "S010"
+    }
+
+    fn propose(&self, _doc: &Document, diag: &Diagnostic, _type_def: &TypeDef) -> Option<Patch> {
+        // `missing required section "NAME"`, nested as "Consequences > Positive"
+        let section_name = extract_quoted(&diag.message)?;
+        let leaf_name = section_name.rsplit(" > ").next().unwrap_or(&section_name);
+        let level = section_name.matches(" > ").count() + 1;
+
+        Some(Patch::new(
+            "S010",
+            format!("added section \"{section_name}\""),
+            vec![Edit::AppendSection {
+                heading: leaf_name.to_string(),
+                level: level as u8,
+            }],
+        ))
+    }
+}
+
+/// S023: sort a table's rows by its `ordered-by` column.
+pub struct TableOrderFixer;
+
+impl Fixer for TableOrderFixer {
+    fn applies_to(&self, code: &str) -> bool {
+        code == "S023"
+    }
+
+    fn propose(&self, _doc: &Document, diag: &Diagnostic, _type_def: &TypeDef) -> Option<Patch> {
+        // `table in "SECTION" is not sorted by "COLUMN" (ascending): ...`
+        let section = extract_quoted(&diag.message)?;
+        let column = extract_nth_quoted(&diag.message, 1)?;
+        let ascending = !diag.message.contains("(descending)");
+
+        Some(Patch::new(
+            "S023",
+            format!("sorted table in \"{section}\" by \"{column}\""),
+            vec![Edit::SortTable {
+                section,
+                column,
+                ascending,
+            }],
+        ))
+    }
+}
+
+/// Runs registered fixers over a document's diagnostics.
+pub struct FixEngine {
+    fixers: Vec<Box<dyn Fixer>>,
+}
+
+impl FixEngine {
+    /// Engine with the built-in fixers (F010, F021, S010, S023).
+    pub fn new() -> Self {
+        Self::empty()
+            .with_fixer(MissingFieldFixer)
+            .with_fixer(EnumValueFixer)
+            .with_fixer(MissingSectionFixer)
+            .with_fixer(TableOrderFixer)
+    }
+
+    /// Engine with no fixers registered.
+    pub fn empty() -> Self {
+        Self { fixers: Vec::new() }
+    }
+
+    pub fn with_fixer(mut self, fixer: impl Fixer + 'static) -> Self {
+        self.fixers.push(Box::new(fixer));
+        self
+    }
+
+    /// Whether some registered fixer handles `code`.
+    pub fn handles(&self, code: &str) -> bool {
+        self.fixers.iter().any(|f| f.applies_to(code))
+    }
+
+    /// Patches for `diags`, in diagnostic order. The first fixer that applies to a
+    /// code and returns a patch wins. Documents without a known type get none.
+    pub fn propose(&self, doc: &Document, diags: &[Diagnostic], schema: &Schema) -> Vec<Patch> {
+        let Some(type_def) = doc
+            .frontmatter
+            .as_ref()
+            .and_then(|fm| fm.get_display("type"))
+            .and_then(|t| schema.get_type(&t))
+        else {
+            return Vec::new();
+        };
+        diags
+            .iter()
+            .filter_map(|diag| {
+                self.fixers
+                    .iter()
+                    .filter(|f| f.applies_to(&diag.code))
+                    .find_map(|f| f.propose(doc, diag, type_def))
+            })
+            .collect()
+    }
+
+    /// Propose patches and apply the applicable ones to `doc` (in memory).
+    pub fn fix(&self, doc: &mut Document, diags: &[Diagnostic], schema: &Schema) -> Vec<Patch> {
+        let patches = self.propose(doc, diags, schema);
+        for patch in &patches {
+            patch.apply(doc);
+        }
+        patches
+    }
+}
+
+impl Default for FixEngine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Extract the first double-quoted substring from a message.
+fn extract_quoted(msg: &str) -> Option<String> {
+    extract_nth_quoted(msg, 0)
+}
+
+/// Extract the nth double-quoted substring from a message.
+fn extract_nth_quoted(msg: &str, n: usize) -> Option<String> {
+    let mut count = 0;
+    let mut chars = msg.chars().peekable();
+    while let Some(ch) = chars.next() {
+        if ch == '"' {
+            let mut val = String::new();
+            for c in chars.by_ref() {
+                if c == '"' {
+                    break;
+                }
+                val.push(c);
+            }
+            if count == n {
+                return Some(val);
+            }
+            count += 1;
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::validation::validate_document;
+    use std::collections::HashSet;
+
+    fn schema() -> Schema {
+        Schema::from_str(
+            r#"
+type "adr" {
+    field "title" type="string" required=#true
+    field "status" type="enum" required=#true default="proposed" {
+        values "proposed" "accepted" "rejected"
+    }
+    section "Decision" required=#true
+    section "Consequences" required=#true {
+        section "Positive" required=#true
+    }
+}
+"#,
+        )
+        .unwrap()
+    }
+
+    fn diagnostics(doc: &Document, schema: &Schema) -> Vec<Diagnostic> {
+        validate_document(doc, schema, &HashSet::new(), &HashSet::new(), None).diagnostics
+    }
+
+    #[test]
+    fn test_extract_quoted() {
+        assert_eq!(
+            extract_quoted(r#"missing required field "title""#),
+            Some("title".to_string())
+        );
+        assert_eq!(
+            extract_quoted(r#"missing required section "Decision""#),
+            Some("Decision".to_string())
+        );
+    }
+
+    #[test]
+    fn test_extract_nth_quoted() {
+        let msg = r#"field "status" has invalid value "aceppted""#;
+        assert_eq!(extract_nth_quoted(msg, 0), Some("status".to_string()));
+        assert_eq!(extract_nth_quoted(msg, 1), Some("aceppted".to_string()));
+    }
+
+    #[test]
+    fn test_propose_does_not_modify() {
+        let schema = schema();
+        let doc = Document::from_str("---\ntype: adr\nstatus: aceppted\n---\n\n# Decision\n\nX\n").unwrap();
+        let patches = FixEngine::new().propose(&doc, &diagnostics(&doc, &schema), &schema);
+
+        let codes: Vec<&str> = patches.iter().map(|p| p.code.as_str()).collect();
+        assert_eq!(codes, vec!["F010", "F021", "S010"]);
+        assert!(!patches[0].is_applicable(), "title has no default");
+        assert_eq!(
+            patches[1].edits,
+            vec![Edit::SetField { field: "status".into(), value: "accepted".into() }]
+        );
+        assert!(doc.raw.contains("aceppted"));
+    }
+
+    #[test]
+    fn test_fix_applies_patches() {
+        let schema = schema();
+        let mut doc = Document::from_str("---\ntype: adr\ntitle: T\n---\n\n# Decision\n\nX\n").unwrap();
+        let diags = diagnostics(&doc, &schema);
+        FixEngine::new().fix(&mut doc, &diags, &schema);
+
+        assert!(doc.raw.contains("status: proposed"));
+        assert!(doc.body.contains("\n# Consequences\n"));
+        let remaining = diagnostics(&Document::from_str(&doc.raw).unwrap(), &schema);
+        assert!(remaining.iter().all(|d| d.code != "F010"));
+    }
+
+    #[test]
+    fn test_sort_table_fix() {
+        let schema = Schema::from_str(
+            "type \"log\" {\n    section \"Timeline\" {\n        table {\n            ordered-by \"Time\" ascending=#false\n        }\n    }\n}\n",
+        )
+        .unwrap();
+        let mut doc = Document::from_str(
+            "---\ntype: log\n---\n\n# Timeline\n\n| Time | Event |\n|---|---|\n| 09:30 | alert |\n| 11:00 | fix |\n| 10:15 | deploy |\n\nAfter.\n",
+        )
+        .unwrap();
+        let diags = diagnostics(&doc, &schema);
+        let patches = FixEngine::new().fix(&mut doc, &diags, &schema);
+        assert_eq!(
+            patches[0].edits,
+            vec![Edit::SortTable { section: "Timeline".into(), column: "Time".into(), ascending: false }]
+        );
+        assert!(
+            doc.body.contains("| 11:00 | fix |\n| 10:15 | deploy |\n| 09:30 | alert |\n\nAfter."),
+            "{}",
+            doc.body
+        );
+        assert!(diagnostics(&Document::from_str(&doc.raw).unwrap(), &schema).is_empty());
+    }
+
+    #[test]
+    fn test_custom_fixer_and_serialization() {
+        struct TitleFixer;
+        impl Fixer for TitleFixer {
+            fn applies_to(&self, code: &str) -> bool {
+                code == "F010"
+            }
+            fn propose(&self, _doc: &Document, _diag: &Diagnostic, _type_def: &TypeDef) -> Option<Patch> {
+                Some(Patch::new(
+                    "F010",
+                    "added placeholder title",
+                    vec![Edit::SetField { field: "title".into(), value: "TODO".into() }],
+                ))
+            }
+        }
+
+        let schema = schema();
+        let doc = Document::from_str("---\ntype: adr\nstatus: proposed\n---\n").unwrap();
+        let engine = FixEngine::empty().with_fixer(TitleFixer);
+        assert!(engine.handles("F010"));
+        assert!(!engine.handles("S010"));
+
+        let patches = engine.propose(&doc, &diagnostics(&doc, &schema), &schema);
+        assert_eq!(patches.len(), 1);
+        let json = serde_json::to_value(&patches[0]).unwrap();
+        assert_eq!(json["edits"][0]["op"], "set-field");
+        let back: Patch = serde_json::from_value(json).unwrap();
+        assert_eq!(back, patches[0]);
+    }
+}
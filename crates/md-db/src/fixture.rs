@@ -0,0 +1,668 @@
+//! Fixture corpora generated from a schema, for the integration tests of projects using md-db.
+//!
+//! [`generate`] builds the documents in memory: `per_type` valid documents of each
+//! type, filled in like `md-db describe --example` with distinct titles and refs that
+//! point at other generated documents, and with [`FixtureOptions::with_errors`] the
+//! candidate invalid variants, each a valid document with one defect aimed at one
+//! diagnostic code. [`write`] puts them under `<out>/valid` and `<out>/invalid`, keeps
+//! the first variant per code that validation actually reports, and records them in
+//! `<out>/invalid/expected.json`.
+
+use std::collections::{BTreeMap, HashSet};
+use std::path::Path;
+
+use serde_yaml::Value;
+
+use crate::error::{Error, Result};
+use crate::frontmatter::Frontmatter;
+use crate::schema::{Cardinality, FieldDef, FieldType, Schema, SectionDef, TypeDef};
+use crate::users::UserConfig;
+use crate::validation::ValidationResult;
+
+pub const VALID_DIR: &str = "valid";
+pub const INVALID_DIR: &str = "invalid";
+/// Maps each invalid variant's file name to the code it raises.
+pub const EXPECTED_FILE: &str = "expected.json";
+
+/// Numbering of invalid variants starts here, after any valid document's.
+const VARIANT_BASE: usize = 101;
+
+/// The codes invalid variants aim at, in the order they are tried. Codes that need
+/// something the schema lacks (an enum field for F021, a table for S020) are skipped.
+pub const CODES: &[&str] = &[
+    "E000", "F002", "F010", "F011", "F020", "F021", "F030", "F031", "F040", "F041", "I001", "I002", "R011",
+    "R012", "R020", "R021", "R022", "S010", "S020", "S021", "S030", "S031", "S032", "A010", "Q001", "T010",
+    "U010", "U011", "U012",
+];
+
+#[derive(Debug, Clone)]
+pub struct FixtureOptions {
+    /// Documents per type; singletons get one, `max_count` types at most that many.
+    pub per_type: usize,
+    /// Fill optional ref fields too, and link documents through every relation and its inverse.
+    pub link: bool,
+    /// Also build invalid variants.
+    pub with_errors: bool,
+}
+
+impl Default for FixtureOptions {
+    fn default() -> Self {
+        Self {
+            per_type: 2,
+            link: false,
+            with_errors: false,
+        }
+    }
+}
+
+/// A generated file, named relative to its corpus folder.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FixtureFile {
+    pub file: String,
+    pub content: String,
+}
+
+/// Files that together should raise `code`, each of them (two documents sharing a uid for F041).
+#[derive(Debug, Clone)]
+pub struct Variant {
+    pub code: &'static str,
+    pub files: Vec<FixtureFile>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Corpus {
+    pub valid: Vec<FixtureFile>,
+    /// Candidates, possibly several per code; [`write`] keeps the first that works.
+    pub variants: Vec<Variant>,
+}
+
+#[derive(Debug)]
+pub struct FixtureReport {
+    /// Validation of the valid corpus; any error here is a bug in the generator or the schema.
+    pub valid: ValidationResult,
+    pub valid_files: usize,
+    /// Invalid variant file -> the code it raises.
+    pub expected: BTreeMap<String, String>,
+    /// Codes of [`CODES`] no variant raises for this schema.
+    pub missed: Vec<&'static str>,
+}
+
+/// A document before rendering.
+#[derive(Debug, Clone)]
+struct Draft<'a> {
+    type_def: &'a TypeDef,
+    file: String,
+    id: String,
+    data: BTreeMap<String, Value>,
+    body: String,
+}
+
+impl Draft<'_> {
+    fn render(&self) -> FixtureFile {
+        let fm = Frontmatter::from_data(self.data.clone());
+        FixtureFile {
+            file: self.file.clone(),
+            content: format!("---\n{}---\n{}", fm.to_yaml_string(), self.body),
+        }
+    }
+}
+
+/// Build the corpus for `schema`. Documents use the handles of `users` when given.
+pub fn generate(schema: &Schema, users: Option<&UserConfig>, opts: &FixtureOptions) -> Corpus {
+    let mut drafts = Vec::new();
+    for type_def in &schema.types {
+        if type_def.singleton {
+            if let Some(file) = singleton_file(type_def) {
+                drafts.push(draft(type_def, schema, users, file, None));
+            }
+            continue;
+        }
+        let count = opts.per_type.min(type_def.max_count.unwrap_or(usize::MAX));
+        for n in 1..=count {
+            drafts.push(draft(type_def, schema, users, numbered_file(type_def, n), Some(n)));
+        }
+    }
+
+    fill_refs(&mut drafts, schema, opts.link);
+    if opts.link {
+        link_relations(&mut drafts, schema);
+    }
+
+    let variants = if opts.with_errors {
+        variants(&drafts, schema, users)
+    } else {
+        Vec::new()
+    };
+    Corpus {
+        valid: drafts.iter().map(Draft::render).collect(),
+        variants,
+    }
+}
+
+/// Write `corpus` under `out`, which must not hold a non-empty `valid/` or `invalid/`
+/// folder yet, and validate the result.
+pub fn write(corpus: &Corpus, out: &Path, schema: &Schema, users: Option<&UserConfig>) -> Result<FixtureReport> {
+    let valid_dir = out.join(VALID_DIR);
+    let invalid_dir = out.join(INVALID_DIR);
+    for dir in [&valid_dir, &invalid_dir] {
+        if dir.read_dir().is_ok_and(|mut entries| entries.next().is_some()) {
+            return Err(Error::Fixture(format!("{} is not empty", dir.display())));
+        }
+    }
+
+    write_files(&valid_dir, &corpus.valid)?;
+    let valid = crate::validation::validate_directory(&valid_dir, schema, None, users)?;
+
+    let mut expected = BTreeMap::new();
+    let mut missed = Vec::new();
+    if !corpus.variants.is_empty() {
+        write_files(&invalid_dir, &corpus.valid)?;
+        for variant in &corpus.variants {
+            write_files(&invalid_dir, &variant.files)?;
+        }
+
+        // Keep the first candidate per code that raises it in every one of its files
+        let result = crate::validation::validate_directory(&invalid_dir, schema, None, users)?;
+        let mut kept: Vec<&Variant> = Vec::new();
+        for variant in &corpus.variants {
+            if !kept.iter().any(|k| k.code == variant.code) && raises(&result, &invalid_dir, variant) {
+                kept.push(variant);
+                continue;
+            }
+            for f in &variant.files {
+                let _ = std::fs::remove_file(invalid_dir.join(&f.file));
+            }
+        }
+
+        // Removing the other candidates must not change what the kept ones raise
+        let result = crate::validation::validate_directory(&invalid_dir, schema, None, users)?;
+        for variant in kept {
+            if !raises(&result, &invalid_dir, variant) {
+                for f in &variant.files {
+                    let _ = std::fs::remove_file(invalid_dir.join(&f.file));
+                }
+                continue;
+            }
+            for f in &variant.files {
+                expected.insert(f.file.clone(), variant.code.to_string());
+            }
+        }
+        missed = CODES
+            .iter()
+            .copied()
+            .filter(|code| !expected.values().any(|c| c.as_str() == *code))
+            .collect();
+
+        let json = serde_json::to_string_pretty(&expected)?;
+        crate::durability::write(invalid_dir.join(EXPECTED_FILE), json + "\n")?;
+    }
+
+    Ok(FixtureReport {
+        valid,
+        valid_files: corpus.valid.len(),
+        expected,
+        missed,
+    })
+}
+
+fn write_files(dir: &Path, files: &[FixtureFile]) -> Result<()> {
+    std::fs::create_dir_all(dir).map_err(|_| Error::WriteFailed(dir.to_path_buf()))?;
+    for f in files {
+        crate::durability::write(dir.join(&f.file), &f.content)?;
+    }
+    Ok(())
+}
+
+fn raises(result: &ValidationResult, dir: &Path, variant: &Variant) -> bool {
+    variant.files.iter().all(|f| {
+        let path = dir.join(&f.file).display().to_string();
+        result
+            .file_results
+            .iter()
+            .filter(|r| r.path == path)
+            .any(|r| r.diagnostics.iter().any(|d| d.code == variant.code))
+    })
+}
+
+fn numbered_file(type_def: &TypeDef, n: usize) -> String {
+    format!("{}-{n:03}.md", type_def.name.to_lowercase())
+}
+
+/// A file name the singleton's `match` pattern accepts, if one can be derived.
+fn singleton_file(type_def: &TypeDef) -> Option<String> {
+    let file = type_def
+        .match_pattern
+        .as_deref()?
+        .replace('*', &type_def.name.to_lowercase());
+    type_def.matches_filename(&file).then_some(file)
+}
+
+fn draft<'a>(
+    type_def: &'a TypeDef,
+    schema: &Schema,
+    users: Option<&UserConfig>,
+    file: String,
+    n: Option<usize>,
+) -> Draft<'a> {
+    let (mut data, body) = crate::template::example_parts(type_def, schema, users);
+    // Titles are unique within a type (I002)
+    if let (Some(n), Some(title)) = (n, data.get_mut("title")) {
+        if title.is_string() {
+            *title = Value::String(format!("Example {} document {n}", type_def.name));
+        }
+    }
+    Draft {
+        type_def,
+        id: crate::graph::path_to_id(Path::new(&file)),
+        file,
+        data,
+        body,
+    }
+}
+
+fn allows(target_types: &[String], type_name: &str) -> bool {
+    target_types.is_empty() || target_types.iter().any(|t| t == type_name)
+}
+
+/// The allowed target types of a ref field: its own, else its relation's.
+fn ref_targets<'s>(field: &'s FieldDef, schema: &'s Schema) -> &'s [String] {
+    if !field.target_types.is_empty() {
+        return &field.target_types;
+    }
+    match schema.find_relation(&field.name) {
+        Some((rel, _)) => &rel.target_types,
+        None => &[],
+    }
+}
+
+/// Point ref fields at generated documents: required ones always, optional ones
+/// with `link` (else they are dropped). Relation fields are left to [`link_relations`].
+fn fill_refs(drafts: &mut [Draft], schema: &Schema, link: bool) {
+    let targets: Vec<(String, String)> = drafts
+        .iter()
+        .filter(|d| !d.type_def.singleton)
+        .map(|d| (d.type_def.name.clone(), d.id.clone()))
+        .collect();
+    for draft in drafts.iter_mut() {
+        let type_def = draft.type_def;
+        for field in &type_def.fields {
+            if !matches!(field.field_type, FieldType::Ref | FieldType::RefArray) {
+                continue;
+            }
+            let relation = schema.find_relation(&field.name).is_some();
+            if !field.required && (!link || relation) {
+                draft.data.remove(&field.name);
+                continue;
+            }
+            if relation && link {
+                continue;
+            }
+            let allowed = ref_targets(field, schema);
+            let target = targets
+                .iter()
+                .filter(|(t, _)| allows(allowed, t))
+                .map(|(_, id)| id)
+                .find(|id| **id != draft.id)
+                .or_else(|| targets.iter().map(|(_, id)| id).find(|id| **id == draft.id));
+            let Some(target) = target else {
+                continue;
+            };
+            let value = Value::String(target.clone());
+            let value = match field.field_type {
+                FieldType::RefArray => Value::Sequence(vec![value]),
+                _ => value,
+            };
+            draft.data.insert(field.name.clone(), value);
+        }
+    }
+}
+
+/// Link documents through every relation, setting the inverse on the other side.
+/// Within an allowed type each document points at the previous one, so no relation
+/// has a cycle; other types point at the first document of an allowed type.
+fn link_relations(drafts: &mut [Draft], schema: &Schema) {
+    for rel in &schema.relations {
+        let mut pairs = Vec::new();
+        for (i, d) in drafts.iter().enumerate() {
+            if d.type_def.singleton {
+                continue;
+            }
+            let same_type = |p: &Draft| !p.type_def.singleton && p.type_def.name == d.type_def.name;
+            let target = if allows(&rel.target_types, &d.type_def.name) {
+                drafts[..i].iter().rposition(same_type)
+            } else {
+                drafts
+                    .iter()
+                    .position(|p| !p.type_def.singleton && allows(&rel.target_types, &p.type_def.name))
+            };
+            if let Some(j) = target {
+                pairs.push((i, j));
+            }
+        }
+
+        // A cardinality-one field takes a single ref, on either side
+        let mut taken: HashSet<(usize, &str)> = HashSet::new();
+        for (i, j) in pairs {
+            let inverse = rel.inverse.as_deref();
+            if rel.cardinality == Cardinality::One {
+                let free = !taken.contains(&(i, rel.name.as_str()));
+                if !free || inverse.is_some_and(|inv| taken.contains(&(j, inv))) {
+                    continue;
+                }
+                taken.insert((i, rel.name.as_str()));
+                if let Some(inv) = inverse {
+                    taken.insert((j, inv));
+                }
+            }
+            let (source, target) = (drafts[i].id.clone(), drafts[j].id.clone());
+            add_ref(&mut drafts[i].data, &rel.name, target, rel.cardinality);
+            if let Some(inv) = inverse {
+                add_ref(&mut drafts[j].data, inv, source, rel.cardinality);
+            }
+        }
+    }
+}
+
+fn add_ref(data: &mut BTreeMap<String, Value>, field: &str, id: String, cardinality: Cardinality) {
+    let id = Value::String(id);
+    if cardinality == Cardinality::One {
+        data.insert(field.to_string(), id);
+        return;
+    }
+    let entry = data.entry(field.to_string()).or_insert_with(|| Value::Sequence(Vec::new()));
+    match entry {
+        Value::Sequence(seq) if !seq.contains(&id) => seq.push(id),
+        Value::Sequence(_) => {}
+        other => *other = Value::Sequence(vec![other.clone(), id]),
+    }
+}
+
+/// Candidate variants for every code, one per type that offers something to change.
+fn variants(drafts: &[Draft], schema: &Schema, users: Option<&UserConfig>) -> Vec<Variant> {
+    // The first document of each type is the base of its variants
+    let mut bases: Vec<&Draft> = Vec::new();
+    for d in drafts {
+        if !d.type_def.singleton && !bases.iter().any(|b| b.type_def.name == d.type_def.name) {
+            bases.push(d);
+        }
+    }
+
+    let mut next: BTreeMap<&str, usize> = BTreeMap::new();
+    let mut out = Vec::new();
+    for &code in CODES {
+        for &base in &bases {
+            let type_def = base.type_def;
+            let n = next.entry(type_def.name.as_str()).or_insert(VARIANT_BASE);
+            let mut draft = base.clone();
+            draft.file = numbered_file(type_def, *n);
+            draft.id = crate::graph::path_to_id(Path::new(&draft.file));
+            if draft.data.get("title").is_some_and(Value::is_string) {
+                draft.data.insert("title".into(), Value::String(format!("Example {} document {n}", type_def.name)));
+            }
+            // The base's relations would claim edges its targets don't confirm
+            draft.data.retain(|key, _| schema.find_relation(key).is_none());
+
+            if let Some(files) = mutate(code, draft, base, drafts, schema, users) {
+                *n += files.len();
+                out.push(Variant { code, files });
+            }
+        }
+    }
+    out
+}
+
+/// `draft`, a renumbered copy of `base`, changed to raise `code`; `None` when this
+/// type offers nothing to change for it.
+fn mutate(
+    code: &'static str,
+    mut draft: Draft,
+    base: &Draft,
+    drafts: &[Draft],
+    schema: &Schema,
+    users: Option<&UserConfig>,
+) -> Option<Vec<FixtureFile>> {
+    let type_def = base.type_def;
+    let fields = &type_def.fields;
+    let field_of = |pred: &dyn Fn(&FieldDef) -> bool| {
+        fields.iter().find(|f| pred(f) && draft.data.contains_key(&f.name))
+    };
+    // A valid document of another type than the allowed ones, or the first of an allowed type
+    let other_type = |allowed: &[String]| {
+        drafts
+            .iter()
+            .find(|d| !d.type_def.singleton && !allowed.iter().any(|t| *t == d.type_def.name))
+            .map(|d| d.id.clone())
+    };
+    let allowed_type = |allowed: &[String]| {
+        drafts
+            .iter()
+            .find(|d| !d.type_def.singleton && allows(allowed, &d.type_def.name))
+            .map(|d| d.id.clone())
+    };
+
+    match code {
+        "E000" => {
+            let content = format!("---\ntype: {}\ntitle: [unclosed\n---\n{}", type_def.name, draft.body);
+            return Some(vec![FixtureFile { file: draft.file, content }]);
+        }
+        "F002" => {
+            draft.data.insert("type".into(), Value::String("no-such-type".into()));
+        }
+        "F010" => {
+            let field = field_of(&|f: &FieldDef| f.required)?;
+            draft.data.remove(&field.name);
+        }
+        "F011" => {
+            let field = field_of(&|f: &FieldDef| !f.aliases.is_empty())?;
+            let value = draft.data.remove(&field.name)?;
+            draft.data.insert(field.aliases[0].clone(), value);
+        }
+        "F020" => {
+            let field = field_of(&|f: &FieldDef| matches!(f.field_type, FieldType::Number | FieldType::Bool))?;
+            draft.data.insert(field.name.clone(), Value::String("not-a-value".into()));
+        }
+        "F021" => {
+            let field = field_of(&|f: &FieldDef| matches!(f.field_type, FieldType::Enum(_)))?;
+            draft.data.insert(field.name.clone(), Value::String("no-such-value".into()));
+        }
+        "F030" => {
+            let field = field_of(&|f: &FieldDef| f.pattern.is_some() && f.field_type == FieldType::String)?;
+            draft.data.insert(field.name.clone(), Value::String("!".into()));
+        }
+        "F031" => {
+            let field = field_of(&|f: &FieldDef| {
+                matches!(f.field_type, FieldType::Date | FieldType::DateTime)
+                    && (f.max.is_some() || f.not_future || f.min.is_some())
+            })?;
+            let date = if field.max.is_some() || field.not_future { "9999-12-31" } else { "0001-01-01" };
+            let value = match field.field_type {
+                FieldType::DateTime => format!("{date}T00:00:00Z"),
+                _ => date.to_string(),
+            };
+            draft.data.insert(field.name.clone(), Value::String(value));
+        }
+        "F040" => {
+            draft.data.insert(crate::uid::UID_FIELD.into(), Value::String("not-a-uid".into()));
+        }
+        "F041" => {
+            let uid = Value::String(crate::uid::generate());
+            draft.data.insert(crate::uid::UID_FIELD.into(), uid);
+            let mut twin = draft.clone();
+            let n = first_number(&draft.id)? + 1;
+            twin.file = numbered_file(type_def, n);
+            if let Some(title) = twin.data.get_mut("title").filter(|t| t.is_string()) {
+                *title = Value::String(format!("Example {} document {n}", type_def.name));
+            }
+            return Some(vec![draft.render(), twin.render()]);
+        }
+        "I001" => {
+            // Sorts after the original, which keeps the ID
+            draft.file = base.file.replace(".md", "_copy.md");
+            draft.data.insert("title".into(), Value::String(format!("Copy of {}", base.id)));
+        }
+        "I002" => {
+            let title = base.data.get("title").filter(|t| t.is_string())?;
+            draft.data.insert("title".into(), title.clone());
+        }
+        "R011" | "R012" => {
+            let field = field_of(&|f: &FieldDef| {
+                matches!(f.field_type, FieldType::Ref | FieldType::RefArray) && schema.find_relation(&f.name).is_none()
+            });
+            let (name, allowed, many) = match field {
+                Some(f) => (f.name.clone(), ref_targets(f, schema).to_vec(), f.field_type == FieldType::RefArray),
+                None => {
+                    let rel = schema.relations.iter().find(|r| code == "R011" || !r.target_types.is_empty())?;
+                    (rel.name.clone(), rel.target_types.clone(), rel.cardinality == Cardinality::Many)
+                }
+            };
+            let target = if code == "R011" {
+                let prefix = allowed_type(&allowed)?;
+                format!("{}-999", prefix.rsplit_once('-').map_or(prefix.as_str(), |(p, _)| p))
+            } else {
+                if allowed.is_empty() {
+                    return None;
+                }
+                other_type(&allowed)?
+            };
+            let value = Value::String(target);
+            draft.data.insert(name, if many { Value::Sequence(vec![value]) } else { value });
+        }
+        "R020" => {
+            let rel = schema.relations.iter().find(|r| r.cardinality == Cardinality::Many)?;
+            let target = Value::String(allowed_type(&rel.target_types)?);
+            draft.data.insert(rel.name.clone(), Value::Sequence(vec![target.clone(), target]));
+        }
+        "R021" => {
+            let rel = schema.relations.iter().find(|r| r.cardinality == Cardinality::One)?;
+            let target = Value::String(allowed_type(&rel.target_types)?);
+            draft.data.insert(rel.name.clone(), Value::Sequence(vec![target]));
+        }
+        "R022" => {
+            // Both directions of the same pair, from one side
+            let rel = schema.relations.iter().find(|r| r.inverse.as_ref().is_some_and(|inv| *inv != r.name))?;
+            if !allows(&rel.target_types, &type_def.name) {
+                return None;
+            }
+            let target = drafts.iter().find(|d| d.id != base.id && d.type_def.name == type_def.name)?;
+            let value = |id: &str| match rel.cardinality {
+                Cardinality::One => Value::String(id.to_string()),
+                Cardinality::Many => Value::Sequence(vec![Value::String(id.to_string())]),
+            };
+            draft.data.insert(rel.name.clone(), value(&target.id));
+            draft.data.insert(rel.inverse.clone()?, value(&target.id));
+        }
+        "S010" | "S020" | "S021" | "S030" | "S031" | "S032" => {
+            let mut changed = type_def.clone();
+            if !strip_section(&mut changed.sections, code) {
+                return None;
+            }
+            draft.body = crate::template::example_parts(&changed, schema, users).1;
+        }
+        "A010" => {
+            draft.body.push_str("\n![Missing diagram](missing-diagram.png)\n");
+        }
+        "Q001" => {
+            draft.body.push_str("\n```md-db-query\nnot a query\n```\n");
+        }
+        "T010" => {
+            let max = type_def.max_count?;
+            if drafts.iter().filter(|d| d.type_def.name == type_def.name).count() < max {
+                return None;
+            }
+        }
+        "U010" => {
+            let field = field_of(&|f: &FieldDef| f.field_type == FieldType::User)?;
+            draft.data.insert(field.name.clone(), Value::String("no-at-sign".into()));
+        }
+        "U011" => {
+            users?;
+            let field = field_of(&|f: &FieldDef| f.field_type == FieldType::User)?;
+            draft.data.insert(field.name.clone(), Value::String("@no-such-user".into()));
+        }
+        "U012" => {
+            users?;
+            draft.body.push_str("\nAsk @no-such-user for details.\n");
+        }
+        _ => return None,
+    }
+    Some(vec![draft.render()])
+}
+
+fn first_number(id: &str) -> Option<usize> {
+    id.rsplit_once('-')?.1.parse().ok()
+}
+
+/// Drop the part of the first section that `code` requires, so the regenerated body
+/// lacks it. Returns whether any section had one.
+fn strip_section(sections: &mut Vec<SectionDef>, code: &str) -> bool {
+    if code == "S010" {
+        if let Some(i) = sections.iter().position(|s| s.required) {
+            sections.remove(i);
+            return true;
+        }
+    }
+    for section in sections.iter_mut() {
+        let stripped = match code {
+            "S020" if section.table.as_ref().is_some_and(|t| t.required) => {
+                section.table = None;
+                true
+            }
+            "S021" => section.table.as_mut().is_some_and(|t| {
+                let required = t.columns.iter().position(|c| c.required);
+                required.map(|i| t.columns.remove(i)).is_some()
+            }),
+            "S030" if section.content.as_ref().and_then(|c| c.min_paragraphs).is_some_and(|n| n > 1) => {
+                section.content = None;
+                true
+            }
+            "S031" if section.list.as_ref().is_some_and(|l| l.required) => {
+                section.list = None;
+                true
+            }
+            "S032" if section.diagram.as_ref().is_some_and(|d| d.required) => {
+                section.diagram = None;
+                true
+            }
+            _ => false,
+        };
+        if stripped || strip_section(&mut section.children, code) {
+            return true;
+        }
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generate_fixtures() {
+        let content = std::fs::read_to_string("../../tests/fixtures/schema.kdl").unwrap();
+        let schema = Schema::from_str(&content).unwrap();
+        let users = UserConfig::from_file("../../tests/fixtures/users.yaml").unwrap();
+        let dir = tempfile::tempdir().unwrap();
+
+        let opts = FixtureOptions {
+            per_type: 2,
+            link: true,
+            with_errors: true,
+        };
+        let corpus = generate(&schema, Some(&users), &opts);
+        assert!(corpus.valid.iter().any(|f| f.file == "adr-002.md"));
+        let report = write(&corpus, dir.path(), &schema, Some(&users)).unwrap();
+        assert_eq!(report.valid.total_errors(), 0, "{:?}", report.valid.file_results);
+
+        // Each kept variant raises its code, and every code is raised once at most
+        let codes: Vec<&String> = report.expected.values().collect();
+        for code in ["F010", "F021", "I002", "R011", "S010", "U011"] {
+            assert!(codes.iter().any(|c| *c == code), "no variant for {code}: {codes:?}");
+        }
+        let expected = std::fs::read_to_string(dir.path().join(INVALID_DIR).join(EXPECTED_FILE)).unwrap();
+        let expected: BTreeMap<String, String> = serde_json::from_str(&expected).unwrap();
+        assert_eq!(expected, report.expected);
+
+        // A second run refuses to overwrite
+        assert!(write(&corpus, dir.path(), &schema, Some(&users)).is_err());
+    }
+}
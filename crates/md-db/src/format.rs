@@ -0,0 +1,390 @@
+//! Canonical formatting for managed documents (`md-db fmt`).
+//!
+//! [`format`] lays a document out one way: frontmatter keys in schema order with
+//! serde_yaml's minimal quoting, ATX headings without skipped levels, pipe tables
+//! padded to their column widths, and the whitespace rules of
+//! [`normalize::format_body`](crate::normalize::format_body). Unlike normalization
+//! it never changes a value, so `fmt --check` is safe to run in CI. The result is
+//! idempotent.
+
+use comrak::nodes::{NodeValue, TableAlignment};
+use comrak::Arena;
+use serde_yaml::{Mapping, Value};
+
+use crate::ast_util;
+use crate::document::Document;
+use crate::normalize::{self, Change, ChangeKind, NormalizeOptions, Normalized};
+use crate::schema::{LintKind, Schema, TypeDef};
+
+/// Format a managed document. Documents whose type the schema doesn't know are
+/// returned unchanged.
+pub fn format(doc: &Document, schema: &Schema) -> Normalized {
+    let Some(type_def) = normalize::type_def_for(doc, schema) else {
+        return Normalized {
+            content: doc.raw.clone(),
+            changes: Vec::new(),
+        };
+    };
+    let mut changes = Vec::new();
+
+    let head = normalize::frontmatter_block(&doc.raw);
+    let head = match head {
+        Some(block) => format_frontmatter(block, doc, type_def, schema, &mut changes),
+        None => String::new(),
+    };
+
+    let mut body = format_blocks(&doc.body, &mut changes);
+    let formatted = normalize::format_body(&body);
+    if formatted != body {
+        changes.push(change("normalized whitespace and blank lines".into()));
+        body = formatted;
+    }
+
+    let content = if doc.frontmatter.is_some() {
+        format!("{head}{body}")
+    } else {
+        body
+    };
+    Normalized { content, changes }
+}
+
+/// [`normalize`](crate::normalize::normalize) and then [`format`]: the coercions,
+/// heading aliases, and timestamps of normalization in the canonical layout. Backs
+/// `fmt --normalize` and the LSP's formatting request; the changes of both passes
+/// are listed.
+pub fn normalize_and_format(doc: &Document, schema: &Schema, opts: &NormalizeOptions) -> Normalized {
+    let normalized = normalize::normalize(doc, schema, opts);
+    let Ok(mut next) = Document::from_str(&normalized.content) else {
+        return normalized;
+    };
+    next.path = doc.path.clone();
+    let formatted = format(&next, schema);
+    let mut changes = normalized.changes;
+    changes.extend(formatted.changes);
+    Normalized {
+        content: formatted.content,
+        changes,
+    }
+}
+
+fn change(description: String) -> Change {
+    Change {
+        kind: ChangeKind::Format,
+        description,
+    }
+}
+
+/// Key order: the lint `frontmatter-order` keys when set, else `type` and the
+/// type's fields; other keys follow in their original order.
+fn key_order(block: &str, type_def: &TypeDef, schema: &Schema) -> Vec<String> {
+    let configured = schema.lint.as_ref().and_then(|lint| {
+        lint.rules.iter().find_map(|rule| match &rule.kind {
+            LintKind::FrontmatterOrder(keys) if !keys.is_empty() => Some(keys.clone()),
+            _ => None,
+        })
+    });
+    let mut order = configured.unwrap_or_else(|| {
+        std::iter::once("type".to_string())
+            .chain(type_def.fields.iter().map(|f| f.name.clone()))
+            .collect()
+    });
+    for line in block.lines() {
+        if line.starts_with([' ', '\t', '-', '#']) || line.trim_end() == "---" {
+            continue;
+        }
+        if let Some((key, _)) = line.split_once(':') {
+            let key = key.trim().trim_matches(|c| c == '"' || c == '\'');
+            if !key.is_empty() && !order.iter().any(|k| k == key) {
+                order.push(key.to_string());
+            }
+        }
+    }
+    order
+}
+
+/// Reserialize the frontmatter in key order. Blocks with comments are kept as
+/// written, since serde_yaml would drop them.
+fn format_frontmatter(
+    block: &str,
+    doc: &Document,
+    type_def: &TypeDef,
+    schema: &Schema,
+    changes: &mut Vec<Change>,
+) -> String {
+    let Some(fm) = doc.frontmatter.as_ref() else {
+        return block.to_string();
+    };
+    if block.lines().any(|l| l.trim_start().starts_with('#')) {
+        return block.to_string();
+    }
+
+    let mut map = Mapping::new();
+    for key in key_order(block, type_def, schema) {
+        if let Some(value) = fm.data().get(&key) {
+            map.insert(Value::String(key), value.clone());
+        }
+    }
+    // Keys the line scan missed (quoted or flow-style mappings)
+    for (key, value) in fm.data() {
+        let key = Value::String(key.clone());
+        if !map.contains_key(&key) {
+            map.insert(key, value.clone());
+        }
+    }
+
+    let yaml = if map.is_empty() {
+        String::new()
+    } else {
+        serde_yaml::to_string(&Value::Mapping(map)).unwrap_or_default()
+    };
+    let formatted = format!("---\n{yaml}---\n");
+    if formatted == block {
+        return formatted;
+    }
+    changes.push(change("frontmatter keys in schema order with canonical quoting".into()));
+    formatted
+}
+
+/// Rewrite top-level headings and tables line by line.
+fn format_blocks(body: &str, changes: &mut Vec<Change>) -> String {
+    let lines: Vec<&str> = body.split_inclusive('\n').collect();
+    let line_text = |n: usize| lines.get(n - 1).map_or("", |l| l.trim_end_matches(['\n', '\r']));
+
+    let arena = Arena::new();
+    let opts = ast_util::comrak_opts();
+    let root = comrak::parse_document(&arena, body, &opts);
+
+    // (first line, last line, replacement lines), all 1-based
+    let mut rewrites: Vec<(usize, usize, Vec<String>)> = Vec::new();
+    // (original level, new level) of the enclosing headings
+    let mut stack: Vec<(u8, u8)> = Vec::new();
+    let mut tables = 0usize;
+
+    for node in root.children() {
+        let data = node.data.borrow();
+        let (start, end) = (data.sourcepos.start.line, data.sourcepos.end.line);
+        match data.value {
+            NodeValue::Heading(ref h) => {
+                while stack.last().is_some_and(|(orig, _)| *orig >= h.level) {
+                    stack.pop();
+                }
+                let level = match stack.last() {
+                    Some((_, parent)) => h.level.min(parent + 1),
+                    None => h.level,
+                };
+                stack.push((h.level, level));
+
+                let text = if h.setext {
+                    (start..end).map(|n| line_text(n).trim()).collect::<Vec<_>>().join(" ")
+                } else {
+                    atx_text(line_text(start)).to_string()
+                };
+                let hashes = "#".repeat(level as usize);
+                let line = if text.is_empty() { hashes } else { format!("{hashes} {text}") };
+                if h.setext || line != line_text(start) {
+                    let what = if level != h.level {
+                        format!("level {} -> {level}", h.level)
+                    } else if h.setext {
+                        "setext -> ATX".into()
+                    } else {
+                        "canonical ATX form".into()
+                    };
+                    changes.push(change(format!("heading \"{text}\": {what}")));
+                    rewrites.push((start, end, vec![line]));
+                }
+            }
+            NodeValue::Table(ref table) => {
+                let rows: Vec<&str> = (start..=end).map(line_text).collect();
+                if let Some(aligned) = align_table(&rows, &table.alignments) {
+                    if aligned.iter().map(String::as_str).ne(rows.iter().copied()) {
+                        tables += 1;
+                        rewrites.push((start, end, aligned));
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    if tables > 0 {
+        changes.push(change(format!("aligned {tables} table(s)")));
+    }
+    if rewrites.is_empty() {
+        return body.to_string();
+    }
+
+    let mut out = String::with_capacity(body.len());
+    let mut n = 1;
+    while n <= lines.len() {
+        match rewrites.iter().find(|(start, _, _)| *start == n) {
+            Some((_, end, replacement)) => {
+                for line in replacement {
+                    out.push_str(line);
+                    out.push('\n');
+                }
+                n = end + 1;
+            }
+            None => {
+                out.push_str(lines[n - 1]);
+                n += 1;
+            }
+        }
+    }
+    out
+}
+
+/// An ATX heading's text without the opening hashes or closing sequence.
+fn atx_text(line: &str) -> &str {
+    let rest = line.trim_start().trim_start_matches('#').trim();
+    let open = rest.trim_end_matches('#');
+    if open.len() < rest.len() && (open.is_empty() || open.ends_with([' ', '\t'])) {
+        open.trim_end()
+    } else {
+        rest
+    }
+}
+
+/// A pipe table padded to its column widths, or `None` when a row has more
+/// cells than the header (GFM drops them; rewriting would lose text).
+fn align_table(rows: &[&str], alignments: &[TableAlignment]) -> Option<Vec<String>> {
+    let cells: Vec<Vec<String>> = rows.iter().map(|r| split_row(r)).collect();
+    let columns = cells.first()?.len();
+    if columns == 0 || cells.iter().any(|row| row.len() > columns) {
+        return None;
+    }
+    let align: Vec<TableAlignment> =
+        (0..columns).map(|i| alignments.get(i).copied().unwrap_or(TableAlignment::None)).collect();
+
+    let mut widths = vec![3usize; columns];
+    for (r, row) in cells.iter().enumerate() {
+        if r == 1 {
+            continue;
+        }
+        for (i, cell) in row.iter().enumerate() {
+            widths[i] = widths[i].max(cell.chars().count());
+        }
+    }
+
+    let render = |row: &[String]| {
+        let padded: Vec<String> = (0..columns)
+            .map(|i| {
+                let cell = row.get(i).map_or("", String::as_str);
+                pad(cell, widths[i], align[i])
+            })
+            .collect();
+        format!("| {} |", padded.join(" | "))
+    };
+    let delimiter: Vec<String> = (0..columns).map(|i| delimiter(widths[i], align[i])).collect();
+
+    let mut out = vec![render(&cells[0]), format!("| {} |", delimiter.join(" | "))];
+    out.extend(cells.iter().skip(2).map(|row| render(row)));
+    Some(out)
+}
+
+fn pad(cell: &str, width: usize, align: TableAlignment) -> String {
+    let fill = width.saturating_sub(cell.chars().count());
+    match align {
+        TableAlignment::Right => format!("{}{cell}", " ".repeat(fill)),
+        TableAlignment::Center => format!("{}{cell}{}", " ".repeat(fill / 2), " ".repeat(fill - fill / 2)),
+        TableAlignment::Left | TableAlignment::None => format!("{cell}{}", " ".repeat(fill)),
+    }
+}
+
+fn delimiter(width: usize, align: TableAlignment) -> String {
+    match align {
+        TableAlignment::Left => format!(":{}", "-".repeat(width - 1)),
+        TableAlignment::Right => format!("{}:", "-".repeat(width - 1)),
+        TableAlignment::Center => format!(":{}:", "-".repeat(width - 2)),
+        TableAlignment::None => "-".repeat(width),
+    }
+}
+
+/// Split a table row on unescaped pipes, trimming the cells.
+fn split_row(row: &str) -> Vec<String> {
+    let row = row.trim();
+    let row = row.strip_prefix('|').unwrap_or(row);
+    let row = match row.strip_suffix('|') {
+        Some(rest) if !rest.ends_with('\\') => rest,
+        _ => row,
+    };
+    let mut cells = Vec::new();
+    let mut cell = String::new();
+    let mut escaped = false;
+    for c in row.chars() {
+        if c == '|' && !escaped {
+            cells.push(cell.trim().to_string());
+            cell.clear();
+        } else {
+            cell.push(c);
+        }
+        escaped = c == '\\' && !escaped;
+    }
+    cells.push(cell.trim().to_string());
+    cells
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn schema() -> Schema {
+        Schema::from_str(
+            r#"
+type "adr" {
+    field "title" type="string" required=#true
+    field "status" type="string"
+    field "author" type="string"
+    section "Context"
+}
+"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_format_document() {
+        let raw = "---\nauthor: \"onni\"\nstatus: 'accepted'\ntype: adr\ntitle: T\ntags: [a, b]\n---\n\nContext\n=======\nWhy.   \n\n### Options ##\n\n|Name|Score|\n|:-|--:|\n| Redis | 10 |\n|Memcached|9|\n\n#### Notes\n";
+        let doc = Document::from_str(raw).unwrap();
+        let result = format(&doc, &schema());
+
+        assert_eq!(
+            result.content,
+            "---\ntype: adr\ntitle: T\nstatus: accepted\nauthor: onni\ntags:\n- a\n- b\n---\n\n# Context\n\nWhy.\n\n## Options\n\n| Name      | Score |\n| :-------- | ----: |\n| Redis     |    10 |\n| Memcached |     9 |\n\n### Notes\n"
+        );
+        let descriptions: Vec<&str> = result.changes.iter().map(|c| c.description.as_str()).collect();
+        assert!(descriptions.contains(&"heading \"Options\": level 3 -> 2"), "{descriptions:?}");
+        assert!(descriptions.contains(&"aligned 1 table(s)"), "{descriptions:?}");
+
+        let again = format(&Document::from_str(&result.content).unwrap(), &schema());
+        assert!(!again.changed(), "{:?}", again.changes);
+    }
+
+    #[test]
+    fn test_format_keeps_commented_frontmatter_and_unmanaged_docs() {
+        let raw = "---\n# owner: platform\ntitle: T\ntype: adr\n---\n\n# Context\n\nWhy.\n";
+        let doc = Document::from_str(raw).unwrap();
+        assert!(!format(&doc, &schema()).changed());
+
+        let raw = "# Readme\n\n|a|b|\n|-|-|\n";
+        let doc = Document::from_str(raw).unwrap();
+        assert_eq!(format(&doc, &schema()).content, raw);
+    }
+
+    #[test]
+    fn test_normalize_and_format() {
+        let schema = Schema::from_str(
+            "type \"adr\" {\n    field \"title\" type=\"string\"\n    field \"count\" type=\"number\"\n}\n",
+        )
+        .unwrap();
+        let doc = Document::from_str("---\ncount: \"3\"\ntype: adr\ntitle: T\n---\n\nWhy.   \n").unwrap();
+        let result = normalize_and_format(&doc, &schema, &NormalizeOptions::default());
+        assert_eq!(result.content, "---\ntype: adr\ntitle: T\ncount: 3\n---\n\nWhy.\n");
+        assert!(result.changes.iter().any(|c| c.kind == ChangeKind::Coerce), "{:?}", result.changes);
+        assert!(result.changes.iter().any(|c| c.kind == ChangeKind::Format), "{:?}", result.changes);
+    }
+
+    #[test]
+    fn test_split_row_escaped_pipe() {
+        assert_eq!(split_row(r"| a \| b | c |"), vec![r"a \| b", "c"]);
+        assert_eq!(split_row("a|b"), vec!["a", "b"]);
+    }
+}
@@ -1,10 +1,16 @@
 use std::collections::BTreeMap;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
 
 use gray_matter::{engine::YAML, Matter};
 use serde_yaml::Value;
 
 use crate::error::{Error, Result};
 
+/// Largest frontmatter block [`Frontmatter::read_from_file`] reads line by line
+/// before falling back to parsing the whole file.
+const MAX_FRONTMATTER_BYTES: usize = 64 * 1024;
+
 #[derive(Debug, Clone)]
 pub struct Frontmatter {
     data: BTreeMap<String, Value>,
@@ -35,6 +41,38 @@ impl Frontmatter {
         }
     }
 
+    /// Read only the frontmatter of a file, stopping at the closing `---`; the body
+    /// is neither read nor parsed. Returns `None` if the file has no frontmatter.
+    /// Unterminated or oversized blocks fall back to a full [`Self::try_parse`].
+    pub fn read_from_file(path: impl AsRef<Path>) -> Result<Option<Self>> {
+        let path = path.as_ref();
+        let mut reader = BufReader::new(std::fs::File::open(path)?);
+        let mut line = String::new();
+        reader.read_line(&mut line)?;
+        if line.trim_end() != "---" {
+            return Ok(None);
+        }
+
+        let mut yaml = String::new();
+        loop {
+            line.clear();
+            if reader.read_line(&mut line)? == 0 || yaml.len() > MAX_FRONTMATTER_BYTES {
+                let content = std::fs::read_to_string(path)?;
+                return Self::try_parse(&content).map(|(fm, _)| fm);
+            }
+            if line.trim_end() == "---" {
+                break;
+            }
+            yaml.push_str(&line);
+        }
+
+        if yaml.trim().is_empty() {
+            return Ok(None);
+        }
+        let data = serde_yaml::from_str(&yaml).map_err(|e| Error::FrontmatterParse(e.to_string()))?;
+        Ok(Some(Self { data }))
+    }
+
     /// Get a value by dotted path (e.g. "links.superseded_by").
     pub fn get(&self, path: &str) -> Option<&Value> {
         let parts: Vec<&str> = path.split('.').collect();
@@ -112,12 +150,52 @@ impl Frontmatter {
         self.data.remove(key)
     }
 
+    /// Apply an RFC 7386 JSON Merge Patch: `null` removes a key, objects merge
+    /// recursively, anything else (arrays included) replaces the value.
+    pub fn merge_patch(&mut self, patch: &serde_json::Value) -> Result<()> {
+        let serde_json::Value::Object(obj) = patch else {
+            return Err(Error::InvalidFieldValue("merge patch must be a JSON object".into()));
+        };
+        for (key, value) in obj {
+            if value.is_null() {
+                self.data.remove(key);
+            } else {
+                let merged = merge_value(self.data.remove(key), value)?;
+                self.data.insert(key.clone(), merged);
+            }
+        }
+        Ok(())
+    }
+
     /// Serialize as YAML string (infallible for BTreeMap).
     pub fn to_yaml_string(&self) -> String {
         serde_yaml::to_string(&self.data).unwrap_or_default()
     }
 }
 
+/// Merge-patch one value. Non-mapping targets are replaced when the patch is an object.
+fn merge_value(target: Option<Value>, patch: &serde_json::Value) -> Result<Value> {
+    let serde_json::Value::Object(obj) = patch else {
+        return Ok(serde_yaml::to_value(patch)?);
+    };
+    let mut map = match target {
+        Some(Value::Mapping(map)) => map,
+        _ => serde_yaml::Mapping::new(),
+    };
+    for (key, value) in obj {
+        let key = Value::String(key.clone());
+        if value.is_null() {
+            map.remove(&key);
+        } else if let Some(slot) = map.get_mut(&key) {
+            let old = std::mem::replace(slot, Value::Null);
+            *slot = merge_value(Some(old), value)?;
+        } else {
+            map.insert(key, merge_value(None, value)?);
+        }
+    }
+    Ok(Value::Mapping(map))
+}
+
 pub fn yaml_value_to_string(v: &Value) -> String {
     match v {
         Value::Null => "null".to_string(),
@@ -133,6 +211,62 @@ pub fn yaml_value_to_string(v: &Value) -> String {
     }
 }
 
+/// A value as it reads after `key: ` on one line.
+pub fn yaml_inline(value: &Value) -> String {
+    match value {
+        Value::Sequence(_) | Value::Mapping(_) => serde_json::to_string(&yaml_to_json(value)).unwrap_or_default(),
+        _ => serde_yaml::to_string(value).unwrap_or_default().trim_end().to_string(),
+    }
+}
+
+/// Set top-level `key: value` lines in the frontmatter, keeping the rest of its
+/// text (comments, key order, quoting) as written. `values` are inline YAML, as
+/// from [`yaml_inline`]. Adds a frontmatter block when there is none.
+pub fn set_frontmatter_lines(content: &str, values: &[(&str, String)]) -> String {
+    let Some(block) = crate::normalize::frontmatter_block(content) else {
+        let mut out = String::from("---\n");
+        for (key, value) in values {
+            out.push_str(&format!("{key}: {value}\n"));
+        }
+        out.push_str("---\n");
+        out.push_str(content);
+        return out;
+    };
+
+    let mut lines: Vec<String> = block.lines().map(String::from).collect();
+    for (key, value) in values {
+        let line = format!("{key}: {value}");
+        let found = lines.iter().position(|l| {
+            !l.starts_with([' ', '\t', '-', '#'])
+                && l.split_once(':').is_some_and(|(k, _)| k.trim().trim_matches(['"', '\'']) == *key)
+        });
+        match found {
+            Some(i) => {
+                // Drop the old value's continuation lines (nested or list items)
+                let mut end = i + 1;
+                while end < lines.len() - 1 && lines[end].starts_with([' ', '\t', '-']) {
+                    end += 1;
+                }
+                lines.splice(i..end, [line]);
+            }
+            None => {
+                let close = lines.len() - 1;
+                lines.insert(close, line);
+            }
+        }
+    }
+    let mut out = lines.join("\n");
+    out.push('\n');
+    out.push_str(&content[block.len()..]);
+    out
+}
+
+/// The content after its frontmatter block, or all of it when there is none.
+pub fn strip_frontmatter(content: &str) -> &str {
+    let block = crate::normalize::frontmatter_block(content).map_or(0, str::len);
+    &content[block..]
+}
+
 /// Parse a string into a YAML value, trying bool/number/sequence before falling back to string.
 pub fn parse_yaml_value(s: &str) -> Value {
     let trimmed = s.trim();
@@ -260,6 +394,30 @@ mod tests {
         assert!(!fm.has_field("status"));
     }
 
+    #[test]
+    fn test_merge_patch() {
+        let raw = "---\nstatus: proposed\nowner: \"@bob\"\nmeta:\n  review: 1\n  team: core\n---\n";
+        let (mut fm, _) = Frontmatter::parse(raw).unwrap();
+        let patch = serde_json::json!({
+            "status": "accepted",
+            "owner": null,
+            "reviewers": ["@alice"],
+            "priority": 2,
+            "meta": { "review": null, "signed": true },
+        });
+        fm.merge_patch(&patch).unwrap();
+
+        assert_eq!(fm.get_display("status"), Some("accepted".into()));
+        assert!(!fm.has_field("owner"));
+        assert!(matches!(fm.get("reviewers"), Some(Value::Sequence(s)) if s.len() == 1));
+        assert!(matches!(fm.get("priority"), Some(Value::Number(_))));
+        assert!(fm.get("meta.review").is_none());
+        assert_eq!(fm.get_display("meta.team"), Some("core".into()));
+        assert_eq!(fm.get("meta.signed"), Some(&Value::Bool(true)));
+
+        assert!(fm.merge_patch(&serde_json::json!(["not", "an", "object"])).is_err());
+    }
+
     #[test]
     fn test_set_from_str() {
         let mut fm = Frontmatter::from_data(BTreeMap::new());
@@ -302,4 +460,42 @@ mod tests {
         );
         assert!(matches!(parse_yaml_value("[a, b]"), Value::Sequence(_)));
     }
+
+    #[test]
+    fn test_read_from_file_stops_at_delimiter() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("doc.md");
+        // The body isn't valid UTF-8, so reading the whole file would fail
+        let mut bytes = b"---\ntitle: Fast\ntags: [a, b]\n---\n\n# Body\n".to_vec();
+        bytes.extend_from_slice(&[0xff, 0xfe, 0xfd]);
+        std::fs::write(&path, bytes).unwrap();
+        let fm = Frontmatter::read_from_file(&path).unwrap().unwrap();
+        assert_eq!(fm.get_display("title").as_deref(), Some("Fast"));
+        assert!(std::fs::read_to_string(&path).is_err());
+
+        std::fs::write(&path, "# No frontmatter\n").unwrap();
+        assert!(Frontmatter::read_from_file(&path).unwrap().is_none());
+
+        // Same result as a full parse on an ordinary file
+        let content = "---\ntitle: Same\nstatus: ok\n---\nbody\n";
+        std::fs::write(&path, content).unwrap();
+        let fast = Frontmatter::read_from_file(&path).unwrap().unwrap();
+        let full = Frontmatter::parse(content).unwrap().0;
+        assert_eq!(fast.data(), full.data());
+    }
+
+    #[test]
+    fn test_set_frontmatter_lines_keeps_formatting() {
+        let content = "---\n# owner comment\nzeta: 1\ntags:\n  - a\n  - b\ntitle: 'Old'\n---\nBody  \n";
+        let out = set_frontmatter_lines(
+            content,
+            &[("tags", yaml_inline(&parse_yaml_value("[x]"))), ("new", "yes".into())],
+        );
+        assert_eq!(out, "---\n# owner comment\nzeta: 1\ntags: [\"x\"]\ntitle: 'Old'\nnew: yes\n---\nBody  \n");
+        assert_eq!(strip_frontmatter(&out), "Body  \n");
+
+        let added = set_frontmatter_lines("# Notes\n", &[("title", "Notes".into())]);
+        assert_eq!(added, "---\ntitle: Notes\n---\n# Notes\n");
+        assert_eq!(strip_frontmatter("# Notes\n"), "# Notes\n");
+    }
 }
@@ -1,15 +1,18 @@
 use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
 use std::path::{Path, PathBuf};
 
+use serde::{Deserialize, Serialize};
+
 use crate::ast_util;
 use crate::document::Document;
 use crate::error::Result;
+use crate::graph_cache::GraphSnapshot;
 use crate::schema::Schema;
 
 /// A structural diagnostic found during graph health checks.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct GraphDiagnostic {
-    /// Diagnostic code: G010 (cycle), G011 (self-ref), G020 (orphan), G021 (disconnected), G030 (dangling ref)
+    /// Diagnostic code: G010 (cycle), G011 (self-ref), G020 (orphan), G021 (disconnected), G030 (dangling ref), G040 (coverage)
     pub code: String,
     /// "error", "warning", or "info"
     pub severity: String,
@@ -18,7 +21,7 @@ pub struct GraphDiagnostic {
 }
 
 /// A node in the document graph.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DocNode {
     /// Canonical ID derived from filename (e.g. "ADR-001")
     pub id: String,
@@ -26,10 +29,16 @@ pub struct DocNode {
     pub doc_type: Option<String>,
     pub title: Option<String>,
     pub status: Option<String>,
+    /// Stable UUID from the `uid` frontmatter field, if present
+    #[serde(default)]
+    pub uid: Option<String>,
 }
 
+/// Digits in the number of a generated ID (`ADR-001`), unless existing IDs are wider.
+pub const ID_WIDTH: usize = 3;
+
 /// A directed edge (reference) between two documents.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DocEdge {
     pub from: String,
     pub to: String,
@@ -37,6 +46,23 @@ pub struct DocEdge {
     pub relation: String,
 }
 
+/// Edges changed by [`DocGraph::apply_changes`], keyed on the files that were re-scanned.
+#[derive(Debug, Clone, Default)]
+pub struct GraphDelta {
+    /// Edges present after the change but not before.
+    pub added: Vec<DocEdge>,
+    /// Edges present before the change but not after.
+    pub removed: Vec<DocEdge>,
+    /// Node IDs whose file was deleted or stopped being a document.
+    pub removed_nodes: Vec<String>,
+}
+
+impl GraphDelta {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.removed_nodes.is_empty()
+    }
+}
+
 /// The document graph built from a directory of markdown files.
 #[derive(Debug)]
 pub struct DocGraph {
@@ -50,8 +76,10 @@ impl DocGraph {
         let files = crate::discovery::discover_files(&dir, None, &[], false)?;
         let relation_names = schema.all_relation_field_names();
 
-        let mut nodes = BTreeMap::new();
-        let mut edges = Vec::new();
+        let mut graph = DocGraph {
+            nodes: BTreeMap::new(),
+            edges: Vec::new(),
+        };
 
         for path in &files {
             let doc = match Document::from_file(path) {
@@ -59,94 +87,146 @@ impl DocGraph {
                 Err(_) => continue,
             };
 
-            let id = path_to_id(path);
-            let fm = match &doc.frontmatter {
-                Some(fm) => fm,
-                None => {
-                    // Check if this is a singleton type
-                    let filename = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
-                    let singleton_type = schema.types.iter().find(|t| {
-                        t.singleton && t.match_pattern.as_deref() == Some(filename)
-                    });
-                    if let Some(type_def) = singleton_type {
-                        let id = path_to_id(path);
-                        nodes.insert(
-                            id.clone(),
-                            DocNode {
-                                id: id.clone(),
-                                path: path.clone(),
-                                doc_type: Some(type_def.name.clone()),
-                                title: None,
-                                status: None,
-                            },
-                        );
-                    }
-                    continue;
-                }
-            };
+            if let Some((node, edges)) = scan_document(path, &doc, schema, &relation_names) {
+                graph.nodes.insert(node.id.clone(), node);
+                graph.edges.extend(edges);
+            }
+        }
 
-            let doc_type = fm.get_display("type");
-            let title = fm.get_display("title");
-            let status = fm.get_display("status");
-
-            nodes.insert(
-                id.clone(),
-                DocNode {
-                    id: id.clone(),
-                    path: path.clone(),
-                    doc_type,
-                    title,
-                    status,
-                },
-            );
-
-            // Extract outgoing refs from relation fields
-            for rel_name in &relation_names {
-                if let Some(val) = fm.get(rel_name) {
-                    let refs = extract_refs(val);
-                    for target in refs {
-                        edges.push(DocEdge {
-                            from: id.clone(),
-                            to: target,
-                            relation: rel_name.to_string(),
-                        });
-                    }
-                }
+        graph.resolve_uid_refs();
+        Ok(graph)
+    }
+
+    /// Build a graph from in-memory file contents, e.g. files read at another git revision.
+    /// Files that fail to parse are skipped.
+    pub fn from_contents(files: &[(PathBuf, String)], schema: &Schema) -> Self {
+        let relation_names = schema.all_relation_field_names();
+        let mut graph = DocGraph {
+            nodes: BTreeMap::new(),
+            edges: Vec::new(),
+        };
+
+        for (path, content) in files {
+            let Ok(mut doc) = Document::from_str(content) else {
+                continue;
+            };
+            doc.path = Some(path.clone());
+            if let Some((node, edges)) = scan_document(path, &doc, schema, &relation_names) {
+                graph.nodes.insert(node.id.clone(), node);
+                graph.edges.extend(edges);
             }
+        }
 
-            // Extract inline links from document body
-            let inline_links = ast_util::extract_links(&doc.body);
-            let doc_dir = path.parent();
-            for url in inline_links {
-                let target_id = if url.ends_with(".md") {
-                    // Relative .md path — resolve against doc directory
-                    let link_path = if let Some(dir) = doc_dir {
-                        dir.join(&url)
-                    } else {
-                        PathBuf::from(&url)
-                    };
-                    path_to_id(&link_path)
-                } else if is_string_id(&url) {
-                    // String ID pattern like "ADR-001"
-                    url.to_uppercase()
-                } else {
-                    // External or unrecognized link — skip
-                    continue;
-                };
-
-                // Deduplicate: skip if a frontmatter edge already exists for this pair
-                let already_exists = edges.iter().any(|e| e.from == id && e.to == target_id);
-                if !already_exists {
-                    edges.push(DocEdge {
-                        from: id.clone(),
-                        to: target_id,
-                        relation: "inline_ref".to_string(),
-                    });
-                }
+        graph.resolve_uid_refs();
+        graph
+    }
+
+    /// Build a graph, reusing the on-disk snapshot in `<dir>/.md-db/graph.bin`.
+    /// Only files whose content hash changed since the last run are re-parsed.
+    /// The snapshot is refreshed on a best-effort basis; write failures are ignored.
+    pub fn build_cached(dir: impl AsRef<Path>, schema: &Schema) -> Result<Self> {
+        let dir = dir.as_ref();
+        let files = crate::discovery::discover_files(dir, None, &[], false)?;
+
+        let mut snapshot = GraphSnapshot::load(dir, schema);
+        snapshot.refresh(&files, schema);
+        if snapshot.is_dirty() {
+            let _ = snapshot.save();
+        }
+
+        Ok(snapshot.to_graph())
+    }
+
+    /// The documents within `depth` links of `id` (an ID or uid) in either direction,
+    /// and the edges between them, without building the graph of the whole corpus.
+    /// Backed by the `<dir>/.md-db/graph.bin` snapshot: new files are parsed, and of
+    /// the rest only the visited documents (and files that ref them) are checked for
+    /// changes, so after the first run a lookup costs a `stat` per visited file.
+    /// `refs_from`/`refs_to` (and their transitive forms up to `depth`) give the same
+    /// answers as on the full graph, except for refs added to unvisited files since
+    /// the snapshot was last fully refreshed.
+    pub fn neighborhood(dir: impl AsRef<Path>, schema: &Schema, id: &str, depth: usize) -> Result<Self> {
+        let dir = dir.as_ref();
+        let files = crate::discovery::discover_files(dir, None, &[], false)?;
+
+        let mut snapshot = GraphSnapshot::load(dir, schema);
+        let graph = snapshot.refresh_neighborhood(&files, schema, id, depth);
+        if snapshot.is_dirty() {
+            let _ = snapshot.save();
+        }
+
+        Ok(graph)
+    }
+
+    /// Re-scan a single file and patch its node and outgoing edges in place.
+    /// A file that no longer exists is removed from the graph.
+    pub fn update_file(&mut self, path: &Path, schema: &Schema) {
+        self.apply_changes(&[path.to_path_buf()], schema);
+    }
+
+    /// Re-scan a batch of changed files (created, modified, or deleted) and report
+    /// which outgoing edges changed. Paths must be spelled the way discovery spells
+    /// them (e.g. `docs/adr-001.md` when the graph was built from `docs`).
+    pub fn apply_changes(&mut self, paths: &[PathBuf], schema: &Schema) -> GraphDelta {
+        let ids_for = |graph: &DocGraph| -> HashSet<String> {
+            graph
+                .nodes
+                .values()
+                .filter(|n| paths.contains(&n.path))
+                .map(|n| n.id.clone())
+                .collect()
+        };
+        let edges_for = |graph: &DocGraph, ids: &HashSet<String>| -> Vec<DocEdge> {
+            graph.edges.iter().filter(|e| ids.contains(&e.from)).cloned().collect()
+        };
+
+        let before_ids = ids_for(self);
+        let before = edges_for(self, &before_ids);
+
+        let relation_names = schema.all_relation_field_names();
+        for path in paths {
+            self.remove_file(path);
+            let Ok(doc) = Document::from_file(path) else {
+                continue;
+            };
+            if let Some((node, edges)) = scan_document(path, &doc, schema, &relation_names) {
+                self.nodes.insert(node.id.clone(), node);
+                self.edges.extend(edges);
             }
         }
+        self.resolve_uid_refs();
+
+        let after_ids = ids_for(self);
+        let after = edges_for(self, &after_ids);
+        let key = |e: &DocEdge| (e.from.clone(), e.to.clone(), e.relation.clone());
+        let before_keys: HashSet<_> = before.iter().map(key).collect();
+        let after_keys: HashSet<_> = after.iter().map(key).collect();
+
+        let mut removed_nodes: Vec<String> = before_ids.difference(&after_ids).cloned().collect();
+        removed_nodes.sort();
+        GraphDelta {
+            added: after.into_iter().filter(|e| !before_keys.contains(&key(e))).collect(),
+            removed: before.into_iter().filter(|e| !after_keys.contains(&key(e))).collect(),
+            removed_nodes,
+        }
+    }
 
-        Ok(DocGraph { nodes, edges })
+    /// Remove the node and outgoing edges contributed by a file.
+    /// Incoming edges are kept so they surface as dangling refs.
+    pub fn remove_file(&mut self, path: &Path) {
+        let ids: Vec<String> = self
+            .nodes
+            .values()
+            .filter(|n| n.path.as_path() == path)
+            .map(|n| n.id.clone())
+            .collect();
+        if ids.is_empty() {
+            return;
+        }
+        for id in &ids {
+            self.nodes.remove(id);
+        }
+        self.edges.retain(|e| !ids.contains(&e.from));
     }
 
     /// Get all outgoing refs from a document.
@@ -208,6 +288,33 @@ impl DocGraph {
         result
     }
 
+    /// Look up a node by its stable uid (case-insensitive).
+    pub fn find_by_uid(&self, uid: &str) -> Option<&DocNode> {
+        self.nodes.values().find(|n| {
+            n.uid
+                .as_deref()
+                .is_some_and(|u| u.eq_ignore_ascii_case(uid))
+        })
+    }
+
+    /// Rewrite edges that point at a uid so they target the document's display ID.
+    /// Refs by uid keep resolving after the target file is renamed or moved.
+    pub(crate) fn resolve_uid_refs(&mut self) {
+        let by_uid: HashMap<String, String> = self
+            .nodes
+            .values()
+            .filter_map(|n| n.uid.as_ref().map(|u| (u.to_uppercase(), n.id.clone())))
+            .collect();
+        if by_uid.is_empty() {
+            return;
+        }
+        for edge in &mut self.edges {
+            if let Some(id) = by_uid.get(&edge.to.to_uppercase()) {
+                edge.to = id.clone();
+            }
+        }
+    }
+
     /// Collect node IDs matching the filter type, or all node IDs if no filter.
     fn active_ids(&self, filter_type: Option<&str>) -> HashSet<&str> {
         if let Some(ft) = filter_type {
@@ -221,8 +328,16 @@ impl DocGraph {
         }
     }
 
-    /// Export graph as mermaid diagram.
-    pub fn to_mermaid(&self, filter_type: Option<&str>) -> String {
+    /// Whether the node's status is terminal for its type (see [`Schema::status_field`]).
+    fn is_retired(node: &DocNode, schema: &Schema) -> bool {
+        let (Some(doc_type), Some(status)) = (&node.doc_type, &node.status) else {
+            return false;
+        };
+        schema.status_field(doc_type).is_terminal(status)
+    }
+
+    /// Export graph as mermaid diagram. Retired documents get a parallelogram.
+    pub fn to_mermaid(&self, filter_type: Option<&str>, schema: &Schema) -> String {
         let mut out = String::from("graph LR\n");
         let active_ids = self.active_ids(filter_type);
 
@@ -235,9 +350,7 @@ impl DocGraph {
                 .title
                 .as_deref()
                 .unwrap_or(id.as_str());
-            let shape = if node.status.as_deref() == Some("deprecated")
-                || node.status.as_deref() == Some("superseded")
-            {
+            let shape = if Self::is_retired(node, schema) {
                 format!("  {id}[/\"{label}\"/]")
             } else {
                 format!("  {id}[\"{label}\"]")
@@ -261,8 +374,8 @@ impl DocGraph {
         out
     }
 
-    /// Export graph as DOT (graphviz) format.
-    pub fn to_dot(&self, filter_type: Option<&str>) -> String {
+    /// Export graph as DOT (graphviz) format. Retired documents are dashed.
+    pub fn to_dot(&self, filter_type: Option<&str>, schema: &Schema) -> String {
         let mut out = String::from("digraph docs {\n  rankdir=LR;\n  node [shape=box];\n\n");
         let active_ids = self.active_ids(filter_type);
 
@@ -271,9 +384,7 @@ impl DocGraph {
                 continue;
             }
             let label = node.title.as_deref().unwrap_or(id.as_str());
-            let style = if node.status.as_deref() == Some("deprecated")
-                || node.status.as_deref() == Some("superseded")
-            {
+            let style = if Self::is_retired(node, schema) {
                 " style=dashed"
             } else {
                 ""
@@ -305,6 +416,7 @@ impl DocGraph {
         self.check_orphans(&mut diags);
         self.check_disconnected(&mut diags);
         self.check_dangling_refs(&mut diags);
+        self.check_coverage(schema, &mut diags);
         diags
     }
 
@@ -514,27 +626,172 @@ impl DocGraph {
         }
     }
 
+    /// G040: documents violating a schema `coverage` rule. One diagnostic per rule,
+    /// listing every non-compliant document.
+    fn check_coverage(&self, schema: &Schema, diags: &mut Vec<GraphDiagnostic>) {
+        for rule in &schema.coverage {
+            // A link counts from either side: `caused_by` on the incident, or
+            // `triggers` on the decision pointing back at it.
+            let counterpart = schema
+                .find_relation(&rule.relation)
+                .map(|(r, is_inverse)| {
+                    if is_inverse {
+                        r.name.clone()
+                    } else {
+                        r.inverse.clone().unwrap_or_else(|| r.name.clone())
+                    }
+                })
+                .unwrap_or_else(|| rule.relation.clone());
+
+            let type_matches = |id: &str| match &rule.to_type {
+                Some(t) => self
+                    .nodes
+                    .get(id)
+                    .is_some_and(|n| n.doc_type.as_deref() == Some(t.as_str())),
+                None => self.nodes.contains_key(id),
+            };
+
+            let missing: Vec<&str> = self
+                .nodes
+                .values()
+                .filter(|n| n.doc_type.as_deref() == Some(rule.doc_type.as_str()))
+                .filter(|n| {
+                    rule.statuses.is_empty()
+                        || n.status.as_ref().is_some_and(|s| rule.statuses.contains(s))
+                })
+                .filter(|n| {
+                    !self.edges.iter().any(|e| {
+                        (e.from == n.id && e.relation == rule.relation && type_matches(&e.to))
+                            || (e.to == n.id && e.relation == counterpart && type_matches(&e.from))
+                    })
+                })
+                .map(|n| n.id.as_str())
+                .collect();
+
+            if !missing.is_empty() {
+                diags.push(GraphDiagnostic {
+                    code: "G040".into(),
+                    severity: rule.severity.clone(),
+                    message: format!(
+                        "coverage rule '{}' not met by {} document(s): {}",
+                        rule.describe(),
+                        missing.len(),
+                        missing.join(", ")
+                    ),
+                });
+            }
+        }
+    }
+
     /// Find next available numeric ID for a type prefix (e.g. "ADR" → "ADR-005").
+    /// Numbers are zero-padded to the widest existing ID, at least [`ID_WIDTH`] digits.
     pub fn next_id(&self, prefix: &str) -> String {
         let prefix_upper = prefix.to_uppercase();
-        let max = self
+        let (max, width) = self
             .nodes
             .keys()
             .filter_map(|id| {
                 let parts: Vec<&str> = id.splitn(2, '-').collect();
                 if parts.len() == 2 && parts[0] == prefix_upper {
-                    parts[1].parse::<u32>().ok()
+                    parts[1].parse::<u32>().ok().map(|n| (n, parts[1].len()))
                 } else {
                     None
                 }
             })
-            .max()
-            .unwrap_or(0);
+            .fold((0, ID_WIDTH), |(max, width), (n, len)| (max.max(n), width.max(len)));
 
-        format!("{}-{:03}", prefix_upper, max + 1)
+        format!("{}-{:0width$}", prefix_upper, max + 1)
     }
 }
 
+/// Compute the node and outgoing edges a single parsed document contributes to the graph.
+/// Returns `None` for files without frontmatter that don't match a singleton type.
+/// Singletons get their title from the first heading and keep their inline links.
+pub(crate) fn scan_document(
+    path: &Path,
+    doc: &Document,
+    schema: &Schema,
+    relation_names: &[&str],
+) -> Option<(DocNode, Vec<DocEdge>)> {
+    let id = path_to_id(path);
+    // Singletons are typed by file name, with or without frontmatter
+    let singleton = schema.singleton_for(path);
+    let fm = doc.frontmatter.as_ref();
+    if fm.is_none() && singleton.is_none() {
+        return None;
+    }
+    let field = |key: &str| fm.and_then(|fm| fm.get_display(key));
+
+    let doc_type = singleton.map(|t| t.name.clone()).or_else(|| field("type"));
+    let status_field = doc_type.as_deref().map(|t| schema.status_field(t)).unwrap_or_default();
+    let node = DocNode {
+        id: id.clone(),
+        path: path.to_path_buf(),
+        title: field("title").or_else(|| singleton.and_then(|_| first_heading(&doc.body))),
+        status: field(&status_field.field),
+        doc_type,
+        uid: field(crate::uid::UID_FIELD)
+            .filter(|u| crate::uid::is_uid(u))
+            .map(|u| u.to_ascii_lowercase()),
+    };
+
+    let mut edges = Vec::new();
+
+    // Extract outgoing refs from relation fields
+    for rel_name in relation_names {
+        if let Some(val) = fm.and_then(|fm| fm.get(rel_name)) {
+            for target in extract_refs(val) {
+                edges.push(DocEdge {
+                    from: id.clone(),
+                    to: target,
+                    relation: rel_name.to_string(),
+                });
+            }
+        }
+    }
+
+    // Extract inline links from document body
+    let inline_links = ast_util::extract_links(&doc.body);
+    let doc_dir = path.parent();
+    for url in inline_links {
+        let target_id = if url.ends_with(".md") {
+            // Relative .md path — resolve against doc directory
+            let link_path = if let Some(dir) = doc_dir {
+                dir.join(&url)
+            } else {
+                PathBuf::from(&url)
+            };
+            path_to_id(&link_path)
+        } else if is_string_id(&url) {
+            // String ID pattern like "ADR-001"
+            url.to_uppercase()
+        } else {
+            // External or unrecognized link — skip
+            continue;
+        };
+
+        // Deduplicate: skip if a frontmatter edge already exists for this pair
+        let already_exists = edges.iter().any(|e| e.to == target_id);
+        if !already_exists {
+            edges.push(DocEdge {
+                from: id.clone(),
+                to: target_id,
+                relation: "inline_ref".to_string(),
+            });
+        }
+    }
+
+    Some((node, edges))
+}
+
+/// Text of the first heading in a body, used as a singleton's title.
+fn first_heading(body: &str) -> Option<String> {
+    let arena = comrak::Arena::new();
+    let root = comrak::parse_document(&arena, body, &ast_util::comrak_opts());
+    let heading = ast_util::find_headings(root, None).into_iter().next()?;
+    Some(ast_util::collect_text(heading).trim().to_string()).filter(|t| !t.is_empty())
+}
+
 /// Derive a document ID from its file path.
 /// Extracts the type-prefix + number from the filename:
 ///   `docs/adr-001.md` → `ADR-001`
@@ -601,7 +858,7 @@ fn is_string_id(s: &str) -> bool {
 }
 
 /// Extract ref strings from a YAML value (single string or array of strings).
-fn extract_refs(val: &serde_yaml::Value) -> Vec<String> {
+pub(crate) fn extract_refs(val: &serde_yaml::Value) -> Vec<String> {
     match val {
         serde_yaml::Value::String(s) => vec![s.to_uppercase()],
         serde_yaml::Value::Sequence(seq) => seq
@@ -630,6 +887,38 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_singletons_are_nodes_with_links() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("README.md"),
+            "# My Project\n\nSee [the decision](adr-001.md).\n",
+        )
+        .unwrap();
+        std::fs::write(dir.path().join("CHANGELOG-2025.md"), "---\nowner: \"@ops\"\n---\n# Changes\n").unwrap();
+        std::fs::write(dir.path().join("adr-001.md"), "---\ntype: adr\ntitle: Decide\n---\n").unwrap();
+        let schema = Schema::from_str(
+            r#"
+type "readme" singleton=#true {
+    match "README.md"
+}
+type "changelog" singleton=#true {
+    match "CHANGELOG*.md"
+}
+"#,
+        )
+        .unwrap();
+        let graph = DocGraph::build(dir.path(), &schema).unwrap();
+
+        let readme = &graph.nodes["README"];
+        assert_eq!(readme.doc_type.as_deref(), Some("readme"));
+        assert_eq!(readme.title.as_deref(), Some("My Project"));
+        assert_eq!(graph.refs_to("ADR-001")[0].from, "README");
+
+        // Glob match, and frontmatter doesn't hide the singleton type
+        assert_eq!(graph.nodes["CHANGELOG-2025"].doc_type.as_deref(), Some("changelog"));
+    }
+
     #[test]
     fn test_build_graph_from_fixtures() {
         let schema_content = std::fs::read_to_string("../../tests/fixtures/schema.kdl").unwrap();
@@ -687,6 +976,26 @@ mod tests {
         // Only one OPP fixture
         let next = graph.next_id("OPP");
         assert_eq!(next, "OPP-002");
+
+        // Widened IDs keep their width
+        let node = |id: &str| DocNode {
+            id: id.into(),
+            path: PathBuf::from(format!("{}.md", id.to_lowercase())),
+            doc_type: Some("inc".into()),
+            title: None,
+            status: None,
+            uid: None,
+        };
+        let wide = DocGraph {
+            nodes: ["INC-999", "INC-1000"].map(|id| (id.to_string(), node(id))).into(),
+            edges: Vec::new(),
+        };
+        assert_eq!(wide.next_id("inc"), "INC-1001");
+        let padded = DocGraph {
+            nodes: [("INC-0012".to_string(), node("INC-0012"))].into(),
+            edges: Vec::new(),
+        };
+        assert_eq!(padded.next_id("inc"), "INC-0013");
     }
 
     #[test]
@@ -695,7 +1004,7 @@ mod tests {
         let schema = Schema::from_str(&schema_content).unwrap();
         let graph = DocGraph::build("../../tests/fixtures", &schema).unwrap();
 
-        let mermaid = graph.to_mermaid(None);
+        let mermaid = graph.to_mermaid(None, &schema);
         assert!(mermaid.starts_with("graph LR"));
         assert!(mermaid.contains("ADR-001"));
         assert!(mermaid.contains("-->"));
@@ -707,10 +1016,13 @@ mod tests {
         let schema = Schema::from_str(&schema_content).unwrap();
         let graph = DocGraph::build("../../tests/fixtures", &schema).unwrap();
 
-        let dot = graph.to_dot(None);
+        let dot = graph.to_dot(None, &schema);
         assert!(dot.starts_with("digraph docs"));
         assert!(dot.contains("ADR-001"));
         assert!(dot.contains("->"));
+        // adr-003 is superseded, a terminal status
+        let adr3 = dot.lines().find(|l| l.trim_start().starts_with("\"ADR-003\" [")).unwrap();
+        assert!(adr3.contains("style=dashed"), "{adr3}");
     }
 
     // ─── Health check tests ──────────────────────────────────────────────────
@@ -722,6 +1034,7 @@ mod tests {
             doc_type: Some("test".into()),
             title: Some(id.into()),
             status: None,
+            uid: None,
         }
     }
 
@@ -737,9 +1050,22 @@ mod tests {
                     cardinality: Cardinality::Many,
                     description: None,
                     acyclic: Some(true),
+                    group: None,
+                    target_types: Vec::new(),
                 })
                 .collect(),
             ref_formats: vec![],
+            coverage: vec![],
+            prose: None,
+            relation_groups: vec![],
+            lint: None,
+            assets: None,
+            locale: None,
+            index: None,
+            permalink: None,
+            views: vec![],
+            projections: vec![],
+            patterns: Default::default(),
         }
     }
 
@@ -748,6 +1074,17 @@ mod tests {
             types: vec![],
             relations: vec![],
             ref_formats: vec![],
+            coverage: vec![],
+            prose: None,
+            relation_groups: vec![],
+            lint: None,
+            assets: None,
+            locale: None,
+            index: None,
+            permalink: None,
+            views: vec![],
+            projections: vec![],
+            patterns: Default::default(),
         }
     }
 
@@ -878,6 +1215,163 @@ mod tests {
         assert!(g030[0].message.contains("MISSING"));
     }
 
+    #[test]
+    fn test_uid_refs_survive_rename() {
+        let dir = tempfile::tempdir().unwrap();
+        let schema = Schema::from_str(
+            r#"relation "supersedes" inverse="superseded_by" cardinality="one""#,
+        )
+        .unwrap();
+        std::fs::write(
+            dir.path().join("adr-001.md"),
+            "---\ntype: adr\ntitle: Old\nuid: 01890a5d-ac96-774b-bcce-b302099a8057\n---\n",
+        )
+        .unwrap();
+        std::fs::write(
+            dir.path().join("adr-002.md"),
+            "---\ntype: adr\ntitle: New\nsupersedes: 01890a5d-ac96-774b-bcce-b302099a8057\n---\n",
+        )
+        .unwrap();
+
+        let graph = DocGraph::build(dir.path(), &schema).unwrap();
+        assert_eq!(graph.refs_from("ADR-002")[0].to, "ADR-001");
+        assert_eq!(
+            graph
+                .find_by_uid("01890A5D-AC96-774B-BCCE-B302099A8057")
+                .map(|n| n.id.as_str()),
+            Some("ADR-001")
+        );
+
+        std::fs::rename(dir.path().join("adr-001.md"), dir.path().join("adr-010.md")).unwrap();
+        let graph = DocGraph::build(dir.path(), &schema).unwrap();
+        assert_eq!(graph.refs_from("ADR-002")[0].to, "ADR-010");
+        assert!(graph.check_health(&schema).iter().all(|d| d.code != "G030"));
+    }
+
+    #[test]
+    fn test_apply_changes() {
+        let dir = tempfile::tempdir().unwrap();
+        let schema = Schema::from_str(r#"relation "enables" cardinality="many""#).unwrap();
+        let a = dir.path().join("adr-001.md");
+        let b = dir.path().join("adr-002.md");
+        std::fs::write(&a, "---\ntype: adr\nenables: [ADR-002]\n---\n").unwrap();
+        std::fs::write(&b, "---\ntype: adr\n---\n").unwrap();
+        let mut graph = DocGraph::build(dir.path(), &schema).unwrap();
+        assert!(graph.check_health(&schema).iter().all(|d| d.code != "G030"));
+
+        // Retarget the edge
+        std::fs::write(&a, "---\ntype: adr\nenables: [ADR-003]\n---\n").unwrap();
+        let delta = graph.apply_changes(std::slice::from_ref(&a), &schema);
+        assert_eq!(delta.added.len(), 1);
+        assert_eq!(delta.added[0].to, "ADR-003");
+        assert_eq!(delta.removed[0].to, "ADR-002");
+        assert!(graph.check_health(&schema).iter().any(|d| d.code == "G030"));
+
+        // Deleting a file drops its node; nothing else changes
+        std::fs::remove_file(&b).unwrap();
+        let delta = graph.apply_changes(std::slice::from_ref(&b), &schema);
+        assert_eq!(delta.removed_nodes, vec!["ADR-002"]);
+        assert!(delta.added.is_empty() && delta.removed.is_empty());
+        assert!(!graph.nodes.contains_key("ADR-002"));
+
+        // No change, no delta
+        assert!(graph.apply_changes(std::slice::from_ref(&a), &schema).is_empty());
+    }
+
+    fn typed_node(id: &str, doc_type: &str, status: Option<&str>) -> DocNode {
+        DocNode {
+            doc_type: Some(doc_type.into()),
+            status: status.map(String::from),
+            ..make_node(id)
+        }
+    }
+
+    #[test]
+    fn test_check_coverage() {
+        let schema = Schema::from_str(
+            r#"
+relation "triggers" inverse="caused_by" cardinality="many"
+relation "enables" inverse="enabled_by" cardinality="many"
+coverage {
+    every "inc" must-have-relation "caused_by" to-type "adr" severity="warning"
+    every "opp" status="accepted" must-have-relation "enabled_by" to-type "adr"
+}
+"#,
+        )
+        .unwrap();
+
+        let mut nodes = BTreeMap::new();
+        for node in [
+            typed_node("ADR-001", "adr", Some("accepted")),
+            typed_node("INC-001", "inc", None),
+            typed_node("INC-002", "inc", None),
+            typed_node("INC-003", "inc", None),
+            typed_node("OPP-001", "opp", Some("accepted")),
+            typed_node("OPP-002", "opp", Some("proposed")),
+        ] {
+            nodes.insert(node.id.clone(), node);
+        }
+
+        let edges = vec![
+            // Forward link on the incident
+            DocEdge {
+                from: "INC-001".into(),
+                to: "ADR-001".into(),
+                relation: "caused_by".into(),
+            },
+            // Inverse link declared on the decision
+            DocEdge {
+                from: "ADR-001".into(),
+                to: "INC-002".into(),
+                relation: "triggers".into(),
+            },
+        ];
+
+        let graph = DocGraph { nodes, edges };
+        let diags = graph.check_health(&schema);
+        let g040: Vec<_> = diags.iter().filter(|d| d.code == "G040").collect();
+        assert_eq!(g040.len(), 2);
+
+        assert_eq!(g040[0].severity, "warning");
+        assert!(g040[0].message.contains("INC-003"));
+        assert!(!g040[0].message.contains("INC-001"));
+        assert!(!g040[0].message.contains("INC-002"));
+
+        // Only accepted opps are checked
+        assert_eq!(g040[1].severity, "error");
+        assert!(g040[1].message.contains("OPP-001"));
+        assert!(!g040[1].message.contains("OPP-002"));
+    }
+
+    #[test]
+    fn test_check_coverage_respects_to_type() {
+        let schema = Schema::from_str(
+            r#"
+relation "triggers" inverse="caused_by" cardinality="many"
+coverage {
+    every "inc" must-have-relation "caused_by" to-type "adr"
+}
+"#,
+        )
+        .unwrap();
+
+        let mut nodes = BTreeMap::new();
+        nodes.insert("INC-001".into(), typed_node("INC-001", "inc", None));
+        nodes.insert("GOV-001".into(), typed_node("GOV-001", "gov", None));
+
+        let edges = vec![DocEdge {
+            from: "INC-001".into(),
+            to: "GOV-001".into(),
+            relation: "caused_by".into(),
+        }];
+
+        let graph = DocGraph { nodes, edges };
+        let diags = graph.check_health(&schema);
+        assert!(diags
+            .iter()
+            .any(|d| d.code == "G040" && d.message.contains("INC-001")));
+    }
+
     #[test]
     fn test_check_healthy_graph() {
         let mut nodes = BTreeMap::new();
@@ -0,0 +1,676 @@
+//! Persistent graph snapshots shared across CLI invocations.
+//!
+//! Building a `DocGraph` parses every markdown file. A `GraphSnapshot` stores each
+//! file's node and outgoing edges under `<dir>/.md-db/graph.bin`, keyed by a hash of
+//! the file content, so subsequent builds only re-parse files that actually changed.
+//! The file uses the versioned [`cache_file`](crate::cache_file) format.
+//!
+//! On a large corpus even assembling the whole graph is too slow for interactive
+//! lookups. [`GraphSnapshot::refresh_modified`] only reads files whose modification
+//! time or size changed, and [`GraphSnapshot::neighborhood`] returns the documents
+//! around one node without building the rest, walking per-ID adjacency lists kept
+//! next to the entries. [`GraphSnapshot::refresh_neighborhood`] checks only the
+//! files it visits.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+use serde::{Deserialize, Serialize};
+
+use crate::cache::simple_hash;
+use crate::cache_file::{self, Loaded};
+use crate::document::Document;
+use crate::error::Result;
+use crate::graph::{scan_document, DocEdge, DocGraph, DocNode};
+use crate::schema::Schema;
+
+/// Directory (relative to the docs root) holding md-db's on-disk caches.
+pub const CACHE_DIR: &str = ".md-db";
+
+/// File name of the graph snapshot inside `CACHE_DIR`.
+pub const GRAPH_FILE: &str = "graph.bin";
+
+/// Graph contribution of a single file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct FileEntry {
+    content_hash: u64,
+    /// Modification time (nanoseconds since the epoch) and size when scanned.
+    #[serde(default)]
+    stamp: (u64, u64),
+    node: Option<DocNode>,
+    edges: Vec<DocEdge>,
+}
+
+/// Which entries touch each ID, kept in step with the entries so a neighborhood
+/// walk reads only the entries it visits.
+#[derive(Debug, Clone, Default)]
+struct Adjacency {
+    /// Entries whose node has the ID; their edges are its outgoing refs.
+    out: HashMap<String, Vec<PathBuf>>,
+    /// Entries with a ref to the ID or uid, uppercased as written.
+    incoming: HashMap<String, Vec<PathBuf>>,
+    /// Uppercased uid -> the ID of its node.
+    uids: HashMap<String, String>,
+}
+
+impl Adjacency {
+    fn add(&mut self, key: &Path, entry: &FileEntry) {
+        if let Some(ref node) = entry.node {
+            push_unique(self.out.entry(node.id.clone()).or_default(), key);
+            if let Some(ref uid) = node.uid {
+                self.uids.insert(uid.to_uppercase(), node.id.clone());
+            }
+        }
+        for edge in &entry.edges {
+            push_unique(self.incoming.entry(edge.to.to_uppercase()).or_default(), key);
+        }
+    }
+
+    fn remove(&mut self, key: &Path, entry: &FileEntry) {
+        let drop_key = |map: &mut HashMap<String, Vec<PathBuf>>, name: String| {
+            if let Some(keys) = map.get_mut(&name) {
+                keys.retain(|k| k != key);
+                if keys.is_empty() {
+                    map.remove(&name);
+                }
+            }
+        };
+        if let Some(ref node) = entry.node {
+            drop_key(&mut self.out, node.id.clone());
+            if let Some(ref uid) = node.uid {
+                let uid = uid.to_uppercase();
+                if self.uids.get(&uid) == Some(&node.id) {
+                    self.uids.remove(&uid);
+                }
+            }
+        }
+        for edge in &entry.edges {
+            drop_key(&mut self.incoming, edge.to.to_uppercase());
+        }
+    }
+}
+
+fn push_unique(keys: &mut Vec<PathBuf>, key: &Path) {
+    if !keys.iter().any(|k| k == key) {
+        keys.push(key.to_path_buf());
+    }
+}
+
+/// Counters describing what a refresh did.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RefreshStats {
+    /// Files whose cached contribution was reused as-is.
+    pub reused: usize,
+    /// Files that were (re-)parsed.
+    pub rescanned: usize,
+    /// Entries dropped because the file disappeared.
+    pub removed: usize,
+}
+
+/// On-disk snapshot of a directory's document graph.
+#[derive(Debug, Clone)]
+pub struct GraphSnapshot {
+    /// Hash of the schema parts that affect scanning (relation fields, singleton patterns).
+    schema_key: u64,
+    /// Entries keyed by path relative to `root`.
+    files: BTreeMap<PathBuf, FileEntry>,
+    adjacency: Adjacency,
+    root: PathBuf,
+    dirty: bool,
+}
+
+impl GraphSnapshot {
+    /// Create an empty snapshot for a docs directory.
+    pub fn new(dir: impl AsRef<Path>, schema: &Schema) -> Self {
+        Self {
+            schema_key: schema_key(schema),
+            files: BTreeMap::new(),
+            adjacency: Adjacency::default(),
+            root: dir.as_ref().to_path_buf(),
+            dirty: false,
+        }
+    }
+
+    /// Path of the snapshot file for a docs directory.
+    pub fn path_for(dir: impl AsRef<Path>) -> PathBuf {
+        dir.as_ref().join(CACHE_DIR).join(GRAPH_FILE)
+    }
+
+    /// Load the snapshot for a docs directory.
+    /// Returns an empty snapshot if the file is missing, written by a different
+    /// md-db version, built against an incompatible schema, or corrupt. An existing
+    /// file that can't be used is marked for rewriting on the next save.
+    pub fn load(dir: impl AsRef<Path>, schema: &Schema) -> Self {
+        let dir = dir.as_ref();
+        let key = schema_key(schema);
+        let mut snap = Self::new(dir, schema);
+        match cache_file::load(&Self::path_for(dir), &cache_file::GRAPH, key) {
+            Loaded::Hit(files) => {
+                for (key, entry) in files {
+                    snap.insert_entry(key, entry);
+                }
+            }
+            Loaded::Missing => {}
+            Loaded::Outdated | Loaded::Corrupt(_) => snap.dirty = true,
+        }
+        snap
+    }
+
+    /// Write the snapshot to `<dir>/.md-db/graph.bin`.
+    /// Writes to a temporary file first and renames it so concurrent readers never
+    /// observe a partially written snapshot.
+    pub fn save(&self) -> Result<()> {
+        cache_file::save(&Self::path_for(&self.root), &cache_file::GRAPH, self.schema_key, &self.files)
+    }
+
+    /// Bring the snapshot in line with the given file list.
+    /// Unchanged files are reused, changed or new files are re-parsed, and entries
+    /// for files not in the list are dropped.
+    pub fn refresh(&mut self, files: &[PathBuf], schema: &Schema) -> RefreshStats {
+        self.refresh_with(files, schema, false)
+    }
+
+    /// Like [`refresh`](Self::refresh), but an entry whose file has the same
+    /// modification time and size as when it was scanned is reused without
+    /// reading the file, so an unchanged corpus costs one `stat` per file.
+    pub fn refresh_modified(&mut self, files: &[PathBuf], schema: &Schema) -> RefreshStats {
+        self.refresh_with(files, schema, true)
+    }
+
+    fn refresh_with(&mut self, files: &[PathBuf], schema: &Schema, trust_stamps: bool) -> RefreshStats {
+        let mut stats = RefreshStats::default();
+        let mut seen: HashSet<PathBuf> = HashSet::new();
+
+        for path in files {
+            let key = self.key_for(path);
+            if trust_stamps {
+                let unchanged = stamp(path).is_some_and(|s| self.files.get(&key).is_some_and(|e| e.stamp == s));
+                if unchanged {
+                    stats.reused += 1;
+                    seen.insert(key);
+                    continue;
+                }
+            }
+            match self.update_entry(path, &key, schema) {
+                Some(true) => stats.rescanned += 1,
+                Some(false) => stats.reused += 1,
+                None => continue,
+            }
+            seen.insert(key);
+        }
+
+        let gone: Vec<PathBuf> = self.files.keys().filter(|key| !seen.contains(*key)).cloned().collect();
+        for key in &gone {
+            self.remove_entry(key);
+        }
+        stats.removed = gone.len();
+        if stats.removed > 0 {
+            self.dirty = true;
+        }
+
+        stats
+    }
+
+    /// Re-scan a single file if its content changed. A missing file is removed.
+    /// Returns `true` if the snapshot was modified.
+    pub fn update_file(&mut self, path: &Path, schema: &Schema) -> bool {
+        let key = self.key_for(path);
+        match self.update_entry(path, &key, schema) {
+            Some(changed) => changed,
+            None => self.remove_file(path),
+        }
+    }
+
+    /// Drop the entry for a file. Returns `true` if an entry was removed.
+    pub fn remove_file(&mut self, path: &Path) -> bool {
+        let key = self.key_for(path);
+        let removed = self.remove_entry(&key).is_some();
+        if removed {
+            self.dirty = true;
+        }
+        removed
+    }
+
+    /// Assemble a `DocGraph` from the cached per-file contributions.
+    pub fn to_graph(&self) -> DocGraph {
+        let mut nodes = BTreeMap::new();
+        let mut edges = Vec::new();
+
+        for (key, entry) in &self.files {
+            if let Some(ref node) = entry.node {
+                let mut node = node.clone();
+                node.path = self.root.join(key);
+                nodes.insert(node.id.clone(), node);
+            }
+            edges.extend(entry.edges.iter().cloned());
+        }
+
+        let mut graph = DocGraph { nodes, edges };
+        graph.resolve_uid_refs();
+        graph
+    }
+
+    /// The documents within `depth` links of `id` (an ID or uid), following refs in
+    /// both directions, and the edges between them. Only the entries of visited
+    /// documents are read; the graph of the rest of the corpus is never assembled.
+    pub fn neighborhood(&self, id: &str, depth: usize) -> DocGraph {
+        let start = self.resolve(&id.to_uppercase());
+        let mut ids: HashSet<String> = HashSet::from([start.clone()]);
+        let mut frontier = vec![start];
+        for _ in 0..depth {
+            frontier = self.expand(&frontier, &mut ids);
+            if frontier.is_empty() {
+                break;
+            }
+        }
+        self.collect(&ids)
+    }
+
+    /// Like [`neighborhood`](Self::neighborhood), but first brings the entries it
+    /// visits up to date: new files in `files` are scanned, missing ones dropped,
+    /// and a visited document's file, or one that refs it, is re-read when its
+    /// modification time or size changed. Other files are not checked, so a ref
+    /// added to an unvisited file shows up after the next full refresh.
+    pub fn refresh_neighborhood(&mut self, files: &[PathBuf], schema: &Schema, id: &str, depth: usize) -> DocGraph {
+        let listed: HashSet<PathBuf> = files.iter().map(|p| self.key_for(p)).collect();
+        let gone: Vec<PathBuf> = self.files.keys().filter(|key| !listed.contains(*key)).cloned().collect();
+        for key in &gone {
+            self.remove_entry(key);
+            self.dirty = true;
+        }
+        for path in files {
+            let key = self.key_for(path);
+            if !self.files.contains_key(&key) {
+                self.update_entry(path, &key, schema);
+            }
+        }
+
+        let mut checked: HashSet<PathBuf> = HashSet::new();
+        let start = self.resolve(&id.to_uppercase());
+        let mut ids: HashSet<String> = HashSet::from([start.clone()]);
+        let mut frontier = vec![start];
+        for _ in 0..depth {
+            for id in &frontier {
+                for key in self.keys_around(id) {
+                    self.refresh_key(key, schema, &mut checked);
+                }
+            }
+            frontier = self.expand(&frontier, &mut ids);
+            if frontier.is_empty() {
+                break;
+            }
+        }
+        // The last ring is never expanded, but its nodes and edges are returned
+        for id in &ids {
+            for key in self.adjacency.out.get(id).cloned().unwrap_or_default() {
+                self.refresh_key(key, schema, &mut checked);
+            }
+        }
+        self.collect(&ids)
+    }
+
+    /// A ref target as a display ID: uids resolve to their node's ID.
+    fn resolve(&self, target: &str) -> String {
+        self.adjacency.uids.get(&target.to_uppercase()).cloned().unwrap_or_else(|| target.to_string())
+    }
+
+    /// The cached entries of `id`'s document and of the documents that may ref it.
+    fn keys_around(&self, id: &str) -> Vec<PathBuf> {
+        let out = self.adjacency.out.get(id).into_iter().flatten();
+        let uids = out.clone().filter_map(|key| self.files.get(key)?.node.as_ref()?.uid.as_ref());
+        let names = std::iter::once(id.to_uppercase()).chain(uids.map(|u| u.to_uppercase()));
+        let incoming = names.filter_map(|name| self.adjacency.incoming.get(&name)).flatten();
+        out.chain(incoming).cloned().collect()
+    }
+
+    /// The refs from or to `id`, with uid targets resolved.
+    fn edges_around(&self, id: &str) -> Vec<DocEdge> {
+        self.keys_around(id)
+            .iter()
+            .filter_map(|key| self.files.get(key))
+            .flat_map(|entry| &entry.edges)
+            .map(|e| DocEdge {
+                to: self.resolve(&e.to),
+                ..e.clone()
+            })
+            .filter(|e| e.from == id || e.to == id)
+            .collect()
+    }
+
+    /// The IDs one link from the frontier that aren't in `ids` yet, added to it.
+    fn expand(&self, frontier: &[String], ids: &mut HashSet<String>) -> Vec<String> {
+        let mut next = Vec::new();
+        for id in frontier {
+            for edge in self.edges_around(id) {
+                let other = if edge.from == *id { edge.to } else { edge.from };
+                if ids.insert(other.clone()) {
+                    next.push(other);
+                }
+            }
+        }
+        next
+    }
+
+    /// The nodes of `ids` and the edges between them.
+    fn collect(&self, ids: &HashSet<String>) -> DocGraph {
+        // In entry order, as `to_graph` would list them
+        let mut keys: Vec<&PathBuf> = ids.iter().filter_map(|id| self.adjacency.out.get(id)).flatten().collect();
+        keys.sort();
+        keys.dedup();
+
+        let mut nodes = BTreeMap::new();
+        let mut edges = Vec::new();
+        for key in keys {
+            let Some(entry) = self.files.get(key) else { continue };
+            if let Some(ref node) = entry.node {
+                let mut node = node.clone();
+                node.path = self.root.join(key);
+                nodes.insert(node.id.clone(), node);
+            }
+            let resolved = entry.edges.iter().map(|e| DocEdge {
+                to: self.resolve(&e.to),
+                ..e.clone()
+            });
+            edges.extend(resolved.filter(|e| ids.contains(&e.to)));
+        }
+        DocGraph { nodes, edges }
+    }
+
+    /// Re-read the file of an entry once per walk, if its stamp changed.
+    fn refresh_key(&mut self, key: PathBuf, schema: &Schema, checked: &mut HashSet<PathBuf>) {
+        if !checked.insert(key.clone()) {
+            return;
+        }
+        let path = self.root.join(&key);
+        if stamp(&path).is_some_and(|s| self.files.get(&key).is_some_and(|e| e.stamp == s)) {
+            return;
+        }
+        if self.update_entry(&path, &key, schema).is_none() && self.remove_entry(&key).is_some() {
+            self.dirty = true;
+        }
+    }
+
+    /// Every cached document node, without the edges.
+    pub fn nodes(&self) -> impl Iterator<Item = DocNode> + '_ {
+        self.files.iter().filter_map(|(key, entry)| {
+            let mut node = entry.node.clone()?;
+            node.path = self.root.join(key);
+            Some(node)
+        })
+    }
+
+    /// Whether the snapshot changed since it was loaded.
+    pub fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
+    /// Number of files tracked by the snapshot.
+    pub fn len(&self) -> usize {
+        self.files.len()
+    }
+
+    /// Whether the snapshot tracks no files.
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+
+    /// Key a path relative to the snapshot root so the cache survives invocations
+    /// from different working directories.
+    fn key_for(&self, path: &Path) -> PathBuf {
+        path.strip_prefix(&self.root)
+            .map(|p| p.to_path_buf())
+            .unwrap_or_else(|_| path.to_path_buf())
+    }
+
+    fn insert_entry(&mut self, key: PathBuf, entry: FileEntry) {
+        self.remove_entry(&key);
+        self.adjacency.add(&key, &entry);
+        self.files.insert(key, entry);
+    }
+
+    fn remove_entry(&mut self, key: &Path) -> Option<FileEntry> {
+        let entry = self.files.remove(key)?;
+        self.adjacency.remove(key, &entry);
+        Some(entry)
+    }
+
+    /// Refresh one entry. Returns `Some(true)` if re-parsed, `Some(false)` if reused,
+    /// and `None` if the file could not be read.
+    fn update_entry(&mut self, path: &Path, key: &Path, schema: &Schema) -> Option<bool> {
+        // Taken before reading, so a write in between leaves the entry looking stale
+        let stamp = stamp(path).unwrap_or_default();
+        let content = std::fs::read_to_string(path).ok()?;
+        let content_hash = simple_hash(&content);
+
+        if let Some(entry) = self.files.get_mut(key) {
+            if entry.content_hash == content_hash {
+                if entry.stamp != stamp {
+                    entry.stamp = stamp;
+                    self.dirty = true;
+                }
+                return Some(false);
+            }
+        }
+
+        let relation_names = schema.all_relation_field_names();
+        let (node, edges) = match Document::from_str(&content) {
+            Ok(doc) => match scan_document(path, &doc, schema, &relation_names) {
+                Some((node, edges)) => (Some(node), edges),
+                None => (None, Vec::new()),
+            },
+            Err(_) => (None, Vec::new()),
+        };
+
+        self.insert_entry(
+            key.to_path_buf(),
+            FileEntry {
+                content_hash,
+                stamp,
+                node,
+                edges,
+            },
+        );
+        self.dirty = true;
+        Some(true)
+    }
+}
+
+/// A file's modification time (nanoseconds since the epoch) and size.
+fn stamp(path: &Path) -> Option<(u64, u64)> {
+    let meta = std::fs::metadata(path).ok()?;
+    let modified = meta.modified().ok()?.duration_since(UNIX_EPOCH).ok()?;
+    Some((modified.as_nanos() as u64, meta.len()))
+}
+
+/// Hash the schema parts that influence how a file is scanned into the graph.
+pub fn schema_key(schema: &Schema) -> u64 {
+    let mut key = String::new();
+    for name in schema.all_relation_field_names() {
+        key.push_str(name);
+        key.push('\n');
+    }
+    for t in schema.types.iter().filter(|t| t.singleton) {
+        key.push_str(&t.name);
+        key.push('=');
+        key.push_str(t.match_pattern.as_deref().unwrap_or(""));
+        key.push('\n');
+    }
+    simple_hash(&key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCHEMA: &str = r#"
+type "adr" {
+    field "title" type="string" required=#true
+}
+
+relation "enables" inverse="enabled_by" cardinality="many"
+"#;
+
+    fn write(dir: &Path, name: &str, content: &str) -> PathBuf {
+        let path = dir.join(name);
+        std::fs::write(&path, content).unwrap();
+        path
+    }
+
+    fn discover(dir: &Path) -> Vec<PathBuf> {
+        crate::discovery::discover_files(dir, None, &[], false).unwrap()
+    }
+
+    #[test]
+    fn test_refresh_reuses_unchanged_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let schema = Schema::from_str(SCHEMA).unwrap();
+        write(dir.path(), "adr-001.md", "---\ntitle: A\nenables: [ADR-002]\n---\n");
+        write(dir.path(), "adr-002.md", "---\ntitle: B\n---\n");
+
+        let mut snap = GraphSnapshot::new(dir.path(), &schema);
+        let stats = snap.refresh(&discover(dir.path()), &schema);
+        assert_eq!(stats.rescanned, 2);
+        snap.save().unwrap();
+
+        let mut loaded = GraphSnapshot::load(dir.path(), &schema);
+        assert_eq!(loaded.len(), 2);
+        let stats = loaded.refresh(&discover(dir.path()), &schema);
+        assert_eq!(stats.reused, 2);
+        assert_eq!(stats.rescanned, 0);
+        assert!(!loaded.is_dirty());
+    }
+
+    #[test]
+    fn test_refresh_patches_changed_and_removed_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let schema = Schema::from_str(SCHEMA).unwrap();
+        let a = write(dir.path(), "adr-001.md", "---\ntitle: A\nenables: [ADR-002]\n---\n");
+        let b = write(dir.path(), "adr-002.md", "---\ntitle: B\n---\n");
+
+        let mut snap = GraphSnapshot::new(dir.path(), &schema);
+        snap.refresh(&discover(dir.path()), &schema);
+        assert_eq!(snap.to_graph().refs_from("ADR-001").len(), 1);
+
+        std::fs::write(&a, "---\ntitle: A\n---\n").unwrap();
+        std::fs::remove_file(&b).unwrap();
+
+        let stats = snap.refresh(&discover(dir.path()), &schema);
+        assert_eq!(stats.rescanned, 1);
+        assert_eq!(stats.removed, 1);
+
+        let graph = snap.to_graph();
+        assert!(graph.refs_from("ADR-001").is_empty());
+        assert!(!graph.nodes.contains_key("ADR-002"));
+    }
+
+    #[test]
+    fn test_schema_change_discards_snapshot() {
+        let dir = tempfile::tempdir().unwrap();
+        let schema = Schema::from_str(SCHEMA).unwrap();
+        write(dir.path(), "adr-001.md", "---\ntitle: A\n---\n");
+
+        let mut snap = GraphSnapshot::new(dir.path(), &schema);
+        snap.refresh(&discover(dir.path()), &schema);
+        snap.save().unwrap();
+
+        let other = Schema::from_str(&format!("{SCHEMA}\nrelation \"related\"\n")).unwrap();
+        let loaded = GraphSnapshot::load(dir.path(), &other);
+        assert!(loaded.is_empty());
+    }
+
+    #[test]
+    fn test_corrupt_snapshot_is_rebuilt() {
+        let dir = tempfile::tempdir().unwrap();
+        let schema = Schema::from_str(SCHEMA).unwrap();
+        write(dir.path(), "adr-001.md", "---\ntitle: A\n---\n");
+        DocGraph::build_cached(dir.path(), &schema).unwrap();
+
+        let path = GraphSnapshot::path_for(dir.path());
+        let mut bytes = std::fs::read(&path).unwrap();
+        bytes.truncate(bytes.len() - 3);
+        std::fs::write(&path, &bytes).unwrap();
+
+        assert!(GraphSnapshot::load(dir.path(), &schema).is_empty());
+        let graph = DocGraph::build_cached(dir.path(), &schema).unwrap();
+        assert!(graph.nodes.contains_key("ADR-001"));
+        assert_eq!(GraphSnapshot::load(dir.path(), &schema).len(), 1);
+    }
+
+    #[test]
+    fn test_neighborhood() {
+        let dir = tempfile::tempdir().unwrap();
+        let schema = Schema::from_str(SCHEMA).unwrap();
+        write(dir.path(), "adr-001.md", "---\ntitle: A\nenables: [ADR-002]\n---\n");
+        write(dir.path(), "adr-002.md", "---\ntitle: B\nenables: [ADR-003]\n---\n");
+        write(dir.path(), "adr-003.md", "---\ntitle: C\nenables: [ADR-004]\n---\n");
+        write(dir.path(), "adr-004.md", "---\ntitle: D\n---\n");
+
+        let mut snap = GraphSnapshot::new(dir.path(), &schema);
+        snap.refresh_modified(&discover(dir.path()), &schema);
+        let graph = snap.neighborhood("adr-002", 1);
+        assert_eq!(graph.nodes.keys().collect::<Vec<_>>(), ["ADR-001", "ADR-002", "ADR-003"]);
+        assert_eq!(graph.refs_to("ADR-002").len(), 1);
+        assert_eq!(graph.refs_from("ADR-002").len(), 1);
+        assert_eq!(snap.neighborhood("ADR-002", 2).nodes.len(), 4);
+        assert_eq!(snap.nodes().count(), 4);
+
+        // Unchanged files are reused by stamp; a rewritten one is rescanned
+        std::fs::write(dir.path().join("adr-004.md"), "---\ntitle: D\nenables: [ADR-001]\n---\n").unwrap();
+        let stats = snap.refresh_modified(&discover(dir.path()), &schema);
+        assert_eq!((stats.reused, stats.rescanned), (3, 1));
+        assert!(snap.neighborhood("ADR-001", 1).nodes.contains_key("ADR-004"));
+    }
+
+    #[test]
+    fn test_refresh_neighborhood_reads_visited_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let schema = Schema::from_str(SCHEMA).unwrap();
+        write(dir.path(), "adr-001.md", "---\ntitle: A\nenables: [ADR-002]\n---\n");
+        write(dir.path(), "adr-002.md", "---\ntitle: B\n---\n");
+        write(dir.path(), "adr-003.md", "---\ntitle: C\n---\n");
+
+        let mut snap = GraphSnapshot::new(dir.path(), &schema);
+        snap.refresh(&discover(dir.path()), &schema);
+
+        write(dir.path(), "adr-001.md", "---\ntitle: A2\nenables: [ADR-002]\n---\n");
+        write(dir.path(), "adr-003.md", "---\ntitle: C2\n---\n");
+        write(dir.path(), "adr-004.md", "---\ntitle: D\nenables: [ADR-002]\n---\n");
+        let graph = snap.refresh_neighborhood(&discover(dir.path()), &schema, "ADR-002", 1);
+        assert_eq!(graph.nodes.keys().collect::<Vec<_>>(), ["ADR-001", "ADR-002", "ADR-004"]);
+        assert_eq!(graph.nodes["ADR-001"].title.as_deref(), Some("A2"));
+        assert_eq!(graph.refs_to("ADR-002").len(), 2);
+
+        // ADR-003 was not visited, so its entry is as cached
+        let cached = snap.to_graph();
+        assert_eq!(cached.nodes["ADR-003"].title.as_deref(), Some("C"));
+        assert!(snap.is_dirty());
+    }
+
+    #[test]
+    fn test_build_cached_matches_build() {
+        let schema_content = std::fs::read_to_string("../../tests/fixtures/schema.kdl").unwrap();
+        let schema = Schema::from_str(&schema_content).unwrap();
+
+        let dir = tempfile::tempdir().unwrap();
+        for entry in std::fs::read_dir("../../tests/fixtures").unwrap() {
+            let path = entry.unwrap().path();
+            if path.extension().map_or(false, |e| e == "md") {
+                std::fs::copy(&path, dir.path().join(path.file_name().unwrap())).unwrap();
+            }
+        }
+
+        let fresh = DocGraph::build(dir.path(), &schema).unwrap();
+        let cold = DocGraph::build_cached(dir.path(), &schema).unwrap();
+        assert!(GraphSnapshot::path_for(dir.path()).exists());
+        let warm = DocGraph::build_cached(dir.path(), &schema).unwrap();
+
+        for graph in [&cold, &warm] {
+            assert_eq!(
+                graph.nodes.keys().collect::<Vec<_>>(),
+                fresh.nodes.keys().collect::<Vec<_>>()
+            );
+            assert_eq!(graph.edges.len(), fresh.edges.len());
+        }
+    }
+}
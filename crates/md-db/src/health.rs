@@ -0,0 +1,143 @@
+//! Document health for the exported index.
+//!
+//! A document starts at 100 points and loses the schema's `index` weights for
+//! each validation error and warning, an overdue review, an expiry date inside
+//! its warning window, and unfilled required sections. Documents with errors,
+//! an overdue review, or an expiring date "need attention" and are listed at
+//! the top of the index.
+
+use crate::frontmatter::Frontmatter;
+use crate::schema::{AutoTimestamp, IndexRules, Schema};
+
+/// Health inputs and score for one document.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DocHealth {
+    pub errors: usize,
+    pub warnings: usize,
+    /// The review date, when it has passed.
+    pub overdue: Option<String>,
+    /// The expiry date, when it is within the warning window or past.
+    pub expiring: Option<String>,
+    /// Whether `expiring` is already past.
+    pub expired: bool,
+    /// Share of required sections filled, 0–100.
+    pub completeness: Option<usize>,
+    /// The date recency is measured from.
+    pub updated: Option<String>,
+    /// Whole days since `updated`.
+    pub age_days: Option<i64>,
+    /// 0–100, higher is healthier.
+    pub score: u32,
+}
+
+impl DocHealth {
+    pub fn needs_attention(&self) -> bool {
+        self.errors > 0 || self.overdue.is_some() || self.expiring.is_some()
+    }
+}
+
+/// Score a document given its validation counts and completeness. `today` is in
+/// days since 1970-01-01 (see [`today`]).
+pub fn assess(
+    fm: Option<&Frontmatter>,
+    schema: Option<&Schema>,
+    rules: &IndexRules,
+    (errors, warnings): (usize, usize),
+    completeness: Option<usize>,
+    today: i64,
+) -> DocHealth {
+    let date_of = |field: &str| -> Option<(String, i64)> {
+        let value = fm?.get_display(field)?;
+        let days = crate::validation::parse_timestamp(&value)?.div_euclid(86_400);
+        Some((value, days))
+    };
+
+    let overdue = rules
+        .review_field
+        .as_deref()
+        .and_then(date_of)
+        .filter(|(_, day)| *day < today)
+        .map(|(value, _)| value);
+    let expires = rules
+        .expires_field
+        .as_deref()
+        .and_then(date_of)
+        .filter(|(_, day)| *day - today <= rules.warn_days);
+    let expired = expires.as_ref().is_some_and(|(_, day)| *day < today);
+    let updated = date_of(&recency_field(fm, schema, rules));
+
+    let w = &rules.weights;
+    let mut lost = errors as u32 * w.error + warnings as u32 * w.warning;
+    if overdue.is_some() {
+        lost += w.overdue;
+    }
+    if expires.is_some() {
+        lost += w.expiring;
+    }
+    if let Some(percent) = completeness {
+        lost += w.incomplete * (100 - percent.min(100) as u32) / 100;
+    }
+
+    DocHealth {
+        errors,
+        warnings,
+        overdue,
+        expiring: expires.map(|(value, _)| value),
+        expired,
+        completeness,
+        age_days: updated.as_ref().map(|(_, day)| today - day),
+        updated: updated.map(|(value, _)| value),
+        score: 100u32.saturating_sub(lost),
+    }
+}
+
+/// The field recency is read from: the `index` block's `recency`, else the
+/// document type's `auto="updated"` field, else `updated`.
+fn recency_field(fm: Option<&Frontmatter>, schema: Option<&Schema>, rules: &IndexRules) -> String {
+    if let Some(ref field) = rules.recency_field {
+        return field.clone();
+    }
+    fm.and_then(|fm| fm.get_display("type"))
+        .and_then(|t| schema?.get_type(&t))
+        .and_then(|t| t.fields.iter().find(|f| f.auto == Some(AutoTimestamp::Updated)))
+        .map_or_else(|| "updated".to_string(), |f| f.name.clone())
+}
+
+/// Today in days since 1970-01-01 (UTC).
+pub fn today() -> i64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64 / 86_400)
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_assess() {
+        let schema = Schema::from_str(
+            "index {\n    review-by \"review\"\n    expires \"until\" warn-days=10\n}\ntype \"pol\" {\n    field \"changed\" type=\"date\" auto=\"updated\"\n}\n",
+        )
+        .unwrap();
+        let rules = schema.index.clone().unwrap();
+        let today = crate::validation::parse_date("2024-06-01").unwrap();
+        let (fm, _) =
+            Frontmatter::parse("---\ntype: pol\nreview: 2024-05-01\nuntil: 2024-06-05\nchanged: 2024-05-25\n---\n").unwrap();
+
+        let health = assess(Some(&fm), Some(&schema), &rules, (1, 2), Some(50), today);
+        assert_eq!(health.overdue.as_deref(), Some("2024-05-01"));
+        assert_eq!(health.expiring.as_deref(), Some("2024-06-05"));
+        assert!(!health.expired);
+        assert_eq!(health.age_days, Some(7));
+        // 100 - 30 - 2*5 - 25 - 15 - 10
+        assert_eq!(health.score, 10);
+        assert!(health.needs_attention());
+
+        let (fresh, _) = Frontmatter::parse("---\ntype: pol\nreview: 2025-01-01\nuntil: 2025-01-01\n---\n").unwrap();
+        let health = assess(Some(&fresh), Some(&schema), &rules, (0, 0), None, today);
+        assert_eq!((health.score, health.needs_attention()), (100, false));
+        assert_eq!(health.updated, None);
+    }
+}
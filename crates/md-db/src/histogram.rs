@@ -0,0 +1,217 @@
+//! Value counts for a frontmatter field across documents.
+//!
+//! Array fields count each element once per document, so percentages (share of
+//! documents) can add up to more than 100%. User fields can be rolled up to the
+//! teams their members belong to.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde::Serialize;
+use serde_yaml::Value;
+
+use crate::frontmatter::{yaml_value_to_string, Frontmatter};
+use crate::users::UserConfig;
+
+/// Bucket label for users that belong to no team when rolling up.
+pub const NO_TEAM: &str = "(no team)";
+
+/// One value and how many documents carry it.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Bucket {
+    pub value: String,
+    pub count: usize,
+    /// Share of all counted documents, 0–100.
+    pub percent: f64,
+}
+
+/// Values past the top-N cutoff, collapsed into one bucket.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct OtherBucket {
+    /// Number of distinct values collapsed.
+    pub values: usize,
+    pub count: usize,
+    pub percent: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Histogram {
+    pub field: String,
+    pub documents: usize,
+    /// Documents where the field is absent, null, or an empty list.
+    pub missing: usize,
+    /// Most frequent first; ties by value.
+    pub buckets: Vec<Bucket>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub other: Option<OtherBucket>,
+}
+
+/// Options for [`histogram`].
+#[derive(Debug, Clone, Default)]
+pub struct HistogramOptions<'a> {
+    /// Keep the N most frequent values and collapse the rest into `other`.
+    pub top: Option<usize>,
+    /// Count `@user` values as their teams (`@team/name`) instead.
+    pub team_rollup: Option<&'a UserConfig>,
+}
+
+/// Count the values of `field` across `docs`.
+pub fn histogram(docs: &[Frontmatter], field: &str, opts: &HistogramOptions) -> Histogram {
+    let mut counts: BTreeMap<String, usize> = BTreeMap::new();
+    let mut missing = 0;
+
+    for fm in docs {
+        let values = field_values(fm.get(field));
+        if values.is_empty() {
+            missing += 1;
+            continue;
+        }
+        // A document counts once per distinct value
+        let values: BTreeSet<String> = match opts.team_rollup {
+            Some(users) => values.iter().flat_map(|v| teams_of(users, v)).collect(),
+            None => values.into_iter().collect(),
+        };
+        for value in values {
+            *counts.entry(value).or_insert(0) += 1;
+        }
+    }
+
+    let documents = docs.len();
+    let percent = |count: usize| {
+        if documents == 0 {
+            0.0
+        } else {
+            count as f64 * 100.0 / documents as f64
+        }
+    };
+
+    let mut buckets: Vec<Bucket> = counts
+        .into_iter()
+        .map(|(value, count)| Bucket {
+            value,
+            count,
+            percent: percent(count),
+        })
+        .collect();
+    buckets.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.value.cmp(&b.value)));
+
+    let other = match opts.top {
+        Some(n) if buckets.len() > n => {
+            let rest = buckets.split_off(n);
+            let count = rest.iter().map(|b| b.count).sum();
+            Some(OtherBucket {
+                values: rest.len(),
+                count,
+                percent: percent(count),
+            })
+        }
+        _ => None,
+    };
+
+    Histogram {
+        field: field.to_string(),
+        documents,
+        missing,
+        buckets,
+        other,
+    }
+}
+
+/// Display strings for a field value: one per array element, none for null.
+fn field_values(value: Option<&Value>) -> Vec<String> {
+    match value {
+        None | Some(Value::Null) => Vec::new(),
+        Some(Value::Sequence(items)) => items
+            .iter()
+            .filter(|v| !v.is_null())
+            .map(yaml_value_to_string)
+            .collect(),
+        Some(v) => vec![yaml_value_to_string(v)],
+    }
+}
+
+/// Teams a user reference rolls up to. Team refs stay as they are; anything else
+/// that isn't a known user is kept verbatim so typos stay visible.
+fn teams_of(users: &UserConfig, reference: &str) -> Vec<String> {
+    let Some(handle) = reference.strip_prefix('@') else {
+        return vec![reference.to_string()];
+    };
+    if handle.starts_with("team/") {
+        return vec![reference.to_string()];
+    }
+    match users.users.get(handle) {
+        Some(user) if !user.teams.is_empty() => {
+            user.teams.iter().map(|t| format!("@team/{t}")).collect()
+        }
+        Some(_) => vec![NO_TEAM.to_string()],
+        None => vec![reference.to_string()],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn docs(yamls: &[&str]) -> Vec<Frontmatter> {
+        yamls
+            .iter()
+            .map(|y| {
+                let content = format!("---\n{y}\n---\n");
+                Frontmatter::try_parse(&content).unwrap().0.unwrap()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_scalar_counts_and_top_n() {
+        let docs = docs(&[
+            "status: accepted",
+            "status: accepted",
+            "status: proposed",
+            "status: rejected",
+            "title: no status",
+        ]);
+        let h = histogram(&docs, "status", &HistogramOptions { top: Some(2), ..Default::default() });
+        assert_eq!(h.documents, 5);
+        assert_eq!(h.missing, 1);
+        assert_eq!(h.buckets.len(), 2);
+        assert_eq!(h.buckets[0], Bucket { value: "accepted".into(), count: 2, percent: 40.0 });
+        assert_eq!(h.buckets[1].value, "proposed");
+        let other = h.other.unwrap();
+        assert_eq!((other.values, other.count), (1, 1));
+    }
+
+    #[test]
+    fn test_array_and_typed_values() {
+        let docs = docs(&[
+            "tags: [db, infra, db]\nurgent: true",
+            "tags: [db]\nurgent: false",
+            "tags: []",
+        ]);
+        let h = histogram(&docs, "tags", &HistogramOptions::default());
+        assert_eq!(h.missing, 1);
+        assert_eq!(h.buckets[0], Bucket { value: "db".into(), count: 2, percent: 200.0 / 3.0 });
+        assert_eq!(h.buckets[1].value, "infra");
+
+        let h = histogram(&docs, "urgent", &HistogramOptions::default());
+        let values: Vec<&str> = h.buckets.iter().map(|b| b.value.as_str()).collect();
+        assert_eq!(values, vec!["false", "true"]);
+    }
+
+    #[test]
+    fn test_team_rollup() {
+        let users = UserConfig::from_str(
+            "users:\n  alice:\n    teams: [platform]\n  bob:\n    teams: [platform, data]\n  carol: {}\nteams:\n  platform: {}\n  data: {}\n",
+        )
+        .unwrap();
+        let docs = docs(&[
+            "owner: \"@alice\"",
+            "owner: \"@bob\"",
+            "owner: \"@carol\"",
+            "owner: \"@team/data\"",
+        ]);
+        let opts = HistogramOptions { top: None, team_rollup: Some(&users) };
+        let h = histogram(&docs, "owner", &opts);
+        let counts: Vec<(&str, usize)> = h.buckets.iter().map(|b| (b.value.as_str(), b.count)).collect();
+        assert_eq!(counts, vec![("@team/data", 2), ("@team/platform", 2), (NO_TEAM, 1)]);
+    }
+}
@@ -0,0 +1,175 @@
+//! A frontmatter field's values across git history (`md-db field-history`).
+//!
+//! [`field_history`] reads the file at every commit that touched it and keeps the
+//! commits where the field's value changed, so an ADR's status reads as its
+//! transitions: proposed → accepted → superseded. [`field_blame`] keeps only the
+//! last of those per field (`md-db blame`).
+
+use std::path::Path;
+
+use serde::Serialize;
+
+use crate::error::{Error, Result};
+use crate::frontmatter::Frontmatter;
+use crate::vcs::{self, Commit};
+
+/// One change of the field's value.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FieldChange {
+    pub commit: String,
+    pub date: String,
+    pub author: String,
+    /// Path of the file at that commit.
+    pub path: String,
+    /// The value before this commit; `None` when the field was absent (or the file new).
+    pub from: Option<String>,
+    /// The value after this commit; `None` when the field was removed.
+    pub value: Option<String>,
+}
+
+/// The changes of `field` (a dotted path, as in `get`) in the file at `path`,
+/// oldest first, following renames.
+pub fn field_history(path: &Path, field: &str) -> Result<Vec<FieldChange>> {
+    Ok(transitions(&revisions(path)?, field))
+}
+
+/// The file's content at every commit that touched it, oldest first.
+fn revisions(path: &Path) -> Result<Vec<(Commit, String)>> {
+    let mut revisions = Vec::new();
+    for commit in vcs::file_log(path)?.into_iter().rev() {
+        // A commit that deleted the file has no blob; the field is gone
+        let content = vcs::read_repo_file_at(&commit.hash, &commit.path).unwrap_or_default();
+        revisions.push((commit, content));
+    }
+    Ok(revisions)
+}
+
+/// Who set a field's current value.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FieldBlame {
+    pub field: String,
+    /// The value in the working tree; `None` when the field is absent.
+    pub value: Option<String>,
+    /// The commit that set that value; `None` when it isn't committed yet (or the
+    /// field was never set).
+    pub change: Option<FieldChange>,
+}
+
+/// The commit that last changed each of `fields` in the file at `path`, or of
+/// every top-level field of its frontmatter when `fields` is empty.
+pub fn field_blame(path: &Path, fields: &[String]) -> Result<Vec<FieldBlame>> {
+    let content = std::fs::read_to_string(path).map_err(|_| Error::FileNotFound(path.to_path_buf()))?;
+    let (current, _) = Frontmatter::try_parse(&content)?;
+    Ok(blame(&revisions(path)?, current.as_ref(), fields))
+}
+
+/// [`field_blame`] over already-read revisions and the working-tree frontmatter.
+pub fn blame(revisions: &[(Commit, String)], current: Option<&Frontmatter>, fields: &[String]) -> Vec<FieldBlame> {
+    let fields: Vec<String> = if fields.is_empty() {
+        current.map(|fm| fm.keys().cloned().collect()).unwrap_or_default()
+    } else {
+        fields.to_vec()
+    };
+    fields
+        .into_iter()
+        .map(|field| {
+            let value = current.and_then(|fm| fm.get_display(&field));
+            let change = transitions(revisions, &field).pop().filter(|last| last.value == value);
+            FieldBlame { field, value, change }
+        })
+        .collect()
+}
+
+/// The commits, oldest first, at which `field` takes a new value.
+pub fn transitions(revisions: &[(Commit, String)], field: &str) -> Vec<FieldChange> {
+    let mut changes = Vec::new();
+    let mut current: Option<String> = None;
+    for (commit, content) in revisions {
+        // Revisions whose frontmatter doesn't parse say nothing about the field
+        let Ok((fm, _)) = Frontmatter::try_parse(content) else {
+            continue;
+        };
+        let value = fm.and_then(|fm| fm.get_display(field));
+        if value == current {
+            continue;
+        }
+        changes.push(FieldChange {
+            commit: commit.hash.clone(),
+            date: commit.date.clone(),
+            author: commit.author.clone(),
+            path: commit.path.display().to_string(),
+            from: current.take(),
+            value: value.clone(),
+        });
+        current = value;
+    }
+    changes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn rev(hash: &str, date: &str, content: &str) -> (Commit, String) {
+        let commit = Commit {
+            hash: hash.into(),
+            date: date.into(),
+            author: "Alice".into(),
+            path: PathBuf::from("docs/adr-001.md"),
+        };
+        (commit, content.to_string())
+    }
+
+    #[test]
+    fn test_transitions() {
+        let revisions = vec![
+            rev("a1", "2026-01-02", "---\ntitle: T\nstatus: proposed\n---\n"),
+            rev("b2", "2026-01-05", "---\ntitle: T2\nstatus: proposed\n---\n"),
+            rev("c3", "2026-02-10", "---\ntitle: T2\nstatus: accepted\n---\n"),
+            rev("d4", "2026-03-01", "---\ntitle: T2\n---\n"),
+        ];
+        let changes = transitions(&revisions, "status");
+        let summary: Vec<(&str, Option<&str>, Option<&str>)> = changes
+            .iter()
+            .map(|c| (c.commit.as_str(), c.from.as_deref(), c.value.as_deref()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("a1", None, Some("proposed")),
+                ("c3", Some("proposed"), Some("accepted")),
+                ("d4", Some("accepted"), None),
+            ]
+        );
+        assert_eq!(changes[1].date, "2026-02-10");
+
+        // A field that never appears has no history
+        assert!(transitions(&revisions, "owner").is_empty());
+    }
+
+    #[test]
+    fn test_blame() {
+        let revisions = vec![
+            rev("a1", "2026-01-02", "---\ntitle: T\nstatus: proposed\n---\n"),
+            rev("b2", "2026-01-05", "---\ntitle: T2\nstatus: proposed\n---\n"),
+            rev("c3", "2026-02-10", "---\ntitle: T2\nstatus: accepted\n---\n"),
+        ];
+        let (current, _) = Frontmatter::parse("---\ntitle: T3\nstatus: accepted\n---\n").unwrap();
+        let fields = ["status".to_string(), "title".to_string(), "owner".to_string()];
+        let blamed = blame(&revisions, Some(&current), &fields);
+        let summary: Vec<(&str, Option<&str>, Option<&str>)> = blamed
+            .iter()
+            .map(|b| (b.field.as_str(), b.value.as_deref(), b.change.as_ref().map(|c| c.commit.as_str())))
+            .collect();
+        // The title edit isn't committed; owner was never set
+        assert_eq!(
+            summary,
+            vec![("status", Some("accepted"), Some("c3")), ("title", Some("T3"), None), ("owner", None, None)]
+        );
+
+        // No fields named means every top-level field, in key order
+        let all = blame(&revisions, Some(&current), &[]);
+        assert_eq!(all.iter().map(|b| b.field.as_str()).collect::<Vec<_>>(), vec!["status", "title"]);
+    }
+}
@@ -0,0 +1,214 @@
+//! Bulk document creation from CSV.
+//!
+//! Each data row becomes one document of the given type, rendered through the same
+//! template engine as `md-db new` and numbered from the directory's next free ID.
+//! Columns map to fields through an explicit `Column=field` list; unmapped columns
+//! whose header names a field of the type (case-insensitively) are used as-is, and
+//! the rest are ignored. Cells are read like `--field` values, so `true`, `42`, and
+//! `[a, b]` keep their YAML types. Empty cells leave the field at its default.
+
+use std::path::{Path, PathBuf};
+
+use crate::error::{Error, Result};
+use crate::graph::DocGraph;
+use crate::schema::{Schema, TypeDef};
+
+/// One document `plan` would create.
+#[derive(Debug, Clone)]
+pub struct PlannedDoc {
+    pub id: String,
+    pub path: PathBuf,
+    pub content: String,
+}
+
+/// Options for [`plan`].
+#[derive(Debug, Clone, Default)]
+pub struct ImportOptions {
+    /// `(column header, field)` pairs, from `Title=title,Pri=priority`.
+    pub map: Vec<(String, String)>,
+    /// Expand date placeholders to real values.
+    pub fill: bool,
+    /// Give each document a stable `uid`.
+    pub uid: bool,
+}
+
+/// The documents for every row of `csv`, with paths under `dir` (in the type's folder).
+/// Also returns the headers that matched no field.
+pub fn plan(
+    csv: &str,
+    type_def: &TypeDef,
+    schema: &Schema,
+    schema_dir: &Path,
+    dir: &Path,
+    opts: &ImportOptions,
+) -> Result<(Vec<PlannedDoc>, Vec<String>)> {
+    let mut rows = parse_csv(csv)?.into_iter();
+    let headers = rows.next().ok_or_else(|| Error::Import("CSV has no header row".into()))?;
+
+    for (column, field) in &opts.map {
+        if !headers.iter().any(|h| h.trim() == column) {
+            return Err(Error::Import(format!("--map names column '{column}', not in the CSV header")));
+        }
+        if !type_def.fields.iter().any(|f| &f.name == field) {
+            return Err(Error::Import(format!(
+                "column '{column}' maps to unknown field '{field}' of type '{}'",
+                type_def.name
+            )));
+        }
+    }
+    let mut ignored = Vec::new();
+    let fields: Vec<Option<String>> = headers
+        .iter()
+        .map(|h| {
+            let h = h.trim();
+            let field = match opts.map.iter().find(|(column, _)| column == h) {
+                Some((_, field)) => Some(field.clone()),
+                None => type_def.fields.iter().find(|f| f.name.eq_ignore_ascii_case(h)).map(|f| f.name.clone()),
+            };
+            if field.is_none() {
+                ignored.push(h.to_string());
+            }
+            field
+        })
+        .collect();
+
+    let first = DocGraph::build(dir, schema)?.next_id(&type_def.name);
+    let (prefix, start) = split_id(&first);
+    let folder = dir.join(type_def.folder.as_deref().unwrap_or("."));
+
+    let mut docs = Vec::new();
+    for row in rows.filter(|r| r.iter().any(|cell| !cell.trim().is_empty())) {
+        let id = format!("{prefix}-{:03}", start + docs.len() as u32);
+        let mut values: Vec<(String, String)> = fields
+            .iter()
+            .zip(&row)
+            .filter_map(|(field, cell)| Some((field.clone()?, cell.trim().to_string())))
+            .filter(|(_, cell)| !cell.is_empty())
+            .collect();
+        if opts.uid {
+            values.push((crate::uid::UID_FIELD.to_string(), crate::uid::generate()));
+        }
+        let content = crate::template::new_document(type_def, schema, schema_dir, &values, Some(&id), opts.fill)?;
+        let path = folder.join(format!("{}.md", id.to_lowercase()));
+        if path.exists() {
+            return Err(Error::Import(format!("{} already exists", path.display())));
+        }
+        docs.push(PlannedDoc { id, path, content });
+    }
+    Ok((docs, ignored))
+}
+
+fn split_id(id: &str) -> (&str, u32) {
+    let (prefix, number) = id.rsplit_once('-').unwrap_or((id, "1"));
+    (prefix, number.parse().unwrap_or(1))
+}
+
+/// Parse RFC 4180 CSV: quoted fields may hold commas, newlines, and `""` escapes.
+/// Accepts CRLF or LF line ends and a leading byte-order mark.
+pub fn parse_csv(text: &str) -> Result<Vec<Vec<String>>> {
+    let text = text.strip_prefix('\u{feff}').unwrap_or(text);
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut line = 1;
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if quoted {
+            match c {
+                '"' if chars.peek() == Some(&'"') => {
+                    chars.next();
+                    field.push('"');
+                }
+                '"' => quoted = false,
+                c => {
+                    if c == '\n' {
+                        line += 1;
+                    }
+                    field.push(c);
+                }
+            }
+            continue;
+        }
+        match c {
+            '"' if field.is_empty() => quoted = true,
+            ',' => row.push(std::mem::take(&mut field)),
+            '\r' if chars.peek() == Some(&'\n') => {}
+            '\n' => {
+                row.push(std::mem::take(&mut field));
+                rows.push(std::mem::take(&mut row));
+                line += 1;
+            }
+            c => field.push(c),
+        }
+    }
+    if quoted {
+        return Err(Error::Import(format!("unterminated quoted field starting before line {line}")));
+    }
+    if !field.is_empty() || !row.is_empty() {
+        row.push(field);
+        rows.push(row);
+    }
+    Ok(rows)
+}
+
+/// Parse `Title=title,Pri=priority` into `(column, field)` pairs.
+pub fn parse_map(spec: &str) -> Result<Vec<(String, String)>> {
+    spec.split(',')
+        .filter(|pair| !pair.trim().is_empty())
+        .map(|pair| {
+            let (column, field) = pair
+                .split_once('=')
+                .ok_or_else(|| Error::Import(format!("invalid --map entry '{pair}', expected Column=field")))?;
+            Ok((column.trim().to_string(), field.trim().to_string()))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_csv() {
+        let rows = parse_csv("\u{feff}Title,Notes\r\n\"Cache, warm\",\"say \"\"hi\"\"\nthen go\"\r\nPlain,\n").unwrap();
+        assert_eq!(rows.len(), 3);
+        assert_eq!(rows[1], vec!["Cache, warm", "say \"hi\"\nthen go"]);
+        assert_eq!(rows[2], vec!["Plain", ""]);
+        assert!(parse_csv("a,\"b\n").is_err());
+    }
+
+    #[test]
+    fn test_plan_import() {
+        let dir = tempfile::tempdir().unwrap();
+        let schema = Schema::from_str(
+            "type \"opp\" folder=\"opps\" {\n    field \"title\" type=\"string\" required=#true\n    field \"priority\" type=\"number\"\n    field \"status\" type=\"enum\" {\n        values \"new\" \"done\"\n    }\n}\n",
+        )
+        .unwrap();
+        std::fs::create_dir_all(dir.path().join("opps")).unwrap();
+        std::fs::write(dir.path().join("opps/opp-007.md"), "---\ntype: opp\ntitle: Old\n---\n").unwrap();
+
+        let opts = ImportOptions {
+            map: parse_map("Title=title, Pri=priority").unwrap(),
+            ..Default::default()
+        };
+        let csv = "Title,Pri,Status,Owner\nFaster search,2,new,ana\n,,,\nBulk edit,,done,bo\n";
+        let type_def = schema.get_type("opp").unwrap();
+        let (docs, ignored) = plan(csv, type_def, &schema, dir.path(), dir.path(), &opts).unwrap();
+
+        assert_eq!(ignored, vec!["Owner"]);
+        assert_eq!(docs.len(), 2);
+        assert_eq!(docs[0].id, "OPP-008");
+        assert_eq!(docs[1].path, dir.path().join("opps").join("opp-009.md"));
+        assert!(docs[0].content.contains("title: Faster search"));
+        assert!(docs[0].content.contains("priority: 2"));
+        assert!(docs[1].content.contains("status: done"));
+
+        let bad = ImportOptions {
+            map: parse_map("Title=name").unwrap(),
+            ..Default::default()
+        };
+        assert!(plan(csv, type_def, &schema, dir.path(), dir.path(), &bad).is_err());
+    }
+}
@@ -0,0 +1,414 @@
+//! Keep action-item table rows in step with GitHub issues (`md-db sync github-issues`).
+//!
+//! Each row of the first table in a section (e.g. "Action Items") becomes an
+//! issue titled `ID: action`, assigned to the row's `Owner`. The issue number is
+//! written back into the row's issue column (added when the table has none), so
+//! later runs update the same issue. The action and owner are pushed to the
+//! issue's title and assignee. The `Status` column follows whichever side
+//! changed since the last run: a closed issue marks the row done, and a row
+//! marked done closes the issue. `<root>/.md-db/github-issues.json` records each
+//! row as last synced.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::document::Document;
+use crate::error::{Error, Result};
+use crate::graph_cache::CACHE_DIR;
+
+/// State file (inside `CACHE_DIR`) with the last synced state of each row.
+const STATE_FILE: &str = "github-issues.json";
+
+/// Hidden marker appended to issue bodies so issues can be traced back to a document.
+const MARKER_PREFIX: &str = "<!-- md-db-action-item:";
+
+/// A row as last synced with its issue.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ItemRecord {
+    pub title: String,
+    pub assignee: Option<String>,
+    /// Whether the issue was closed (and the row done).
+    pub closed: bool,
+}
+
+/// Synced rows of a docs directory, by `ID#issue`.
+#[derive(Debug)]
+pub struct IssueState {
+    root: PathBuf,
+    items: BTreeMap<String, ItemRecord>,
+}
+
+impl IssueState {
+    /// Load `<root>/.md-db/github-issues.json`, or start empty.
+    pub fn open(root: impl AsRef<Path>) -> Result<Self> {
+        let root = root.as_ref().to_path_buf();
+        let path = root.join(CACHE_DIR).join(STATE_FILE);
+        let items = if path.exists() {
+            serde_json::from_str(&std::fs::read_to_string(&path)?)?
+        } else {
+            BTreeMap::new()
+        };
+        Ok(Self { root, items })
+    }
+
+    pub fn items(&self) -> &BTreeMap<String, ItemRecord> {
+        &self.items
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let dir = self.root.join(CACHE_DIR);
+        std::fs::create_dir_all(&dir).map_err(|_| Error::WriteFailed(dir.clone()))?;
+        let path = dir.join(STATE_FILE);
+        let data = serde_json::to_string_pretty(&self.items)?;
+        crate::durability::write(&path, data)?;
+        Ok(())
+    }
+}
+
+/// Backend that creates and updates issues.
+pub trait IssueTracker {
+    /// Create an issue and return its number.
+    fn create(&mut self, title: &str, body: &str, assignee: Option<&str>) -> Result<u64>;
+    fn edit(&mut self, number: u64, title: Option<&str>, add: Option<&str>, remove: Option<&str>) -> Result<()>;
+    fn is_closed(&mut self, number: u64) -> Result<bool>;
+    fn set_closed(&mut self, number: u64, closed: bool) -> Result<()>;
+}
+
+#[derive(Debug, Clone)]
+pub struct IssueSyncOptions<'a> {
+    /// Heading of the section holding the table (case-insensitive).
+    pub section: &'a str,
+    /// Column with the action; the first column when `None`.
+    pub title_column: Option<&'a str>,
+    /// Column holding the issue number.
+    pub issue_column: &'a str,
+    /// Status values that mean done; the first is written when an issue is closed.
+    pub done_values: &'a [String],
+    /// Status written when a closed issue is reopened.
+    pub open_value: &'a str,
+    /// URL prefix for the document link in issue bodies (e.g. a repository blob URL).
+    pub link_base: Option<&'a str>,
+    /// Only read issues; create, edit, close, and record nothing.
+    pub dry_run: bool,
+}
+
+/// What happened to one row.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ItemAction {
+    Created,
+    Retitled,
+    Assigned(Option<String>),
+    Closed,
+    Reopened,
+    /// The issue was closed or reopened; the row's status follows.
+    StatusSet(String),
+}
+
+impl std::fmt::Display for ItemAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ItemAction::Created => write!(f, "created"),
+            ItemAction::Retitled => write!(f, "retitled"),
+            ItemAction::Assigned(Some(user)) => write!(f, "assigned to {user}"),
+            ItemAction::Assigned(None) => write!(f, "unassigned"),
+            ItemAction::Closed => write!(f, "closed"),
+            ItemAction::Reopened => write!(f, "reopened"),
+            ItemAction::StatusSet(value) => write!(f, "status set to {value}"),
+        }
+    }
+}
+
+/// A row and what the sync did for it.
+#[derive(Debug, Clone)]
+pub struct ItemChange {
+    pub doc_id: String,
+    pub title: String,
+    /// `None` for an issue not created yet (dry run).
+    pub issue: Option<u64>,
+    pub actions: Vec<ItemAction>,
+}
+
+/// Sync the action items of `doc` (ID `doc_id`) with their issues. The document
+/// is changed in memory (issue numbers, statuses); the caller saves it.
+/// Documents without the section or a table in it have no items.
+pub fn sync_document(
+    doc: &mut Document,
+    doc_id: &str,
+    tracker: &mut dyn IssueTracker,
+    state: &mut IssueState,
+    opts: &IssueSyncOptions,
+) -> Result<Vec<ItemChange>> {
+    let Some(table) = doc.get_section(opts.section).ok().and_then(|s| s.tables().into_iter().next()) else {
+        return Ok(Vec::new());
+    };
+    let column = |name: &str| table.headers().iter().find(|h| h.eq_ignore_ascii_case(name)).cloned();
+    let title_column = match opts.title_column {
+        Some(name) => column(name).ok_or_else(|| Error::ColumnNotFound(name.to_string()))?,
+        None => match table.headers().first() {
+            Some(first) => first.clone(),
+            None => return Ok(Vec::new()),
+        },
+    };
+    let owner_column = column("Owner");
+    let status_column = column("Status");
+    let issue_column = match column(opts.issue_column) {
+        Some(name) => name,
+        None => {
+            doc.add_table_column(opts.section, 0, opts.issue_column)?;
+            opts.issue_column.to_string()
+        }
+    };
+    let done_value = opts.done_values.first().map_or("done", String::as_str);
+    let doc_link = match (opts.link_base, doc.path.as_deref()) {
+        (Some(base), Some(path)) => {
+            let path = path.to_string_lossy().replace('\\', "/");
+            format!("[{doc_id}]({}/{})", base.trim_end_matches('/'), path.trim_start_matches("./"))
+        }
+        (None, Some(path)) => format!("{doc_id} (`{}`)", path.display()),
+        (_, None) => doc_id.to_string(),
+    };
+
+    let mut changes = Vec::new();
+    for row in 0..table.rows().len() {
+        let cell = |column: Option<&String>| column.and_then(|c| table.get_cell(c, row)).map_or("", str::trim);
+        let action = cell(Some(&title_column));
+        if action.is_empty() {
+            continue;
+        }
+        let title = format!("{doc_id}: {action}");
+        let assignee = cell(owner_column.as_ref())
+            .split([',', ' '])
+            .map(|owner| owner.trim_start_matches('@'))
+            .find(|owner| !owner.is_empty())
+            .map(str::to_string);
+        let status = cell(status_column.as_ref());
+        let done = opts.done_values.iter().any(|v| v.eq_ignore_ascii_case(status));
+        let mut change = ItemChange {
+            doc_id: doc_id.to_string(),
+            title: title.clone(),
+            issue: issue_number(cell(Some(&issue_column))),
+            actions: Vec::new(),
+        };
+
+        let Some(number) = change.issue else {
+            change.actions.push(ItemAction::Created);
+            if done {
+                change.actions.push(ItemAction::Closed);
+            }
+            if !opts.dry_run {
+                let body = format!(
+                    "Action item from {doc_link}, section \"{}\".\n\n{MARKER_PREFIX}{doc_id} -->\n",
+                    opts.section
+                );
+                let number = tracker.create(&title, &body, assignee.as_deref())?;
+                if done {
+                    tracker.set_closed(number, true)?;
+                }
+                doc.set_table_cell(opts.section, 0, &issue_column, row, &format!("#{number}"))?;
+                let record = ItemRecord { title, assignee, closed: done };
+                state.items.insert(format!("{doc_id}#{number}"), record);
+                change.issue = Some(number);
+            }
+            changes.push(change);
+            continue;
+        };
+
+        let key = format!("{doc_id}#{number}");
+        let record = state.items.get(&key).cloned();
+        let retitle = record.as_ref().is_none_or(|r| r.title != title);
+        let reassign = record.as_ref().is_none_or(|r| r.assignee != assignee);
+        if retitle || reassign {
+            if retitle {
+                change.actions.push(ItemAction::Retitled);
+            }
+            if reassign {
+                change.actions.push(ItemAction::Assigned(assignee.clone()));
+            }
+            if !opts.dry_run {
+                let previous = record.as_ref().and_then(|r| r.assignee.as_deref()).filter(|_| reassign);
+                let add = assignee.as_deref().filter(|_| reassign);
+                tracker.edit(number, retitle.then_some(title.as_str()), add, previous.filter(|p| Some(*p) != add))?;
+            }
+        }
+
+        // The status follows the side that changed since the last sync; the row wins otherwise
+        let mut closed = tracker.is_closed(number)?;
+        if let Some(ref status_column) = status_column {
+            if done != closed {
+                if record.as_ref().is_some_and(|r| r.closed != closed) {
+                    let value = if closed { done_value } else { opts.open_value };
+                    doc.set_table_cell(opts.section, 0, status_column, row, value)?;
+                    change.actions.push(ItemAction::StatusSet(value.to_string()));
+                } else {
+                    if !opts.dry_run {
+                        tracker.set_closed(number, done)?;
+                    }
+                    change.actions.push(if done { ItemAction::Closed } else { ItemAction::Reopened });
+                    closed = done;
+                }
+            }
+        }
+        if !opts.dry_run {
+            state.items.insert(key, ItemRecord { title, assignee, closed });
+        }
+        changes.push(change);
+    }
+    Ok(changes)
+}
+
+/// The issue number in a cell: `#12`, `12`, or an issue URL.
+fn issue_number(cell: &str) -> Option<u64> {
+    if let Ok(number) = cell.parse() {
+        return Some(number);
+    }
+    let re = Regex::new(r"(?:#|/issues/)(\d+)").unwrap();
+    re.captures(cell).and_then(|c| c[1].parse().ok())
+}
+
+/// GitHub Issues through the `gh` CLI, which handles authentication.
+#[derive(Debug, Clone, Default)]
+pub struct GitHubIssues {
+    /// `owner/name`; defaults to the repository of the current directory.
+    pub repo: Option<String>,
+    pub labels: Vec<String>,
+}
+
+impl IssueTracker for GitHubIssues {
+    fn create(&mut self, title: &str, body: &str, assignee: Option<&str>) -> Result<u64> {
+        let mut args = vec!["issue", "create", "--title", title, "--body-file", "-"];
+        for label in &self.labels {
+            args.extend(["--label", label.as_str()]);
+        }
+        if let Some(assignee) = assignee {
+            args.extend(["--assignee", assignee]);
+        }
+        // `gh issue create` prints the new issue URL
+        let url = crate::triage::gh(self.repo.as_deref(), &args, Some(body))?;
+        let url = url.lines().last().unwrap_or_default();
+        url.rsplit('/')
+            .next()
+            .and_then(|n| n.parse().ok())
+            .ok_or_else(|| Error::Ticket(format!("unexpected gh output: {url}")))
+    }
+
+    fn edit(&mut self, number: u64, title: Option<&str>, add: Option<&str>, remove: Option<&str>) -> Result<()> {
+        let number = number.to_string();
+        let mut args = vec!["issue", "edit", number.as_str()];
+        if let Some(title) = title {
+            args.extend(["--title", title]);
+        }
+        if let Some(add) = add {
+            args.extend(["--add-assignee", add]);
+        }
+        if let Some(remove) = remove {
+            args.extend(["--remove-assignee", remove]);
+        }
+        crate::triage::gh(self.repo.as_deref(), &args, None)?;
+        Ok(())
+    }
+
+    fn is_closed(&mut self, number: u64) -> Result<bool> {
+        let number = number.to_string();
+        let args = ["issue", "view", number.as_str(), "--json", "state", "--jq", ".state"];
+        Ok(crate::triage::gh(self.repo.as_deref(), &args, None)? == "CLOSED")
+    }
+
+    fn set_closed(&mut self, number: u64, closed: bool) -> Result<()> {
+        let number = number.to_string();
+        let verb = if closed { "close" } else { "reopen" };
+        crate::triage::gh(self.repo.as_deref(), &["issue", verb, number.as_str()], None)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Default)]
+    struct FakeTracker {
+        closed: BTreeMap<u64, bool>,
+        calls: Vec<String>,
+    }
+
+    impl IssueTracker for FakeTracker {
+        fn create(&mut self, title: &str, _body: &str, assignee: Option<&str>) -> Result<u64> {
+            let number = self.closed.len() as u64 + 1;
+            self.closed.insert(number, false);
+            self.calls.push(format!("create {title} {assignee:?}"));
+            Ok(number)
+        }
+        fn edit(&mut self, number: u64, title: Option<&str>, add: Option<&str>, remove: Option<&str>) -> Result<()> {
+            self.calls.push(format!("edit {number} {title:?} {add:?} {remove:?}"));
+            Ok(())
+        }
+        fn is_closed(&mut self, number: u64) -> Result<bool> {
+            Ok(self.closed[&number])
+        }
+        fn set_closed(&mut self, number: u64, closed: bool) -> Result<()> {
+            self.closed.insert(number, closed);
+            self.calls.push(format!("{} {number}", if closed { "close" } else { "reopen" }));
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_sync_document() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut doc = Document::from_str(
+            "---\ntype: inc\n---\n\n# Action Items\n\n| Action | Owner | Status |\n|---|---|---|\n\
+             | Add lag alert | @ana | open |\n| Write runbook | @bo | done |\n",
+        )
+        .unwrap();
+        let done = vec!["done".to_string()];
+        let opts = IssueSyncOptions {
+            section: "action items",
+            title_column: None,
+            issue_column: "Issue",
+            done_values: &done,
+            open_value: "open",
+            link_base: None,
+            dry_run: false,
+        };
+        let mut tracker = FakeTracker::default();
+        let mut state = IssueState::open(dir.path()).unwrap();
+
+        let changes = sync_document(&mut doc, "INC-001", &mut tracker, &mut state, &opts).unwrap();
+        assert_eq!(changes[1].actions, vec![ItemAction::Created, ItemAction::Closed]);
+        assert!(
+            doc.body.contains("| Add lag alert | @ana | open | #1 |\n| Write runbook | @bo | done | #2 |"),
+            "{}",
+            doc.body
+        );
+        assert_eq!(
+            tracker.calls,
+            vec![
+                "create INC-001: Add lag alert Some(\"ana\")",
+                "create INC-001: Write runbook Some(\"bo\")",
+                "close 2"
+            ]
+        );
+
+        // Nothing changed: no edits
+        tracker.calls.clear();
+        let changes = sync_document(&mut doc, "INC-001", &mut tracker, &mut state, &opts).unwrap();
+        assert!(changes.iter().all(|c| c.actions.is_empty()));
+        assert!(tracker.calls.is_empty());
+
+        // Issue #1 closed on GitHub, #2's row reopened and reassigned in the document
+        tracker.closed.insert(1, true);
+        let mut doc = Document::from_str(&doc.raw.replace("| @bo | done |", "| @cy | open |")).unwrap();
+        let changes = sync_document(&mut doc, "INC-001", &mut tracker, &mut state, &opts).unwrap();
+        assert_eq!(changes[0].actions, vec![ItemAction::StatusSet("done".into())]);
+        assert_eq!(changes[1].actions, vec![ItemAction::Assigned(Some("cy".into())), ItemAction::Reopened]);
+        assert!(doc.body.contains("| Add lag alert | @ana | done | #1 |\n| Write runbook | @cy | open | #2 |"));
+        assert_eq!(tracker.calls, vec!["edit 2 None Some(\"cy\") Some(\"bo\")", "reopen 2"]);
+
+        state.save().unwrap();
+        assert!(IssueState::open(dir.path()).unwrap().items()["INC-001#1"].closed);
+        assert_eq!(issue_number("https://github.com/o/r/issues/42"), Some(42));
+    }
+}
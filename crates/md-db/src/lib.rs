@@ -1,19 +1,81 @@
+pub mod aggregate;
+pub mod assets;
 pub mod ast_util;
+pub mod audit;
+pub mod auth;
+pub mod badges;
+pub mod board;
+pub mod changelog;
+mod curl;
+pub mod db;
 pub mod discovery;
 pub mod diff;
 pub mod document;
+pub mod durability;
 pub mod error;
 pub mod export;
+pub mod fix;
+pub mod fixture;
+pub mod format;
 pub mod frontmatter;
 pub mod graph;
+pub mod graph_cache;
+pub mod health;
+pub mod histogram;
+pub mod history;
+pub mod import;
+pub mod issue_sync;
+pub mod lineage;
+pub mod lint;
+pub mod locale;
+pub mod marker;
+pub mod mcp_config;
+pub mod merge;
 pub mod migrate;
+pub mod normalize;
+pub mod notify;
+pub mod ordering;
+pub mod outline;
 pub mod output;
+pub mod owners;
+pub mod pattern;
+pub mod permalink;
+pub mod pipeline;
+pub mod project;
+pub mod projection;
+pub mod promote;
+pub mod prose;
+pub mod query;
+pub mod reassign;
+pub mod sanitize;
 pub mod schema;
+mod schema_export;
+pub mod schema_test;
 pub mod section;
+pub mod selftest;
+pub mod span;
+pub mod split;
+pub mod sqlite;
 pub mod table;
+pub mod tables;
 pub mod template;
+pub mod terminal;
+pub mod theme;
+pub mod triage;
+pub mod uid;
+pub mod undo;
 pub mod users;
 pub mod cache;
+pub mod capacity;
+pub mod cache_file;
+pub mod conflicts;
+pub mod context;
+pub mod diagram;
 pub mod sync;
 pub mod search;
 pub mod validation;
+pub mod vcs;
+pub mod views;
+
+pub use db::Db;
+pub use normalize::{normalize, NormalizeOptions};
@@ -0,0 +1,251 @@
+//! A decision's history along its `supersedes` chain.
+//!
+//! Each generation is one document in the chain, oldest first, with a structural
+//! diff against the generation it replaced.
+
+use std::collections::{BTreeMap, HashSet};
+
+use serde::Serialize;
+
+use crate::diff::{self, DocDiff, FieldChangeKind, SectionChangeKind};
+use crate::document::Document;
+use crate::error::{Error, Result};
+use crate::graph::DocGraph;
+use crate::schema::Schema;
+
+/// Relation whose chain is followed; its inverse (e.g. `superseded_by`) is honored too.
+pub const SUPERSEDES: &str = "supersedes";
+
+/// One document in the chain.
+#[derive(Debug, Clone, Serialize)]
+pub struct Generation {
+    pub id: String,
+    pub path: String,
+    pub title: Option<String>,
+    pub status: Option<String>,
+    pub date: Option<String>,
+    pub authors: Vec<String>,
+    /// Changes from the previous generation; `None` for the first.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub diff: Option<DocDiff>,
+}
+
+/// The full chain a document belongs to, oldest first.
+#[derive(Debug, Clone, Serialize)]
+pub struct Lineage {
+    /// The document the lineage was requested for.
+    pub id: String,
+    pub generations: Vec<Generation>,
+}
+
+impl Lineage {
+    /// Mermaid `timeline` with one entry per generation.
+    pub fn to_mermaid(&self) -> String {
+        let mut out = format!("timeline\n    title Lineage of {}\n", self.id);
+        for g in &self.generations {
+            let mut entry = g.id.clone();
+            if let Some(ref title) = g.title {
+                entry.push_str(&format!(" {title}"));
+            }
+            if let Some(ref status) = g.status {
+                entry.push_str(&format!(" ({status})"));
+            }
+            // ':' separates events in mermaid timelines
+            let entry = entry.replace(':', " -");
+            out.push_str(&format!(
+                "    {} : {}\n",
+                g.date.as_deref().unwrap_or("undated"),
+                entry
+            ));
+        }
+        out
+    }
+}
+
+/// Trace the `supersedes` chain through `id`: everything it replaced and everything that replaced it.
+pub fn trace(graph: &DocGraph, id: &str, schema: &Schema) -> Result<Lineage> {
+    if !graph.nodes.contains_key(id) {
+        return Err(Error::DocumentNotFound(id.to_string()));
+    }
+
+    // newer -> older, and older -> newer
+    let mut older: BTreeMap<&str, &str> = BTreeMap::new();
+    let mut newer: BTreeMap<&str, &str> = BTreeMap::new();
+    for edge in &graph.edges {
+        let Some((rel, is_inverse)) = schema.find_relation(&edge.relation) else {
+            continue;
+        };
+        if rel.name != SUPERSEDES || !graph.nodes.contains_key(&edge.to) {
+            continue;
+        }
+        let (new, old) = if is_inverse {
+            (edge.to.as_str(), edge.from.as_str())
+        } else {
+            (edge.from.as_str(), edge.to.as_str())
+        };
+        older.entry(new).or_insert(old);
+        newer.entry(old).or_insert(new);
+    }
+
+    // Walk back to the root, then forward to the newest generation
+    let mut seen = HashSet::new();
+    let mut root = id;
+    seen.insert(root);
+    while let Some(&prev) = older.get(root) {
+        if !seen.insert(prev) {
+            break;
+        }
+        root = prev;
+    }
+    let mut chain = vec![root];
+    let mut seen = HashSet::from([root]);
+    let mut current = root;
+    while let Some(&next) = newer.get(current) {
+        if !seen.insert(next) {
+            break;
+        }
+        chain.push(next);
+        current = next;
+    }
+
+    let mut generations = Vec::new();
+    let mut previous: Option<String> = None;
+    for gen_id in chain {
+        let node = &graph.nodes[gen_id];
+        let raw = std::fs::read_to_string(&node.path)?;
+        let doc = Document::from_str(&raw)?;
+        let fm = doc.frontmatter.as_ref();
+
+        let mut authors = Vec::new();
+        for key in ["author", "authors"] {
+            match fm.and_then(|fm| fm.get(key)) {
+                Some(serde_yaml::Value::Sequence(items)) => {
+                    authors.extend(items.iter().map(crate::frontmatter::yaml_value_to_string));
+                }
+                Some(v) => authors.push(crate::frontmatter::yaml_value_to_string(v)),
+                None => {}
+            }
+        }
+
+        let diff = match previous {
+            Some(ref prev) => Some(diff::diff_documents(prev, &raw)?),
+            None => None,
+        };
+        generations.push(Generation {
+            id: gen_id.to_string(),
+            path: node.path.display().to_string(),
+            title: node.title.clone(),
+            status: node.status.clone(),
+            date: fm.and_then(|fm| fm.get_display("date")),
+            authors,
+            diff,
+        });
+        previous = Some(raw);
+    }
+
+    Ok(Lineage {
+        id: id.to_string(),
+        generations,
+    })
+}
+
+/// One-line summary of a diff: `fields: status, title; sections: +Migration, ~Decision`.
+pub fn summarize_diff(diff: &DocDiff) -> String {
+    let mut parts = Vec::new();
+    if !diff.field_changes.is_empty() {
+        let fields: Vec<String> = diff
+            .field_changes
+            .iter()
+            .map(|c| {
+                let sign = match c.kind {
+                    FieldChangeKind::Added => "+",
+                    FieldChangeKind::Removed => "-",
+                    FieldChangeKind::Changed => "~",
+                };
+                format!("{sign}{}", c.field)
+            })
+            .collect();
+        parts.push(format!("fields: {}", fields.join(", ")));
+    }
+    if !diff.section_changes.is_empty() {
+        let sections: Vec<String> = diff
+            .section_changes
+            .iter()
+            .map(|c| {
+                let sign = match c.kind {
+                    SectionChangeKind::Added => "+",
+                    SectionChangeKind::Removed => "-",
+                    SectionChangeKind::Modified => "~",
+                };
+                format!("{sign}{}", c.section)
+            })
+            .collect();
+        parts.push(format!("sections: {}", sections.join(", ")));
+    }
+    if parts.is_empty() {
+        "no structural changes".into()
+    } else {
+        parts.join("; ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn setup() -> (tempfile::TempDir, Schema) {
+        let dir = tempfile::tempdir().unwrap();
+        let files = [
+            (
+                "adr-001.md",
+                "---\ntype: adr\ntitle: Use MySQL\nstatus: superseded\ndate: 2023-01-10\nauthor: \"@alice\"\nsuperseded_by: ADR-002\n---\n\n# Decision\n\nMySQL.\n",
+            ),
+            (
+                "adr-002.md",
+                "---\ntype: adr\ntitle: Use Postgres\nstatus: superseded\ndate: 2024-02-01\nauthors: [\"@bob\", \"@carol\"]\n---\n\n# Decision\n\nPostgres.\n\n# Migration\n\nDump and load.\n",
+            ),
+            (
+                "adr-003.md",
+                "---\ntype: adr\ntitle: Use Postgres 16\nstatus: accepted\ndate: 2025-03-03\nsupersedes: ADR-002\n---\n\n# Decision\n\nPostgres.\n\n# Migration\n\nDump and load.\n",
+            ),
+            ("adr-004.md", "---\ntype: adr\ntitle: Unrelated\nstatus: accepted\n---\n"),
+        ];
+        for (name, content) in files {
+            std::fs::write(dir.path().join(name), content).unwrap();
+        }
+        let schema =
+            Schema::from_str(r#"relation "supersedes" inverse="superseded_by" cardinality="one""#).unwrap();
+        (dir, schema)
+    }
+
+    #[test]
+    fn test_trace_from_middle_of_chain() {
+        let (dir, schema) = setup();
+        let graph = DocGraph::build(dir.path(), &schema).unwrap();
+        let lineage = trace(&graph, "ADR-002", &schema).unwrap();
+
+        let ids: Vec<&str> = lineage.generations.iter().map(|g| g.id.as_str()).collect();
+        assert_eq!(ids, vec!["ADR-001", "ADR-002", "ADR-003"]);
+        assert_eq!(lineage.generations[0].authors, vec!["@alice"]);
+        assert_eq!(lineage.generations[1].authors, vec!["@bob", "@carol"]);
+        assert!(lineage.generations[0].diff.is_none());
+
+        let summary = summarize_diff(lineage.generations[1].diff.as_ref().unwrap());
+        assert!(summary.contains("~title"), "{summary}");
+        assert!(summary.contains("+Migration"), "{summary}");
+    }
+
+    #[test]
+    fn test_single_generation_and_mermaid() {
+        let (dir, schema) = setup();
+        let graph = DocGraph::build(dir.path(), &schema).unwrap();
+        let lineage = trace(&graph, "ADR-004", &schema).unwrap();
+        assert_eq!(lineage.generations.len(), 1);
+        assert!(lineage.to_mermaid().contains("undated : ADR-004 Unrelated (accepted)"));
+
+        let lineage = trace(&graph, "ADR-003", &schema).unwrap();
+        assert!(lineage.to_mermaid().contains("2023-01-10 : ADR-001 Use MySQL (superseded)"));
+
+        assert!(matches!(trace(&graph, "ADR-999", &schema), Err(Error::DocumentNotFound(_))));
+    }
+}
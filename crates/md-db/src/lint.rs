@@ -0,0 +1,448 @@
+//! Style lint configured by the schema's `lint { }` block.
+//!
+//! Lint is separate from validation: validation checks that a document has the
+//! shape its type requires, lint checks how it is written. Every rule has its
+//! own severity, and diagnostics reuse the validation report types.
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::document::Document;
+use crate::normalize::{fence_open, is_atx_heading};
+use crate::schema::{HeadingCase, LintKind, LintRule, Schema, SectionDef, TypeDef};
+use crate::validation::{Diagnostic, FileResult, Severity, ValidationResult};
+
+/// Words title case leaves lowercase unless they start the heading.
+const MINOR_WORDS: &[&str] = &[
+    "a", "an", "and", "as", "at", "but", "by", "for", "in", "nor", "of", "on", "or", "per", "the",
+    "to", "vs", "via", "with",
+];
+
+/// A heading found in the document body.
+struct Heading {
+    line: usize,
+    level: usize,
+    text: String,
+}
+
+/// Lint one document against the schema's lint rules. Returns no diagnostics
+/// when the schema has no `lint` block.
+pub fn lint_document(doc: &Document, schema: &Schema) -> FileResult {
+    let path = doc
+        .path
+        .as_ref()
+        .map(|p| p.display().to_string())
+        .unwrap_or_else(|| "<string>".to_string());
+    let mut diagnostics = Vec::new();
+    let Some(ref lint) = schema.lint else {
+        return FileResult { path, diagnostics };
+    };
+
+    let type_def = doc
+        .frontmatter
+        .as_ref()
+        .and_then(|fm| fm.get_display("type"))
+        .and_then(|t| schema.get_type(&t))
+        .or_else(|| doc.path.as_ref().and_then(|p| schema.singleton_for(p)));
+
+    let (fm_lines, body_start) = split_frontmatter(&doc.raw);
+    let lines: Vec<&str> = doc.raw.lines().collect();
+    let body = Body::scan(&lines, body_start);
+
+    for rule in &lint.rules {
+        let diags = &mut diagnostics;
+        match &rule.kind {
+            LintKind::HeadingCase(case) => check_heading_case(&body, *case, type_def, rule, diags),
+            LintKind::MaxLineLength(max) => check_line_length(&body, &lines, *max, rule, diags),
+            LintKind::TableTrailingWhitespace => check_table_whitespace(&body, &lines, rule, diags),
+            LintKind::DuplicateHeadings => check_duplicate_headings(&body, rule, diags),
+            LintKind::EmptySections => check_empty_sections(&body, &lines, rule, diags),
+            LintKind::FrontmatterOrder(keys) => {
+                check_frontmatter_order(&fm_lines, keys, type_def, rule, diags)
+            }
+        }
+    }
+
+    crate::span::fill(doc, &mut diagnostics);
+    diagnostics.sort_by_key(|d| line_of(&d.location));
+    FileResult { path, diagnostics }
+}
+
+/// Lint every markdown file under `dir`.
+pub fn lint_directory(
+    dir: impl AsRef<Path>,
+    schema: &Schema,
+    pattern: Option<&str>,
+) -> crate::error::Result<ValidationResult> {
+    let files = crate::discovery::discover_files(&dir, pattern, &[], false)?;
+    let mut file_results = Vec::new();
+    for path in &files {
+        match Document::from_file(path) {
+            Ok(doc) => file_results.push(lint_document(&doc, schema)),
+            Err(e) => file_results.push(FileResult {
+                path: path.display().to_string(),
+                diagnostics: vec![Diagnostic {
+                    severity: Severity::Error,
+                    code: "E000".into(),
+                    message: format!("failed to parse: {e}"),
+                    location: "file".into(),
+                    hint: None,
+                    span: std::fs::read_to_string(path)
+                        .ok()
+                        .and_then(|raw| crate::span::yaml_error_span(&raw)),
+                }],
+            }),
+        }
+    }
+    Ok(ValidationResult { file_results })
+}
+
+/// Body lines (0-based indices into the file) classified once for every rule.
+struct Body {
+    start: usize,
+    /// Lines inside fenced code blocks, including the fences.
+    in_code: Vec<bool>,
+    headings: Vec<Heading>,
+}
+
+impl Body {
+    fn scan(lines: &[&str], start: usize) -> Self {
+        let mut in_code = vec![false; lines.len()];
+        let mut headings = Vec::new();
+        let mut fence: Option<(char, usize)> = None;
+        for (i, line) in lines.iter().enumerate().skip(start) {
+            if let Some((c, n)) = fence {
+                in_code[i] = true;
+                let t = line.trim();
+                if t.len() >= n && t.chars().all(|ch| ch == c) {
+                    fence = None;
+                }
+                continue;
+            }
+            if let Some(open) = fence_open(line) {
+                in_code[i] = true;
+                fence = Some(open);
+                continue;
+            }
+            if is_atx_heading(line) {
+                let t = line.trim_start();
+                let level = t.len() - t.trim_start_matches('#').len();
+                let text = t[level..].trim().trim_end_matches('#').trim_end().to_string();
+                headings.push(Heading { line: i, level, text });
+            }
+        }
+        Self { start, in_code, headings }
+    }
+
+    /// Indices of body lines outside code blocks.
+    fn prose_lines(&self) -> impl Iterator<Item = usize> + '_ {
+        (self.start..self.in_code.len()).filter(|i| !self.in_code[*i])
+    }
+}
+
+/// Top-level frontmatter lines and the index of the first body line.
+fn split_frontmatter(raw: &str) -> (Vec<(usize, &str)>, usize) {
+    let mut lines = raw.lines().enumerate();
+    if lines.next().map(|(_, l)| l.trim_end()) != Some("---") {
+        return (Vec::new(), 0);
+    }
+    let mut fm = Vec::new();
+    for (i, line) in lines {
+        if line.trim_end() == "---" {
+            return (fm, i + 1);
+        }
+        fm.push((i, line));
+    }
+    // Unterminated: treat everything as body
+    (Vec::new(), 0)
+}
+
+fn diag(rule: &LintRule, code: &str, line: usize, message: String, hint: &str) -> Diagnostic {
+    Diagnostic {
+        severity: if rule.severity == "error" {
+            Severity::Error
+        } else {
+            Severity::Warning
+        },
+        code: code.into(),
+        message,
+        location: format!("line:{}", line + 1),
+        hint: Some(hint.into()),
+        span: None,
+    }
+}
+
+/// Sort key for diagnostics: file line, frontmatter first.
+fn line_of(location: &str) -> usize {
+    location
+        .strip_prefix("line:")
+        .and_then(|n| n.parse().ok())
+        .unwrap_or(0)
+}
+
+fn check_heading_case(
+    body: &Body,
+    case: HeadingCase,
+    type_def: Option<&TypeDef>,
+    rule: &LintRule,
+    diags: &mut Vec<Diagnostic>,
+) {
+    // Headings the schema prescribes are correct by definition
+    let mut prescribed = Vec::new();
+    if let Some(td) = type_def {
+        collect_section_names(&td.sections, &mut prescribed);
+    }
+    for h in &body.headings {
+        if prescribed.contains(&h.text.as_str()) || heading_case_ok(&h.text, case) {
+            continue;
+        }
+        let (name, hint) = match case {
+            HeadingCase::Sentence => ("sentence", "capitalize only the first word and proper nouns"),
+            HeadingCase::Title => ("title", "capitalize every word except short joining words"),
+        };
+        diags.push(diag(
+            rule,
+            "L001",
+            h.line,
+            format!("heading \"{}\" is not in {name} case", h.text),
+            hint,
+        ));
+    }
+}
+
+fn collect_section_names<'a>(sections: &'a [SectionDef], out: &mut Vec<&'a str>) {
+    for s in sections {
+        out.push(&s.name);
+        out.extend(s.aliases.iter().map(String::as_str));
+        collect_section_names(&s.children, out);
+    }
+}
+
+/// Whether `text` follows the case style. Sentence case requires a capital first
+/// letter and flags headings where every later long word is capitalized; proper
+/// nouns make a stricter check unreliable.
+fn heading_case_ok(text: &str, case: HeadingCase) -> bool {
+    let words: Vec<&str> = text.split_whitespace().collect();
+    let Some(first) = words.first() else {
+        return true;
+    };
+    if first.chars().next().is_some_and(|c| c.is_lowercase()) {
+        return false;
+    }
+    let is_capitalized = |w: &str| {
+        let mut chars = w.chars();
+        chars.next().is_some_and(|c| c.is_uppercase()) && chars.all(|c| !c.is_alphabetic() || c.is_lowercase())
+    };
+    match case {
+        HeadingCase::Sentence => {
+            let long: Vec<&&str> = words[1..].iter().filter(|w| w.chars().count() > 3).collect();
+            long.len() < 2 || !long.iter().all(|w| is_capitalized(w))
+        }
+        HeadingCase::Title => words[1..].iter().all(|w| {
+            MINOR_WORDS.contains(&w.to_lowercase().as_str())
+                || !w.chars().next().is_some_and(|c| c.is_lowercase())
+        }),
+    }
+}
+
+fn check_line_length(body: &Body, lines: &[&str], max: usize, rule: &LintRule, diags: &mut Vec<Diagnostic>) {
+    for i in body.prose_lines() {
+        let line = lines[i];
+        let len = line.chars().count();
+        // Tables and unbreakable lines (a long URL) can't be wrapped
+        if len <= max || line.trim_start().starts_with('|') || !line.trim().contains(char::is_whitespace) {
+            continue;
+        }
+        diags.push(diag(
+            rule,
+            "L002",
+            i,
+            format!("line is {len} characters (max {max})"),
+            "wrap the line",
+        ));
+    }
+}
+
+fn check_table_whitespace(body: &Body, lines: &[&str], rule: &LintRule, diags: &mut Vec<Diagnostic>) {
+    for i in body.prose_lines() {
+        let line = lines[i];
+        if line.trim_start().starts_with('|') && line.ends_with([' ', '\t']) {
+            diags.push(diag(
+                rule,
+                "L003",
+                i,
+                "table row has trailing whitespace".into(),
+                "remove the whitespace after the last pipe",
+            ));
+        }
+    }
+}
+
+fn check_duplicate_headings(body: &Body, rule: &LintRule, diags: &mut Vec<Diagnostic>) {
+    let mut parents: Vec<&Heading> = Vec::new();
+    let mut seen: HashMap<String, usize> = HashMap::new();
+    for h in &body.headings {
+        while parents.last().is_some_and(|p| p.level >= h.level) {
+            parents.pop();
+        }
+        let key = parents
+            .iter()
+            .map(|p| p.text.as_str())
+            .chain([h.text.as_str()])
+            .collect::<Vec<_>>()
+            .join("\u{1f}");
+        match seen.get(&key) {
+            Some(first) => diags.push(diag(
+                rule,
+                "L004",
+                h.line,
+                format!("duplicate heading \"{}\" (first at line {})", h.text, first + 1),
+                "merge the sections or rename one",
+            )),
+            None => {
+                seen.insert(key, h.line);
+            }
+        }
+        parents.push(h);
+    }
+}
+
+fn check_empty_sections(body: &Body, lines: &[&str], rule: &LintRule, diags: &mut Vec<Diagnostic>) {
+    for (i, h) in body.headings.iter().enumerate() {
+        let next = body.headings.get(i + 1);
+        if next.is_some_and(|n| n.level > h.level) {
+            continue; // has subsections
+        }
+        let end = next.map_or(lines.len(), |n| n.line);
+        if lines[h.line + 1..end].iter().all(|l| l.trim().is_empty()) {
+            diags.push(diag(
+                rule,
+                "L005",
+                h.line,
+                format!("section \"{}\" is empty", h.text),
+                "add content or remove the heading",
+            ));
+        }
+    }
+}
+
+fn check_frontmatter_order(
+    fm_lines: &[(usize, &str)],
+    keys: &[String],
+    type_def: Option<&TypeDef>,
+    rule: &LintRule,
+    diags: &mut Vec<Diagnostic>,
+) {
+    let order: Vec<&str> = if keys.is_empty() {
+        let Some(td) = type_def else {
+            return;
+        };
+        std::iter::once("type")
+            .chain(td.fields.iter().map(|f| f.name.as_str()))
+            .collect()
+    } else {
+        keys.iter().map(String::as_str).collect()
+    };
+
+    let mut latest: Option<(usize, &str)> = None;
+    for &(i, line) in fm_lines {
+        let Some(key) = top_level_key(line) else {
+            continue;
+        };
+        let Some(rank) = order.iter().position(|k| *k == key) else {
+            continue;
+        };
+        match latest {
+            Some((seen, after)) if rank < seen => diags.push(diag(
+                rule,
+                "L006",
+                i,
+                format!("frontmatter key \"{key}\" should come before \"{after}\""),
+                &format!("order keys as: {}", order.join(", ")),
+            )),
+            _ => latest = Some((rank, key)),
+        }
+    }
+}
+
+/// The key of a top-level `key: value` frontmatter line.
+fn top_level_key(line: &str) -> Option<&str> {
+    if line.starts_with([' ', '\t', '-', '#']) {
+        return None;
+    }
+    let key = line.split_once(':')?.0.trim();
+    let key = key.trim_matches(|c| c == '"' || c == '\'');
+    (!key.is_empty()).then_some(key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn schema() -> Schema {
+        Schema::from_str(
+            r#"
+type "adr" {
+    field "title" type="string"
+    field "status" type="string"
+    section "Alternatives Considered"
+}
+lint {
+    heading-case "sentence" severity="error"
+    max-line-length 40
+    table-trailing-whitespace
+    duplicate-headings
+    empty-sections
+    frontmatter-order
+}
+"#,
+        )
+        .unwrap()
+    }
+
+    fn codes(content: &str) -> Vec<(String, String)> {
+        let doc = Document::from_str(content).unwrap();
+        lint_document(&doc, &schema())
+            .diagnostics
+            .into_iter()
+            .map(|d| (d.code, d.location))
+            .collect()
+    }
+
+    #[test]
+    fn test_lint_rules() {
+        let content = "---\ntype: adr\nstatus: accepted\ntitle: Pick a DB\n---\n\n# Pick A Database Engine\n\n## Alternatives Considered\n\n| A | B |   \n|---|---|\n\n## Notes\n\n### Open\n\n## Notes\n\nThis line is much longer than forty characters in total.\n\n```\nthis code line is also longer than forty characters\n```\n\nhttps://example.com/a/very/long/url/that/cannot/be/wrapped\n";
+        let got = codes(content);
+        let expect = [
+            ("L006", "line:4"),
+            ("L001", "line:7"),
+            ("L003", "line:11"),
+            ("L005", "line:16"),
+            ("L004", "line:18"),
+            ("L002", "line:20"),
+        ];
+        let got: Vec<(&str, &str)> = got.iter().map(|(c, l)| (c.as_str(), l.as_str())).collect();
+        assert_eq!(got, expect);
+
+        let doc = Document::from_str(content).unwrap();
+        let result = lint_document(&doc, &schema());
+        assert_eq!(result.errors(), 1, "only heading-case is an error");
+    }
+
+    #[test]
+    fn test_heading_case() {
+        assert!(heading_case_ok("Use PostgreSQL for storage", HeadingCase::Sentence));
+        assert!(heading_case_ok("Why Kafka", HeadingCase::Sentence));
+        assert!(!heading_case_ok("Rollout Plan Details", HeadingCase::Sentence));
+        assert!(!heading_case_ok("context", HeadingCase::Sentence));
+        assert!(heading_case_ok("Rollout Plan for the Team", HeadingCase::Title));
+        assert!(!heading_case_ok("Rollout plan", HeadingCase::Title));
+    }
+
+    #[test]
+    fn test_no_lint_block_is_clean() {
+        let doc = Document::from_str("---\ntype: adr\n---\n\n## empty\n").unwrap();
+        let schema = Schema::from_str("type \"adr\" {}").unwrap();
+        assert!(lint_document(&doc, &schema).diagnostics.is_empty());
+    }
+}
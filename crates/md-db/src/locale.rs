@@ -0,0 +1,312 @@
+//! Localization of generated HTML: UI labels, date formats, and number separators.
+//!
+//! Labels are keyed by their English text; `{name}` placeholders are filled in by
+//! [`Locale::label_with`]. Built-in locales cover the site chrome in English, Finnish,
+//! Swedish, and German. A schema's top-level `locale` block picks the project default
+//! and can override any of it:
+//!
+//! ```kdl
+//! locale "fi" date-format="D.M.YYYY" decimal-separator="," thousands-separator=" " {
+//!     label "Referenced by" "Viittaavat dokumentit"
+//! }
+//! ```
+
+use std::collections::BTreeMap;
+
+use serde_yaml::Value;
+
+/// Languages with built-in translations.
+pub const BUILTIN: &[&str] = &["en", "fi", "sv", "de"];
+
+/// How generated pages render labels, dates, and numbers.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Locale {
+    /// BCP 47 language tag, used for `<html lang>`.
+    pub lang: String,
+    /// Date pattern using `YYYY`, `MM`, `M`, `DD`, and `D` (e.g. `D.M.YYYY`).
+    pub date_format: String,
+    pub decimal_separator: char,
+    /// Groups integer digits in threes, for numbers of five digits or more.
+    pub thousands_separator: Option<char>,
+    /// Translations keyed by the English label.
+    pub labels: BTreeMap<String, String>,
+}
+
+impl Default for Locale {
+    fn default() -> Self {
+        Self {
+            lang: "en".into(),
+            date_format: "YYYY-MM-DD".into(),
+            decimal_separator: '.',
+            thousands_separator: None,
+            labels: BTreeMap::new(),
+        }
+    }
+}
+
+impl Locale {
+    /// The built-in locale for a language tag's primary subtag (`fi-FI` → Finnish).
+    /// The returned locale keeps the tag as given.
+    pub fn builtin(tag: &str) -> Option<Self> {
+        let primary = tag.split(['-', '_']).next().unwrap_or("").to_lowercase();
+        let (date_format, decimal, thousands, labels): (&str, char, Option<char>, &[(&str, &str)]) =
+            match primary.as_str() {
+                "en" => ("YYYY-MM-DD", '.', None, &[]),
+                "fi" => ("D.M.YYYY", ',', Some('\u{a0}'), FI),
+                "sv" => ("YYYY-MM-DD", ',', Some('\u{a0}'), SV),
+                "de" => ("DD.MM.YYYY", ',', Some('.'), DE),
+                _ => return None,
+            };
+        Some(Self {
+            lang: tag.to_string(),
+            date_format: date_format.into(),
+            decimal_separator: decimal,
+            thousands_separator: thousands,
+            labels: labels.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect(),
+        })
+    }
+
+    /// The translation of `key`, or `key` itself.
+    pub fn label(&self, key: &str) -> String {
+        self.labels.get(key).cloned().unwrap_or_else(|| key.to_string())
+    }
+
+    /// The translation of `key` with `{name}` placeholders replaced.
+    pub fn label_with(&self, key: &str, args: &[(&str, &str)]) -> String {
+        let mut label = self.label(key);
+        for (name, value) in args {
+            label = label.replace(&format!("{{{name}}}"), value);
+        }
+        label
+    }
+
+    /// Format `YYYY-MM-DD`, or a datetime as its date plus `HH:MM`. `None` when `s`
+    /// is not a date.
+    pub fn format_date(&self, s: &str) -> Option<String> {
+        let date = s.get(..10)?;
+        crate::validation::parse_date(date)?;
+        let (year, month, day) = (&date[..4], &date[5..7], &date[8..10]);
+        let formatted = self
+            .date_format
+            .replace("YYYY", year)
+            .replace("MM", month)
+            .replace("DD", day)
+            .replace('M', month.trim_start_matches('0'))
+            .replace('D', day.trim_start_matches('0'));
+        match s.len() {
+            10 => Some(formatted),
+            _ if crate::validation::parse_datetime(s).is_some() => Some(format!("{formatted} {}", s.get(11..16)?)),
+            _ => None,
+        }
+    }
+
+    /// Format a decimal number with this locale's separators. `None` when `s` is not a number.
+    pub fn format_number(&self, s: &str) -> Option<String> {
+        s.parse::<f64>().ok().filter(|n| n.is_finite())?;
+        let (sign, digits) = match s.strip_prefix('-') {
+            Some(rest) => ("-", rest),
+            None => ("", s),
+        };
+        let (int, frac) = match digits.split_once('.') {
+            Some((int, frac)) => (int, Some(frac)),
+            None => (digits, None),
+        };
+        if !int.chars().all(|c| c.is_ascii_digit()) {
+            return None;
+        }
+
+        let mut out = String::from(sign);
+        match self.thousands_separator {
+            Some(sep) if int.len() >= 5 => {
+                for (i, c) in int.chars().enumerate() {
+                    if i > 0 && (int.len() - i) % 3 == 0 {
+                        out.push(sep);
+                    }
+                    out.push(c);
+                }
+            }
+            _ => out.push_str(int),
+        }
+        if let Some(frac) = frac {
+            out.push(self.decimal_separator);
+            out.push_str(frac);
+        }
+        Some(out)
+    }
+
+    /// Display a frontmatter value like `yaml_value_to_string`, with numbers and dates localized.
+    pub fn format_value(&self, value: &Value) -> String {
+        match value {
+            Value::Number(n) => {
+                let s = n.to_string();
+                self.format_number(&s).unwrap_or(s)
+            }
+            Value::String(s) => self.format_date(s).unwrap_or_else(|| s.clone()),
+            Value::Sequence(items) => {
+                let items: Vec<String> = items.iter().map(|v| self.format_value(v)).collect();
+                format!("[{}]", items.join(", "))
+            }
+            other => crate::frontmatter::yaml_value_to_string(other),
+        }
+    }
+}
+
+const FI: &[(&str, &str)] = &[
+    ("Index", "Hakemisto"),
+    ("Schema", "Skeema"),
+    ("Search", "Haku"),
+    ("Graph", "Verkko"),
+    ("No results", "Ei tuloksia"),
+    ("Contents", "Sisällys"),
+    ("Documents", "Dokumentit"),
+    ("Generated {date}", "Luotu {date}"),
+    ("Field", "Kenttä"),
+    ("Value", "Arvo"),
+    ("Referenced by", "Viittaukset"),
+    ("Untitled", "Nimetön"),
+    ("Document Index", "Dokumenttihakemisto"),
+    ("{n} documents", "{n} dokumenttia"),
+    ("Schema Reference", "Skeeman kuvaus"),
+    ("Type", "Tyyppi"),
+    ("Required", "Pakollinen"),
+    ("Description", "Kuvaus"),
+    ("yes", "kyllä"),
+    ("Example document", "Esimerkkidokumentti"),
+    ("Relations", "Suhteet"),
+    ("Relation", "Suhde"),
+    ("Inverse", "Käänteinen"),
+    ("Cardinality", "Kardinaliteetti"),
+    ("one", "yksi"),
+    ("many", "monta"),
+    ("other", "muut"),
+    ("To fill: {sections}", "Täytettävä: {sections}"),
+    ("{n} min read", "{n} min lukuaika"),
+    ("{filled}/{required} sections", "{filled}/{required} osiota"),
+    ("Needs attention", "Vaatii huomiota"),
+    ("Fair", "Kohtalainen"),
+    ("Healthy", "Kunnossa"),
+    ("Last 7 days", "Viimeiset 7 päivää"),
+    ("Last 30 days", "Viimeiset 30 päivää"),
+    ("Last 90 days", "Viimeiset 90 päivää"),
+    ("Older", "Vanhemmat"),
+    ("Undated", "Päiväämättömät"),
+    ("{n} errors", "{n} virhettä"),
+    ("review overdue since {date}", "katselmointi myöhässä {date} alkaen"),
+    ("expires {date}", "vanhenee {date}"),
+    ("expired {date}", "vanhentunut {date}"),
+];
+
+const SV: &[(&str, &str)] = &[
+    ("Index", "Innehåll"),
+    ("Search", "Sök"),
+    ("Graph", "Graf"),
+    ("No results", "Inga träffar"),
+    ("Contents", "Innehållsförteckning"),
+    ("Documents", "Dokument"),
+    ("Generated {date}", "Skapad {date}"),
+    ("Field", "Fält"),
+    ("Value", "Värde"),
+    ("Referenced by", "Refereras av"),
+    ("Untitled", "Namnlös"),
+    ("Document Index", "Dokumentförteckning"),
+    ("{n} documents", "{n} dokument"),
+    ("Schema Reference", "Schemareferens"),
+    ("Type", "Typ"),
+    ("Required", "Obligatorisk"),
+    ("Description", "Beskrivning"),
+    ("yes", "ja"),
+    ("Example document", "Exempeldokument"),
+    ("Relations", "Relationer"),
+    ("Inverse", "Invers"),
+    ("Cardinality", "Kardinalitet"),
+    ("one", "en"),
+    ("many", "flera"),
+    ("other", "övrigt"),
+    ("To fill: {sections}", "Att fylla i: {sections}"),
+    ("{n} min read", "{n} min läsning"),
+    ("{filled}/{required} sections", "{filled}/{required} avsnitt"),
+    ("Needs attention", "Kräver åtgärd"),
+    ("Fair", "Godtagbar"),
+    ("Healthy", "Bra"),
+    ("Last 7 days", "Senaste 7 dagarna"),
+    ("Last 30 days", "Senaste 30 dagarna"),
+    ("Last 90 days", "Senaste 90 dagarna"),
+    ("Older", "Äldre"),
+    ("Undated", "Odaterade"),
+    ("{n} errors", "{n} fel"),
+    ("review overdue since {date}", "granskning försenad sedan {date}"),
+    ("expires {date}", "upphör {date}"),
+    ("expired {date}", "upphörde {date}"),
+];
+
+const DE: &[(&str, &str)] = &[
+    ("Index", "Übersicht"),
+    ("Search", "Suche"),
+    ("Graph", "Graph"),
+    ("No results", "Keine Treffer"),
+    ("Contents", "Inhaltsverzeichnis"),
+    ("Documents", "Dokumente"),
+    ("Generated {date}", "Erstellt am {date}"),
+    ("Field", "Feld"),
+    ("Value", "Wert"),
+    ("Referenced by", "Referenziert von"),
+    ("Untitled", "Ohne Titel"),
+    ("Document Index", "Dokumentenübersicht"),
+    ("{n} documents", "{n} Dokumente"),
+    ("Schema Reference", "Schema-Referenz"),
+    ("Type", "Typ"),
+    ("Required", "Pflicht"),
+    ("Description", "Beschreibung"),
+    ("yes", "ja"),
+    ("Example document", "Beispieldokument"),
+    ("Relations", "Beziehungen"),
+    ("Relation", "Beziehung"),
+    ("Inverse", "Umkehrung"),
+    ("Cardinality", "Kardinalität"),
+    ("one", "eins"),
+    ("many", "viele"),
+    ("other", "sonstige"),
+    ("To fill: {sections}", "Auszufüllen: {sections}"),
+    ("{n} min read", "{n} Min. Lesezeit"),
+    ("{filled}/{required} sections", "{filled}/{required} Abschnitte"),
+    ("Needs attention", "Handlungsbedarf"),
+    ("Fair", "Mittel"),
+    ("Healthy", "Gut"),
+    ("Last 7 days", "Letzte 7 Tage"),
+    ("Last 30 days", "Letzte 30 Tage"),
+    ("Last 90 days", "Letzte 90 Tage"),
+    ("Older", "Älter"),
+    ("Undated", "Undatiert"),
+    ("{n} errors", "{n} Fehler"),
+    ("review overdue since {date}", "Review überfällig seit {date}"),
+    ("expires {date}", "läuft ab am {date}"),
+    ("expired {date}", "abgelaufen am {date}"),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_finnish_locale() {
+        let fi = Locale::builtin("fi-FI").unwrap();
+        assert_eq!(fi.lang, "fi-FI");
+        assert_eq!(fi.label("Referenced by"), "Viittaukset");
+        assert_eq!(fi.label_with("{n} documents", &[("n", "3")]), "3 dokumenttia");
+        assert_eq!(fi.format_date("2024-03-05").as_deref(), Some("5.3.2024"));
+        assert_eq!(fi.format_date("2024-03-05T09:30:00Z").as_deref(), Some("5.3.2024 09:30"));
+        assert_eq!(fi.format_date("2024-13-05"), None);
+        assert_eq!(fi.format_number("1234567.5").as_deref(), Some("1\u{a0}234\u{a0}567,5"));
+        assert_eq!(fi.format_number("2024").as_deref(), Some("2024"));
+        assert_eq!(fi.format_value(&serde_yaml::from_str("99.9").unwrap()), "99,9");
+        assert!(Locale::builtin("xx").is_none());
+    }
+
+    #[test]
+    fn test_default_locale_is_english() {
+        let en = Locale::default();
+        assert_eq!(en.label("Referenced by"), "Referenced by");
+        assert_eq!(en.format_date("2024-03-05").as_deref(), Some("2024-03-05"));
+        assert_eq!(en.format_number("-12345.5").as_deref(), Some("-12345.5"));
+    }
+}
@@ -0,0 +1,111 @@
+//! Marker-delimited blocks that md-db regenerates inside hand-written documents.
+//!
+//! ```markdown
+//! <!-- md-db:badges -->
+//! (generated, replaced on every run)
+//! <!-- /md-db:badges -->
+//! ```
+//!
+//! Markers must sit on a line of their own. Markers inside fenced code blocks are
+//! ignored, so a README can document the syntax without being rewritten.
+
+use std::ops::Range;
+
+use crate::error::{Error, Result};
+
+/// `<!-- md-db:NAME -->`
+pub fn start_marker(name: &str) -> String {
+    format!("<!-- md-db:{name} -->")
+}
+
+/// `<!-- /md-db:NAME -->`
+pub fn end_marker(name: &str) -> String {
+    format!("<!-- /md-db:{name} -->")
+}
+
+/// Byte range of the lines between the start and end markers of block `name`,
+/// or `None` if the text has no such block. A start marker without an end marker
+/// is an error.
+pub fn find_block(text: &str, name: &str) -> Result<Option<Range<usize>>> {
+    let start = start_marker(name);
+    let end = end_marker(name);
+
+    let mut fence: Option<(char, usize)> = None;
+    let mut content_start = None;
+    let mut offset = 0;
+    for line in text.split_inclusive('\n') {
+        let line_start = offset;
+        offset += line.len();
+        let trimmed = line.trim();
+
+        if let Some((c, n)) = fence {
+            let t = line.trim_start();
+            if t.len() - t.trim_start_matches(c).len() >= n && t.trim_start_matches(c).trim().is_empty() {
+                fence = None;
+            }
+            continue;
+        }
+        if let Some(open) = crate::normalize::fence_open(line) {
+            fence = Some(open);
+            continue;
+        }
+
+        match content_start {
+            None if trimmed == start => content_start = Some(offset),
+            Some(from) if trimmed == end => return Ok(Some(from..line_start)),
+            Some(_) if trimmed == start => {
+                return Err(Error::Marker(format!("nested {start} before {end}")));
+            }
+            _ => {}
+        }
+    }
+
+    match content_start {
+        Some(_) => Err(Error::Marker(format!("{start} has no closing {end}"))),
+        None => Ok(None),
+    }
+}
+
+/// Replace the contents of block `name` with `content`. Returns `None` if the
+/// text has no such block; the markers themselves are kept.
+pub fn replace_block(text: &str, name: &str, content: &str) -> Result<Option<String>> {
+    let Some(range) = find_block(text, name)? else {
+        return Ok(None);
+    };
+    let mut body = content.to_string();
+    if !body.is_empty() && !body.ends_with('\n') {
+        body.push('\n');
+    }
+    let mut out = String::with_capacity(text.len() + body.len());
+    out.push_str(&text[..range.start]);
+    out.push_str(&body);
+    out.push_str(&text[range.end..]);
+    Ok(Some(out))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_replace_block() {
+        let text = "# Project\n\n<!-- md-db:badges -->\nold\nlines\n<!-- /md-db:badges -->\n\nMore.\n";
+        let out = replace_block(text, "badges", "new").unwrap().unwrap();
+        assert_eq!(out, "# Project\n\n<!-- md-db:badges -->\nnew\n<!-- /md-db:badges -->\n\nMore.\n");
+        // Idempotent
+        assert_eq!(replace_block(&out, "badges", "new").unwrap().unwrap(), out);
+
+        assert!(replace_block(text, "toc", "x").unwrap().is_none());
+        assert!(find_block("<!-- md-db:badges -->\nno end\n", "badges").is_err());
+    }
+
+    #[test]
+    fn test_markers_in_code_fences_are_ignored() {
+        let text = "```markdown\n<!-- md-db:badges -->\n<!-- /md-db:badges -->\n```\n";
+        assert!(find_block(text, "badges").unwrap().is_none());
+
+        let text = format!("{text}\n<!-- md-db:badges -->\n<!-- /md-db:badges -->\n");
+        let range = find_block(&text, "badges").unwrap().unwrap();
+        assert!(range.is_empty());
+    }
+}
@@ -0,0 +1,206 @@
+//! Capability config for the MCP server.
+//!
+//! An `mcp` node (in the `md-db.kdl` manifest or a file passed to
+//! `md-db mcp --config`) limits what connected agents can do:
+//!
+//! ```kdl
+//! mcp root="docs/" max-result-bytes=200000 {
+//!     allow "md-db-get" "md-db-list" "md-db-search"
+//!     deny "md-db-set"
+//!     allow-origin "https://agents.example.com"
+//!     allow-host "mcp.example.com"
+//! }
+//! ```
+//!
+//! `root` sandboxes every path a tool reads or writes; it is relative to the
+//! config file, and the project schema found for it stays readable even when it
+//! lives above the root. `allow` (when given) lists the only tools offered; `deny`
+//! removes tools on top of that. Results larger than `max-result-bytes` are
+//! refused rather than truncated. Over HTTP, `allow-origin` and `allow-host` add
+//! browser origins and `Host` names to the loopback ones the server accepts.
+
+use std::path::{Component, Path, PathBuf};
+
+use kdl::{KdlDocument, KdlNode};
+
+use crate::error::{Error, Result};
+use crate::schema::{get_i64_prop, get_string_prop};
+
+/// Limits on the MCP server; the default allows everything.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct McpConfig {
+    /// Tools offered; empty means all.
+    pub allow: Vec<String>,
+    pub deny: Vec<String>,
+    pub max_result_bytes: Option<usize>,
+    /// Canonical sandbox directory.
+    pub root: Option<PathBuf>,
+    /// Files readable outside `root`: the project schema and its `users.yaml`.
+    pub extra_reads: Vec<PathBuf>,
+    /// Non-loopback `Origin`s accepted over HTTP, e.g. `https://agents.example.com`.
+    pub allow_origins: Vec<String>,
+    /// Non-loopback `Host` names accepted over HTTP, e.g. behind a reverse proxy.
+    pub allow_hosts: Vec<String>,
+}
+
+impl McpConfig {
+    /// The `mcp` node of a KDL file, or the default config when it has none.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path).map_err(|_| Error::FileNotFound(path.to_path_buf()))?;
+        let dir = path.parent().filter(|d| !d.as_os_str().is_empty()).unwrap_or(Path::new("."));
+        Self::from_str(&content, dir).map_err(|e| match e {
+            Error::Capability(msg) => Error::Capability(format!("{}: {msg}", path.display())),
+            other => other,
+        })
+    }
+
+    /// Parse the `mcp` node of `content`, resolving `root` against `dir`. Other
+    /// top-level nodes are ignored so a manifest can carry the block.
+    pub fn from_str(content: &str, dir: &Path) -> Result<Self> {
+        let doc: KdlDocument = content
+            .parse()
+            .map_err(|e: kdl::KdlError| Error::Capability(format!("{e:#}")))?;
+        match doc.nodes().iter().find(|n| n.name().value() == "mcp") {
+            Some(node) => parse_node(node, dir),
+            None => Ok(Self::default()),
+        }
+    }
+
+    /// Whether `tool` may be listed and called.
+    pub fn tool_allowed(&self, tool: &str) -> bool {
+        (self.allow.is_empty() || self.allow.iter().any(|t| t == tool)) && !self.deny.iter().any(|t| t == tool)
+    }
+
+    /// Check that `path` (existing or about to be created) is inside the root.
+    /// Symlinks and `..` are resolved first, so neither can escape it.
+    pub fn check_path(&self, path: &Path) -> Result<()> {
+        let Some(ref root) = self.root else {
+            return Ok(());
+        };
+        let resolved = resolve(path)
+            .ok_or_else(|| Error::Capability(format!("cannot resolve path {}", path.display())))?;
+        if resolved.starts_with(root) || self.extra_reads.contains(&resolved) {
+            return Ok(());
+        }
+        Err(Error::Capability(format!("{} is outside the MCP root {}", path.display(), root.display())))
+    }
+
+    /// Refuse a result of `len` bytes when over the limit.
+    pub fn check_result_size(&self, len: usize) -> Result<()> {
+        match self.max_result_bytes {
+            Some(max) if len > max => Err(Error::Capability(format!(
+                "result is {len} bytes, over the limit of {max}; narrow the request (limit, select, section)"
+            ))),
+            _ => Ok(()),
+        }
+    }
+}
+
+fn parse_node(node: &KdlNode, dir: &Path) -> Result<McpConfig> {
+    let mut config = McpConfig::default();
+    for child in node.children().map(|c| c.nodes()).unwrap_or_default() {
+        let names: Vec<String> = child
+            .entries()
+            .iter()
+            .filter(|e| e.name().is_none())
+            .filter_map(|e| e.value().as_string().map(String::from))
+            .collect();
+        match child.name().value() {
+            "allow" => config.allow.extend(names),
+            "deny" => config.deny.extend(names),
+            "allow-origin" => config.allow_origins.extend(names.iter().map(|o| o.trim_end_matches('/').to_string())),
+            "allow-host" => config.allow_hosts.extend(names),
+            other => {
+                return Err(Error::Capability(format!(
+                    "unknown node '{other}' in mcp (expected allow, deny, allow-origin, allow-host)"
+                )))
+            }
+        }
+    }
+
+    if let Some(max) = get_i64_prop(node, "max-result-bytes") {
+        let max = usize::try_from(max)
+            .map_err(|_| Error::Capability(format!("max-result-bytes must not be negative, got {max}")))?;
+        config.max_result_bytes = Some(max);
+    }
+    if let Some(root) = get_string_prop(node, "root") {
+        let root = dir.join(root);
+        let root = root
+            .canonicalize()
+            .map_err(|_| Error::Capability(format!("root {} is not a directory", root.display())))?;
+        if let Ok(schema) = crate::project::find_schema(&root) {
+            let users = schema.parent().map(|d| d.join(crate::db::USERS_FILE));
+            config.extra_reads.extend(std::iter::once(schema).chain(users).filter_map(|p| p.canonicalize().ok()));
+        }
+        config.root = Some(root);
+    }
+    Ok(config)
+}
+
+/// Absolute form of `path` with symlinks resolved. A path that doesn't exist yet
+/// resolves through its closest existing ancestor; `..` after that is refused.
+fn resolve(path: &Path) -> Option<PathBuf> {
+    let path = if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        std::env::current_dir().ok()?.join(path)
+    };
+    let mut existing = path.as_path();
+    let mut rest = Vec::new();
+    loop {
+        if let Ok(canonical) = existing.canonicalize() {
+            let mut resolved = canonical;
+            for part in rest.iter().rev() {
+                match part {
+                    Component::Normal(name) => resolved.push(name),
+                    Component::CurDir => {}
+                    _ => return None,
+                }
+            }
+            return Some(resolved);
+        }
+        rest.push(existing.components().next_back()?);
+        existing = existing.parent()?;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_check() {
+        let dir = tempfile::tempdir().unwrap();
+        let docs = dir.path().join("docs");
+        std::fs::create_dir_all(&docs).unwrap();
+        std::fs::write(dir.path().join("schema.kdl"), "").unwrap();
+        std::fs::write(dir.path().join("secret.txt"), "").unwrap();
+
+        let kdl = "root \"docs\"\nmcp root=\"docs/\" max-result-bytes=100 {\n    allow \"md-db-get\" \"md-db-set\"\n    deny \"md-db-set\"\n}\n";
+        let config = McpConfig::from_str(kdl, dir.path()).unwrap();
+        assert!(config.tool_allowed("md-db-get"));
+        assert!(!config.tool_allowed("md-db-set"));
+        assert!(!config.tool_allowed("md-db-list"));
+
+        assert!(config.check_path(&docs.join("adr-001.md")).is_ok());
+        assert!(config.check_path(&docs.join("new/adr-002.md")).is_ok());
+        assert!(config.check_path(&dir.path().join("schema.kdl")).is_ok());
+        assert!(config.check_path(&dir.path().join("secret.txt")).is_err());
+        assert!(config.check_path(&docs.join("../secret.txt")).is_err());
+        assert!(config.check_path(&docs.join("new/../../secret.txt")).is_err());
+
+        assert!(config.check_result_size(100).is_ok());
+        assert!(config.check_result_size(101).is_err());
+
+        let open = McpConfig::from_str("root \"docs\"\n", dir.path()).unwrap();
+        assert!(open.tool_allowed("md-db-set") && open.check_path(Path::new("/etc/passwd")).is_ok());
+        assert!(McpConfig::from_str("mcp { grant \"md-db-get\" }", dir.path()).is_err());
+
+        let web = "mcp {\n    allow-origin \"https://agents.example.com/\"\n    allow-host \"mcp.example.com\"\n}\n";
+        let web = McpConfig::from_str(web, dir.path()).unwrap();
+        assert_eq!(web.allow_origins, vec!["https://agents.example.com"]);
+        assert_eq!(web.allow_hosts, vec!["mcp.example.com"]);
+        assert!(McpConfig::from_str("mcp root=\"missing\"", dir.path()).is_err());
+    }
+}
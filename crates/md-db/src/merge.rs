@@ -0,0 +1,394 @@
+//! Merge one document into another (`md-db merge`), the inverse of [`crate::split`].
+//!
+//! Each top-level section of the merged-away document is appended to the kept
+//! document's section with the same heading, or the one a mapping names;
+//! sections without a counterpart are added at the end. Relation fields are
+//! unioned. References to the merged-away document anywhere under the
+//! directory (relation and `ref` fields, by ID or uid, and Markdown links to
+//! its file) are pointed at the kept one, and the merged-away document is left
+//! as a stub superseded by it, so its ID keeps resolving.
+
+use std::collections::HashSet;
+use std::path::Path;
+
+use serde_yaml::Value;
+
+use crate::assets::{absolute, local_target, normalize, relative_path, url_path};
+use crate::ast_util;
+use crate::document::Document;
+use crate::error::{Error, Result};
+use crate::graph::{extract_refs, path_to_id};
+use crate::schema::{Cardinality, FieldType, Schema};
+
+/// Field on the stub naming the document it was merged into.
+pub const SUPERSEDED_BY: &str = "superseded_by";
+
+#[derive(Debug, Clone, Default)]
+pub struct MergeOptions<'a> {
+    /// (heading in the merged-away document, heading in the kept one) pairs for
+    /// sections whose headings differ.
+    pub sections: &'a [(String, String)],
+    /// Append every section at the end instead of matching headings.
+    pub concat: bool,
+}
+
+/// Every document a merge changes, not yet written.
+#[derive(Debug, Clone)]
+pub struct Merge {
+    pub into_id: String,
+    /// The kept document, with the other's content and relations merged in.
+    pub into: Document,
+    pub from_id: String,
+    /// The merged-away document, reduced to a stub superseded by the kept one.
+    pub from: Document,
+    /// Other documents whose references now point at the kept document.
+    pub referrers: Vec<Document>,
+    /// Single-valued relations set on both; the kept document's value stays.
+    pub conflicts: Vec<String>,
+}
+
+/// Plan merging the document at `from_path` into the one at `into_path`,
+/// cascading references from the documents under `dir`.
+pub fn plan_merge(
+    into_path: &Path,
+    from_path: &Path,
+    dir: &Path,
+    schema: &Schema,
+    opts: &MergeOptions,
+) -> Result<Merge> {
+    let into_id = path_to_id(into_path);
+    let from_id = path_to_id(from_path);
+    if into_id == from_id {
+        return Err(Error::Merge(format!("cannot merge {into_id} into itself")));
+    }
+    let mut into = Document::from_file(into_path)?;
+    let mut from = Document::from_file(from_path)?;
+    let into_file = absolute(into_path);
+    let from_file = absolute(from_path);
+    let into_dir = into_file.parent().unwrap_or(Path::new("/")).to_path_buf();
+    let from_dir = from_file.parent().unwrap_or(Path::new("/")).to_path_buf();
+
+    // Relative links in the moved text resolve from the kept document's folder
+    if into_dir != from_dir {
+        let body = ast_util::rewrite_urls(&from.body, |url| {
+            let target = local_target(url)?;
+            let suffix = url.find(['#', '?']).map_or("", |i| &url[i..]);
+            let moved = relative_path(&into_dir, &normalize(&from_dir.join(target)));
+            Some(format!("{}{suffix}", url_path(&moved)))
+        });
+        from.set_body(&body);
+    }
+    merge_body(&mut into, &from, &into_id, opts)?;
+
+    // The merged-away document's ID and uid both count as references to it
+    let mut old_keys = vec![from_id.clone()];
+    old_keys.extend(from.frontmatter.as_ref().and_then(|fm| fm.get_display(crate::uid::UID_FIELD)));
+    let ref_fields = ref_field_names(schema);
+    let relation_names = schema.all_relation_field_names();
+
+    let mut conflicts = Vec::new();
+    for &name in &relation_names {
+        let theirs: Vec<String> = field_refs(&from, name)
+            .into_iter()
+            .filter(|r| !r.eq_ignore_ascii_case(&into_id) && !matches_any(r, &old_keys))
+            .collect();
+        if theirs.is_empty() {
+            continue;
+        }
+        let ours = field_refs(&into, name);
+        if is_single(schema, name) {
+            match ours.first() {
+                None => into.set_field(name, Value::String(theirs[0].clone())),
+                Some(kept) if !kept.eq_ignore_ascii_case(&theirs[0]) => {
+                    conflicts.push(format!("{name}: kept {kept}, dropped {}", theirs[0]))
+                }
+                Some(_) => {}
+            }
+            continue;
+        }
+        let mut union = ours.clone();
+        for r in theirs {
+            if !matches_any(&r, &union) {
+                union.push(r);
+            }
+        }
+        if union.len() > ours.len() {
+            into.set_field(name, Value::Sequence(union.into_iter().map(Value::String).collect()));
+        }
+    }
+
+    // The stub keeps only its identity: its relations now live on the kept document
+    let mut stub = from.clone();
+    for name in &relation_names {
+        stub.remove_field(name);
+    }
+    let from_type = from.frontmatter.as_ref().and_then(|fm| fm.get_display("type")).unwrap_or_default();
+    let status = schema.status_field(&from_type);
+    stub.set_field_from_str(&status.field, status.superseded_value());
+    let successor = Value::String(into_id.clone());
+    stub.set_field(
+        SUPERSEDED_BY,
+        if is_single(schema, SUPERSEDED_BY) { successor } else { Value::Sequence(vec![successor]) },
+    );
+    let link = url_path(&relative_path(&from_dir, &into_file));
+    stub.set_body(&format!("\nMerged into [{into_id}]({link}).\n"));
+
+    // And the kept document records what it absorbed, when the schema pairs the field
+    if let Some((relation, true)) = schema.find_relation(SUPERSEDED_BY) {
+        let field = relation.name.as_str();
+        let ours = field_refs(&into, field);
+        if relation.cardinality == Cardinality::Many {
+            if !matches_any(&from_id, &ours) {
+                let mut refs: Vec<Value> = ours.into_iter().map(Value::String).collect();
+                refs.push(Value::String(from_id.clone()));
+                into.set_field(field, Value::Sequence(refs));
+            }
+        } else if let Some(kept) = ours.first() {
+            conflicts.push(format!("{field}: kept {kept}, dropped {from_id}"));
+        } else {
+            into.set_field(field, Value::String(from_id.clone()));
+        }
+    }
+    let redirect = Redirect {
+        old_keys: &old_keys,
+        old_file: &from_file,
+        new_id: &into_id,
+        new_file: &into_file,
+        fields: &ref_fields,
+    };
+    redirect.apply(&mut into, &into_file);
+
+    // Ref fields aren't graph edges, so every document is checked
+    let mut referrers = Vec::new();
+    for path in crate::discovery::discover_files(dir, None, &[], false)? {
+        let file = absolute(&path);
+        if file == into_file || file == from_file {
+            continue;
+        }
+        let Ok(mut doc) = Document::from_file(&path) else { continue };
+        if redirect.apply(&mut doc, &file) {
+            referrers.push(doc);
+        }
+    }
+
+    Ok(Merge {
+        into_id,
+        into,
+        from_id,
+        from: stub,
+        referrers,
+        conflicts,
+    })
+}
+
+/// Move the sections of `from` into `into`: appended to the matching (or
+/// mapped) section, or added at the end at `into`'s top heading level. Text
+/// before `from`'s first heading goes before `into`'s first heading.
+fn merge_body(into: &mut Document, from: &Document, into_id: &str, opts: &MergeOptions) -> Result<()> {
+    let from_sections = from.sections();
+    for (heading, _) in opts.sections {
+        if !from_sections.iter().any(|s| s.heading.trim().eq_ignore_ascii_case(heading)) {
+            return Err(Error::SectionNotFound(heading.clone()));
+        }
+    }
+
+    let preamble = match from_sections.first() {
+        Some(first) => from.body.find(&first.raw).map_or("", |i| &from.body[..i]),
+        None => from.body.as_str(),
+    };
+    if !preamble.trim().is_empty() {
+        let body = match into.sections().first().and_then(|s| into.body.find(&s.raw)) {
+            Some(i) => {
+                let head = into.body[..i].trim_end();
+                let sep = if head.is_empty() { "\n" } else { "\n\n" };
+                format!("{head}{sep}{}\n\n{}", preamble.trim(), &into.body[i..])
+            }
+            None => format!("{}\n\n{}\n", into.body.trim_end(), preamble.trim()),
+        };
+        into.set_body(&body);
+    }
+
+    let top_level = into.sections().first().map_or(1, |s| s.level);
+    for section in &from_sections {
+        let heading = section.heading.trim();
+        let mapped = opts.sections.iter().find(|(f, _)| f.eq_ignore_ascii_case(heading)).map(|(_, t)| t.as_str());
+        let target = match mapped {
+            Some(target) => Some(into.get_section(target).map_err(|_| {
+                Error::Merge(format!("{into_id} has no section \"{target}\" for \"{heading}\""))
+            })?),
+            None if opts.concat => None,
+            None => into.get_section(heading).ok(),
+        };
+        match target {
+            Some(existing) => {
+                if existing.content.trim() == section.content.trim() {
+                    continue;
+                }
+                let by = i32::from(existing.level) - i32::from(section.level);
+                let content = ast_util::shift_headings(section.content.trim(), by);
+                into.append_to_section(&existing.heading, &format!("{}\n", content.trim()))?;
+            }
+            None => {
+                let by = i32::from(top_level) - i32::from(section.level);
+                let raw = ast_util::shift_headings(&section.raw, by);
+                into.set_body(&format!("{}\n\n{}\n", into.body.trim_end(), raw.trim()));
+            }
+        }
+    }
+    into.set_body(&format!("{}\n", into.body.trim_end()));
+    Ok(())
+}
+
+/// References to the merged-away document, and what they become.
+struct Redirect<'a> {
+    /// Its ID, then its uid if it has one.
+    old_keys: &'a [String],
+    old_file: &'a Path,
+    new_id: &'a str,
+    new_file: &'a Path,
+    /// Fields that hold references.
+    fields: &'a HashSet<String>,
+}
+
+impl Redirect<'_> {
+    /// Point `doc`'s references and its Markdown links to the old document at
+    /// the new one; `doc_file` is its absolute path. A reference of the new
+    /// document to itself is dropped. Returns whether anything changed.
+    fn apply(&self, doc: &mut Document, doc_file: &Path) -> bool {
+        let own_id = path_to_id(doc_file);
+        let mut changed = false;
+        for field in self.fields {
+            let Some(value) = doc.frontmatter.as_ref().and_then(|fm| fm.get(field)).cloned() else {
+                continue;
+            };
+            let refs = extract_refs(&value);
+            if !refs.iter().any(|r| matches_any(r, self.old_keys)) {
+                continue;
+            }
+            let mut kept: Vec<String> = Vec::new();
+            for r in refs {
+                let r = if matches_any(&r, self.old_keys) { self.new_id.to_string() } else { r };
+                if !r.eq_ignore_ascii_case(&own_id) && !matches_any(&r, &kept) {
+                    kept.push(r);
+                }
+            }
+            if kept.is_empty() {
+                doc.remove_field(field);
+            } else if let Value::Sequence(_) = value {
+                doc.set_field(field, Value::Sequence(kept.into_iter().map(Value::String).collect()));
+            } else {
+                doc.set_field(field, Value::String(kept.remove(0)));
+            }
+            changed = true;
+        }
+
+        let doc_dir = doc_file.parent().unwrap_or(Path::new("/"));
+        let body = ast_util::rewrite_urls(&doc.body, |url| {
+            if url.eq_ignore_ascii_case(&self.old_keys[0]) {
+                return Some(self.new_id.to_string());
+            }
+            let target = local_target(url)?;
+            if normalize(&doc_dir.join(target)) != self.old_file {
+                return None;
+            }
+            let suffix = url.find(['#', '?']).map_or("", |i| &url[i..]);
+            Some(format!("{}{suffix}", url_path(&relative_path(doc_dir, self.new_file))))
+        });
+        if body != doc.body {
+            doc.set_body(&body);
+            changed = true;
+        }
+        changed
+    }
+}
+
+/// Relation fields plus fields declared `ref` or `ref[]` on any type.
+fn ref_field_names(schema: &Schema) -> HashSet<String> {
+    let mut names: HashSet<String> = schema.all_relation_field_names().into_iter().map(str::to_string).collect();
+    for type_def in &schema.types {
+        for field in &type_def.fields {
+            if matches!(field.field_type, FieldType::Ref | FieldType::RefArray) {
+                names.insert(field.name.clone());
+            }
+        }
+    }
+    names
+}
+
+/// Whether relation field `name` holds one ID (a "one" relation, either side).
+fn is_single(schema: &Schema, name: &str) -> bool {
+    schema
+        .find_relation(name)
+        .is_none_or(|(relation, _)| relation.cardinality == Cardinality::One)
+}
+
+fn field_refs(doc: &Document, field: &str) -> Vec<String> {
+    doc.frontmatter.as_ref().and_then(|fm| fm.get(field)).map(extract_refs).unwrap_or_default()
+}
+
+fn matches_any(r: &str, keys: &[String]) -> bool {
+    keys.iter().any(|k| k.eq_ignore_ascii_case(r))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plan_merge() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        let write = |name: &str, content: &str| std::fs::write(root.join(name), content).unwrap();
+        write(
+            "opp-001.md",
+            "---\ntype: opp\ntitle: Faster builds\nenables: [ADR-001]\n---\n\n# Problem\n\nSlow builds.\n\n# Notes\n\nA.\n",
+        );
+        write(
+            "opp-002.md",
+            "---\ntype: opp\ntitle: Faster CI\nuid: 0190a8e4-0000-7000-8000-000000000002\nenables: [ADR-002, OPP-001]\n\
+             ---\n\nIntro.\n\n# Problem\n\nCI is slow too.\n\n# Evidence\n\nSee [ADR-002](adr-002.md).\n",
+        );
+        write("adr-001.md", "---\ntype: adr\nenabled_by: [OPP-001]\n---\n");
+        write("adr-002.md", "---\ntype: adr\nenabled_by: [OPP-002]\n---\n\nCame from [OPP-002](opp-002.md#evidence).\n");
+        write("adr-003.md", "---\ntype: adr\nsource: 0190a8e4-0000-7000-8000-000000000002\n---\n");
+        let schema = Schema::from_str(
+            "type \"opp\"\ntype \"adr\" {\n    field \"source\" type=\"ref\"\n}\n\
+             relation \"enables\" inverse=\"enabled_by\" cardinality=\"many\"\n\
+             relation \"supersedes\" inverse=\"superseded_by\" cardinality=\"one\"\n",
+        )
+        .unwrap();
+        let sections = [("Evidence".to_string(), "Notes".to_string())];
+        let opts = MergeOptions { sections: &sections, concat: false };
+
+        let merge = plan_merge(&root.join("opp-001.md"), &root.join("opp-002.md"), root, &schema, &opts).unwrap();
+        assert_eq!(
+            merge.into.body,
+            "\nIntro.\n\n# Problem\n\nSlow builds.\n\nCI is slow too.\n\n# Notes\n\nA.\n\nSee [ADR-002](adr-002.md).\n"
+        );
+        let fm = merge.into.frontmatter.as_ref().unwrap();
+        assert_eq!(fm.get_display("enables").as_deref(), Some("[ADR-001, ADR-002]"));
+        assert_eq!(fm.get_display("supersedes").as_deref(), Some("OPP-002"));
+        assert!(merge.conflicts.is_empty(), "{:?}", merge.conflicts);
+
+        let stub = merge.from.frontmatter.as_ref().unwrap();
+        assert_eq!(stub.get_display("status").as_deref(), Some("superseded"));
+        assert_eq!(stub.get_display("superseded_by").as_deref(), Some("OPP-001"));
+        assert!(stub.get("enables").is_none());
+        assert_eq!(merge.from.body, "\nMerged into [OPP-001](opp-001.md).\n");
+
+        // References by ID, by uid, and by link all move to OPP-001
+        let referrers: Vec<(String, &Document)> = merge
+            .referrers
+            .iter()
+            .map(|d| (path_to_id(d.path.as_ref().unwrap()), d))
+            .collect();
+        assert_eq!(referrers.iter().map(|(id, _)| id.as_str()).collect::<Vec<_>>(), ["ADR-002", "ADR-003"]);
+        let adr2 = referrers[0].1;
+        assert_eq!(adr2.frontmatter.as_ref().unwrap().get_display("enabled_by").as_deref(), Some("[OPP-001]"));
+        assert_eq!(adr2.body, "\nCame from [OPP-002](opp-001.md#evidence).\n");
+        assert_eq!(referrers[1].1.frontmatter.as_ref().unwrap().get_display("source").as_deref(), Some("OPP-001"));
+
+        let err = plan_merge(&root.join("opp-001.md"), &root.join("opp-001.md"), root, &schema, &opts).unwrap_err();
+        assert!(err.to_string().contains("into itself"), "{err}");
+    }
+}
@@ -1,14 +1,19 @@
 //! Schema migration — detect schema changes and help migrate documents.
 //!
 //! Compares two schemas, produces a diff, scans documents, and builds a migration plan.
+//! The diff also names its breaking changes: those that can make a document that
+//! validated under the old schema fail under the new one.
 
 use std::collections::{HashMap, HashSet};
 use std::fmt;
 use std::path::{Path, PathBuf};
 
+use serde_json::{json, Value};
+
 use crate::discovery;
 use crate::document::Document;
 use crate::schema::{FieldDef, FieldType, Schema, SectionDef, TypeDef};
+use crate::validation::Severity;
 
 // ─── Schema Diff ─────────────────────────────────────────────────────────────
 
@@ -29,6 +34,9 @@ pub struct TypeChange {
     pub changed_fields: Vec<FieldChange>,
     pub added_sections: Vec<String>,
     pub removed_sections: Vec<String>,
+    /// Sections required now that were not before: new required sections and
+    /// formerly optional ones.
+    pub required_sections: Vec<String>,
 }
 
 /// A field that changed between schema versions.
@@ -109,12 +117,16 @@ impl fmt::Display for SchemaDiff {
                 }
             }
             for s in &tc.added_sections {
+                let req = if tc.required_sections.contains(s) { " (required)" } else { "" };
                 writeln!(
                     f,
-                    "  + section \"{s}\" on type \"{}\"",
+                    "  + section \"{s}\"{req} on type \"{}\"",
                     tc.type_name
                 )?;
             }
+            for s in tc.required_sections.iter().filter(|s| !tc.added_sections.contains(s)) {
+                writeln!(f, "  ~ section \"{s}\" now required on type \"{}\"", tc.type_name)?;
+            }
             for s in &tc.removed_sections {
                 writeln!(
                     f,
@@ -205,6 +217,13 @@ fn diff_type(old: &TypeDef, new: &TypeDef) -> TypeChange {
         .cloned()
         .collect();
 
+    let old_required = collect_required_sections(&old.sections);
+    let mut required_sections: Vec<String> = collect_required_sections(&new.sections)
+        .difference(&old_required)
+        .cloned()
+        .collect();
+    required_sections.sort();
+
     TypeChange {
         type_name: new.name.clone(),
         added_fields,
@@ -212,6 +231,7 @@ fn diff_type(old: &TypeDef, new: &TypeDef) -> TypeChange {
         changed_fields,
         added_sections,
         removed_sections,
+        required_sections,
     }
 }
 
@@ -222,6 +242,7 @@ impl TypeChange {
             && self.changed_fields.is_empty()
             && self.added_sections.is_empty()
             && self.removed_sections.is_empty()
+            && self.required_sections.is_empty()
     }
 }
 
@@ -232,6 +253,192 @@ impl SchemaDiff {
             && self.removed_types.is_empty()
             && self.type_changes.is_empty()
     }
+
+    /// The diff as JSON, as printed by `migrate --format json` and `diff --format json`.
+    pub fn to_json(&self) -> Value {
+        let type_changes: Vec<Value> = self
+            .type_changes
+            .iter()
+            .map(|tc| {
+                json!({
+                    "type": tc.type_name,
+                    "added_fields": tc.added_fields.iter().map(|f| &f.name).collect::<Vec<_>>(),
+                    "removed_fields": tc.removed_fields.iter().map(|f| &f.name).collect::<Vec<_>>(),
+                    "changed_fields": tc.changed_fields.iter().map(|c| {
+                        json!({
+                            "name": c.name,
+                            "removed_enum_values": c.removed_enum_values,
+                            "added_enum_values": c.added_enum_values,
+                        })
+                    }).collect::<Vec<_>>(),
+                    "added_sections": tc.added_sections,
+                    "removed_sections": tc.removed_sections,
+                    "required_sections": tc.required_sections,
+                })
+            })
+            .collect();
+        json!({
+            "added_types": self.added_types,
+            "removed_types": self.removed_types,
+            "type_changes": type_changes,
+        })
+    }
+
+    /// Changes that can invalidate existing documents, in diff order.
+    pub fn breaking_changes(&self) -> Vec<BreakingChange> {
+        let mut changes: Vec<BreakingChange> = self
+            .removed_types
+            .iter()
+            .map(|t| BreakingChange::new(BreakingKind::RemovedType, t, None, None))
+            .collect();
+        for tc in &self.type_changes {
+            let t = tc.type_name.as_str();
+            for field in tc.added_fields.iter().filter(|f| f.required) {
+                changes.push(BreakingChange::new(BreakingKind::RequiredField, t, Some(&field.name), None));
+            }
+            for fc in &tc.changed_fields {
+                if fc.new.required && !fc.old.required {
+                    changes.push(BreakingChange::new(BreakingKind::RequiredField, t, Some(&fc.name), None));
+                }
+                let retyped = match (&fc.old.field_type, &fc.new.field_type) {
+                    (FieldType::Enum(_), FieldType::Enum(_)) => false,
+                    (old, new) => old != new,
+                };
+                if retyped {
+                    changes.push(BreakingChange::new(BreakingKind::FieldTypeChanged, t, Some(&fc.name), None));
+                }
+                for value in &fc.removed_enum_values {
+                    changes.push(BreakingChange::new(
+                        BreakingKind::RemovedEnumValue,
+                        t,
+                        Some(&fc.name),
+                        Some(value),
+                    ));
+                }
+            }
+            for section in &tc.required_sections {
+                changes.push(BreakingChange::new(BreakingKind::RequiredSection, t, Some(section), None));
+            }
+        }
+        changes
+    }
+}
+
+// ─── Breaking Changes ────────────────────────────────────────────────────────
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BreakingKind {
+    /// Documents of the type no longer have a schema.
+    RemovedType,
+    /// A new required field, or an optional one made required.
+    RequiredField,
+    /// Existing values may not parse as the new type.
+    FieldTypeChanged,
+    RemovedEnumValue,
+    /// A new required section, or an optional one made required.
+    RequiredSection,
+}
+
+impl BreakingKind {
+    pub fn name(self) -> &'static str {
+        match self {
+            BreakingKind::RemovedType => "removed-type",
+            BreakingKind::RequiredField => "required-field",
+            BreakingKind::FieldTypeChanged => "field-type-changed",
+            BreakingKind::RemovedEnumValue => "removed-enum-value",
+            BreakingKind::RequiredSection => "required-section",
+        }
+    }
+}
+
+/// A schema change that can make valid documents invalid.
+#[derive(Debug, Clone)]
+pub struct BreakingChange {
+    pub kind: BreakingKind,
+    pub type_name: String,
+    /// The field or section changed; `None` for a removed type.
+    pub name: Option<String>,
+    /// The enum value removed.
+    pub value: Option<String>,
+    /// Documents it would invalidate, once estimated from a corpus with [`estimate_breakage`].
+    pub affected_docs: Option<Vec<PathBuf>>,
+}
+
+impl BreakingChange {
+    fn new(kind: BreakingKind, type_name: &str, name: Option<&String>, value: Option<&String>) -> Self {
+        Self {
+            kind,
+            type_name: type_name.to_string(),
+            name: name.cloned(),
+            value: value.cloned(),
+            affected_docs: None,
+        }
+    }
+
+    pub fn to_json(&self) -> Value {
+        json!({
+            "kind": self.kind.name(),
+            "type": self.type_name,
+            "name": self.name,
+            "value": self.value,
+            "message": self.to_string(),
+            "affected_docs": self.affected_docs,
+        })
+    }
+}
+
+impl fmt::Display for BreakingChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let t = &self.type_name;
+        let name = self.name.as_deref().unwrap_or_default();
+        match self.kind {
+            BreakingKind::RemovedType => write!(f, "type \"{t}\" removed"),
+            BreakingKind::RequiredField => write!(f, "field \"{name}\" required on type \"{t}\""),
+            BreakingKind::FieldTypeChanged => write!(f, "field \"{name}\" changed type on type \"{t}\""),
+            BreakingKind::RemovedEnumValue => write!(
+                f,
+                "enum value \"{}\" removed from \"{name}\" on type \"{t}\"",
+                self.value.as_deref().unwrap_or_default()
+            ),
+            BreakingKind::RequiredSection => write!(f, "section \"{name}\" required on type \"{t}\""),
+        }
+    }
+}
+
+/// Fill in `affected_docs` of each change from the documents under `dir`: those
+/// of the type that lack a newly required field or section, use a removed enum
+/// value, or hold a value the new field type rejects.
+pub fn estimate_breakage(changes: &mut [BreakingChange], new: &Schema, dir: &Path) {
+    let docs_by_type = load_docs_by_type(dir);
+    let files: Vec<PathBuf> = docs_by_type.values().flatten().map(|(p, _)| p.clone()).collect();
+    let known_files: HashSet<PathBuf> =
+        files.iter().map(|p| p.canonicalize().unwrap_or_else(|_| p.clone())).collect();
+    let known_ids = crate::validation::collect_known_ids(&files);
+
+    for change in changes.iter_mut() {
+        let docs = docs_by_type.get(&change.type_name).map(Vec::as_slice).unwrap_or_default();
+        let name = change.name.as_deref().unwrap_or_default();
+        let breaks = |doc: &Document| -> bool {
+            let fm = doc.frontmatter.as_ref();
+            match change.kind {
+                BreakingKind::RemovedType => true,
+                BreakingKind::RequiredField => fm.is_none_or(|fm| !fm.has_field(name)),
+                BreakingKind::RemovedEnumValue => {
+                    fm.and_then(|fm| fm.get_display(name)).as_deref() == change.value.as_deref()
+                }
+                BreakingKind::RequiredSection => doc.get_section(name).is_err(),
+                BreakingKind::FieldTypeChanged => {
+                    fm.is_some_and(|fm| fm.has_field(name))
+                        && crate::validation::validate_document(doc, new, &known_files, &known_ids, None)
+                            .diagnostics
+                            .iter()
+                            .any(|d| d.severity == Severity::Error && d.location == format!("frontmatter.{name}"))
+                }
+            }
+        };
+        let affected = docs.iter().filter(|(_, doc)| breaks(doc)).map(|(p, _)| p.clone()).collect();
+        change.affected_docs = Some(affected);
+    }
 }
 
 fn fields_differ(a: &FieldDef, b: &FieldDef) -> bool {
@@ -261,6 +468,15 @@ fn collect_section_names(sections: &[SectionDef]) -> HashSet<String> {
     names
 }
 
+fn collect_required_sections(sections: &[SectionDef]) -> HashSet<String> {
+    let mut names = HashSet::new();
+    for s in sections.iter().filter(|s| s.required) {
+        names.insert(s.name.clone());
+        names.extend(collect_required_sections(&s.children));
+    }
+    names
+}
+
 // ─── Migration Plan ──────────────────────────────────────────────────────────
 
 /// A concrete plan of actions to apply to documents.
@@ -284,6 +500,12 @@ pub enum ActionKind {
         field_name: String,
         default_value: String,
     },
+    /// Move a field's value to its new name (the new field lists the old one as an `alias`).
+    RenameField {
+        type_name: String,
+        from: String,
+        to: String,
+    },
     /// Remove a field from documents.
     RemoveField {
         type_name: String,
@@ -302,6 +524,73 @@ pub enum ActionKind {
     },
 }
 
+impl MigrationPlan {
+    /// The actions as JSON, with their affected documents and counts.
+    pub fn to_json(&self) -> Value {
+        let actions: Vec<Value> = self
+            .actions
+            .iter()
+            .map(|a| {
+                let kind = match &a.kind {
+                    ActionKind::AddField {
+                        type_name,
+                        field_name,
+                        default_value,
+                    } => json!({
+                        "action": "add_field",
+                        "type": type_name,
+                        "field": field_name,
+                        "default": default_value,
+                    }),
+                    ActionKind::RenameField { type_name, from, to } => json!({
+                        "action": "rename_field",
+                        "type": type_name,
+                        "from": from,
+                        "to": to,
+                    }),
+                    ActionKind::RemoveField {
+                        type_name,
+                        field_name,
+                    } => json!({
+                        "action": "remove_field",
+                        "type": type_name,
+                        "field": field_name,
+                    }),
+                    ActionKind::RemovedEnumValue {
+                        type_name,
+                        field_name,
+                        value,
+                    } => json!({
+                        "action": "removed_enum_value",
+                        "type": type_name,
+                        "field": field_name,
+                        "value": value,
+                    }),
+                    ActionKind::AddSection {
+                        type_name,
+                        section_name,
+                    } => json!({
+                        "action": "add_section",
+                        "type": type_name,
+                        "section": section_name,
+                    }),
+                };
+                let docs: Vec<String> = a
+                    .affected_docs
+                    .iter()
+                    .map(|p| p.display().to_string())
+                    .collect();
+                json!({
+                    "kind": kind,
+                    "affected_docs": docs,
+                    "count": a.affected_docs.len(),
+                })
+            })
+            .collect();
+        Value::Array(actions)
+    }
+}
+
 impl fmt::Display for MigrationPlan {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         if self.actions.is_empty() {
@@ -322,6 +611,9 @@ impl fmt::Display for MigrationPlan {
                         "  {count} doc(s): add field {field_name}={default_value}"
                     )?;
                 }
+                ActionKind::RenameField { from, to, .. } => {
+                    writeln!(f, "  {count} doc(s): rename field {from} -> {to}")?;
+                }
                 ActionKind::RemoveField { field_name, .. } => {
                     writeln!(f, "  {count} doc(s): remove field {field_name}")?;
                 }
@@ -349,26 +641,38 @@ impl fmt::Display for MigrationPlan {
 pub fn compute_migration(diff: &SchemaDiff, dir: &Path) -> MigrationPlan {
     let mut actions = Vec::new();
 
-    // Discover all markdown files once
-    let all_files = discovery::discover_files(dir, Some("*.md"), &[], false).unwrap_or_default();
+    let docs_by_type = load_docs_by_type(dir);
 
-    // Build a map: type_name -> Vec<(PathBuf, Document)>
-    let mut docs_by_type: HashMap<String, Vec<(PathBuf, Document)>> = HashMap::new();
-    for path in &all_files {
-        if let Ok(doc) = Document::from_file(path) {
-            if let Some(fm) = &doc.frontmatter {
-                if let Some(type_val) = fm.get_display("type") {
-                    docs_by_type
-                        .entry(type_val)
-                        .or_default()
-                        .push((path.clone(), doc));
+    for tc in &diff.type_changes {
+        let docs = docs_by_type.get(&tc.type_name).cloned().unwrap_or_default();
+
+        // Added fields that name a removed one as their alias: move the values over
+        let mut renamed: HashMap<&str, &str> = HashMap::new();
+        for field in &tc.added_fields {
+            for alias in &field.aliases {
+                renamed.insert(alias, &field.name);
+                let affected: Vec<PathBuf> = docs
+                    .iter()
+                    .filter(|(_, doc)| {
+                        doc.frontmatter
+                            .as_ref()
+                            .is_some_and(|fm| fm.has_field(alias) && !fm.has_field(&field.name))
+                    })
+                    .map(|(p, _)| p.clone())
+                    .collect();
+
+                if !affected.is_empty() {
+                    actions.push(MigrationAction {
+                        kind: ActionKind::RenameField {
+                            type_name: tc.type_name.clone(),
+                            from: alias.clone(),
+                            to: field.name.clone(),
+                        },
+                        affected_docs: affected,
+                    });
                 }
             }
         }
-    }
-
-    for tc in &diff.type_changes {
-        let docs = docs_by_type.get(&tc.type_name).cloned().unwrap_or_default();
 
         // Added fields with defaults
         for field in &tc.added_fields {
@@ -378,7 +682,7 @@ pub fn compute_migration(diff: &SchemaDiff, dir: &Path) -> MigrationPlan {
                     .filter(|(_, doc)| {
                         doc.frontmatter
                             .as_ref()
-                            .map(|fm| !fm.has_field(&field.name))
+                            .map(|fm| !fm.has_field(&field.name) && !field.aliases.iter().any(|a| fm.has_field(a)))
                             .unwrap_or(true)
                     })
                     .map(|(p, _)| p.clone())
@@ -399,12 +703,14 @@ pub fn compute_migration(diff: &SchemaDiff, dir: &Path) -> MigrationPlan {
 
         // Removed fields
         for field in &tc.removed_fields {
+            // A renamed field is only removed where the new name is already set
+            let renamed_to = renamed.get(field.name.as_str());
             let affected: Vec<PathBuf> = docs
                 .iter()
                 .filter(|(_, doc)| {
                     doc.frontmatter
                         .as_ref()
-                        .map(|fm| fm.has_field(&field.name))
+                        .map(|fm| fm.has_field(&field.name) && renamed_to.is_none_or(|to| fm.has_field(to)))
                         .unwrap_or(false)
                 })
                 .map(|(p, _)| p.clone())
@@ -472,6 +778,28 @@ pub fn compute_migration(diff: &SchemaDiff, dir: &Path) -> MigrationPlan {
     MigrationPlan { actions }
 }
 
+/// Documents under `dir` grouped by their `type` field.
+fn load_docs_by_type(dir: &Path) -> HashMap<String, Vec<(PathBuf, Document)>> {
+    // Discover all markdown files once
+    let all_files = discovery::discover_files(dir, Some("*.md"), &[], false).unwrap_or_default();
+
+    // Build a map: type_name -> Vec<(PathBuf, Document)>
+    let mut docs_by_type: HashMap<String, Vec<(PathBuf, Document)>> = HashMap::new();
+    for path in &all_files {
+        if let Ok(doc) = Document::from_file(path) {
+            if let Some(fm) = &doc.frontmatter {
+                if let Some(type_val) = fm.get_display("type") {
+                    docs_by_type
+                        .entry(type_val)
+                        .or_default()
+                        .push((path.clone(), doc));
+                }
+            }
+        }
+    }
+    docs_by_type
+}
+
 /// Apply a migration plan: mutate documents on disk.
 pub fn apply_migration(plan: &MigrationPlan) -> Result<ApplyResult, crate::error::Error> {
     let mut modified = 0u32;
@@ -491,6 +819,16 @@ pub fn apply_migration(plan: &MigrationPlan) -> Result<ApplyResult, crate::error
                     modified += 1;
                 }
             }
+            ActionKind::RenameField { from, to, .. } => {
+                for path in &action.affected_docs {
+                    let mut doc = Document::from_file(path)?;
+                    if let Some(value) = doc.remove_field(from) {
+                        doc.set_field(to, value);
+                    }
+                    doc.save()?;
+                    modified += 1;
+                }
+            }
             ActionKind::RemoveField { field_name, .. } => {
                 for path in &action.affected_docs {
                     let mut doc = Document::from_file(path)?;
@@ -520,8 +858,7 @@ pub fn apply_migration(plan: &MigrationPlan) -> Result<ApplyResult, crate::error
                     }
                     raw.push_str(&doc.body);
                     let path = doc.path.as_ref().ok_or(crate::error::Error::NoPath)?;
-                    std::fs::write(path, &raw)
-                        .map_err(|_| crate::error::Error::WriteFailed(path.clone()))?;
+                    crate::durability::write(path, &raw)?;
                     modified += 1;
                 }
             }
@@ -711,6 +1048,13 @@ type "rfc" { field "x" type="string"; section "S" }
                 pattern: None,
                 description: None,
                 default: Some("medium".to_string()),
+                auto: None,
+                min: None,
+                max: None,
+                not_future: false,
+                target_types: Vec::new(),
+                aliases: Vec::new(),
+                examples: Vec::new(),
             });
         }
 
@@ -731,6 +1075,46 @@ type "rfc" { field "x" type="string"; section "S" }
         );
     }
 
+    #[test]
+    fn test_breaking_changes() {
+        let diff = diff_schemas(&schema_v1(), &schema_v2());
+        let mut breaking = diff.breaking_changes();
+        let mut kinds: Vec<_> = breaking.iter().map(|c| (c.kind, c.name.clone(), c.value.clone())).collect();
+        kinds.sort_by_key(|(_, name, value)| (name.clone(), value.clone()));
+        let field = |name: &str| Some(name.to_string());
+        assert_eq!(
+            kinds,
+            vec![
+                (BreakingKind::RequiredField, field("priority"), None),
+                (BreakingKind::RemovedEnumValue, field("status"), field("deprecated")),
+                (BreakingKind::RemovedEnumValue, field("status"), field("rejected")),
+            ]
+        );
+
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("adr-001.md"), "---\ntype: adr\ntitle: A\nstatus: rejected\n---\n").unwrap();
+        std::fs::write(
+            dir.path().join("adr-002.md"),
+            "---\ntype: adr\ntitle: B\nstatus: accepted\npriority: high\n---\n",
+        )
+        .unwrap();
+        estimate_breakage(&mut breaking, &schema_v2(), dir.path());
+        let affected = |name: &str, value: Option<&str>| {
+            let change = breaking.iter().find(|c| c.name.as_deref() == Some(name) && c.value.as_deref() == value);
+            change.unwrap().affected_docs.as_ref().unwrap().len()
+        };
+        assert_eq!(affected("priority", None), 1);
+        assert_eq!(affected("status", Some("rejected")), 1);
+        assert_eq!(affected("status", Some("deprecated")), 0);
+
+        // Making an optional section required breaks documents without it
+        let new = Schema::from_str(r#"type "adr" { field "x" type="string"; section "S" required=#true }"#).unwrap();
+        let old = Schema::from_str(r#"type "adr" { field "x" type="string"; section "S" }"#).unwrap();
+        let breaking = diff_schemas(&old, &new).breaking_changes();
+        assert_eq!(breaking[0].kind, BreakingKind::RequiredSection);
+        assert_eq!(breaking[0].to_string(), "section \"S\" required on type \"adr\"");
+    }
+
     #[test]
     fn test_migration_plan_display_empty() {
         let plan = MigrationPlan {
@@ -834,6 +1218,37 @@ type "rfc" { field "x" type="string"; section "S" }
         let doc = Document::from_file(&doc_path).unwrap();
         assert!(!doc.frontmatter().unwrap().has_field("old_field"));
 
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+    #[test]
+    fn test_migration_renames_aliased_field() {
+        let dir = std::env::temp_dir().join("md_db_migrate_test_rename");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let old_doc = dir.join("task-001.md");
+        let both_doc = dir.join("task-002.md");
+        std::fs::write(&old_doc, "---\ntype: task\nassignee: ana\n---\n").unwrap();
+        std::fs::write(&both_doc, "---\ntype: task\nassignee: ana\nowner: bo\n---\n").unwrap();
+
+        let old = Schema::from_str("type \"task\" {\n    field \"assignee\"\n}\n").unwrap();
+        let new = Schema::from_str(
+            "type \"task\" {\n    field \"owner\" default=\"nobody\" {\n        alias \"assignee\"\n    }\n}\n",
+        )
+        .unwrap();
+        let plan = compute_migration(&diff_schemas(&old, &new), &dir);
+        let kinds: Vec<String> = plan.actions.iter().map(|a| format!("{:?}", a.kind)).collect();
+        assert_eq!(plan.actions.len(), 2, "{kinds:?}");
+        assert!(matches!(plan.actions[0].kind, ActionKind::RenameField { .. }));
+        assert_eq!(plan.actions[0].affected_docs, vec![old_doc.clone()]);
+        assert!(matches!(plan.actions[1].kind, ActionKind::RemoveField { .. }));
+        assert_eq!(plan.actions[1].affected_docs, vec![both_doc.clone()]);
+
+        apply_migration(&plan).unwrap();
+        let fm = Document::from_file(&old_doc).unwrap().frontmatter.unwrap();
+        assert_eq!(fm.get_display("owner").as_deref(), Some("ana"));
+        assert!(!fm.has_field("assignee"));
+
         let _ = std::fs::remove_dir_all(&dir);
     }
 }
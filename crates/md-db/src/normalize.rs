@@ -0,0 +1,516 @@
+//! One-pass normalization for on-save rewriting.
+//!
+//! [`normalize`] applies everything the schema and options say a saved document
+//! should look like — field coercions, heading aliases, auto-timestamps, and
+//! whitespace formatting — and returns the new content with a summary of what
+//! changed. The result is idempotent: normalizing the output again changes nothing.
+//!
+//! `fmt --normalize` and the LSP's formatting request run it before the canonical
+//! layout, through [`format::normalize_and_format`](crate::format::normalize_and_format).
+//! Plain `fmt` uses only the whitespace rules ([`format_body`]), since it must
+//! never change a value.
+
+use comrak::nodes::NodeValue;
+use comrak::Arena;
+use serde::Serialize;
+use serde_yaml::Value;
+
+use crate::ast_util;
+use crate::document::Document;
+use crate::schema::{AutoTimestamp, FieldType, Schema, SectionDef, TypeDef};
+
+/// Which normalizations to apply.
+#[derive(Debug, Clone)]
+pub struct NormalizeOptions {
+    /// Trailing whitespace, blank-line runs, blank lines around headings, final newline.
+    pub format: bool,
+    /// Coerce frontmatter values to their schema field types.
+    pub coerce: bool,
+    /// Rewrite alias headings (and case variants) to the schema section name.
+    pub headings: bool,
+    /// Fill missing `auto` timestamp fields.
+    pub timestamps: bool,
+    /// Refresh `auto="updated"` fields; set when the caller is saving an edit.
+    pub touch: bool,
+    /// Value to stamp instead of the current date (reproducible output, tests).
+    pub now: Option<String>,
+}
+
+impl Default for NormalizeOptions {
+    fn default() -> Self {
+        Self {
+            format: true,
+            coerce: true,
+            headings: true,
+            timestamps: true,
+            touch: false,
+            now: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ChangeKind {
+    Format,
+    Coerce,
+    Heading,
+    Timestamp,
+}
+
+/// One normalization that changed the document.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Change {
+    pub kind: ChangeKind,
+    pub description: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Normalized {
+    /// The full normalized file content.
+    pub content: String,
+    pub changes: Vec<Change>,
+}
+
+impl Normalized {
+    pub fn changed(&self) -> bool {
+        !self.changes.is_empty()
+    }
+}
+
+/// Normalize a document against its schema type. Documents without a known type
+/// only get formatting.
+pub fn normalize(doc: &Document, schema: &Schema, opts: &NormalizeOptions) -> Normalized {
+    let type_def = type_def_for(doc, schema);
+    let mut changes = Vec::new();
+    let mut updated = doc.clone();
+    let mut fm_changed = false;
+
+    if let Some(type_def) = type_def {
+        if opts.coerce {
+            fm_changed |= coerce_fields(&mut updated, type_def, schema, &mut changes);
+        }
+        if opts.timestamps {
+            fm_changed |= stamp_fields(&mut updated, type_def, opts, &mut changes);
+        }
+    }
+
+    let mut body = updated.body.clone();
+    if opts.headings {
+        if let Some(type_def) = type_def {
+            body = rename_headings(&body, type_def, &mut changes);
+        }
+    }
+    if opts.format {
+        let formatted = format_body(&body);
+        if formatted != body {
+            changes.push(Change {
+                kind: ChangeKind::Format,
+                description: "normalized whitespace and blank lines".into(),
+            });
+            body = formatted;
+        }
+    }
+
+    // Keep the original frontmatter text unless a field changed, so formatting
+    // alone never reorders keys or requotes values.
+    let content = if fm_changed {
+        updated.set_body(&body);
+        updated.raw
+    } else if body == doc.body {
+        doc.raw.clone()
+    } else if doc.frontmatter.is_none() {
+        body
+    } else {
+        match frontmatter_block(&doc.raw) {
+            Some(head) => format!("{head}{body}"),
+            None => {
+                updated.set_body(&body);
+                updated.raw
+            }
+        }
+    };
+
+    Normalized { content, changes }
+}
+
+/// The raw `---` ... `---` block at the start of a file, including its last newline.
+pub(crate) fn frontmatter_block(raw: &str) -> Option<&str> {
+    let mut offset = raw.strip_prefix("---")?.find('\n')? + 4;
+    for line in raw[offset..].split_inclusive('\n') {
+        offset += line.len();
+        if line.trim_end() == "---" {
+            return Some(&raw[..offset]);
+        }
+    }
+    None
+}
+
+/// The document's type from frontmatter, or the singleton type matching its file name.
+pub(crate) fn type_def_for<'a>(doc: &Document, schema: &'a Schema) -> Option<&'a TypeDef> {
+    if let Some(type_name) = doc.frontmatter.as_ref().and_then(|fm| fm.get_display("type")) {
+        return schema.get_type(&type_name);
+    }
+    schema.singleton_for(doc.path.as_ref()?)
+}
+
+fn coerce_fields(
+    doc: &mut Document,
+    type_def: &TypeDef,
+    schema: &Schema,
+    changes: &mut Vec<Change>,
+) -> bool {
+    let Some(fm) = doc.frontmatter.as_ref() else {
+        return false;
+    };
+    let mut coerced = Vec::new();
+    for (key, value) in fm.data() {
+        let field_type = match type_def.fields.iter().find(|f| &f.name == key) {
+            Some(f) => f.field_type.clone(),
+            None => match schema.find_relation(key) {
+                Some((relation, _)) => relation.field_type(),
+                None => continue,
+            },
+        };
+        if let Some(new_value) = coerce(value, &field_type) {
+            changes.push(Change {
+                kind: ChangeKind::Coerce,
+                description: format!(
+                    "{key}: {} -> {} ({field_type})",
+                    display(value),
+                    display(&new_value)
+                ),
+            });
+            coerced.push((key.clone(), new_value));
+        }
+    }
+    let changed = !coerced.is_empty();
+    for (key, value) in coerced {
+        doc.set_field(&key, value);
+    }
+    changed
+}
+
+/// The value converted to `field_type`, or `None` when it already fits or can't be converted.
+fn coerce(value: &Value, field_type: &FieldType) -> Option<Value> {
+    let Value::String(s) = value else {
+        return None;
+    };
+    let trimmed = s.trim();
+    match field_type {
+        FieldType::Number => {
+            if let Ok(n) = trimmed.parse::<i64>() {
+                Some(Value::Number(n.into()))
+            } else {
+                trimmed
+                    .parse::<f64>()
+                    .ok()
+                    .filter(|f| f.is_finite())
+                    .map(|f| Value::Number(f.into()))
+            }
+        }
+        FieldType::Bool => match trimmed.to_ascii_lowercase().as_str() {
+            "true" | "yes" | "on" => Some(Value::Bool(true)),
+            "false" | "no" | "off" => Some(Value::Bool(false)),
+            _ => None,
+        },
+        FieldType::Enum(values) => values
+            .iter()
+            .find(|v| v.eq_ignore_ascii_case(trimmed) && v.as_str() != s)
+            .map(|v| Value::String(v.clone())),
+        FieldType::StringArray | FieldType::RefArray | FieldType::UserArray => {
+            Some(Value::Sequence(vec![Value::String(s.clone())]))
+        }
+        FieldType::String
+        | FieldType::Ref
+        | FieldType::User
+        | FieldType::Date
+        | FieldType::DateTime => None,
+    }
+}
+
+fn display(value: &Value) -> String {
+    match value {
+        Value::String(s) => format!("{s:?}"),
+        other => crate::frontmatter::yaml_value_to_string(other),
+    }
+}
+
+fn stamp_fields(
+    doc: &mut Document,
+    type_def: &TypeDef,
+    opts: &NormalizeOptions,
+    changes: &mut Vec<Change>,
+) -> bool {
+    let mut changed = false;
+    for field in &type_def.fields {
+        let Some(auto) = field.auto else {
+            continue;
+        };
+        let current = doc
+            .frontmatter
+            .as_ref()
+            .and_then(|fm| fm.get_display(&field.name))
+            .filter(|v| !v.trim().is_empty());
+        let stamp = opts.now.clone().unwrap_or_else(|| {
+            match field.pattern.as_deref() {
+                Some(p) if p.contains('T') => crate::template::format_now(),
+                _ => crate::template::format_today(),
+            }
+        });
+        let refresh = auto == AutoTimestamp::Updated && opts.touch;
+        match current {
+            None => {}
+            Some(ref value) if refresh && *value != stamp => {}
+            Some(_) => continue,
+        }
+        changes.push(Change {
+            kind: ChangeKind::Timestamp,
+            description: format!("{}: set to {stamp}", field.name),
+        });
+        doc.set_field(&field.name, Value::String(stamp));
+        changed = true;
+    }
+    changed
+}
+
+/// Rewrite ATX headings that match a section alias, or a section name in a
+/// different case, to the schema's spelling.
+fn rename_headings(body: &str, type_def: &TypeDef, changes: &mut Vec<Change>) -> String {
+    let mut defs = Vec::new();
+    collect_section_defs(&type_def.sections, &mut defs);
+
+    let arena = Arena::new();
+    let opts = ast_util::comrak_opts();
+    let root = comrak::parse_document(&arena, body, &opts);
+    let headings: Vec<(usize, u8, String)> = root
+        .descendants()
+        .filter_map(|node| {
+            let data = node.data.borrow();
+            let NodeValue::Heading(ref h) = data.value else {
+                return None;
+            };
+            if h.setext {
+                return None;
+            }
+            Some((data.sourcepos.start.line, h.level, ast_util::collect_text(node).trim().to_string()))
+        })
+        .collect();
+
+    let mut renames = Vec::new();
+    for (line, level, text) in &headings {
+        let Some(def) = defs.iter().find(|d| {
+            d.name.eq_ignore_ascii_case(text) || d.aliases.iter().any(|a| a.eq_ignore_ascii_case(text))
+        }) else {
+            continue;
+        };
+        if def.name == *text {
+            continue;
+        }
+        // Don't create a duplicate when the canonical heading is already there.
+        let is_alias = !def.name.eq_ignore_ascii_case(text);
+        if is_alias && headings.iter().any(|(_, _, other)| other.eq_ignore_ascii_case(&def.name)) {
+            continue;
+        }
+        changes.push(Change {
+            kind: ChangeKind::Heading,
+            description: format!("heading \"{text}\" -> \"{}\"", def.name),
+        });
+        renames.push((*line, format!("{} {}", "#".repeat(*level as usize), def.name)));
+    }
+    if renames.is_empty() {
+        return body.to_string();
+    }
+
+    let mut out = String::with_capacity(body.len());
+    for (i, line) in body.split_inclusive('\n').enumerate() {
+        match renames.iter().find(|(l, _)| *l == i + 1) {
+            Some((_, heading)) => {
+                out.push_str(heading);
+                if line.ends_with('\n') {
+                    out.push('\n');
+                }
+            }
+            None => out.push_str(line),
+        }
+    }
+    out
+}
+
+fn collect_section_defs<'a>(sections: &'a [SectionDef], out: &mut Vec<&'a SectionDef>) {
+    for section in sections {
+        out.push(section);
+        collect_section_defs(&section.children, out);
+    }
+}
+
+/// Strip trailing whitespace (keeping hard breaks), collapse blank-line runs,
+/// put a blank line around ATX headings, and end with a single newline. Fenced
+/// code blocks are left alone.
+pub fn format_body(body: &str) -> String {
+    let mut out: Vec<String> = Vec::new();
+    let mut fence: Option<(char, usize)> = None;
+    let mut after_heading = false;
+
+    let lines: Vec<&str> = body.lines().collect();
+    for (i, line) in lines.iter().enumerate() {
+        if let Some((c, n)) = fence {
+            out.push(line.to_string());
+            let t = line.trim();
+            if t.len() >= n && t.chars().all(|ch| ch == c) {
+                fence = None;
+            }
+            continue;
+        }
+
+        let continues = lines.get(i + 1).is_some_and(|next| !next.trim().is_empty());
+        let line = trim_trailing(line, continues);
+        if line.is_empty() {
+            if out.last().is_some_and(|l| !l.is_empty()) || out.is_empty() {
+                out.push(String::new());
+            }
+            after_heading = false;
+            continue;
+        }
+
+        let heading = is_atx_heading(line);
+        if (heading || after_heading) && out.last().is_some_and(|l| !l.is_empty()) {
+            out.push(String::new());
+        }
+        if let Some(open) = fence_open(line) {
+            fence = Some(open);
+        }
+        out.push(line.to_string());
+        after_heading = heading;
+    }
+
+    while out.last().is_some_and(|l| l.is_empty()) {
+        out.pop();
+    }
+    if out.is_empty() {
+        return String::new();
+    }
+    let mut result = out.join("\n");
+    result.push('\n');
+    result
+}
+
+/// `continues` says whether the next line is part of the same block.
+fn trim_trailing(line: &str, continues: bool) -> &str {
+    let trimmed = line.trim_end();
+    let tail = &line[trimmed.len()..];
+    // Two or more trailing spaces before a continuation line are a Markdown hard line break.
+    if continues && !trimmed.is_empty() && tail.len() >= 2 && tail.chars().all(|c| c == ' ') {
+        line
+    } else {
+        trimmed
+    }
+}
+
+pub(crate) fn is_atx_heading(line: &str) -> bool {
+    let indent = line.len() - line.trim_start_matches(' ').len();
+    let rest = &line[indent..];
+    let hashes = rest.len() - rest.trim_start_matches('#').len();
+    indent <= 3
+        && (1..=6).contains(&hashes)
+        && rest[hashes..].chars().next().is_none_or(|c| c == ' ' || c == '\t')
+}
+
+/// The fence character and length if `line` opens a fenced code block.
+pub(crate) fn fence_open(line: &str) -> Option<(char, usize)> {
+    let t = line.trim_start();
+    if line.len() - t.len() > 3 {
+        return None;
+    }
+    let c = t.chars().next().filter(|c| *c == '`' || *c == '~')?;
+    let n = t.len() - t.trim_start_matches(c).len();
+    (n >= 3).then_some((c, n))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn schema() -> Schema {
+        Schema::from_str(
+            r#"
+relation "related" cardinality="many"
+
+type "adr" {
+    field "title" type="string" required=#true
+    field "status" type="enum" {
+        values "proposed" "accepted"
+    }
+    field "priority" type="number"
+    field "draft" type="bool"
+    field "tags" type="string[]"
+    field "created" type="string" auto="created"
+    field "updated" type="string" auto="updated"
+    section "Context" required=#true {
+        alias "Background"
+    }
+    section "Decision" required=#true
+}
+"#,
+        )
+        .unwrap()
+    }
+
+    fn opts(touch: bool) -> NormalizeOptions {
+        NormalizeOptions {
+            touch,
+            now: Some("2026-10-16".into()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_coercions_and_timestamps() {
+        let doc = Document::from_str(
+            "---\ntype: adr\ntitle: T\nstatus: Accepted\npriority: \"3\"\ndraft: \"no\"\ntags: infra\nrelated: ADR-002\nupdated: 2020-01-01\n---\n\n# Context\n\nWhy.\n\n# Decision\n\nWhat.\n",
+        )
+        .unwrap();
+        let result = normalize(&doc, &schema(), &opts(true));
+        let out = Document::from_str(&result.content).unwrap();
+        let fm = out.frontmatter.unwrap();
+        assert_eq!(fm.get("status"), Some(&Value::String("accepted".into())));
+        assert_eq!(fm.get("priority"), Some(&Value::Number(3.into())));
+        assert_eq!(fm.get("draft"), Some(&Value::Bool(false)));
+        assert_eq!(fm.get_display("tags").as_deref(), Some("[infra]"));
+        assert!(fm.get("related").unwrap().is_sequence());
+        assert_eq!(fm.get_display("created").as_deref(), Some("2026-10-16"));
+        assert_eq!(fm.get_display("updated").as_deref(), Some("2026-10-16"));
+
+        let kinds: Vec<ChangeKind> = result.changes.iter().map(|c| c.kind).collect();
+        assert_eq!(kinds.iter().filter(|k| **k == ChangeKind::Coerce).count(), 5);
+        assert_eq!(kinds.iter().filter(|k| **k == ChangeKind::Timestamp).count(), 2);
+
+        let again = normalize(&out_doc(&result.content), &schema(), &opts(true));
+        assert!(!again.changed(), "{:?}", again.changes);
+
+        // Without touch, an existing `updated` is left alone
+        let untouched = normalize(&doc, &schema(), &opts(false));
+        let fm = out_doc(&untouched.content).frontmatter.unwrap();
+        assert_eq!(fm.get_display("updated").as_deref(), Some("2020-01-01"));
+    }
+
+    #[test]
+    fn test_headings_and_format_keep_frontmatter_text() {
+        let raw = "---\ntype: adr\ntitle: T\ncreated: 2026-01-01\nupdated: 2026-01-01\n---\n\n# background\nWhy.   \n\n\n\n## Notes\nLine with break  \nnext\n```\nkept   \n\n\n```\n# Decision\nWhat.\n\n\n";
+        let doc = Document::from_str(raw).unwrap();
+        let result = normalize(&doc, &schema(), &opts(false));
+
+        assert!(result.content.starts_with("---\ntype: adr\ntitle: T\n"), "frontmatter text kept");
+        assert!(result.content.ends_with(
+            "# Context\n\nWhy.\n\n## Notes\n\nLine with break  \nnext\n```\nkept   \n\n\n```\n\n# Decision\n\nWhat.\n"
+        ), "{}", result.content);
+        let kinds: Vec<ChangeKind> = result.changes.iter().map(|c| c.kind).collect();
+        assert_eq!(kinds, vec![ChangeKind::Heading, ChangeKind::Format]);
+
+        assert!(!normalize(&out_doc(&result.content), &schema(), &opts(false)).changed());
+    }
+
+    fn out_doc(content: &str) -> Document {
+        Document::from_str(content).unwrap()
+    }
+}
@@ -0,0 +1,167 @@
+//! Event notifications: a JSON payload POSTed to a webhook or piped to a command.
+//!
+//! The payload is an object with the `event` name, a one-line `text` summary
+//! (what a Slack incoming webhook shows), and the event's own fields. A command
+//! runs through `sh -c` with the payload on stdin and the event name in
+//! `MD_DB_EVENT`. A webhook that doesn't answer, or a command that doesn't exit,
+//! within its timeout fails rather than stalling the caller (`watch` sends from
+//! its event loop); the command is killed.
+
+use std::io::{Read, Write};
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+
+use serde_json::{json, Value};
+
+use crate::curl;
+use crate::error::{Error, Result};
+
+/// Seconds a webhook or command may take, unless configured.
+pub const DEFAULT_TIMEOUT_SECS: u64 = 10;
+
+/// Where events go.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Notifier {
+    /// POST to this URL (through `curl`), giving up after `timeout` seconds.
+    Webhook { url: String, timeout: u64 },
+    /// Run this shell command, killing it after `timeout` seconds.
+    Command { command: String, timeout: u64 },
+}
+
+impl Notifier {
+    /// The notifiers for `--notify URL`, `--notify-cmd CMD`, and `--notify-timeout SECS`.
+    pub fn from_args(url: Option<&str>, command: Option<&str>, timeout: u64) -> Vec<Notifier> {
+        let webhook = url.map(|u| Notifier::Webhook {
+            url: u.to_string(),
+            timeout,
+        });
+        let command = command.map(|c| Notifier::Command {
+            command: c.to_string(),
+            timeout,
+        });
+        webhook.into_iter().chain(command).collect()
+    }
+
+    pub fn send(&self, payload: &Value) -> Result<()> {
+        let data = payload.to_string();
+        let (mut command, stdin) = match self {
+            Notifier::Webhook { url, timeout } => {
+                // The URL goes through the config on stdin, as webhook URLs often hold a token
+                let mut command = Command::new("curl");
+                command.args(["--config", "-"]);
+                let config = format!(
+                    "url = {}\nrequest = \"POST\"\nheader = \"Content-Type: application/json\"\n\
+                     data-binary = {}\nconnect-timeout = {timeout}\nmax-time = {timeout}\n\
+                     silent\nshow-error\nfail\noutput = \"/dev/null\"\n",
+                    curl::quote(url),
+                    curl::quote(&data)
+                );
+                (command, config)
+            }
+            Notifier::Command { command: cmd, .. } => {
+                let mut command = Command::new("sh");
+                command.args(["-c", cmd]);
+                command.env("MD_DB_EVENT", payload["event"].as_str().unwrap_or_default());
+                (command, data)
+            }
+        };
+        let mut child = command
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| Error::Notify(format!("failed to run {}: {e}", self.program())))?;
+        // Pipes are served from threads, so a command that never reads its input
+        // or floods its error output still runs into the deadline
+        if let Some(mut pipe) = child.stdin.take() {
+            std::thread::spawn(move || pipe.write_all(stdin.as_bytes()));
+        }
+        let stderr = child.stderr.take().map(|mut pipe| {
+            std::thread::spawn(move || {
+                let mut text = String::new();
+                let _ = pipe.read_to_string(&mut text);
+                text
+            })
+        });
+
+        let deadline = Instant::now() + Duration::from_secs(self.timeout());
+        let status = loop {
+            if let Some(status) = child.try_wait()? {
+                break status;
+            }
+            if Instant::now() >= deadline {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(Error::Notify(format!("{} timed out after {}s", self.program(), self.timeout())));
+            }
+            std::thread::sleep(Duration::from_millis(20));
+        };
+        if !status.success() {
+            let stderr = stderr.and_then(|t| t.join().ok()).unwrap_or_default();
+            return Err(Error::Notify(format!("{} failed: {}", self.program(), stderr.trim())));
+        }
+        Ok(())
+    }
+
+    fn program(&self) -> &str {
+        match self {
+            Notifier::Webhook { .. } => "curl",
+            Notifier::Command { command, .. } => command,
+        }
+    }
+
+    fn timeout(&self) -> u64 {
+        match self {
+            Notifier::Webhook { timeout, .. } | Notifier::Command { timeout, .. } => *timeout,
+        }
+    }
+}
+
+/// An event payload: `event`, `text`, then the fields of `fields` (an object).
+pub fn event(name: &str, text: &str, fields: Value) -> Value {
+    let mut payload = json!({ "event": name, "text": text });
+    if let (Some(out), Value::Object(fields)) = (payload.as_object_mut(), fields) {
+        out.extend(fields);
+    }
+    payload
+}
+
+/// Send `payload` to each notifier. A failing notifier is reported in the
+/// returned messages and does not stop the others.
+pub fn send_all(notifiers: &[Notifier], payload: &Value) -> Vec<String> {
+    notifiers
+        .iter()
+        .filter_map(|n| n.send(payload).err().map(|e| e.to_string()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_command_notifier() {
+        let dir = tempfile::tempdir().unwrap();
+        let out = dir.path().join("event.json");
+        let payload = event("reassign", "2 documents reassigned", json!({ "from": "bob", "count": 2 }));
+        assert_eq!(payload["event"], "reassign");
+        assert_eq!(payload["count"], 2);
+
+        let cmd = format!("echo \"$MD_DB_EVENT\" > '{0}'; cat >> '{0}'", out.display());
+        let notifiers = Notifier::from_args(None, Some(&cmd), DEFAULT_TIMEOUT_SECS);
+        assert!(send_all(&notifiers, &payload).is_empty());
+        let written = std::fs::read_to_string(&out).unwrap();
+        let (name, body) = written.split_once('\n').unwrap();
+        assert_eq!(name, "reassign");
+        assert_eq!(serde_json::from_str::<Value>(body).unwrap(), payload);
+
+        let errors = send_all(&Notifier::from_args(None, Some("exit 3"), DEFAULT_TIMEOUT_SECS), &payload);
+        assert_eq!(errors.len(), 1);
+
+        // A hung command is killed at the deadline
+        let started = Instant::now();
+        let errors = send_all(&Notifier::from_args(None, Some("exec sleep 30"), 1), &payload);
+        assert!(errors[0].contains("timed out after 1s"), "{errors:?}");
+        assert!(started.elapsed() < Duration::from_secs(10));
+    }
+}
@@ -0,0 +1,122 @@
+//! Default document order from each type's `order-by` keys.
+//!
+//! Documents are grouped by type name, then ordered by the type's keys, then by
+//! ID so the result never depends on filesystem order. Values that both parse as
+//! dates or datetimes compare chronologically (offsets applied), numbers
+//! numerically, anything else as text.
+//! Documents missing a key sort after those that have it, in either direction.
+
+use std::cmp::Ordering;
+
+use crate::frontmatter::Frontmatter;
+use crate::schema::{OrderKey, Schema};
+
+/// Compare two field values; `None` sorts last regardless of `desc`.
+pub fn compare_values(a: Option<&str>, b: Option<&str>, desc: bool) -> Ordering {
+    match (a, b) {
+        (None, None) => Ordering::Equal,
+        (None, Some(_)) => Ordering::Greater,
+        (Some(_), None) => Ordering::Less,
+        (Some(a), Some(b)) => {
+            let ord = compare_text(a, b);
+            if desc { ord.reverse() } else { ord }
+        }
+    }
+}
+
+/// Compare two present values: chronologically when both are dates/datetimes,
+/// numerically when both are numbers, otherwise as text.
+pub fn compare_text(a: &str, b: &str) -> Ordering {
+    use crate::validation::parse_timestamp;
+    if let (Some(x), Some(y)) = (parse_timestamp(a), parse_timestamp(b)) {
+        return x.cmp(&y);
+    }
+    match (a.parse::<f64>(), b.parse::<f64>()) {
+        (Ok(x), Ok(y)) => x.partial_cmp(&y).unwrap_or(Ordering::Equal),
+        _ => a.cmp(b),
+    }
+}
+
+/// Compare two documents by `keys`, falling back to ID.
+pub fn compare_by_keys(
+    a: (&str, Option<&Frontmatter>),
+    b: (&str, Option<&Frontmatter>),
+    keys: &[OrderKey],
+) -> Ordering {
+    for key in keys {
+        let va = a.1.and_then(|fm| fm.get_display(&key.field));
+        let vb = b.1.and_then(|fm| fm.get_display(&key.field));
+        let ord = compare_values(va.as_deref(), vb.as_deref(), key.desc);
+        if ord != Ordering::Equal {
+            return ord;
+        }
+    }
+    a.0.cmp(b.0)
+}
+
+/// Sort items by type, then by their type's `order-by` keys, then by ID.
+/// `entry` returns an item's ID and frontmatter.
+pub fn sort_documents<T>(
+    items: &mut [T],
+    schema: Option<&Schema>,
+    entry: impl Fn(&T) -> (&str, Option<&Frontmatter>),
+) {
+    let type_of = |fm: Option<&Frontmatter>| fm.and_then(|fm| fm.get_display("type"));
+    items.sort_by(|x, y| {
+        let (a, b) = (entry(x), entry(y));
+        let (ta, tb) = (type_of(a.1), type_of(b.1));
+        ta.cmp(&tb).then_with(|| {
+            let keys = ta
+                .as_deref()
+                .and_then(|t| schema?.get_type(t))
+                .map(|t| t.order_by.as_slice())
+                .unwrap_or(&[]);
+            compare_by_keys(a, b, keys)
+        })
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fm(yaml: &str) -> Frontmatter {
+        Frontmatter::try_parse(&format!("---\n{yaml}\n---\n")).unwrap().0.unwrap()
+    }
+
+    #[test]
+    fn test_compare_values() {
+        assert_eq!(compare_values(Some("9"), Some("10"), false), Ordering::Less);
+        assert_eq!(compare_values(Some("2024-01-02"), Some("2023-12-31"), true), Ordering::Less);
+        assert_eq!(compare_values(None, Some("a"), true), Ordering::Greater);
+        assert_eq!(compare_values(Some("a"), None, false), Ordering::Less);
+    }
+
+    #[test]
+    fn test_sort_documents_by_type_keys_then_id() {
+        let schema = Schema::from_str(
+            r#"
+type "adr" {
+    field "date" type="string"
+    order-by "date" desc=#true
+}
+type "inc" {
+    field "severity" type="number"
+}
+"#,
+        )
+        .unwrap();
+        let docs = vec![
+            ("ADR-001".to_string(), fm("type: adr\ndate: 2023-05-01")),
+            ("INC-002".to_string(), fm("type: inc")),
+            ("ADR-003".to_string(), fm("type: adr")),
+            ("ADR-002".to_string(), fm("type: adr\ndate: 2024-01-15")),
+            ("INC-001".to_string(), fm("type: inc")),
+            ("ADR-004".to_string(), fm("type: adr\ndate: 2024-01-15")),
+        ];
+        let mut items: Vec<&(String, Frontmatter)> = docs.iter().collect();
+        sort_documents(&mut items, Some(&schema), |(id, fm)| (id.as_str(), Some(fm)));
+        let ids: Vec<&str> = items.iter().map(|(id, _)| id.as_str()).collect();
+        assert_eq!(ids, vec!["ADR-002", "ADR-004", "ADR-001", "ADR-003", "INC-001", "INC-002"]);
+    }
+}
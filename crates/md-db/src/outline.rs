@@ -0,0 +1,286 @@
+//! Heading tree of a document with per-section size (`md-db outline`).
+//!
+//! Each heading lists the words written directly under it (prose only, as in
+//! reading time), and its tables and diagrams. With a schema, sections the type
+//! declares are marked required or optional and filled or not, and required
+//! sections the document lacks are listed where they belong.
+
+use std::path::Path;
+
+use comrak::nodes::NodeValue;
+use serde::Serialize;
+
+use crate::document::Document;
+use crate::schema::{Schema, SectionDef};
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct OutlineSection {
+    pub heading: String,
+    pub level: u8,
+    /// Words under the heading before the next heading of any level.
+    pub words: usize,
+    pub tables: usize,
+    /// Fenced `mermaid` and `d2` blocks.
+    pub diagrams: usize,
+    /// Whether the schema requires the section; `None` when it doesn't declare it.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub required: Option<bool>,
+    /// Whether the section, subsections included, holds more than scaffold.
+    pub filled: bool,
+    /// `false` for a required section the document lacks.
+    pub present: bool,
+    pub children: Vec<OutlineSection>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Outline {
+    pub path: Option<String>,
+    pub doc_type: Option<String>,
+    pub words: usize,
+    pub sections: Vec<OutlineSection>,
+}
+
+impl Outline {
+    /// Required sections present and filled, out of all required ones.
+    pub fn required_filled(&self) -> (usize, usize) {
+        fn walk(sections: &[OutlineSection], acc: &mut (usize, usize)) {
+            for s in sections {
+                if s.required == Some(true) {
+                    acc.1 += 1;
+                    if s.present && s.filled {
+                        acc.0 += 1;
+                    }
+                }
+                walk(&s.children, acc);
+            }
+        }
+        let mut acc = (0, 0);
+        walk(&self.sections, &mut acc);
+        acc
+    }
+}
+
+/// Build the outline of `doc`. `schema` adds the required flags.
+pub fn outline(doc: &Document, schema: Option<&Schema>) -> Outline {
+    let doc_type = doc.frontmatter.as_ref().and_then(|fm| fm.get_display("type"));
+    let type_def = schema.and_then(|s| {
+        doc_type
+            .as_deref()
+            .and_then(|t| s.get_type(t))
+            .or_else(|| doc.path.as_deref().and_then(|p| s.singleton_for(p)))
+    });
+
+    let arena = comrak::Arena::new();
+    let root = comrak::parse_document(&arena, &doc.body, &crate::ast_util::comrak_opts());
+    let headings = crate::ast_util::find_headings(root, None);
+    let starts: Vec<usize> = headings
+        .iter()
+        .map(|h| crate::ast_util::section_byte_range(h, &doc.body).start)
+        .collect();
+
+    // Flat list in document order, then nested by level
+    let mut flat = Vec::new();
+    for (i, node) in headings.iter().enumerate() {
+        let content = crate::ast_util::section_content_byte_range(node, &doc.body);
+        let own_end = starts.get(i + 1).copied().unwrap_or(doc.body.len()).clamp(content.start, content.end);
+        let own = &doc.body[content.start..own_end];
+        let (tables, diagrams) = blocks(own);
+        flat.push(OutlineSection {
+            heading: crate::ast_util::collect_text(node).trim().to_string(),
+            level: crate::ast_util::heading_level(node).unwrap_or(1),
+            words: crate::prose::word_count(&crate::prose::prose_text(own)),
+            tables,
+            diagrams,
+            required: None,
+            filled: !crate::template::is_scaffold(&doc.body[content]),
+            present: true,
+            children: Vec::new(),
+        });
+    }
+    let mut sections = nest(&mut flat.into_iter().peekable(), 0);
+    if let Some(type_def) = type_def {
+        mark(&mut sections, &type_def.sections);
+    }
+
+    Outline {
+        path: doc.path.as_ref().map(|p| p.display().to_string()),
+        doc_type: type_def.map(|t| t.name.clone()).or(doc_type),
+        words: crate::prose::word_count(&crate::prose::prose_text(&doc.body)),
+        sections,
+    }
+}
+
+/// Build the outline of the file at `path`.
+pub fn outline_file(path: &Path, schema: Option<&Schema>) -> crate::error::Result<Outline> {
+    Ok(outline(&Document::from_file(path)?, schema))
+}
+
+fn blocks(markdown: &str) -> (usize, usize) {
+    let arena = comrak::Arena::new();
+    let root = comrak::parse_document(&arena, markdown, &crate::ast_util::comrak_opts());
+    let tables = crate::ast_util::find_tables(root).len();
+    let diagrams = root
+        .descendants()
+        .filter(|n| match n.data.borrow().value {
+            NodeValue::CodeBlock(ref cb) => {
+                let lang = cb.info.split_whitespace().next().unwrap_or_default();
+                crate::diagram::CHECKED_LANGUAGES.contains(&lang)
+            }
+            _ => false,
+        })
+        .count();
+    (tables, diagrams)
+}
+
+/// Take the sections deeper than `parent_level` off the front of `flat`, nested.
+fn nest(flat: &mut std::iter::Peekable<impl Iterator<Item = OutlineSection>>, parent_level: u8) -> Vec<OutlineSection> {
+    let mut out = Vec::new();
+    while let Some(mut section) = flat.next_if(|s| s.level > parent_level) {
+        section.children = nest(flat, section.level);
+        out.push(section);
+    }
+    out
+}
+
+/// Match sections against the schema's `defs` by heading, and append required
+/// ones that are missing. A heading the schema doesn't declare (a title, say)
+/// passes `defs` on to its children.
+fn mark(sections: &mut Vec<OutlineSection>, defs: &[SectionDef]) {
+    let mut matched = Vec::new();
+    for section in sections.iter_mut() {
+        match defs.iter().find(|d| d.name == section.heading) {
+            Some(def) => {
+                section.required = Some(def.required);
+                matched.push(def.name.as_str());
+                mark(&mut section.children, &def.children);
+            }
+            None => {
+                mark(&mut section.children, defs);
+                let found = |d: &SectionDef| contains_heading(&section.children, &d.name);
+                matched.extend(defs.iter().filter(|d| found(d)).map(|d| d.name.as_str()));
+            }
+        }
+    }
+    let level = sections.first().map_or(1, |s| s.level);
+    for def in defs.iter().filter(|d| d.required && !matched.contains(&d.name.as_str())) {
+        sections.push(missing(def, level));
+    }
+}
+
+fn contains_heading(sections: &[OutlineSection], heading: &str) -> bool {
+    sections.iter().any(|s| s.heading == heading || contains_heading(&s.children, heading))
+}
+
+fn missing(def: &SectionDef, level: u8) -> OutlineSection {
+    OutlineSection {
+        heading: def.name.clone(),
+        level,
+        words: 0,
+        tables: 0,
+        diagrams: 0,
+        required: Some(true),
+        filled: false,
+        present: false,
+        children: def.children.iter().filter(|c| c.required).map(|c| missing(c, level + 1)).collect(),
+    }
+}
+
+/// Indented heading tree: `✓` marks a filled required section, `✗` one that is
+/// missing or still scaffold.
+pub fn to_text(outline: &Outline) -> String {
+    fn walk(sections: &[OutlineSection], depth: usize, out: &mut String) {
+        for s in sections {
+            let mark = match s.required {
+                Some(true) if s.present && s.filled => "✓ ",
+                Some(true) => "✗ ",
+                _ => "  ",
+            };
+            let mut details = Vec::new();
+            if s.present {
+                details.push(count(s.words, "word"));
+            } else {
+                details.push("missing".to_string());
+            }
+            if s.tables > 0 {
+                details.push(count(s.tables, "table"));
+            }
+            if s.diagrams > 0 {
+                details.push(count(s.diagrams, "diagram"));
+            }
+            out.push_str(&format!("{}{mark}{} ({})\n", "  ".repeat(depth), s.heading, details.join(", ")));
+            walk(&s.children, depth + 1, out);
+        }
+    }
+
+    let mut out = String::new();
+    let name = outline.path.as_deref().unwrap_or("<stdin>");
+    let (filled, required) = outline.required_filled();
+    let mut summary = vec![count(outline.words, "word")];
+    if let Some(ref t) = outline.doc_type {
+        summary.insert(0, t.clone());
+    }
+    if required > 0 {
+        summary.push(format!("{filled}/{required} required sections"));
+    }
+    out.push_str(&format!("{name} ({})\n", summary.join(", ")));
+    walk(&outline.sections, 1, &mut out);
+    out
+}
+
+fn count(n: usize, noun: &str) -> String {
+    format!("{n} {noun}{}", if n == 1 { "" } else { "s" })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCHEMA: &str = "type \"adr\" {\n    section \"Context\" required=#true\n    \
+                          section \"Options\" {\n        section \"Comparison\" required=#true\n    }\n    \
+                          section \"Decision\" required=#true\n    section \"Consequences\" required=#true\n}\n";
+
+    #[test]
+    fn test_outline() {
+        let doc = Document::from_str(
+            "---\ntype: adr\n---\n# Use Kafka\n\n## Context\n\nWe need a queue for events.\n\n\
+             ## Options\n\n| Option | Cost |\n|---|---|\n| Kafka | high |\n\n### Comparison\n\n\
+             ```mermaid\ngraph TD\n  A --> B\n```\n\n## Decision\n\nTODO\n",
+        )
+        .unwrap();
+        let schema = Schema::from_str(SCHEMA).unwrap();
+        let outline = outline(&doc, Some(&schema));
+
+        assert_eq!(outline.sections.len(), 1);
+        let title = &outline.sections[0];
+        assert_eq!((title.heading.as_str(), title.required), ("Use Kafka", None));
+        let summary: Vec<(&str, usize, Option<bool>, bool, bool)> = title
+            .children
+            .iter()
+            .map(|s| (s.heading.as_str(), s.words, s.required, s.filled, s.present))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("Context", 6, Some(true), true, true),
+                ("Options", 0, Some(false), true, true),
+                ("Decision", 1, Some(true), false, true),
+                ("Consequences", 0, Some(true), false, false),
+            ]
+        );
+        let options = &title.children[1];
+        assert_eq!(options.tables, 1);
+        assert_eq!((options.children[0].diagrams, options.children[0].required), (1, Some(true)));
+        assert_eq!(outline.required_filled(), (2, 4));
+
+        let text = to_text(&outline);
+        assert!(text.starts_with("<stdin> (adr, "), "{text}");
+        assert!(text.contains("2/4 required sections"), "{text}");
+        assert!(text.contains("    ✓ Context (6 words)\n"), "{text}");
+        assert!(text.contains("      ✓ Comparison (0 words, 1 diagram)\n"), "{text}");
+        assert!(text.contains("    ✗ Consequences (missing)\n"), "{text}");
+
+        // Without a schema nothing is marked
+        let plain = super::outline(&doc, None);
+        assert!(!to_text(&plain).contains('✓'));
+    }
+}
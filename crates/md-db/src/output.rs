@@ -1,4 +1,6 @@
-use std::io::IsTerminal;
+pub mod problem;
+pub mod projection;
+pub mod sarif;
 
 use serde_json::Value;
 
@@ -15,6 +17,14 @@ pub enum OutputFormat {
     Json,
     /// One-liner per diagnostic: `code:severity:location:message`
     Compact,
+    /// SARIF 2.1.0 log, for code scanning (validation results only)
+    Sarif,
+    /// Comma-separated values with a header row (tabular results only)
+    Csv,
+    /// Tab-separated values with a header row (tabular results only)
+    Tsv,
+    /// JSON Lines: one compact object per result, written as results are produced
+    Ndjson,
 }
 
 impl OutputFormat {
@@ -24,14 +34,19 @@ impl OutputFormat {
             "markdown" | "md" => Some(Self::Markdown),
             "json" => Some(Self::Json),
             "compact" => Some(Self::Compact),
+            "sarif" => Some(Self::Sarif),
+            "csv" => Some(Self::Csv),
+            "tsv" => Some(Self::Tsv),
+            "ndjson" | "jsonl" => Some(Self::Ndjson),
             "auto" => Some(Self::auto()),
             _ => None,
         }
     }
 
     /// Auto-detect: JSON when stdout is not a TTY, text otherwise.
+    /// Output headed for the pager counts as a TTY.
     pub fn auto() -> Self {
-        if std::io::stdout().is_terminal() {
+        if crate::terminal::stdout_is_tty() {
             Self::Text
         } else {
             Self::Json
@@ -77,50 +92,135 @@ pub fn format_list(
 ) -> String {
     match format {
         OutputFormat::Json => {
-            let arr: Vec<Value> = entries
-                .iter()
-                .map(|e| {
-                    let mut obj = serde_json::Map::new();
-                    obj.insert(
-                        "path".to_string(),
-                        Value::String(e.path.clone()),
-                    );
-                    if let Some(ref fm) = e.frontmatter_json {
-                        match fields {
-                            Some(field_list) => {
-                                for f in field_list {
-                                    if let Some(v) = fm.get(f) {
-                                        obj.insert(f.clone(), v.clone());
-                                    }
-                                }
-                            }
-                            None => {
-                                if let Value::Object(map) = fm {
-                                    for (k, v) in map {
-                                        obj.insert(k.clone(), v.clone());
-                                    }
-                                }
-                            }
-                        }
-                    }
-                    Value::Object(obj)
-                })
-                .collect();
+            let arr: Vec<Value> = entries.iter().map(|e| list_entry_json(e, fields)).collect();
             serde_json::to_string_pretty(&arr).unwrap_or_default()
         }
+        OutputFormat::Ndjson => entries
+            .iter()
+            .map(|e| list_entry_json(e, fields).to_string())
+            .collect::<Vec<_>>()
+            .join("\n"),
         _ => entries
             .iter()
-            .map(|e| e.path.clone())
+            .map(|e| match (fields, &e.frontmatter_json) {
+                // Tab-separated columns: path, then each selected field
+                (Some(field_list), Some(fm)) => {
+                    let mut cols = vec![e.path.clone()];
+                    for f in field_list {
+                        cols.push(match fm.get(f) {
+                            Some(Value::String(s)) => s.clone(),
+                            Some(Value::Null) | None => String::new(),
+                            Some(v) => v.to_string(),
+                        });
+                    }
+                    cols.join("\t")
+                }
+                _ => e.path.clone(),
+            })
             .collect::<Vec<_>>()
             .join("\n"),
     }
 }
 
+/// The JSON object for one list entry: `path` plus its frontmatter, or only the
+/// selected `fields` of it.
+pub fn list_entry_json(entry: &ListEntry, fields: &Option<Vec<String>>) -> Value {
+    let mut obj = serde_json::Map::new();
+    obj.insert("path".to_string(), Value::String(entry.path.clone()));
+    if let Some(ref fm) = entry.frontmatter_json {
+        match fields {
+            Some(field_list) => {
+                for f in field_list {
+                    if let Some(v) = fm.get(f) {
+                        obj.insert(f.clone(), v.clone());
+                    }
+                }
+            }
+            None => {
+                if let Value::Object(map) = fm {
+                    for (k, v) in map {
+                        obj.insert(k.clone(), v.clone());
+                    }
+                }
+            }
+        }
+    }
+    Value::Object(obj)
+}
+
 pub struct ListEntry {
     pub path: String,
     pub frontmatter_json: Option<Value>,
 }
 
+/// Where a page of results sits within the full result set.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Page {
+    /// Number of items before pagination.
+    pub total: usize,
+    pub offset: usize,
+    pub limit: Option<usize>,
+    /// Number of items in this page.
+    pub returned: usize,
+}
+
+impl Page {
+    /// Whether items exist past the end of this page.
+    pub fn truncated(&self) -> bool {
+        self.offset + self.returned < self.total
+    }
+
+    /// Offset of the next page, if there is one.
+    pub fn next_offset(&self) -> Option<usize> {
+        self.truncated().then_some(self.offset + self.returned)
+    }
+
+    pub fn to_json(&self) -> Value {
+        serde_json::json!({
+            "total": self.total,
+            "offset": self.offset,
+            "limit": self.limit,
+            "count": self.returned,
+            "truncated": self.truncated(),
+            "next_offset": self.next_offset(),
+        })
+    }
+}
+
+/// Apply `offset`/`limit` to a result list, returning the page and its position.
+pub fn paginate<T>(items: Vec<T>, offset: usize, limit: Option<usize>) -> (Vec<T>, Page) {
+    let total = items.len();
+    let page: Vec<T> = items
+        .into_iter()
+        .skip(offset)
+        .take(limit.unwrap_or(usize::MAX))
+        .collect();
+    let returned = page.len();
+    (
+        page,
+        Page {
+            total,
+            offset,
+            limit,
+            returned,
+        },
+    )
+}
+
+/// Keep only the listed keys of a JSON object. Missing keys are skipped;
+/// non-object values are returned unchanged.
+pub fn project_fields(value: &Value, fields: &[String]) -> Value {
+    match value {
+        Value::Object(map) => Value::Object(
+            fields
+                .iter()
+                .filter_map(|f| map.get(f).map(|v| (f.clone(), v.clone())))
+                .collect(),
+        ),
+        other => other.clone(),
+    }
+}
+
 fn strip_markdown(md: &str) -> String {
     use comrak::{Arena, Options};
     let arena = Arena::new();
@@ -128,3 +228,68 @@ fn strip_markdown(md: &str) -> String {
     let root = comrak::parse_document(&arena, md, &opts);
     crate::ast_util::collect_text_blocks(root)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_paginate() {
+        let (page, info) = paginate((0..10).collect::<Vec<_>>(), 4, Some(3));
+        assert_eq!(page, vec![4, 5, 6]);
+        assert_eq!(info.total, 10);
+        assert!(info.truncated());
+        assert_eq!(info.next_offset(), Some(7));
+
+        let (page, info) = paginate((0..10).collect::<Vec<_>>(), 8, Some(5));
+        assert_eq!(page, vec![8, 9]);
+        assert!(!info.truncated());
+        assert_eq!(info.next_offset(), None);
+
+        let (page, info) = paginate((0..3).collect::<Vec<_>>(), 0, None);
+        assert_eq!(page.len(), 3);
+        assert!(!info.truncated());
+
+        let (page, info) = paginate((0..3).collect::<Vec<_>>(), 10, None);
+        assert!(page.is_empty());
+        assert!(!info.truncated());
+    }
+
+    #[test]
+    fn test_project_fields() {
+        let fm = serde_json::json!({"title": "T", "status": "accepted", "body": "long"});
+        let projected = project_fields(&fm, &["status".into(), "missing".into()]);
+        assert_eq!(projected, serde_json::json!({"status": "accepted"}));
+    }
+
+    #[test]
+    fn test_format_list_text_with_fields() {
+        let entries = vec![ListEntry {
+            path: "adr-001.md".into(),
+            frontmatter_json: Some(serde_json::json!({"title": "T", "status": "accepted"})),
+        }];
+        let out = format_list(
+            &entries,
+            OutputFormat::Text,
+            &Some(vec!["status".into(), "owner".into()]),
+        );
+        assert_eq!(out, "adr-001.md\taccepted\t");
+    }
+
+    #[test]
+    fn test_format_list_ndjson() {
+        let entries = vec![
+            ListEntry {
+                path: "adr-001.md".into(),
+                frontmatter_json: Some(serde_json::json!({"title": "T", "status": "accepted"})),
+            },
+            ListEntry {
+                path: "notes.md".into(),
+                frontmatter_json: None,
+            },
+        ];
+        assert_eq!(OutputFormat::from_str("jsonl"), Some(OutputFormat::Ndjson));
+        let out = format_list(&entries, OutputFormat::Ndjson, &Some(vec!["status".into()]));
+        assert_eq!(out, "{\"path\":\"adr-001.md\",\"status\":\"accepted\"}\n{\"path\":\"notes.md\"}");
+    }
+}
@@ -0,0 +1,319 @@
+//! RFC 7807 problem details (`application/problem+json`) for HTTP error responses.
+//!
+//! Each failure has a [`ProblemKind`] with a stable `type` URI
+//! (`urn:md-db:problem:document-not-found`, ...), title, and status code, so
+//! clients branch on `type` rather than on the `detail` text. Extension members
+//! carry data, e.g. the diagnostics of a `validation-failed` problem.
+
+use serde_json::{json, Map, Value};
+
+use crate::error::Error;
+use crate::validation::ValidationResult;
+
+pub const CONTENT_TYPE: &str = "application/problem+json";
+
+/// Prefix of every problem `type`.
+pub const TYPE_PREFIX: &str = "urn:md-db:problem:";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProblemKind {
+    /// The request is malformed: bad JSON, a missing or invalid parameter.
+    BadRequest,
+    /// No valid `Authorization: Bearer` token.
+    Unauthorized,
+    /// No document with the requested ID or path.
+    DocumentNotFound,
+    /// No such route, or no such section, field, or table in a document.
+    NotFound,
+    MethodNotAllowed,
+    /// The document changed since it was read; the write was not made.
+    WriteConflict,
+    /// The document does not validate; `diagnostics` lists why.
+    ValidationFailed,
+    /// The schema could not be found or parsed.
+    SchemaError,
+    Internal,
+}
+
+impl ProblemKind {
+    pub const ALL: [ProblemKind; 9] = [
+        ProblemKind::BadRequest,
+        ProblemKind::Unauthorized,
+        ProblemKind::DocumentNotFound,
+        ProblemKind::NotFound,
+        ProblemKind::MethodNotAllowed,
+        ProblemKind::WriteConflict,
+        ProblemKind::ValidationFailed,
+        ProblemKind::SchemaError,
+        ProblemKind::Internal,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ProblemKind::BadRequest => "bad-request",
+            ProblemKind::Unauthorized => "unauthorized",
+            ProblemKind::DocumentNotFound => "document-not-found",
+            ProblemKind::NotFound => "not-found",
+            ProblemKind::MethodNotAllowed => "method-not-allowed",
+            ProblemKind::WriteConflict => "write-conflict",
+            ProblemKind::ValidationFailed => "validation-failed",
+            ProblemKind::SchemaError => "schema-error",
+            ProblemKind::Internal => "internal-error",
+        }
+    }
+
+    pub fn title(self) -> &'static str {
+        match self {
+            ProblemKind::BadRequest => "Bad request",
+            ProblemKind::Unauthorized => "Unauthorized",
+            ProblemKind::DocumentNotFound => "Document not found",
+            ProblemKind::NotFound => "Not found",
+            ProblemKind::MethodNotAllowed => "Method not allowed",
+            ProblemKind::WriteConflict => "Write conflict",
+            ProblemKind::ValidationFailed => "Validation failed",
+            ProblemKind::SchemaError => "Schema error",
+            ProblemKind::Internal => "Internal error",
+        }
+    }
+
+    pub fn status(self) -> u16 {
+        match self {
+            ProblemKind::BadRequest => 400,
+            ProblemKind::Unauthorized => 401,
+            ProblemKind::DocumentNotFound | ProblemKind::NotFound => 404,
+            ProblemKind::MethodNotAllowed => 405,
+            ProblemKind::WriteConflict => 409,
+            ProblemKind::ValidationFailed => 422,
+            ProblemKind::SchemaError | ProblemKind::Internal => 500,
+        }
+    }
+
+    pub fn type_uri(self) -> String {
+        format!("{TYPE_PREFIX}{}", self.name())
+    }
+}
+
+/// A problem details object.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Problem {
+    pub kind: ProblemKind,
+    pub detail: String,
+    /// The request path the problem occurred at.
+    pub instance: Option<String>,
+    /// Extension members, after the standard ones.
+    pub extensions: Map<String, Value>,
+}
+
+impl Problem {
+    pub fn new(kind: ProblemKind, detail: impl Into<String>) -> Self {
+        Self {
+            kind,
+            detail: detail.into(),
+            instance: None,
+            extensions: Map::new(),
+        }
+    }
+
+    pub fn with_instance(mut self, instance: impl Into<String>) -> Self {
+        self.instance = Some(instance.into());
+        self
+    }
+
+    pub fn with_extension(mut self, key: &str, value: Value) -> Self {
+        self.extensions.insert(key.to_string(), value);
+        self
+    }
+
+    /// `validation-failed` with every diagnostic of `result`, and its error and warning counts.
+    pub fn validation_failed(result: &ValidationResult) -> Self {
+        let diagnostics: Vec<Value> = result
+            .file_results
+            .iter()
+            .flat_map(|f| {
+                f.diagnostics.iter().map(|d| {
+                    json!({
+                        "path": f.path,
+                        "severity": d.severity.to_string(),
+                        "code": d.code,
+                        "message": d.message,
+                        "location": d.location,
+                        "hint": d.hint,
+                        "span": d.span,
+                    })
+                })
+            })
+            .collect();
+        let (errors, warnings) = (result.total_errors(), result.total_warnings());
+        Self::new(ProblemKind::ValidationFailed, format!("{errors} error(s), {warnings} warning(s)"))
+            .with_extension("errors", json!(errors))
+            .with_extension("warnings", json!(warnings))
+            .with_extension("diagnostics", Value::Array(diagnostics))
+    }
+
+    pub fn status(&self) -> u16 {
+        self.kind.status()
+    }
+
+    pub fn to_json(&self) -> Value {
+        let mut out = Map::new();
+        out.insert("type".into(), json!(self.kind.type_uri()));
+        out.insert("title".into(), json!(self.kind.title()));
+        out.insert("status".into(), json!(self.status()));
+        out.insert("detail".into(), json!(self.detail));
+        if let Some(ref instance) = self.instance {
+            out.insert("instance".into(), json!(instance));
+        }
+        for (key, value) in &self.extensions {
+            out.entry(key.clone()).or_insert_with(|| value.clone());
+        }
+        Value::Object(out)
+    }
+
+    /// The status, content type, and body for a client sending `accept`
+    /// (the `Accept` header, if any): problem+json unless the client asks for
+    /// plain JSON or only accepts text.
+    pub fn render(&self, accept: Option<&str>) -> (u16, &'static str, String) {
+        let body = || serde_json::to_string_pretty(&self.to_json()).unwrap_or_default();
+        match negotiate(accept) {
+            Media::ProblemJson => (self.status(), CONTENT_TYPE, body()),
+            Media::Json => (self.status(), "application/json", body()),
+            Media::Text => {
+                let text = format!("{} {}: {}\n", self.status(), self.kind.title(), self.detail);
+                (self.status(), "text/plain; charset=utf-8", text)
+            }
+        }
+    }
+}
+
+impl From<&Error> for Problem {
+    fn from(e: &Error) -> Self {
+        let kind = match e {
+            Error::FileNotFound(_) | Error::DocumentNotFound(_) => ProblemKind::DocumentNotFound,
+            Error::SectionNotFound(_)
+            | Error::FieldNotFound(_)
+            | Error::TableNotFound(_)
+            | Error::CellNotFound { .. }
+            | Error::ColumnNotFound(_)
+            | Error::RowOutOfBounds { .. } => ProblemKind::NotFound,
+            Error::InvalidFieldValue(_) | Error::Json(_) | Error::Workflow(_) => ProblemKind::BadRequest,
+            Error::Undo(_) => ProblemKind::WriteConflict,
+            Error::Auth(_) => ProblemKind::Unauthorized,
+            Error::SchemaParse(_) | Error::SchemaDiscovery(_) | Error::TypeNotFound(_) => ProblemKind::SchemaError,
+            _ => ProblemKind::Internal,
+        };
+        Problem::new(kind, e.to_string())
+    }
+}
+
+impl From<Error> for Problem {
+    fn from(e: Error) -> Self {
+        Problem::from(&e)
+    }
+}
+
+/// Media types an error body can take.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Media {
+    ProblemJson,
+    Json,
+    Text,
+}
+
+/// The error media type best matching an `Accept` header. Without one, or
+/// with a wildcard preferred at least as much, it is problem+json.
+pub fn negotiate(accept: Option<&str>) -> Media {
+    let Some(accept) = accept.filter(|a| !a.trim().is_empty()) else {
+        return Media::ProblemJson;
+    };
+    let mut best: Option<(f32, Media)> = None;
+    for range in accept.split(',') {
+        let mut parts = range.split(';');
+        let media = parts.next().unwrap_or_default().trim().to_ascii_lowercase();
+        let q = parts
+            .filter_map(|p| p.trim().strip_prefix("q="))
+            .find_map(|q| q.trim().parse::<f32>().ok())
+            .unwrap_or(1.0);
+        let candidate = match media.as_str() {
+            "application/problem+json" | "application/*" | "*/*" => Media::ProblemJson,
+            "application/json" => Media::Json,
+            "text/plain" | "text/html" | "text/*" => Media::Text,
+            _ => continue,
+        };
+        // Earlier ranges win ties
+        if q > 0.0 && best.is_none_or(|(best_q, _)| q > best_q) {
+            best = Some((q, candidate));
+        }
+    }
+    best.map_or(Media::ProblemJson, |(_, media)| media)
+}
+
+/// The reason phrase of a status code, for a status line.
+pub fn reason(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        201 => "Created",
+        202 => "Accepted",
+        204 => "No Content",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        403 => "Forbidden",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        406 => "Not Acceptable",
+        409 => "Conflict",
+        413 => "Content Too Large",
+        415 => "Unsupported Media Type",
+        422 => "Unprocessable Content",
+        _ => "Internal Server Error",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::validation::{Diagnostic, FileResult, Severity};
+
+    #[test]
+    fn test_problem_details() {
+        let problem = Problem::from(Error::DocumentNotFound("ADR-404".into())).with_instance("/docs/ADR-404");
+        assert_eq!(
+            problem.to_json(),
+            json!({
+                "type": "urn:md-db:problem:document-not-found",
+                "title": "Document not found",
+                "status": 404,
+                "detail": "document not found: ADR-404",
+                "instance": "/docs/ADR-404",
+            })
+        );
+        assert_eq!(Problem::from(Error::Undo("changed since".into())).status(), 409);
+        assert_eq!(Problem::from(Error::SchemaParse("bad".into())).kind, ProblemKind::SchemaError);
+
+        let result = ValidationResult {
+            file_results: vec![FileResult {
+                path: "docs/adr-001.md".into(),
+                diagnostics: vec![Diagnostic {
+                    severity: Severity::Error,
+                    code: "F010".into(),
+                    message: "missing required field \"date\"".into(),
+                    location: "frontmatter".into(),
+                    hint: None,
+                    span: None,
+                }],
+            }],
+        };
+        let json = Problem::validation_failed(&result).to_json();
+        assert_eq!(json["status"], 422);
+        assert_eq!(json["errors"], 1);
+        assert_eq!(json["diagnostics"][0]["code"], "F010");
+
+        assert_eq!(negotiate(None), Media::ProblemJson);
+        assert_eq!(negotiate(Some("application/json")), Media::Json);
+        assert_eq!(negotiate(Some("text/html,application/xhtml+xml,*/*;q=0.8")), Media::Text);
+        assert_eq!(negotiate(Some("text/plain;q=0.5, application/problem+json")), Media::ProblemJson);
+        let (status, content_type, body) = problem.render(Some("text/plain"));
+        assert_eq!((status, content_type), (404, "text/plain; charset=utf-8"));
+        assert_eq!(body, "404 Document not found: document not found: ADR-404\n");
+        assert_eq!(reason(422), "Unprocessable Content");
+    }
+}
@@ -0,0 +1,206 @@
+//! Column projections for tabular output.
+//!
+//! A column spec is `id`, `path`, a computed column (`words`, `reading_time`,
+//! `completeness`), a table cell `Section.Row.Column` (row 0-based, in the first
+//! table under that heading, e.g. `Action Items.0.Status`), or otherwise a frontmatter
+//! field (dotted paths reach into nested values). Specs are comma-separated; wrap one
+//! in double quotes when it contains a comma.
+
+use std::path::Path;
+
+use serde_json::Value;
+
+use crate::document::Document;
+use crate::frontmatter::yaml_to_json;
+use crate::schema::Schema;
+
+/// Computed columns, derived from the document body rather than stored.
+pub const COMPUTED: &[&str] = &["words", "reading_time", "completeness"];
+
+/// One output column.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Column {
+    /// Document ID derived from the file name.
+    Id,
+    Path,
+    Computed(String),
+    Cell { section: String, row: usize, column: String },
+    Field(String),
+}
+
+impl Column {
+    pub fn parse(spec: &str) -> Self {
+        let spec = spec.trim();
+        match spec {
+            "id" => return Self::Id,
+            "path" => return Self::Path,
+            s if COMPUTED.contains(&s) => return Self::Computed(s.to_string()),
+            _ => {}
+        }
+        let parts: Vec<&str> = spec.rsplitn(3, '.').collect();
+        if let [column, row, section] = parts[..] {
+            if let Ok(row) = row.parse() {
+                if !section.is_empty() && !column.is_empty() {
+                    return Self::Cell {
+                        section: section.to_string(),
+                        row,
+                        column: column.to_string(),
+                    };
+                }
+            }
+        }
+        Self::Field(spec.to_string())
+    }
+
+    /// Header text for the column; parses back to the same column.
+    pub fn name(&self) -> String {
+        match self {
+            Self::Id => "id".into(),
+            Self::Path => "path".into(),
+            Self::Computed(name) | Self::Field(name) => name.clone(),
+            Self::Cell { section, row, column } => format!("{section}.{row}.{column}"),
+        }
+    }
+}
+
+/// Split a `--columns` value on commas outside double quotes.
+pub fn parse_columns(spec: &str) -> Vec<Column> {
+    let mut specs = Vec::new();
+    let mut current = String::new();
+    let mut quoted = false;
+    for c in spec.chars() {
+        match c {
+            '"' => quoted = !quoted,
+            ',' if !quoted => specs.push(std::mem::take(&mut current)),
+            c => current.push(c),
+        }
+    }
+    specs.push(current);
+    specs
+        .iter()
+        .filter(|s| !s.trim().is_empty())
+        .map(|s| Column::parse(s))
+        .collect()
+}
+
+/// Values of `columns` for one document. Missing values are null. `schema` is only
+/// needed for the `completeness` column.
+pub fn project(doc: &Document, path: &Path, columns: &[Column], schema: Option<&Schema>) -> Vec<Value> {
+    let badges = columns
+        .iter()
+        .any(|c| matches!(c, Column::Computed(_)))
+        .then(|| crate::badges::compute(doc, schema));
+    columns
+        .iter()
+        .map(|column| match column {
+            Column::Id => Value::String(crate::graph::path_to_id(path)),
+            Column::Path => Value::String(path.display().to_string()),
+            Column::Computed(name) => match (name.as_str(), badges.as_ref()) {
+                ("words", Some(b)) => Value::from(b.words),
+                ("reading_time", Some(b)) => Value::from(b.reading_minutes),
+                ("completeness", Some(b)) => {
+                    b.completeness.as_ref().map_or(Value::Null, |c| Value::from(c.percent()))
+                }
+                _ => Value::Null,
+            },
+            Column::Cell { section, row, column } => {
+                cell(doc, section, *row, column).map_or(Value::Null, Value::String)
+            }
+            Column::Field(name) => doc
+                .frontmatter
+                .as_ref()
+                .and_then(|fm| fm.get(name))
+                .map_or(Value::Null, yaml_to_json),
+        })
+        .collect()
+}
+
+fn cell(doc: &Document, section: &str, row: usize, column: &str) -> Option<String> {
+    let table = doc.get_section(section).ok()?.tables().into_iter().next()?;
+    let col = table.headers().iter().position(|h| h.eq_ignore_ascii_case(column))?;
+    table.rows().get(row)?.get(col).cloned()
+}
+
+/// Render rows as CSV (`,`, RFC 4180 quoting, CRLF line ends) or TSV (`\t`; tabs and
+/// newlines inside values become spaces). Arrays are joined with "; ".
+pub fn to_delimited(headers: &[String], rows: &[Vec<Value>], delimiter: char) -> String {
+    let line_end = if delimiter == ',' { "\r\n" } else { "\n" };
+    let field = |s: &str| -> String {
+        if delimiter != ',' {
+            s.replace(['\t', '\n', '\r'], " ")
+        } else if s.contains([',', '"', '\n', '\r']) {
+            format!("\"{}\"", s.replace('"', "\"\""))
+        } else {
+            s.to_string()
+        }
+    };
+    let sep = delimiter.to_string();
+
+    let mut out = String::new();
+    let header: Vec<String> = headers.iter().map(|h| field(h)).collect();
+    out.push_str(&header.join(&sep));
+    out.push_str(line_end);
+    for row in rows {
+        let cells: Vec<String> = row.iter().map(|v| field(&cell_text(v))).collect();
+        out.push_str(&cells.join(&sep));
+        out.push_str(line_end);
+    }
+    out
+}
+
+/// Plain-text form of a value for one spreadsheet cell.
+pub fn cell_text(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(s) => s.clone(),
+        Value::Array(items) => items.iter().map(cell_text).collect::<Vec<_>>().join("; "),
+        other => other.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_columns() {
+        let cols = parse_columns(r#"id,status,"Action Items.0.Status",words,"a,b""#);
+        assert_eq!(
+            cols,
+            vec![
+                Column::Id,
+                Column::Field("status".into()),
+                Column::Cell {
+                    section: "Action Items".into(),
+                    row: 0,
+                    column: "Status".into()
+                },
+                Column::Computed("words".into()),
+                Column::Field("a,b".into()),
+            ]
+        );
+        assert_eq!(cols[2].name(), "Action Items.0.Status");
+    }
+
+    #[test]
+    fn test_project_and_csv() {
+        let doc = Document::from_str(
+            "---\ntype: inc\ntitle: Outage, again\ntags: [db, ops]\n---\n\n# Action Items\n\n| Action | Status |\n|---|---|\n| Retry | open |\n",
+        )
+        .unwrap();
+        let columns = parse_columns(r#"id,title,tags,"Action Items.0.Status","Action Items.5.Status",owner"#);
+        let row = project(&doc, Path::new("docs/inc-004.md"), &columns, None);
+        assert_eq!(row[0], "INC-004");
+        assert_eq!(row[3], "open");
+        assert_eq!(row[4], Value::Null);
+
+        let headers: Vec<String> = columns.iter().map(Column::name).collect();
+        let csv = to_delimited(&headers, &[row.clone()], ',');
+        assert_eq!(
+            csv,
+            "id,title,tags,Action Items.0.Status,Action Items.5.Status,owner\r\nINC-004,\"Outage, again\",db; ops,open,,\r\n"
+        );
+        let tsv = to_delimited(&headers, &[row], '\t');
+        assert!(tsv.ends_with("INC-004\tOutage, again\tdb; ops\topen\t\t\n"));
+    }
+}
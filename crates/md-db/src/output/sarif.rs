@@ -0,0 +1,241 @@
+//! SARIF 2.1.0 output for validation results, for GitHub code scanning and other
+//! SARIF consumers.
+//!
+//! Each diagnostic code (`F010`, `S010`, ...) is a rule. Regions come from the
+//! diagnostic's [`Span`](crate::span::Span); diagnostics without one point at line 1.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use serde_json::{json, Value};
+
+use crate::document::Document;
+use crate::validation::{Severity, ValidationResult};
+
+pub const SARIF_VERSION: &str = "2.1.0";
+pub const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+
+/// Short description for each diagnostic code, shown by SARIF viewers.
+const RULES: &[(&str, &str)] = &[
+    ("A010", "Broken asset link"),
+    ("A011", "Orphaned asset"),
+    ("E000", "File could not be parsed"),
+    ("F000", "Missing frontmatter"),
+    ("F001", "Missing document type"),
+    ("F002", "Unknown document type"),
+    ("F010", "Missing required field"),
+    ("F011", "Renamed field"),
+    ("F020", "Type mismatch"),
+    ("F021", "Invalid enum value"),
+    ("F030", "Pattern mismatch"),
+    ("F031", "Date out of range"),
+    ("F040", "Invalid uid"),
+    ("F041", "Duplicate uid"),
+    ("F050", "Promoted field drift"),
+    ("F060", "Inconsistent with linked document"),
+    ("H001", "Raw HTML removed from export"),
+    ("I001", "Duplicate document ID"),
+    ("I002", "Duplicate title within a type"),
+    ("Q001", "Invalid query block"),
+    ("R001", "Bad ref format"),
+    ("R010", "Broken file ref"),
+    ("R011", "Unresolved ID"),
+    ("R012", "Wrong target type"),
+    ("R020", "Duplicate relation target"),
+    ("R021", "Array on a cardinality-one relation"),
+    ("R022", "Relation and inverse disagree"),
+    ("R030", "Missing inverse ref"),
+    ("R031", "Stale inverse ref"),
+    ("S000", "Invalid schema pattern"),
+    ("S010", "Missing section"),
+    ("S020", "Missing table"),
+    ("S021", "Missing column"),
+    ("S022", "Empty required table cell"),
+    ("S023", "Table rows out of order"),
+    ("S030", "Too few paragraphs"),
+    ("S031", "Missing list"),
+    ("S032", "Missing diagram"),
+    ("S033", "Diagram syntax error"),
+    ("S040", "Too few repeated sections"),
+    ("S041", "Too many repeated sections"),
+    ("S042", "Repeated sections out of order"),
+    ("S050", "Heading anchor collision"),
+    ("P001", "Long sentences"),
+    ("P002", "Passive voice"),
+    ("P003", "Low readability"),
+    ("P004", "No decision verb"),
+    ("L001", "Heading case"),
+    ("L002", "Line too long"),
+    ("L003", "Table trailing whitespace"),
+    ("L004", "Duplicate heading"),
+    ("L005", "Empty section"),
+    ("L006", "Frontmatter key order"),
+    ("T010", "Too many documents of a type"),
+    ("T020", "Missing singleton document"),
+    ("U010", "Invalid user format"),
+    ("U011", "Unknown user or team"),
+    ("U012", "Unknown mention in body"),
+];
+
+/// Short description of a diagnostic code, if it is a known rule.
+pub fn rule_description(code: &str) -> Option<&'static str> {
+    RULES.iter().find(|(c, _)| *c == code).map(|(_, d)| *d)
+}
+
+fn level(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Error => "error",
+        Severity::Warning => "warning",
+    }
+}
+
+/// Build a SARIF log for `result`. File paths are reported relative to `root`
+/// when they are under it, with `/` separators, as code scanning expects
+/// repository-relative URIs.
+pub fn to_sarif(result: &ValidationResult, root: Option<&Path>) -> Value {
+    // Rules in code order; rule indices refer to this order
+    let mut rules: BTreeMap<&str, Severity> = BTreeMap::new();
+    for fr in &result.file_results {
+        for d in &fr.diagnostics {
+            rules.entry(d.code.as_str()).or_insert(d.severity);
+        }
+    }
+    let rule_index: BTreeMap<&str, usize> = rules.keys().enumerate().map(|(i, c)| (*c, i)).collect();
+
+    let mut results = Vec::new();
+    for fr in &result.file_results {
+        if fr.diagnostics.is_empty() {
+            continue;
+        }
+        // Diagnostics built outside validation may lack a span
+        let doc = if fr.diagnostics.iter().all(|d| d.span.is_some()) {
+            None
+        } else {
+            Document::from_file(&fr.path).ok()
+        };
+        let uri = artifact_uri(&fr.path, root);
+        for d in &fr.diagnostics {
+            let span = d
+                .span
+                .or_else(|| doc.as_ref().and_then(|doc| crate::span::locate(doc, &d.location)));
+            let region = match span {
+                Some(s) => json!({
+                    "startLine": s.line,
+                    "startColumn": s.column,
+                    "endLine": s.end_line,
+                    "endColumn": s.end_column,
+                }),
+                None => json!({ "startLine": 1 }),
+            };
+            let text = match &d.hint {
+                Some(hint) => format!("{}\nhint: {hint}", d.message),
+                None => d.message.clone(),
+            };
+            results.push(json!({
+                "ruleId": d.code,
+                "ruleIndex": rule_index[d.code.as_str()],
+                "level": level(d.severity),
+                "message": { "text": text },
+                "locations": [{
+                    "physicalLocation": {
+                        "artifactLocation": { "uri": uri },
+                        "region": region,
+                    },
+                    "logicalLocations": [{ "name": d.location }],
+                }],
+            }));
+        }
+    }
+
+    let rules: Vec<Value> = rules
+        .iter()
+        .map(|(code, severity)| {
+            let mut rule = json!({
+                "id": code,
+                "defaultConfiguration": { "level": level(*severity) },
+            });
+            if let Some(desc) = rule_description(code) {
+                rule["shortDescription"] = json!({ "text": desc });
+            }
+            rule
+        })
+        .collect();
+
+    json!({
+        "$schema": SARIF_SCHEMA,
+        "version": SARIF_VERSION,
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": "md-db",
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "informationUri": "https://github.com/decisiongraph/md-db-rs",
+                    "rules": rules,
+                }
+            },
+            "results": results,
+        }]
+    })
+}
+
+fn artifact_uri(path: &str, root: Option<&Path>) -> String {
+    let p = Path::new(path);
+    let rel = root.and_then(|r| p.strip_prefix(r).ok()).unwrap_or(p);
+    let uri = rel.to_string_lossy().replace('\\', "/");
+    uri.strip_prefix("./").map(str::to_string).unwrap_or(uri)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::validation::{Diagnostic, FileResult};
+
+    #[test]
+    fn test_to_sarif() {
+        let result = ValidationResult {
+            file_results: vec![
+                FileResult {
+                    path: "./docs/missing.md".into(),
+                    diagnostics: vec![
+                        Diagnostic {
+                            severity: Severity::Warning,
+                            code: "U012".into(),
+                            message: "mention \"@bob\" is not a known user or team".into(),
+                            location: "body:3".into(),
+                            hint: None,
+                            span: None,
+                        },
+                        Diagnostic {
+                            severity: Severity::Error,
+                            code: "F010".into(),
+                            message: "missing required field \"date\"".into(),
+                            location: "frontmatter".into(),
+                            hint: Some("add date".into()),
+                            span: None,
+                        },
+                    ],
+                },
+                FileResult { path: "docs/ok.md".into(), diagnostics: vec![] },
+            ],
+        };
+        let sarif = to_sarif(&result, None);
+        assert_eq!(sarif["version"], "2.1.0");
+        let run = &sarif["runs"][0];
+        let rules = run["tool"]["driver"]["rules"].as_array().unwrap();
+        assert_eq!(rules.len(), 2);
+        assert_eq!(rules[0]["id"], "F010");
+        assert_eq!(rules[0]["shortDescription"]["text"], "Missing required field");
+        assert_eq!(rules[1]["defaultConfiguration"]["level"], "warning");
+
+        let results = run["results"].as_array().unwrap();
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0]["ruleId"], "U012");
+        assert_eq!(results[0]["ruleIndex"], 1);
+        assert_eq!(results[1]["level"], "error");
+        assert_eq!(results[1]["message"]["text"], "missing required field \"date\"\nhint: add date");
+        let loc = &results[0]["locations"][0]["physicalLocation"];
+        assert_eq!(loc["artifactLocation"]["uri"], "docs/missing.md");
+        // Unreadable file: no line to resolve
+        assert_eq!(loc["region"]["startLine"], 1);
+    }
+}
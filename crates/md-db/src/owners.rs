@@ -0,0 +1,169 @@
+//! Who owns and who is mentioned in which documents.
+//!
+//! Owners come from the `user`/`user[]` fields the schema declares for each type
+//! (`author`, `owner`, `reviewers`, ...). Mentions are `@handle` references in the
+//! body, found with [`crate::users::find_mentions`].
+
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+
+use crate::document::Document;
+use crate::error::Result;
+use crate::schema::{FieldType, Schema};
+
+/// A user or team named in a `user`/`user[]` field.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct OwnerEntry {
+    pub handle: String,
+    pub id: String,
+    pub path: PathBuf,
+    pub field: String,
+}
+
+/// An `@mention` in a document body.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MentionEntry {
+    pub handle: String,
+    pub id: String,
+    pub path: PathBuf,
+    /// 1-based line within the body.
+    pub line: usize,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct OwnersIndex {
+    pub owners: Vec<OwnerEntry>,
+    pub mentions: Vec<MentionEntry>,
+}
+
+impl OwnersIndex {
+    /// Index every markdown file under `dir`. Files that fail to parse are skipped.
+    pub fn build(dir: impl AsRef<Path>, schema: &Schema) -> Result<Self> {
+        let files = crate::discovery::discover_files(&dir, None, &[], false)?;
+        let mut index = Self::default();
+        for path in &files {
+            if let Ok(doc) = Document::from_file(path) {
+                index.add(path, &doc, schema);
+            }
+        }
+        Ok(index)
+    }
+
+    /// Add one document's owners and mentions.
+    pub fn add(&mut self, path: &Path, doc: &Document, schema: &Schema) {
+        let id = crate::graph::path_to_id(path);
+
+        let type_def = doc
+            .frontmatter
+            .as_ref()
+            .and_then(|fm| fm.get_display("type"))
+            .and_then(|t| schema.get_type(&t));
+        if let (Some(type_def), Some(fm)) = (type_def, doc.frontmatter.as_ref()) {
+            for field in &type_def.fields {
+                if !matches!(field.field_type, FieldType::User | FieldType::UserArray) {
+                    continue;
+                }
+                let handles: Vec<String> = match fm.get(&field.name) {
+                    Some(serde_yaml::Value::String(s)) => vec![s.clone()],
+                    Some(serde_yaml::Value::Sequence(seq)) => {
+                        seq.iter().filter_map(|v| v.as_str().map(str::to_string)).collect()
+                    }
+                    _ => vec![],
+                };
+                for handle in handles {
+                    self.owners.push(OwnerEntry {
+                        handle,
+                        id: id.clone(),
+                        path: path.to_path_buf(),
+                        field: field.name.clone(),
+                    });
+                }
+            }
+        }
+
+        for mention in crate::users::find_mentions(&doc.body) {
+            self.mentions.push(MentionEntry {
+                handle: mention.handle,
+                id: id.clone(),
+                path: path.to_path_buf(),
+                line: mention.line,
+            });
+        }
+    }
+
+    /// Owner entries for `handle` (with or without the leading `@`).
+    pub fn owned_by(&self, handle: &str) -> Vec<&OwnerEntry> {
+        let handle = normalize_handle(handle);
+        self.owners.iter().filter(|o| o.handle.eq_ignore_ascii_case(&handle)).collect()
+    }
+
+    /// Mentions of `handle` (with or without the leading `@`).
+    pub fn mentioning(&self, handle: &str) -> Vec<&MentionEntry> {
+        let handle = normalize_handle(handle);
+        self.mentions.iter().filter(|m| m.handle.eq_ignore_ascii_case(&handle)).collect()
+    }
+
+    /// Every handle with its owned-document and mention counts, sorted by handle.
+    pub fn summary(&self) -> Vec<(String, usize, usize)> {
+        let mut counts: std::collections::BTreeMap<String, (usize, usize)> = Default::default();
+        for o in &self.owners {
+            counts.entry(o.handle.to_lowercase()).or_default().0 += 1;
+        }
+        for m in &self.mentions {
+            counts.entry(m.handle.to_lowercase()).or_default().1 += 1;
+        }
+        counts.into_iter().map(|(h, (owned, mentioned))| (h, owned, mentioned)).collect()
+    }
+}
+
+fn normalize_handle(handle: &str) -> String {
+    if handle.starts_with('@') {
+        handle.to_string()
+    } else {
+        format!("@{handle}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_owners_and_mentions() {
+        let schema = Schema::from_str(
+            r#"
+type "adr" {
+    field "author" type="user"
+    field "reviewers" type="user[]"
+    field "title" type="string"
+}
+"#,
+        )
+        .unwrap();
+        let doc = Document::from_str(
+            "---\ntype: adr\ntitle: \"@notanowner\"\nauthor: \"@alice\"\nreviewers: [\"@bob\", \"@team/platform\"]\n---\n\nPing @Alice and @carol.\n",
+        )
+        .unwrap();
+        let mut index = OwnersIndex::default();
+        index.add(Path::new("docs/adr-001.md"), &doc, &schema);
+
+        let owned: Vec<_> = index.owned_by("alice").iter().map(|o| (o.id.as_str(), o.field.as_str())).collect();
+        assert_eq!(owned, vec![("ADR-001", "author")]);
+        assert_eq!(index.owned_by("@team/platform")[0].field, "reviewers");
+        assert!(index.owned_by("@notanowner").is_empty());
+
+        let mentions = index.mentioning("@alice");
+        assert_eq!(mentions.len(), 1);
+        assert_eq!(mentions[0].handle, "@Alice");
+        assert_eq!(
+            index.summary(),
+            vec![
+                ("@alice".to_string(), 1, 1),
+                ("@bob".to_string(), 1, 0),
+                ("@carol".to_string(), 0, 1),
+                ("@team/platform".to_string(), 1, 0),
+            ]
+        );
+    }
+}
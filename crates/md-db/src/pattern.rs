@@ -0,0 +1,108 @@
+//! Schema regexes, compiled once and bounded.
+//!
+//! Field `pattern`s and `ref-format`s come from user schemas and run against every
+//! document. Each [`Schema`](crate::schema::Schema) compiles them once into a
+//! [`PatternCache`], with limits on pattern length, nesting depth, and compiled
+//! size. A pattern over a limit is rejected and reported as `S000` rather than
+//! compiled again for every value.
+//!
+//! The regex engine has no backtracking: a match runs in time linear in the input
+//! for a compiled program of bounded size. Capping the input at [`MAX_INPUT_LEN`]
+//! therefore bounds the time of every match, which is what a per-match timeout
+//! would otherwise have to do.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use regex::{Regex, RegexBuilder};
+
+/// Longest pattern source accepted, in bytes.
+pub const MAX_PATTERN_LEN: usize = 1000;
+/// Deepest nesting of groups and repetitions accepted.
+pub const MAX_NESTING: u32 = 32;
+/// Largest compiled program accepted, in bytes.
+pub const MAX_COMPILED_SIZE: usize = 1 << 20;
+/// Lazy DFA cache per regex, in bytes.
+const DFA_SIZE: usize = 2 << 20;
+/// Longest value a pattern is matched against, in bytes.
+pub const MAX_INPUT_LEN: usize = 16 * 1024;
+
+/// A schema pattern that failed to compile or exceeded a limit.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RejectedPattern {
+    /// Where the schema declares it, e.g. `field "id" of type "adr"`.
+    pub source: String,
+    pub pattern: String,
+    pub reason: String,
+}
+
+/// Compile `pattern` within the limits. The error says why it was rejected.
+pub fn compile(pattern: &str) -> Result<Regex, String> {
+    if pattern.len() > MAX_PATTERN_LEN {
+        return Err(format!("pattern is {} bytes (max {MAX_PATTERN_LEN})", pattern.len()));
+    }
+    RegexBuilder::new(pattern)
+        .size_limit(MAX_COMPILED_SIZE)
+        .dfa_size_limit(DFA_SIZE)
+        .nest_limit(MAX_NESTING)
+        .build()
+        .map_err(|e| match e {
+            regex::Error::CompiledTooBig(limit) => {
+                format!("pattern compiles to more than {limit} bytes; reduce repetition counts")
+            }
+            e => e.to_string(),
+        })
+}
+
+/// Compiled patterns keyed by source. Clones share the cache; a key always maps
+/// to the same result, so sharing is safe.
+#[derive(Debug, Clone, Default)]
+pub struct PatternCache {
+    compiled: Arc<Mutex<HashMap<String, Result<Regex, String>>>>,
+}
+
+impl PatternCache {
+    /// The compiled pattern, compiling it on first use.
+    pub fn get(&self, pattern: &str) -> Result<Regex, String> {
+        let mut compiled = self.compiled.lock().unwrap_or_else(|e| e.into_inner());
+        compiled
+            .entry(pattern.to_string())
+            .or_insert_with(|| compile(pattern))
+            .clone()
+    }
+
+    /// Match `value` against `pattern`. `Err` if the pattern is rejected or the
+    /// value is longer than [`MAX_INPUT_LEN`].
+    pub fn is_match(&self, pattern: &str, value: &str) -> Result<bool, String> {
+        let re = self.get(pattern)?;
+        if value.len() > MAX_INPUT_LEN {
+            return Err(format!("value is {} bytes, longer than the {MAX_INPUT_LEN} matched", value.len()));
+        }
+        Ok(re.is_match(value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_limits() {
+        assert!(compile(r"^ADR-\d{3}$").is_ok());
+        assert!(compile(&"a".repeat(MAX_PATTERN_LEN + 1)).unwrap_err().contains("bytes (max"));
+        assert!(compile("(a{1000}){1000}").unwrap_err().contains("reduce repetition"));
+        let nested = format!("{}a{}", "(".repeat(40), ")".repeat(40));
+        assert!(compile(&nested).is_err());
+        assert!(compile("(").is_err());
+    }
+
+    #[test]
+    fn test_cache() {
+        let cache = PatternCache::default();
+        assert_eq!(cache.is_match(r"^\d+$", "42"), Ok(true));
+        assert_eq!(cache.clone().is_match(r"^\d+$", "x"), Ok(false));
+        assert_eq!(cache.compiled.lock().unwrap().len(), 1);
+        assert!(cache.is_match(".*", &"x".repeat(MAX_INPUT_LEN + 1)).is_err());
+        assert!(cache.is_match("(a{1000}){1000}", "a").is_err());
+    }
+}
@@ -0,0 +1,296 @@
+//! Permalinks for exported pages.
+//!
+//! A `permalink` template, top-level or inside a `type`, sets where each
+//! document's page lives on the exported site:
+//!
+//! ```kdl
+//! permalink "/docs/{id}.html"
+//! type "adr" {
+//!     permalink "/decisions/{year}/{slug}/"
+//! }
+//! ```
+//!
+//! Placeholders are `{id}`, `{slug}` (the title), `{year}` (of `date`), and any
+//! frontmatter field such as `{type}`; every value is slugified. A document's own
+//! `permalink` field wins over the template, and without either the page stays at
+//! `/{id}.html`. A URL ending in `/` is written as that directory's `index.html`.
+//!
+//! The export records each document's URL in `permalinks.json`, keyed by uid (or
+//! ID when the document has none). When a later export finds a URL changed — a
+//! new template, a renamed document — the old URL gets a redirect page, and keeps
+//! it on every export after that.
+
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::document::Document;
+use crate::error::{Error, Result};
+use crate::frontmatter::Frontmatter;
+use crate::schema::Schema;
+
+/// Frontmatter field holding a document's stored permalink.
+pub const FIELD: &str = "permalink";
+
+/// Record of published URLs, written next to the exported pages.
+pub const MANIFEST_FILE: &str = "permalinks.json";
+
+/// Pages the export writes itself; no permalink may take their place.
+pub const RESERVED: &[&str] = &[
+    "/",
+    "/index.html",
+    "/schema.html",
+    "/permalinks.json",
+    "/search.html",
+    "/search-index.js",
+    "/graph.html",
+];
+
+/// Directory of the per-type listing pages.
+pub const TYPE_PAGES: &str = "/types/";
+
+/// Directories the export fills itself (type pages, theme files); no permalink may point into them.
+pub const RESERVED_DIRS: &[&str] = &[TYPE_PAGES, "/theme/"];
+
+/// URL of a page without a permalink template.
+pub fn default_url(id: &str) -> String {
+    format!("/{}.html", id.to_lowercase())
+}
+
+/// The document's URL: its `permalink` field, else its type's (or the schema's)
+/// template, else [`default_url`].
+pub fn permalink(id: &str, doc: &Document, schema: Option<&Schema>) -> String {
+    let fm = doc.frontmatter.as_ref();
+    if let Some(stored) = fm.and_then(|fm| fm.get_display(FIELD)).filter(|s| !s.trim().is_empty()) {
+        let stored = stored.trim();
+        return if stored.starts_with('/') { stored.to_string() } else { format!("/{stored}") };
+    }
+    let template = schema.and_then(|s| {
+        fm.and_then(|fm| fm.get_display("type"))
+            .and_then(|t| s.get_type(&t))
+            .and_then(|t| t.permalink.as_deref())
+            .or(s.permalink.as_deref())
+    });
+    match template {
+        Some(template) => expand(template, id, fm),
+        None => default_url(id),
+    }
+}
+
+/// Fill a template's placeholders for one document.
+pub fn expand(template: &str, id: &str, fm: Option<&Frontmatter>) -> String {
+    let placeholder = Regex::new(r"\{([A-Za-z0-9_.-]+)\}").unwrap();
+    let url = placeholder.replace_all(template, |caps: &regex::Captures| {
+        let value = match &caps[1] {
+            "id" => id.to_string(),
+            "slug" => fm.and_then(|fm| fm.get_display("title")).unwrap_or_else(|| id.to_string()),
+            "year" => fm
+                .and_then(|fm| fm.get_display("date"))
+                .map(|d| d.chars().take(4).collect())
+                .unwrap_or_default(),
+            field => fm.and_then(|fm| fm.get_display(field)).unwrap_or_default(),
+        };
+        slugify(&value)
+    });
+    // A placeholder with no value leaves an empty path segment
+    let mut out = String::with_capacity(url.len());
+    for c in url.chars() {
+        if !(c == '/' && out.ends_with('/')) {
+            out.push(c);
+        }
+    }
+    out
+}
+
+/// Lowercase, with every run of characters other than letters and digits as one `-`.
+pub fn slugify(s: &str) -> String {
+    let mut out = String::new();
+    for c in s.to_lowercase().chars() {
+        if c.is_alphanumeric() {
+            out.push(c);
+        } else if !out.is_empty() && !out.ends_with('-') {
+            out.push('-');
+        }
+    }
+    out.trim_end_matches('-').to_string()
+}
+
+/// File under the output directory that serves `url`.
+pub fn output_file(url: &str) -> PathBuf {
+    let path = url.trim_start_matches('/');
+    if path.is_empty() || path.ends_with('/') {
+        PathBuf::from(format!("{path}index.html"))
+    } else {
+        PathBuf::from(path)
+    }
+}
+
+/// Link from the page at `from` to the page at `to`, relative so the site works
+/// under any base path.
+pub fn relative_href(from: &str, to: &str) -> String {
+    let depth = from.trim_start_matches('/').matches('/').count();
+    let href = format!("{}{}", "../".repeat(depth), to.trim_start_matches('/'));
+    if href.is_empty() {
+        "./".to_string()
+    } else {
+        href
+    }
+}
+
+/// Check that no two documents, and no document and a built-in page, share a URL.
+/// `urls` maps document ID to URL.
+pub fn check_unique(urls: &BTreeMap<String, String>) -> Result<()> {
+    let mut seen: BTreeMap<PathBuf, &str> = RESERVED.iter().map(|u| (output_file(u), "the export itself")).collect();
+    for (id, url) in urls {
+        if url.split('/').any(|part| part == "..") {
+            return Err(Error::Export(format!("permalink \"{url}\" of {id} must not contain '..'")));
+        }
+        if let Some(dir) = RESERVED_DIRS.iter().find(|dir| url.starts_with(*dir)) {
+            let message = format!("permalink \"{url}\" of {id} is inside {dir}, which the export writes itself");
+            return Err(Error::Export(message));
+        }
+        if let Some(other) = seen.insert(output_file(url), id) {
+            return Err(Error::Export(format!("permalink \"{url}\" of {id} is already used by {other}")));
+        }
+    }
+    Ok(())
+}
+
+/// Published URLs and the redirects kept for URLs that moved.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Manifest {
+    /// Current URL per document, keyed by uid or ID.
+    pub documents: BTreeMap<String, String>,
+    /// Old URL to the current URL it redirects to.
+    pub redirects: BTreeMap<String, String>,
+}
+
+impl Manifest {
+    /// The manifest of a previous export into `output_dir`; empty when there is none.
+    pub fn load(output_dir: &Path) -> Self {
+        std::fs::read_to_string(output_dir.join(MANIFEST_FILE))
+            .ok()
+            .and_then(|s| serde_json::from_str(&s).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self, output_dir: &Path) -> Result<()> {
+        let path = output_dir.join(MANIFEST_FILE);
+        let json = serde_json::to_string_pretty(self)? + "\n";
+        if std::fs::read_to_string(&path).is_ok_and(|old| old == json) {
+            return Ok(());
+        }
+        crate::durability::write(&path, json)
+    }
+
+    /// Replace the published URLs with `current` (key to URL). Every URL that
+    /// moved becomes a redirect; older redirects follow it to the newest URL, and
+    /// redirects to URLs that no longer exist, or from URLs in use again, are
+    /// dropped. Returns the redirects added, old URL first.
+    pub fn update(&mut self, current: BTreeMap<String, String>) -> Vec<(String, String)> {
+        let mut added = Vec::new();
+        for (key, url) in &current {
+            if let Some(old) = self.documents.get(key).filter(|old| *old != url) {
+                self.redirects.insert(old.clone(), url.clone());
+                added.push((old.clone(), url.clone()));
+            }
+        }
+
+        let live: HashSet<&str> = current.values().map(String::as_str).collect();
+        let moved = self.redirects.clone();
+        for target in self.redirects.values_mut() {
+            // Follow A -> B -> C to C; a cycle stops after one lap
+            for _ in 0..moved.len() {
+                match moved.get(target.as_str()) {
+                    Some(next) if !live.contains(target.as_str()) => *target = next.clone(),
+                    _ => break,
+                }
+            }
+        }
+        self.redirects.retain(|old, target| !live.contains(old.as_str()) && live.contains(target.as_str()));
+        added.retain(|(old, _)| self.redirects.contains_key(old));
+        self.documents = current;
+        added
+    }
+}
+
+/// Key a document is tracked by in the manifest: its uid, else its ID.
+pub fn manifest_key(id: &str, doc: &Document) -> String {
+    doc.frontmatter
+        .as_ref()
+        .and_then(|fm| fm.get_display(crate::uid::UID_FIELD))
+        .unwrap_or_else(|| id.to_string())
+}
+
+/// A page that sends the browser on to `href`.
+pub fn redirect_page(href: &str) -> String {
+    let href = htmlescape::encode_minimal(href);
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>Redirecting…</title>\n\
+         <link rel=\"canonical\" href=\"{href}\">\n<meta http-equiv=\"refresh\" content=\"0; url={href}\">\n\
+         </head>\n<body>\n<p>Moved to <a href=\"{href}\">{href}</a>.</p>\n</body>\n</html>\n"
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_permalink() {
+        let schema = Schema::from_str(
+            "permalink \"/docs/{id}.html\"\ntype \"adr\" {\n    permalink \"/decisions/{year}/{slug}/\"\n}\n\
+             type \"inc\" {\n    field \"title\"\n}\n",
+        )
+        .unwrap();
+        let doc = |fm: &str| Document::from_str(&format!("---\n{fm}\n---\n")).unwrap();
+
+        let adr = doc("type: adr\ntitle: Use Kafka (v2)!\ndate: 2024-03-05");
+        assert_eq!(permalink("ADR-001", &adr, Some(&schema)), "/decisions/2024/use-kafka-v2/");
+        let undated = doc("type: adr\ntitle: Use Kafka");
+        assert_eq!(permalink("ADR-002", &undated, Some(&schema)), "/decisions/use-kafka/");
+        let inc = doc("type: inc\ntitle: Outage");
+        assert_eq!(permalink("INC-001", &inc, Some(&schema)), "/docs/inc-001.html");
+        let stored = doc("type: adr\npermalink: kafka/");
+        assert_eq!(permalink("ADR-003", &stored, Some(&schema)), "/kafka/");
+        assert_eq!(permalink("ADR-004", &inc, None), "/adr-004.html");
+
+        assert_eq!(output_file("/decisions/use-kafka/"), PathBuf::from("decisions/use-kafka/index.html"));
+        assert_eq!(output_file("/adr-001.html"), PathBuf::from("adr-001.html"));
+        assert_eq!(relative_href("/decisions/use-kafka/", "/adr-001.html"), "../../adr-001.html");
+        assert_eq!(relative_href("/adr-001.html", "/decisions/use-kafka/"), "decisions/use-kafka/");
+        assert_eq!(relative_href("/docs/a.html", "/"), "../");
+
+        assert!(Schema::from_str("permalink \"docs/{id}\"").is_err());
+        let clash: BTreeMap<String, String> =
+            [("ADR-001".to_string(), "/a/".to_string()), ("ADR-002".to_string(), "/a/index.html".to_string())].into();
+        assert!(check_unique(&clash).unwrap_err().to_string().contains("already used by ADR-001"));
+        let index: BTreeMap<String, String> = [("ADR-001".to_string(), "/".to_string())].into();
+        assert!(check_unique(&index).is_err());
+        let listing: BTreeMap<String, String> = [("ADR-001".to_string(), "/types/adr.html".to_string())].into();
+        assert!(check_unique(&listing).is_err());
+    }
+
+    #[test]
+    fn test_manifest_update() {
+        let urls = |pairs: &[(&str, &str)]| -> BTreeMap<String, String> {
+            pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+        };
+        let mut manifest = Manifest::default();
+        assert!(manifest.update(urls(&[("a", "/a.html"), ("b", "/b.html")])).is_empty());
+
+        let added = manifest.update(urls(&[("a", "/x/a/"), ("b", "/b.html")]));
+        assert_eq!(added, vec![("/a.html".to_string(), "/x/a/".to_string())]);
+
+        // A second move keeps the first URL working, pointed at the newest one
+        manifest.update(urls(&[("a", "/y/a/"), ("b", "/b.html")]));
+        assert_eq!(manifest.redirects, urls(&[("/a.html", "/y/a/"), ("/x/a/", "/y/a/")]));
+
+        // Deleted documents take their redirects with them; a reused URL is a page again
+        manifest.update(urls(&[("b", "/a.html")]));
+        assert_eq!(manifest.redirects, urls(&[("/b.html", "/a.html")]));
+        assert_eq!(manifest.documents, urls(&[("b", "/a.html")]));
+    }
+}
@@ -0,0 +1,292 @@
+//! Post-processing steps for `md-db new`, declared in the project manifest:
+//!
+//! ```kdl
+//! on-new {
+//!     step "defaults"
+//!     step "fix"
+//!     step "toc"
+//!     step "inverse"
+//!     step "git-add"
+//!     step "editor"
+//! }
+//! ```
+//!
+//! Content steps (`defaults`, `fix`, `toc`) rewrite the generated text before it
+//! is written; file steps (`inverse`, `git-add`, `editor`) run on the written
+//! file afterwards. Each group keeps its declared order.
+
+use std::path::Path;
+
+use kdl::KdlNode;
+use serde_yaml::Value;
+
+use crate::document::Document;
+use crate::error::{Error, Result};
+use crate::graph::DocGraph;
+use crate::schema::{get_string_arg, Schema};
+use crate::sync::SyncPlan;
+
+/// Name of the marker block the `toc` step writes.
+pub const TOC_BLOCK: &str = "toc";
+
+/// One step of the `on-new` pipeline.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NewStep {
+    /// Replace placeholder field values (`YYYY-MM-DD`, `@`, empty) with the schema default.
+    Defaults,
+    /// Apply the `md-db fix` patches the new document needs.
+    Fix,
+    /// Insert a table of contents between `<!-- md-db:toc -->` markers.
+    Toc,
+    /// Add the inverse ref to each document the new one links to.
+    Inverse,
+    /// Stage the written files with `git add`.
+    GitAdd,
+    /// Open the new document in `$VISUAL` / `$EDITOR`.
+    Editor,
+}
+
+impl NewStep {
+    pub const ALL: [NewStep; 6] = [
+        NewStep::Defaults,
+        NewStep::Fix,
+        NewStep::Toc,
+        NewStep::Inverse,
+        NewStep::GitAdd,
+        NewStep::Editor,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            NewStep::Defaults => "defaults",
+            NewStep::Fix => "fix",
+            NewStep::Toc => "toc",
+            NewStep::Inverse => "inverse",
+            NewStep::GitAdd => "git-add",
+            NewStep::Editor => "editor",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|s| s.name() == name)
+    }
+
+    /// Whether the step rewrites the generated text rather than working on files.
+    pub fn edits_content(self) -> bool {
+        matches!(self, NewStep::Defaults | NewStep::Fix | NewStep::Toc)
+    }
+}
+
+/// Parse an `on-new` node's `step` children.
+pub(crate) fn parse_on_new(node: &KdlNode) -> Result<Vec<NewStep>> {
+    let mut steps = Vec::new();
+    for child in node.children().map(|c| c.nodes()).unwrap_or_default() {
+        if child.name().value() != "step" {
+            return Err(Error::Manifest(format!(
+                "unknown node '{}' in on-new (expected step)",
+                child.name().value()
+            )));
+        }
+        let name = get_string_arg(child).ok_or_else(|| Error::Manifest("step node missing name argument".into()))?;
+        let step = NewStep::from_name(&name).ok_or_else(|| {
+            let known: Vec<&str> = NewStep::ALL.iter().map(|s| s.name()).collect();
+            Error::Manifest(format!("unknown on-new step '{name}' (expected: {})", known.join(", ")))
+        })?;
+        steps.push(step);
+    }
+    Ok(steps)
+}
+
+/// Run the content steps among `steps` over a generated document, in order.
+/// Returns the new text and one line per step that changed something.
+pub fn apply_content_steps(content: &str, steps: &[NewStep], schema: &Schema) -> Result<(String, Vec<String>)> {
+    let mut doc = Document::from_str(content)?;
+    let mut notes = Vec::new();
+    for step in steps.iter().filter(|s| s.edits_content()) {
+        let note = match step {
+            NewStep::Defaults => {
+                let filled = fill_defaults(&mut doc, schema);
+                (!filled.is_empty()).then(|| format!("defaults: filled {}", filled.join(", ")))
+            }
+            NewStep::Fix => {
+                let fixed = fix(&mut doc, schema);
+                (fixed > 0).then(|| format!("fix: applied {fixed} patch(es)"))
+            }
+            NewStep::Toc => insert_toc(&mut doc)?.then(|| "toc: inserted table of contents".to_string()),
+            _ => None,
+        };
+        notes.extend(note);
+        doc = Document::from_str(&doc.raw)?;
+    }
+    Ok((doc.raw, notes))
+}
+
+/// Replace placeholder values of the type's fields with their schema default.
+/// Returns the names of the fields filled.
+pub fn fill_defaults(doc: &mut Document, schema: &Schema) -> Vec<String> {
+    let Some(type_def) = doc
+        .frontmatter
+        .as_ref()
+        .and_then(|fm| fm.get_display("type"))
+        .and_then(|t| schema.get_type(&t))
+    else {
+        return Vec::new();
+    };
+    let mut filled = Vec::new();
+    for field in &type_def.fields {
+        let placeholder = match doc.frontmatter.as_ref().and_then(|fm| fm.get(&field.name)) {
+            None | Some(Value::Null) => true,
+            Some(Value::String(s)) => s.is_empty() || s == "@" || s.starts_with("YYYY-MM-DD"),
+            _ => false,
+        };
+        if !placeholder {
+            continue;
+        }
+        if let Some(value) = crate::template::field_default_string(field) {
+            doc.set_field_from_str(&field.name, &value);
+            filled.push(field.name.clone());
+        }
+    }
+    filled
+}
+
+/// Apply the built-in fixes for the document's own diagnostics. Returns the
+/// number of patches applied.
+pub fn fix(doc: &mut Document, schema: &Schema) -> usize {
+    let empty = std::collections::HashSet::new();
+    let result = crate::validation::validate_document(doc, schema, &empty, &empty, None);
+    crate::fix::FixEngine::new()
+        .fix(doc, &result.diagnostics, schema)
+        .iter()
+        .filter(|p| p.is_applicable())
+        .count()
+}
+
+/// Write a table of contents of the body's headings into the `toc` marker
+/// block, inserting the block after a leading title heading (or at the top of
+/// the body) when there is none. Returns whether the text changed.
+pub fn insert_toc(doc: &mut Document) -> Result<bool> {
+    let headings = headings(&doc.body);
+    let title = doc.body.trim_start().starts_with("# ");
+    let listed = if title { &headings[1.min(headings.len())..] } else { &headings[..] };
+    let min_level = listed.iter().map(|(level, _)| *level).min().unwrap_or(1);
+    let toc: String = listed
+        .iter()
+        .map(|(level, text)| {
+            let indent = "  ".repeat((level - min_level) as usize);
+            format!("{indent}- [{text}](#{})\n", crate::ast_util::heading_anchor(text))
+        })
+        .collect();
+
+    let body = match crate::marker::replace_block(&doc.body, TOC_BLOCK, &toc)? {
+        Some(body) => body,
+        None => {
+            let block = format!(
+                "{}\n{toc}{}\n",
+                crate::marker::start_marker(TOC_BLOCK),
+                crate::marker::end_marker(TOC_BLOCK)
+            );
+            let at = if title {
+                let start = doc.body.len() - doc.body.trim_start().len();
+                doc.body[start..].find('\n').map_or(doc.body.len(), |i| start + i + 1)
+            } else {
+                0
+            };
+            let (before, after) = doc.body.split_at(at);
+            let sep = if title { "\n" } else { "" };
+            format!("{before}{sep}{block}\n{}", after.trim_start_matches('\n'))
+        }
+    };
+    if body == doc.body {
+        return Ok(false);
+    }
+    doc.set_body(&body);
+    Ok(true)
+}
+
+/// (level, text) of every heading in `body`, in order.
+fn headings(body: &str) -> Vec<(u8, String)> {
+    let arena = comrak::Arena::new();
+    let root = comrak::parse_document(&arena, body, &crate::ast_util::comrak_opts());
+    crate::ast_util::find_headings(root, None)
+        .into_iter()
+        .map(|node| {
+            let level = crate::ast_util::heading_level(node).unwrap_or(1);
+            (level, crate::ast_util::collect_text(node).trim().to_string())
+        })
+        .collect()
+}
+
+/// The sync actions that add the document at `path` to the inverse fields of
+/// the documents it links to. Other missing inverses in the graph are left alone.
+pub fn inverse_plan(graph: &DocGraph, schema: &Schema, path: &Path) -> SyncPlan {
+    let canonical = |p: &Path| p.canonicalize().unwrap_or_else(|_| p.to_path_buf());
+    let target = canonical(path);
+    let Some(id) = graph.nodes.values().find(|n| canonical(&n.path) == target).map(|n| n.id.clone()) else {
+        return SyncPlan {
+            actions: Vec::new(),
+            warnings: Vec::new(),
+        };
+    };
+    let mut plan = crate::sync::plan_from_graph(graph, schema);
+    plan.actions.retain_mut(|a| {
+        a.add_refs.retain(|r| *r == id);
+        !a.add_refs.is_empty()
+    });
+    plan.warnings.retain(|w| w.ends_with(&format!("cannot add {id}")));
+    plan
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCHEMA: &str = "relation \"enables\" inverse=\"enabled_by\" cardinality=\"many\"\ntype \"adr\" {\n    field \"title\" type=\"string\" required=#true\n    field \"date\" type=\"date\" required=#true\n    field \"status\" type=\"enum\" required=#true {\n        values \"proposed\" \"accepted\"\n    }\n    section \"Context\" required=#true\n    section \"Decision\" required=#true\n}\n";
+
+    #[test]
+    fn test_content_steps() {
+        let schema = Schema::from_str(SCHEMA).unwrap();
+        let content = "---\ntype: adr\ntitle: Use Kafka\ndate: YYYY-MM-DD\nstatus: proposd\n---\n# Use Kafka\n\n## Context\n\n## Decision\n";
+        let steps = [NewStep::Defaults, NewStep::Fix, NewStep::Toc];
+        let (out, notes) = apply_content_steps(content, &steps, &schema).unwrap();
+
+        let doc = Document::from_str(&out).unwrap();
+        let fm = doc.frontmatter().unwrap();
+        assert_eq!(fm.get_display("date"), Some(crate::template::format_today()));
+        assert_eq!(fm.get_display("status").as_deref(), Some("proposed"));
+        assert!(
+            out.contains("# Use Kafka\n\n<!-- md-db:toc -->\n- [Context](#context)\n- [Decision](#decision)\n<!-- /md-db:toc -->\n\n## Context"),
+            "{out}"
+        );
+        assert_eq!(notes.len(), 3, "{notes:?}");
+
+        // Running again changes nothing
+        let (again, notes) = apply_content_steps(&out, &steps, &schema).unwrap();
+        assert_eq!((again, notes.len()), (out, 0));
+    }
+
+    #[test]
+    fn test_inverse_plan_only_touches_new_doc() {
+        let dir = tempfile::tempdir().unwrap();
+        let schema = Schema::from_str(SCHEMA).unwrap();
+        std::fs::write(dir.path().join("adr-001.md"), "---\ntype: adr\nenables: [ADR-002]\n---\n").unwrap();
+        std::fs::write(dir.path().join("adr-002.md"), "---\ntype: adr\n---\n").unwrap();
+        std::fs::write(dir.path().join("adr-003.md"), "---\ntype: adr\nenables: [ADR-002]\n---\n").unwrap();
+
+        let graph = DocGraph::build(dir.path(), &schema).unwrap();
+        let plan = inverse_plan(&graph, &schema, &dir.path().join("adr-003.md"));
+        assert_eq!(plan.actions.len(), 1);
+        let action = &plan.actions[0];
+        assert_eq!((action.doc_id.as_str(), action.add_refs.as_slice()), ("ADR-002", &["ADR-003".to_string()][..]));
+        assert!(inverse_plan(&graph, &schema, &dir.path().join("missing.md")).is_empty());
+    }
+
+    #[test]
+    fn test_parse_on_new() {
+        let doc: kdl::KdlDocument = "on-new {\n    step \"fix\"\n    step \"git-add\"\n}\n".parse().unwrap();
+        assert_eq!(parse_on_new(&doc.nodes()[0]).unwrap(), vec![NewStep::Fix, NewStep::GitAdd]);
+        let bad: kdl::KdlDocument = "on-new { step \"lint\" }".parse().unwrap();
+        let msg = parse_on_new(&bad.nodes()[0]).unwrap_err().to_string();
+        assert!(msg.contains("unknown on-new step 'lint'"), "{msg}");
+    }
+}
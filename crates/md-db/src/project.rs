@@ -0,0 +1,342 @@
+//! Projects: finding the schema from anywhere inside one, and multi-root manifests.
+//!
+//! `--schema auto` (the CLI default) walks up from the target file or directory the
+//! way git looks for `.git`: the closest directory holding a schema wins, so a
+//! nested project shadows the one around it. The walk stops at the repository root
+//! (the first directory with a `.git` entry), so a stray schema above the checkout
+//! is never picked up.
+//!
+//! A directory can instead hold an `md-db.kdl` manifest naming the schema and the
+//! directories (roots) that make up the project:
+//!
+//! ```kdl
+//! schema "schema.kdl"
+//! root "docs"
+//! root "rfcs" pattern="rfc-*.md"
+//! root "security/policies"
+//! ```
+//!
+//! Discovering files in the manifest's directory walks every root, so all of them
+//! share one ID namespace and one document graph. An `on-new` block lists the
+//! steps `md-db new` runs after generating a document (see [`crate::pipeline`]).
+
+use std::path::{Path, PathBuf};
+
+use kdl::KdlDocument;
+
+use crate::discovery::Filter;
+use crate::error::{Error, Result};
+use crate::pipeline::NewStep;
+use crate::schema::{get_string_arg, get_string_prop};
+
+/// `--schema` value that asks for discovery.
+pub const AUTO: &str = "auto";
+
+/// Where a directory can keep its schema, relative to it.
+pub const SCHEMA_CANDIDATES: &[&str] = &["schema.kdl", ".md-db/schema.kdl"];
+
+/// File name of a project manifest.
+pub const MANIFEST: &str = "md-db.kdl";
+
+/// One directory of a multi-root project.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Root {
+    /// The directory, joined onto the manifest's directory.
+    pub path: PathBuf,
+    /// File name glob for this root; `*.md` when unset.
+    pub pattern: Option<String>,
+}
+
+/// A project described by an `md-db.kdl` manifest.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Project {
+    /// The directory holding the manifest.
+    pub dir: PathBuf,
+    /// The schema file; `schema.kdl` next to the manifest when unset.
+    pub schema: PathBuf,
+    pub roots: Vec<Root>,
+    /// Steps `md-db new` runs after generating a document.
+    pub on_new: Vec<NewStep>,
+}
+
+impl Project {
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path)?;
+        let dir = path.parent().unwrap_or(Path::new("."));
+        Self::from_str(&content, dir)
+            .map_err(|e| Error::Manifest(format!("{}: {}", path.display(), manifest_message(e))))
+    }
+
+    /// Parse a manifest whose paths are relative to `dir`.
+    pub fn from_str(content: &str, dir: &Path) -> Result<Self> {
+        let doc: KdlDocument = content
+            .parse()
+            .map_err(|e: kdl::KdlError| Error::Manifest(format!("{e:#}")))?;
+
+        let mut schema = None;
+        let mut roots = Vec::new();
+        let mut on_new = Vec::new();
+        for node in doc.nodes() {
+            match node.name().value() {
+                "schema" => {
+                    let path = get_string_arg(node)
+                        .ok_or_else(|| Error::Manifest("schema node missing path argument".into()))?;
+                    schema = Some(dir.join(path));
+                }
+                "root" => {
+                    let path = get_string_arg(node)
+                        .ok_or_else(|| Error::Manifest("root node missing path argument".into()))?;
+                    roots.push(Root {
+                        path: dir.join(path),
+                        pattern: get_string_prop(node, "pattern"),
+                    });
+                }
+                "on-new" => on_new = crate::pipeline::parse_on_new(node)?,
+                // MCP capability config, read by `mcp_config`
+                "mcp" => {}
+                other => return Err(Error::Manifest(format!("unknown node '{other}'"))),
+            }
+        }
+        if roots.is_empty() {
+            return Err(Error::Manifest("no root directories listed".into()));
+        }
+
+        Ok(Self {
+            dir: dir.to_path_buf(),
+            schema: schema.unwrap_or_else(|| dir.join(SCHEMA_CANDIDATES[0])),
+            roots,
+            on_new,
+        })
+    }
+
+    /// The project whose manifest is directly in `dir`.
+    pub fn at(dir: &Path) -> Result<Option<Self>> {
+        let manifest = dir.join(MANIFEST);
+        if manifest.is_file() {
+            Self::from_file(manifest).map(Some)
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// The closest project at or above `start`, stopping at the repository root.
+    pub fn find(start: &Path) -> Result<Option<Self>> {
+        for dir in search_dirs(start)?.0 {
+            if let Some(project) = Self::at(&dir)? {
+                return Ok(Some(project));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Markdown files across all roots, sorted and without duplicates from
+    /// overlapping roots. `pattern` overrides each root's own pattern.
+    pub fn files(&self, pattern: Option<&str>, filters: &[Filter], no_ignore: bool) -> Result<Vec<PathBuf>> {
+        let mut files = Vec::new();
+        let mut seen = std::collections::HashSet::new();
+        for root in &self.roots {
+            if !root.path.is_dir() {
+                return Err(Error::Manifest(format!(
+                    "root {} listed in {} is not a directory",
+                    root.path.display(),
+                    self.dir.join(MANIFEST).display()
+                )));
+            }
+            let pattern = pattern.or(root.pattern.as_deref());
+            for path in crate::discovery::walk_files(&root.path, pattern, filters, no_ignore)? {
+                if seen.insert(path.canonicalize().unwrap_or_else(|_| path.clone())) {
+                    files.push(path);
+                }
+            }
+        }
+        files.sort();
+        Ok(files)
+    }
+}
+
+/// Drop the "project manifest error: " prefix when wrapping a message.
+fn manifest_message(e: Error) -> String {
+    match e {
+        Error::Manifest(msg) => msg,
+        other => other.to_string(),
+    }
+}
+
+/// `start`'s directory and its parents, up to and including the repository
+/// root, which is returned too when the walk reached one.
+pub(crate) fn search_dirs(start: &Path) -> Result<(Vec<PathBuf>, Option<PathBuf>)> {
+    let start = if start.as_os_str().is_empty() { Path::new(".") } else { start };
+    let abs = start
+        .canonicalize()
+        .or_else(|_| std::env::current_dir().map(|d| d.join(start)))?;
+    let mut dir = if abs.is_dir() {
+        abs.clone()
+    } else {
+        abs.parent().map(Path::to_path_buf).unwrap_or_else(|| abs.clone())
+    };
+
+    let mut dirs = Vec::new();
+    loop {
+        dirs.push(dir.clone());
+        if dir.join(".git").exists() {
+            return Ok((dirs, Some(dir)));
+        }
+        match dir.parent() {
+            Some(parent) => dir = parent.to_path_buf(),
+            None => return Ok((dirs, None)),
+        }
+    }
+}
+
+/// Whether a `--schema` value asks for discovery.
+pub fn is_auto(schema: &Path) -> bool {
+    schema.as_os_str() == AUTO
+}
+
+/// Resolve a `--schema` value: `auto` is discovered from `target`, anything else
+/// is a path and returned as given.
+pub fn resolve_schema(schema: &Path, target: &Path) -> Result<PathBuf> {
+    if is_auto(schema) {
+        find_schema(target)
+    } else {
+        Ok(schema.to_path_buf())
+    }
+}
+
+/// Like [`resolve_schema`], for commands that work without a schema: `auto`
+/// yields `None` when no schema is found, an explicit path must exist.
+pub fn resolve_optional_schema(schema: &Path, target: &Path) -> Result<Option<PathBuf>> {
+    if is_auto(schema) {
+        Ok(find_schema(target).ok())
+    } else {
+        Ok(Some(schema.to_path_buf()))
+    }
+}
+
+/// The schema of the closest directory at or above `start` (a file or directory)
+/// that has one. A manifest's `schema` takes precedence in its directory; two
+/// schema files in the same directory are ambiguous and an error.
+pub fn find_schema(start: &Path) -> Result<PathBuf> {
+    let (dirs, repo_root) = search_dirs(start)?;
+    for dir in &dirs {
+        if let Some(project) = Project::at(dir)? {
+            if !project.schema.is_file() {
+                return Err(Error::SchemaDiscovery(format!(
+                    "{} names schema {}, which does not exist",
+                    dir.join(MANIFEST).display(),
+                    project.schema.display()
+                )));
+            }
+            return Ok(project.schema);
+        }
+        let found: Vec<PathBuf> = SCHEMA_CANDIDATES
+            .iter()
+            .map(|c| dir.join(c))
+            .filter(|p| p.is_file())
+            .collect();
+        match found.as_slice() {
+            [one] => return Ok(one.clone()),
+            [] => {}
+            many => {
+                let names: Vec<String> = many.iter().map(|p| p.display().to_string()).collect();
+                return Err(Error::SchemaDiscovery(format!(
+                    "ambiguous schema in {}: {}; remove one or pass --schema PATH",
+                    dir.display(),
+                    names.join(" and ")
+                )));
+            }
+        }
+    }
+
+    let limit = match repo_root {
+        Some(root) => format!(" up to the repository root {}", root.display()),
+        None => " or any parent directory".to_string(),
+    };
+    Err(Error::SchemaDiscovery(format!(
+        "no {}, {} found from {}{limit}; pass --schema PATH",
+        MANIFEST,
+        SCHEMA_CANDIDATES.join(" or "),
+        dirs[0].display()
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_schema() {
+        let tmp = tempfile::tempdir().unwrap();
+        let outer = tmp.path().canonicalize().unwrap();
+        let repo = outer.join("repo");
+        let nested = repo.join("sub/proj");
+        for d in [repo.join(".git"), repo.join("docs/adr"), nested.join(".md-db"), nested.join("docs")] {
+            std::fs::create_dir_all(d).unwrap();
+        }
+        std::fs::write(repo.join("schema.kdl"), "").unwrap();
+        std::fs::write(nested.join(".md-db/schema.kdl"), "").unwrap();
+        std::fs::write(repo.join("docs/adr/adr-001.md"), "").unwrap();
+
+        assert_eq!(find_schema(&repo.join("docs/adr/adr-001.md")).unwrap(), repo.join("schema.kdl"));
+        assert_eq!(find_schema(&repo.join("docs/adr/missing.md")).unwrap(), repo.join("schema.kdl"));
+        // The closest project wins
+        assert_eq!(find_schema(&nested.join("docs")).unwrap(), nested.join(".md-db/schema.kdl"));
+
+        std::fs::write(nested.join("schema.kdl"), "").unwrap();
+        let msg = find_schema(&nested.join("docs")).unwrap_err().to_string();
+        assert!(msg.contains("ambiguous schema"), "{msg}");
+
+        // A schema above the repository root is not used
+        std::fs::remove_file(repo.join("schema.kdl")).unwrap();
+        std::fs::write(outer.join("schema.kdl"), "").unwrap();
+        let msg = find_schema(&repo.join("docs")).unwrap_err().to_string();
+        assert!(msg.contains("up to the repository root"), "{msg}");
+
+        let given = Path::new("custom.kdl");
+        assert_eq!(resolve_schema(given, &repo).unwrap(), given);
+    }
+
+    #[test]
+    fn test_project_manifest() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path().canonicalize().unwrap();
+        for d in [".git", "docs", "rfcs", "config"] {
+            std::fs::create_dir_all(dir.join(d)).unwrap();
+        }
+        std::fs::write(
+            dir.join(MANIFEST),
+            "schema \"config/schema.kdl\"\nroot \"docs\"\nroot \"rfcs\" pattern=\"rfc-*.md\"\n",
+        )
+        .unwrap();
+        std::fs::write(
+            dir.join("config/schema.kdl"),
+            "relation \"related\" cardinality=\"many\"\ntype \"adr\" {\n    field \"title\" type=\"string\"\n}\ntype \"rfc\" {\n    field \"title\" type=\"string\"\n}\n",
+        )
+        .unwrap();
+        std::fs::write(dir.join("docs/adr-001.md"), "---\ntype: adr\ntitle: A\nrelated: [RFC-001]\n---\n").unwrap();
+        std::fs::write(dir.join("rfcs/rfc-001.md"), "---\ntype: rfc\ntitle: R\n---\n").unwrap();
+        std::fs::write(dir.join("rfcs/notes.md"), "# Notes\n").unwrap();
+
+        let files = crate::discovery::discover_files(&dir, None, &[], false).unwrap();
+        assert_eq!(files, vec![dir.join("docs/adr-001.md"), dir.join("rfcs/rfc-001.md")]);
+
+        // The manifest's schema wins, from any root
+        assert_eq!(find_schema(&dir.join("rfcs")).unwrap(), dir.join("config/schema.kdl"));
+
+        // One root validated alone still resolves refs into the others
+        let schema = crate::schema::Schema::from_file(dir.join("config/schema.kdl")).unwrap();
+        let result = crate::validation::validate_directory(dir.join("docs"), &schema, None, None).unwrap();
+        let codes: Vec<&str> =
+            result.file_results.iter().flat_map(|f| &f.diagnostics).map(|d| d.code.as_str()).collect();
+        assert!(!codes.contains(&"R011"), "{codes:?}");
+
+        std::fs::write(dir.join(MANIFEST), "root \"docs\"\non-new {\n    step \"fix\"\n    step \"editor\"\n}\n").unwrap();
+        let project = Project::find(&dir.join("docs")).unwrap().unwrap();
+        assert_eq!(project.on_new, vec![NewStep::Fix, NewStep::Editor]);
+
+        std::fs::write(dir.join(MANIFEST), "root \"docs\"\nfolder \"x\"\n").unwrap();
+        let msg = Project::find(&dir.join("docs")).unwrap_err().to_string();
+        assert!(msg.contains("unknown node 'folder'"), "{msg}");
+    }
+}
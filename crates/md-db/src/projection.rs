@@ -0,0 +1,284 @@
+//! Projections: generated read-model documents declared in the schema (`md-db project`).
+//!
+//! ```kdl
+//! projection "decision-register" path="decision-register.md" title="Decision Register" {
+//!     description "Every decision with its owner and date."
+//!     table "Accepted" type="adr" where="status=accepted" sort="-date" columns="id,title,owner,date"
+//!     table "Proposed" type="adr" columns="id,title,owner" {
+//!         where "status=proposed"
+//!     }
+//! }
+//! ```
+//!
+//! Each `table` is a query over the typed documents, with the keys of
+//! `md-db-query` blocks (`type`, `where`, `has`, `missing`, `sort`, `columns`,
+//! `limit`), rendered under its heading with IDs linking to the documents. The
+//! tables go between `<!-- md-db:projection -->` markers, so text around them is
+//! kept; a missing file is created with the title and description. `path` is
+//! relative to the docs directory. `validate` warns (V001) when a projection's
+//! file is missing or its tables are out of date.
+
+use std::path::{Path, PathBuf};
+
+use kdl::KdlNode;
+
+use crate::assets::{normalize, relative_path, url_path};
+use crate::document::Document;
+use crate::error::{Error, Result};
+use crate::graph::path_to_id;
+use crate::marker;
+use crate::query::Query;
+use crate::schema::{get_i64_prop, get_string_arg, get_string_prop, Schema};
+
+/// Marker block holding the generated tables.
+pub const BLOCK_NAME: &str = "projection";
+
+/// A generated document.
+#[derive(Debug, Clone)]
+pub struct ProjectionDef {
+    pub name: String,
+    /// File path relative to the docs directory.
+    pub path: String,
+    /// Heading of a newly created file; the name when `None`.
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub tables: Vec<ProjectionTable>,
+}
+
+/// One query of a projection and the heading it goes under.
+#[derive(Debug, Clone)]
+pub struct ProjectionTable {
+    pub heading: Option<String>,
+    pub query: Query,
+}
+
+/// Whether a projection's file matches what its queries give now.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProjectionStatus {
+    Fresh,
+    Stale,
+    Missing,
+}
+
+impl std::fmt::Display for ProjectionStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ProjectionStatus::Fresh => write!(f, "up to date"),
+            ProjectionStatus::Stale => write!(f, "out of date"),
+            ProjectionStatus::Missing => write!(f, "missing"),
+        }
+    }
+}
+
+/// A projection rendered against the current documents, not yet written.
+#[derive(Debug, Clone)]
+pub struct Projection {
+    pub name: String,
+    pub path: PathBuf,
+    pub status: ProjectionStatus,
+    /// The file's contents with the tables up to date.
+    pub content: String,
+}
+
+impl ProjectionDef {
+    /// The generated block for the file at `file`: one table per `table`,
+    /// under its heading, with IDs linking to the documents (`docs` with their
+    /// paths set).
+    pub fn render(&self, file: &Path, docs: &[(String, &Document)], schema: Option<&Schema>) -> String {
+        let this_id = path_to_id(file);
+        // Lexically, as the file may not exist yet
+        let lexical = |path: &Path| normalize(&std::env::current_dir().unwrap_or_default().join(path));
+        let dir = lexical(file.parent().unwrap_or(Path::new(".")));
+        let mut out = String::new();
+        for table in &self.tables {
+            if let Some(ref heading) = table.heading {
+                out.push_str(&format!("## {heading}\n\n"));
+            }
+            let mut result = table.query.run(&this_id, docs, schema);
+            if result.rows().is_empty() {
+                out.push_str("*No matching documents.*\n\n");
+                continue;
+            }
+            for row in 0..result.rows().len() {
+                let Some(id) = result.get_cell("id", row).map(str::to_string) else { break };
+                let path = docs.iter().find(|(doc_id, _)| *doc_id == id).and_then(|(_, doc)| doc.path.as_deref());
+                if let Some(path) = path {
+                    let href = url_path(&relative_path(&dir, &lexical(path)));
+                    let _ = result.set_cell("id", row, format!("[{id}]({href})"));
+                }
+            }
+            out.push_str(&result.to_markdown());
+            out.push('\n');
+        }
+        format!("{}\n", out.trim_end())
+    }
+
+    /// A new file for the projection around the generated `block`.
+    fn new_file(&self, block: &str) -> String {
+        let mut out = format!("# {}\n\n", self.title.as_deref().unwrap_or(&self.name));
+        if let Some(ref description) = self.description {
+            out.push_str(&format!("{description}\n\n"));
+        }
+        out.push_str(&format!(
+            "{}\n{block}{}\n",
+            marker::start_marker(BLOCK_NAME),
+            marker::end_marker(BLOCK_NAME)
+        ));
+        out
+    }
+}
+
+/// Render `def` over `docs` (ID, document) for the docs directory `dir` and
+/// compare it with the file on disk.
+pub fn materialize(def: &ProjectionDef, dir: &Path, docs: &[(String, &Document)], schema: &Schema) -> Result<Projection> {
+    let path = dir.join(&def.path);
+    let block = def.render(&path, docs, Some(schema));
+    let (status, content) = match std::fs::read_to_string(&path) {
+        Ok(text) => {
+            let updated = marker::replace_block(&text, BLOCK_NAME, &block)?.ok_or_else(|| {
+                Error::Marker(format!(
+                    "{} has no projection block; add the lines {} and {} where the tables should go",
+                    path.display(),
+                    marker::start_marker(BLOCK_NAME),
+                    marker::end_marker(BLOCK_NAME)
+                ))
+            })?;
+            let status = if updated == text { ProjectionStatus::Fresh } else { ProjectionStatus::Stale };
+            (status, updated)
+        }
+        Err(_) => (ProjectionStatus::Missing, def.new_file(&block)),
+    };
+    Ok(Projection {
+        name: def.name.clone(),
+        path,
+        status,
+        content,
+    })
+}
+
+/// Every projection of `schema` over the typed documents under `dir`.
+/// Singletons are left out, as in validation.
+pub fn project(dir: &Path, schema: &Schema) -> Result<Vec<Projection>> {
+    let mut docs = Vec::new();
+    for path in crate::discovery::discover_files(dir, None, &[], false)? {
+        if schema.singleton_for(&path).is_some() {
+            continue;
+        }
+        let Ok(doc) = Document::from_file(&path) else { continue };
+        if doc.frontmatter.as_ref().is_some_and(|fm| fm.get("type").is_some()) {
+            docs.push((path_to_id(&path), doc));
+        }
+    }
+    let docs: Vec<(String, &Document)> = docs.iter().map(|(id, doc)| (id.clone(), doc)).collect();
+    schema.projections.iter().map(|def| materialize(def, dir, &docs, schema)).collect()
+}
+
+/// Parse a `projection` node.
+pub(crate) fn parse_projection(node: &KdlNode) -> Result<ProjectionDef> {
+    let name = get_string_arg(node).ok_or_else(|| Error::SchemaParse("projection node missing name".into()))?;
+    let err = |msg: String| Error::SchemaParse(format!("projection '{name}': {msg}"));
+    let path = get_string_prop(node, "path").ok_or_else(|| err("needs a path".into()))?;
+    let mut projection = ProjectionDef {
+        name: name.clone(),
+        path,
+        title: get_string_prop(node, "title"),
+        description: get_string_prop(node, "description"),
+        tables: Vec::new(),
+    };
+    for child in node.children().map(|c| c.nodes()).unwrap_or_default() {
+        match child.name().value() {
+            "description" => {
+                projection.description = Some(get_string_arg(child).ok_or_else(|| err("description needs a value".into()))?);
+            }
+            "table" => projection.tables.push(parse_table(child).map_err(err)?),
+            other => return Err(err(format!("unknown node '{other}' (expected description, table)"))),
+        }
+    }
+    if projection.tables.is_empty() {
+        return Err(err("needs at least one table".into()));
+    }
+    Ok(projection)
+}
+
+/// A `table` node: its properties and `where`/`has`/`missing` children as the
+/// lines of an `md-db-query` block.
+fn parse_table(node: &KdlNode) -> std::result::Result<ProjectionTable, String> {
+    let heading = get_string_arg(node);
+    let label = heading.as_deref().map_or_else(|| "table".to_string(), |h| format!("table \"{h}\""));
+    let mut source = String::new();
+    for key in ["type", "where", "has", "missing", "sort", "columns"] {
+        if let Some(value) = get_string_prop(node, key) {
+            source.push_str(&format!("{key}: {value}\n"));
+        }
+    }
+    if let Some(limit) = get_i64_prop(node, "limit") {
+        source.push_str(&format!("limit: {limit}\n"));
+    }
+    for child in node.children().map(|c| c.nodes()).unwrap_or_default() {
+        let key = child.name().value();
+        if !["where", "has", "missing"].contains(&key) {
+            return Err(format!("{label}: unknown node '{key}' (expected where, has, missing)"));
+        }
+        let value = get_string_arg(child).ok_or_else(|| format!("{label}: {key} needs a value"))?;
+        source.push_str(&format!("{key}: {value}\n"));
+    }
+    // Errors name a line of the generated source; the key and value say enough
+    let query = Query::parse(&source).map_err(|e| {
+        let message = e.split_once(": ").map_or(e.as_str(), |(_, message)| message);
+        format!("{label}: {message}")
+    })?;
+    if query.links.is_some() {
+        return Err(format!("{label}: links is only for md-db-query blocks"));
+    }
+    Ok(ProjectionTable { heading, query })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_project() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        std::fs::create_dir_all(root.join("decisions")).unwrap();
+        let doc = |name: &str, fm: &str| std::fs::write(root.join(name), format!("---\n{fm}\n---\n")).unwrap();
+        doc("decisions/adr-001.md", "type: adr\ntitle: Use Kafka\nstatus: accepted\nowner: ana\ndate: 2024-01-10");
+        doc("decisions/adr-002.md", "type: adr\ntitle: Use Avro\nstatus: accepted\nowner: bo\ndate: 2024-03-02");
+        doc("decisions/adr-003.md", "type: adr\ntitle: Use gRPC\nstatus: proposed\nowner: ana");
+        let schema = Schema::from_str(
+            "type \"adr\"\n\
+             projection \"register\" path=\"reports/register.md\" title=\"Decision Register\" {\n    \
+             description \"Every decision.\"\n    \
+             table \"Accepted\" type=\"adr\" where=\"status=accepted\" sort=\"-date\" columns=\"id,title,owner,date\"\n    \
+             table \"Rejected\" type=\"adr\" {\n        where \"status=rejected\"\n    }\n}\n",
+        )
+        .unwrap();
+
+        let projections = project(root, &schema).unwrap();
+        assert_eq!(projections[0].status, ProjectionStatus::Missing);
+        assert_eq!(
+            projections[0].content,
+            "# Decision Register\n\nEvery decision.\n\n<!-- md-db:projection -->\n## Accepted\n\n\
+             | id | title | owner | date |\n|---|---|---|---|\n\
+             | [ADR-002](../decisions/adr-002.md) | Use Avro | bo | 2024-03-02 |\n\
+             | [ADR-001](../decisions/adr-001.md) | Use Kafka | ana | 2024-01-10 |\n\n\
+             ## Rejected\n\n*No matching documents.*\n<!-- /md-db:projection -->\n"
+        );
+
+        // Text around the block is kept; a changed document makes it stale
+        std::fs::create_dir_all(root.join("reports")).unwrap();
+        let edited = projections[0].content.replace("Every decision.", "Kept by the architecture group.");
+        std::fs::write(root.join("reports/register.md"), &edited).unwrap();
+        assert_eq!(project(root, &schema).unwrap()[0].status, ProjectionStatus::Fresh);
+        doc("decisions/adr-003.md", "type: adr\ntitle: Use gRPC\nstatus: accepted\nowner: ana\ndate: 2024-05-01");
+        let stale = project(root, &schema).unwrap().remove(0);
+        assert_eq!(stale.status, ProjectionStatus::Stale);
+        assert!(stale.content.contains("Kept by the architecture group."));
+        assert!(stale.content.contains("| [ADR-003](../decisions/adr-003.md) | Use gRPC | ana | 2024-05-01 |"));
+
+        assert!(Schema::from_str("projection \"x\" path=\"x.md\"").is_err());
+        let err = Schema::from_str("projection \"x\" path=\"x.md\" {\n    table where=\"status\"\n}\n").unwrap_err();
+        assert!(err.to_string().contains("projection 'x': table: invalid condition"), "{err}");
+    }
+}
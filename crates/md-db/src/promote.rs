@@ -0,0 +1,222 @@
+//! Promote authoritative body values (table cells, `yaml` blocks) into frontmatter.
+//!
+//! Schema types declare `promote` nodes; `sync --promote` copies the body value
+//! into the frontmatter field and validation flags drift between the two.
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use serde_yaml::Value;
+
+use crate::document::Document;
+use crate::error::Result;
+use crate::frontmatter::{parse_yaml_value, yaml_value_to_string};
+use crate::schema::{FieldType, PromoteDef, PromoteSource, Schema, TypeDef};
+
+/// A frontmatter field whose value differs from (or is missing next to) its body source.
+#[derive(Debug, Clone)]
+pub struct Promotion {
+    pub path: PathBuf,
+    /// Document ID (e.g. "SLO-001").
+    pub doc_id: String,
+    pub field: String,
+    /// Value found in the body.
+    pub value: Value,
+    /// Current frontmatter value, if any.
+    pub current: Option<Value>,
+}
+
+impl Promotion {
+    /// True when the frontmatter already holds a different value (as opposed to none).
+    pub fn is_drift(&self) -> bool {
+        self.current.is_some()
+    }
+
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "path": self.path.display().to_string(),
+            "doc_id": self.doc_id,
+            "field": self.field,
+            "value": crate::frontmatter::yaml_to_json(&self.value),
+            "current": self.current.as_ref().map(crate::frontmatter::yaml_to_json),
+            "drift": self.is_drift(),
+        })
+    }
+}
+
+/// Read the body value a promote rule points at.
+/// Returns `None` if the section, table, cell, block, or key is missing.
+pub fn extract_value(doc: &Document, def: &PromoteDef, type_def: &TypeDef) -> Option<Value> {
+    let section = doc.get_section(&def.section).ok()?;
+    match &def.source {
+        PromoteSource::TableCell { table, column, row } => {
+            let tables = section.tables();
+            let cell = tables.get(*table)?.get_cell(column, *row)?.trim().to_string();
+            if cell.is_empty() {
+                return None;
+            }
+            let is_string_field = type_def
+                .fields
+                .iter()
+                .find(|f| f.name == def.field)
+                .is_some_and(|f| f.field_type == FieldType::String);
+            Some(if is_string_field {
+                Value::String(cell)
+            } else {
+                parse_yaml_value(&cell)
+            })
+        }
+        PromoteSource::YamlKey(key) => {
+            let (_, body) = section
+                .code_blocks()
+                .into_iter()
+                .find(|(info, _)| matches!(info.to_lowercase().as_str(), "yaml" | "yml"))?;
+            let mut value: Value = serde_yaml::from_str(&body).ok()?;
+            for part in key.split('.') {
+                value = value.get(part)?.clone();
+            }
+            Some(value)
+        }
+    }
+}
+
+/// Compare every promote rule of the document's type against its frontmatter.
+/// Only out-of-sync fields are returned.
+pub fn check_document(doc: &Document, type_def: &TypeDef) -> Vec<Promotion> {
+    let path = doc.path.clone().unwrap_or_default();
+    let doc_id = crate::graph::path_to_id(&path);
+    let mut out = Vec::new();
+
+    for def in &type_def.promotes {
+        let Some(value) = extract_value(doc, def, type_def) else {
+            continue;
+        };
+        let current = doc.frontmatter.as_ref().and_then(|fm| fm.get(&def.field)).cloned();
+        let in_sync = current
+            .as_ref()
+            .is_some_and(|c| yaml_value_to_string(c) == yaml_value_to_string(&value));
+        if !in_sync {
+            out.push(Promotion {
+                path: path.clone(),
+                doc_id: doc_id.clone(),
+                field: def.field.clone(),
+                value,
+                current,
+            });
+        }
+    }
+    out
+}
+
+/// Scan files and collect the promotions needed to bring frontmatter in line with the body.
+pub fn plan_promotions(files: &[PathBuf], schema: &Schema) -> Vec<Promotion> {
+    let mut out = Vec::new();
+    for path in files {
+        let Ok(doc) = Document::from_file(path) else {
+            continue;
+        };
+        let Some(type_def) = doc
+            .frontmatter
+            .as_ref()
+            .and_then(|fm| fm.get_display("type"))
+            .and_then(|t| schema.get_type(&t))
+        else {
+            continue;
+        };
+        if type_def.promotes.is_empty() {
+            continue;
+        }
+        out.extend(check_document(&doc, type_def));
+    }
+    out
+}
+
+/// Write promoted values into each document's frontmatter.
+pub fn apply_promotions(promotions: &[Promotion]) -> Result<()> {
+    let mut by_path: BTreeMap<&PathBuf, Vec<&Promotion>> = BTreeMap::new();
+    for p in promotions {
+        by_path.entry(&p.path).or_default().push(p);
+    }
+
+    for (path, items) in by_path {
+        let mut doc = Document::from_file(path)?;
+        for p in items {
+            doc.set_field(&p.field, p.value.clone());
+        }
+        doc.save()?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn slo_schema() -> Schema {
+        Schema::from_str(
+            r#"
+type "slo" {
+    field "target" type="number"
+    field "owner" type="string"
+    field "window" type="string"
+    promote "target" section="Objective" column="Target"
+    promote "owner" section="Objective" column="Owner"
+    promote "window" section="Budget" yaml-key="budget.window"
+    section "Objective"
+    section "Budget"
+}
+"#,
+        )
+        .unwrap()
+    }
+
+    const SLO: &str = "---\ntype: slo\ntarget: 99.5\n---\n\n# Objective\n\n| Target | Owner |\n|--------|-------|\n| 99.9 | @sre |\n\n# Budget\n\n```yaml\nbudget:\n  window: 30d\n```\n";
+
+    #[test]
+    fn test_check_document_finds_drift_and_missing() {
+        let schema = slo_schema();
+        let doc = Document::from_str(SLO).unwrap();
+        let promos = check_document(&doc, &schema.types[0]);
+        assert_eq!(promos.len(), 3);
+
+        let target = promos.iter().find(|p| p.field == "target").unwrap();
+        assert!(target.is_drift());
+        assert_eq!(yaml_value_to_string(&target.value), "99.9");
+        assert!(target.value.is_number());
+
+        let owner = promos.iter().find(|p| p.field == "owner").unwrap();
+        assert!(!owner.is_drift());
+        assert_eq!(owner.value, Value::String("@sre".into()));
+
+        let window = promos.iter().find(|p| p.field == "window").unwrap();
+        assert_eq!(window.value, Value::String("30d".into()));
+    }
+
+    #[test]
+    fn test_in_sync_document_has_no_promotions() {
+        let schema = slo_schema();
+        let content = SLO.replace("target: 99.5", "target: 99.9\nowner: \"@sre\"\nwindow: 30d");
+        let doc = Document::from_str(&content).unwrap();
+        assert!(check_document(&doc, &schema.types[0]).is_empty());
+    }
+
+    #[test]
+    fn test_plan_and_apply() {
+        let schema = slo_schema();
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("slo-001.md");
+        std::fs::write(&path, SLO).unwrap();
+
+        let plan = plan_promotions(&[path.clone()], &schema);
+        assert_eq!(plan.len(), 3);
+        assert_eq!(plan[0].doc_id, "SLO-001");
+
+        apply_promotions(&plan).unwrap();
+        let doc = Document::from_file(&path).unwrap();
+        let fm = doc.frontmatter.as_ref().unwrap();
+        assert_eq!(fm.get_display("target").as_deref(), Some("99.9"));
+        assert_eq!(fm.get_display("window").as_deref(), Some("30d"));
+        assert!(doc.body.contains("| 99.9 | @sre |"));
+        assert!(plan_promotions(&[path], &schema).is_empty());
+    }
+}
@@ -0,0 +1,287 @@
+//! Prose metrics for the optional writing-quality lint.
+//!
+//! Heuristics, not grammar: sentences are split on terminal punctuation,
+//! passive voice is a form of "to be" followed by a past participle, and
+//! readability is the Flesch reading-ease score (higher is easier; 60–70 is
+//! plain English, below 30 is hard going).
+
+use comrak::nodes::NodeValue;
+use comrak::Arena;
+
+use crate::ast_util;
+use crate::document::Document;
+use crate::schema::TypeDef;
+
+/// Words that end in a period without ending the sentence.
+const ABBREVIATIONS: &[&str] = &["e.g", "i.e", "etc", "vs", "cf", "approx", "mr", "ms", "dr", "no"];
+
+const BE_FORMS: &[&str] = &["am", "is", "are", "was", "were", "be", "been", "being"];
+
+/// Common irregular past participles (regular ones end in "-ed").
+const IRREGULAR_PARTICIPLES: &[&str] = &[
+    "begun", "bought", "broken", "brought", "built", "caught", "chosen", "cut", "done", "drawn",
+    "driven", "found", "forgotten", "given", "held", "hidden", "kept", "known", "left", "lost",
+    "made", "paid", "put", "read", "run", "seen", "sent", "set", "shown", "sold", "spent",
+    "split", "spoken", "taken", "taught", "thought", "told", "understood", "won", "written",
+];
+
+/// Metrics for a block of prose.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ProseStats {
+    pub sentences: usize,
+    pub words: usize,
+    pub syllables: usize,
+    /// Sentences that read as passive voice.
+    pub passive: usize,
+    /// Word count of the longest sentence.
+    pub longest: usize,
+}
+
+impl ProseStats {
+    /// Share of passive sentences, 0.0 for empty text.
+    pub fn passive_ratio(&self) -> f64 {
+        if self.sentences == 0 {
+            0.0
+        } else {
+            self.passive as f64 / self.sentences as f64
+        }
+    }
+
+    /// Flesch reading ease; `None` for empty text.
+    pub fn reading_ease(&self) -> Option<f64> {
+        if self.sentences == 0 || self.words == 0 {
+            return None;
+        }
+        let words_per_sentence = self.words as f64 / self.sentences as f64;
+        let syllables_per_word = self.syllables as f64 / self.words as f64;
+        Some(206.835 - 1.015 * words_per_sentence - 84.6 * syllables_per_word)
+    }
+
+    /// Fold another block's metrics into this one.
+    pub fn add(&mut self, other: &ProseStats) {
+        self.sentences += other.sentences;
+        self.words += other.words;
+        self.syllables += other.syllables;
+        self.passive += other.passive;
+        self.longest = self.longest.max(other.longest);
+    }
+}
+
+/// Paragraph and list text of a markdown fragment; code blocks, tables, and headings are skipped.
+pub fn prose_text(markdown: &str) -> String {
+    let arena = Arena::new();
+    let opts = ast_util::comrak_opts();
+    let root = comrak::parse_document(&arena, markdown, &opts);
+
+    let mut parts = Vec::new();
+    for node in root.descendants() {
+        if matches!(node.data.borrow().value, NodeValue::Paragraph) {
+            let text = ast_util::collect_text(node);
+            let text = text.trim();
+            if !text.is_empty() {
+                parts.push(text.to_string());
+            }
+        }
+    }
+    parts.join("\n\n")
+}
+
+/// Prose of each required top-level section present in the document, as `(heading, text)`.
+/// Subsections are included in their parent's text.
+pub fn required_section_texts(doc: &Document, type_def: &TypeDef) -> Vec<(String, String)> {
+    type_def
+        .sections
+        .iter()
+        .filter(|s| s.required)
+        .filter_map(|s| {
+            let section = doc.get_section(&s.name).ok()?;
+            Some((s.name.clone(), prose_text(&section.content)))
+        })
+        .collect()
+}
+
+/// Split text into sentences on `.`, `!`, `?` followed by whitespace, and on blank lines.
+pub fn sentences(text: &str) -> Vec<String> {
+    let mut out = Vec::new();
+    for block in text.split("\n\n") {
+        let mut current = String::new();
+        let mut chars = block.chars().peekable();
+        while let Some(c) = chars.next() {
+            current.push(if c == '\n' { ' ' } else { c });
+            let at_boundary = chars.peek().is_none_or(|n| n.is_whitespace());
+            if matches!(c, '.' | '!' | '?') && at_boundary && !ends_with_abbreviation(&current) {
+                push_sentence(&mut out, &current);
+                current.clear();
+            }
+        }
+        push_sentence(&mut out, &current);
+    }
+    out
+}
+
+fn push_sentence(out: &mut Vec<String>, s: &str) {
+    let s = s.trim();
+    if words(s).next().is_some() {
+        out.push(s.to_string());
+    }
+}
+
+fn ends_with_abbreviation(s: &str) -> bool {
+    let last = s.split_whitespace().last().unwrap_or("");
+    let word = last.trim_end_matches('.').trim_start_matches(['(', '"', '\'']).to_lowercase();
+    ABBREVIATIONS.contains(&word.as_str())
+}
+
+/// Words of a sentence, lowercased and stripped of surrounding punctuation.
+fn words(sentence: &str) -> impl Iterator<Item = String> + '_ {
+    sentence
+        .split_whitespace()
+        .map(|w| {
+            w.trim_matches(|c: char| !c.is_alphanumeric())
+                .to_lowercase()
+        })
+        .filter(|w| w.chars().any(|c| c.is_alphabetic()))
+}
+
+/// Number of words in plain text, counted the same way as sentence metrics.
+pub fn word_count(text: &str) -> usize {
+    words(text).count()
+}
+
+/// Estimate syllables by counting vowel groups, dropping a silent final "e".
+pub fn syllables(word: &str) -> usize {
+    let word = word.to_lowercase();
+    let mut count = 0;
+    let mut prev_vowel = false;
+    for c in word.chars() {
+        let vowel = matches!(c, 'a' | 'e' | 'i' | 'o' | 'u' | 'y');
+        if vowel && !prev_vowel {
+            count += 1;
+        }
+        prev_vowel = vowel;
+    }
+    if word.ends_with('e') && !word.ends_with("le") && count > 1 {
+        count -= 1;
+    }
+    count.max(1)
+}
+
+/// A form of "to be" followed (allowing one adverb) by a past participle.
+pub fn is_passive(sentence: &str) -> bool {
+    let words: Vec<String> = words(sentence).collect();
+    words.iter().enumerate().any(|(i, w)| {
+        if !BE_FORMS.contains(&w.as_str()) {
+            return false;
+        }
+        let mut next = words.iter().skip(i + 1);
+        match next.next() {
+            Some(n) if is_participle(n) => true,
+            Some(n) if n.ends_with("ly") || n == "not" || n == "also" => {
+                next.next().is_some_and(|n| is_participle(n))
+            }
+            _ => false,
+        }
+    })
+}
+
+fn is_participle(word: &str) -> bool {
+    (word.len() > 3 && word.ends_with("ed")) || IRREGULAR_PARTICIPLES.contains(&word)
+}
+
+/// Compute metrics for plain text.
+pub fn analyze(text: &str) -> ProseStats {
+    let mut stats = ProseStats::default();
+    for sentence in sentences(text) {
+        let word_list: Vec<String> = words(&sentence).collect();
+        stats.sentences += 1;
+        stats.words += word_list.len();
+        stats.syllables += word_list.iter().map(|w| syllables(w)).sum::<usize>();
+        stats.longest = stats.longest.max(word_list.len());
+        if is_passive(&sentence) {
+            stats.passive += 1;
+        }
+    }
+    stats
+}
+
+/// Sentences longer than `max_words`, with their word counts.
+pub fn long_sentences(text: &str, max_words: usize) -> Vec<(String, usize)> {
+    sentences(text)
+        .into_iter()
+        .map(|s| {
+            let n = words(&s).count();
+            (s, n)
+        })
+        .filter(|(_, n)| *n > max_words)
+        .collect()
+}
+
+/// Whether any of `verbs` occurs as a whole word in the text.
+pub fn uses_any_verb(text: &str, verbs: &[String]) -> bool {
+    let lower = text.to_lowercase();
+    lower
+        .split(|c: char| !c.is_alphanumeric() && c != '\'')
+        .any(|w| verbs.iter().any(|v| v == w))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sentences() {
+        let s = sentences("We use Postgres, e.g. for billing. Costs rose 3.5% this year!\n\nIs it fast? Yes");
+        assert_eq!(
+            s,
+            vec![
+                "We use Postgres, e.g. for billing.",
+                "Costs rose 3.5% this year!",
+                "Is it fast?",
+                "Yes",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_passive_voice() {
+        assert!(is_passive("The database was chosen by the platform team."));
+        assert!(is_passive("Requests are automatically retried."));
+        assert!(is_passive("It has been decided."));
+        assert!(!is_passive("We chose the database."));
+        assert!(!is_passive("The team is ready."));
+    }
+
+    #[test]
+    fn test_syllables_and_reading_ease() {
+        assert_eq!(syllables("cat"), 1);
+        assert_eq!(syllables("database"), 3);
+        assert_eq!(syllables("table"), 2);
+
+        let easy = analyze("We use it. It is fast. The cat sat.");
+        let hard = analyze(
+            "Comprehensive organizational infrastructure modernization necessitates considerable interdepartmental coordination.",
+        );
+        assert!(easy.reading_ease().unwrap() > 80.0);
+        assert!(hard.reading_ease().unwrap() < 0.0);
+        assert_eq!(analyze("").reading_ease(), None);
+    }
+
+    #[test]
+    fn test_prose_text_skips_code_and_tables() {
+        let md = "Intro text.\n\n```sh\nrm -rf /tmp/x. Not prose.\n```\n\n| A | B |\n|---|---|\n| x. | y |\n\n- A list item.\n";
+        let text = prose_text(md);
+        assert_eq!(sentences(&text), vec!["Intro text.", "A list item."]);
+    }
+
+    #[test]
+    fn test_long_sentences_and_verbs() {
+        let text = "Short one. This sentence has exactly seven words here.";
+        let long = long_sentences(text, 5);
+        assert_eq!(long.len(), 1);
+        assert_eq!(long[0].1, 7);
+
+        let verbs = vec!["will".to_string(), "adopt".to_string()];
+        assert!(uses_any_verb("We will adopt Postgres.", &verbs));
+        assert!(!uses_any_verb("Willing adopters.", &verbs));
+    }
+}
@@ -0,0 +1,438 @@
+//! `md-db-query` fenced blocks: per-document listings computed at export time.
+//!
+//! ````markdown
+//! ```md-db-query
+//! type: inc
+//! where: status != resolved
+//! links: caused_by
+//! columns: id, title, status
+//! sort: -date
+//! limit: 10
+//! ```
+//! ````
+//!
+//! `where` also takes `<`, `<=`, `>`, `>=`; dates compare chronologically.
+//!
+//! `links` keeps documents that point at the current document through the
+//! relation (or that the current document points at through its inverse).
+
+use std::path::Path;
+
+use crate::discovery::{check_filters, Filter};
+use crate::document::Document;
+use crate::frontmatter::Frontmatter;
+use crate::graph::path_to_id;
+use crate::schema::Schema;
+use crate::table::Table;
+
+/// Info string that marks a fenced code block as a query.
+pub const QUERY_LANG: &str = "md-db-query";
+
+const DEFAULT_COLUMNS: [&str; 3] = ["id", "title", "status"];
+
+/// A parsed `md-db-query` block.
+#[derive(Debug, Clone, Default)]
+pub struct Query {
+    pub doc_type: Option<String>,
+    pub filters: Vec<Filter>,
+    /// Relation linking results to the current document; `*` for any relation.
+    pub links: Option<String>,
+    pub columns: Vec<String>,
+    /// Sort field and whether it's descending.
+    pub sort: Option<(String, bool)>,
+    pub limit: Option<usize>,
+}
+
+/// A fenced query block found in a document body.
+#[derive(Debug, Clone)]
+pub struct QueryBlock {
+    /// 1-based line of the opening fence within the body.
+    pub line: usize,
+    pub source: String,
+    /// Byte range of the whole fenced block (fences included).
+    range: std::ops::Range<usize>,
+}
+
+impl Query {
+    /// Parse the body of a query block. Errors name the offending line.
+    pub fn parse(source: &str) -> Result<Self, String> {
+        let mut query = Query::default();
+
+        for (i, raw) in source.lines().enumerate() {
+            let line = raw.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let err = |msg: String| format!("line {}: {msg}", i + 1);
+            let (key, value) = line
+                .split_once(':')
+                .map(|(k, v)| (k.trim(), v.trim()))
+                .ok_or_else(|| err(format!("expected 'key: value', got '{line}'")))?;
+            if value.is_empty() {
+                return Err(err(format!("'{key}' needs a value")));
+            }
+
+            match key {
+                "type" => query.doc_type = Some(value.to_string()),
+                "where" => query.filters.push(parse_condition(value).map_err(err)?),
+                "has" => query.filters.push(Filter::HasField(value.to_string())),
+                "missing" => query.filters.push(Filter::NotHasField(value.to_string())),
+                "links" => query.links = Some(value.to_string()),
+                "columns" => {
+                    query.columns = value
+                        .split(',')
+                        .map(|c| c.trim().to_string())
+                        .filter(|c| !c.is_empty())
+                        .collect();
+                }
+                "sort" => {
+                    query.sort = Some(match value.strip_prefix('-') {
+                        Some(field) => (field.to_string(), true),
+                        None => (value.to_string(), false),
+                    });
+                }
+                "limit" => {
+                    query.limit = Some(
+                        value
+                            .parse()
+                            .map_err(|_| err(format!("limit must be a number, got '{value}'")))?,
+                    );
+                }
+                other => return Err(err(format!("unknown key '{other}'"))),
+            }
+        }
+
+        if query.columns.is_empty() {
+            query.columns = DEFAULT_COLUMNS.iter().map(|c| c.to_string()).collect();
+        }
+        Ok(query)
+    }
+
+    /// Run the query for the document `this_id` over the whole document set.
+    /// `schema` enables inverse-relation matching for `links`.
+    pub fn run(
+        &self,
+        this_id: &str,
+        docs: &[(String, &Document)],
+        schema: Option<&Schema>,
+    ) -> Table {
+        let this_doc = docs.iter().find(|(id, _)| id == this_id).map(|(_, d)| *d);
+
+        let mut rows: Vec<(&String, &Frontmatter)> = docs
+            .iter()
+            .filter(|(id, _)| id != this_id)
+            .filter_map(|(id, doc)| doc.frontmatter.as_ref().map(|fm| (id, fm)))
+            .filter(|(_, fm)| {
+                self.doc_type
+                    .as_ref()
+                    .map(|t| fm.get_display("type").as_deref() == Some(t.as_str()))
+                    .unwrap_or(true)
+            })
+            .filter(|(_, fm)| check_filters(fm, &self.filters))
+            .filter(|(id, fm)| match &self.links {
+                Some(rel) => links_to(id, fm, this_id, this_doc, rel, schema),
+                None => true,
+            })
+            .collect();
+
+        if let Some((ref field, descending)) = self.sort {
+            rows.sort_by(|a, b| {
+                let cmp = match (a.1.get_display(field), b.1.get_display(field)) {
+                    (Some(x), Some(y)) => crate::ordering::compare_text(&x, &y),
+                    (x, y) => x.cmp(&y),
+                };
+                if descending { cmp.reverse() } else { cmp }
+            });
+        }
+        if let Some(limit) = self.limit {
+            rows.truncate(limit);
+        }
+
+        let cells = rows
+            .into_iter()
+            .map(|(id, fm)| {
+                self.columns
+                    .iter()
+                    .map(|col| {
+                        let value = match col.as_str() {
+                            "id" => id.clone(),
+                            other => fm.get_display(other).unwrap_or_default(),
+                        };
+                        value.replace('|', "\\|")
+                    })
+                    .collect()
+            })
+            .collect();
+        Table::new(self.columns.clone(), cells)
+    }
+}
+
+/// Parse `field = value`, `field != value`, `field ~= value`, or `field in a, b`.
+pub(crate) fn parse_condition(expr: &str) -> Result<Filter, String> {
+    let pair = |op: &str| {
+        expr.split_once(op)
+            .map(|(k, v)| (k.trim().to_string(), v.trim().to_string()))
+    };
+    if expr.contains(['<', '>']) {
+        return Filter::parse_comparison(expr)
+            .ok_or_else(|| format!("invalid comparison '{expr}' (expected key < value, <=, >, or >=)"));
+    }
+    if let Some((key, value)) = pair("!=") {
+        return Ok(Filter::FieldNotEquals { key, value });
+    }
+    if let Some((key, value)) = pair("~=") {
+        return Ok(Filter::FieldContains { key, value });
+    }
+    if let Some((key, value)) = pair("=") {
+        return Ok(Filter::FieldEquals { key, value });
+    }
+    if let Some((key, values)) = pair(" in ") {
+        return Ok(Filter::FieldIn {
+            key,
+            values: values.split(',').map(|v| v.trim().to_string()).collect(),
+        });
+    }
+    Err(format!(
+        "invalid condition '{expr}' (expected =, !=, ~=, <, <=, >, >=, or in)"
+    ))
+}
+
+/// Whether `fm` (document `id`) is linked to the current document through `relation`.
+fn links_to(
+    id: &str,
+    fm: &Frontmatter,
+    this_id: &str,
+    this_doc: Option<&Document>,
+    relation: &str,
+    schema: Option<&Schema>,
+) -> bool {
+    let this_uid = this_doc
+        .and_then(|d| d.frontmatter.as_ref())
+        .and_then(|f| f.get_display(crate::uid::UID_FIELD));
+    let is_this = |r: &str| {
+        ref_id(r) == this_id
+            || this_uid.as_deref().is_some_and(|u| u.eq_ignore_ascii_case(r))
+    };
+
+    let (forward, inverse): (Vec<String>, Vec<String>) = if relation == "*" {
+        let names: Vec<String> = schema
+            .map(|s| s.all_relation_field_names().iter().map(|n| n.to_string()).collect())
+            .unwrap_or_default();
+        (names.clone(), names)
+    } else {
+        let inverse = schema
+            .and_then(|s| s.find_relation(relation))
+            .map(|(r, is_inverse)| {
+                if is_inverse {
+                    r.name.clone()
+                } else {
+                    r.inverse.clone().unwrap_or_else(|| r.name.clone())
+                }
+            });
+        (vec![relation.to_string()], inverse.into_iter().collect())
+    };
+
+    // Result document points at this one
+    if forward.iter().any(|f| field_refs(fm, f).iter().any(|r| is_this(r))) {
+        return true;
+    }
+    // This document points back through the inverse field
+    let this_fm = this_doc.and_then(|d| d.frontmatter.as_ref());
+    let own_uid = fm.get_display(crate::uid::UID_FIELD);
+    inverse.iter().any(|f| {
+        this_fm
+            .map(|tf| field_refs(tf, f))
+            .unwrap_or_default()
+            .iter()
+            .any(|r| ref_id(r) == id || own_uid.as_deref().is_some_and(|u| u.eq_ignore_ascii_case(r)))
+    })
+}
+
+fn field_refs(fm: &Frontmatter, field: &str) -> Vec<String> {
+    match fm.get(field) {
+        Some(serde_yaml::Value::String(s)) => vec![s.clone()],
+        Some(serde_yaml::Value::Sequence(seq)) => seq
+            .iter()
+            .filter_map(|v| v.as_str().map(String::from))
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Normalize a ref (`ADR-001`, `./adr-001.md`) to a display ID.
+fn ref_id(r: &str) -> String {
+    if r.ends_with(".md") {
+        path_to_id(Path::new(r))
+    } else {
+        r.to_uppercase()
+    }
+}
+
+/// Find every `md-db-query` fenced block in a markdown body.
+pub fn find_query_blocks(body: &str) -> Vec<QueryBlock> {
+    let mut blocks = Vec::new();
+    let mut offset = 0;
+    // (fence marker, start byte, start line, source)
+    let mut open: Option<(String, usize, usize, String)> = None;
+
+    for (i, line) in body.split_inclusive('\n').enumerate() {
+        let trimmed = line.trim();
+        match open {
+            None => {
+                let fence: String = trimmed.chars().take_while(|c| *c == '`' || *c == '~').collect();
+                if fence.len() >= 3 && trimmed[fence.len()..].trim() == QUERY_LANG {
+                    open = Some((fence, offset, i + 1, String::new()));
+                }
+            }
+            Some((ref fence, _, _, ref mut source)) => {
+                let marker = fence.chars().next().unwrap_or('`');
+                let closes = trimmed.starts_with(fence.as_str())
+                    && trimmed.trim_start_matches(marker).is_empty();
+                if !closes {
+                    source.push_str(line);
+                } else if let Some((_, start, start_line, source)) = open.take() {
+                    blocks.push(QueryBlock {
+                        line: start_line,
+                        source,
+                        range: start..offset + line.len(),
+                    });
+                }
+            }
+        }
+        offset += line.len();
+    }
+    blocks
+}
+
+/// Replace each query block in `body` with a markdown table of its results.
+/// Blocks that fail to parse become a quoted error so the page still renders.
+pub fn expand_queries(
+    body: &str,
+    this_id: &str,
+    docs: &[(String, &Document)],
+    schema: Option<&Schema>,
+) -> String {
+    let blocks = find_query_blocks(body);
+    if blocks.is_empty() {
+        return body.to_string();
+    }
+
+    let mut out = String::with_capacity(body.len());
+    let mut last = 0;
+    for block in blocks {
+        out.push_str(&body[last..block.range.start]);
+        match Query::parse(&block.source) {
+            Ok(query) => {
+                let table = query.run(this_id, docs, schema);
+                if table.rows().is_empty() {
+                    out.push_str("*No matching documents.*\n");
+                } else {
+                    out.push_str(&table.to_markdown());
+                }
+            }
+            Err(e) => out.push_str(&format!("> md-db-query error: {e}\n")),
+        }
+        last = block.range.end;
+    }
+    out.push_str(&body[last..]);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn schema() -> Schema {
+        Schema::from_str(r#"relation "triggers" inverse="caused_by" cardinality="many""#).unwrap()
+    }
+
+    fn docs() -> Vec<(String, Document)> {
+        vec![
+            (
+                "ADR-001".into(),
+                Document::from_str("---\ntype: adr\ntitle: Use Postgres\ntriggers: [INC-003]\n---\n").unwrap(),
+            ),
+            (
+                "INC-001".into(),
+                Document::from_str("---\ntype: inc\ntitle: Outage\nstatus: open\ndate: 2025-01-02\ncaused_by: [ADR-001]\n---\n").unwrap(),
+            ),
+            (
+                "INC-002".into(),
+                Document::from_str("---\ntype: inc\ntitle: Old | outage\nstatus: resolved\ncaused_by: [\"./adr-001.md\"]\n---\n").unwrap(),
+            ),
+            (
+                "INC-003".into(),
+                Document::from_str("---\ntype: inc\ntitle: Slow\nstatus: open\ndate: 2025-03-01\n---\n").unwrap(),
+            ),
+            (
+                "INC-004".into(),
+                Document::from_str("---\ntype: inc\ntitle: Unrelated\nstatus: open\n---\n").unwrap(),
+            ),
+        ]
+    }
+
+    #[test]
+    fn test_parse_query() {
+        let q = Query::parse(
+            "# open incidents\ntype: inc\nwhere: status != resolved\nwhere: severity in high, critical\nlinks: caused_by\nsort: -date\nlimit: 5\n",
+        )
+        .unwrap();
+        assert_eq!(q.doc_type.as_deref(), Some("inc"));
+        assert_eq!(q.filters.len(), 2);
+        assert!(matches!(q.filters[1], Filter::FieldIn { ref values, .. } if values.len() == 2));
+        assert_eq!(q.links.as_deref(), Some("caused_by"));
+        assert_eq!(q.sort, Some(("date".into(), true)));
+        assert_eq!(q.limit, Some(5));
+        assert_eq!(q.columns, vec!["id", "title", "status"]);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let err = Query::parse("type: inc\nbogus: 1\n").unwrap_err();
+        assert!(err.contains("line 2") && err.contains("unknown key 'bogus'"), "{err}");
+        assert!(Query::parse("limit: many").unwrap_err().contains("limit must be a number"));
+        assert!(Query::parse("where: status").unwrap_err().contains("invalid condition"));
+        assert!(Query::parse("just text").is_err());
+    }
+
+    #[test]
+    fn test_run_links_both_directions() {
+        let owned = docs();
+        let docs: Vec<(String, &Document)> = owned.iter().map(|(id, d)| (id.clone(), d)).collect();
+        let schema = schema();
+
+        let q = Query::parse("type: inc\nlinks: caused_by\nwhere: status = open\nsort: -date").unwrap();
+        let table = q.run("ADR-001", &docs, Some(&schema));
+        let ids: Vec<&str> = table.rows().iter().map(|r| r[0].as_str()).collect();
+        // INC-003 is linked only through `triggers` on the ADR
+        assert_eq!(ids, vec!["INC-003", "INC-001"]);
+
+        // Without a schema only the forward field counts
+        let table = q.run("ADR-001", &docs, None);
+        assert_eq!(table.rows().len(), 1);
+    }
+
+    #[test]
+    fn test_expand_queries() {
+        let owned = docs();
+        let docs: Vec<(String, &Document)> = owned.iter().map(|(id, d)| (id.clone(), d)).collect();
+        let body = "# Incidents\n\n```md-db-query\ntype: inc\nlinks: caused_by\ncolumns: id, title\n```\n\nAfter.\n\n~~~md-db-query\nnope\n~~~\n";
+
+        let out = expand_queries(body, "ADR-001", &docs, None);
+        assert!(out.starts_with("# Incidents\n\n| id | title |"));
+        assert!(out.contains("| INC-002 | Old \\| outage |"));
+        assert!(!out.contains("INC-004"));
+        assert!(out.contains("After.\n"));
+        assert!(out.contains("> md-db-query error: line 1"));
+        assert!(!out.contains("```"));
+    }
+
+    #[test]
+    fn test_find_query_blocks_ignores_other_fences() {
+        let body = "```rust\nlet x = 1;\n```\n\n```md-db-query\ntype: adr\n```\n";
+        let blocks = find_query_blocks(body);
+        assert_eq!(blocks.len(), 1);
+        assert_eq!(blocks[0].line, 5);
+        assert_eq!(blocks[0].source, "type: adr\n");
+    }
+}
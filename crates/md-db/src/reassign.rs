@@ -0,0 +1,191 @@
+//! Bulk ownership handoff (`md-db reassign --from @bob --to @alice`).
+//!
+//! Replaces one user with another in frontmatter user fields and in table
+//! columns such as `Owner`. Handles compare without the leading `@` and
+//! case-insensitively; each replaced value keeps its own `@` style. In a list,
+//! the old user is replaced where they stood, or dropped if the new one is
+//! already listed.
+
+use std::path::PathBuf;
+
+use regex::Regex;
+use serde_yaml::Value;
+
+use crate::document::Document;
+use crate::error::Result;
+use crate::graph::path_to_id;
+use crate::schema::{FieldType, Schema};
+
+#[derive(Debug, Clone)]
+pub struct ReassignOptions<'a> {
+    pub from: &'a str,
+    pub to: &'a str,
+    /// Frontmatter fields to reassign; every `user`/`user[]` field of the
+    /// document's type when empty.
+    pub fields: &'a [String],
+    /// Table columns to reassign, in any section (case-insensitive).
+    pub columns: &'a [String],
+}
+
+/// A document with the user replaced, not yet saved.
+#[derive(Debug, Clone)]
+pub struct Reassignment {
+    pub doc_id: String,
+    pub path: PathBuf,
+    /// Where the user was replaced: field names, then `Section › Column (row N)`.
+    pub locations: Vec<String>,
+    pub doc: Document,
+}
+
+/// The documents among `files` where `opts.from` appears in a reassigned
+/// field or column, with the change made. Files that do not parse are skipped.
+pub fn plan_reassign(files: &[PathBuf], schema: &Schema, opts: &ReassignOptions) -> Result<Vec<Reassignment>> {
+    let from = handle(opts.from);
+    let to = handle(opts.to);
+    let mut plan = Vec::new();
+    for path in files {
+        let Ok(mut doc) = Document::from_file(path) else { continue };
+        let mut locations = reassign_fields(&mut doc, schema, opts, from, to);
+        locations.extend(reassign_tables(&mut doc, opts.columns, from, to)?);
+        if !locations.is_empty() {
+            plan.push(Reassignment {
+                doc_id: path_to_id(path),
+                path: path.clone(),
+                locations,
+                doc,
+            });
+        }
+    }
+    Ok(plan)
+}
+
+fn reassign_fields(doc: &mut Document, schema: &Schema, opts: &ReassignOptions, from: &str, to: &str) -> Vec<String> {
+    let Some(fm) = doc.frontmatter.clone() else {
+        return Vec::new();
+    };
+    let fields: Vec<String> = if opts.fields.is_empty() {
+        let type_def = fm.get_display("type").and_then(|t| schema.get_type(&t));
+        type_def.map_or_else(Vec::new, |t| {
+            t.fields
+                .iter()
+                .filter(|f| matches!(f.field_type, FieldType::User | FieldType::UserArray))
+                .map(|f| f.name.clone())
+                .collect()
+        })
+    } else {
+        opts.fields.to_vec()
+    };
+
+    let mut locations = Vec::new();
+    for field in fields {
+        let value = match fm.get(&field) {
+            Some(Value::String(s)) if handle(s).eq_ignore_ascii_case(from) => Value::String(replace_handle(s, to)),
+            Some(Value::Sequence(items)) => {
+                let listed = |user: &str| {
+                    items.iter().any(|i| i.as_str().is_some_and(|s| handle(s).eq_ignore_ascii_case(user)))
+                };
+                if !listed(from) {
+                    continue;
+                }
+                let keep_new = !listed(to);
+                let items = items
+                    .iter()
+                    .filter_map(|item| match item.as_str() {
+                        Some(s) if handle(s).eq_ignore_ascii_case(from) => {
+                            keep_new.then(|| Value::String(replace_handle(s, to)))
+                        }
+                        _ => Some(item.clone()),
+                    })
+                    .collect();
+                Value::Sequence(items)
+            }
+            _ => continue,
+        };
+        doc.set_field(&field, value);
+        locations.push(field);
+    }
+    locations
+}
+
+fn reassign_tables(doc: &mut Document, columns: &[String], from: &str, to: &str) -> Result<Vec<String>> {
+    let re = Regex::new(&format!(r"(?i)(^|[^\w@.-])(@?){}($|[^\w.-])", regex::escape(from))).unwrap();
+    let mut locations = Vec::new();
+    for section in doc.sections() {
+        for (index, table) in section.tables().iter().enumerate() {
+            for column in table.headers().iter().filter(|h| columns.iter().any(|c| c.eq_ignore_ascii_case(h))) {
+                for row in 0..table.rows().len() {
+                    let cell = table.get_cell(column, row).unwrap_or_default();
+                    let replaced = re.replace_all(cell, format!("${{1}}${{2}}{to}${{3}}").as_str());
+                    if replaced != cell {
+                        doc.set_table_cell(&section.heading, index, column, row, &replaced)?;
+                        locations.push(format!("{} › {column} (row {})", section.heading, row + 1));
+                    }
+                }
+            }
+        }
+    }
+    Ok(locations)
+}
+
+/// A user reference without its leading `@`.
+fn handle(user: &str) -> &str {
+    user.trim().trim_start_matches('@')
+}
+
+/// `to` in the `@` style of `old`.
+fn replace_handle(old: &str, to: &str) -> String {
+    if old.trim().starts_with('@') {
+        format!("@{to}")
+    } else {
+        to.to_string()
+    }
+}
+
+/// Save every reassigned document.
+pub fn apply(plan: &[Reassignment]) -> Result<()> {
+    plan.iter().try_for_each(|r| r.doc.save())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plan_reassign() {
+        let dir = tempfile::tempdir().unwrap();
+        let write = |name: &str, text: &str| {
+            let path = dir.path().join(name);
+            std::fs::write(&path, text).unwrap();
+            path
+        };
+        let files = vec![
+            write(
+                "adr-001.md",
+                "---\ntype: adr\nauthor: '@bob'\nreviewers:\n- '@carol'\n- bob\n---\n\n# Actions\n\n\
+                 | Action | Owner |\n|---|---|\n| Add alert | @Bob, @carol |\n| Runbook | @bobby |\n",
+            ),
+            write("adr-002.md", "---\ntype: adr\nauthor: '@carol'\nreviewers:\n- '@bob'\n- '@alice'\n---\n"),
+            write("adr-003.md", "---\ntype: adr\nauthor: '@carol'\n---\n"),
+        ];
+        let schema = Schema::from_str(
+            "type \"adr\" {\n    field \"author\" type=\"user\"\n    field \"reviewers\" type=\"user[]\"\n}\n",
+        )
+        .unwrap();
+        let columns = vec!["owner".to_string()];
+        let opts = ReassignOptions { from: "@bob", to: "alice", fields: &[], columns: &columns };
+        let plan = plan_reassign(&files, &schema, &opts).unwrap();
+
+        assert_eq!(plan.len(), 2);
+        assert_eq!(plan[0].locations, vec!["author", "reviewers", "Actions › Owner (row 1)"]);
+        let fm = plan[0].doc.frontmatter.as_ref().unwrap();
+        assert_eq!(fm.get_display("author").as_deref(), Some("@alice"));
+        assert_eq!(fm.get("reviewers").unwrap()[1].as_str(), Some("alice"));
+        assert!(plan[0].doc.body.contains("| Add alert | @alice, @carol |\n| Runbook | @bobby |"));
+        // Already listed: the old user is dropped
+        let reviewers = plan[1].doc.frontmatter.as_ref().unwrap().get("reviewers").unwrap();
+        assert_eq!(reviewers.as_sequence().unwrap().len(), 1);
+
+        apply(&plan).unwrap();
+        assert!(plan_reassign(&files, &schema, &opts).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,223 @@
+//! Allowlist HTML sanitizer for exported pages.
+//!
+//! Markdown may embed raw HTML, which the HTML export passes through this filter.
+//! Tags in [`ALLOWED_TAGS`] keep their allowed attributes; other tags lose their
+//! markup but keep their text, except [`DROPPED_WITH_CONTENT`] elements (`script`,
+//! `style`, `iframe`, …), which go entirely. `href` and `src` keep only `http`,
+//! `https`, `mailto`, and relative URLs. Comments are dropped, and tags a
+//! document leaves open are closed, so one page can't swallow the page around it.
+//!
+//! `md-db export --unsafe-html` skips the filter. Validation warns (`H001`) about
+//! raw HTML in managed documents that the filter would remove.
+
+use comrak::nodes::NodeValue;
+use regex::Regex;
+
+/// Tags kept in exported pages.
+pub const ALLOWED_TAGS: &[&str] = &[
+    "a", "abbr", "b", "blockquote", "br", "caption", "cite", "code", "dd", "del", "details", "dfn", "div", "dl", "dt",
+    "em", "figcaption", "figure", "h1", "h2", "h3", "h4", "h5", "h6", "hr", "i", "img", "ins", "kbd", "li", "mark",
+    "ol", "p", "pre", "q", "s", "samp", "small", "span", "strong", "sub", "summary", "sup", "table", "tbody", "td",
+    "tfoot", "th", "thead", "time", "tr", "u", "ul", "var", "wbr",
+];
+
+/// Elements removed together with everything inside them.
+pub const DROPPED_WITH_CONTENT: &[&str] = &[
+    "applet", "embed", "frame", "frameset", "iframe", "math", "noembed", "noframes", "noscript", "object", "plaintext",
+    "script", "select", "style", "svg", "template", "textarea", "title", "xmp",
+];
+
+const VOID_TAGS: &[&str] = &["br", "hr", "img", "wbr"];
+
+/// Attributes kept on every allowed tag.
+const GLOBAL_ATTRIBUTES: &[&str] = &["class", "dir", "lang", "title"];
+
+/// Attributes kept on particular tags.
+const TAG_ATTRIBUTES: &[(&str, &[&str])] = &[
+    ("a", &["href"]),
+    ("details", &["open"]),
+    ("img", &["alt", "height", "src", "width"]),
+    ("ol", &["start", "type"]),
+    ("td", &["align", "colspan", "rowspan"]),
+    ("th", &["align", "colspan", "rowspan", "scope"]),
+    ("time", &["datetime"]),
+];
+
+const URL_ATTRIBUTES: &[&str] = &["href", "src"];
+
+const URL_SCHEMES: &[&str] = &["http", "https", "mailto"];
+
+/// Raw HTML in a markdown body that [`sanitize`] would remove.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UnsafeHtml {
+    /// 1-based line within the body.
+    pub line: usize,
+    /// What goes, e.g. `<script> element` or `onclick attribute on <img>`.
+    pub what: String,
+}
+
+/// Filter rendered HTML down to the allowlist.
+pub fn sanitize(html: &str) -> String {
+    clean(html, &mut Vec::new())
+}
+
+/// Raw HTML blocks and inline tags in `body` that the export would remove, in order.
+pub fn find_unsafe_html(body: &str) -> Vec<UnsafeHtml> {
+    let arena = comrak::Arena::new();
+    let root = comrak::parse_document(&arena, body, &crate::ast_util::comrak_opts());
+    let mut found = Vec::new();
+    for node in root.descendants() {
+        let data = node.data.borrow();
+        let literal = match data.value {
+            NodeValue::HtmlBlock(ref block) => &block.literal,
+            NodeValue::HtmlInline(ref html) => html,
+            _ => continue,
+        };
+        let mut removed = Vec::new();
+        clean(literal, &mut removed);
+        let line = data.sourcepos.start.line;
+        found.extend(removed.into_iter().map(|what| UnsafeHtml { line, what }));
+    }
+    found
+}
+
+/// [`sanitize`], noting in `removed` each tag and attribute it takes out.
+fn clean(html: &str, removed: &mut Vec<String>) -> String {
+    let tag_re = Regex::new(
+        r#"^<(/?)([A-Za-z][A-Za-z0-9-]*)((?:\s+[^\s"'>/=]+(?:\s*=\s*(?:"[^"]*"|'[^']*'|[^\s"'=<>`]+))?)*)\s*/?>"#,
+    )
+    .unwrap();
+    let mut out = String::with_capacity(html.len());
+    let mut open: Vec<String> = Vec::new();
+    let mut rest = html;
+    while let Some(at) = rest.find('<') {
+        out.push_str(&rest[..at]);
+        rest = &rest[at..];
+        if let Some(comment) = rest.strip_prefix("<!--") {
+            rest = comment.find("-->").map_or("", |end| &comment[end + 3..]);
+            continue;
+        }
+        let Some(caps) = tag_re.captures(rest) else {
+            // Not a tag (`a < b`, `<!DOCTYPE`): keep it as text
+            out.push_str("&lt;");
+            rest = &rest[1..];
+            continue;
+        };
+        let closing = !caps[1].is_empty();
+        let name = caps[2].to_ascii_lowercase();
+        let attrs = caps.get(3).map_or("", |m| m.as_str());
+        rest = &rest[caps[0].len()..];
+
+        if DROPPED_WITH_CONTENT.contains(&name.as_str()) {
+            if !closing {
+                removed.push(format!("<{name}> element"));
+                rest = skip_past_close(rest, &name);
+            }
+            continue;
+        }
+        if !ALLOWED_TAGS.contains(&name.as_str()) {
+            if !closing {
+                removed.push(format!("<{name}> tag"));
+            }
+            continue;
+        }
+        if closing {
+            // Close whatever is still open inside it; a stray closing tag goes
+            if let Some(pos) = open.iter().rposition(|t| *t == name) {
+                for tag in open.drain(pos..).rev() {
+                    out.push_str(&format!("</{tag}>"));
+                }
+            }
+            continue;
+        }
+        out.push_str(&format!("<{name}{}>", attributes(&name, attrs, removed)));
+        if !VOID_TAGS.contains(&name.as_str()) {
+            open.push(name);
+        }
+    }
+    out.push_str(rest);
+    for tag in open.into_iter().rev() {
+        out.push_str(&format!("</{tag}>"));
+    }
+    out
+}
+
+/// The allowed attributes of a `tag` out of its raw attribute text, double-quoted.
+fn attributes(tag: &str, raw: &str, removed: &mut Vec<String>) -> String {
+    let attr_re = Regex::new(r#"([^\s"'>/=]+)(?:\s*=\s*(?:"([^"]*)"|'([^']*)'|([^\s"'=<>`]+)))?"#).unwrap();
+    let allowed = TAG_ATTRIBUTES.iter().find(|(t, _)| *t == tag).map_or(&[][..], |(_, attrs)| attrs);
+    let mut out = String::new();
+    for caps in attr_re.captures_iter(raw) {
+        let name = caps[1].to_ascii_lowercase();
+        let value = caps.get(2).or(caps.get(3)).or(caps.get(4)).map_or("", |m| m.as_str());
+        if !GLOBAL_ATTRIBUTES.contains(&name.as_str()) && !allowed.contains(&name.as_str()) {
+            removed.push(format!("{name} attribute on <{tag}>"));
+            continue;
+        }
+        if URL_ATTRIBUTES.contains(&name.as_str()) && !safe_url(value) {
+            removed.push(format!("{name} URL \"{value}\" on <{tag}>"));
+            continue;
+        }
+        let value = value.replace('"', "&quot;").replace('<', "&lt;").replace('>', "&gt;");
+        out.push_str(&format!(" {name}=\"{value}\""));
+    }
+    out
+}
+
+/// Whether a URL is relative or uses an allowed scheme. Entities are decoded
+/// first, so `jav&#x61;script:` is caught; a value that doesn't decode is refused.
+fn safe_url(value: &str) -> bool {
+    let Ok(decoded) = htmlescape::decode_html(value) else {
+        return false;
+    };
+    // Browsers ignore whitespace and control characters inside the scheme
+    let url: String = decoded
+        .chars()
+        .filter(|c| !c.is_whitespace() && !c.is_control())
+        .collect::<String>()
+        .to_ascii_lowercase();
+    match url.find([':', '/', '?', '#']) {
+        Some(at) if url[at..].starts_with(':') => URL_SCHEMES.contains(&&url[..at]),
+        _ => true,
+    }
+}
+
+/// `rest` after the closing tag of the `name` element it is inside; empty when unclosed.
+fn skip_past_close<'a>(rest: &'a str, name: &str) -> &'a str {
+    // ASCII lowercasing keeps byte offsets
+    let Some(at) = rest.to_ascii_lowercase().find(&format!("</{name}")) else {
+        return "";
+    };
+    let after = &rest[at..];
+    after.find('>').map_or("", |end| &after[end + 1..])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sanitize() {
+        let html = "<p>Hi <script>alert(1)</script><b onclick=\"x()\" class=\"k\">bold</b> \
+                    <a href=\"JaVa&#x53;cript:alert(1)\">bad</a> <a href=\"https://example.com/?a=1&amp;b=2\">ok</a>\
+                    <!-- note --> <font color=red>red</font> 1 < 2</p>\n<details><summary>More</summary><p>open";
+        assert_eq!(
+            sanitize(html),
+            "<p>Hi <b class=\"k\">bold</b> <a>bad</a> <a href=\"https://example.com/?a=1&amp;b=2\">ok</a> \
+             red 1 &lt; 2</p>\n<details><summary>More</summary><p>open</p></details>"
+        );
+        assert_eq!(sanitize("<img src=x onerror=alert(1)><IFRAME src=x></iframe>after"), "<img src=\"x\">after");
+        assert_eq!(sanitize("<div><em>x</div></em>"), "<div><em>x</em></div>");
+        assert!(safe_url("../adr-001.html#options") && safe_url("mailto:a@example.com"));
+        assert!(!safe_url(" data:text/html,x") && !safe_url("java\tscript:x"));
+    }
+
+    #[test]
+    fn test_find_unsafe_html() {
+        let body = "# Notes\n\n<details><summary>Fine</summary>\n\nText\n\n</details>\n\n\
+                    Click <span onclick=\"steal()\">here</span>.\n\n<script>\nalert(1)\n</script>\n";
+        let found = find_unsafe_html(body);
+        let found: Vec<(usize, &str)> = found.iter().map(|u| (u.line, u.what.as_str())).collect();
+        assert_eq!(found, vec![(9, "onclick attribute on <span>"), (11, "<script> element")]);
+    }
+}
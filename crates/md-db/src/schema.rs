@@ -3,6 +3,11 @@ use std::path::Path;
 use kdl::{KdlDocument, KdlNode, KdlValue};
 
 use crate::error::{Error, Result};
+use crate::frontmatter::Frontmatter;
+use crate::locale::Locale;
+use crate::pattern::{PatternCache, RejectedPattern};
+use crate::projection::ProjectionDef;
+use crate::views::ViewDef;
 
 /// A parsed schema containing document type definitions and relation vocabulary.
 #[derive(Debug, Clone)]
@@ -10,9 +15,30 @@ pub struct Schema {
     pub types: Vec<TypeDef>,
     pub relations: Vec<RelationDef>,
     pub ref_formats: Vec<RefFormat>,
+    pub coverage: Vec<CoverageRule>,
+    /// Optional prose-lint rules applied to required sections.
+    pub prose: Option<ProseRules>,
+    /// Declared relation groups, in display order.
+    pub relation_groups: Vec<RelationGroupDef>,
+    /// Optional style rules checked by `md-db lint` (separate from validation).
+    pub lint: Option<LintRules>,
+    /// Optional asset rules from the top-level `assets` block.
+    pub assets: Option<AssetRules>,
+    /// Project default for generated HTML, from the top-level `locale` block.
+    pub locale: Option<Locale>,
+    /// Export index layout and health inputs, from the top-level `index` block.
+    pub index: Option<IndexRules>,
+    /// Default URL template for exported pages (`permalink "/docs/{id}/"`); see [`crate::permalink`].
+    pub permalink: Option<String>,
+    /// Saved views (`view "open-incidents" type="inc" ...`); see [`crate::views`].
+    pub views: Vec<ViewDef>,
+    /// Generated documents (`projection "register" path="register.md" { table ... }`); see [`crate::projection`].
+    pub projections: Vec<ProjectionDef>,
+    /// Field patterns and ref-formats, compiled once.
+    patterns: PatternCache,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct TypeDef {
     pub name: String,
     pub description: Option<String>,
@@ -26,10 +52,153 @@ pub struct TypeDef {
     pub match_pattern: Option<String>,
     pub fields: Vec<FieldDef>,
     pub sections: Vec<SectionDef>,
+    /// Repeated top-level sections matched by heading pattern.
+    pub section_patterns: Vec<SectionPatternDef>,
     pub rules: Vec<RuleDef>,
+    pub promotes: Vec<PromoteDef>,
+    /// Default document order (export index, `list` without `--sort`), highest priority first.
+    pub order_by: Vec<OrderKey>,
+    /// Cross-document checks against linked documents, evaluated by `validate_directory`.
+    pub consistency: Vec<ConsistencyDef>,
+    /// Lifecycle status field and its value meanings; see [`Schema::status_field`].
+    pub status_field: Option<StatusFieldDef>,
+    /// Template file `new` renders instead of the generated skeleton, relative
+    /// to the schema file (`template "templates/adr.md"`).
+    pub template: Option<String>,
+    /// Legal status transitions; see [`Schema::workflow`].
+    pub workflow: Option<WorkflowDef>,
+    /// Whole-document snippets checked by `md-db schema test`.
+    pub examples: Vec<SchemaExample>,
+    /// URL template for this type's exported pages, overriding the top-level one.
+    pub permalink: Option<String>,
 }
 
-#[derive(Debug, Clone)]
+impl TypeDef {
+    /// Whether this is a singleton type whose `match` pattern accepts `filename`.
+    /// Patterns are globs (`CHANGELOG*.md`); a plain name matches itself.
+    pub fn matches_filename(&self, filename: &str) -> bool {
+        let Some(ref pattern) = self.match_pattern else {
+            return false;
+        };
+        self.singleton
+            && glob::Pattern::new(pattern).map_or(pattern == filename, |p| p.matches(filename))
+    }
+}
+
+/// Legal transitions of a type's status field, enforced by `set` and `batch`:
+///
+/// ```kdl
+/// workflow {
+///     transition "proposed" to="accepted,rejected"
+///     transition "accepted" to="superseded"
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct WorkflowDef {
+    /// The field the workflow governs (`workflow field="state"`); the type's
+    /// status field when unset.
+    pub field: Option<String>,
+    /// Allowed targets per state, in declaration order.
+    pub transitions: Vec<(String, Vec<String>)>,
+}
+
+impl WorkflowDef {
+    /// Whether `from` may change to `to`. A state that is only ever a target is
+    /// final; a value the workflow never names is not constrained.
+    pub fn allows(&self, from: &str, to: &str) -> bool {
+        if from == to {
+            return true;
+        }
+        match self.transitions.iter().find(|(state, _)| state == from) {
+            Some((_, targets)) => targets.iter().any(|t| t == to),
+            None => !self.states().any(|s| s == from),
+        }
+    }
+
+    /// Every state named in the workflow.
+    pub fn states(&self) -> impl Iterator<Item = &String> {
+        self.transitions.iter().flat_map(|(from, to)| std::iter::once(from).chain(to))
+    }
+
+    /// Why `field: from → to` is not allowed, or `None` when it is.
+    pub fn check(&self, field: &str, from: &str, to: &str) -> Option<String> {
+        if self.allows(from, to) {
+            return None;
+        }
+        let allowed = match self.transitions.iter().find(|(state, _)| state == from) {
+            Some((_, targets)) => format!("{from} may become {}", targets.join(", ")),
+            None => format!("{from} is final"),
+        };
+        Some(format!("{field}: {from} \u{2192} {to} is not an allowed transition ({allowed})"))
+    }
+}
+
+/// Status values that mean "in effect" for types without a `status-field`.
+pub const DEFAULT_ACTIVE_STATUSES: &[&str] = &["accepted"];
+/// Status values that mean "retired" for types without a `status-field`.
+pub const DEFAULT_TERMINAL_STATUSES: &[&str] =
+    &["rejected", "deprecated", "superseded", "resolved", "closed", "done", "cancelled"];
+
+/// A type's lifecycle status field and what its values mean to tooling:
+/// `status-field "status" active="accepted" terminal="rejected,superseded"`.
+/// A status that is neither active nor terminal is open (proposed, investigating).
+#[derive(Debug, Clone, PartialEq)]
+pub struct StatusFieldDef {
+    pub field: String,
+    /// Values meaning the document is in effect (an accepted decision).
+    pub active: Vec<String>,
+    /// Values meaning the document is retired (superseded, rejected, resolved).
+    /// Graphs draw these documents dashed.
+    pub terminal: Vec<String>,
+    /// Value `md-db deprecate` sets; "deprecated" when unset.
+    pub deprecated: Option<String>,
+    /// Value `md-db deprecate --superseded-by` sets; "superseded" when unset.
+    pub superseded: Option<String>,
+}
+
+impl Default for StatusFieldDef {
+    fn default() -> Self {
+        Self {
+            field: "status".into(),
+            active: DEFAULT_ACTIVE_STATUSES.iter().map(|s| s.to_string()).collect(),
+            terminal: DEFAULT_TERMINAL_STATUSES.iter().map(|s| s.to_string()).collect(),
+            deprecated: None,
+            superseded: None,
+        }
+    }
+}
+
+impl StatusFieldDef {
+    pub fn is_active(&self, status: &str) -> bool {
+        self.active.iter().any(|s| s == status)
+    }
+
+    pub fn is_terminal(&self, status: &str) -> bool {
+        self.terminal.iter().any(|s| s == status)
+    }
+
+    /// Neither active nor terminal.
+    pub fn is_open(&self, status: &str) -> bool {
+        !self.is_active(status) && !self.is_terminal(status)
+    }
+
+    pub fn deprecated_value(&self) -> &str {
+        self.deprecated.as_deref().unwrap_or("deprecated")
+    }
+
+    pub fn superseded_value(&self) -> &str {
+        self.superseded.as_deref().unwrap_or("superseded")
+    }
+}
+
+/// One key of a type's default ordering. Ties fall through to the next key, then the document ID.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OrderKey {
+    pub field: String,
+    pub desc: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub struct FieldDef {
     pub name: String,
     pub field_type: FieldType,
@@ -37,6 +206,48 @@ pub struct FieldDef {
     pub pattern: Option<String>,
     pub description: Option<String>,
     pub default: Option<String>,
+    /// Timestamp maintained by [`crate::normalize`] (`auto="created"` / `auto="updated"`).
+    pub auto: Option<AutoTimestamp>,
+    /// Earliest allowed value for `date`/`datetime` fields (ISO 8601, inclusive).
+    pub min: Option<String>,
+    /// Latest allowed value for `date`/`datetime` fields (ISO 8601, inclusive).
+    pub max: Option<String>,
+    /// Reject `date`/`datetime` values later than today / now.
+    pub not_future: bool,
+    /// For `ref`/`ref[]` fields: document types the refs may point at (`target-type="adr"`).
+    /// Empty means any type.
+    pub target_types: Vec<String>,
+    /// Former names (`alias "assignee"`). A document that still uses one validates and
+    /// reads as this field, with a warning to rename it.
+    pub aliases: Vec<String>,
+    /// Values checked by `md-db schema test`.
+    pub examples: Vec<SchemaExample>,
+}
+
+impl FieldDef {
+    /// Human-readable `min`/`max`/`not-future` bounds, e.g. "≥ 2020-01-01, not in the future".
+    pub fn range_summary(&self) -> Option<String> {
+        let mut parts = Vec::new();
+        if let Some(ref min) = self.min {
+            parts.push(format!("≥ {min}"));
+        }
+        if let Some(ref max) = self.max {
+            parts.push(format!("≤ {max}"));
+        }
+        if self.not_future {
+            parts.push("not in the future".to_string());
+        }
+        (!parts.is_empty()).then(|| parts.join(", "))
+    }
+}
+
+/// When an `auto` field is stamped with the current date.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AutoTimestamp {
+    /// Filled once, when missing.
+    Created,
+    /// Filled when missing and refreshed on every save.
+    Updated,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -50,6 +261,10 @@ pub enum FieldType {
     RefArray,
     User,
     UserArray,
+    /// ISO 8601 calendar date (`2024-03-01`).
+    Date,
+    /// ISO 8601 date and time (`2024-03-01T09:30:00Z`, offset optional).
+    DateTime,
 }
 
 impl std::fmt::Display for FieldType {
@@ -64,12 +279,86 @@ impl std::fmt::Display for FieldType {
             FieldType::RefArray => write!(f, "ref[]"),
             FieldType::User => write!(f, "user"),
             FieldType::UserArray => write!(f, "user[]"),
+            FieldType::Date => write!(f, "date"),
+            FieldType::DateTime => write!(f, "datetime"),
         }
     }
 }
 
+/// `consistent-with "date" relation="supersedes" op=">="`: a field of this type compared
+/// with a field of every document linked through `relation`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConsistencyDef {
+    pub field: String,
+    pub relation: String,
+    /// Field read on the linked document; defaults to `field`.
+    pub other_field: String,
+    pub op: ConsistencyOp,
+    /// "error" or "warning". Defaults to "error".
+    pub severity: String,
+}
+
+impl ConsistencyDef {
+    /// Human-readable form, e.g. `date >= supersedes.date`.
+    pub fn describe(&self) -> String {
+        format!("{} {} {}.{}", self.field, self.op, self.relation, self.other_field)
+    }
+}
+
+/// Comparison used by a [`ConsistencyDef`]. Values compare as dates, then numbers, then text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConsistencyOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl ConsistencyOp {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "==" | "=" => Some(Self::Eq),
+            "!=" => Some(Self::Ne),
+            "<" => Some(Self::Lt),
+            "<=" => Some(Self::Le),
+            ">" => Some(Self::Gt),
+            ">=" => Some(Self::Ge),
+            _ => None,
+        }
+    }
+
+    /// Whether `ordering` (this document's value vs. the linked one) satisfies the operator.
+    pub fn holds(self, ordering: std::cmp::Ordering) -> bool {
+        use std::cmp::Ordering::*;
+        match self {
+            Self::Eq => ordering == Equal,
+            Self::Ne => ordering != Equal,
+            Self::Lt => ordering == Less,
+            Self::Le => ordering != Greater,
+            Self::Gt => ordering == Greater,
+            Self::Ge => ordering != Less,
+        }
+    }
+}
+
+impl std::fmt::Display for ConsistencyOp {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            Self::Eq => "==",
+            Self::Ne => "!=",
+            Self::Lt => "<",
+            Self::Le => "<=",
+            Self::Gt => ">",
+            Self::Ge => ">=",
+        };
+        write!(f, "{s}")
+    }
+}
+
 /// A conditional validation rule: when a field equals a value, other fields become required.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct RuleDef {
     pub name: String,
     pub when_field: String,
@@ -77,43 +366,287 @@ pub struct RuleDef {
     pub then_required: Vec<String>,
 }
 
+/// Writing-quality thresholds from the top-level `prose` block. Violations are warnings.
+#[derive(Debug, Clone, Default)]
+pub struct ProseRules {
+    /// Longest allowed sentence, in words.
+    pub max_sentence_words: Option<usize>,
+    /// Highest allowed share of passive-voice sentences (0.0–1.0).
+    pub max_passive_ratio: Option<f64>,
+    /// Lowest allowed Flesch reading-ease score.
+    pub min_readability: Option<f64>,
+    pub require_verbs: Vec<RequireVerbs>,
+}
+
+/// Asset checks from the top-level `assets { }` block.
+#[derive(Debug, Clone, Default)]
+pub struct AssetRules {
+    /// `orphans "docs/assets"` — folders (relative to the validated directory) whose
+    /// files must be linked from some document.
+    pub orphan_folders: Vec<String>,
+}
+
+/// Export index layout from the top-level `index` block:
+///
+/// ```kdl
+/// index group-by="status" order-by="health" {
+///     review-by "next_review"
+///     expires "valid_until" warn-days=30
+///     weights error=30 warning=5 overdue=25 expiring=15 incomplete=20
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct IndexRules {
+    /// How documents are split into collapsible groups.
+    pub group_by: IndexKey,
+    /// Order within a group; `None` keeps each type's `order-by`, then ID.
+    pub order_by: Option<IndexKey>,
+    /// Date field whose value, once past, makes the review overdue.
+    pub review_field: Option<String>,
+    /// Date field flagged from `warn_days` before it until it is renewed.
+    pub expires_field: Option<String>,
+    pub warn_days: i64,
+    /// Date field recency is measured from; defaults to the type's `auto="updated"`
+    /// field, else `updated`.
+    pub recency_field: Option<String>,
+    pub weights: HealthWeights,
+}
+
+impl Default for IndexRules {
+    fn default() -> Self {
+        Self {
+            group_by: IndexKey::Type,
+            order_by: None,
+            review_field: None,
+            expires_field: None,
+            warn_days: 30,
+            recency_field: None,
+            weights: HealthWeights::default(),
+        }
+    }
+}
+
+/// What the export index groups or orders documents by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IndexKey {
+    Type,
+    /// The type's status field.
+    Status,
+    /// Newest first, in age buckets.
+    Recency,
+    /// Lowest health score first.
+    Health,
+}
+
+impl IndexKey {
+    fn parse(s: &str) -> Option<Self> {
+        match s {
+            "type" => Some(Self::Type),
+            "status" => Some(Self::Status),
+            "recency" => Some(Self::Recency),
+            "health" => Some(Self::Health),
+            _ => None,
+        }
+    }
+}
+
+/// Points a document's health score (out of 100) loses per finding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HealthWeights {
+    pub error: u32,
+    pub warning: u32,
+    pub overdue: u32,
+    pub expiring: u32,
+    /// Lost in full at 0% completeness, pro rata above it.
+    pub incomplete: u32,
+}
+
+impl Default for HealthWeights {
+    fn default() -> Self {
+        Self {
+            error: 30,
+            warning: 5,
+            overdue: 25,
+            expiring: 15,
+            incomplete: 20,
+        }
+    }
+}
+
+/// Style rules from the top-level `lint { }` block. Each rule carries its own severity.
+#[derive(Debug, Clone, Default)]
+pub struct LintRules {
+    pub rules: Vec<LintRule>,
+}
+
 #[derive(Debug, Clone)]
+pub struct LintRule {
+    pub kind: LintKind,
+    /// "error" or "warning". Defaults to "warning".
+    pub severity: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum LintKind {
+    /// `heading-case "sentence"` or `heading-case "title"`.
+    HeadingCase(HeadingCase),
+    /// `max-line-length 100` — prose lines only; code, tables, and unbreakable lines are skipped.
+    MaxLineLength(usize),
+    /// `table-trailing-whitespace` — table rows ending in spaces or tabs.
+    TableTrailingWhitespace,
+    /// `duplicate-headings` — the same heading twice under the same parent.
+    DuplicateHeadings,
+    /// `empty-sections` — a heading with no content and no subsections.
+    EmptySections,
+    /// `frontmatter-order "type" "title" ...` — keys in this relative order;
+    /// with no keys, the document type's field declaration order.
+    FrontmatterOrder(Vec<String>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum HeadingCase {
+    Sentence,
+    Title,
+}
+
+/// A section that must use at least one of the listed verbs
+/// (e.g. "Decision" must say what we "will" do).
+#[derive(Debug, Clone)]
+pub struct RequireVerbs {
+    pub section: String,
+    pub verbs: Vec<String>,
+}
+
+/// A body value mirrored into a frontmatter field by `sync --promote`.
+/// The body is authoritative; the frontmatter copy is for tools that only read frontmatter.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PromoteDef {
+    /// Frontmatter field that receives the value.
+    pub field: String,
+    /// Section heading holding the source value.
+    pub section: String,
+    pub source: PromoteSource,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum PromoteSource {
+    /// A cell in the nth table of the section.
+    TableCell {
+        table: usize,
+        column: String,
+        row: usize,
+    },
+    /// A (dotted) key in the first `yaml`-fenced code block of the section.
+    YamlKey(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub struct SectionDef {
     pub name: String,
     pub required: bool,
     pub description: Option<String>,
+    /// Alternative headings rewritten to `name` on normalization (`alias "Background"`).
+    pub aliases: Vec<String>,
     pub children: Vec<SectionDef>,
     pub table: Option<TableDef>,
     pub content: Option<ContentDef>,
     pub list: Option<ListDef>,
     pub diagram: Option<DiagramDef>,
+    /// Repeated subsections matched by heading pattern.
+    pub section_patterns: Vec<SectionPatternDef>,
+    /// Section bodies checked by `md-db schema test`.
+    pub examples: Vec<SchemaExample>,
 }
 
+/// A snippet declared with `example` (must validate cleanly) or
+/// `counter-example` (must fail), the schema's own unit tests.
+///
+/// ```kdl
+/// field "status" type="enum" {
+///     values "proposed" "accepted"
+///     example "accepted"
+///     counter-example "approved" expect="F021"
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct SchemaExample {
+    pub source: String,
+    pub counter: bool,
+    /// Codes a counter-example must produce (`expect="F021,F020"`); when empty,
+    /// any error will do.
+    pub expect: Vec<String>,
+}
+
+/// A repeated section: every heading fully matching `pattern` (e.g. `Day \d+`)
+/// counts towards `min`/`max` and must satisfy the constraints in `section`.
 #[derive(Debug, Clone)]
+pub struct SectionPatternDef {
+    pub pattern: String,
+    pub regex: regex::Regex,
+    pub min: Option<usize>,
+    pub max: Option<usize>,
+    /// Constraints applied to each matching section; `name` holds the pattern.
+    pub section: SectionDef,
+}
+
+// `regex` is compiled from `pattern`
+impl PartialEq for SectionPatternDef {
+    fn eq(&self, other: &Self) -> bool {
+        self.pattern == other.pattern && self.min == other.min && self.max == other.max && self.section == other.section
+    }
+}
+
+impl SectionPatternDef {
+    /// Whether a heading (trimmed) matches the pattern in full.
+    pub fn matches(&self, heading: &str) -> bool {
+        self.regex.is_match(heading.trim())
+    }
+}
+
+/// Compile a section heading pattern, anchored to match the whole heading.
+pub fn section_pattern_regex(pattern: &str) -> std::result::Result<regex::Regex, regex::Error> {
+    regex::RegexBuilder::new(&format!("^(?:{pattern})$"))
+        .size_limit(crate::pattern::MAX_COMPILED_SIZE)
+        .nest_limit(crate::pattern::MAX_NESTING)
+        .build()
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub struct ContentDef {
     pub min_paragraphs: Option<usize>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct ListDef {
     pub required: bool,
     pub min_items: Option<usize>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct DiagramDef {
     pub required: bool,
     pub diagram_type: Option<String>,
+    /// Syntax-check mermaid and D2 sources (`check=#true`), see [`crate::diagram`].
+    pub check: bool,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct TableDef {
     pub required: bool,
     pub description: Option<String>,
     pub columns: Vec<ColumnDef>,
+    /// Column the rows must be sorted by (`ordered-by "Time" ascending=#true`).
+    pub ordered_by: Option<OrderedBy>,
 }
 
-#[derive(Debug, Clone)]
+/// A table's row-order constraint. Cells compare as dates, times, numbers, then text.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OrderedBy {
+    pub column: String,
+    pub ascending: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub struct ColumnDef {
     pub name: String,
     pub col_type: FieldType,
@@ -123,7 +656,7 @@ pub struct ColumnDef {
 
 /// A user-defined relationship type. Defined once at schema level,
 /// available as frontmatter fields on all document types.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct RelationDef {
     /// The frontmatter field name (e.g. "supersedes").
     pub name: String,
@@ -134,6 +667,27 @@ pub struct RelationDef {
     pub description: Option<String>,
     /// If true, cycles through this relation are reported as errors.
     pub acyclic: Option<bool>,
+    /// Display group (e.g. "lifecycle"), used to organize describe, inspect, and export.
+    pub group: Option<String>,
+    /// Document types the relation field may point at (`target-type="adr"`). Empty
+    /// means any type. Checked on the relation's own field, not its inverse.
+    pub target_types: Vec<String>,
+}
+
+/// `relation-group "lifecycle" description="..."`; declaration order is display order.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RelationGroupDef {
+    pub name: String,
+    pub description: Option<String>,
+}
+
+/// Relations sharing a group, as returned by [`Schema::grouped_relations`].
+#[derive(Debug, Clone)]
+pub struct RelationGroup<'a> {
+    /// `None` for relations without a group.
+    pub name: Option<&'a str>,
+    pub description: Option<&'a str>,
+    pub relations: Vec<&'a RelationDef>,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -152,7 +706,43 @@ impl RelationDef {
     }
 }
 
-#[derive(Debug, Clone)]
+/// A schema-level linkage requirement: every document of a type must be connected
+/// to at least one document through a relation (in either direction).
+#[derive(Debug, Clone, PartialEq)]
+pub struct CoverageRule {
+    /// Document type the rule applies to (e.g. "inc").
+    pub doc_type: String,
+    /// Relation field the document must have (e.g. "caused_by"). The inverse
+    /// field on the other document counts too.
+    pub relation: String,
+    /// Only links to documents of this type count (e.g. "adr").
+    pub to_type: Option<String>,
+    /// Only documents with one of these statuses are checked (e.g. ["accepted"]).
+    pub statuses: Vec<String>,
+    /// "error", "warning", or "info". Defaults to "error".
+    pub severity: String,
+}
+
+impl CoverageRule {
+    /// Human-readable form of the rule, e.g. `every accepted opp must-have-relation enabled_by to-type adr`.
+    pub fn describe(&self) -> String {
+        let mut out = String::from("every ");
+        if !self.statuses.is_empty() {
+            out.push_str(&self.statuses.join("|"));
+            out.push(' ');
+        }
+        out.push_str(&self.doc_type);
+        out.push_str(" must-have-relation ");
+        out.push_str(&self.relation);
+        if let Some(ref t) = self.to_type {
+            out.push_str(" to-type ");
+            out.push_str(t);
+        }
+        out
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub struct RefFormat {
     pub name: String,
     pub pattern: String,
@@ -178,12 +768,60 @@ impl Schema {
         let mut types = Vec::new();
         let mut relations = Vec::new();
         let mut ref_formats = Vec::new();
+        let mut coverage = Vec::new();
+        let mut prose = None;
+        let mut relation_groups: Vec<RelationGroupDef> = Vec::new();
+        let mut lint = None;
+        let mut assets = None;
+        let mut locale = None;
+        let mut index = None;
+        let mut permalink = None;
+        let mut views: Vec<ViewDef> = Vec::new();
+        let mut projections: Vec<ProjectionDef> = Vec::new();
 
         for node in doc.nodes() {
             match node.name().value() {
                 "type" => types.push(parse_type_def(node)?),
                 "relation" => relations.push(parse_relation_def(node)?),
                 "ref-format" => ref_formats.extend(parse_ref_formats(node)?),
+                "coverage" => coverage.extend(parse_coverage_rules(node)?),
+                "prose" => prose = Some(parse_prose_rules(node)?),
+                "lint" => lint = Some(parse_lint_rules(node)?),
+                "assets" => assets = Some(parse_asset_rules(node)?),
+                "locale" => locale = Some(parse_locale(node)?),
+                "index" => index = Some(parse_index_rules(node)?),
+                "permalink" => permalink = Some(parse_permalink(node, "schema")?),
+                "view" => {
+                    let view = crate::views::parse_view(node)?;
+                    if views.iter().any(|v| v.name == view.name) {
+                        return Err(Error::SchemaParse(format!("view '{}' is declared twice", view.name)));
+                    }
+                    views.push(view);
+                }
+                "projection" => {
+                    let projection = crate::projection::parse_projection(node)?;
+                    if projections.iter().any(|p| p.name == projection.name) {
+                        return Err(Error::SchemaParse(format!(
+                            "projection '{}' is declared twice",
+                            projection.name
+                        )));
+                    }
+                    projections.push(projection);
+                }
+                "relation-group" => {
+                    let name = get_string_arg(node).ok_or_else(|| {
+                        Error::SchemaParse("relation-group node missing name".into())
+                    })?;
+                    if relation_groups.iter().any(|g| g.name == name) {
+                        return Err(Error::SchemaParse(format!(
+                            "relation-group '{name}' is declared twice"
+                        )));
+                    }
+                    relation_groups.push(RelationGroupDef {
+                        name,
+                        description: get_string_prop(node, "description"),
+                    });
+                }
                 other => {
                     return Err(Error::SchemaParse(format!(
                         "unknown top-level node: '{other}'"
@@ -192,11 +830,184 @@ impl Schema {
             }
         }
 
-        Ok(Self {
+        let schema = Self {
             types,
             relations,
             ref_formats,
-        })
+            coverage,
+            prose,
+            relation_groups,
+            lint,
+            assets,
+            locale,
+            index,
+            permalink,
+            views,
+            projections,
+            patterns: PatternCache::default(),
+        };
+        // Compile every pattern now; rejected ones are reported by validation
+        schema.rejected_patterns();
+        for (source, target_types) in schema.target_type_constraints() {
+            if let Some(unknown) = target_types.iter().find(|t| schema.get_type(t).is_none()) {
+                return Err(Error::SchemaParse(format!("{source}: unknown target-type '{unknown}'")));
+            }
+        }
+        for t in &schema.types {
+            let Some(ref sf) = t.status_field else { continue };
+            let Some(FieldType::Enum(values)) = t.fields.iter().find(|f| f.name == sf.field).map(|f| &f.field_type)
+            else {
+                continue;
+            };
+            let mut listed = sf.active.iter().chain(&sf.terminal).chain(&sf.deprecated).chain(&sf.superseded);
+            if let Some(unknown) = listed.find(|v| !values.contains(v)) {
+                return Err(Error::SchemaParse(format!(
+                    "status-field in type '{}': '{unknown}' is not a value of field '{}'",
+                    t.name, sf.field
+                )));
+            }
+        }
+        for t in &schema.types {
+            let Some((field, workflow)) = schema.workflow(&t.name) else { continue };
+            let Some(FieldType::Enum(values)) = t.fields.iter().find(|f| f.name == field).map(|f| &f.field_type)
+            else {
+                continue;
+            };
+            if let Some(unknown) = workflow.states().find(|v| !values.contains(v)) {
+                return Err(Error::SchemaParse(format!(
+                    "workflow in type '{}': '{unknown}' is not a value of field '{field}'",
+                    t.name
+                )));
+            }
+        }
+        for t in &schema.types {
+            for f in &t.fields {
+                if let Some(alias) = f.aliases.iter().find(|a| t.fields.iter().any(|other| &other.name == *a)) {
+                    return Err(Error::SchemaParse(format!(
+                        "field '{}' in type '{}': alias '{alias}' is already a field of the type",
+                        f.name, t.name
+                    )));
+                }
+            }
+            for c in &t.consistency {
+                if schema.find_relation(&c.relation).is_none() {
+                    return Err(Error::SchemaParse(format!(
+                        "consistent-with '{}' in type '{}': unknown relation '{}'",
+                        c.field, t.name, c.relation
+                    )));
+                }
+            }
+        }
+        Ok(schema)
+    }
+
+    /// The lifecycle status field of `type_name`: its `status-field` declaration, or
+    /// the `status` field with [`DEFAULT_ACTIVE_STATUSES`] and [`DEFAULT_TERMINAL_STATUSES`].
+    pub fn status_field(&self, type_name: &str) -> StatusFieldDef {
+        self.get_type(type_name)
+            .and_then(|t| t.status_field.clone())
+            .unwrap_or_default()
+    }
+
+    /// The workflow of `type_name` and the field it governs.
+    pub fn workflow(&self, type_name: &str) -> Option<(String, &WorkflowDef)> {
+        let workflow = self.get_type(type_name)?.workflow.as_ref()?;
+        let field = workflow.field.clone().unwrap_or_else(|| self.status_field(type_name).field);
+        Some((field, workflow))
+    }
+
+    /// Why editing a document's frontmatter from `before` to `after` breaks its
+    /// type's workflow, or `None` when it doesn't (or the type has none). Setting
+    /// a status on a document that had none is not a transition.
+    pub fn transition_violation(&self, before: &Frontmatter, after: &Frontmatter) -> Option<String> {
+        let doc_type = after.get_display("type")?;
+        let (field, workflow) = self.workflow(&doc_type)?;
+        let from = before.get_display(&field)?;
+        let to = after.get_display(&field)?;
+        workflow.check(&field, &from, &to)
+    }
+
+    /// Every declared `target-type` list, with where it is declared.
+    fn target_type_constraints(&self) -> Vec<(String, &[String])> {
+        let fields = self.types.iter().flat_map(|t| {
+            t.fields
+                .iter()
+                .map(move |f| (format!("field '{}' in type '{}'", f.name, t.name), f.target_types.as_slice()))
+        });
+        let relations = self.relations.iter().map(|r| (format!("relation '{}'", r.name), r.target_types.as_slice()));
+        fields.chain(relations).filter(|(_, t)| !t.is_empty()).collect()
+    }
+
+    /// Types a ref in `field_name` of a `type_name` document may point at: the
+    /// field's own `target-type`, else the relation's. Empty means any type.
+    pub fn ref_target_types(&self, type_name: &str, field_name: &str) -> &[String] {
+        let field = self
+            .get_type(type_name)
+            .and_then(|t| t.fields.iter().find(|f| f.name == field_name))
+            .filter(|f| !f.target_types.is_empty());
+        if let Some(f) = field {
+            return &f.target_types;
+        }
+        self.relations
+            .iter()
+            .find(|r| r.name == field_name)
+            .map(|r| r.target_types.as_slice())
+            .unwrap_or_default()
+    }
+
+    /// Rename fields a document still stores under an `alias` to their current name,
+    /// for reading. A key already set under the current name wins. Returns the
+    /// `(alias, field)` pairs that were renamed.
+    pub fn apply_aliases(&self, fm: &mut Frontmatter) -> Vec<(String, String)> {
+        let Some(type_def) = fm.get_display("type").and_then(|t| self.get_type(&t)) else {
+            return Vec::new();
+        };
+        let mut renamed = Vec::new();
+        for field in &type_def.fields {
+            for alias in &field.aliases {
+                if fm.has_field(&field.name) {
+                    break;
+                }
+                if let Some(value) = fm.remove(alias) {
+                    fm.set(&field.name, value);
+                    renamed.push((alias.clone(), field.name.clone()));
+                }
+            }
+        }
+        renamed
+    }
+
+    /// The compiled schema patterns (field `pattern`s, `ref-format`s).
+    pub fn patterns(&self) -> &PatternCache {
+        &self.patterns
+    }
+
+    /// Field patterns and ref-formats rejected by the regex limits in [`crate::pattern`].
+    pub fn rejected_patterns(&self) -> Vec<RejectedPattern> {
+        let mut rejected = Vec::new();
+        for t in &self.types {
+            for f in &t.fields {
+                if let Some(ref p) = f.pattern {
+                    if let Err(e) = self.patterns.get(p) {
+                        rejected.push(RejectedPattern {
+                            source: format!("field \"{}\" of type \"{}\"", f.name, t.name),
+                            pattern: p.clone(),
+                            reason: e,
+                        });
+                    }
+                }
+            }
+        }
+        for rf in &self.ref_formats {
+            if let Err(e) = self.patterns.get(&rf.pattern) {
+                rejected.push(RejectedPattern {
+                    source: format!("ref-format \"{}\"", rf.name),
+                    pattern: rf.pattern.clone(),
+                    reason: e,
+                });
+            }
+        }
+        rejected
     }
 
     /// Look up a type definition by name.
@@ -204,6 +1015,12 @@ impl Schema {
         self.types.iter().find(|t| t.name == name)
     }
 
+    /// The singleton type whose `match` pattern accepts the file name of `path`.
+    pub fn singleton_for(&self, path: &Path) -> Option<&TypeDef> {
+        let filename = path.file_name()?.to_str()?;
+        self.types.iter().find(|t| t.matches_filename(filename))
+    }
+
     /// Get all relation field names (both direct names and inverse names).
     /// These are valid frontmatter fields on any document type.
     pub fn all_relation_field_names(&self) -> Vec<&str> {
@@ -232,6 +1049,66 @@ impl Schema {
         None
     }
 
+    /// Relations organized by group: declared groups first in declaration order,
+    /// then undeclared groups by first use, then ungrouped relations. Relations
+    /// keep declaration order within a group; empty groups are omitted.
+    pub fn grouped_relations(&self) -> Vec<RelationGroup<'_>> {
+        let mut groups: Vec<RelationGroup> = self
+            .relation_groups
+            .iter()
+            .map(|g| RelationGroup {
+                name: Some(g.name.as_str()),
+                description: g.description.as_deref(),
+                relations: Vec::new(),
+            })
+            .collect();
+        let mut ungrouped = Vec::new();
+        for r in &self.relations {
+            let Some(ref name) = r.group else {
+                ungrouped.push(r);
+                continue;
+            };
+            match groups.iter_mut().find(|g| g.name == Some(name.as_str())) {
+                Some(group) => group.relations.push(r),
+                None => groups.push(RelationGroup {
+                    name: Some(name.as_str()),
+                    description: None,
+                    relations: vec![r],
+                }),
+            }
+        }
+        groups.push(RelationGroup {
+            name: None,
+            description: None,
+            relations: ungrouped,
+        });
+        groups.retain(|g| !g.relations.is_empty());
+        groups
+    }
+
+    /// Whether any relation has a group, i.e. whether grouped rendering applies.
+    pub fn has_relation_groups(&self) -> bool {
+        self.relations.iter().any(|r| r.group.is_some())
+    }
+
+    /// Sort key for a relation field (name or inverse) in grouped display order.
+    /// Fields that aren't relations (e.g. `inline_ref`) sort last.
+    pub fn relation_rank(&self, field_name: &str) -> (usize, usize) {
+        for (g, group) in self.grouped_relations().iter().enumerate() {
+            for (i, r) in group.relations.iter().enumerate() {
+                if r.name == field_name || r.inverse.as_deref() == Some(field_name) {
+                    return (g, i);
+                }
+            }
+        }
+        (usize::MAX, 0)
+    }
+
+    /// Group of a relation field (name or inverse), if it has one.
+    pub fn relation_group(&self, field_name: &str) -> Option<&str> {
+        self.find_relation(field_name).and_then(|(r, _)| r.group.as_deref())
+    }
+
     /// Get the cardinality for a relation field name.
     /// Inverse relations inherit the parent's cardinality.
     pub fn relation_cardinality(&self, field_name: &str) -> Option<Cardinality> {
@@ -259,8 +1136,17 @@ fn parse_type_def(node: &KdlNode) -> Result<TypeDef> {
 
     let mut fields = Vec::new();
     let mut sections = Vec::new();
+    let mut section_patterns = Vec::new();
     let mut match_pattern = None;
     let mut rules = Vec::new();
+    let mut promotes = Vec::new();
+    let mut order_by = Vec::new();
+    let mut consistency = Vec::new();
+    let mut status_field = None;
+    let mut template = None;
+    let mut workflow = None;
+    let mut examples = Vec::new();
+    let mut permalink = None;
 
     for child in children.nodes() {
         match child.name().value() {
@@ -273,6 +1159,7 @@ fn parse_type_def(node: &KdlNode) -> Result<TypeDef> {
                 fields.push(parse_field_def(child)?);
             }
             "section" => sections.push(parse_section_def(child)?),
+            "section-pattern" => section_patterns.push(parse_section_pattern_def(child)?),
             "match" => {
                 match_pattern = get_string_arg(child);
                 if match_pattern.is_none() {
@@ -282,6 +1169,46 @@ fn parse_type_def(node: &KdlNode) -> Result<TypeDef> {
                 }
             }
             "rule" => rules.push(parse_rule_def(child)?),
+            "promote" => {
+                if singleton {
+                    return Err(Error::SchemaParse(format!(
+                        "singleton type '{name}' cannot promote into frontmatter"
+                    )));
+                }
+                promotes.push(parse_promote_def(child)?);
+            }
+            "order-by" => {
+                let field = get_string_arg(child).ok_or_else(|| {
+                    Error::SchemaParse(format!("order-by in type '{name}' missing field argument"))
+                })?;
+                let desc = get_bool_prop(child, "desc").unwrap_or(false);
+                order_by.push(OrderKey { field, desc });
+            }
+            "consistent-with" => consistency.push(parse_consistency_def(child, &name)?),
+            "status-field" => {
+                if status_field.is_some() {
+                    return Err(Error::SchemaParse(format!(
+                        "type '{name}' declares status-field more than once"
+                    )));
+                }
+                status_field = Some(parse_status_field_def(child, &name)?);
+            }
+            "workflow" => {
+                if workflow.is_some() {
+                    return Err(Error::SchemaParse(format!("type '{name}' declares workflow more than once")));
+                }
+                workflow = Some(parse_workflow_def(child, &name)?);
+            }
+            "template" => {
+                template = get_string_arg(child);
+                if template.is_none() {
+                    return Err(Error::SchemaParse(format!(
+                        "template node in type '{name}' missing path argument"
+                    )));
+                }
+            }
+            "example" | "counter-example" => examples.push(parse_example(child, &format!("type '{name}'"))?),
+            "permalink" => permalink = Some(parse_permalink(child, &format!("type '{name}'"))?),
             other => {
                 return Err(Error::SchemaParse(format!(
                     "unknown node in type '{name}': '{other}'"
@@ -305,7 +1232,110 @@ fn parse_type_def(node: &KdlNode) -> Result<TypeDef> {
         match_pattern,
         fields,
         sections,
+        section_patterns,
         rules,
+        promotes,
+        order_by,
+        consistency,
+        status_field,
+        template,
+        workflow,
+        examples,
+        permalink,
+    })
+}
+
+/// Parse `permalink "/decisions/{id}/"`. The template must be a site-absolute path.
+fn parse_permalink(node: &KdlNode, owner: &str) -> Result<String> {
+    let template = get_string_arg(node)
+        .ok_or_else(|| Error::SchemaParse(format!("permalink in {owner} missing template argument")))?;
+    if !template.starts_with('/') {
+        return Err(Error::SchemaParse(format!(
+            "permalink \"{template}\" in {owner} must start with '/'"
+        )));
+    }
+    if template.split('/').any(|part| part == "..") {
+        return Err(Error::SchemaParse(format!("permalink \"{template}\" in {owner} must not contain '..'")));
+    }
+    Ok(template)
+}
+
+/// Parse `example "..."` or `counter-example "..." expect="F021"`. Numbers and
+/// booleans are taken as their text, so a field example can be `example 3`.
+fn parse_example(node: &KdlNode, owner: &str) -> Result<SchemaExample> {
+    let kind = node.name().value();
+    let value = node
+        .entries()
+        .iter()
+        .find(|e| e.name().is_none())
+        .map(|e| e.value())
+        .ok_or_else(|| Error::SchemaParse(format!("{kind} in {owner} missing snippet argument")))?;
+    let source = match value {
+        KdlValue::String(s) => s.clone(),
+        KdlValue::Integer(n) => n.to_string(),
+        KdlValue::Float(f) => f.to_string(),
+        KdlValue::Bool(b) => b.to_string(),
+        KdlValue::Null => "null".to_string(),
+    };
+    let counter = kind == "counter-example";
+    let expect = get_list_prop(node, "expect");
+    if !counter && !expect.is_empty() {
+        return Err(Error::SchemaParse(format!(
+            "example in {owner} has expect=; only counter-example takes expected codes"
+        )));
+    }
+    Ok(SchemaExample { source, counter, expect })
+}
+
+/// Parse a `workflow` block of `transition "from" to="a,b"` nodes. Several
+/// `transition` nodes for one state add up.
+fn parse_workflow_def(node: &KdlNode, type_name: &str) -> Result<WorkflowDef> {
+    let mut workflow = WorkflowDef {
+        field: get_string_prop(node, "field"),
+        transitions: Vec::new(),
+    };
+    for child in node.children().map(|c| c.nodes()).unwrap_or_default() {
+        if child.name().value() != "transition" {
+            return Err(Error::SchemaParse(format!(
+                "unknown node in workflow of type '{type_name}': '{}' (expected transition)",
+                child.name().value()
+            )));
+        }
+        let from = get_string_arg(child).ok_or_else(|| {
+            Error::SchemaParse(format!("transition in workflow of type '{type_name}' missing state argument"))
+        })?;
+        let to = get_list_prop(child, "to");
+        if to.is_empty() {
+            return Err(Error::SchemaParse(format!(
+                "transition \"{from}\" in workflow of type '{type_name}' needs to=\"STATE,...\""
+            )));
+        }
+        match workflow.transitions.iter_mut().find(|(state, _)| *state == from) {
+            Some((_, targets)) => {
+                for t in to {
+                    if !targets.contains(&t) {
+                        targets.push(t);
+                    }
+                }
+            }
+            None => workflow.transitions.push((from, to)),
+        }
+    }
+    Ok(workflow)
+}
+
+/// Parse `status-field "status" active="accepted" terminal="rejected,superseded"`.
+/// Values listed for an enum field must be among its values.
+fn parse_status_field_def(node: &KdlNode, type_name: &str) -> Result<StatusFieldDef> {
+    let field = get_string_arg(node).ok_or_else(|| {
+        Error::SchemaParse(format!("status-field in type '{type_name}' missing field argument"))
+    })?;
+    Ok(StatusFieldDef {
+        field,
+        active: get_list_prop(node, "active"),
+        terminal: get_list_prop(node, "terminal"),
+        deprecated: get_string_prop(node, "deprecated"),
+        superseded: get_string_prop(node, "superseded"),
     })
 }
 
@@ -318,9 +1348,67 @@ fn parse_field_def(node: &KdlNode) -> Result<FieldDef> {
     let pattern = get_string_prop(node, "pattern");
     let description = get_string_prop(node, "description");
     let default = get_string_prop(node, "default");
+    let auto = match get_string_prop(node, "auto").as_deref() {
+        None => None,
+        Some("created") => Some(AutoTimestamp::Created),
+        Some("updated") => Some(AutoTimestamp::Updated),
+        Some(other) => {
+            return Err(Error::SchemaParse(format!(
+                "field '{name}': unknown auto '{other}' (expected created or updated)"
+            )));
+        }
+    };
 
     let field_type = parse_field_type(&type_str, node)?;
 
+    let min = get_string_prop(node, "min");
+    let max = get_string_prop(node, "max");
+    let not_future = get_bool_prop(node, "not-future").unwrap_or(false);
+    let is_temporal = matches!(field_type, FieldType::Date | FieldType::DateTime);
+    if !is_temporal && (min.is_some() || max.is_some() || not_future) {
+        return Err(Error::SchemaParse(format!(
+            "field '{name}': min, max, and not-future need type=\"date\" or type=\"datetime\""
+        )));
+    }
+    for (key, bound) in [("min", &min), ("max", &max)] {
+        if let Some(b) = bound {
+            if crate::validation::parse_timestamp(b).is_none() {
+                return Err(Error::SchemaParse(format!(
+                    "field '{name}': {key} '{b}' is not an ISO 8601 date or datetime"
+                )));
+            }
+        }
+    }
+    let target_types = parse_target_types(node);
+    if !target_types.is_empty() && !matches!(field_type, FieldType::Ref | FieldType::RefArray) {
+        return Err(Error::SchemaParse(format!(
+            "field '{name}': target-type needs type=\"ref\" or type=\"ref[]\""
+        )));
+    }
+
+    let aliases: Vec<String> = node
+        .children()
+        .map(|c| {
+            c.nodes()
+                .iter()
+                .filter(|n| n.name().value() == "alias")
+                .flat_map(|n| n.entries().iter().filter(|e| e.name().is_none()))
+                .filter_map(|e| e.value().as_string().map(|s| s.to_string()))
+                .collect()
+        })
+        .unwrap_or_default();
+    if aliases.contains(&name) {
+        return Err(Error::SchemaParse(format!("field '{name}': alias repeats the field's own name")));
+    }
+    let examples = node
+        .children()
+        .map(|c| c.nodes())
+        .unwrap_or_default()
+        .iter()
+        .filter(|n| matches!(n.name().value(), "example" | "counter-example"))
+        .map(|n| parse_example(n, &format!("field '{name}'")))
+        .collect::<Result<Vec<_>>>()?;
+
     Ok(FieldDef {
         name,
         field_type,
@@ -328,9 +1416,21 @@ fn parse_field_def(node: &KdlNode) -> Result<FieldDef> {
         pattern,
         description,
         default,
+        auto,
+        min,
+        max,
+        not_future,
+        target_types,
+        aliases,
+        examples,
     })
 }
 
+/// `target-type="adr"` or `target-type="adr,rfc"`.
+fn parse_target_types(node: &KdlNode) -> Vec<String> {
+    get_list_prop(node, "target-type")
+}
+
 fn parse_field_type(type_str: &str, node: &KdlNode) -> Result<FieldType> {
     match type_str {
         "string" => Ok(FieldType::String),
@@ -341,6 +1441,8 @@ fn parse_field_type(type_str: &str, node: &KdlNode) -> Result<FieldType> {
         "ref[]" => Ok(FieldType::RefArray),
         "user" => Ok(FieldType::User),
         "user[]" => Ok(FieldType::UserArray),
+        "date" => Ok(FieldType::Date),
+        "datetime" => Ok(FieldType::DateTime),
         "enum" => {
             let values = node
                 .children()
@@ -381,15 +1483,29 @@ fn parse_section_def(node: &KdlNode) -> Result<SectionDef> {
     let mut content = None;
     let mut list = None;
     let mut diagram = None;
+    let mut section_patterns = Vec::new();
+    let mut aliases = Vec::new();
+    let mut examples = Vec::new();
 
     if let Some(body) = node.children() {
         for child in body.nodes() {
             match child.name().value() {
+                "alias" => aliases.extend(
+                    child
+                        .entries()
+                        .iter()
+                        .filter(|e| e.name().is_none())
+                        .filter_map(|e| e.value().as_string().map(|s| s.to_string())),
+                ),
                 "section" => children.push(parse_section_def(child)?),
+                "section-pattern" => section_patterns.push(parse_section_pattern_def(child)?),
                 "table" => table = Some(parse_table_def(child)?),
                 "content" => content = Some(parse_content_def(child)?),
                 "list" => list = Some(parse_list_def(child)?),
                 "diagram" => diagram = Some(parse_diagram_def(child)?),
+                "example" | "counter-example" => {
+                    examples.push(parse_example(child, &format!("section '{name}'"))?)
+                }
                 other => {
                     return Err(Error::SchemaParse(format!(
                         "unknown node in section '{name}': '{other}'"
@@ -403,11 +1519,59 @@ fn parse_section_def(node: &KdlNode) -> Result<SectionDef> {
         name,
         required,
         description,
+        aliases,
         children,
         table,
         content,
         list,
         diagram,
+        section_patterns,
+        examples,
+    })
+}
+
+/// Parse `section-pattern "Day \\d+" min=1 max=30 { ... }`. The body takes the
+/// same constraint nodes as `section`.
+fn parse_section_pattern_def(node: &KdlNode) -> Result<SectionPatternDef> {
+    let section = parse_section_def(node)
+        .map_err(|e| match e {
+            Error::SchemaParse(msg) => Error::SchemaParse(msg.replace("section node", "section-pattern node")),
+            other => other,
+        })?;
+    let pattern = section.name.clone();
+    if !section.examples.is_empty() {
+        return Err(Error::SchemaParse(format!(
+            "section-pattern '{pattern}' cannot have examples; give them to a section instead"
+        )));
+    }
+    let regex = section_pattern_regex(&pattern).map_err(|e| {
+        Error::SchemaParse(format!("invalid section-pattern '{pattern}': {e}"))
+    })?;
+
+    let count = |key: &str| -> Result<Option<usize>> {
+        match get_i64_prop(node, key) {
+            Some(n) if n < 0 => Err(Error::SchemaParse(format!(
+                "section-pattern '{pattern}': {key} must not be negative"
+            ))),
+            other => Ok(other.map(|n| n as usize)),
+        }
+    };
+    let min = count("min")?;
+    let max = count("max")?;
+    if let (Some(lo), Some(hi)) = (min, max) {
+        if lo > hi {
+            return Err(Error::SchemaParse(format!(
+                "section-pattern '{pattern}': min={lo} is greater than max={hi}"
+            )));
+        }
+    }
+
+    Ok(SectionPatternDef {
+        pattern,
+        regex,
+        min,
+        max,
+        section,
     })
 }
 
@@ -415,19 +1579,37 @@ fn parse_table_def(node: &KdlNode) -> Result<TableDef> {
     let required = get_bool_prop(node, "required").unwrap_or(false);
     let description = get_string_prop(node, "description");
     let mut columns = Vec::new();
+    let mut ordered_by = None;
 
     if let Some(body) = node.children() {
         for child in body.nodes() {
-            if child.name().value() == "column" {
-                columns.push(parse_column_def(child)?);
+            match child.name().value() {
+                "column" => columns.push(parse_column_def(child)?),
+                "ordered-by" => {
+                    let column = get_string_arg(child)
+                        .ok_or_else(|| Error::SchemaParse("ordered-by node missing column name".into()))?;
+                    let ascending = get_bool_prop(child, "ascending").unwrap_or(true);
+                    ordered_by = Some(OrderedBy { column, ascending });
+                }
+                _ => {}
             }
         }
     }
 
+    if let Some(ref order) = ordered_by {
+        if !columns.is_empty() && !columns.iter().any(|c| c.name == order.column) {
+            return Err(Error::SchemaParse(format!(
+                "ordered-by column '{}' is not a column of the table",
+                order.column
+            )));
+        }
+    }
+
     Ok(TableDef {
         required,
         description,
         columns,
+        ordered_by,
     })
 }
 
@@ -464,6 +1646,7 @@ fn parse_relation_def(node: &KdlNode) -> Result<RelationDef> {
     let inverse = get_string_prop(node, "inverse");
     let description = get_string_prop(node, "description");
     let acyclic = get_bool_prop(node, "acyclic");
+    let group = get_string_prop(node, "group");
 
     let cardinality_str = get_string_prop(node, "cardinality").unwrap_or("many".into());
     let cardinality = match cardinality_str.as_str() {
@@ -482,6 +1665,8 @@ fn parse_relation_def(node: &KdlNode) -> Result<RelationDef> {
         cardinality,
         description,
         acyclic,
+        group,
+        target_types: parse_target_types(node),
     })
 }
 
@@ -502,6 +1687,7 @@ fn parse_diagram_def(node: &KdlNode) -> Result<DiagramDef> {
     Ok(DiagramDef {
         required: get_bool_prop(node, "required").unwrap_or(true),
         diagram_type: get_string_prop(node, "type"),
+        check: get_bool_prop(node, "check").unwrap_or(false),
     })
 }
 
@@ -568,9 +1754,393 @@ fn parse_ref_formats(node: &KdlNode) -> Result<Vec<RefFormat>> {
     Ok(formats)
 }
 
+/// Parse `promote "target" section="Objective" column="Target" row=0`
+/// or `promote "budget" section="Budget" yaml-key="error_budget"`.
+/// Parse `consistent-with "severity" relation="governed_by" field="severity" op="=="`.
+fn parse_consistency_def(node: &KdlNode, type_name: &str) -> Result<ConsistencyDef> {
+    let field = get_string_arg(node).ok_or_else(|| {
+        Error::SchemaParse(format!("consistent-with in type '{type_name}' missing field argument"))
+    })?;
+    let relation = get_string_prop(node, "relation").ok_or_else(|| {
+        Error::SchemaParse(format!("consistent-with '{field}' in type '{type_name}' missing relation"))
+    })?;
+    let op_str = get_string_prop(node, "op").unwrap_or_else(|| "==".into());
+    let op = ConsistencyOp::parse(&op_str).ok_or_else(|| {
+        Error::SchemaParse(format!(
+            "consistent-with '{field}': unknown op '{op_str}' (expected ==, !=, <, <=, >, >=)"
+        ))
+    })?;
+    let severity = get_string_prop(node, "severity").unwrap_or_else(|| "error".into());
+    if severity != "error" && severity != "warning" {
+        return Err(Error::SchemaParse(format!(
+            "consistent-with '{field}': severity must be error or warning, got '{severity}'"
+        )));
+    }
+    Ok(ConsistencyDef {
+        other_field: get_string_prop(node, "field").unwrap_or_else(|| field.clone()),
+        field,
+        relation,
+        op,
+        severity,
+    })
+}
+
+fn parse_promote_def(node: &KdlNode) -> Result<PromoteDef> {
+    let field = get_string_arg(node)
+        .ok_or_else(|| Error::SchemaParse("promote node missing field name".into()))?;
+    let section = get_string_prop(node, "section").ok_or_else(|| {
+        Error::SchemaParse(format!("promote '{field}' missing section"))
+    })?;
+
+    let source = match (get_string_prop(node, "column"), get_string_prop(node, "yaml-key")) {
+        (Some(column), None) => PromoteSource::TableCell {
+            table: get_i64_prop(node, "table").unwrap_or(0).max(0) as usize,
+            column,
+            row: get_i64_prop(node, "row").unwrap_or(0).max(0) as usize,
+        },
+        (None, Some(key)) => PromoteSource::YamlKey(key),
+        _ => {
+            return Err(Error::SchemaParse(format!(
+                "promote '{field}' needs exactly one of column or yaml-key"
+            )));
+        }
+    };
+
+    Ok(PromoteDef {
+        field,
+        section,
+        source,
+    })
+}
+
+/// Parse the `prose { max-sentence-words 30; require-verbs "will" section="Decision" }` block.
+fn parse_prose_rules(node: &KdlNode) -> Result<ProseRules> {
+    let mut rules = ProseRules::default();
+    let Some(body) = node.children() else {
+        return Ok(rules);
+    };
+    for child in body.nodes() {
+        let name = child.name().value();
+        let number = || {
+            child
+                .entries()
+                .iter()
+                .find(|e| e.name().is_none())
+                .and_then(|e| e.value().as_float().or_else(|| e.value().as_integer().map(|n| n as f64)))
+                .ok_or_else(|| Error::SchemaParse(format!("prose rule '{name}' needs a number")))
+        };
+        match name {
+            "max-sentence-words" => {
+                let n = number()?;
+                if n < 1.0 {
+                    return Err(Error::SchemaParse("prose rule 'max-sentence-words' must be at least 1".into()));
+                }
+                rules.max_sentence_words = Some(n as usize);
+            }
+            "max-passive-ratio" => {
+                let n = number()?;
+                if !(0.0..=1.0).contains(&n) {
+                    return Err(Error::SchemaParse(format!(
+                        "prose rule 'max-passive-ratio' must be between 0 and 1, got {n}"
+                    )));
+                }
+                rules.max_passive_ratio = Some(n);
+            }
+            "min-readability" => rules.min_readability = Some(number()?),
+            "require-verbs" => {
+                let verbs: Vec<String> = child
+                    .entries()
+                    .iter()
+                    .filter(|e| e.name().is_none())
+                    .filter_map(|e| e.value().as_string().map(|s| s.to_lowercase()))
+                    .collect();
+                if verbs.is_empty() {
+                    return Err(Error::SchemaParse("prose rule 'require-verbs' needs at least one verb".into()));
+                }
+                let section = get_string_prop(child, "section").unwrap_or_else(|| "Decision".into());
+                rules.require_verbs.push(RequireVerbs { section, verbs });
+            }
+            other => {
+                return Err(Error::SchemaParse(format!("unknown node in prose: '{other}'")));
+            }
+        }
+    }
+    Ok(rules)
+}
+
+/// Parse the `assets { orphans "docs/assets" }` block.
+fn parse_index_rules(node: &KdlNode) -> Result<IndexRules> {
+    let key = |prop: &str| -> Result<Option<IndexKey>> {
+        get_string_prop(node, prop)
+            .map(|v| {
+                IndexKey::parse(&v).ok_or_else(|| {
+                    let expected = "expected type, status, recency, or health";
+                    Error::SchemaParse(format!("index: unknown {prop} '{v}' ({expected})"))
+                })
+            })
+            .transpose()
+    };
+    let mut rules = IndexRules {
+        group_by: key("group-by")?.unwrap_or(IndexKey::Type),
+        order_by: key("order-by")?,
+        ..IndexRules::default()
+    };
+    let Some(body) = node.children() else {
+        return Ok(rules);
+    };
+    for child in body.nodes() {
+        let name = child.name().value();
+        let field = || get_string_arg(child).ok_or_else(|| Error::SchemaParse(format!("index: {name} needs a field")));
+        match name {
+            "review-by" => rules.review_field = Some(field()?),
+            "expires" => {
+                rules.expires_field = Some(field()?);
+                if let Some(days) = get_i64_prop(child, "warn-days") {
+                    rules.warn_days = days;
+                }
+            }
+            "recency" => rules.recency_field = Some(field()?),
+            "weights" => {
+                let w = &mut rules.weights;
+                for (key, weight) in [
+                    ("error", &mut w.error),
+                    ("warning", &mut w.warning),
+                    ("overdue", &mut w.overdue),
+                    ("expiring", &mut w.expiring),
+                    ("incomplete", &mut w.incomplete),
+                ] {
+                    if let Some(v) = get_i64_prop(child, key) {
+                        *weight = u32::try_from(v)
+                            .map_err(|_| Error::SchemaParse(format!("index: weight {key} must not be negative")))?;
+                    }
+                }
+            }
+            other => {
+                return Err(Error::SchemaParse(format!("unknown node in index: '{other}'")));
+            }
+        }
+    }
+    Ok(rules)
+}
+
+fn parse_asset_rules(node: &KdlNode) -> Result<AssetRules> {
+    let mut rules = AssetRules::default();
+    let Some(body) = node.children() else {
+        return Ok(rules);
+    };
+    for child in body.nodes() {
+        match child.name().value() {
+            "orphans" => {
+                let folders: Vec<String> = child
+                    .entries()
+                    .iter()
+                    .filter(|e| e.name().is_none())
+                    .filter_map(|e| e.value().as_string().map(String::from))
+                    .collect();
+                if folders.is_empty() {
+                    return Err(Error::SchemaParse("assets rule 'orphans' needs at least one folder".into()));
+                }
+                rules.orphan_folders.extend(folders);
+            }
+            other => {
+                return Err(Error::SchemaParse(format!("unknown node in assets: '{other}'")));
+            }
+        }
+    }
+    Ok(rules)
+}
+
+fn parse_lint_rules(node: &KdlNode) -> Result<LintRules> {
+    let mut rules = LintRules::default();
+    let Some(body) = node.children() else {
+        return Ok(rules);
+    };
+    for child in body.nodes() {
+        let name = child.name().value();
+        let severity = get_string_prop(child, "severity").unwrap_or_else(|| "warning".into());
+        match severity.as_str() {
+            "error" | "warning" => {}
+            "off" => continue,
+            other => {
+                return Err(Error::SchemaParse(format!(
+                    "lint rule '{name}' has unknown severity '{other}' (expected error, warning, or off)"
+                )));
+            }
+        }
+        let kind = match name {
+            "heading-case" => match get_string_arg(child).as_deref() {
+                Some("sentence") => LintKind::HeadingCase(HeadingCase::Sentence),
+                Some("title") => LintKind::HeadingCase(HeadingCase::Title),
+                other => {
+                    return Err(Error::SchemaParse(format!(
+                        "lint rule 'heading-case' expects \"sentence\" or \"title\", got {other:?}"
+                    )));
+                }
+            },
+            "max-line-length" => {
+                let n = child
+                    .entries()
+                    .iter()
+                    .find(|e| e.name().is_none())
+                    .and_then(|e| e.value().as_integer())
+                    .filter(|n| *n > 0)
+                    .ok_or_else(|| {
+                        Error::SchemaParse("lint rule 'max-line-length' needs a positive number".into())
+                    })?;
+                LintKind::MaxLineLength(n as usize)
+            }
+            "table-trailing-whitespace" => LintKind::TableTrailingWhitespace,
+            "duplicate-headings" => LintKind::DuplicateHeadings,
+            "empty-sections" => LintKind::EmptySections,
+            "frontmatter-order" => LintKind::FrontmatterOrder(
+                child
+                    .entries()
+                    .iter()
+                    .filter(|e| e.name().is_none())
+                    .filter_map(|e| e.value().as_string().map(String::from))
+                    .collect(),
+            ),
+            other => {
+                return Err(Error::SchemaParse(format!("unknown node in lint: '{other}'")));
+            }
+        };
+        rules.rules.push(LintRule { kind, severity });
+    }
+    Ok(rules)
+}
+
+fn parse_coverage_rules(node: &KdlNode) -> Result<Vec<CoverageRule>> {
+    let mut rules = Vec::new();
+    if let Some(body) = node.children() {
+        for child in body.nodes() {
+            match child.name().value() {
+                "every" => rules.push(parse_coverage_rule(child)?),
+                other => {
+                    return Err(Error::SchemaParse(format!(
+                        "unknown node in coverage: '{other}'"
+                    )));
+                }
+            }
+        }
+    }
+    Ok(rules)
+}
+
+/// Parse `every "inc" must-have-relation "caused_by" to-type "adr" severity="warning"`.
+/// Keywords are bare arguments, each followed by its value.
+fn parse_coverage_rule(node: &KdlNode) -> Result<CoverageRule> {
+    let args: Vec<String> = node
+        .entries()
+        .iter()
+        .filter(|e| e.name().is_none())
+        .filter_map(|e| e.value().as_string().map(|s| s.to_string()))
+        .collect();
+
+    let doc_type = args
+        .first()
+        .cloned()
+        .ok_or_else(|| Error::SchemaParse("coverage rule missing document type".into()))?;
+
+    let mut relation = None;
+    let mut to_type = None;
+    let mut rest = args[1..].iter();
+    while let Some(keyword) = rest.next() {
+        let value = rest.next().cloned().ok_or_else(|| {
+            Error::SchemaParse(format!(
+                "coverage rule for '{doc_type}': '{keyword}' is missing a value"
+            ))
+        })?;
+        match keyword.as_str() {
+            "must-have-relation" => relation = Some(value),
+            "to-type" => to_type = Some(value),
+            other => {
+                return Err(Error::SchemaParse(format!(
+                    "coverage rule for '{doc_type}': unknown keyword '{other}'"
+                )));
+            }
+        }
+    }
+
+    let relation = relation.ok_or_else(|| {
+        Error::SchemaParse(format!(
+            "coverage rule for '{doc_type}' missing must-have-relation"
+        ))
+    })?;
+
+    let statuses = get_list_prop(node, "status");
+
+    let severity = get_string_prop(node, "severity").unwrap_or_else(|| "error".into());
+    if !matches!(severity.as_str(), "error" | "warning" | "info") {
+        return Err(Error::SchemaParse(format!(
+            "coverage rule for '{doc_type}': invalid severity '{severity}'"
+        )));
+    }
+
+    Ok(CoverageRule {
+        doc_type,
+        relation,
+        to_type,
+        statuses,
+        severity,
+    })
+}
+
 // ─── KDL helper functions ────────────────────────────────────────────────────
 
-fn get_string_arg(node: &KdlNode) -> Option<String> {
+/// `locale "fi" date-format=".." decimal-separator=".." thousands-separator=".." { label "en" "fi" }`.
+/// Starts from the built-in locale for the language, if any; an empty
+/// `thousands-separator` turns digit grouping off.
+fn parse_locale(node: &KdlNode) -> Result<Locale> {
+    let lang = get_string_arg(node).ok_or_else(|| Error::SchemaParse("locale node missing language".into()))?;
+    let mut locale = Locale::builtin(&lang).unwrap_or_else(|| Locale {
+        lang: lang.clone(),
+        ..Locale::default()
+    });
+    if let Some(format) = get_string_prop(node, "date-format") {
+        locale.date_format = format;
+    }
+    let single_char = |key: &str, value: &str| -> Result<Option<char>> {
+        let mut chars = value.chars();
+        match (chars.next(), chars.next()) {
+            (c, None) => Ok(c),
+            _ => Err(Error::SchemaParse(format!("locale '{lang}': {key} must be a single character"))),
+        }
+    };
+    if let Some(sep) = get_string_prop(node, "decimal-separator") {
+        locale.decimal_separator = single_char("decimal-separator", &sep)?
+            .ok_or_else(|| Error::SchemaParse(format!("locale '{lang}': decimal-separator is empty")))?;
+    }
+    if let Some(sep) = get_string_prop(node, "thousands-separator") {
+        locale.thousands_separator = single_char("thousands-separator", &sep)?;
+    }
+
+    if let Some(body) = node.children() {
+        for child in body.nodes() {
+            match child.name().value() {
+                "label" => {
+                    let args: Vec<&str> = child
+                        .entries()
+                        .iter()
+                        .filter(|e| e.name().is_none())
+                        .filter_map(|e| e.value().as_string())
+                        .collect();
+                    let [key, value] = args[..] else {
+                        return Err(Error::SchemaParse(format!(
+                            "locale '{lang}': label needs the English text and its translation"
+                        )));
+                    };
+                    locale.labels.insert(key.to_string(), value.to_string());
+                }
+                other => {
+                    return Err(Error::SchemaParse(format!("unknown node in locale: '{other}'")));
+                }
+            }
+        }
+    }
+    Ok(locale)
+}
+
+pub(crate) fn get_string_arg(node: &KdlNode) -> Option<String> {
     node.entries()
         .iter()
         .find(|e| e.name().is_none())
@@ -578,7 +2148,7 @@ fn get_string_arg(node: &KdlNode) -> Option<String> {
         .map(|s| s.to_string())
 }
 
-fn get_string_prop(node: &KdlNode, key: &str) -> Option<String> {
+pub(crate) fn get_string_prop(node: &KdlNode, key: &str) -> Option<String> {
     node.entries()
         .iter()
         .find(|e| e.name().map(|n| n.value()) == Some(key))
@@ -586,6 +2156,18 @@ fn get_string_prop(node: &KdlNode, key: &str) -> Option<String> {
         .map(|s| s.to_string())
 }
 
+/// A comma-separated string property (`status="open,mitigated"`) as a list.
+fn get_list_prop(node: &KdlNode, key: &str) -> Vec<String> {
+    get_string_prop(node, key)
+        .map(|s| {
+            s.split(',')
+                .map(|v| v.trim().to_string())
+                .filter(|v| !v.is_empty())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
 fn get_bool_prop(node: &KdlNode, key: &str) -> Option<bool> {
     node.entries()
         .iter()
@@ -596,7 +2178,7 @@ fn get_bool_prop(node: &KdlNode, key: &str) -> Option<bool> {
         })
 }
 
-fn get_i64_prop(node: &KdlNode, key: &str) -> Option<i64> {
+pub(crate) fn get_i64_prop(node: &KdlNode, key: &str) -> Option<i64> {
     node.entries()
         .iter()
         .find(|e| e.name().map(|n| n.value()) == Some(key))
@@ -679,6 +2261,7 @@ type "doc" {
         table required=#true {
             column "Name" type="string" required=#true
             column "Score" type="number"
+            ordered-by "Score" ascending=#false
         }
     }
 }
@@ -689,6 +2272,14 @@ type "doc" {
         assert_eq!(table.columns.len(), 2);
         assert!(table.columns[0].required);
         assert_eq!(table.columns[1].col_type, FieldType::Number);
+        assert_eq!(
+            table.ordered_by,
+            Some(OrderedBy { column: "Score".into(), ascending: false })
+        );
+
+        let bad = kdl.replace("ordered-by \"Score\"", "ordered-by \"Time\"");
+        let err = Schema::from_str(&bad).unwrap_err().to_string();
+        assert!(err.contains("ordered-by column 'Time'"), "{err}");
     }
 
     #[test]
@@ -1067,4 +2658,404 @@ type "doc" {
         assert!(!schema.types[0].singleton);
         assert!(schema.types[0].match_pattern.is_none());
     }
+
+    #[test]
+    fn test_parse_coverage_rules() {
+        let kdl = r#"
+coverage {
+    every "inc" must-have-relation "caused_by" to-type "adr" severity="warning"
+    every "opp" status="accepted" must-have-relation "enabled_by"
+}
+"#;
+        let schema = Schema::from_str(kdl).unwrap();
+        assert_eq!(schema.coverage.len(), 2);
+
+        let inc = &schema.coverage[0];
+        assert_eq!(inc.doc_type, "inc");
+        assert_eq!(inc.relation, "caused_by");
+        assert_eq!(inc.to_type.as_deref(), Some("adr"));
+        assert_eq!(inc.severity, "warning");
+        assert!(inc.statuses.is_empty());
+
+        let opp = &schema.coverage[1];
+        assert_eq!(opp.statuses, vec!["accepted"]);
+        assert!(opp.to_type.is_none());
+        assert_eq!(opp.severity, "error");
+        assert_eq!(
+            opp.describe(),
+            "every accepted opp must-have-relation enabled_by"
+        );
+    }
+
+    #[test]
+    fn test_coverage_rule_requires_relation() {
+        let kdl = r#"
+coverage {
+    every "inc" to-type "adr"
+}
+"#;
+        let msg = format!("{}", Schema::from_str(kdl).unwrap_err());
+        assert!(msg.contains("missing must-have-relation"));
+    }
+
+    #[test]
+    fn test_coverage_rule_rejects_unknown_keyword() {
+        let kdl = r#"
+coverage {
+    every "inc" must-have-relation "caused_by" from-type "adr"
+}
+"#;
+        let msg = format!("{}", Schema::from_str(kdl).unwrap_err());
+        assert!(msg.contains("unknown keyword 'from-type'"));
+    }
+
+    #[test]
+    fn test_parse_promote_defs() {
+        let kdl = r#"
+type "slo" {
+    field "target" type="number"
+    field "budget" type="string"
+    promote "target" section="Objective" column="Target" row=1
+    promote "budget" section="Budget" yaml-key="error_budget.monthly"
+    section "Objective"
+    section "Budget"
+}
+"#;
+        let schema = Schema::from_str(kdl).unwrap();
+        let promotes = &schema.types[0].promotes;
+        assert_eq!(promotes.len(), 2);
+        assert_eq!(promotes[0].field, "target");
+        assert_eq!(promotes[0].section, "Objective");
+        assert_eq!(
+            promotes[0].source,
+            PromoteSource::TableCell {
+                table: 0,
+                column: "Target".into(),
+                row: 1
+            }
+        );
+        assert_eq!(
+            promotes[1].source,
+            PromoteSource::YamlKey("error_budget.monthly".into())
+        );
+    }
+
+    #[test]
+    fn test_promote_requires_one_source() {
+        let kdl = r#"
+type "slo" {
+    promote "target" section="Objective"
+}
+"#;
+        let msg = format!("{}", Schema::from_str(kdl).unwrap_err());
+        assert!(msg.contains("exactly one of column or yaml-key"));
+    }
+
+    #[test]
+    fn test_parse_order_by() {
+        let kdl = r#"
+type "adr" {
+    field "date" type="string"
+    field "priority" type="number"
+    order-by "date" desc=#true
+    order-by "priority"
+}
+"#;
+        let schema = Schema::from_str(kdl).unwrap();
+        let order = &schema.get_type("adr").unwrap().order_by;
+        assert_eq!(
+            order,
+            &vec![
+                OrderKey { field: "date".into(), desc: true },
+                OrderKey { field: "priority".into(), desc: false },
+            ]
+        );
+        assert!(Schema::from_str("type \"a\" {\n    order-by desc=#true\n}").is_err());
+    }
+
+    #[test]
+    fn test_parse_prose_rules() {
+        let kdl = r#"
+prose {
+    max-sentence-words 30
+    max-passive-ratio 0.25
+    min-readability 40
+    require-verbs "will" "adopt" section="Decision"
+}
+"#;
+        let schema = Schema::from_str(kdl).unwrap();
+        let prose = schema.prose.unwrap();
+        assert_eq!(prose.max_sentence_words, Some(30));
+        assert_eq!(prose.max_passive_ratio, Some(0.25));
+        assert_eq!(prose.min_readability, Some(40.0));
+        assert_eq!(prose.require_verbs[0].section, "Decision");
+        assert_eq!(prose.require_verbs[0].verbs, vec!["will", "adopt"]);
+
+        assert!(Schema::from_str("prose { max-passive-ratio 2.0 }").is_err());
+        assert!(Schema::from_str("prose { max-sentence-words }").is_err());
+        assert!(Schema::from_str("type \"a\" {\n}\n").unwrap().prose.is_none());
+    }
+
+    #[test]
+    fn test_parse_asset_rules() {
+        let schema = Schema::from_str("assets {\n    orphans \"docs/assets\" \"docs/img\"\n}\n").unwrap();
+        assert_eq!(schema.assets.unwrap().orphan_folders, vec!["docs/assets", "docs/img"]);
+        assert!(Schema::from_str("assets { orphans }").is_err());
+        assert!(Schema::from_str("assets { unknown }").is_err());
+    }
+
+    #[test]
+    fn test_parse_locale() {
+        let kdl = "locale \"fi\" thousands-separator=\"\" {\n    label \"Referenced by\" \"Viittaavat\"\n}\n";
+        let locale = Schema::from_str(kdl).unwrap().locale.unwrap();
+        assert_eq!(locale.date_format, "D.M.YYYY");
+        assert_eq!(locale.thousands_separator, None);
+        assert_eq!(locale.label("Referenced by"), "Viittaavat");
+        assert_eq!(locale.label("Field"), "Kenttä");
+
+        let custom = Schema::from_str("locale \"nl\" date-format=\"DD-MM-YYYY\"").unwrap().locale.unwrap();
+        assert_eq!(custom.format_date("2024-03-05").as_deref(), Some("05-03-2024"));
+        assert!(Schema::from_str("locale \"fi\" decimal-separator=\",,\"").is_err());
+        assert!(Schema::from_str("locale \"fi\" { label \"Index\" }").is_err());
+    }
+
+    #[test]
+    fn test_parse_index_rules() {
+        let kdl = "index group-by=\"recency\" order-by=\"health\" {\n    review-by \"next_review\"\n    expires \"valid_until\" warn-days=14\n    weights error=50 incomplete=0\n}\n";
+        let rules = Schema::from_str(kdl).unwrap().index.unwrap();
+        assert_eq!(rules.group_by, IndexKey::Recency);
+        assert_eq!(rules.order_by, Some(IndexKey::Health));
+        assert_eq!(rules.review_field.as_deref(), Some("next_review"));
+        assert_eq!((rules.expires_field.as_deref(), rules.warn_days), (Some("valid_until"), 14));
+        assert_eq!((rules.weights.error, rules.weights.warning, rules.weights.incomplete), (50, 5, 0));
+
+        assert!(Schema::from_str("index group-by=\"size\"").is_err());
+        assert!(Schema::from_str("index { weights error=-1 }").is_err());
+        assert!(Schema::from_str("index { review-by }").is_err());
+    }
+
+    #[test]
+    fn test_field_aliases() {
+        let kdl = "type \"task\" {\n    field \"owner\" type=\"string\" {\n        alias \"assignee\" \"lead\"\n    }\n}\n";
+        let schema = Schema::from_str(kdl).unwrap();
+        assert_eq!(schema.types[0].fields[0].aliases, vec!["assignee", "lead"]);
+
+        let (mut fm, _) = Frontmatter::parse("---\ntype: task\nassignee: ana\n---\n").unwrap();
+        assert_eq!(schema.apply_aliases(&mut fm), vec![("assignee".to_string(), "owner".to_string())]);
+        assert_eq!(fm.get_display("owner").as_deref(), Some("ana"));
+        assert!(!fm.has_field("assignee"));
+
+        let (mut both, _) = Frontmatter::parse("---\ntype: task\nowner: bo\nlead: ana\n---\n").unwrap();
+        assert!(schema.apply_aliases(&mut both).is_empty());
+        assert_eq!(both.get_display("owner").as_deref(), Some("bo"));
+
+        let clash = "type \"task\" {\n    field \"owner\" {\n        alias \"lead\"\n    }\n    field \"lead\"\n}\n";
+        assert!(Schema::from_str(clash).is_err());
+    }
+
+    #[test]
+    fn test_parse_section_patterns() {
+        let kdl = r#"
+type "runbook" {
+    section-pattern "Day \\d+" min=1 max=30 description="One section per day" {
+        list min-items=1
+        section-pattern "Step [A-Z]"
+    }
+}
+"#;
+        let schema = Schema::from_str(kdl).unwrap();
+        let t = &schema.types[0];
+        assert!(t.sections.is_empty());
+        let p = &t.section_patterns[0];
+        assert_eq!(p.pattern, r"Day \d+");
+        assert_eq!((p.min, p.max), (Some(1), Some(30)));
+        assert!(p.matches("Day 12"));
+        assert!(!p.matches("Day 12 recap"));
+        assert!(p.section.list.is_some());
+        assert_eq!(p.section.section_patterns[0].pattern, "Step [A-Z]");
+    }
+
+    #[test]
+    fn test_section_pattern_errors() {
+        let bad_regex = r#"type "r" { section-pattern "Day (" }"#;
+        let msg = format!("{}", Schema::from_str(bad_regex).unwrap_err());
+        assert!(msg.contains("invalid section-pattern"), "{msg}");
+
+        let bad_range = r#"type "r" { section-pattern "Day \\d+" min=3 max=1 }"#;
+        let msg = format!("{}", Schema::from_str(bad_range).unwrap_err());
+        assert!(msg.contains("min=3 is greater than max=1"), "{msg}");
+    }
+
+    #[test]
+    fn test_parse_auto_and_aliases() {
+        let kdl = r#"
+type "adr" {
+    field "created" type="string" auto="created"
+    field "updated" type="string" auto="updated"
+    section "Context" {
+        alias "Background" "Problem"
+    }
+}
+"#;
+        let schema = Schema::from_str(kdl).unwrap();
+        let t = &schema.types[0];
+        assert_eq!(t.fields[0].auto, Some(AutoTimestamp::Created));
+        assert_eq!(t.fields[1].auto, Some(AutoTimestamp::Updated));
+        assert_eq!(t.sections[0].aliases, vec!["Background", "Problem"]);
+
+        let bad = r#"type "adr" { field "at" auto="sometimes" }"#;
+        let msg = format!("{}", Schema::from_str(bad).unwrap_err());
+        assert!(msg.contains("unknown auto 'sometimes'"), "{msg}");
+    }
+
+    #[test]
+    fn test_relation_groups_order() {
+        let kdl = r#"
+relation-group "causality" description="Why things happened"
+relation-group "lifecycle"
+relation "related_to" cardinality="many"
+relation "superseded_by" inverse="supersedes" cardinality="one" group="lifecycle"
+relation "caused_by" inverse="causes" cardinality="many" group="causality"
+relation "owned_by" cardinality="one" group="ownership"
+relation "enables" cardinality="many" group="causality"
+"#;
+        let schema = Schema::from_str(kdl).unwrap();
+        let groups = schema.grouped_relations();
+        let names: Vec<_> = groups.iter().map(|g| g.name).collect();
+        assert_eq!(names, vec![Some("causality"), Some("lifecycle"), Some("ownership"), None]);
+        assert_eq!(groups[0].description, Some("Why things happened"));
+        let causality: Vec<_> = groups[0].relations.iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(causality, vec!["caused_by", "enables"]);
+
+        assert!(schema.relation_rank("causes") < schema.relation_rank("supersedes"));
+        assert!(schema.relation_rank("related_to") < schema.relation_rank("inline_ref"));
+        assert_eq!(schema.relation_group("supersedes"), Some("lifecycle"));
+        assert_eq!(schema.relation_group("related_to"), None);
+
+        let dup = "relation-group \"a\"\nrelation-group \"a\"";
+        assert!(Schema::from_str(dup).is_err());
+    }
+
+    #[test]
+    fn test_parse_consistent_with() {
+        let kdl = r#"
+relation "supersedes" inverse="superseded_by" cardinality="one"
+relation "governed_by" cardinality="one"
+type "adr" {
+    field "date" type="date"
+    consistent-with "date" relation="supersedes" op=">=" severity="warning"
+}
+type "inc" {
+    field "severity" type="string"
+    consistent-with "severity" relation="governed_by" field="risk"
+}
+"#;
+        let schema = Schema::from_str(kdl).unwrap();
+        let adr = &schema.get_type("adr").unwrap().consistency[0];
+        assert_eq!(adr.op, ConsistencyOp::Ge);
+        assert_eq!(adr.other_field, "date");
+        assert_eq!(adr.severity, "warning");
+        let inc = &schema.get_type("inc").unwrap().consistency[0];
+        assert_eq!(inc.describe(), "severity == governed_by.risk");
+
+        let bad_rel = "type \"a\" {\n    consistent-with \"x\" relation=\"nope\"\n}";
+        let msg = Schema::from_str(bad_rel).unwrap_err().to_string();
+        assert!(msg.contains("unknown relation 'nope'"), "{msg}");
+        let bad_op = "relation \"r\"\ntype \"a\" {\n    consistent-with \"x\" relation=\"r\" op=\"~\"\n}";
+        assert!(Schema::from_str(bad_op).is_err());
+    }
+
+    #[test]
+    fn test_parse_workflow() {
+        let kdl = r#"
+type "adr" {
+    field "status" type="enum" {
+        values "proposed" "accepted" "rejected" "superseded" "draft"
+    }
+    workflow {
+        transition "proposed" to="accepted"
+        transition "proposed" to="rejected, accepted"
+        transition "accepted" to="superseded"
+    }
+}
+"#;
+        let schema = Schema::from_str(kdl).unwrap();
+        let (field, workflow) = schema.workflow("adr").unwrap();
+        assert_eq!(field, "status");
+        assert_eq!(workflow.transitions[0].1, vec!["accepted", "rejected"]);
+        assert!(workflow.allows("proposed", "rejected"));
+        assert!(workflow.allows("accepted", "accepted"));
+        assert!(!workflow.allows("superseded", "proposed"));
+        // Values the workflow doesn't name are free
+        assert!(workflow.allows("draft", "accepted"));
+        assert_eq!(
+            workflow.check("status", "proposed", "superseded").as_deref(),
+            Some("status: proposed \u{2192} superseded is not an allowed transition (proposed may become accepted, rejected)")
+        );
+
+        let (before, _) = Frontmatter::parse("---\ntype: adr\nstatus: superseded\n---\n").unwrap();
+        let (after, _) = Frontmatter::parse("---\ntype: adr\nstatus: accepted\n---\n").unwrap();
+        let msg = schema.transition_violation(&before, &after).unwrap();
+        assert!(msg.ends_with("(superseded is final)"), "{msg}");
+        assert_eq!(schema.transition_violation(&after, &before), None);
+
+        let bad = kdl.replace("to=\"superseded\"", "to=\"retired\"");
+        let msg = Schema::from_str(&bad).unwrap_err().to_string();
+        assert!(msg.contains("workflow in type 'adr': 'retired' is not a value"), "{msg}");
+    }
+
+    #[test]
+    fn test_parse_status_field() {
+        let kdl = r#"
+type "adr" {
+    field "status" type="enum" {
+        values "proposed" "accepted" "superseded"
+    }
+}
+type "inc" {
+    field "state" type="enum" {
+        values "open" "mitigated" "resolved" "archived"
+    }
+    status-field "state" active="mitigated" terminal="resolved, archived" deprecated="archived"
+}
+"#;
+        let schema = Schema::from_str(kdl).unwrap();
+        let inc = schema.status_field("inc");
+        assert_eq!(inc.field, "state");
+        assert!(inc.is_open("open") && inc.is_active("mitigated") && inc.is_terminal("archived"));
+        assert_eq!(inc.deprecated_value(), "archived");
+        assert_eq!(inc.superseded_value(), "superseded");
+
+        // Undeclared: the `status` field with the default vocabulary
+        let adr = schema.status_field("adr");
+        assert_eq!(adr, StatusFieldDef::default());
+        assert!(adr.is_terminal("superseded") && adr.is_open("proposed"));
+
+        let bad = "type \"inc\" {\n    field \"state\" type=\"enum\" {\n        values \"open\"\n    }\n    status-field \"state\" terminal=\"closed\"\n}";
+        let msg = Schema::from_str(bad).unwrap_err().to_string();
+        assert!(msg.contains("'closed' is not a value of field 'state'"), "{msg}");
+    }
+
+    #[test]
+    fn test_parse_lint_rules() {
+        let kdl = r#"
+lint {
+    heading-case "sentence" severity="error"
+    max-line-length 100
+    empty-sections severity="off"
+    frontmatter-order "type" "title"
+}
+"#;
+        let schema = Schema::from_str(kdl).unwrap();
+        let rules = schema.lint.unwrap().rules;
+        assert_eq!(rules.len(), 3);
+        assert_eq!(rules[0].kind, LintKind::HeadingCase(HeadingCase::Sentence));
+        assert_eq!(rules[0].severity, "error");
+        assert_eq!(rules[1].kind, LintKind::MaxLineLength(100));
+        assert_eq!(rules[1].severity, "warning");
+        assert_eq!(rules[2].kind, LintKind::FrontmatterOrder(vec!["type".into(), "title".into()]));
+
+        assert!(Schema::from_str(r#"lint { heading-case "shouty" }"#).is_err());
+        assert!(Schema::from_str(r#"lint { max-line-length 80 severity="fatal" }"#).is_err());
+    }
 }
@@ -0,0 +1,427 @@
+//! Schema definitions written back out as KDL, for the selftest round-trip.
+//!
+//! The output is built from the parsed [`Schema`], not copied from the source
+//! file, so it shows the schema as md-db understood it: comments are gone,
+//! defaults are spelled out, lists are normalized (`status="accepted,proposed"`),
+//! and repeated `transition` nodes for one state are merged.
+//! Types, relations, relation groups, ref-formats, and coverage rules are
+//! exported; project settings blocks such as `prose` are not.
+
+use kdl::{KdlDocument, KdlNode};
+
+use crate::schema::{
+    AutoTimestamp, Cardinality, ColumnDef, FieldDef, FieldType, PromoteSource, RelationDef, Schema, SchemaExample,
+    SectionDef, TypeDef,
+};
+
+/// The schema's definitions as a formatted KDL document that parses back to the same definitions.
+pub(crate) fn export(schema: &Schema) -> String {
+    let mut doc = KdlDocument::new();
+    let nodes = doc.nodes_mut();
+    for group in &schema.relation_groups {
+        let mut node = KdlNode::new("relation-group");
+        node.push(group.name.as_str());
+        push_opt(&mut node, "description", &group.description);
+        nodes.push(node);
+    }
+    nodes.extend(schema.relations.iter().map(relation_node));
+    if !schema.ref_formats.is_empty() {
+        let mut node = KdlNode::new("ref-format");
+        for rf in &schema.ref_formats {
+            let mut format = KdlNode::new(rf.name.as_str());
+            format.push(("pattern", rf.pattern.as_str()));
+            node.ensure_children().nodes_mut().push(format);
+        }
+        nodes.push(node);
+    }
+    nodes.extend(schema.types.iter().map(type_node));
+    if !schema.coverage.is_empty() {
+        let mut node = KdlNode::new("coverage");
+        for rule in &schema.coverage {
+            let mut every = KdlNode::new("every");
+            every.push(rule.doc_type.as_str());
+            every.push("must-have-relation");
+            every.push(rule.relation.as_str());
+            if let Some(ref to_type) = rule.to_type {
+                every.push("to-type");
+                every.push(to_type.as_str());
+            }
+            push_list(&mut every, "status", &rule.statuses);
+            every.push(("severity", rule.severity.as_str()));
+            node.ensure_children().nodes_mut().push(every);
+        }
+        nodes.push(node);
+    }
+    doc.autoformat();
+    doc.to_string()
+}
+
+fn relation_node(r: &RelationDef) -> KdlNode {
+    let mut node = KdlNode::new("relation");
+    node.push(r.name.as_str());
+    push_opt(&mut node, "inverse", &r.inverse);
+    let cardinality = match r.cardinality {
+        Cardinality::One => "one",
+        Cardinality::Many => "many",
+    };
+    node.push(("cardinality", cardinality));
+    push_opt(&mut node, "description", &r.description);
+    if let Some(acyclic) = r.acyclic {
+        node.push(("acyclic", acyclic));
+    }
+    push_opt(&mut node, "group", &r.group);
+    push_list(&mut node, "target-type", &r.target_types);
+    node
+}
+
+fn type_node(t: &TypeDef) -> KdlNode {
+    let mut node = KdlNode::new("type");
+    node.push(t.name.as_str());
+    push_opt(&mut node, "description", &t.description);
+    push_opt(&mut node, "folder", &t.folder);
+    if let Some(n) = t.max_count {
+        node.push(("max_count", n as i128));
+    }
+    if t.singleton {
+        node.push(("singleton", true));
+    }
+
+    // A type always has a body, even an empty one
+    let body = node.ensure_children().nodes_mut();
+    if let Some(ref pattern) = t.match_pattern {
+        body.push(arg_node("match", pattern));
+    }
+    if let Some(ref template) = t.template {
+        body.push(arg_node("template", template));
+    }
+    if let Some(ref permalink) = t.permalink {
+        body.push(arg_node("permalink", permalink));
+    }
+    body.extend(t.fields.iter().map(field_node));
+    if let Some(ref sf) = t.status_field {
+        let mut status = arg_node("status-field", &sf.field);
+        push_list(&mut status, "active", &sf.active);
+        push_list(&mut status, "terminal", &sf.terminal);
+        push_opt(&mut status, "deprecated", &sf.deprecated);
+        push_opt(&mut status, "superseded", &sf.superseded);
+        body.push(status);
+    }
+    if let Some(ref workflow) = t.workflow {
+        let mut node = KdlNode::new("workflow");
+        push_opt(&mut node, "field", &workflow.field);
+        for (from, to) in &workflow.transitions {
+            let mut transition = arg_node("transition", from);
+            push_list(&mut transition, "to", to);
+            node.ensure_children().nodes_mut().push(transition);
+        }
+        body.push(node);
+    }
+    for key in &t.order_by {
+        let mut order = arg_node("order-by", &key.field);
+        if key.desc {
+            order.push(("desc", true));
+        }
+        body.push(order);
+    }
+    for rule in &t.rules {
+        let mut node = arg_node("rule", &rule.name);
+        let mut when = arg_node("when", &rule.when_field);
+        when.push(("equals", rule.when_equals.as_str()));
+        let children = node.ensure_children().nodes_mut();
+        children.push(when);
+        children.extend(rule.then_required.iter().map(|f| arg_node("then-required", f)));
+        body.push(node);
+    }
+    for promote in &t.promotes {
+        let mut node = arg_node("promote", &promote.field);
+        node.push(("section", promote.section.as_str()));
+        match promote.source {
+            PromoteSource::TableCell { table, ref column, row } => {
+                node.push(("column", column.as_str()));
+                node.push(("table", table as i128));
+                node.push(("row", row as i128));
+            }
+            PromoteSource::YamlKey(ref key) => node.push(("yaml-key", key.as_str())),
+        }
+        body.push(node);
+    }
+    for c in &t.consistency {
+        let mut node = arg_node("consistent-with", &c.field);
+        node.push(("relation", c.relation.as_str()));
+        node.push(("field", c.other_field.as_str()));
+        node.push(("op", c.op.to_string()));
+        node.push(("severity", c.severity.as_str()));
+        body.push(node);
+    }
+    body.extend(t.sections.iter().map(section_node));
+    for p in &t.section_patterns {
+        let mut node = section_node(&p.section);
+        node.set_name("section-pattern");
+        push_count(&mut node, "min", p.min);
+        push_count(&mut node, "max", p.max);
+        body.push(node);
+    }
+    body.extend(t.examples.iter().map(example_node));
+    node
+}
+
+fn field_node(f: &FieldDef) -> KdlNode {
+    let mut node = arg_node("field", &f.name);
+    node.push(("type", field_type_name(&f.field_type)));
+    if f.required {
+        node.push(("required", true));
+    }
+    push_opt(&mut node, "pattern", &f.pattern);
+    push_opt(&mut node, "description", &f.description);
+    push_opt(&mut node, "default", &f.default);
+    if let Some(auto) = f.auto {
+        let auto = match auto {
+            AutoTimestamp::Created => "created",
+            AutoTimestamp::Updated => "updated",
+        };
+        node.push(("auto", auto));
+    }
+    push_opt(&mut node, "min", &f.min);
+    push_opt(&mut node, "max", &f.max);
+    if f.not_future {
+        node.push(("not-future", true));
+    }
+    push_list(&mut node, "target-type", &f.target_types);
+
+    let mut children = Vec::new();
+    if let FieldType::Enum(ref values) = f.field_type {
+        let mut node = KdlNode::new("values");
+        for v in values {
+            node.push(v.as_str());
+        }
+        children.push(node);
+    }
+    if !f.aliases.is_empty() {
+        let mut alias = KdlNode::new("alias");
+        for a in &f.aliases {
+            alias.push(a.as_str());
+        }
+        children.push(alias);
+    }
+    children.extend(f.examples.iter().map(example_node));
+    if !children.is_empty() {
+        node.ensure_children().nodes_mut().extend(children);
+    }
+    node
+}
+
+fn section_node(s: &SectionDef) -> KdlNode {
+    let mut node = arg_node("section", &s.name);
+    if s.required {
+        node.push(("required", true));
+    }
+    push_opt(&mut node, "description", &s.description);
+
+    let mut children = Vec::new();
+    if !s.aliases.is_empty() {
+        let mut alias = KdlNode::new("alias");
+        for a in &s.aliases {
+            alias.push(a.as_str());
+        }
+        children.push(alias);
+    }
+    if let Some(ref table) = s.table {
+        let mut node = KdlNode::new("table");
+        node.push(("required", table.required));
+        push_opt(&mut node, "description", &table.description);
+        let mut columns: Vec<KdlNode> = table.columns.iter().map(column_node).collect();
+        if let Some(ref order) = table.ordered_by {
+            let mut ordered = arg_node("ordered-by", &order.column);
+            ordered.push(("ascending", order.ascending));
+            columns.push(ordered);
+        }
+        if !columns.is_empty() {
+            node.ensure_children().nodes_mut().extend(columns);
+        }
+        children.push(node);
+    }
+    if let Some(ref content) = s.content {
+        let mut node = KdlNode::new("content");
+        push_count(&mut node, "min-paragraphs", content.min_paragraphs);
+        children.push(node);
+    }
+    if let Some(ref list) = s.list {
+        let mut node = KdlNode::new("list");
+        node.push(("required", list.required));
+        push_count(&mut node, "min-items", list.min_items);
+        children.push(node);
+    }
+    if let Some(ref diagram) = s.diagram {
+        let mut node = KdlNode::new("diagram");
+        node.push(("required", diagram.required));
+        push_opt(&mut node, "type", &diagram.diagram_type);
+        if diagram.check {
+            node.push(("check", true));
+        }
+        children.push(node);
+    }
+    children.extend(s.children.iter().map(section_node));
+    for p in &s.section_patterns {
+        let mut node = section_node(&p.section);
+        node.set_name("section-pattern");
+        push_count(&mut node, "min", p.min);
+        push_count(&mut node, "max", p.max);
+        children.push(node);
+    }
+    children.extend(s.examples.iter().map(example_node));
+    if !children.is_empty() {
+        node.ensure_children().nodes_mut().extend(children);
+    }
+    node
+}
+
+fn column_node(c: &ColumnDef) -> KdlNode {
+    let mut node = arg_node("column", &c.name);
+    node.push(("type", field_type_name(&c.col_type)));
+    if c.required {
+        node.push(("required", true));
+    }
+    push_opt(&mut node, "description", &c.description);
+    node
+}
+
+fn example_node(e: &SchemaExample) -> KdlNode {
+    let mut node = arg_node(if e.counter { "counter-example" } else { "example" }, &e.source);
+    push_list(&mut node, "expect", &e.expect);
+    node
+}
+
+/// The `type=` value a field type is declared with.
+fn field_type_name(t: &FieldType) -> &'static str {
+    match t {
+        FieldType::String => "string",
+        FieldType::Number => "number",
+        FieldType::Bool => "bool",
+        FieldType::Enum(_) => "enum",
+        FieldType::Ref => "ref",
+        FieldType::StringArray => "string[]",
+        FieldType::RefArray => "ref[]",
+        FieldType::User => "user",
+        FieldType::UserArray => "user[]",
+        FieldType::Date => "date",
+        FieldType::DateTime => "datetime",
+    }
+}
+
+/// `name "arg"`.
+fn arg_node(name: &str, arg: &str) -> KdlNode {
+    let mut node = KdlNode::new(name);
+    node.push(arg);
+    node
+}
+
+fn push_opt(node: &mut KdlNode, key: &str, value: &Option<String>) {
+    if let Some(v) = value {
+        node.push((key, v.as_str()));
+    }
+}
+
+/// A comma-separated list property, as `get_list_prop` reads it; omitted when empty.
+fn push_list(node: &mut KdlNode, key: &str, values: &[String]) {
+    if !values.is_empty() {
+        node.push((key, values.join(",")));
+    }
+}
+
+fn push_count(node: &mut KdlNode, key: &str, value: Option<usize>) {
+    if let Some(n) = value {
+        node.push((key, n as i128));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCHEMA: &str = r#"
+relation-group "lifecycle" description="How decisions replace each other"
+relation "supersedes" inverse="superseded_by" cardinality="one" group="lifecycle" target-type="adr"
+relation "enables" inverse="enabled_by" acyclic=#true
+
+ref-format {
+    adr pattern="ADR-\\d+"
+}
+
+type "adr" description="Decision record" folder="docs/adr" {
+    template "templates/adr.md"
+    permalink "/decisions/{id}/"
+    field "title" type="string" required=#true
+    field "status" type="enum" required=#true default="proposed" {
+        values "proposed" "accepted" "superseded"
+        alias "state"
+        counter-example "approved" expect="F021"
+    }
+    field "decided" type="date" min="2020-01-01" not-future=#true
+    field "related" type="ref[]" target-type="adr"
+    field "owner" type="user" auto="created"
+    status-field "status" active="accepted" terminal="superseded"
+    workflow {
+        transition "proposed" to="accepted"
+        transition "accepted" to="superseded"
+    }
+    order-by "decided" desc=#true
+    rule "accepted-needs-owner" {
+        when "status" equals="accepted"
+        then-required "owner"
+    }
+    promote "owner" section="Decision" column="Owner" row=1
+    consistent-with "decided" relation="supersedes" op=">="
+    section "Decision" required=#true {
+        alias "Outcome"
+        content min-paragraphs=2
+        table {
+            column "Owner" type="user" required=#true
+            column "Due"
+            ordered-by "Due" ascending=#false
+        }
+        section "Notes" {
+            list min-items=1
+            diagram type="mermaid" check=#true
+        }
+        example "We use Kafka."
+    }
+    section-pattern "Day \\d+" min=1 max=3 {
+        content
+    }
+}
+
+type "readme" singleton=#true max_count=1 {
+    match "README.md"
+}
+
+coverage {
+    every "adr" must-have-relation "enables" to-type "adr" status="accepted" severity="warning"
+}
+"#;
+
+    #[test]
+    fn test_export_round_trips() {
+        let schema = Schema::from_str(SCHEMA).unwrap();
+        let exported = export(&schema);
+        let reparsed = Schema::from_str(&exported).unwrap_or_else(|e| panic!("{e}\n{exported}"));
+
+        assert_eq!(schema.types, reparsed.types);
+        assert_eq!(schema.relations, reparsed.relations);
+        assert_eq!(schema.relation_groups, reparsed.relation_groups);
+        assert_eq!(schema.ref_formats, reparsed.ref_formats);
+        assert_eq!(schema.coverage, reparsed.coverage);
+        // Exporting the export changes nothing
+        assert_eq!(export(&reparsed), exported);
+    }
+
+    #[test]
+    fn test_export_quotes_strings() {
+        let schema = Schema::from_str(
+            "type \"note\" description=\"Say \\\"hi\\\"\\nthen go\" {\n    field \"title\" pattern=\"^[A-Z]\"\n}\n",
+        )
+        .unwrap();
+        let reparsed = Schema::from_str(&export(&schema)).unwrap();
+        assert_eq!(reparsed.types[0].description.as_deref(), Some("Say \"hi\"\nthen go"));
+        assert_eq!(reparsed.types[0].fields[0].pattern.as_deref(), Some("^[A-Z]"));
+    }
+}
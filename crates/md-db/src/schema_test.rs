@@ -0,0 +1,309 @@
+//! The schema's own unit tests (`md-db schema test`).
+//!
+//! Types, fields, and sections can carry `example` snippets that must validate
+//! cleanly and `counter-example` snippets that must fail:
+//!
+//! ```kdl
+//! type "adr" {
+//!     example "---\ntitle: Use Kafka\nstatus: accepted\n---\n# Decision\n\nWe use Kafka.\n"
+//!     field "status" type="enum" {
+//!         values "proposed" "accepted"
+//!         counter-example "approved" expect="F021"
+//!     }
+//!     section "Decision" required=#true {
+//!         content min-paragraphs=2
+//!         counter-example "TBD" expect="S030"
+//!     }
+//! }
+//! ```
+//!
+//! A type's snippet is a whole document (`type:` is filled in when left out). A
+//! field's snippet is its value, checked in a document holding only that field;
+//! a section's is its body, checked under its heading and its parents'. Only
+//! diagnostics at that field or section (or its subsections) count, so the other
+//! required fields and sections don't have to be spelled out.
+
+use std::collections::HashSet;
+
+use serde::Serialize;
+
+use crate::document::Document;
+use crate::schema::{FieldDef, Schema, SchemaExample, SectionDef, TypeDef};
+use crate::validation::{self, Diagnostic, Severity};
+
+/// Outcome of one example.
+#[derive(Debug, Clone, Serialize)]
+pub struct ExampleResult {
+    /// Where the example is declared, e.g. `adr > field "status"`.
+    pub target: String,
+    pub counter: bool,
+    /// First line of the snippet.
+    pub snippet: String,
+    pub passed: bool,
+    /// Codes the snippet produced, in order, without repeats.
+    pub codes: Vec<String>,
+    /// Why the example failed.
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SchemaTestReport {
+    pub results: Vec<ExampleResult>,
+}
+
+impl SchemaTestReport {
+    pub fn passed(&self) -> bool {
+        self.results.iter().all(|r| r.passed)
+    }
+
+    pub fn failed(&self) -> usize {
+        self.results.iter().filter(|r| !r.passed).count()
+    }
+}
+
+/// Check every example and counter-example in the schema, in declaration order.
+pub fn run(schema: &Schema) -> SchemaTestReport {
+    let mut results = Vec::new();
+    for type_def in &schema.types {
+        for example in &type_def.examples {
+            let diags = match Document::from_str(&example.source) {
+                Ok(mut doc) => {
+                    if doc.frontmatter.as_ref().is_some_and(|fm| !fm.has_field("type")) {
+                        doc.set_field_from_str("type", &type_def.name);
+                    }
+                    validate(&doc, type_def, schema)
+                }
+                Err(e) => vec![unparsable(e)],
+            };
+            results.push(judge(format!("type \"{}\"", type_def.name), example, &diags));
+        }
+        for field in &type_def.fields {
+            for example in &field.examples {
+                results.push(judge(
+                    format!("{} > field \"{}\"", type_def.name, field.name),
+                    example,
+                    &check_field(type_def, field, example, schema),
+                ));
+            }
+        }
+        let mut path = Vec::new();
+        for section in &type_def.sections {
+            check_sections(type_def, section, &mut path, schema, &mut results);
+        }
+    }
+    SchemaTestReport { results }
+}
+
+fn check_field(type_def: &TypeDef, field: &FieldDef, example: &SchemaExample, schema: &Schema) -> Vec<Diagnostic> {
+    let mut doc = match Document::from_str(&format!("---\ntype: {}\n---\n", type_def.name)) {
+        Ok(doc) => doc,
+        Err(e) => return vec![unparsable(e)],
+    };
+    doc.set_field_from_str(&field.name, &example.source);
+    let at = format!("frontmatter.{}", field.name);
+    validate(&doc, type_def, schema)
+        .into_iter()
+        .filter(|d| {
+            d.location
+                .strip_prefix(&at)
+                .is_some_and(|rest| rest.is_empty() || rest.starts_with(['[', '.']))
+        })
+        .collect()
+}
+
+/// Check the examples of `section` and its subsections; `path` holds the headings above it.
+fn check_sections<'a>(
+    type_def: &TypeDef,
+    section: &'a SectionDef,
+    path: &mut Vec<&'a str>,
+    schema: &Schema,
+    results: &mut Vec<ExampleResult>,
+) {
+    path.push(&section.name);
+    for example in &section.examples {
+        let target = format!("{} > section \"{}\"", type_def.name, path.join(" > "));
+        results.push(judge(target, example, &check_section(type_def, section, path, example, schema)));
+    }
+    for child in &section.children {
+        check_sections(type_def, child, path, schema, results);
+    }
+    path.pop();
+}
+
+fn check_section(
+    type_def: &TypeDef,
+    section: &SectionDef,
+    path: &[&str],
+    example: &SchemaExample,
+    schema: &Schema,
+) -> Vec<Diagnostic> {
+    let mut content = if type_def.singleton {
+        String::new()
+    } else {
+        format!("---\ntype: {}\n---\n\n", type_def.name)
+    };
+    for (depth, heading) in path.iter().enumerate() {
+        content.push_str(&format!("{} {heading}\n\n", "#".repeat(depth + 1)));
+    }
+    content.push_str(&example.source);
+    content.push('\n');
+    let doc = match Document::from_str(&content) {
+        Ok(doc) => doc,
+        Err(e) => return vec![unparsable(e)],
+    };
+
+    let mut names = Vec::new();
+    collect_names(section, &mut names);
+    // Missing required subsections are reported against the document body
+    let nested = format!("\"{} > ", path.join(" > "));
+    validate(&doc, type_def, schema)
+        .into_iter()
+        .filter(|d| {
+            names.iter().any(|n| d.location.starts_with(&format!("section \"{n}\"")))
+                || (d.code == "S010" && d.message.contains(&nested))
+        })
+        .collect()
+}
+
+fn collect_names<'a>(section: &'a SectionDef, names: &mut Vec<&'a str>) {
+    names.push(&section.name);
+    for child in &section.children {
+        collect_names(child, names);
+    }
+}
+
+fn validate(doc: &Document, type_def: &TypeDef, schema: &Schema) -> Vec<Diagnostic> {
+    let result = if type_def.singleton {
+        validation::validate_singleton(doc, type_def, None)
+    } else {
+        // Without known files or IDs, refs go unresolved rather than reported
+        validation::validate_document(doc, schema, &HashSet::new(), &HashSet::new(), None)
+    };
+    result.diagnostics
+}
+
+fn unparsable(e: crate::error::Error) -> Diagnostic {
+    Diagnostic {
+        severity: Severity::Error,
+        code: "F000".into(),
+        message: format!("example does not parse: {e}"),
+        location: "frontmatter".into(),
+        hint: None,
+        span: None,
+    }
+}
+
+fn judge(target: String, example: &SchemaExample, diags: &[Diagnostic]) -> ExampleResult {
+    let mut codes: Vec<String> = Vec::new();
+    for d in diags {
+        if !codes.contains(&d.code) {
+            codes.push(d.code.clone());
+        }
+    }
+    let reason = if !example.counter {
+        diags.first().map(|d| format!("expected no diagnostics, got {}: {}", d.code, d.message))
+    } else if example.expect.is_empty() {
+        let errors = diags.iter().any(|d| d.severity == Severity::Error);
+        match (errors, codes.is_empty()) {
+            (true, _) => None,
+            (false, true) => Some("expected an error, got none".to_string()),
+            (false, false) => Some(format!("expected an error, got only warnings ({})", codes.join(", "))),
+        }
+    } else {
+        let missing: Vec<&str> = example
+            .expect
+            .iter()
+            .filter(|c| !codes.contains(c))
+            .map(String::as_str)
+            .collect();
+        if missing.is_empty() {
+            None
+        } else if codes.is_empty() {
+            Some(format!("expected {}, got no diagnostics", missing.join(", ")))
+        } else {
+            Some(format!("expected {}, got {}", missing.join(", "), codes.join(", ")))
+        }
+    };
+    ExampleResult {
+        target,
+        counter: example.counter,
+        snippet: example.source.lines().find(|l| !l.trim().is_empty()).unwrap_or_default().to_string(),
+        passed: reason.is_none(),
+        codes,
+        reason,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCHEMA: &str = r##"
+type "adr" {
+    example "---\ntitle: Use Kafka\nstatus: accepted\n---\n# Context\n\nWhy.\n\n# Decision\n\nWe use Kafka.\n\nIt scales.\n"
+    counter-example "---\ntitle: Use Kafka\n---\n# Decision\n\nTBD\n" expect="F010,S010"
+    field "title" type="string" required=#true
+    field "status" type="enum" required=#true {
+        values "proposed" "accepted"
+        example "accepted"
+        counter-example "approved" expect="F021"
+        counter-example "proposed"
+    }
+    field "effort" type="number" {
+        example 3
+        counter-example "lots" expect="F020"
+    }
+    section "Context" required=#true
+    section "Decision" required=#true {
+        content min-paragraphs=2
+        example "We use Kafka.\n\nIt scales."
+        counter-example "TBD" expect="S030"
+    }
+}
+"##;
+
+    #[test]
+    fn test_run() {
+        let schema = Schema::from_str(SCHEMA).unwrap();
+        let report = run(&schema);
+        let summary: Vec<(&str, bool, bool)> =
+            report.results.iter().map(|r| (r.target.as_str(), r.counter, r.passed)).collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("type \"adr\"", false, true),
+                ("type \"adr\"", true, true),
+                ("adr > field \"status\"", false, true),
+                ("adr > field \"status\"", true, true),
+                ("adr > field \"status\"", true, false),
+                ("adr > field \"effort\"", false, true),
+                ("adr > field \"effort\"", true, true),
+                ("adr > section \"Decision\"", false, true),
+                ("adr > section \"Decision\"", true, true),
+            ],
+            "{:#?}",
+            report.results
+        );
+        // A counter-example that validates is the schema being too lax
+        let lax = &report.results[4];
+        assert_eq!(lax.reason.as_deref(), Some("expected an error, got none"));
+        assert_eq!((report.passed(), report.failed()), (false, 1));
+    }
+
+    #[test]
+    fn test_reports_unexpected_codes() {
+        let schema = Schema::from_str(
+            "type \"adr\" {\n    field \"status\" type=\"enum\" {\n        values \"a\" \"b\"\n        \
+             example \"c\"\n        counter-example \"d\" expect=\"F020\"\n    }\n}\n",
+        )
+        .unwrap();
+        let report = run(&schema);
+        let reasons: Vec<&str> = report.results.iter().filter_map(|r| r.reason.as_deref()).collect();
+        assert_eq!(reasons.len(), 2, "{reasons:?}");
+        assert!(reasons[0].starts_with("expected no diagnostics, got F021"), "{}", reasons[0]);
+        assert!(reasons[1].starts_with("expected F020, got F021"), "{}", reasons[1]);
+
+        // Only counter-examples take expected codes
+        assert!(Schema::from_str("type \"adr\" {\n    example \"x\" expect=\"F010\"\n}\n").is_err());
+    }
+}
@@ -47,10 +47,30 @@ pub fn search_documents(
     query: &str,
     options: &SearchOptions,
 ) -> Result<Vec<SearchResult>> {
-    let dir = dir.as_ref();
     let mut results = Vec::new();
+    search_documents_with(dir, query, options, |result| {
+        results.push(result);
+        Ok(())
+    })?;
+    results.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(results)
+}
 
-    for entry in WalkDir::new(dir).follow_links(true).into_iter().flatten() {
+/// Like [`search_documents`], but hands each result to `on_result` as soon as its
+/// document is searched (in directory order) instead of collecting them.
+pub fn search_documents_with<F>(
+    dir: impl AsRef<Path>,
+    query: &str,
+    options: &SearchOptions,
+    mut on_result: F,
+) -> Result<()>
+where
+    F: FnMut(SearchResult) -> Result<()>,
+{
+    let dir = dir.as_ref();
+    let mut found = 0;
+
+    for entry in WalkDir::new(dir).follow_links(true).sort_by_file_name().into_iter().flatten() {
         let path = entry.path();
         if !path.is_file() {
             continue;
@@ -65,17 +85,17 @@ pub fn search_documents(
         };
 
         if let Some(result) = search_single_document(path, &raw, query, options) {
-            results.push(result);
+            on_result(result)?;
+            found += 1;
             if let Some(max) = options.max_results {
-                if results.len() >= max {
+                if found >= max {
                     break;
                 }
             }
         }
     }
 
-    results.sort_by(|a, b| a.path.cmp(&b.path));
-    Ok(results)
+    Ok(())
 }
 
 /// Search a single document's raw content. Returns None if no matches.
@@ -1,4 +1,5 @@
 use comrak::Arena;
+use comrak::nodes::NodeValue;
 
 use crate::ast_util;
 use crate::table::Table;
@@ -34,6 +35,19 @@ impl Section {
             .collect()
     }
 
+    /// Fenced code blocks within this section as (info string, body) pairs.
+    pub fn code_blocks(&self) -> Vec<(String, String)> {
+        let arena = Arena::new();
+        let opts = ast_util::comrak_opts();
+        let root = comrak::parse_document(&arena, &self.content, &opts);
+        root.descendants()
+            .filter_map(|n| match n.data.borrow().value {
+                NodeValue::CodeBlock(ref cb) => Some((cb.info.trim().to_string(), cb.literal.clone())),
+                _ => None,
+            })
+            .collect()
+    }
+
     /// Get subsections (headings one level deeper within this section).
     pub fn subsections(&self) -> Vec<Section> {
         let arena = Arena::new();
@@ -98,4 +112,14 @@ mod tests {
         assert_eq!(subs[0].heading.trim(), "Sub1");
         assert_eq!(subs[1].heading.trim(), "Sub2");
     }
+
+    #[test]
+    fn test_code_blocks() {
+        let content = "Intro.\n\n```yaml\ntarget: 99.9\n```\n\n```\nplain\n```\n";
+        let s = Section::new("Test".into(), 2, format!("## Test\n\n{content}"), content.to_string());
+        let blocks = s.code_blocks();
+        assert_eq!(blocks.len(), 2);
+        assert_eq!(blocks[0], ("yaml".to_string(), "target: 99.9\n".to_string()));
+        assert_eq!(blocks[1].0, "");
+    }
 }
@@ -0,0 +1,360 @@
+//! End-to-end consistency checks against a live project.
+//!
+//! Each check runs an operation twice (or two ways) and reports where the results
+//! diverge. Checks that write (sync, fix) run on a scratch copy of the docs, so
+//! the project itself is never modified.
+
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+
+use crate::document::Document;
+use crate::error::{Error, Result};
+use crate::fix::FixEngine;
+use crate::graph::DocGraph;
+use crate::graph_cache::GraphSnapshot;
+use crate::schema::{Schema, TypeDef};
+use crate::schema_export;
+use crate::sync;
+use crate::users::UserConfig;
+use crate::validation;
+
+/// Outcome of one check.
+#[derive(Debug, Clone, Serialize)]
+pub struct Check {
+    pub name: &'static str,
+    pub passed: bool,
+    /// One line per divergence; a short summary when the check passed.
+    pub details: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SelftestReport {
+    pub checks: Vec<Check>,
+}
+
+impl SelftestReport {
+    pub fn passed(&self) -> bool {
+        self.checks.iter().all(|c| c.passed)
+    }
+}
+
+/// Run every check on the docs in `dir` with the schema at `schema_path`.
+pub fn run(dir: &Path, schema_path: &Path, users: Option<&UserConfig>) -> Result<SelftestReport> {
+    let schema = Schema::from_file(schema_path)?;
+
+    let checks = vec![
+        schema_round_trip(&schema),
+        graph_determinism(dir, &schema)?,
+        cache_matches_cold(dir, &schema)?,
+        sync_idempotent(dir, &schema)?,
+        fix_idempotent(dir, &schema, users)?,
+    ];
+    Ok(SelftestReport { checks })
+}
+
+/// Parse -> export -> parse yields the same type, field, and relation definitions.
+fn schema_round_trip(schema: &Schema) -> Check {
+    let name = "schema round-trip";
+    let exported = schema_export::export(schema);
+    let reparsed = match Schema::from_str(&exported) {
+        Ok(s) => s,
+        Err(e) => return fail(name, vec![format!("exported schema fails to parse: {e}")]),
+    };
+
+    let mut details = Vec::new();
+    for t in &schema.types {
+        let Some(other) = reparsed.get_type(&t.name) else {
+            details.push(format!("type \"{}\" missing after round-trip", t.name));
+            continue;
+        };
+        for f in &t.fields {
+            match other.fields.iter().find(|o| o.name == f.name) {
+                Some(o) if f == o => {}
+                Some(_) => details.push(format!("field \"{}\" of type \"{}\" differs after round-trip", f.name, t.name)),
+                None => details.push(format!("field \"{}\" of type \"{}\" missing after round-trip", f.name, t.name)),
+            }
+        }
+        if other.fields.len() != t.fields.len() {
+            details.push(format!("type \"{}\": {} field(s) before, {} after", t.name, t.fields.len(), other.fields.len()));
+        }
+        // Everything else the type declares: sections, rules, promotes, ...
+        let rest = |t: &TypeDef| TypeDef { fields: Vec::new(), ..t.clone() };
+        if rest(t) != rest(other) {
+            details.push(format!("type \"{}\" differs after round-trip", t.name));
+        }
+    }
+    if schema.types.len() != reparsed.types.len() {
+        details.push(format!("{} type(s) before, {} after", schema.types.len(), reparsed.types.len()));
+    }
+    for r in &schema.relations {
+        match reparsed.relations.iter().find(|o| o.name == r.name) {
+            Some(o) if r == o => {}
+            Some(_) => details.push(format!("relation \"{}\" differs after round-trip", r.name)),
+            None => details.push(format!("relation \"{}\" missing after round-trip", r.name)),
+        }
+    }
+    if schema.relations.len() != reparsed.relations.len() {
+        details.push(format!("{} relation(s) before, {} after", schema.relations.len(), reparsed.relations.len()));
+    }
+    let parts = [
+        ("relation groups", schema.relation_groups == reparsed.relation_groups),
+        ("ref formats", schema.ref_formats == reparsed.ref_formats),
+        ("coverage rules", schema.coverage == reparsed.coverage),
+    ];
+    for (what, same) in parts {
+        if !same {
+            details.push(format!("{what} differ after round-trip"));
+        }
+    }
+
+    if details.is_empty() {
+        pass(name, format!("{} type(s), {} relation(s)", schema.types.len(), schema.relations.len()))
+    } else {
+        fail(name, details)
+    }
+}
+
+/// Two cold builds produce the same nodes and edges, in the same order.
+fn graph_determinism(dir: &Path, schema: &Schema) -> Result<Check> {
+    let name = "graph determinism";
+    let first = DocGraph::build(dir, schema)?;
+    let second = DocGraph::build(dir, schema)?;
+    let details = compare_graphs(&first, &second, false)?;
+    Ok(if details.is_empty() {
+        pass(name, format!("{} node(s), {} edge(s)", first.nodes.len(), first.edges.len()))
+    } else {
+        fail(name, details)
+    })
+}
+
+/// The cached graph (.md-db/graph.bin brought up to date) equals a cold build.
+/// The snapshot is refreshed in memory only.
+fn cache_matches_cold(dir: &Path, schema: &Schema) -> Result<Check> {
+    let name = "cache vs cold build";
+    let files = crate::discovery::discover_files(dir, None, &[], false)?;
+    let mut snapshot = GraphSnapshot::load(dir, schema);
+    let stats = snapshot.refresh(&files, schema);
+    let cached = snapshot.to_graph();
+    let cold = DocGraph::build(dir, schema)?;
+    let details = compare_graphs(&cold, &cached, true)?;
+    Ok(if details.is_empty() {
+        pass(name, format!("{} reused, {} rescanned", stats.reused, stats.rescanned))
+    } else {
+        fail(name, details)
+    })
+}
+
+/// After one sync, a second sync has nothing left to do.
+fn sync_idempotent(dir: &Path, schema: &Schema) -> Result<Check> {
+    let name = "sync idempotency";
+    let scratch = Scratch::copy_of(dir)?;
+    let first = sync::compute_sync_plan(&scratch.root, schema)?;
+    sync::apply_sync_plan(&first)?;
+    let second = sync::compute_sync_plan(&scratch.root, schema)?;
+
+    Ok(if second.is_empty() {
+        pass(name, format!("first run: {} update(s), second run: none", first.actions.len()))
+    } else {
+        let details = second
+            .actions
+            .iter()
+            .map(|a| {
+                format!(
+                    "{}: second run still adds {} to \"{}\"",
+                    a.doc_id,
+                    a.add_refs.join(", "),
+                    a.field_name
+                )
+            })
+            .collect();
+        fail(name, details)
+    })
+}
+
+/// After one fix pass, a second pass changes no file.
+fn fix_idempotent(dir: &Path, schema: &Schema, users: Option<&UserConfig>) -> Result<Check> {
+    let name = "fix idempotency";
+    let scratch = Scratch::copy_of(dir)?;
+    let engine = FixEngine::new();
+
+    let first = fix_pass(&scratch.root, schema, users, &engine)?;
+    let second = fix_pass(&scratch.root, schema, users, &engine)?;
+
+    Ok(if second.is_empty() {
+        pass(name, format!("first run changed {} file(s), second run none", first.len()))
+    } else {
+        let details = second
+            .iter()
+            .map(|p| format!("{}: changed again on the second run", scratch.display(p)))
+            .collect();
+        fail(name, details)
+    })
+}
+
+/// Validate and fix every document under `root`; returns the files that changed.
+fn fix_pass(root: &Path, schema: &Schema, users: Option<&UserConfig>, engine: &FixEngine) -> Result<Vec<PathBuf>> {
+    let result = validation::validate_directory(root, schema, None, users)?;
+    let mut changed = Vec::new();
+    for fr in &result.file_results {
+        if fr.diagnostics.is_empty() {
+            continue;
+        }
+        let path = PathBuf::from(&fr.path);
+        let Ok(mut doc) = Document::from_file(&path) else {
+            continue;
+        };
+        let before = doc.raw.clone();
+        engine.fix(&mut doc, &fr.diagnostics, schema);
+        if doc.raw != before {
+            doc.save()?;
+            changed.push(path);
+        }
+    }
+    Ok(changed)
+}
+
+/// Differences between two graphs. `ignore_order` compares edges as a set.
+fn compare_graphs(a: &DocGraph, b: &DocGraph, ignore_order: bool) -> Result<Vec<String>> {
+    let mut details = Vec::new();
+    for (id, node) in &a.nodes {
+        match b.nodes.get(id) {
+            None => details.push(format!("{id}: missing from the second graph")),
+            Some(other) if serde_json::to_value(node)? != serde_json::to_value(other)? => {
+                details.push(format!("{id}: node differs"));
+            }
+            Some(_) => {}
+        }
+    }
+    for id in b.nodes.keys().filter(|id| !a.nodes.contains_key(*id)) {
+        details.push(format!("{id}: missing from the first graph"));
+    }
+
+    let edge_keys = |g: &DocGraph| {
+        let mut keys: Vec<String> = g
+            .edges
+            .iter()
+            .map(|e| format!("{} -{}-> {}", e.from, e.relation, e.to))
+            .collect();
+        if ignore_order {
+            keys.sort();
+        }
+        keys
+    };
+    let (ea, eb) = (edge_keys(a), edge_keys(b));
+    if ea != eb {
+        let only_a: Vec<&String> = ea.iter().filter(|e| !eb.contains(e)).collect();
+        let only_b: Vec<&String> = eb.iter().filter(|e| !ea.contains(e)).collect();
+        if only_a.is_empty() && only_b.is_empty() {
+            details.push("edges come out in a different order".into());
+        }
+        details.extend(only_a.into_iter().map(|e| format!("edge {e} only in the first graph")));
+        details.extend(only_b.into_iter().map(|e| format!("edge {e} only in the second graph")));
+    }
+    Ok(details)
+}
+
+fn pass(name: &'static str, summary: String) -> Check {
+    Check {
+        name,
+        passed: true,
+        details: vec![summary],
+    }
+}
+
+fn fail(name: &'static str, details: Vec<String>) -> Check {
+    Check {
+        name,
+        passed: false,
+        details,
+    }
+}
+
+/// A throwaway copy of a docs directory's markdown files, removed on drop.
+struct Scratch {
+    root: PathBuf,
+}
+
+impl Scratch {
+    fn copy_of(dir: &Path) -> Result<Self> {
+        let nanos = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos();
+        let root = std::env::temp_dir().join(format!("md-db-selftest-{}-{nanos}", std::process::id()));
+        let scratch = Scratch { root };
+
+        for path in crate::discovery::discover_files(dir, None, &[], false)? {
+            let rel = path.strip_prefix(dir).unwrap_or(&path);
+            let dest = scratch.root.join(rel);
+            if let Some(parent) = dest.parent() {
+                std::fs::create_dir_all(parent).map_err(|_| Error::WriteFailed(parent.to_path_buf()))?;
+            }
+            std::fs::copy(&path, &dest).map_err(|_| Error::WriteFailed(dest.clone()))?;
+        }
+        Ok(scratch)
+    }
+
+    /// A scratch path shown relative to the copy, i.e. as it is in the project.
+    fn display(&self, path: &Path) -> String {
+        path.strip_prefix(&self.root).unwrap_or(path).display().to_string()
+    }
+}
+
+impl Drop for Scratch {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.root);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCHEMA: &str = r#"
+relation "supersedes" inverse="superseded_by" cardinality="one"
+
+type "adr" {
+    field "title" type="string" required=#true
+    field "status" type="enum" required=#true {
+        values "proposed" "accepted" "superseded"
+    }
+    section "Decision" required=#true
+}
+"#;
+
+    #[test]
+    fn test_selftest_passes_and_leaves_project_untouched() {
+        let dir = tempfile::tempdir().unwrap();
+        let schema_path = dir.path().join("schema.kdl");
+        std::fs::write(&schema_path, SCHEMA).unwrap();
+        let docs = dir.path().join("docs");
+        std::fs::create_dir(&docs).unwrap();
+        let old = "---\ntype: adr\ntitle: Old\nstatus: superseded\n---\n\n# Decision\n\nA.\n";
+        let new = "---\ntype: adr\ntitle: New\nstatus: Accepted\nsupersedes: ADR-001\n---\n";
+        std::fs::write(docs.join("adr-001.md"), old).unwrap();
+        std::fs::write(docs.join("adr-002.md"), new).unwrap();
+
+        let report = run(&docs, &schema_path, None).unwrap();
+        assert!(report.passed(), "{report:?}");
+        assert_eq!(report.checks.len(), 5);
+
+        // sync and fix ran on a copy
+        assert_eq!(std::fs::read_to_string(docs.join("adr-001.md")).unwrap(), old);
+        assert_eq!(std::fs::read_to_string(docs.join("adr-002.md")).unwrap(), new);
+        let sync = report.checks.iter().find(|c| c.name == "sync idempotency").unwrap();
+        assert!(sync.details[0].starts_with("first run: 1 update"), "{:?}", sync.details);
+    }
+
+    #[test]
+    fn test_compare_graphs_reports_edge_order() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("adr-001.md"), "---\ntype: adr\nrelated: [ADR-002, ADR-003]\n---\n").unwrap();
+        let schema = Schema::from_str(r#"relation "related""#).unwrap();
+        let a = DocGraph::build(dir.path(), &schema).unwrap();
+        let mut b = DocGraph::build(dir.path(), &schema).unwrap();
+        b.edges.reverse();
+        assert_eq!(compare_graphs(&a, &b, false).unwrap(), vec!["edges come out in a different order"]);
+        assert!(compare_graphs(&a, &b, true).unwrap().is_empty());
+    }
+}
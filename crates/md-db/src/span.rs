@@ -0,0 +1,278 @@
+//! Source positions for diagnostics.
+//!
+//! Validators describe where a problem is with a location path
+//! (`frontmatter.reviewers[1]`, `section "Decision" > table`, `body:12`). [`locate`]
+//! resolves that path against the document to a [`Span`] in file coordinates, so
+//! editors can jump to the offending key or heading. Headings and tables come from
+//! comrak source positions, YAML syntax errors from the YAML parser.
+
+use comrak::Arena;
+use comrak::nodes::AstNode;
+use serde::Serialize;
+
+use crate::ast_util;
+use crate::document::Document;
+use crate::validation::Diagnostic;
+
+/// A range in the file. Lines and columns are 1-based; columns count characters.
+/// The end is exclusive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Span {
+    pub line: usize,
+    pub column: usize,
+    pub end_line: usize,
+    pub end_column: usize,
+}
+
+impl Span {
+    /// The text of a line, from its first non-blank character to its end.
+    fn of_line(line: usize, text: &str) -> Self {
+        let indent = text.chars().take_while(|c| c.is_whitespace()).count();
+        Self {
+            line,
+            column: indent + 1,
+            end_line: line,
+            end_column: text.trim_end().chars().count() + 1,
+        }
+    }
+}
+
+/// Resolve a diagnostic location to a span, or `None` when the location has no
+/// position (`file`, `type "adr"`) or its target isn't in the document (a missing
+/// section).
+pub fn locate(doc: &Document, location: &str) -> Option<Span> {
+    let lines: Vec<&str> = doc.raw.lines().collect();
+    let line_span = |n: usize| lines.get(n.checked_sub(1)?).map(|text| Span::of_line(n, text));
+
+    if let Some(n) = location.strip_prefix("line:") {
+        return line_span(n.parse().ok()?);
+    }
+    if let Some(n) = location.strip_prefix("body:") {
+        let n: usize = n.parse().ok()?;
+        return line_span(doc.body_line_offset() + n);
+    }
+    if location == "frontmatter" {
+        return doc.frontmatter.as_ref().and_then(|_| line_span(1));
+    }
+    if let Some(path) = location.strip_prefix("frontmatter.") {
+        return frontmatter_span(&lines, path);
+    }
+    if let Some(rest) = location.strip_prefix("section \"") {
+        let (name, rest) = rest.split_once('"')?;
+        return section_span(doc, name, rest.trim_start().starts_with("> table"));
+    }
+    None
+}
+
+/// Fill in the span of every diagnostic that doesn't have one yet.
+pub fn fill(doc: &Document, diags: &mut [Diagnostic]) {
+    for d in diags.iter_mut().filter(|d| d.span.is_none()) {
+        d.span = locate(doc, &d.location);
+    }
+}
+
+/// Position of the YAML syntax error in a document's frontmatter, if it has one.
+pub fn yaml_error_span(raw: &str) -> Option<Span> {
+    let mut lines = raw.lines();
+    if lines.next()?.trim_end() != "---" {
+        return None;
+    }
+    let yaml: Vec<&str> = lines.take_while(|l| l.trim_end() != "---").collect();
+    let err = serde_yaml::from_str::<serde_yaml::Value>(&yaml.join("\n")).err()?;
+    let loc = err.location()?;
+    // +1 for the opening `---`
+    let line = loc.line() + 1;
+    Some(Span { line, column: loc.column(), end_line: line, end_column: loc.column() + 1 })
+}
+
+enum Segment<'a> {
+    Key(&'a str),
+    Index(usize),
+}
+
+/// Split `reviewers[1].name` into keys and indices.
+fn segments(path: &str) -> Vec<Segment<'_>> {
+    let mut out = Vec::new();
+    for part in path.split('.') {
+        let (key, mut rest) = part.split_once('[').map_or((part, ""), |(k, r)| (k, r));
+        if !key.is_empty() {
+            out.push(Segment::Key(key));
+        }
+        while let Some((n, tail)) = rest.split_once(']') {
+            if let Ok(i) = n.parse() {
+                out.push(Segment::Index(i));
+            }
+            rest = tail.trim_start_matches('[');
+        }
+    }
+    out
+}
+
+fn indent_of(line: &str) -> usize {
+    line.len() - line.trim_start_matches(' ').len()
+}
+
+/// Span of a key or block-sequence item in the frontmatter. Stops at the deepest
+/// segment it can find, so `tags[2]` on a flow sequence (`tags: [a, b, c]`)
+/// points at the `tags` line.
+fn frontmatter_span(lines: &[&str], path: &str) -> Option<Span> {
+    if lines.first()?.trim_end() != "---" {
+        return None;
+    }
+    let end = lines.iter().skip(1).position(|l| l.trim_end() == "---")? + 1;
+
+    let mut found: Option<Span> = None;
+    // The value being searched is on lines `from..end`; `parent` is the column of
+    // its key, or `None` at the top level
+    let mut from = 1;
+    let mut parent: Option<usize> = None;
+    for seg in segments(path) {
+        let mut hit = None;
+        let mut seq_indent = None;
+        let mut count = 0;
+        for (i, line) in lines.iter().enumerate().take(end).skip(from) {
+            if line.trim().is_empty() || line.trim_start().starts_with('#') {
+                continue;
+            }
+            let indent = indent_of(line);
+            // A block sequence may sit at its key's indent; anything else there ends the value
+            let item = line.trim_start().starts_with('-');
+            if i > from && parent.is_some_and(|p| indent < p || (indent == p && !item)) {
+                break;
+            }
+            match seg {
+                Segment::Key(key) => {
+                    let t = line.trim_start().trim_start_matches("- ");
+                    let col = line.len() - t.len();
+                    if parent.is_none() && indent > 0 {
+                        continue;
+                    }
+                    if t.strip_prefix(key).is_some_and(|r| r.starts_with(':')) {
+                        hit = Some((i, col, col));
+                        break;
+                    }
+                }
+                Segment::Index(n) => {
+                    if item && indent == *seq_indent.get_or_insert(indent) {
+                        if count == n {
+                            // Keys of a mapping item are indented past the dash
+                            hit = Some((i, indent, indent + 1));
+                            break;
+                        }
+                        count += 1;
+                    }
+                }
+            }
+        }
+        let Some((i, col, next_parent)) = hit else { break };
+        let text = lines[i];
+        found = Some(Span {
+            line: i + 1,
+            column: text[..col].chars().count() + 1,
+            end_line: i + 1,
+            end_column: text.trim_end().chars().count() + 1,
+        });
+        from = i;
+        parent = Some(next_parent);
+    }
+    found
+}
+
+/// Span of a (possibly nested, `A > B`) section heading, or of the first table in
+/// the section when `table` is set.
+fn section_span(doc: &Document, name: &str, table: bool) -> Option<Span> {
+    let arena = Arena::new();
+    let opts = ast_util::comrak_opts();
+    let root = comrak::parse_document(&arena, &doc.body, &opts);
+    let offset = doc.body_line_offset();
+
+    let headings = ast_util::find_headings(root, None);
+    let mut start = 0;
+    let mut heading = None;
+    for part in name.split(" > ") {
+        let target = part.trim().to_lowercase();
+        let i = start
+            + headings[start..]
+                .iter()
+                .position(|n| ast_util::collect_text(n).trim().to_lowercase() == target)?;
+        heading = Some(headings[i]);
+        start = i + 1;
+    }
+    let heading = heading?;
+    if !table {
+        return Some(node_span(heading, &doc.body, offset));
+    }
+
+    let level = ast_util::heading_level(heading)?;
+    let from = heading.data.borrow().sourcepos.start.line;
+    let until = headings[start..]
+        .iter()
+        .find(|n| ast_util::heading_level(n).is_some_and(|l| l <= level))
+        .map_or(usize::MAX, |n| n.data.borrow().sourcepos.start.line);
+    let table = ast_util::find_tables(root).into_iter().find(|t| {
+        let line = t.data.borrow().sourcepos.start.line;
+        line > from && line < until
+    });
+    Some(node_span(table.unwrap_or(heading), &doc.body, offset))
+}
+
+fn node_span<'a>(node: &'a AstNode<'a>, body: &str, line_offset: usize) -> Span {
+    let pos = node.data.borrow().sourcepos;
+    // comrak counts bytes; spans count characters
+    let chars = |line: usize, bytes: usize| {
+        let text = body.lines().nth(line.saturating_sub(1)).unwrap_or("");
+        let end = (0..=bytes.min(text.len())).rev().find(|&i| text.is_char_boundary(i)).unwrap_or(0);
+        text[..end].chars().count()
+    };
+    Span {
+        line: pos.start.line + line_offset,
+        column: chars(pos.start.line, pos.start.column - 1) + 1,
+        end_line: pos.end.line + line_offset,
+        // comrak's end column is inclusive
+        end_column: chars(pos.end.line, pos.end.column) + 1,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DOC: &str = "---\ntype: adr\nstatus: nope\ntags: [a, b]\nreviewers:\n  - \"@alice\"\n  - \"@bob\"\nlinks:\n  supersedes: ADR-001\n---\n\n# Title\n\n```md\n## Decision\n```\n\n## Decision\n\n| A | B |\n|---|---|\n| 1 | 2 |\n\nText @ghost\n";
+
+    #[test]
+    fn test_locate() {
+        let doc = Document::from_str(DOC).unwrap();
+        let line = |loc: &str| locate(&doc, loc).map(|s| (s.line, s.column));
+
+        assert_eq!(line("frontmatter"), Some((1, 1)));
+        assert_eq!(locate(&doc, "frontmatter.status"), Some(Span { line: 3, column: 1, end_line: 3, end_column: 13 }));
+        assert_eq!(line("frontmatter.tags[1]"), Some((4, 1)));
+        assert_eq!(line("frontmatter.reviewers[1]"), Some((7, 3)));
+        assert_eq!(line("frontmatter.links.supersedes"), Some((9, 3)));
+        assert_eq!(line("frontmatter.missing"), None);
+
+        // The heading inside the code fence is not a heading
+        assert_eq!(line("section \"Decision\""), Some((18, 1)));
+        assert_eq!(line("section \"Title > Decision\""), Some((18, 1)));
+        assert_eq!(line("section \"Decision\" > table"), Some((20, 1)));
+        assert_eq!(line("section \"Missing\""), None);
+
+        let body_line = doc.body.lines().position(|l| l.contains("@ghost")).unwrap() + 1;
+        assert_eq!(line(&format!("body:{body_line}")), Some((24, 1)));
+        assert_eq!(line("line:2"), Some((2, 1)));
+        assert_eq!(line("type \"adr\""), None);
+    }
+
+    #[test]
+    fn test_node_span_counts_chars() {
+        let doc = Document::from_str("---\ntype: adr\n---\n\n## Über straße\n").unwrap();
+        assert_eq!(locate(&doc, "section \"Über straße\""), Some(Span { line: 5, column: 1, end_line: 5, end_column: 15 }));
+    }
+
+    #[test]
+    fn test_yaml_error_span() {
+        let span = yaml_error_span("---\ntype: adr\ntitle: [unclosed\n---\n\nBody\n").unwrap();
+        assert!(span.line >= 3, "{span:?}");
+        assert_eq!(yaml_error_span("---\ntype: adr\n---\n"), None);
+    }
+}
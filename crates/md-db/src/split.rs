@@ -0,0 +1,267 @@
+//! Extract a section into a document of its own (`md-db split`).
+//!
+//! The section's content, subsections included, moves to a new document of the
+//! chosen type under the next free ID. Its headings are raised so the subsections
+//! become the new document's top-level sections, and the original keeps the
+//! heading with a link to the new document in place of the content. Fields the
+//! new type declares are copied from the original, the new document points back
+//! at it through a relation (and the original at the new one through the
+//! relation's inverse, if declared), and relative links and `#anchor` links on
+//! both sides are rewritten so they still resolve after the move.
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use comrak::Arena;
+use serde_yaml::Value;
+
+use crate::assets::{absolute, local_target, normalize, relative_path, url_path};
+use crate::ast_util;
+use crate::document::Document;
+use crate::error::{Error, Result};
+use crate::graph::{path_to_id, DocGraph};
+use crate::schema::{Cardinality, FieldType, Schema};
+
+/// Fields never copied to the new document: it gets its own.
+const OWN_FIELDS: &[&str] = &["type", "title", "permalink", crate::uid::UID_FIELD];
+
+#[derive(Debug, Clone)]
+pub struct SplitOptions<'a> {
+    /// Heading of the section to extract (case-insensitive).
+    pub section: &'a str,
+    /// Type of the new document.
+    pub doc_type: &'a str,
+    /// Relation field on the new document that points at the original.
+    pub relation: &'a str,
+    /// Title of the new document; the section heading when `None`.
+    pub title: Option<&'a str>,
+}
+
+/// Both documents after a split, not yet written.
+#[derive(Debug, Clone)]
+pub struct Split {
+    pub parent_id: String,
+    /// The original document, with the section's content replaced by a link.
+    pub parent: Document,
+    pub child_id: String,
+    pub child_path: PathBuf,
+    /// The new document; its `path` is `child_path`.
+    pub child: Document,
+    /// Fields copied from the original.
+    pub copied: Vec<String>,
+}
+
+/// Plan moving `opts.section` of the document at `path` into a new document.
+/// The ID is the next free one for the type among the documents under `dir`,
+/// and the file goes in the type's `folder` (relative to `dir`), or next to the
+/// original when it has none. The type's `template` is relative to `schema_dir`.
+pub fn plan_split(path: &Path, dir: &Path, schema: &Schema, schema_dir: &Path, opts: &SplitOptions) -> Result<Split> {
+    let type_def = schema
+        .get_type(opts.doc_type)
+        .ok_or_else(|| Error::TypeNotFound(opts.doc_type.to_string()))?;
+    let (relation, is_inverse) = schema.find_relation(opts.relation).ok_or_else(|| {
+        let known = schema.all_relation_field_names().join(", ");
+        Error::Split(format!("unknown relation \"{}\" (schema declares: {known})", opts.relation))
+    })?;
+
+    let mut parent = Document::from_file(path)?;
+    let parent_id = path_to_id(path);
+    let section = parent.get_section(opts.section)?;
+    if section.content.trim().is_empty() {
+        return Err(Error::Split(format!("section \"{}\" is empty", section.heading.trim())));
+    }
+
+    let child_id = DocGraph::build(dir, schema)?.next_id(&type_def.name);
+    let parent_dir = absolute(path.parent().unwrap_or(Path::new(".")));
+    let child_dir = match type_def.folder {
+        Some(ref folder) => normalize(&absolute(dir).join(folder)),
+        None => parent_dir.clone(),
+    };
+    let file_name = format!("{}.md", child_id.to_lowercase());
+    let child_path = match type_def.folder {
+        Some(ref folder) => dir.join(folder).join(&file_name),
+        None => path.with_file_name(&file_name),
+    };
+    if child_path.exists() {
+        return Err(Error::Split(format!("{} already exists", child_path.display())));
+    }
+
+    // The new document starts from the type's template, for its frontmatter
+    let title = opts.title.map_or_else(|| section.heading.trim().to_string(), str::to_string);
+    let fields = vec![
+        ("title".to_string(), title.clone()),
+        (crate::uid::UID_FIELD.to_string(), crate::uid::generate()),
+    ];
+    let content = crate::template::new_document(type_def, schema, schema_dir, &fields, Some(&child_id), true)?;
+    let mut child = Document::from_str(&content)?;
+    child.path = Some(child_path.clone());
+    child.set_field("title", Value::String(title));
+
+    let relation_fields = schema.all_relation_field_names();
+    let mut copied = Vec::new();
+    if let Some(fm) = parent.frontmatter.clone() {
+        for field in &type_def.fields {
+            let name = field.name.as_str();
+            if OWN_FIELDS.contains(&name) || relation_fields.contains(&name) || field.auto.is_some() {
+                continue;
+            }
+            let Some(value) = fm.get(name) else { continue };
+            if let FieldType::Enum(ref values) = field.field_type {
+                if !value.as_str().is_some_and(|v| values.iter().any(|allowed| allowed == v)) {
+                    continue;
+                }
+            }
+            child.set_field(name, value.clone());
+            copied.push(field.name.clone());
+        }
+    }
+
+    // Forward side of a "one" relation holds a single ID; everything else a list
+    let single = relation.cardinality == Cardinality::One;
+    let (child_field, parent_field) = if is_inverse {
+        (opts.relation.to_string(), Some(relation.name.clone()))
+    } else {
+        (relation.name.clone(), relation.inverse.clone())
+    };
+    let back_ref = Value::String(parent_id.clone());
+    child.set_field(
+        &child_field,
+        if single && !is_inverse { back_ref } else { Value::Sequence(vec![back_ref]) },
+    );
+    if let Some(ref field) = parent_field {
+        add_ref(&mut parent, field, &child_id, single && is_inverse)?;
+    }
+
+    // Anchors that leave with the moved headings
+    let moved_anchors: HashSet<String> = {
+        let arena = Arena::new();
+        let root = comrak::parse_document(&arena, &section.content, &ast_util::comrak_opts());
+        ast_util::find_headings(root, None)
+            .into_iter()
+            .map(|node| ast_util::heading_anchor(&ast_util::collect_text(node)))
+            .collect()
+    };
+    let parent_name = path.file_name().map(PathBuf::from).unwrap_or_default();
+    let to_parent = url_path(&relative_path(&child_dir, &parent_dir.join(parent_name)));
+    let to_child = url_path(&relative_path(&parent_dir, &child_dir.join(&file_name)));
+
+    let raised = ast_util::shift_headings(&section.content, -i32::from(section.level));
+    let moved = ast_util::rewrite_urls(&raised, |url| {
+        if let Some(anchor) = url.strip_prefix('#') {
+            return (!moved_anchors.contains(anchor)).then(|| format!("{to_parent}{url}"));
+        }
+        let target = local_target(url)?;
+        if child_dir == parent_dir {
+            return None;
+        }
+        let suffix = url.find(['#', '?']).map_or("", |i| &url[i..]);
+        let moved_to = relative_path(&child_dir, &normalize(&parent_dir.join(target)));
+        Some(format!("{}{suffix}", url_path(&moved_to)))
+    });
+    child.set_body(&format!("\n{}\n", moved.trim()));
+
+    parent.replace_section_content(opts.section, &format!("\nSee [{child_id}]({to_child}).\n\n"))?;
+    let body = ast_util::rewrite_urls(&parent.body, |url| {
+        let anchor = url.strip_prefix('#')?;
+        moved_anchors.contains(anchor).then(|| format!("{to_child}{url}"))
+    });
+    parent.set_body(&format!("{}\n", body.trim_end()));
+
+    Ok(Split {
+        parent_id,
+        parent,
+        child_id,
+        child_path,
+        child,
+        copied,
+    })
+}
+
+/// Add `id` to the relation `field` of `doc`: as the value of a single-ref
+/// field, or appended to a list.
+fn add_ref(doc: &mut Document, field: &str, id: &str, single: bool) -> Result<()> {
+    let existing = doc.frontmatter.as_ref().and_then(|fm| fm.get(field)).cloned();
+    let value = match existing {
+        None | Some(Value::Null) if single => Value::String(id.to_string()),
+        None | Some(Value::Null) => Value::Sequence(vec![Value::String(id.to_string())]),
+        Some(Value::Sequence(mut refs)) => {
+            refs.push(Value::String(id.to_string()));
+            Value::Sequence(refs)
+        }
+        Some(other) if single => {
+            let current = crate::frontmatter::yaml_value_to_string(&other);
+            return Err(Error::Split(format!("\"{field}\" already refers to {current}")));
+        }
+        Some(other) => Value::Sequence(vec![other, Value::String(id.to_string())]),
+    };
+    doc.set_field(field, value);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plan_split() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        std::fs::create_dir_all(root.join("decisions")).unwrap();
+        std::fs::write(
+            root.join("decisions/adr-001.md"),
+            "---\ntype: adr\ntitle: Messaging\nstatus: accepted\nowner: ana\n---\n\n\
+             # Context\n\nSee [rollout](#phase-one) and [the diagram](img/flow.png).\n\n\
+             # Rollout\n\nIn phases; background in [context](#context).\n\n\
+             ## Phase one\n\nShadow traffic, see [runbook](../runbooks/kafka.md#steps).\n\n\
+             ### Checks\n\nLag below 1s.\n\n# Consequences\n\nMore ops work.\n",
+        )
+        .unwrap();
+        let schema = Schema::from_str(
+            "type \"adr\" folder=\"decisions\" {\n    field \"status\" type=\"enum\" {\n        \
+             values \"proposed\" \"accepted\"\n    }\n}\n\
+             type \"plan\" folder=\"plans\" {\n    field \"title\" type=\"string\"\n    \
+             field \"owner\" type=\"string\"\n    field \"status\" type=\"enum\" {\n        \
+             values \"draft\" \"active\"\n    }\n}\n\
+             relation \"part_of\" inverse=\"parts\" cardinality=\"one\"\n",
+        )
+        .unwrap();
+        let opts = SplitOptions {
+            section: "rollout",
+            doc_type: "plan",
+            relation: "part_of",
+            title: None,
+        };
+
+        let split = plan_split(&root.join("decisions/adr-001.md"), root, &schema, root, &opts).unwrap();
+        assert_eq!((split.parent_id.as_str(), split.child_id.as_str()), ("ADR-001", "PLAN-001"));
+        assert_eq!(split.child_path, root.join("plans/plan-001.md"));
+        // "status" isn't a valid plan status, so only "owner" carries over
+        assert_eq!(split.copied, vec!["owner"]);
+
+        let fm = split.child.frontmatter.as_ref().unwrap();
+        assert_eq!(fm.get_display("title").as_deref(), Some("Rollout"));
+        assert_eq!(fm.get_display("part_of").as_deref(), Some("ADR-001"));
+        assert_eq!(fm.get_display("status").as_deref(), Some("draft"));
+        assert_eq!(
+            split.child.body,
+            "\nIn phases; background in [context](../decisions/adr-001.md#context).\n\n\
+             # Phase one\n\nShadow traffic, see [runbook](../runbooks/kafka.md#steps).\n\n\
+             ## Checks\n\nLag below 1s.\n"
+        );
+
+        let parent = &split.parent;
+        assert_eq!(parent.frontmatter.as_ref().unwrap().get_display("parts").as_deref(), Some("[PLAN-001]"));
+        assert!(
+            parent.body.contains(
+                "See [rollout](../plans/plan-001.md#phase-one) and [the diagram](img/flow.png).\n\n\
+                 # Rollout\n\nSee [PLAN-001](../plans/plan-001.md).\n\n# Consequences\n"
+            ),
+            "{}",
+            parent.body
+        );
+
+        let opts = SplitOptions { relation: "parent", ..opts };
+        let err = plan_split(&root.join("decisions/adr-001.md"), root, &schema, root, &opts).unwrap_err();
+        assert!(err.to_string().contains("unknown relation \"parent\""), "{err}");
+    }
+}
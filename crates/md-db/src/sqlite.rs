@@ -0,0 +1,436 @@
+//! Read-only SQLite mirror of a docs directory, for SQL and BI tools.
+//!
+//! `md-db export --format sqlite` materializes these tables:
+//!
+//! | Table        | One row per                                                    |
+//! |--------------|----------------------------------------------------------------|
+//! | `documents`  | document: `path`, `id`, `uid`, `type`, `title`, `hash`, `body` |
+//! | `fields`     | frontmatter value: `name`, `position` (array index), `value`   |
+//! | `sections`   | heading: `position`, `heading`, `level`, `parent`, `content`   |
+//! | `table_rows` | table cell: `section`, `table_index`, `row_index`, `column_name`, `value` |
+//! | `edges`      | graph edge: `from_id`, `relation`, `to_id`                     |
+//!
+//! Every table is keyed on `path`, relative to the exported directory. With a
+//! schema there is also a `type_<name>` view per document type with one column
+//! per field.
+//!
+//! Refreshes are incremental: each document's content hash is stored, and only
+//! added, changed, and removed files are rewritten. A new mirror format or a
+//! schema with different relations rebuilds everything. The database is written
+//! through the `sqlite3` binary, in one transaction.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use serde_yaml::Value;
+
+use crate::document::Document;
+use crate::error::{Error, Result};
+use crate::graph::{path_to_id, DocEdge, DocGraph};
+use crate::schema::{FieldType, Schema};
+use crate::section::Section;
+
+/// Bump when the tables' layout changes; mirrors of another version are rebuilt.
+pub const FORMAT_VERSION: u32 = 1;
+
+/// Tables md-db owns in the mirror, cleared on rebuild.
+const TABLES: &[&str] = &["documents", "fields", "sections", "table_rows", "edges"];
+
+const CREATE: &str = "\
+CREATE TABLE IF NOT EXISTS md_db_meta (key TEXT PRIMARY KEY, value TEXT);
+CREATE TABLE IF NOT EXISTS documents (path TEXT PRIMARY KEY, id TEXT, uid TEXT, type TEXT, title TEXT, \
+hash TEXT NOT NULL, body TEXT);
+CREATE TABLE IF NOT EXISTS fields (path TEXT NOT NULL, name TEXT NOT NULL, position INTEGER NOT NULL, value, \
+PRIMARY KEY (path, name, position));
+CREATE TABLE IF NOT EXISTS sections (path TEXT NOT NULL, position INTEGER NOT NULL, heading TEXT, level INTEGER, \
+parent TEXT, content TEXT, PRIMARY KEY (path, position));
+CREATE TABLE IF NOT EXISTS table_rows (path TEXT NOT NULL, section TEXT, table_index INTEGER, row_index INTEGER, \
+column_name TEXT, value TEXT);
+CREATE TABLE IF NOT EXISTS edges (path TEXT NOT NULL, from_id TEXT, relation TEXT, to_id TEXT);
+CREATE INDEX IF NOT EXISTS fields_name ON fields (name, value);
+CREATE INDEX IF NOT EXISTS table_rows_path ON table_rows (path, section);
+CREATE INDEX IF NOT EXISTS edges_path ON edges (path);
+CREATE INDEX IF NOT EXISTS edges_to ON edges (to_id);
+";
+
+/// What an existing mirror holds, read back before a refresh.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MirrorState {
+    /// Format and schema key the mirror was built with.
+    pub key: Option<String>,
+    /// Content hash per document path.
+    pub hashes: BTreeMap<String, String>,
+    /// `type_*` views present.
+    pub views: Vec<String>,
+}
+
+/// What a refresh changed.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Refresh {
+    pub added: Vec<String>,
+    pub updated: Vec<String>,
+    pub removed: Vec<String>,
+    pub unchanged: usize,
+    /// Whether every table was cleared first (new mirror, format, or schema).
+    pub rebuilt: bool,
+}
+
+/// Create or refresh the mirror of `dir` at `db`.
+pub fn export(dir: &Path, schema: Option<&Schema>, db: &Path) -> Result<Refresh> {
+    let state = read_state(db)?;
+    let (sql, refresh) = refresh_sql(dir, schema, &state)?;
+    sqlite3(db, &["-bail"], Some(&sql))?;
+    Ok(refresh)
+}
+
+/// Read the key, document hashes, and views of the mirror at `db`. A missing
+/// file, or one without md-db's tables, reads as empty.
+pub fn read_state(db: &Path) -> Result<MirrorState> {
+    let mut state = MirrorState::default();
+    if !db.exists() {
+        return Ok(state);
+    }
+    let query = "SELECT 'view' AS k, name AS a, '' AS b FROM sqlite_master \
+                 WHERE type = 'view' AND name LIKE 'type\\_%' ESCAPE '\\'";
+    state.views = rows(&sqlite3(db, &["-json"], Some(query))?)
+        .into_iter()
+        .map(|(_, name, _)| name)
+        .collect();
+    let query = "SELECT 'meta' AS k, key AS a, value AS b FROM md_db_meta \
+                 UNION ALL SELECT 'doc', path, hash FROM documents";
+    // Not an md-db mirror (yet): its own tables are left alone
+    let Ok(out) = sqlite3(db, &["-json"], Some(query)) else {
+        return Ok(state);
+    };
+    for (kind, a, b) in rows(&out) {
+        match kind.as_str() {
+            "meta" if a == "key" => state.key = Some(b),
+            "doc" => {
+                state.hashes.insert(a, b);
+            }
+            _ => {}
+        }
+    }
+    Ok(state)
+}
+
+/// `(k, a, b)` rows of `sqlite3 -json` output.
+fn rows(json: &str) -> Vec<(String, String, String)> {
+    let rows: Vec<BTreeMap<String, serde_json::Value>> = serde_json::from_str(json).unwrap_or_default();
+    let text = |row: &BTreeMap<String, serde_json::Value>, key: &str| match row.get(key) {
+        Some(serde_json::Value::String(s)) => s.clone(),
+        Some(serde_json::Value::Null) | None => String::new(),
+        Some(other) => other.to_string(),
+    };
+    rows.iter().map(|r| (text(r, "k"), text(r, "a"), text(r, "b"))).collect()
+}
+
+/// The SQL script that brings a mirror in `state` up to date with `dir`.
+pub fn refresh_sql(dir: &Path, schema: Option<&Schema>, state: &MirrorState) -> Result<(String, Refresh)> {
+    let key = mirror_key(schema);
+    let mut refresh = Refresh {
+        rebuilt: state.key.as_deref() != Some(key.as_str()),
+        ..Refresh::default()
+    };
+    let graph = schema.map(|s| DocGraph::build_cached(dir, s)).transpose()?;
+    let ids: HashMap<PathBuf, &str> = graph
+        .iter()
+        .flat_map(|g| g.nodes.values())
+        .map(|n| (n.path.clone(), n.id.as_str()))
+        .collect();
+    let mut edges: HashMap<&str, Vec<&DocEdge>> = HashMap::new();
+    for edge in graph.iter().flat_map(|g| &g.edges) {
+        edges.entry(edge.from.as_str()).or_default().push(edge);
+    }
+
+    let mut sql = String::from("BEGIN;\n");
+    if refresh.rebuilt {
+        for table in TABLES {
+            sql.push_str(&format!("DROP TABLE IF EXISTS {table};\n"));
+        }
+    }
+    sql.push_str(CREATE);
+
+    let mut seen = HashSet::new();
+    for path in crate::discovery::discover_files(dir, None, &[], false)? {
+        let rel = relative(dir, &path);
+        let Ok(content) = std::fs::read_to_string(&path) else {
+            continue;
+        };
+        let hash = format!("{:016x}", crate::cache::hash_bytes(content.as_bytes()));
+        seen.insert(rel.clone());
+        let previous = (!refresh.rebuilt).then(|| state.hashes.get(&rel)).flatten();
+        if previous == Some(&hash) {
+            refresh.unchanged += 1;
+            continue;
+        }
+        let Ok(doc) = Document::from_str(&content) else {
+            if previous.is_some() {
+                delete_rows(&mut sql, &rel);
+                refresh.removed.push(rel);
+            }
+            continue;
+        };
+        if previous.is_some() {
+            delete_rows(&mut sql, &rel);
+            refresh.updated.push(rel.clone());
+        } else {
+            refresh.added.push(rel.clone());
+        }
+        let id = ids.get(&path).map_or_else(|| path_to_id(&path), |id| id.to_string());
+        let doc_edges = edges.get(id.as_str()).map(Vec::as_slice).unwrap_or_default();
+        insert_document(&mut sql, &rel, &id, &hash, &doc, doc_edges);
+    }
+    if !refresh.rebuilt {
+        for rel in state.hashes.keys().filter(|p| !seen.contains(p)) {
+            delete_rows(&mut sql, rel);
+            refresh.removed.push(rel.clone());
+        }
+    }
+
+    for view in &state.views {
+        sql.push_str(&format!("DROP VIEW IF EXISTS {};\n", ident(view)));
+    }
+    if let Some(schema) = schema {
+        type_views(&mut sql, schema);
+    }
+    sql.push_str(&format!(
+        "INSERT OR REPLACE INTO md_db_meta (key, value) VALUES ('key', {});\nCOMMIT;\n",
+        quote(&key)
+    ));
+    Ok((sql, refresh))
+}
+
+/// Changes when the format or the relations the edges were read with do.
+fn mirror_key(schema: Option<&Schema>) -> String {
+    match schema {
+        Some(schema) => format!("v{FORMAT_VERSION}:{:016x}", crate::graph_cache::schema_key(schema)),
+        None => format!("v{FORMAT_VERSION}:none"),
+    }
+}
+
+fn relative(dir: &Path, path: &Path) -> String {
+    path.strip_prefix(dir).unwrap_or(path).to_string_lossy().replace('\\', "/")
+}
+
+fn delete_rows(sql: &mut String, path: &str) {
+    for table in TABLES {
+        sql.push_str(&format!("DELETE FROM {table} WHERE path = {};\n", quote(path)));
+    }
+}
+
+fn insert_document(sql: &mut String, path: &str, id: &str, hash: &str, doc: &Document, edges: &[&DocEdge]) {
+    let fm = doc.frontmatter.as_ref();
+    let text = |key: &str| fm.and_then(|fm| fm.get_display(key)).map_or_else(|| "NULL".to_string(), |v| quote(&v));
+    let p = quote(path);
+    sql.push_str(&format!(
+        "INSERT INTO documents (path, id, uid, type, title, hash, body) VALUES ({p}, {}, {}, {}, {}, {}, {});\n",
+        quote(id),
+        text("uid"),
+        text("type"),
+        text("title"),
+        quote(hash),
+        quote(&doc.body)
+    ));
+
+    for (name, value) in fm.map(|fm| fm.data().iter()).into_iter().flatten() {
+        let values: Vec<&Value> = match value {
+            Value::Sequence(items) => items.iter().collect(),
+            other => vec![other],
+        };
+        for (position, value) in values.into_iter().enumerate() {
+            sql.push_str(&format!(
+                "INSERT INTO fields (path, name, position, value) VALUES ({p}, {}, {position}, {});\n",
+                quote(name),
+                sql_value(value)
+            ));
+        }
+    }
+
+    let mut position = 0;
+    insert_sections(sql, &p, &doc.sections(), None, &mut position);
+
+    for edge in edges {
+        sql.push_str(&format!(
+            "INSERT INTO edges (path, from_id, relation, to_id) VALUES ({p}, {}, {}, {});\n",
+            quote(&edge.from),
+            quote(&edge.relation),
+            quote(&edge.to)
+        ));
+    }
+}
+
+/// Sections depth-first; each table belongs to the closest heading above it.
+fn insert_sections(sql: &mut String, p: &str, sections: &[Section], parent: Option<&str>, position: &mut usize) {
+    for section in sections {
+        let heading = section.heading.trim();
+        sql.push_str(&format!(
+            "INSERT INTO sections (path, position, heading, level, parent, content) \
+             VALUES ({p}, {position}, {}, {}, {}, {});\n",
+            quote(heading),
+            section.level,
+            parent.map_or_else(|| "NULL".to_string(), quote),
+            quote(&section.content)
+        ));
+        *position += 1;
+
+        let subsections = section.subsections();
+        let own = subsections
+            .first()
+            .and_then(|sub| section.content.find(&sub.raw))
+            .map_or(section.content.as_str(), |end| &section.content[..end]);
+        let own = Section::new(heading.to_string(), section.level, String::new(), own.to_string());
+        for (table_index, table) in own.tables().iter().enumerate() {
+            for (row_index, row) in table.rows().iter().enumerate() {
+                for (column, value) in table.headers().iter().zip(row) {
+                    sql.push_str(&format!(
+                        "INSERT INTO table_rows (path, section, table_index, row_index, column_name, value) \
+                         VALUES ({p}, {}, {table_index}, {row_index}, {}, {});\n",
+                        quote(heading),
+                        quote(column),
+                        quote(value.trim())
+                    ));
+                }
+            }
+        }
+        insert_sections(sql, p, &subsections, Some(heading), position);
+    }
+}
+
+/// `type_<name>` views: `path`, `id`, and one column per schema field, array
+/// fields joined with `, `.
+fn type_views(sql: &mut String, schema: &Schema) {
+    for type_def in &schema.types {
+        let mut columns = vec!["d.path".to_string(), "d.id".to_string()];
+        for field in type_def.fields.iter().filter(|f| f.name != "path" && f.name != "id") {
+            let name = quote(&field.name);
+            let value = match field.field_type {
+                FieldType::StringArray | FieldType::RefArray | FieldType::UserArray => format!(
+                    "(SELECT group_concat(value, ', ') FROM fields f WHERE f.path = d.path AND f.name = {name})"
+                ),
+                _ => format!(
+                    "(SELECT value FROM fields f WHERE f.path = d.path AND f.name = {name} AND f.position = 0)"
+                ),
+            };
+            columns.push(format!("{value} AS {}", ident(&field.name)));
+        }
+        sql.push_str(&format!(
+            "CREATE VIEW {} AS SELECT {} FROM documents d WHERE d.type = {};\n",
+            ident(&format!("type_{}", type_def.name)),
+            columns.join(", "),
+            quote(&type_def.name)
+        ));
+    }
+}
+
+/// A frontmatter value as an SQL literal: numbers and booleans typed, nested
+/// values as JSON text.
+fn sql_value(value: &Value) -> String {
+    match value {
+        Value::Null => "NULL".to_string(),
+        Value::Bool(b) => (*b as u8).to_string(),
+        Value::Number(n) if n.as_f64().is_some_and(f64::is_finite) => n.to_string(),
+        Value::Number(n) => quote(&n.to_string()),
+        Value::String(s) => quote(s),
+        Value::Tagged(tagged) => sql_value(&tagged.value),
+        other => quote(&serde_json::to_string(&crate::frontmatter::yaml_to_json(other)).unwrap_or_default()),
+    }
+}
+
+/// An SQL string literal.
+fn quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', "''"))
+}
+
+/// An SQL identifier.
+fn ident(s: &str) -> String {
+    format!("\"{}\"", s.replace('"', "\"\""))
+}
+
+/// Run `sqlite3 DB ARGS`, feeding `stdin` as the script. Returns stdout.
+fn sqlite3(db: &Path, args: &[&str], stdin: Option<&str>) -> Result<String> {
+    let mut child = Command::new("sqlite3")
+        .args(args)
+        .arg(db)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| Error::Export(format!("failed to run sqlite3 (is it installed?): {e}")))?;
+    if let (Some(input), Some(mut pipe)) = (stdin, child.stdin.take()) {
+        pipe.write_all(input.as_bytes())?;
+    }
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        return Err(Error::Export(format!(
+            "sqlite3 failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCHEMA: &str = "relation \"enables\" inverse=\"enabled_by\" cardinality=\"many\"\ntype \"adr\" {\n    field \"title\" type=\"string\"\n    field \"tags\" type=\"string[]\"\n}\n";
+
+    #[test]
+    fn test_refresh_sql() {
+        let dir = tempfile::tempdir().unwrap();
+        let schema = Schema::from_str(SCHEMA).unwrap();
+        std::fs::write(
+            dir.path().join("adr-001.md"),
+            "---\ntype: adr\ntitle: It's Kafka\ntags: [infra, queue]\nenables: [ADR-002]\n---\n# Timeline\n\n| Time | Event |\n|---|---|\n| 09:00 | alert |\n\n## Detail\n\nText\n",
+        )
+        .unwrap();
+        std::fs::write(dir.path().join("adr-002.md"), "---\ntype: adr\ntitle: Two\n---\n").unwrap();
+
+        let (sql, refresh) = refresh_sql(dir.path(), Some(&schema), &MirrorState::default()).unwrap();
+        assert!(refresh.rebuilt);
+        assert_eq!(refresh.added, vec!["adr-001.md", "adr-002.md"]);
+        assert!(sql.contains("DROP TABLE IF EXISTS documents;"));
+        assert!(sql.contains("'adr-001.md', 'ADR-001', NULL, 'adr', 'It''s Kafka'"), "{sql}");
+        assert!(sql.contains("VALUES ('adr-001.md', 'tags', 1, 'queue');"));
+        assert!(sql.contains("VALUES ('adr-001.md', 1, 'Detail', 2, 'Timeline', "));
+        assert!(sql.contains("VALUES ('adr-001.md', 'Timeline', 0, 0, 'Event', 'alert');"));
+        assert_eq!(sql.matches("INSERT INTO table_rows").count(), 2, "table counted once, under Timeline");
+        assert!(sql.contains("VALUES ('adr-001.md', 'ADR-001', 'enables', 'ADR-002');"));
+        assert!(sql.contains("CREATE VIEW \"type_adr\" AS SELECT d.path, d.id, (SELECT value"));
+        assert!(sql.trim_end().ends_with("COMMIT;"));
+
+        // Feed the result back: only the changed and removed files are touched
+        let mut state = MirrorState {
+            key: Some(mirror_key(Some(&schema))),
+            views: vec!["type_adr".into()],
+            ..MirrorState::default()
+        };
+        for name in ["adr-001.md", "adr-002.md"] {
+            let content = std::fs::read(dir.path().join(name)).unwrap();
+            state.hashes.insert(name.into(), format!("{:016x}", crate::cache::hash_bytes(&content)));
+        }
+        state.hashes.insert("gone.md".into(), "0".into());
+        std::fs::write(dir.path().join("adr-002.md"), "---\ntype: adr\ntitle: Two, revised\n---\n").unwrap();
+
+        let (sql, refresh) = refresh_sql(dir.path(), Some(&schema), &state).unwrap();
+        assert!(!refresh.rebuilt);
+        assert_eq!((refresh.unchanged, refresh.updated.as_slice()), (1, &["adr-002.md".to_string()][..]));
+        assert_eq!(refresh.removed, vec!["gone.md"]);
+        assert!(!sql.contains("DROP TABLE"));
+        assert!(sql.contains("DELETE FROM fields WHERE path = 'gone.md';"));
+        assert!(!sql.contains("'adr-001.md'"));
+        assert!(sql.contains("DROP VIEW IF EXISTS \"type_adr\";"));
+    }
+
+    #[test]
+    fn test_sql_values() {
+        assert_eq!(sql_value(&Value::Null), "NULL");
+        assert_eq!(sql_value(&serde_yaml::from_str("true").unwrap()), "1");
+        assert_eq!(sql_value(&serde_yaml::from_str("42").unwrap()), "42");
+        assert_eq!(sql_value(&serde_yaml::from_str("{a: 1}").unwrap()), "'{\"a\":1}'");
+        assert_eq!(ident("odd\"name"), "\"odd\"\"name\"");
+    }
+}
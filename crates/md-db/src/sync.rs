@@ -5,6 +5,7 @@ use crate::document::Document;
 use crate::error::Result;
 use crate::graph::DocGraph;
 use crate::schema::{Cardinality, Schema};
+use crate::validation::{Diagnostic, FileResult, Severity, ValidationResult};
 
 /// A single field update to apply to a document.
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -17,6 +18,8 @@ pub struct SyncAction {
     pub field_name: String,
     /// References to add.
     pub add_refs: Vec<String>,
+    /// Stale inverse references to remove (only planned with `prune`).
+    pub remove_refs: Vec<String>,
 }
 
 /// A plan describing all sync actions needed to make inverse relations consistent.
@@ -42,11 +45,22 @@ impl SyncPlan {
             out.push_str(&format!("warning: {w}\n"));
         }
         for action in &self.actions {
-            let refs = action.add_refs.join(", ");
-            out.push_str(&format!(
-                "{}: add {} to field \"{}\"\n",
-                action.doc_id, refs, action.field_name,
-            ));
+            if !action.add_refs.is_empty() {
+                out.push_str(&format!(
+                    "{}: add {} to field \"{}\"\n",
+                    action.doc_id,
+                    action.add_refs.join(", "),
+                    action.field_name,
+                ));
+            }
+            if !action.remove_refs.is_empty() {
+                out.push_str(&format!(
+                    "{}: remove {} from field \"{}\"\n",
+                    action.doc_id,
+                    action.remove_refs.join(", "),
+                    action.field_name,
+                ));
+            }
         }
         out.push_str(&format!(
             "\n{} document(s) to update.\n",
@@ -66,6 +80,7 @@ impl SyncPlan {
                     "doc_id": a.doc_id,
                     "field": a.field_name,
                     "add_refs": a.add_refs,
+                    "remove_refs": a.remove_refs,
                 })
             })
             .collect();
@@ -75,11 +90,64 @@ impl SyncPlan {
             "action_count": self.actions.len(),
         })
     }
+
+    /// The plan as diagnostics for `sync --check`: R030 for each missing inverse
+    /// ref, R031 for each stale one a prune would remove. Spans point into the
+    /// documents as they are on disk.
+    pub fn to_diagnostics(&self) -> ValidationResult {
+        let mut file_results: Vec<FileResult> = Vec::new();
+        for action in &self.actions {
+            let field = &action.field_name;
+            let location = format!("frontmatter.{field}");
+            let mut diags: Vec<Diagnostic> = action
+                .add_refs
+                .iter()
+                .map(|r| Diagnostic {
+                    severity: Severity::Error,
+                    code: "R030".into(),
+                    message: format!("{r} links to {} but \"{field}\" does not list it", action.doc_id),
+                    location: location.clone(),
+                    hint: Some("run `md-db sync` to add the inverse ref".into()),
+                    span: None,
+                })
+                .collect();
+            diags.extend(action.remove_refs.iter().map(|r| Diagnostic {
+                severity: Severity::Error,
+                code: "R031".into(),
+                message: format!("\"{field}\" lists {r}, which does not link back"),
+                location: location.clone(),
+                hint: Some("run `md-db sync --prune` to remove it, or add the forward ref".into()),
+                span: None,
+            }));
+            if let Ok(doc) = Document::from_file(&action.path) {
+                crate::span::fill(&doc, &mut diags);
+            }
+
+            let path = action.path.display().to_string();
+            match file_results.iter_mut().find(|fr| fr.path == path) {
+                Some(fr) => fr.diagnostics.extend(diags),
+                None => file_results.push(FileResult { path, diagnostics: diags }),
+            }
+        }
+        ValidationResult { file_results }
+    }
 }
 
 /// Compute which inverse-relation fields are missing and need to be added.
 pub fn compute_sync_plan(dir: impl AsRef<Path>, schema: &Schema) -> Result<SyncPlan> {
     let graph = DocGraph::build(&dir, schema)?;
+    Ok(plan_from_graph(&graph, schema))
+}
+
+/// Compute a sync plan from an already-built graph (e.g. one loaded from the graph cache).
+pub fn plan_from_graph(graph: &DocGraph, schema: &Schema) -> SyncPlan {
+    plan_from_graph_with(graph, schema, false)
+}
+
+/// Like [`plan_from_graph`]. With `prune`, the forward field is the source of
+/// truth: an inverse ref whose document no longer links back (or no longer
+/// exists) is removed instead of being mirrored onto the forward side.
+pub fn plan_from_graph_with(graph: &DocGraph, schema: &Schema, prune: bool) -> SyncPlan {
     let mut actions: BTreeMap<(String, String), SyncAction> = BTreeMap::new();
     let mut warnings = Vec::new();
 
@@ -87,6 +155,20 @@ pub fn compute_sync_plan(dir: impl AsRef<Path>, schema: &Schema) -> Result<SyncP
         let Some((rel_def, is_inverse)) = schema.find_relation(&edge.relation) else {
             continue;
         };
+        let links_back = || {
+            graph
+                .edges
+                .iter()
+                .any(|e| e.from == edge.to && e.to == edge.from && e.relation == rel_def.name)
+        };
+        if prune && is_inverse && !links_back() {
+            if let Some(node) = graph.nodes.get(&edge.from) {
+                action_for(&mut actions, &edge.from, &node.path, &edge.relation)
+                    .remove_refs
+                    .push(edge.to.clone());
+            }
+            continue;
+        }
 
         // Determine the inverse field name and its cardinality.
         let (inverse_field, inverse_cardinality) = if is_inverse {
@@ -125,10 +207,13 @@ pub fn compute_sync_plan(dir: impl AsRef<Path>, schema: &Schema) -> Result<SyncP
             continue;
         }
 
-        // Cardinality check for "one" fields
+        // Cardinality check for "one" fields; a value about to be pruned doesn't count
         if inverse_cardinality == Cardinality::One {
             let existing = graph.edges.iter().any(|e| {
-                e.from == *target_id && e.relation == inverse_field
+                e.from == *target_id
+                    && e.relation == inverse_field
+                    && (!prune
+                        || graph.edges.iter().any(|f| f.from == e.to && f.to == e.from && f.relation == rel_def.name))
             });
             if existing {
                 warnings.push(format!(
@@ -140,29 +225,42 @@ pub fn compute_sync_plan(dir: impl AsRef<Path>, schema: &Schema) -> Result<SyncP
         }
 
         // Merge into actions map (group by target doc + field)
-        let key = (target_id.clone(), inverse_field.clone());
-        actions
-            .entry(key)
-            .or_insert_with(|| SyncAction {
-                path: target_node.path.clone(),
-                doc_id: target_id.clone(),
-                field_name: inverse_field.clone(),
-                add_refs: Vec::new(),
-            })
+        action_for(&mut actions, target_id, &target_node.path, &inverse_field)
             .add_refs
             .push(source_id.clone());
     }
 
-    // Sort add_refs within each action for determinism
+    // Sort refs within each action for determinism
     let mut final_actions: Vec<SyncAction> = actions.into_values().collect();
     for action in &mut final_actions {
         action.add_refs.sort();
+        action.add_refs.dedup();
+        action.remove_refs.sort();
+        action.remove_refs.dedup();
     }
 
-    Ok(SyncPlan {
+    SyncPlan {
         actions: final_actions,
         warnings,
-    })
+    }
+}
+
+/// The action for one document field, created on first use.
+fn action_for<'a>(
+    actions: &'a mut BTreeMap<(String, String), SyncAction>,
+    doc_id: &str,
+    path: &Path,
+    field: &str,
+) -> &'a mut SyncAction {
+    actions
+        .entry((doc_id.to_string(), field.to_string()))
+        .or_insert_with(|| SyncAction {
+            path: path.to_path_buf(),
+            doc_id: doc_id.to_string(),
+            field_name: field.to_string(),
+            add_refs: Vec::new(),
+            remove_refs: Vec::new(),
+        })
 }
 
 /// Apply a sync plan: update frontmatter of affected documents.
@@ -185,8 +283,14 @@ pub fn apply_sync_plan(plan: &SyncPlan) -> Result<()> {
             _ => Vec::new(),
         };
 
-        // Build new ref list (existing + additions, deduped)
+        // Build new ref list (existing minus pruned refs, plus additions, deduped)
         let mut new_refs = existing_refs;
+        new_refs.retain(|e| !action.remove_refs.iter().any(|r| e.eq_ignore_ascii_case(r)));
+        if new_refs.is_empty() && action.add_refs.is_empty() {
+            doc.remove_field(&action.field_name);
+            doc.save()?;
+            continue;
+        }
         for r in &action.add_refs {
             if !new_refs.iter().any(|e| e.eq_ignore_ascii_case(r)) {
                 new_refs.push(r.clone());
@@ -372,6 +476,63 @@ type "adr" {
         fs::remove_dir_all(&dir).ok();
     }
 
+    #[test]
+    fn test_prune_and_check() {
+        let dir = tempfile::tempdir().unwrap();
+        let schema = Schema::from_str(
+            "relation \"enables\" inverse=\"enabled_by\" cardinality=\"many\"\nrelation \"supersedes\" inverse=\"superseded_by\" cardinality=\"one\"\n",
+        )
+        .unwrap();
+        // ADR-001 dropped `enables: [ADR-002]`; ADR-003 says it was superseded by ADR-001, which
+        // now supersedes ADR-002 instead
+        let files = [
+            ("adr-001.md", "---\ntype: adr\nenables: [ADR-003]\nsupersedes: ADR-002\n---\n"),
+            ("adr-002.md", "---\ntype: adr\nenabled_by: [ADR-001, ADR-009]\n---\n"),
+            ("adr-003.md", "---\ntype: adr\nsuperseded_by: ADR-001\n---\n"),
+        ];
+        for (name, content) in files {
+            fs::write(dir.path().join(name), content).unwrap();
+        }
+        let graph = DocGraph::build(dir.path(), &schema).unwrap();
+
+        // Without prune, stale inverse refs are mirrored back onto the forward side
+        let plan = plan_from_graph(&graph, &schema);
+        assert!(plan.actions.iter().all(|a| a.remove_refs.is_empty()));
+        assert!(plan.actions.iter().any(|a| a.doc_id == "ADR-001" && a.field_name == "enables"));
+
+        let plan = plan_from_graph_with(&graph, &schema, true);
+        let summary: Vec<(&str, &str, &[String], &[String])> = plan
+            .actions
+            .iter()
+            .map(|a| (a.doc_id.as_str(), a.field_name.as_str(), &a.add_refs[..], &a.remove_refs[..]))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("ADR-002", "enabled_by", &[][..], &["ADR-001".to_string(), "ADR-009".to_string()][..]),
+                ("ADR-002", "superseded_by", &["ADR-001".to_string()][..], &[][..]),
+                ("ADR-003", "enabled_by", &["ADR-001".to_string()][..], &[][..]),
+                ("ADR-003", "superseded_by", &[][..], &["ADR-001".to_string()][..]),
+            ]
+        );
+
+        let check = plan.to_diagnostics();
+        let codes: Vec<&str> =
+            check.file_results.iter().flat_map(|fr| &fr.diagnostics).map(|d| d.code.as_str()).collect();
+        assert_eq!(codes, vec!["R031", "R031", "R030", "R030", "R031"]);
+        let first = &check.file_results[0].diagnostics[0];
+        assert_eq!(first.message, "\"enabled_by\" lists ADR-001, which does not link back");
+        assert_eq!(first.span.as_ref().map(|s| s.line), Some(3));
+
+        apply_sync_plan(&plan).unwrap();
+        let adr2 = Document::from_file(dir.path().join("adr-002.md")).unwrap();
+        assert!(adr2.frontmatter().unwrap().get("enabled_by").is_none());
+        let adr3 = Document::from_file(dir.path().join("adr-003.md")).unwrap();
+        assert!(adr3.frontmatter().unwrap().get("superseded_by").is_none());
+        let graph = DocGraph::build(dir.path(), &schema).unwrap();
+        assert!(plan_from_graph_with(&graph, &schema, true).is_empty());
+    }
+
     #[test]
     fn test_cardinality_one_warning() {
         let dir = std::env::temp_dir().join("md_db_sync_test_card_one");
@@ -1,3 +1,5 @@
+use std::cmp::Ordering;
+
 use serde_json::Value;
 
 use crate::error::{Error, Result};
@@ -87,6 +89,15 @@ impl Table {
         Ok(())
     }
 
+    /// Add a column, empty in every row.
+    pub fn add_column(&mut self, name: &str) {
+        self.headers.push(name.to_string());
+        let width = self.headers.len();
+        for row in &mut self.rows {
+            row.resize(width, String::new());
+        }
+    }
+
     /// Add a row. Pads or truncates to match header count.
     pub fn add_row(&mut self, values: Vec<String>) {
         let mut row = values;
@@ -95,6 +106,47 @@ impl Table {
         self.rows.push(row);
     }
 
+    /// Index of the first row whose `col` value is out of order relative to the
+    /// previous non-empty one, comparing with [`compare_cells`]. Empty cells are skipped.
+    pub fn first_out_of_order(&self, col: &str, ascending: bool) -> Option<usize> {
+        let col_idx = self.headers.iter().position(|h| h == col)?;
+        let mut prev: Option<&str> = None;
+        for (i, row) in self.rows.iter().enumerate() {
+            let cell = row.get(col_idx).map(|s| s.trim()).unwrap_or("");
+            if cell.is_empty() {
+                continue;
+            }
+            if let Some(prev) = prev {
+                let ord = compare_cells(prev, cell);
+                if (ascending && ord.is_gt()) || (!ascending && ord.is_lt()) {
+                    return Some(i);
+                }
+            }
+            prev = Some(cell);
+        }
+        None
+    }
+
+    /// Stable-sort the rows by a column, comparing with [`compare_cells`].
+    /// Rows with an empty cell go last in either direction.
+    pub fn sort_by_column(&mut self, col: &str, ascending: bool) -> Result<()> {
+        let col_idx = self
+            .headers
+            .iter()
+            .position(|h| h == col)
+            .ok_or_else(|| Error::ColumnNotFound(col.to_string()))?;
+        let cell = |row: &[String]| row.get(col_idx).map(|s| s.trim().to_string()).unwrap_or_default();
+        self.rows.sort_by(|a, b| {
+            let (a, b) = (cell(a), cell(b));
+            match (a.is_empty(), b.is_empty()) {
+                (false, false) if ascending => compare_cells(&a, &b),
+                (false, false) => compare_cells(&b, &a),
+                (a_empty, b_empty) => a_empty.cmp(&b_empty),
+            }
+        });
+        Ok(())
+    }
+
     /// Render as GFM markdown table.
     pub fn to_markdown(&self) -> String {
         if self.headers.is_empty() {
@@ -179,6 +231,50 @@ impl Table {
     }
 }
 
+/// Order two cells by what they hold: dates and datetimes chronologically, then
+/// times of day (`HH:MM[:SS]`), then numbers, then text. Cells of different kinds
+/// sort in that order, so the ordering is total.
+pub fn compare_cells(a: &str, b: &str) -> Ordering {
+    let (a, b) = (cell_key(a.trim()), cell_key(b.trim()));
+    a.0.cmp(&b.0)
+        .then(a.1.cmp(&b.1))
+        .then(a.2.total_cmp(&b.2))
+        .then_with(|| a.3.cmp(b.3))
+}
+
+/// (kind, integer value, numeric value, text) of a cell.
+fn cell_key(s: &str) -> (u8, i64, f64, &str) {
+    if let Some(ts) = crate::validation::parse_timestamp(s) {
+        return (0, ts, 0.0, "");
+    }
+    if let Some(secs) = parse_time_of_day(s) {
+        return (1, secs, 0.0, "");
+    }
+    match s.parse::<f64>() {
+        Ok(n) if n.is_finite() => (2, 0, n, ""),
+        _ => (3, 0, 0.0, s),
+    }
+}
+
+/// `H:MM`, `HH:MM`, or `HH:MM:SS` as seconds since midnight.
+fn parse_time_of_day(s: &str) -> Option<i64> {
+    let parts: Vec<&str> = s.split(':').collect();
+    if !(2..=3).contains(&parts.len())
+        || parts[0].is_empty()
+        || parts[0].len() > 2
+        || parts[1..].iter().any(|p| p.len() != 2)
+        || !parts.iter().all(|p| p.bytes().all(|b| b.is_ascii_digit()))
+    {
+        return None;
+    }
+    let values: Vec<i64> = parts.iter().map(|p| p.parse().unwrap_or_default()).collect();
+    let second = values.get(2).copied().unwrap_or(0);
+    if values[0] > 23 || values[1] > 59 || second > 60 {
+        return None;
+    }
+    Some(values[0] * 3600 + values[1] * 60 + second)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -259,4 +355,32 @@ mod tests {
         assert!(md.contains("| Alice | 8 |"));
         assert!(md.contains("| Bob | 6 |"));
     }
+
+    #[test]
+    fn test_order_by_column() {
+        assert_eq!(compare_cells("2024-01-09", "2024-01-10T08:00Z"), Ordering::Less);
+        assert_eq!(compare_cells("9:30", "10:15"), Ordering::Less);
+        assert_eq!(compare_cells("9", "10"), Ordering::Less);
+        assert_eq!(compare_cells("b", "a"), Ordering::Greater);
+
+        let mut t = Table::new(
+            vec!["Time".into(), "Event".into()],
+            vec![
+                vec!["2024-03-02".into(), "deploy".into()],
+                vec!["".into(), "unknown".into()],
+                vec!["2024-03-01 14:05".into(), "alert".into()],
+                vec!["2024-03-10".into(), "review".into()],
+            ],
+        );
+        assert_eq!(t.first_out_of_order("Time", true), Some(2));
+        assert_eq!(t.first_out_of_order("Time", false), Some(3));
+        assert_eq!(t.first_out_of_order("Missing", true), None);
+
+        t.sort_by_column("Time", true).unwrap();
+        assert_eq!(t.get_column("Event").unwrap(), vec!["alert", "deploy", "review", "unknown"]);
+        assert_eq!(t.first_out_of_order("Time", true), None);
+        t.sort_by_column("Time", false).unwrap();
+        assert_eq!(t.get_column("Event").unwrap(), vec!["review", "deploy", "alert", "unknown"]);
+        assert!(t.sort_by_column("Missing", true).is_err());
+    }
 }
@@ -0,0 +1,218 @@
+//! Aggregate one table section across many documents into a single dataset.
+//!
+//! Every matching document contributes the rows of the first table (or the table at a
+//! given index) under a named section, prefixed with the document's ID and title.
+//! When the schema defines a table for that section, its columns fix the column order
+//! and cell types: `number` cells become numbers, `bool` cells booleans, everything
+//! else stays text. Empty cells are null.
+
+use std::path::PathBuf;
+
+use serde_json::Value;
+
+use crate::document::Document;
+use crate::error::Result;
+use crate::schema::{FieldType, Schema, SectionDef, TableDef};
+
+/// Rows gathered from many documents, with `id` and `title` as the first two columns.
+#[derive(Debug, Clone, Default)]
+pub struct TableDataset {
+    pub columns: Vec<String>,
+    pub rows: Vec<Vec<Value>>,
+}
+
+/// Options for [`collect`].
+#[derive(Debug, Clone, Default)]
+pub struct TableQuery {
+    /// Section heading holding the table (case-insensitive).
+    pub section: String,
+    /// Only documents of this type.
+    pub doc_type: Option<String>,
+    /// Which table in the section, 0-based.
+    pub index: usize,
+}
+
+/// Concatenate the table under `query.section` from every document in `files`.
+/// Documents without the section or table are skipped.
+pub fn collect(files: &[PathBuf], schema: &Schema, query: &TableQuery) -> Result<TableDataset> {
+    let mut columns: Vec<String> = vec!["id".into(), "title".into()];
+    let mut types: Vec<Option<FieldType>> = vec![None, None];
+    if let Some(table_def) = query.doc_type.as_deref().and_then(|t| table_def(schema, t, &query.section)) {
+        for col in &table_def.columns {
+            columns.push(col.name.clone());
+            types.push(Some(col.col_type.clone()));
+        }
+    }
+
+    let mut records: Vec<Vec<(String, String)>> = Vec::new();
+    for path in files {
+        let Ok(doc) = Document::from_file(path) else {
+            continue;
+        };
+        let fm = doc.frontmatter.as_ref();
+        let doc_type = fm.and_then(|fm| fm.get_display("type"));
+        if let Some(ref wanted) = query.doc_type {
+            if doc_type.as_deref() != Some(wanted.as_str()) {
+                continue;
+            }
+        }
+        let Ok(section) = doc.get_section(&query.section) else {
+            continue;
+        };
+        let Some(table) = section.tables().into_iter().nth(query.index) else {
+            continue;
+        };
+
+        // Without a --type, the first document's own type decides the columns
+        if columns.len() == 2 {
+            if let Some(table_def) = doc_type.as_deref().and_then(|t| table_def(schema, t, &query.section)) {
+                for col in &table_def.columns {
+                    columns.push(col.name.clone());
+                    types.push(Some(col.col_type.clone()));
+                }
+            }
+        }
+        for header in table.headers() {
+            if !columns.iter().any(|c| c.eq_ignore_ascii_case(header)) {
+                columns.push(header.clone());
+                types.push(None);
+            }
+        }
+
+        let id = crate::graph::path_to_id(path);
+        let title = fm.and_then(|fm| fm.get_display("title")).unwrap_or_default();
+        for row in table.rows() {
+            let mut record = vec![("id".to_string(), id.clone()), ("title".to_string(), title.clone())];
+            record.extend(table.headers().iter().cloned().zip(row.iter().cloned()));
+            records.push(record);
+        }
+    }
+
+    let rows = records
+        .into_iter()
+        .map(|record| {
+            columns
+                .iter()
+                .zip(&types)
+                .map(|(col, ty)| {
+                    let cell = record.iter().find(|(h, _)| h.eq_ignore_ascii_case(col)).map(|(_, v)| v.as_str());
+                    typed_cell(cell.unwrap_or(""), ty.as_ref())
+                })
+                .collect()
+        })
+        .collect();
+
+    Ok(TableDataset { columns, rows })
+}
+
+impl TableDataset {
+    /// RFC 4180 CSV with a header row. Nulls are empty cells.
+    pub fn to_csv(&self) -> String {
+        crate::output::projection::to_delimited(&self.columns, &self.rows, ',')
+    }
+
+    /// Array of row objects keyed by column name.
+    pub fn to_json(&self) -> Value {
+        let rows: Vec<Value> = self
+            .rows
+            .iter()
+            .map(|row| {
+                let obj: serde_json::Map<String, Value> =
+                    self.columns.iter().cloned().zip(row.iter().cloned()).collect();
+                Value::Object(obj)
+            })
+            .collect();
+        Value::Array(rows)
+    }
+}
+
+/// The schema's table definition for `section` in type `doc_type`, searching nested sections.
+pub fn table_def<'a>(schema: &'a Schema, doc_type: &str, section: &str) -> Option<&'a TableDef> {
+    fn find<'a>(sections: &'a [SectionDef], name: &str) -> Option<&'a TableDef> {
+        sections.iter().find_map(|s| {
+            if s.name.eq_ignore_ascii_case(name) {
+                s.table.as_ref()
+            } else {
+                find(&s.children, name)
+            }
+        })
+    }
+    find(&schema.get_type(doc_type)?.sections, section)
+}
+
+fn typed_cell(cell: &str, ty: Option<&FieldType>) -> Value {
+    let cell = cell.trim();
+    if cell.is_empty() {
+        return Value::Null;
+    }
+    match ty {
+        Some(FieldType::Number) => {
+            if let Ok(n) = cell.parse::<i64>() {
+                return Value::from(n);
+            }
+            match cell.parse::<f64>().ok().and_then(serde_json::Number::from_f64) {
+                Some(n) => Value::Number(n),
+                None => Value::String(cell.to_string()),
+            }
+        }
+        Some(FieldType::Bool) => match cell.to_lowercase().as_str() {
+            "true" | "yes" => Value::Bool(true),
+            "false" | "no" => Value::Bool(false),
+            _ => Value::String(cell.to_string()),
+        },
+        _ => Value::String(cell.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCHEMA: &str = r#"
+type "inc" {
+    field "title" type="string"
+    section "Action Items" {
+        table {
+            column "Action" type="string"
+            column "Owner" type="string"
+            column "Days" type="number"
+        }
+    }
+}
+"#;
+
+    #[test]
+    fn test_collect_tables() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("inc-001.md"),
+            "---\ntype: inc\ntitle: Outage, part 1\n---\n\n# Action Items\n\n| Owner | Action | Days |\n|---|---|---|\n| @ana | Add \"retry\" | 3 |\n",
+        )
+        .unwrap();
+        std::fs::write(
+            dir.path().join("inc-002.md"),
+            "---\ntype: inc\ntitle: Second\n---\n\n# Action Items\n\n| Action | Owner | Days |\n|---|---|---|\n| Alerting | @bo |  |\n",
+        )
+        .unwrap();
+        std::fs::write(dir.path().join("notes.md"), "# Action Items\n\n| Action |\n|---|\n| x |\n").unwrap();
+
+        let schema = Schema::from_str(SCHEMA).unwrap();
+        let files = crate::discovery::discover_files(dir.path(), None, &[], false).unwrap();
+        let query = TableQuery {
+            section: "action items".into(),
+            doc_type: Some("inc".into()),
+            index: 0,
+        };
+        let data = collect(&files, &schema, &query).unwrap();
+
+        assert_eq!(data.columns, vec!["id", "title", "Action", "Owner", "Days"]);
+        assert_eq!(data.rows.len(), 2);
+        assert_eq!(data.rows[0][4], Value::from(3));
+        assert_eq!(data.rows[1][4], Value::Null);
+
+        let csv = data.to_csv();
+        assert!(csv.starts_with("id,title,Action,Owner,Days\r\n"));
+        assert!(csv.contains("INC-001,\"Outage, part 1\",\"Add \"\"retry\"\"\",@ana,3\r\n"));
+        assert_eq!(data.to_json()[1]["Owner"], "@bo");
+    }
+}
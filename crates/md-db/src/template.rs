@@ -1,10 +1,14 @@
 use std::collections::BTreeMap;
+use std::path::Path;
 use std::time::{SystemTime, UNIX_EPOCH};
 
+use comrak::nodes::NodeValue;
 use serde_yaml::Value;
 
+use crate::error::{Error, Result};
 use crate::frontmatter::Frontmatter;
 use crate::schema::{FieldDef, FieldType, Schema, SectionDef, TypeDef};
+use crate::users::UserConfig;
 
 /// Generate a markdown document from a schema type definition.
 ///
@@ -40,6 +44,11 @@ pub fn generate_document_opts(
         data.insert(field.name.clone(), value);
     }
 
+    // Stable uid isn't a schema field; pass it through when supplied
+    if let Some(&uid) = overrides.get(crate::uid::UID_FIELD) {
+        data.insert(crate::uid::UID_FIELD.to_string(), Value::String(uid.to_string()));
+    }
+
     let fm = Frontmatter::from_data(data);
     let mut out = String::new();
     out.push_str("---\n");
@@ -54,6 +63,98 @@ pub fn generate_document_opts(
     out
 }
 
+/// A new document of `type_def`: its `template` file rendered when the type
+/// declares one (the path is relative to `schema_dir`), else the generated skeleton.
+pub fn new_document(
+    type_def: &TypeDef,
+    schema: &Schema,
+    schema_dir: &Path,
+    fields: &[(String, String)],
+    next_id: Option<&str>,
+    fill: bool,
+) -> Result<String> {
+    let Some(ref template) = type_def.template else {
+        return Ok(generate_document_opts(type_def, schema, fields, fill));
+    };
+    let path = schema_dir.join(template);
+    let content = std::fs::read_to_string(&path)
+        .map_err(|e| Error::Template(format!("{}: {e}", path.display())))?;
+    render_template(&content, type_def, fields, next_id, fill)
+}
+
+/// Render a template file. `{{field}}` becomes the `--field` value or the field's
+/// default, `{{type}}` the type name, `{{TODAY}}` / `{{NOW}}` the date, and
+/// `{{NEXT_ID}}` the next free ID. Values in the frontmatter are written as YAML
+/// scalars so they stay parseable. Supplied fields the template doesn't mention
+/// in its frontmatter are set there (added, or replacing what the template wrote).
+pub fn render_template(
+    template: &str,
+    type_def: &TypeDef,
+    fields: &[(String, String)],
+    next_id: Option<&str>,
+    fill: bool,
+) -> Result<String> {
+    let overrides: BTreeMap<&str, &str> = fields.iter().map(|(k, v)| (k.as_str(), v.as_str())).collect();
+    let fm_end = crate::normalize::frontmatter_block(template).map_or(0, str::len);
+
+    let mut out = String::with_capacity(template.len());
+    let mut used: Vec<&str> = Vec::new();
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        let Some(len) = rest[start + 2..].find("}}") else {
+            break;
+        };
+        out.push_str(&rest[..start]);
+        let in_frontmatter = template.len() - rest.len() + start < fm_end;
+        let name = rest[start + 2..start + 2 + len].trim();
+        let value = match name {
+            "TODAY" => Value::String(format_today()),
+            "NOW" => Value::String(format_now()),
+            "NEXT_ID" => Value::String(
+                next_id
+                    .ok_or_else(|| Error::Template("template uses {{NEXT_ID}}; pass --dir to number it".into()))?
+                    .to_string(),
+            ),
+            "type" => Value::String(type_def.name.clone()),
+            _ => {
+                if let Some(&raw) = overrides.get(name) {
+                    if in_frontmatter {
+                        used.push(name);
+                    }
+                    crate::frontmatter::parse_yaml_value(raw)
+                } else if let Some(field) = type_def.fields.iter().find(|f| f.name == name) {
+                    default_value(field, fill)
+                } else {
+                    return Err(Error::Template(format!(
+                        "unknown placeholder {{{{{name}}}}}: not a field of type '{}' or TODAY, NOW, NEXT_ID",
+                        type_def.name
+                    )));
+                }
+            }
+        };
+        if in_frontmatter {
+            out.push_str(&crate::frontmatter::yaml_inline(&value));
+        } else {
+            out.push_str(&crate::frontmatter::yaml_value_to_string(&value));
+        }
+        rest = &rest[start + 2 + len + 2..];
+    }
+    out.push_str(rest);
+
+    let mut extra: Vec<(&str, String)> = fields
+        .iter()
+        .filter(|(k, _)| !used.contains(&k.as_str()))
+        .map(|(k, v)| (k.as_str(), crate::frontmatter::yaml_inline(&crate::frontmatter::parse_yaml_value(v))))
+        .collect();
+    if extra.is_empty() {
+        return Ok(out);
+    }
+    if crate::normalize::frontmatter_block(&out).is_none() {
+        extra.insert(0, ("type", type_def.name.clone()));
+    }
+    Ok(crate::frontmatter::set_frontmatter_lines(&out, &extra))
+}
+
 fn default_value(field_def: &FieldDef, fill: bool) -> Value {
     // Schema-defined default takes priority
     if let Some(ref default_str) = field_def.default {
@@ -78,6 +179,10 @@ fn default_value(field_def: &FieldDef, fill: bool) -> Value {
     }
 
     match &field_def.field_type {
+        FieldType::Date if fill => Value::String(format_today()),
+        FieldType::Date => Value::String("YYYY-MM-DD".to_string()),
+        FieldType::DateTime if fill => Value::String(format_now()),
+        FieldType::DateTime => Value::String("YYYY-MM-DDT00:00:00Z".to_string()),
         FieldType::String => Value::String(String::new()),
         FieldType::Number => Value::Number(0.into()),
         FieldType::Bool => Value::Bool(false),
@@ -105,13 +210,13 @@ fn expand_default(s: &str) -> Value {
 }
 
 /// Format current date as YYYY-MM-DD without external crate.
-fn format_today() -> String {
+pub fn format_today() -> String {
     let (year, month, day) = civil_date_from_epoch();
     format!("{year:04}-{month:02}-{day:02}")
 }
 
 /// Format current datetime as YYYY-MM-DDTHH:MM:SSZ without external crate.
-fn format_now() -> String {
+pub fn format_now() -> String {
     let secs = SystemTime::now()
         .duration_since(UNIX_EPOCH)
         .unwrap_or_default()
@@ -176,6 +281,312 @@ fn render_section(out: &mut String, section: &SectionDef, depth: u8) {
     }
 }
 
+/// Text that stands in for content still to be written.
+const PLACEHOLDERS: &[&str] = &["todo", "tbd", "tba", "...", "…", "-"];
+
+/// Whether section content is still the scaffold `new` generated: nothing but
+/// subheadings, empty table headers, comments, and placeholders like `TODO`.
+pub fn is_scaffold(content: &str) -> bool {
+    let arena = comrak::Arena::new();
+    let opts = crate::ast_util::comrak_opts();
+    let root = comrak::parse_document(&arena, content, &opts);
+
+    !root.descendants().any(|node| match node.data.borrow().value {
+        NodeValue::Paragraph => {
+            let text = crate::ast_util::collect_text(node);
+            let text = text.trim().trim_end_matches(':').to_lowercase();
+            !text.is_empty() && !PLACEHOLDERS.contains(&text.as_str()) && !text.starts_with("todo:")
+        }
+        NodeValue::CodeBlock(ref cb) => !cb.literal.trim().is_empty(),
+        NodeValue::TableRow(false) => !crate::ast_util::collect_text(node).trim().is_empty(),
+        _ => false,
+    })
+}
+
+/// Generate a fully filled-in example document for a type.
+///
+/// Unlike `generate_document`, every field gets a plausible value (defaults, first enum
+/// value, strings matching the field pattern, real handles from `user_config`) and every
+/// section gets content satisfying its content/list/diagram/table constraints.
+pub fn generate_example(
+    type_def: &TypeDef,
+    schema: &Schema,
+    user_config: Option<&UserConfig>,
+) -> String {
+    let (data, body) = example_parts(type_def, schema, user_config);
+    let fm = Frontmatter::from_data(data);
+    let mut out = String::new();
+    out.push_str("---\n");
+    out.push_str(&fm.to_yaml_string());
+    out.push_str("---\n");
+    out.push_str(&body);
+    out
+}
+
+/// The frontmatter and body of [`generate_example`], for callers that adjust them
+/// before rendering (the fixture generator).
+pub fn example_parts(
+    type_def: &TypeDef,
+    schema: &Schema,
+    user_config: Option<&UserConfig>,
+) -> (BTreeMap<String, Value>, String) {
+    let mut handles = user_config
+        .map(|u| u.all_user_handles())
+        .unwrap_or_default();
+    handles.sort();
+    if handles.is_empty() {
+        handles = vec!["@alice".to_string(), "@bob".to_string()];
+    }
+
+    let mut data = BTreeMap::new();
+    data.insert("type".to_string(), Value::String(type_def.name.clone()));
+    for field in &type_def.fields {
+        data.insert(
+            field.name.clone(),
+            example_value(field, type_def, schema, &handles),
+        );
+    }
+
+    let mut body = String::new();
+    for section in &type_def.sections {
+        render_example_section(&mut body, section, 1, &handles);
+    }
+
+    (data, body)
+}
+
+fn example_value(field_def: &FieldDef, type_def: &TypeDef, schema: &Schema, handles: &[String]) -> Value {
+    if let Some(ref default_str) = field_def.default {
+        return expand_default(default_str);
+    }
+
+    if let Some(ref pat) = field_def.pattern {
+        if pat.contains(r"\d{4}") && pat.contains(r"\d{2}") {
+            if pat.contains('T') {
+                return Value::String(format_now());
+            }
+            return Value::String(format_today());
+        }
+        if let Some(example) = example_from_pattern(pat) {
+            return match field_def.field_type {
+                FieldType::StringArray => Value::Sequence(vec![Value::String(example)]),
+                _ => Value::String(example),
+            };
+        }
+    }
+
+    match &field_def.field_type {
+        FieldType::String => {
+            if field_def.name == "title" {
+                Value::String(format!("Example {} document", type_def.name))
+            } else {
+                Value::String(format!("Example {}", field_def.name.replace('_', " ")))
+            }
+        }
+        FieldType::Number => Value::Number(1.into()),
+        FieldType::Bool => Value::Bool(true),
+        FieldType::Date => Value::String(example_date(field_def)),
+        FieldType::DateTime => Value::String(format!("{}T09:00:00Z", example_date(field_def))),
+        FieldType::Enum(values) => Value::String(values.first().cloned().unwrap_or_default()),
+        FieldType::User => Value::String(handles[0].clone()),
+        FieldType::UserArray => Value::Sequence(
+            handles.iter().take(2).map(|h| Value::String(h.clone())).collect(),
+        ),
+        FieldType::Ref => Value::String(example_ref(type_def, schema)),
+        FieldType::RefArray => Value::Sequence(vec![Value::String(example_ref(type_def, schema))]),
+        FieldType::StringArray => Value::Sequence(vec![
+            Value::String("example".to_string()),
+        ]),
+    }
+}
+
+/// Today, clamped into the field's `min`/`max` range.
+fn example_date(field_def: &FieldDef) -> String {
+    let mut date = format_today();
+    if let Some(max) = field_def.max.as_deref().and_then(|m| m.get(..10)) {
+        if max < date.as_str() {
+            date = max.to_string();
+        }
+    }
+    if let Some(min) = field_def.min.as_deref().and_then(|m| m.get(..10)) {
+        if min > date.as_str() {
+            date = min.to_string();
+        }
+    }
+    date
+}
+
+/// Example reference ID, preferring a different (non-singleton) type than the document's own.
+fn example_ref(type_def: &TypeDef, schema: &Schema) -> String {
+    let target = schema
+        .types
+        .iter()
+        .filter(|t| !t.singleton)
+        .find(|t| t.name != type_def.name)
+        .unwrap_or(type_def);
+    format!("{}-001", target.name.to_uppercase())
+}
+
+/// Synthesize a string matching a simple regex pattern.
+///
+/// Handles literals, escapes (`\d`, `\w`, `\s`), character classes, and quantifiers.
+/// Returns `None` for patterns using groups, alternation, or negated classes, or
+/// when the synthesized string does not actually match.
+fn example_from_pattern(pattern: &str) -> Option<String> {
+    let chars: Vec<char> = pattern.chars().collect();
+    let mut out = String::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let atom: String = match chars[i] {
+            '^' | '$' => {
+                i += 1;
+                continue;
+            }
+            '(' | ')' | '|' => return None,
+            '.' => {
+                i += 1;
+                "x".to_string()
+            }
+            '\\' => {
+                let c = *chars.get(i + 1)?;
+                i += 2;
+                match c {
+                    'd' => "1".to_string(),
+                    'w' => "a".to_string(),
+                    's' => " ".to_string(),
+                    'D' | 'W' | 'S' | 'b' | 'B' => return None,
+                    other => other.to_string(),
+                }
+            }
+            '[' => {
+                let end = i + chars[i..].iter().position(|&c| c == ']')?;
+                let class = &chars[i + 1..end];
+                i = end + 1;
+                match class {
+                    ['^', ..] | [] => return None,
+                    ['\\', 'd', ..] => "1".to_string(),
+                    ['\\', 'w', ..] => "a".to_string(),
+                    ['\\', c, ..] => c.to_string(),
+                    [c, ..] => c.to_string(),
+                }
+            }
+            c => {
+                i += 1;
+                c.to_string()
+            }
+        };
+
+        let mut count = 1;
+        match chars.get(i) {
+            Some('?') | Some('*') | Some('+') => i += 1,
+            Some('{') => {
+                let end = i + chars[i..].iter().position(|&c| c == '}')?;
+                let spec: String = chars[i + 1..end].iter().collect();
+                count = spec.split(',').next()?.trim().parse::<usize>().ok()?.max(1);
+                i = end + 1;
+            }
+            _ => {}
+        }
+        // Lazy quantifier suffix
+        if chars.get(i) == Some(&'?') {
+            i += 1;
+        }
+
+        out.push_str(&atom.repeat(count));
+    }
+
+    let re = regex::Regex::new(pattern).ok()?;
+    if re.is_match(&out) {
+        Some(out)
+    } else {
+        None
+    }
+}
+
+fn render_example_section(out: &mut String, section: &SectionDef, depth: u8, handles: &[String]) {
+    out.push('\n');
+    for _ in 0..depth {
+        out.push('#');
+    }
+    out.push(' ');
+    out.push_str(&section.name);
+    out.push('\n');
+    out.push('\n');
+
+    let lead = section
+        .description
+        .clone()
+        .unwrap_or_else(|| format!("Example {} content.", section.name.to_lowercase()));
+    let paragraphs = section
+        .content
+        .as_ref()
+        .and_then(|c| c.min_paragraphs)
+        .unwrap_or(1)
+        .max(1);
+    for n in 0..paragraphs {
+        if n == 0 {
+            out.push_str(&lead);
+        } else {
+            out.push_str(&format!("Further detail, paragraph {}.", n + 1));
+        }
+        out.push_str("\n\n");
+    }
+
+    if let Some(ref list_def) = section.list {
+        let items = list_def.min_items.unwrap_or(2).max(1);
+        for n in 1..=items {
+            out.push_str(&format!("- Example item {n}\n"));
+        }
+        out.push('\n');
+    }
+
+    if let Some(ref diagram_def) = section.diagram {
+        let lang = diagram_def
+            .diagram_type
+            .as_deref()
+            .unwrap_or("mermaid")
+            .to_lowercase();
+        let body = match lang.as_str() {
+            "mermaid" => "graph LR\n  A --> B",
+            "plantuml" => "@startuml\nA -> B\n@enduml",
+            "graphviz" | "dot" => "digraph { A -> B }",
+            _ => "A -> B",
+        };
+        out.push_str(&format!("```{lang}\n{body}\n```\n\n"));
+    }
+
+    if let Some(ref table_def) = section.table {
+        let headers: Vec<&str> = table_def.columns.iter().map(|c| c.name.as_str()).collect();
+        out.push_str("| ");
+        out.push_str(&headers.join(" | "));
+        out.push_str(" |\n");
+
+        out.push('|');
+        for _ in &table_def.columns {
+            out.push_str("---|");
+        }
+        out.push('\n');
+
+        let cells: Vec<String> = table_def
+            .columns
+            .iter()
+            .map(|c| match c.col_type {
+                FieldType::Number => "1".to_string(),
+                FieldType::User => handles[0].clone(),
+                _ => format!("Example {}", c.name.to_lowercase()),
+            })
+            .collect();
+        out.push_str("| ");
+        out.push_str(&cells.join(" | "));
+        out.push_str(" |\n");
+    }
+
+    for child in &section.children {
+        render_example_section(out, child, depth + 1, handles);
+    }
+}
+
 /// Return the default value for a field as a plain string.
 ///
 /// Returns `None` if the field has no meaningful default (e.g. user types, arrays).
@@ -198,6 +609,8 @@ pub fn field_default_string(field_def: &FieldDef) -> Option<String> {
         FieldType::Number => Some("0".to_string()),
         FieldType::Bool => Some("false".to_string()),
         FieldType::Enum(values) => values.first().cloned(),
+        FieldType::Date => Some(format_today()),
+        FieldType::DateTime => Some(format_now()),
         _ => None, // user, ref, arrays — no sensible default
     }
 }
@@ -445,6 +858,112 @@ type "test" {
         assert!(doc.contains("status: accepted"));
     }
 
+    #[test]
+    fn test_generate_example_validates() {
+        use std::collections::HashSet;
+
+        let content = std::fs::read_to_string("../../tests/fixtures/schema.kdl").unwrap();
+        let schema = Schema::from_str(&content).unwrap();
+        let users = UserConfig::from_file("../../tests/fixtures/users.yaml").unwrap();
+
+        for type_def in &schema.types {
+            let example = generate_example(type_def, &schema, Some(&users));
+            let doc = crate::document::Document::from_str(&example).unwrap();
+            let result = crate::validation::validate_document(
+                &doc,
+                &schema,
+                &HashSet::new(),
+                &HashSet::new(),
+                Some(&users),
+            );
+            assert_eq!(
+                result.errors(),
+                0,
+                "example for {} should validate, got: {:?}\n{example}",
+                type_def.name,
+                result.diagnostics
+            );
+        }
+    }
+
+    #[test]
+    fn test_generate_example_fills_constraints() {
+        let kdl = r#"
+type "test" {
+    field "code" type="string" pattern="^[A-Z]{3}-\\d+$"
+    field "owner" type="user"
+    section "Steps" {
+        list min-items=3
+    }
+    section "Flow" {
+        diagram type="mermaid"
+    }
+}
+"#;
+        let schema = Schema::from_str(kdl).unwrap();
+        let type_def = schema.get_type("test").unwrap();
+        let doc = generate_example(type_def, &schema, None);
+
+        assert!(doc.contains("code: AAA-1"));
+        assert!(doc.contains("owner: '@alice'") || doc.contains("owner: \"@alice\""));
+        assert!(doc.contains("- Example item 3"));
+        assert!(doc.contains("```mermaid"));
+    }
+
+    #[test]
+    fn test_example_from_pattern() {
+        assert_eq!(example_from_pattern(r"^v\d+\.\d+$").as_deref(), Some("v1.1"));
+        assert_eq!(example_from_pattern(r"^[a-z]+(-[a-z]+)*$"), None);
+    }
+
+    #[test]
+    fn test_render_template() {
+        let kdl = r#"
+type "adr" {
+    field "title" type="string" required=#true
+    field "status" type="enum" {
+        values "proposed" "accepted"
+    }
+    field "date" type="date"
+    template "templates/adr.md"
+}
+"#;
+        let schema = Schema::from_str(kdl).unwrap();
+        let type_def = schema.get_type("adr").unwrap();
+        assert_eq!(type_def.template.as_deref(), Some("templates/adr.md"));
+
+        let template = "---\ntype: {{type}}\n# Keep titles short\ntitle: {{title}}\nstatus: proposed\ndate: {{TODAY}}\n---\n\n# {{NEXT_ID}}: {{title}}\n\n<!-- Why now? -->\n";
+        let fields = vec![
+            ("title".to_string(), "Cache: Redis".to_string()),
+            ("status".to_string(), "accepted".to_string()),
+            ("uid".to_string(), "u1".to_string()),
+        ];
+        let out = render_template(template, type_def, &fields, Some("ADR-007"), false).unwrap();
+        let today = format_today();
+        assert_eq!(
+            out,
+            format!("---\ntype: adr\n# Keep titles short\ntitle: 'Cache: Redis'\nstatus: accepted\ndate: {today}\nuid: u1\n---\n\n# ADR-007: Cache: Redis\n\n<!-- Why now? -->\n")
+        );
+        let doc = crate::document::Document::from_str(&out).unwrap();
+        assert_eq!(doc.frontmatter.unwrap().get_display("title").as_deref(), Some("Cache: Redis"));
+
+        let err = render_template(template, type_def, &[], None, false).unwrap_err().to_string();
+        assert!(err.contains("NEXT_ID"), "{err}");
+        let err = render_template("{{owner}}", type_def, &[], None, false).unwrap_err().to_string();
+        assert!(err.contains("unknown placeholder {{owner}}"), "{err}");
+    }
+
+    #[test]
+    fn test_is_scaffold() {
+        assert!(is_scaffold(""));
+        assert!(is_scaffold("\n## Positive\n\n## Negative\n\n"));
+        assert!(is_scaffold("| Time | Event |\n|---|---|\n"));
+        assert!(is_scaffold("TODO\n\n- TBD\n\n<!-- fill me in -->\n"));
+        assert!(!is_scaffold("We chose PostgreSQL.\n"));
+        assert!(!is_scaffold("| Time | Event |\n|---|---|\n| 10:00 | Paged |\n"));
+        assert!(!is_scaffold("## Positive\n\n- Faster queries\n"));
+    }
+
     #[test]
     fn test_civil_date_sanity() {
         // Just ensure it returns a plausible date
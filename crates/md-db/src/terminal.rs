@@ -0,0 +1,138 @@
+//! Terminal capabilities shared by every command: TTY detection, colors, and the pager.
+//!
+//! Colors are off until the CLI calls [`set_color`], so library callers (and the MCP
+//! server) always get plain text. When output goes through the pager, stdout is a
+//! pipe; the CLI sets [`PAGED_ENV`] so the paged process still formats for a terminal.
+
+use std::io::IsTerminal;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::validation::Severity;
+
+/// Set in the environment of a process whose stdout feeds the pager.
+pub const PAGED_ENV: &str = "MD_DB_PAGED";
+
+static COLOR: AtomicBool = AtomicBool::new(false);
+
+/// `--color auto|always|never`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorChoice {
+    Auto,
+    Always,
+    Never,
+}
+
+impl ColorChoice {
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "auto" => Some(Self::Auto),
+            "always" => Some(Self::Always),
+            "never" => Some(Self::Never),
+            _ => None,
+        }
+    }
+}
+
+/// Whether stdout is a terminal, or a pipe into the pager started on its behalf.
+pub fn stdout_is_tty() -> bool {
+    std::io::stdout().is_terminal() || std::env::var_os(PAGED_ENV).is_some()
+}
+
+/// Resolve and store the color setting. `Auto` colors a terminal unless `NO_COLOR`
+/// is set or `TERM=dumb`.
+pub fn set_color(choice: ColorChoice) {
+    let enabled = match choice {
+        ColorChoice::Always => true,
+        ColorChoice::Never => false,
+        ColorChoice::Auto => {
+            stdout_is_tty()
+                && std::env::var_os("NO_COLOR").is_none_or(|v| v.is_empty())
+                && std::env::var("TERM").ok().as_deref() != Some("dumb")
+        }
+    };
+    COLOR.store(enabled, Ordering::Relaxed);
+}
+
+pub fn color_enabled() -> bool {
+    COLOR.load(Ordering::Relaxed)
+}
+
+/// Semantic styles; every command maps the same meaning to the same color.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Style {
+    Error,
+    Warning,
+    Success,
+    /// Headings and file paths.
+    Heading,
+    /// Locations, hints, and other secondary text.
+    Dim,
+}
+
+impl Style {
+    pub fn for_severity(severity: Severity) -> Self {
+        match severity {
+            Severity::Error => Self::Error,
+            Severity::Warning => Self::Warning,
+        }
+    }
+
+    fn ansi(self) -> &'static str {
+        match self {
+            Self::Error => "1;31",
+            Self::Warning => "1;33",
+            Self::Success => "32",
+            Self::Heading => "1",
+            Self::Dim => "2",
+        }
+    }
+}
+
+/// Wrap `text` in the ANSI codes for `style` when colors are enabled.
+pub fn paint(text: impl std::fmt::Display, style: Style) -> String {
+    styled(text, style, color_enabled())
+}
+
+fn styled(text: impl std::fmt::Display, style: Style, enabled: bool) -> String {
+    if enabled {
+        format!("\x1b[{}m{text}\x1b[0m", style.ansi())
+    } else {
+        text.to_string()
+    }
+}
+
+/// The pager to start: `$PAGER` split on whitespace, or `less`. `None` when paging is
+/// disabled with an empty `PAGER` or `PAGER=cat`.
+pub fn pager_command() -> Option<Vec<String>> {
+    let pager = std::env::var("PAGER").unwrap_or_else(|_| "less".into());
+    let parts: Vec<String> = pager.split_whitespace().map(str::to_string).collect();
+    match parts.first().map(String::as_str) {
+        None | Some("cat") => None,
+        Some(_) => Some(parts),
+    }
+}
+
+/// The editor to open files in: `$VISUAL`, then `$EDITOR`, split on whitespace, or `vi`.
+pub fn editor_command() -> Vec<String> {
+    ["VISUAL", "EDITOR"]
+        .iter()
+        .filter_map(|var| std::env::var(var).ok())
+        .map(|cmd| cmd.split_whitespace().map(str::to_string).collect::<Vec<_>>())
+        .find(|parts| !parts.is_empty())
+        .unwrap_or_else(|| vec!["vi".to_string()])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_styled() {
+        assert_eq!(styled("error", Style::Error, false), "error");
+        assert_eq!(styled("error", Style::Error, true), "\x1b[1;31merror\x1b[0m");
+        assert_eq!(styled(3, Style::Dim, true), "\x1b[2m3\x1b[0m");
+        // Off unless the CLI turns it on
+        assert!(!color_enabled());
+        assert_eq!(ColorChoice::from_str("sometimes"), None);
+    }
+}
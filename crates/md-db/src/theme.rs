@@ -0,0 +1,330 @@
+//! User themes for the HTML export (`md-db export --theme DIR`).
+//!
+//! A theme directory holds templates and static files:
+//!
+//! - `page.html` lays out every document page; `<type>.html` (`adr.html`)
+//!   lays out the pages of that type instead. Without either, the built-in
+//!   layout is used.
+//! - `style.css` is linked from every page after the built-in styles, so its
+//!   rules win.
+//! - Every other file is copied to `theme/` in the output, e.g. `theme/logo.svg`.
+//!
+//! Templates use a subset of Handlebars: `{{name}}` (HTML-escaped), `{{{name}}}`
+//! (as is), dotted paths (`{{frontmatter.owner}}`), `{{#if name}}…{{else}}…{{/if}}`,
+//! `{{#each list}}…{{/each}}` (inside, names resolve against the item first, and
+//! `{{this}}` is the item itself), and `{{! comments }}`. Missing names render
+//! as nothing; a template that doesn't parse fails the export.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use serde_json::Value;
+
+use crate::error::{Error, Result};
+
+/// Template for document types without their own.
+pub const PAGE_TEMPLATE: &str = "page.html";
+
+/// Stylesheet linked from every page.
+pub const STYLESHEET: &str = "style.css";
+
+/// Output directory the theme's static files are copied to.
+pub const OUTPUT_DIR: &str = "theme";
+
+#[derive(Debug, Clone, PartialEq)]
+enum Node {
+    Text(String),
+    Var { path: String, raw: bool },
+    If { path: String, then: Vec<Node>, otherwise: Vec<Node> },
+    Each { path: String, body: Vec<Node> },
+}
+
+/// A parsed template.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Template {
+    nodes: Vec<Node>,
+}
+
+/// A block being parsed: `{{#if}}` (with the nodes before `{{else}}` once it is
+/// seen) or `{{#each}}`, and the line it opened on.
+struct Open {
+    helper: &'static str,
+    path: String,
+    line: usize,
+    nodes: Vec<Node>,
+    before_else: Option<Vec<Node>>,
+}
+
+impl Template {
+    /// Parse `source`. Errors name the 1-based line of the offending tag.
+    pub fn parse(source: &str) -> std::result::Result<Template, String> {
+        let line_of = |at: usize| source[..at].matches('\n').count() + 1;
+        let mut stack: Vec<Open> = Vec::new();
+        let mut nodes: Vec<Node> = Vec::new();
+        let mut rest = source;
+        while let Some(start) = rest.find("{{") {
+            let at = source.len() - rest.len() + start;
+            if start > 0 {
+                current(&mut stack, &mut nodes).push(Node::Text(rest[..start].to_string()));
+            }
+            let tag = &rest[start..];
+            let (inner, raw, len) = if let Some(body) = tag.strip_prefix("{{{") {
+                let end = body.find("}}}").ok_or_else(|| format!("line {}: unclosed {{{{{{", line_of(at)))?;
+                (&body[..end], true, end + 6)
+            } else {
+                let end = tag.find("}}").ok_or_else(|| format!("line {}: unclosed {{{{", line_of(at)))?;
+                (&tag[2..end], false, end + 2)
+            };
+            rest = &tag[len..];
+            let inner = inner.trim();
+
+            if raw {
+                current(&mut stack, &mut nodes).push(Node::Var { path: inner.to_string(), raw: true });
+            } else if inner.starts_with('!') {
+                continue;
+            } else if let Some(open) = inner.strip_prefix('#') {
+                let (helper, path) = open.split_once(char::is_whitespace).unwrap_or((open, ""));
+                let helper = match helper {
+                    "if" => "if",
+                    "each" => "each",
+                    other => return Err(format!("line {}: unknown block {{{{#{other}}}}}", line_of(at))),
+                };
+                if path.trim().is_empty() {
+                    return Err(format!("line {}: {{{{#{helper}}}}} needs a name", line_of(at)));
+                }
+                stack.push(Open {
+                    helper,
+                    path: path.trim().to_string(),
+                    line: line_of(at),
+                    nodes: Vec::new(),
+                    before_else: None,
+                });
+            } else if inner == "else" {
+                match stack.last_mut() {
+                    Some(open) if open.helper == "if" && open.before_else.is_none() => {
+                        open.before_else = Some(std::mem::take(&mut open.nodes));
+                    }
+                    _ => return Err(format!("line {}: {{{{else}}}} outside {{{{#if}}}}", line_of(at))),
+                }
+            } else if let Some(close) = inner.strip_prefix('/') {
+                let open = stack
+                    .pop()
+                    .filter(|open| open.helper == close.trim())
+                    .ok_or_else(|| format!("line {}: unexpected {{{{/{}}}}}", line_of(at), close.trim()))?;
+                let node = match open.before_else {
+                    _ if open.helper == "each" => Node::Each { path: open.path, body: open.nodes },
+                    Some(then) => Node::If { path: open.path, then, otherwise: open.nodes },
+                    None => Node::If { path: open.path, then: open.nodes, otherwise: Vec::new() },
+                };
+                current(&mut stack, &mut nodes).push(node);
+            } else {
+                current(&mut stack, &mut nodes).push(Node::Var { path: inner.to_string(), raw: false });
+            }
+        }
+        if let Some(open) = stack.last() {
+            return Err(format!("line {}: {{{{#{}}}}} is never closed", open.line, open.helper));
+        }
+        if !rest.is_empty() {
+            nodes.push(Node::Text(rest.to_string()));
+        }
+        Ok(Template { nodes })
+    }
+
+    /// Render with `context`, usually a JSON object.
+    pub fn render(&self, context: &Value) -> String {
+        let mut out = String::new();
+        render_nodes(&self.nodes, &mut vec![context], &mut out);
+        out
+    }
+}
+
+fn current<'a>(stack: &'a mut [Open], top: &'a mut Vec<Node>) -> &'a mut Vec<Node> {
+    match stack.last_mut() {
+        Some(open) => &mut open.nodes,
+        None => top,
+    }
+}
+
+fn render_nodes<'v>(nodes: &[Node], scopes: &mut Vec<&'v Value>, out: &mut String) {
+    for node in nodes {
+        match node {
+            Node::Text(text) => out.push_str(text),
+            Node::Var { path, raw } => {
+                let text = lookup(scopes, path).map(display).unwrap_or_default();
+                if *raw {
+                    out.push_str(&text);
+                } else {
+                    out.push_str(&htmlescape::encode_minimal(&text));
+                }
+            }
+            Node::If { path, then, otherwise } => {
+                let branch = if lookup(scopes, path).is_some_and(truthy) { then } else { otherwise };
+                render_nodes(branch, scopes, out);
+            }
+            Node::Each { path, body } => {
+                let items: Vec<&'v Value> = match lookup(scopes, path) {
+                    Some(Value::Array(items)) => items.iter().collect(),
+                    Some(Value::Object(map)) => map.values().collect(),
+                    _ => Vec::new(),
+                };
+                for item in items {
+                    scopes.push(item);
+                    render_nodes(body, scopes, out);
+                    scopes.pop();
+                }
+            }
+        }
+    }
+}
+
+/// `this` is the innermost scope; other names resolve against the innermost
+/// scope that has their first segment.
+fn lookup<'v>(scopes: &[&'v Value], path: &str) -> Option<&'v Value> {
+    let (start, path) = match path.strip_prefix("this") {
+        Some("") => return scopes.last().copied(),
+        Some(rest) if rest.starts_with('.') => (*scopes.last()?, &rest[1..]),
+        _ => {
+            let first = path.split('.').next().unwrap_or(path);
+            (*scopes.iter().rev().find(|s| s.get(first).is_some())?, path)
+        }
+    };
+    path.split('.').try_fold(start, |value, key| match value {
+        Value::Array(items) => items.get(key.parse::<usize>().ok()?),
+        _ => value.get(key),
+    })
+}
+
+/// Handlebars truthiness: `false`, null, `0`, `""`, and `[]` are false.
+fn truthy(value: &Value) -> bool {
+    match value {
+        Value::Null => false,
+        Value::Bool(b) => *b,
+        Value::Number(n) => n.as_f64() != Some(0.0),
+        Value::String(s) => !s.is_empty(),
+        Value::Array(items) => !items.is_empty(),
+        Value::Object(_) => true,
+    }
+}
+
+fn display(value: &Value) -> String {
+    match value {
+        Value::Null | Value::Object(_) => String::new(),
+        Value::String(s) => s.clone(),
+        Value::Array(items) => items.iter().map(display).collect::<Vec<_>>().join(", "),
+        other => other.to_string(),
+    }
+}
+
+/// A loaded theme directory.
+#[derive(Debug, Clone, Default)]
+pub struct Theme {
+    pub dir: PathBuf,
+    page: Option<Template>,
+    /// Templates named after a document type.
+    types: BTreeMap<String, Template>,
+    /// Whether the theme has a [`STYLESHEET`].
+    pub stylesheet: bool,
+}
+
+impl Theme {
+    /// Load and parse every template in `dir`.
+    pub fn load(dir: impl AsRef<Path>) -> Result<Theme> {
+        let dir = dir.as_ref();
+        let entries = std::fs::read_dir(dir).map_err(|_| Error::FileNotFound(dir.to_path_buf()))?;
+        let mut theme = Theme {
+            dir: dir.to_path_buf(),
+            stylesheet: dir.join(STYLESHEET).is_file(),
+            ..Theme::default()
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let (Some(stem), Some("html")) =
+                (path.file_stem().and_then(|s| s.to_str()), path.extension().and_then(|e| e.to_str()))
+            else {
+                continue;
+            };
+            let source = std::fs::read_to_string(&path)?;
+            let template =
+                Template::parse(&source).map_err(|e| Error::Template(format!("{}: {e}", path.display())))?;
+            if stem == "page" {
+                theme.page = Some(template);
+            } else {
+                theme.types.insert(stem.to_string(), template);
+            }
+        }
+        Ok(theme)
+    }
+
+    /// Template for pages of `doc_type`: its own, else `page.html`.
+    pub fn template_for(&self, doc_type: Option<&str>) -> Option<&Template> {
+        doc_type.and_then(|t| self.types.get(t)).or(self.page.as_ref())
+    }
+
+    /// Files other than templates, relative to the theme directory.
+    pub fn static_files(&self) -> Vec<PathBuf> {
+        let mut files: Vec<PathBuf> = walkdir::WalkDir::new(&self.dir)
+            .into_iter()
+            .flatten()
+            .filter(|e| e.file_type().is_file())
+            .filter_map(|e| e.path().strip_prefix(&self.dir).ok().map(Path::to_path_buf))
+            .filter(|rel| rel.components().count() > 1 || rel.extension().and_then(|e| e.to_str()) != Some("html"))
+            .collect();
+        files.sort();
+        files
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_template() {
+        let template = Template::parse(
+            "<h1>{{title}}</h1>{{! note }}\n{{{body}}}\n{{#if backlinks}}<ul>{{#each backlinks}}\
+             <li>{{id}} {{relation}} of {{title}}</li>{{/each}}</ul>{{else}}none{{/if}}\n\
+             {{frontmatter.tags}} {{#each frontmatter.tags}}[{{this}}]{{/each}} {{section.Open Questions}}",
+        )
+        .unwrap();
+        let context = serde_json::json!({
+            "title": "<Kafka>",
+            "body": "<p>Hi</p>",
+            "backlinks": [{ "id": "ADR-002", "relation": "supersedes" }],
+            "frontmatter": { "tags": ["infra", "queues"] },
+            "section": { "Open Questions": "cost" },
+        });
+        assert_eq!(
+            template.render(&context),
+            "<h1>&lt;Kafka&gt;</h1>\n<p>Hi</p>\n<ul><li>ADR-002 supersedes of &lt;Kafka&gt;</li></ul>\n\
+             infra, queues [infra][queues] cost"
+        );
+        let empty = serde_json::json!({ "backlinks": [] });
+        assert_eq!(Template::parse("{{#if backlinks}}x{{else}}none{{/if}}").unwrap().render(&empty), "none");
+
+        assert_eq!(Template::parse("a\n{{#each items}}").unwrap_err(), "line 2: {{#each}} is never closed");
+        assert_eq!(Template::parse("{{#if a}}{{/each}}").unwrap_err(), "line 1: unexpected {{/each}}");
+        assert!(Template::parse("{{#with a}}{{/with}}").unwrap_err().contains("unknown block {{#with}}"));
+        assert!(Template::parse("{{title").is_err());
+    }
+
+    #[test]
+    fn test_load_theme() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("page.html"), "{{title}}").unwrap();
+        std::fs::write(dir.path().join("adr.html"), "ADR {{title}}").unwrap();
+        std::fs::write(dir.path().join("style.css"), "body {}").unwrap();
+        std::fs::create_dir(dir.path().join("img")).unwrap();
+        std::fs::write(dir.path().join("img/logo.svg"), "<svg/>").unwrap();
+
+        let theme = Theme::load(dir.path()).unwrap();
+        assert!(theme.stylesheet);
+        let context = serde_json::json!({ "title": "T" });
+        assert_eq!(theme.template_for(Some("adr")).unwrap().render(&context), "ADR T");
+        assert_eq!(theme.template_for(Some("inc")).unwrap().render(&context), "T");
+        assert_eq!(theme.static_files(), vec![PathBuf::from("img/logo.svg"), PathBuf::from("style.css")]);
+
+        std::fs::write(dir.path().join("inc.html"), "{{#if x}}").unwrap();
+        let err = Theme::load(dir.path()).unwrap_err().to_string();
+        assert!(err.contains("inc.html: line 1: {{#if}} is never closed"), "{err}");
+    }
+}
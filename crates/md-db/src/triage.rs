@@ -0,0 +1,502 @@
+//! Turn validation findings `md-db fix` can't repair into tracking tickets.
+//!
+//! Findings are grouped (per diagnostic code or per file) and each group maps to
+//! one ticket. `<root>/.md-db/triage.json` records the ticket created for each
+//! group, so later runs update the same ticket instead of opening a duplicate,
+//! and can close it once the findings are gone.
+
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use serde::{Deserialize, Serialize};
+
+use crate::assets::sha256_hex;
+use crate::error::{Error, Result};
+use crate::graph_cache::CACHE_DIR;
+use crate::validation::{Diagnostic, ValidationResult, FIXABLE_CODES};
+
+/// State file (inside `CACHE_DIR`) mapping group keys to tickets.
+const STATE_FILE: &str = "triage.json";
+
+/// Hidden marker appended to ticket bodies so tickets can be traced back to a group.
+const MARKER_PREFIX: &str = "<!-- md-db-triage:";
+
+/// How findings are bundled into tickets.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GroupBy {
+    /// One ticket per diagnostic code, listing every affected document.
+    Code,
+    /// One ticket per document, listing all of its findings.
+    File,
+}
+
+impl GroupBy {
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "code" => Some(Self::Code),
+            "file" => Some(Self::File),
+            _ => None,
+        }
+    }
+}
+
+/// Findings that belong in a single ticket.
+#[derive(Debug, Clone)]
+pub struct TicketGroup {
+    /// Stable key (`code:R011`, `file:docs/adr-001.md`).
+    pub key: String,
+    pub title: String,
+    pub body: String,
+    pub findings: usize,
+}
+
+impl TicketGroup {
+    /// Digest of title and body, used to skip no-op updates.
+    pub fn digest(&self) -> String {
+        sha256_hex(format!("{}\n{}", self.title, self.body).as_bytes())
+    }
+}
+
+/// A ticket recorded in the triage state.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TicketRecord {
+    pub provider: String,
+    pub id: String,
+    pub url: String,
+    /// Digest of the last title and body pushed to the ticket.
+    pub digest: String,
+}
+
+/// A ticket as returned by a provider.
+#[derive(Debug, Clone)]
+pub struct Ticket {
+    pub id: String,
+    pub url: String,
+}
+
+/// Backend that creates and updates tickets.
+pub trait TicketProvider {
+    /// Short name stored in the triage state (e.g. "github").
+    fn name(&self) -> &str;
+    fn create(&mut self, title: &str, body: &str) -> Result<Ticket>;
+    fn update(&mut self, id: &str, title: &str, body: &str) -> Result<()>;
+    fn close(&mut self, id: &str, comment: &str) -> Result<()>;
+}
+
+/// What a triage run does for one group.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TriageAction {
+    Create,
+    Update,
+    Unchanged,
+    /// Findings are gone; the ticket is closed with `close_resolved`.
+    Resolve,
+}
+
+impl std::fmt::Display for TriageAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TriageAction::Create => write!(f, "create"),
+            TriageAction::Update => write!(f, "update"),
+            TriageAction::Unchanged => write!(f, "unchanged"),
+            TriageAction::Resolve => write!(f, "resolve"),
+        }
+    }
+}
+
+/// One planned (or applied) step of a triage run.
+#[derive(Debug, Clone)]
+pub struct PlanItem {
+    pub key: String,
+    pub action: TriageAction,
+    /// Current findings; `None` for resolved groups.
+    pub group: Option<TicketGroup>,
+    /// Existing ticket, or the new one once applied.
+    pub ticket: Option<TicketRecord>,
+}
+
+impl PlanItem {
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "key": self.key,
+            "action": self.action.to_string(),
+            "title": self.group.as_ref().map(|g| g.title.clone()),
+            "findings": self.group.as_ref().map(|g| g.findings).unwrap_or(0),
+            "ticket": self.ticket.as_ref().map(|t| serde_json::json!({
+                "provider": t.provider,
+                "id": t.id,
+                "url": t.url,
+            })),
+        })
+    }
+}
+
+/// Tickets recorded for a docs directory.
+#[derive(Debug)]
+pub struct TriageState {
+    root: PathBuf,
+    tickets: BTreeMap<String, TicketRecord>,
+}
+
+impl TriageState {
+    /// Load `<root>/.md-db/triage.json`, or start empty.
+    pub fn open(root: impl AsRef<Path>) -> Result<Self> {
+        let root = root.as_ref().to_path_buf();
+        let path = root.join(CACHE_DIR).join(STATE_FILE);
+        let tickets = if path.exists() {
+            let data = std::fs::read_to_string(&path)?;
+            serde_json::from_str(&data)?
+        } else {
+            BTreeMap::new()
+        };
+        Ok(Self { root, tickets })
+    }
+
+    pub fn tickets(&self) -> &BTreeMap<String, TicketRecord> {
+        &self.tickets
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let dir = self.root.join(CACHE_DIR);
+        std::fs::create_dir_all(&dir).map_err(|_| Error::WriteFailed(dir.clone()))?;
+        let path = dir.join(STATE_FILE);
+        let data = serde_json::to_string_pretty(&self.tickets)?;
+        crate::durability::write(&path, data)?;
+        Ok(())
+    }
+}
+
+/// Group the findings `md-db fix` can't repair. `link_base` is prepended to
+/// document paths in ticket bodies (e.g. a repository blob URL).
+pub fn group_findings(
+    result: &ValidationResult,
+    group_by: GroupBy,
+    link_base: Option<&str>,
+) -> Vec<TicketGroup> {
+    let mut groups: BTreeMap<String, Vec<(&str, &Diagnostic)>> = BTreeMap::new();
+    for fr in &result.file_results {
+        for d in &fr.diagnostics {
+            if FIXABLE_CODES.contains(&d.code.as_str()) {
+                continue;
+            }
+            let key = match group_by {
+                GroupBy::Code => format!("code:{}", d.code),
+                GroupBy::File => format!("file:{}", fr.path),
+            };
+            groups.entry(key).or_default().push((fr.path.as_str(), d));
+        }
+    }
+
+    groups
+        .into_iter()
+        .map(|(key, findings)| {
+            let title = match group_by {
+                GroupBy::Code => {
+                    let mut docs: Vec<&str> = findings.iter().map(|(p, _)| *p).collect();
+                    docs.dedup();
+                    format!("md-db: {} in {} document(s)", findings[0].1.code, docs.len())
+                }
+                GroupBy::File => format!("md-db: {} finding(s) in {}", findings.len(), findings[0].0),
+            };
+            let body = render_body(&key, &findings, link_base);
+            TicketGroup {
+                key,
+                title,
+                body,
+                findings: findings.len(),
+            }
+        })
+        .collect()
+}
+
+fn render_body(key: &str, findings: &[(&str, &Diagnostic)], link_base: Option<&str>) -> String {
+    let cell = |s: &str| s.replace('|', "\\|").replace('\n', " ");
+    let mut out = String::from("Findings reported by `md-db validate` that need a manual fix.\n\n");
+    out.push_str("| Document | Code | Severity | Location | Message |\n");
+    out.push_str("|---|---|---|---|---|\n");
+    for (path, d) in findings {
+        let doc = match link_base {
+            Some(base) => format!("[{path}]({}/{})", base.trim_end_matches('/'), path.trim_start_matches("./")),
+            None => format!("`{path}`"),
+        };
+        out.push_str(&format!(
+            "| {doc} | {} | {} | {} | {} |\n",
+            d.code,
+            d.severity,
+            cell(&d.location),
+            cell(&d.message)
+        ));
+    }
+
+    let mut hints: Vec<&str> = findings.iter().filter_map(|(_, d)| d.hint.as_deref()).collect();
+    hints.sort();
+    hints.dedup();
+    if !hints.is_empty() {
+        out.push_str("\n**Hints**\n\n");
+        for hint in hints {
+            out.push_str(&format!("- {hint}\n"));
+        }
+    }
+    out.push_str(&format!("\n{MARKER_PREFIX}{key} -->\n"));
+    out
+}
+
+/// Compare current groups with the recorded tickets.
+/// Groups whose ticket is recorded but which have no findings left are `Resolve`.
+pub fn plan(groups: Vec<TicketGroup>, state: &TriageState) -> Vec<PlanItem> {
+    let mut items = Vec::new();
+    for group in groups {
+        let ticket = state.tickets.get(&group.key).cloned();
+        let action = match &ticket {
+            None => TriageAction::Create,
+            Some(t) if t.digest == group.digest() => TriageAction::Unchanged,
+            Some(_) => TriageAction::Update,
+        };
+        items.push(PlanItem {
+            key: group.key.clone(),
+            action,
+            group: Some(group),
+            ticket,
+        });
+    }
+    for (key, ticket) in &state.tickets {
+        if !items.iter().any(|i| &i.key == key) {
+            items.push(PlanItem {
+                key: key.clone(),
+                action: TriageAction::Resolve,
+                group: None,
+                ticket: Some(ticket.clone()),
+            });
+        }
+    }
+    items
+}
+
+/// Execute a plan against a provider and record the tickets in `state`.
+/// Resolved tickets are closed and forgotten only with `close_resolved`.
+pub fn apply(
+    items: &mut [PlanItem],
+    provider: &mut dyn TicketProvider,
+    state: &mut TriageState,
+    close_resolved: bool,
+) -> Result<()> {
+    for item in items.iter_mut() {
+        match (item.action, &item.group) {
+            (TriageAction::Create, Some(group)) => {
+                let ticket = provider.create(&group.title, &group.body)?;
+                let record = TicketRecord {
+                    provider: provider.name().to_string(),
+                    id: ticket.id,
+                    url: ticket.url,
+                    digest: group.digest(),
+                };
+                state.tickets.insert(item.key.clone(), record.clone());
+                item.ticket = Some(record);
+            }
+            (TriageAction::Update, Some(group)) => {
+                if let Some(record) = state.tickets.get_mut(&item.key) {
+                    provider.update(&record.id, &group.title, &group.body)?;
+                    record.digest = group.digest();
+                    item.ticket = Some(record.clone());
+                }
+            }
+            (TriageAction::Resolve, _) if close_resolved => {
+                if let Some(record) = state.tickets.remove(&item.key) {
+                    provider.close(&record.id, "All findings are fixed; closed by `md-db triage`.")?;
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// GitHub Issues through the `gh` CLI, which handles authentication.
+#[derive(Debug, Clone, Default)]
+pub struct GitHubProvider {
+    /// `owner/name`; defaults to the repository of the current directory.
+    pub repo: Option<String>,
+    pub labels: Vec<String>,
+}
+
+impl GitHubProvider {
+    fn gh(&self, args: &[&str], stdin: Option<&str>) -> Result<String> {
+        gh(self.repo.as_deref(), args, stdin)
+    }
+}
+
+/// Run `gh` with `args` against `repo` (the current repository when `None`),
+/// feeding it `stdin`, and return its trimmed output.
+pub(crate) fn gh(repo: Option<&str>, args: &[&str], stdin: Option<&str>) -> Result<String> {
+    let mut cmd = Command::new("gh");
+    cmd.args(args);
+    if let Some(repo) = repo {
+        cmd.args(["--repo", repo]);
+    }
+    cmd.stdin(if stdin.is_some() { Stdio::piped() } else { Stdio::null() })
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+
+    let mut child = cmd
+        .spawn()
+        .map_err(|e| Error::Ticket(format!("failed to run gh: {e}")))?;
+    if let (Some(input), Some(mut pipe)) = (stdin, child.stdin.take()) {
+        pipe.write_all(input.as_bytes())?;
+    }
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        return Err(Error::Ticket(format!(
+            "gh {} failed: {}",
+            args.first().copied().unwrap_or(""),
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+impl TicketProvider for GitHubProvider {
+    fn name(&self) -> &str {
+        "github"
+    }
+
+    fn create(&mut self, title: &str, body: &str) -> Result<Ticket> {
+        let mut args = vec!["issue", "create", "--title", title, "--body-file", "-"];
+        for label in &self.labels {
+            args.extend(["--label", label.as_str()]);
+        }
+        // `gh issue create` prints the new issue URL
+        let url = self.gh(&args, Some(body))?;
+        let url = url.lines().last().unwrap_or_default().to_string();
+        let id = url
+            .rsplit('/')
+            .next()
+            .filter(|n| n.chars().all(|c| c.is_ascii_digit()) && !n.is_empty())
+            .ok_or_else(|| Error::Ticket(format!("unexpected gh output: {url}")))?
+            .to_string();
+        Ok(Ticket { id, url })
+    }
+
+    fn update(&mut self, id: &str, title: &str, body: &str) -> Result<()> {
+        self.gh(&["issue", "edit", id, "--title", title, "--body-file", "-"], Some(body))?;
+        Ok(())
+    }
+
+    fn close(&mut self, id: &str, comment: &str) -> Result<()> {
+        self.gh(&["issue", "close", id, "--comment", comment], None)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::validation::{FileResult, Severity};
+
+    #[derive(Default)]
+    struct FakeProvider {
+        next: u32,
+        calls: Vec<String>,
+    }
+
+    impl TicketProvider for FakeProvider {
+        fn name(&self) -> &str {
+            "fake"
+        }
+        fn create(&mut self, title: &str, _body: &str) -> Result<Ticket> {
+            self.next += 1;
+            self.calls.push(format!("create {title}"));
+            Ok(Ticket {
+                id: self.next.to_string(),
+                url: format!("https://example.test/issues/{}", self.next),
+            })
+        }
+        fn update(&mut self, id: &str, _title: &str, _body: &str) -> Result<()> {
+            self.calls.push(format!("update {id}"));
+            Ok(())
+        }
+        fn close(&mut self, id: &str, _comment: &str) -> Result<()> {
+            self.calls.push(format!("close {id}"));
+            Ok(())
+        }
+    }
+
+    fn diag(code: &str, message: &str) -> Diagnostic {
+        Diagnostic {
+            severity: Severity::Error,
+            code: code.into(),
+            message: message.into(),
+            location: "frontmatter".into(),
+            hint: Some("fix the ref".into()),
+            span: None,
+        }
+    }
+
+    fn result(files: &[(&str, Vec<Diagnostic>)]) -> ValidationResult {
+        ValidationResult {
+            file_results: files
+                .iter()
+                .map(|(p, d)| FileResult {
+                    path: p.to_string(),
+                    diagnostics: d.clone(),
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_group_skips_fixable_codes() {
+        let r = result(&[
+            ("docs/adr-001.md", vec![diag("R011", "unresolved \"ADR-9\""), diag("F010", "missing")]),
+            ("docs/adr-002.md", vec![diag("R011", "unresolved | pipe")]),
+        ]);
+        let groups = group_findings(&r, GroupBy::Code, Some("https://example.test/blob/main/"));
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].key, "code:R011");
+        assert_eq!(groups[0].title, "md-db: R011 in 2 document(s)");
+        assert!(groups[0].body.contains("[docs/adr-001.md](https://example.test/blob/main/docs/adr-001.md)"));
+        assert!(groups[0].body.contains("unresolved \\| pipe"));
+        assert!(groups[0].body.contains("- fix the ref"));
+
+        let by_file = group_findings(&r, GroupBy::File, None);
+        assert_eq!(by_file.len(), 2);
+        assert_eq!(by_file[0].key, "file:docs/adr-001.md");
+        assert_eq!(by_file[0].findings, 1);
+    }
+
+    #[test]
+    fn test_reruns_update_instead_of_duplicating() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut provider = FakeProvider::default();
+
+        let r = result(&[("a.md", vec![diag("R011", "one")])]);
+        let mut state = TriageState::open(dir.path()).unwrap();
+        let mut items = plan(group_findings(&r, GroupBy::Code, None), &state);
+        assert_eq!(items[0].action, TriageAction::Create);
+        apply(&mut items, &mut provider, &mut state, false).unwrap();
+        state.save().unwrap();
+
+        // Same findings: nothing to do
+        let mut state = TriageState::open(dir.path()).unwrap();
+        let items = plan(group_findings(&r, GroupBy::Code, None), &state);
+        assert_eq!(items[0].action, TriageAction::Unchanged);
+
+        // New finding in the same group updates the existing ticket
+        let r = result(&[("a.md", vec![diag("R011", "one")]), ("b.md", vec![diag("R011", "two")])]);
+        let mut items = plan(group_findings(&r, GroupBy::Code, None), &state);
+        assert_eq!(items[0].action, TriageAction::Update);
+        apply(&mut items, &mut provider, &mut state, false).unwrap();
+        assert_eq!(provider.calls, vec!["create md-db: R011 in 1 document(s)", "update 1"]);
+
+        // Fixed: resolved, closed only when asked
+        let r = result(&[]);
+        let mut items = plan(group_findings(&r, GroupBy::Code, None), &state);
+        assert_eq!(items[0].action, TriageAction::Resolve);
+        apply(&mut items, &mut provider, &mut state, false).unwrap();
+        assert_eq!(state.tickets().len(), 1);
+        apply(&mut items, &mut provider, &mut state, true).unwrap();
+        assert!(state.tickets().is_empty());
+        assert_eq!(provider.calls.last().unwrap(), "close 1");
+    }
+}
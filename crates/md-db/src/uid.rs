@@ -0,0 +1,189 @@
+//! Stable document identity independent of file path.
+//!
+//! Documents may carry a `uid` frontmatter field holding a UUIDv7. Unlike the
+//! display ID derived from the filename (`ADR-001`), the uid survives renames
+//! and moves, so external systems can reference it safely.
+
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde_yaml::Value;
+
+use crate::document::Document;
+use crate::error::Result;
+use crate::frontmatter::Frontmatter;
+
+/// Frontmatter field holding the document uid.
+pub const UID_FIELD: &str = "uid";
+
+static COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// Generate a new UUIDv7: 48-bit millisecond timestamp, version and variant
+/// bits, and 74 random bits. Lowercase hyphenated form.
+pub fn generate() -> String {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+    let millis = now.as_millis() as u64 & 0xFFFF_FFFF_FFFF;
+
+    let (rand_a, rand_b) = random_pair(now.subsec_nanos());
+
+    let mut bytes = [0u8; 16];
+    bytes[..6].copy_from_slice(&millis.to_be_bytes()[2..]);
+    bytes[6..8].copy_from_slice(&((rand_a & 0x0FFF) as u16 | 0x7000).to_be_bytes());
+    bytes[8..].copy_from_slice(&((rand_b & 0x3FFF_FFFF_FFFF_FFFF) | 0x8000_0000_0000_0000).to_be_bytes());
+
+    let hex: String = bytes.iter().map(|b| format!("{b:02x}")).collect();
+    format!(
+        "{}-{}-{}-{}-{}",
+        &hex[..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..]
+    )
+}
+
+/// Random bits without an external crate: `RandomState` is seeded from the OS,
+/// mixed with the clock and a process-wide counter so two calls never collide.
+fn random_pair(nanos: u32) -> (u64, u64) {
+    let count = COUNTER.fetch_add(1, Ordering::Relaxed);
+    let mut out = [0u64; 2];
+    for (i, slot) in out.iter_mut().enumerate() {
+        let mut hasher = RandomState::new().build_hasher();
+        hasher.write_u32(nanos);
+        hasher.write_u64(count);
+        hasher.write_u32(std::process::id());
+        hasher.write_usize(i);
+        *slot = hasher.finish();
+    }
+    (out[0], out[1])
+}
+
+/// Whether a string is a hyphenated UUID (any version, either case).
+pub fn is_uid(s: &str) -> bool {
+    let bytes = s.as_bytes();
+    bytes.len() == 36
+        && bytes.iter().enumerate().all(|(i, b)| match i {
+            8 | 13 | 18 | 23 => *b == b'-',
+            _ => b.is_ascii_hexdigit(),
+        })
+}
+
+/// Read the uid from a file's frontmatter without parsing the body.
+pub fn read_uid(path: &Path) -> Option<String> {
+    Frontmatter::read_from_file(path)
+        .ok()
+        .flatten()?
+        .get_display(UID_FIELD)
+        .filter(|uid| is_uid(uid))
+        .map(|uid| uid.to_ascii_lowercase())
+}
+
+/// A uid assigned by `backfill`.
+#[derive(Debug, Clone)]
+pub struct Backfilled {
+    pub path: PathBuf,
+    pub uid: String,
+}
+
+/// Assign a fresh uid to every typed document that doesn't have one yet.
+/// Files without frontmatter or a `type` field are left alone.
+/// With `dry_run`, uids are generated and reported but nothing is written.
+pub fn backfill(files: &[PathBuf], dry_run: bool) -> Result<Vec<Backfilled>> {
+    let mut assigned = Vec::new();
+    for path in files {
+        let mut doc = match Document::from_file(path) {
+            Ok(d) => d,
+            Err(_) => continue,
+        };
+        let Some(ref fm) = doc.frontmatter else {
+            continue;
+        };
+        if !fm.has_field("type") || fm.has_field(UID_FIELD) {
+            continue;
+        }
+
+        let uid = generate();
+        doc.set_field(UID_FIELD, Value::String(uid.clone()));
+        if !dry_run {
+            doc.save()?;
+        }
+        assigned.push(Backfilled {
+            path: path.clone(),
+            uid,
+        });
+    }
+    Ok(assigned)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_generate_is_v7() {
+        let uid = generate();
+        assert!(is_uid(&uid), "{uid}");
+        assert_eq!(&uid[14..15], "7");
+        assert!(matches!(&uid[19..20], "8" | "9" | "a" | "b"));
+        assert_eq!(uid, uid.to_lowercase());
+    }
+
+    #[test]
+    fn test_generate_unique_and_time_ordered() {
+        let uids: Vec<String> = (0..200).map(|_| generate()).collect();
+        let unique: HashSet<&String> = uids.iter().collect();
+        assert_eq!(unique.len(), uids.len());
+        // Timestamp prefix never goes backwards
+        for pair in uids.windows(2) {
+            assert!(pair[0][..13] <= pair[1][..13]);
+        }
+    }
+
+    #[test]
+    fn test_is_uid() {
+        assert!(is_uid("01890a5d-ac96-774b-bcce-b302099a8057"));
+        assert!(is_uid("01890A5D-AC96-774B-BCCE-B302099A8057"));
+        assert!(!is_uid("ADR-001"));
+        assert!(!is_uid("01890a5d-ac96-774b-bcce-b302099a805"));
+        assert!(!is_uid("01890a5dxac96-774b-bcce-b302099a8057"));
+    }
+
+    #[test]
+    fn test_backfill() {
+        let dir = tempfile::tempdir().unwrap();
+        let typed = dir.path().join("adr-001.md");
+        let has_uid = dir.path().join("adr-002.md");
+        let untyped = dir.path().join("notes.md");
+        std::fs::write(&typed, "---\ntype: adr\ntitle: One\n---\n\n# Decision\n\nText.\n").unwrap();
+        std::fs::write(
+            &has_uid,
+            "---\ntype: adr\nuid: 01890a5d-ac96-774b-bcce-b302099a8057\n---\n",
+        )
+        .unwrap();
+        std::fs::write(&untyped, "# Notes\n").unwrap();
+        let files = vec![typed.clone(), has_uid.clone(), untyped.clone()];
+
+        let dry = backfill(&files, true).unwrap();
+        assert_eq!(dry.len(), 1);
+        assert!(read_uid(&typed).is_none());
+
+        let done = backfill(&files, false).unwrap();
+        assert_eq!(done.len(), 1);
+        assert_eq!(done[0].path, typed);
+        assert_eq!(read_uid(&typed).as_deref(), Some(done[0].uid.as_str()));
+        assert!(std::fs::read_to_string(&typed).unwrap().contains("# Decision"));
+
+        // Idempotent
+        assert!(backfill(&files, false).unwrap().is_empty());
+        assert_eq!(
+            read_uid(&has_uid).as_deref(),
+            Some("01890a5d-ac96-774b-bcce-b302099a8057")
+        );
+    }
+}
@@ -0,0 +1,359 @@
+//! Undo/redo for file changes made by md-db commands.
+//!
+//! Mutating commands wrap their writes in a [`Recorder`], which snapshots each file
+//! before it is touched and, once the command is done, appends the before/after
+//! contents as one [`Operation`] to `<root>/.md-db/undo-log.json`. `undo` restores the
+//! before contents and `redo` re-applies the after contents, but only when every file
+//! still matches what md-db last wrote, so manual edits made in between are never
+//! overwritten. The log keeps the last [`MAX_STEPS`] operations.
+//!
+//! Servers that write from several threads hold [`write_lock`] for a log root
+//! around each read-modify-write; the log itself is updated under the same lock.
+
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard, OnceLock, PoisonError};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Error, Result};
+use crate::graph_cache::CACHE_DIR;
+
+/// Log file name inside `CACHE_DIR`.
+pub const LOG_FILE: &str = "undo-log.json";
+
+/// Number of operations kept; older ones are dropped.
+pub const MAX_STEPS: usize = 20;
+
+/// One file's content before and after an operation. `None` means the file did not
+/// exist (created by the operation, or removed by it).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FileChange {
+    /// Path relative to the log root.
+    pub path: PathBuf,
+    pub before: Option<String>,
+    pub after: Option<String>,
+}
+
+/// Everything one command invocation changed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Operation {
+    /// Command that made the change (e.g. "set", "batch").
+    pub command: String,
+    /// When it ran, as YYYY-MM-DDTHH:MM:SSZ.
+    pub time: String,
+    pub files: Vec<FileChange>,
+}
+
+/// Undo and redo stacks, most recent operation last.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UndoLog {
+    pub undo: Vec<Operation>,
+    pub redo: Vec<Operation>,
+}
+
+impl UndoLog {
+    /// Load the log under `root`, or an empty one if there is none.
+    pub fn load(root: &Path) -> Result<Self> {
+        let path = log_path(root);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let data = std::fs::read_to_string(&path)?;
+        Ok(serde_json::from_str(&data)?)
+    }
+
+    /// Write the log to `<root>/.md-db/undo-log.json`.
+    pub fn save(&self, root: &Path) -> Result<()> {
+        let dir = root.join(CACHE_DIR);
+        std::fs::create_dir_all(&dir).map_err(|_| Error::WriteFailed(dir.clone()))?;
+        let path = dir.join(LOG_FILE);
+        let data = serde_json::to_string(self)?;
+        crate::durability::write(&path, data)?;
+        Ok(())
+    }
+
+    /// Record a new operation. Redo history is discarded, as after any new edit.
+    pub fn push(&mut self, op: Operation) {
+        self.undo.push(op);
+        self.redo.clear();
+        if self.undo.len() > MAX_STEPS {
+            let excess = self.undo.len() - MAX_STEPS;
+            self.undo.drain(..excess);
+        }
+    }
+}
+
+/// Directory whose `.md-db/` holds the undo log for files under `start`: the nearest
+/// ancestor with an `md-db.kdl` manifest, else the git work tree root, else the
+/// current directory.
+pub fn log_root(start: &Path) -> Result<PathBuf> {
+    let (dirs, git_root) = crate::project::search_dirs(start)?;
+    if let Some(dir) = dirs.iter().find(|d| d.join(crate::project::MANIFEST).is_file()) {
+        return Ok(dir.clone());
+    }
+    match git_root {
+        Some(dir) => Ok(dir),
+        None => Ok(std::env::current_dir()?),
+    }
+}
+
+fn log_path(root: &Path) -> PathBuf {
+    root.join(CACHE_DIR).join(LOG_FILE)
+}
+
+thread_local! {
+    /// Log roots whose [`write_lock`] this thread holds.
+    static HELD: RefCell<Vec<PathBuf>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Held while this thread writes documents under a log root or its undo log.
+#[must_use]
+pub struct WriteLock {
+    root: PathBuf,
+    /// `None` when the thread already held the lock.
+    guard: Option<MutexGuard<'static, ()>>,
+}
+
+impl Drop for WriteLock {
+    fn drop(&mut self) {
+        if self.guard.is_some() {
+            HELD.with(|held| held.borrow_mut().retain(|r| r != &self.root));
+        }
+    }
+}
+
+/// Serialize writes under `root` (a [`log_root`]) between the threads of this
+/// process. Taking it again on a thread that holds it returns at once, so a
+/// request handler can hold it around a command whose [`Recorder`] takes it too.
+pub fn write_lock(root: &Path) -> WriteLock {
+    static LOCKS: OnceLock<Mutex<HashMap<PathBuf, &'static Mutex<()>>>> = OnceLock::new();
+
+    let root = root.to_path_buf();
+    if HELD.with(|held| held.borrow().contains(&root)) {
+        return WriteLock { root, guard: None };
+    }
+    // One mutex per root for the life of the process; a server has a handful of roots
+    let lock: &'static Mutex<()> = *LOCKS
+        .get_or_init(Default::default)
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .entry(root.clone())
+        .or_insert_with(|| &*Box::leak(Box::<Mutex<()>>::default()));
+    let guard = lock.lock().unwrap_or_else(PoisonError::into_inner);
+    HELD.with(|held| held.borrow_mut().push(root.clone()));
+    WriteLock { root, guard: Some(guard) }
+}
+
+/// Collects the files a command touches so the change can be undone.
+#[derive(Debug)]
+pub struct Recorder {
+    command: String,
+    before: BTreeMap<PathBuf, Option<String>>,
+}
+
+impl Recorder {
+    pub fn new(command: &str) -> Self {
+        Self {
+            command: command.to_string(),
+            before: BTreeMap::new(),
+        }
+    }
+
+    /// Snapshot a file before it is written, created, or removed. Only the first
+    /// snapshot of a path counts.
+    pub fn track(&mut self, path: impl AsRef<Path>) {
+        let path = absolute(path.as_ref());
+        if !self.before.contains_key(&path) {
+            let content = std::fs::read_to_string(&path).ok();
+            self.before.insert(path, content);
+        }
+    }
+
+    /// Snapshot several files, e.g. everything a library call may rewrite.
+    pub fn track_all<'a>(&mut self, paths: impl IntoIterator<Item = &'a PathBuf>) {
+        for path in paths {
+            self.track(path);
+        }
+    }
+
+    /// Append the tracked changes to the undo log. Files whose content did not change
+    /// are left out, and nothing is logged when no file changed. Returns the number of
+    /// changed files.
+    pub fn finish(self) -> Result<usize> {
+        let Some(first) = self.before.keys().next() else {
+            return Ok(0);
+        };
+        let root = log_root(first)?;
+
+        let mut files = Vec::new();
+        for (path, before) in self.before {
+            let after = std::fs::read_to_string(&path).ok();
+            if after != before {
+                let path = path.strip_prefix(&root).map(Path::to_path_buf).unwrap_or(path);
+                files.push(FileChange { path, before, after });
+            }
+        }
+        if files.is_empty() {
+            return Ok(0);
+        }
+
+        let count = files.len();
+        let _lock = write_lock(&root);
+        let mut log = UndoLog::load(&root)?;
+        log.push(Operation {
+            command: self.command,
+            time: crate::template::format_now(),
+            files,
+        });
+        log.save(&root)?;
+        Ok(count)
+    }
+}
+
+/// Revert the last `steps` operations, newest first. Stops with an error, before
+/// touching anything, if a file of the next operation changed since md-db wrote it.
+pub fn undo(root: &Path, steps: usize) -> Result<Vec<Operation>> {
+    step(root, steps, true)
+}
+
+/// Re-apply the last `steps` undone operations.
+pub fn redo(root: &Path, steps: usize) -> Result<Vec<Operation>> {
+    step(root, steps, false)
+}
+
+fn step(root: &Path, steps: usize, backward: bool) -> Result<Vec<Operation>> {
+    let _lock = write_lock(root);
+    let mut log = UndoLog::load(root)?;
+    let mut applied = Vec::new();
+
+    for _ in 0..steps {
+        let stack = if backward { &mut log.undo } else { &mut log.redo };
+        let Some(op) = stack.pop() else {
+            break;
+        };
+
+        let conflicts: Vec<String> = op
+            .files
+            .iter()
+            .filter(|f| {
+                let expected = if backward { &f.after } else { &f.before };
+                std::fs::read_to_string(root.join(&f.path)).ok() != *expected
+            })
+            .map(|f| f.path.display().to_string())
+            .collect();
+        if !conflicts.is_empty() {
+            let action = if backward { "undo" } else { "redo" };
+            return Err(Error::Undo(format!(
+                "cannot {action} '{}' from {}: changed since: {}",
+                op.command,
+                op.time,
+                conflicts.join(", ")
+            )));
+        }
+
+        for f in &op.files {
+            let path = root.join(&f.path);
+            let content = if backward { &f.before } else { &f.after };
+            match content {
+                Some(content) => {
+                    if let Some(parent) = path.parent() {
+                        std::fs::create_dir_all(parent).map_err(|_| Error::WriteFailed(parent.to_path_buf()))?;
+                    }
+                    crate::durability::write(&path, content)?;
+                }
+                None => std::fs::remove_file(&path).map_err(|_| Error::WriteFailed(path.clone()))?,
+            }
+        }
+
+        if backward {
+            log.redo.push(op.clone());
+        } else {
+            log.undo.push(op.clone());
+        }
+        // Save after every step so a later conflict keeps the steps already applied.
+        log.save(root)?;
+        applied.push(op);
+    }
+
+    Ok(applied)
+}
+
+fn absolute(path: &Path) -> PathBuf {
+    if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        std::env::current_dir().unwrap_or_default().join(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_undo_redo_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        std::fs::create_dir(root.join(".git")).unwrap();
+        let doc = root.join("adr-001.md");
+        let created = root.join("adr-002.md");
+        std::fs::write(&doc, "status: draft\n").unwrap();
+
+        let mut rec = Recorder::new("set");
+        rec.track(&doc);
+        rec.track(&created);
+        std::fs::write(&doc, "status: accepted\n").unwrap();
+        std::fs::write(&created, "new\n").unwrap();
+        assert_eq!(rec.finish().unwrap(), 2);
+
+        let undone = undo(&root, 1).unwrap();
+        assert_eq!(undone.len(), 1);
+        assert_eq!(std::fs::read_to_string(&doc).unwrap(), "status: draft\n");
+        assert!(!created.exists());
+
+        redo(&root, 1).unwrap();
+        assert_eq!(std::fs::read_to_string(&doc).unwrap(), "status: accepted\n");
+        assert!(created.exists());
+
+        // A manual edit after the operation blocks undo
+        std::fs::write(&doc, "status: rejected\n").unwrap();
+        assert!(undo(&root, 1).is_err());
+        assert_eq!(std::fs::read_to_string(&doc).unwrap(), "status: rejected\n");
+        assert_eq!(UndoLog::load(&root).unwrap().undo.len(), 1);
+    }
+
+    #[test]
+    fn test_write_lock() {
+        let root = Path::new("/md-db-test/write-lock");
+        let held = write_lock(root);
+        // Reentrant on the thread holding it
+        assert!(write_lock(root).guard.is_none());
+
+        let (tx, rx) = std::sync::mpsc::channel();
+        let other = std::thread::spawn(move || {
+            let lock = write_lock(Path::new("/md-db-test/write-lock"));
+            tx.send(lock.guard.is_some()).unwrap();
+        });
+        assert!(rx.recv_timeout(std::time::Duration::from_millis(100)).is_err());
+        drop(held);
+        assert!(rx.recv().unwrap());
+        other.join().unwrap();
+        assert!(write_lock(root).guard.is_some());
+    }
+
+    #[test]
+    fn test_log_is_bounded() {
+        let mut log = UndoLog::default();
+        for i in 0..MAX_STEPS + 5 {
+            log.push(Operation {
+                command: format!("op{i}"),
+                time: String::new(),
+                files: Vec::new(),
+            });
+        }
+        assert_eq!(log.undo.len(), MAX_STEPS);
+        assert_eq!(log.undo[0].command, "op5");
+    }
+}
@@ -149,6 +149,70 @@ impl UserConfig {
     }
 }
 
+/// An `@handle` or `@team/name` mention in a document body.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mention {
+    /// The reference as written, including the `@`.
+    pub handle: String,
+    /// 1-based line within the body.
+    pub line: usize,
+}
+
+/// Find `@handle` and `@team/name` mentions in prose, lists, and tables. Fenced code,
+/// inline code spans, e-mail addresses, and URL paths are skipped.
+pub fn find_mentions(body: &str) -> Vec<Mention> {
+    let mut mentions = Vec::new();
+    let mut fence: Option<(char, usize)> = None;
+    for (i, line) in body.lines().enumerate() {
+        if let Some((c, n)) = fence {
+            let t = line.trim();
+            if t.len() >= n && t.chars().all(|ch| ch == c) {
+                fence = None;
+            }
+            continue;
+        }
+        if let Some(open) = crate::normalize::fence_open(line) {
+            fence = Some(open);
+            continue;
+        }
+
+        // Inline code spans alternate with text on backtick boundaries
+        for (j, segment) in line.split('`').enumerate() {
+            if j % 2 == 1 {
+                continue;
+            }
+            let chars: Vec<char> = segment.chars().collect();
+            let mut k = 0;
+            while k < chars.len() {
+                let preceded_ok = k == 0 || !(chars[k - 1].is_alphanumeric() || "_.-/@".contains(chars[k - 1]));
+                if chars[k] != '@' || !preceded_ok {
+                    k += 1;
+                    continue;
+                }
+                let mut end = k + 1;
+                while end < chars.len() && (chars[end].is_alphanumeric() || "_.-/".contains(chars[end])) {
+                    end += 1;
+                }
+                let handle: String = chars[k + 1..end].iter().collect();
+                // Sentence punctuation isn't part of the handle
+                let handle = handle.trim_end_matches(['.', '-', '/']);
+                let valid = match handle.strip_prefix("team/") {
+                    Some(team) => !team.is_empty() && !team.contains('/'),
+                    None => !handle.is_empty() && !handle.contains('/'),
+                };
+                if valid && handle.starts_with(|c: char| c.is_alphanumeric()) {
+                    mentions.push(Mention {
+                        handle: format!("@{handle}"),
+                        line: i + 1,
+                    });
+                }
+                k = end;
+            }
+        }
+    }
+    mentions
+}
+
 fn parse_user_def(handle: &str, val: &serde_yaml::Value) -> Result<UserDef> {
     let mapping = val
         .as_mapping()
@@ -376,4 +440,33 @@ teams:
         assert!(teams.contains(&"@team/platform".to_string()));
         assert!(teams.contains(&"@team/engineering".to_string()));
     }
+
+    #[test]
+    fn test_find_mentions() {
+        let body = "\
+Owner is @alice, reviewed by @team/platform.
+Mail bob@example.com or see https://x.com/@carol.
+
+| Who | Role |
+|-----|------|
+| @dave | lead |
+
+Run `@eve` or:
+
+```sh
+echo @frank
+```
+(@grace)
+";
+        let found: Vec<(String, usize)> = find_mentions(body).into_iter().map(|m| (m.handle, m.line)).collect();
+        assert_eq!(
+            found,
+            vec![
+                ("@alice".to_string(), 1),
+                ("@team/platform".to_string(), 1),
+                ("@dave".to_string(), 6),
+                ("@grace".to_string(), 13),
+            ]
+        );
+    }
 }
@@ -1,14 +1,14 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fmt;
 use std::path::{Path, PathBuf};
 
-use regex::{Regex, RegexBuilder};
-
 use crate::document::Document;
 use comrak::Arena;
 use comrak::nodes::NodeValue;
 
-use crate::schema::{ContentDef, DiagramDef, FieldDef, FieldType, ListDef, Schema, SectionDef, TableDef, TypeDef};
+use crate::schema::{ContentDef, DiagramDef, FieldDef, FieldType, ListDef, OrderedBy, ProseRules, Schema, SectionDef, SectionPatternDef, TableDef, TypeDef};
+use crate::span::Span;
+use crate::terminal::{paint, Style};
 use crate::users::UserConfig;
 
 /// Severity of a validation diagnostic.
@@ -27,6 +27,9 @@ impl fmt::Display for Severity {
     }
 }
 
+/// Diagnostic codes `md-db fix` knows how to repair.
+pub const FIXABLE_CODES: &[&str] = &["F010", "F021", "S010", "S023"];
+
 /// A single validation diagnostic.
 #[derive(Debug, Clone)]
 pub struct Diagnostic {
@@ -35,6 +38,8 @@ pub struct Diagnostic {
     pub message: String,
     pub location: String,
     pub hint: Option<String>,
+    /// Where `location` is in the file, when it resolves to a line.
+    pub span: Option<Span>,
 }
 
 impl Diagnostic {
@@ -42,14 +47,30 @@ impl Diagnostic {
     pub fn to_compact(&self) -> String {
         format!("{}:{}:{}:{}", self.code, self.severity, self.location, self.message)
     }
+
+    /// Stable identity of this diagnostic in the file at `path`: a hex hash of the
+    /// path, code, location, and message. The span, severity, and hint are left out,
+    /// so the same finding keeps its fingerprint when lines above it move or a
+    /// schema change only adjusts its severity.
+    pub fn fingerprint(&self, path: &str) -> String {
+        let path = path.replace('\\', "/");
+        let path = path.strip_prefix("./").unwrap_or(&path);
+        let key = format!("{path}\0{}\0{}\0{}", self.code, self.location, self.message);
+        format!("{:016x}", crate::cache::simple_hash(&key))
+    }
 }
 
 impl fmt::Display for Diagnostic {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "  {}[{}]: {}", self.severity, self.code, self.message)?;
-        write!(f, "\n    --> {}", self.location)?;
+        let head = format!("{}[{}]", self.severity, self.code);
+        write!(f, "  {}: {}", paint(head, Style::for_severity(self.severity)), self.message)?;
+        let arrow = match self.span {
+            Some(span) => format!("--> {} ({}:{})", self.location, span.line, span.column),
+            None => format!("--> {}", self.location),
+        };
+        write!(f, "\n    {}", paint(arrow, Style::Dim))?;
         if let Some(ref hint) = self.hint {
-            write!(f, "\n    = hint: {hint}")?;
+            write!(f, "\n    {}", paint(format!("= hint: {hint}"), Style::Dim))?;
         }
         Ok(())
     }
@@ -118,8 +139,8 @@ impl ValidationResult {
             if fr.diagnostics.is_empty() {
                 continue;
             }
-            out.push_str(&fr.path);
-            out.push_str(":\n");
+            out.push_str(&paint(format!("{}:", fr.path), Style::Heading));
+            out.push('\n');
             for d in &fr.diagnostics {
                 out.push_str(&format!("{d}\n"));
             }
@@ -128,13 +149,194 @@ impl ValidationResult {
 
         let errors = self.total_errors();
         let warnings = self.total_warnings();
-        out.push_str(&format!(
-            "result: {errors} error(s), {warnings} warning(s)\n"
-        ));
+        let style = match (errors, warnings) {
+            (0, 0) => Style::Success,
+            (0, _) => Style::Warning,
+            _ => Style::Error,
+        };
+        out.push_str(&paint(format!("result: {errors} error(s), {warnings} warning(s)"), style));
+        out.push('\n');
         out
     }
 }
 
+/// Diagnostics that appeared or cleared between two validation runs, each with
+/// its file path.
+#[derive(Debug, Clone, Default)]
+pub struct DiagnosticDelta {
+    pub new: Vec<(String, Diagnostic)>,
+    pub resolved: Vec<(String, Diagnostic)>,
+}
+
+impl DiagnosticDelta {
+    pub fn is_empty(&self) -> bool {
+        self.new.is_empty() && self.resolved.is_empty()
+    }
+}
+
+/// The current diagnostics of every file, keyed by [`Diagnostic::fingerprint`].
+/// Feeding it each new validation run yields what changed since the last one.
+#[derive(Debug, Clone, Default)]
+pub struct DiagnosticState {
+    files: BTreeMap<String, BTreeMap<String, Diagnostic>>,
+}
+
+impl DiagnosticState {
+    /// Replace the state with a full run. Files missing from `result` are treated
+    /// as clean.
+    pub fn replace(&mut self, result: &ValidationResult) -> DiagnosticDelta {
+        let mut paths: Vec<String> = self.files.keys().cloned().collect();
+        paths.extend(result.file_results.iter().map(|f| f.path.clone()));
+        self.update(&paths, result)
+    }
+
+    /// Record a run over `paths` only. A path without a file result in `result`
+    /// (deleted, or no longer a typed document) has no diagnostics.
+    pub fn update(&mut self, paths: &[String], result: &ValidationResult) -> DiagnosticDelta {
+        let mut delta = DiagnosticDelta::default();
+        let mut seen = HashSet::new();
+        for path in paths {
+            if !seen.insert(path) {
+                continue;
+            }
+            let current: BTreeMap<String, Diagnostic> = result
+                .file_results
+                .iter()
+                .filter(|f| f.path == *path)
+                .flat_map(|f| &f.diagnostics)
+                .map(|d| (d.fingerprint(path), d.clone()))
+                .collect();
+            let previous = self.files.remove(path).unwrap_or_default();
+            for (fp, d) in &current {
+                if !previous.contains_key(fp) {
+                    delta.new.push((path.clone(), d.clone()));
+                }
+            }
+            for (fp, d) in previous {
+                if !current.contains_key(&fp) {
+                    delta.resolved.push((path.clone(), d));
+                }
+            }
+            if !current.is_empty() {
+                self.files.insert(path.clone(), current);
+            }
+        }
+        delta
+    }
+
+    pub fn errors(&self) -> usize {
+        self.count(Severity::Error)
+    }
+
+    pub fn warnings(&self) -> usize {
+        self.count(Severity::Warning)
+    }
+
+    fn count(&self, severity: Severity) -> usize {
+        self.files.values().flat_map(|f| f.values()).filter(|d| d.severity == severity).count()
+    }
+}
+
+/// A group of validation checks that can be run on its own (`validate --only`/`--skip`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Phase {
+    /// Field presence, types, values, rules, and users in frontmatter.
+    Frontmatter,
+    /// Required sections, their content, diagrams, prose, query blocks, and mentions.
+    Sections,
+    /// Required tables, their columns, and row order.
+    Tables,
+    /// Ref format and targets, relation fields, and links to files.
+    Refs,
+    /// Checks across documents: target types, inverse pairs, consistency,
+    /// duplicate IDs and uids, type counts, orphaned assets, projections.
+    Graph,
+}
+
+impl Phase {
+    pub const ALL: [Phase; 5] = [Phase::Frontmatter, Phase::Sections, Phase::Tables, Phase::Refs, Phase::Graph];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Phase::Frontmatter => "frontmatter",
+            Phase::Sections => "sections",
+            Phase::Tables => "tables",
+            Phase::Refs => "refs",
+            Phase::Graph => "graph",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|p| p.name() == name)
+    }
+
+    /// The phase a diagnostic code belongs to. `None` for parse errors and a
+    /// missing or unknown `type`, which are reported whatever runs.
+    pub fn of(code: &str) -> Option<Self> {
+        match code {
+            "E000" | "F000" | "F001" | "F002" => None,
+            "F041" | "F060" | "R012" | "R022" | "I001" | "I002" | "T010" | "T020" | "A011" | "V001" => {
+                Some(Phase::Graph)
+            }
+            "S020" | "S021" | "S022" | "S023" => Some(Phase::Tables),
+            "A010" => Some(Phase::Refs),
+            _ if code.starts_with('R') => Some(Phase::Refs),
+            _ if code.starts_with('F') || code == "U010" || code == "U011" => Some(Phase::Frontmatter),
+            _ => Some(Phase::Sections),
+        }
+    }
+}
+
+impl fmt::Display for Phase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Which phases a validation runs. The default runs them all.
+#[derive(Debug, Clone, Default)]
+pub struct ValidationOptions {
+    /// Run only these phases; all of them when empty.
+    pub only: Vec<Phase>,
+    /// Leave these phases out.
+    pub skip: Vec<Phase>,
+}
+
+impl ValidationOptions {
+    /// Options from `--only`/`--skip` phase names.
+    pub fn from_names(only: &[String], skip: &[String]) -> Result<Self, String> {
+        let parse = |names: &[String]| {
+            names
+                .iter()
+                .flat_map(|n| n.split(','))
+                .map(|n| {
+                    Phase::from_name(n.trim()).ok_or_else(|| {
+                        let known: Vec<&str> = Phase::ALL.iter().map(|p| p.name()).collect();
+                        format!("unknown validation phase \"{}\" (expected {})", n.trim(), known.join(", "))
+                    })
+                })
+                .collect::<Result<Vec<_>, _>>()
+        };
+        Ok(Self {
+            only: parse(only)?,
+            skip: parse(skip)?,
+        })
+    }
+
+    pub fn runs(&self, phase: Phase) -> bool {
+        (self.only.is_empty() || self.only.contains(&phase)) && !self.skip.contains(&phase)
+    }
+
+    /// Whether diagnostics with `code` are reported.
+    pub fn reports(&self, code: &str) -> bool {
+        Phase::of(code).is_none_or(|phase| self.runs(phase))
+    }
+
+    fn runs_any(&self, phases: &[Phase]) -> bool {
+        phases.iter().any(|&p| self.runs(p))
+    }
+}
+
 /// Validate a single document against its type definition in the schema.
 pub fn validate_document(
     doc: &Document,
@@ -142,6 +344,33 @@ pub fn validate_document(
     known_files: &HashSet<PathBuf>,
     known_ids: &HashSet<String>,
     user_config: Option<&UserConfig>,
+) -> FileResult {
+    validate_document_with(doc, schema, known_files, known_ids, user_config, &ValidationOptions::default())
+}
+
+/// [`validate_document`] running only the phases `opts` selects.
+pub fn validate_document_with(
+    doc: &Document,
+    schema: &Schema,
+    known_files: &HashSet<PathBuf>,
+    known_ids: &HashSet<String>,
+    user_config: Option<&UserConfig>,
+    opts: &ValidationOptions,
+) -> FileResult {
+    let mut result = check_document(doc, schema, known_files, known_ids, user_config, opts);
+    // Checks that span phases run when any of them does; keep only what was asked for
+    result.diagnostics.retain(|d| opts.reports(&d.code));
+    crate::span::fill(doc, &mut result.diagnostics);
+    result
+}
+
+fn check_document(
+    doc: &Document,
+    schema: &Schema,
+    known_files: &HashSet<PathBuf>,
+    known_ids: &HashSet<String>,
+    user_config: Option<&UserConfig>,
+    opts: &ValidationOptions,
 ) -> FileResult {
     let path = doc
         .path
@@ -161,6 +390,7 @@ pub fn validate_document(
                 message: "document has no frontmatter".into(),
                 location: "frontmatter".into(),
                 hint: Some("add YAML frontmatter between --- delimiters".into()),
+                span: None,
             });
             return FileResult { path, diagnostics };
         }
@@ -176,6 +406,7 @@ pub fn validate_document(
                 message: "missing required field \"type\"".into(),
                 location: "frontmatter".into(),
                 hint: Some("add 'type: <typename>' to frontmatter".into()),
+                span: None,
             });
             return FileResult { path, diagnostics };
         }
@@ -192,22 +423,107 @@ pub fn validate_document(
                 message: format!("unknown document type \"{type_name}\""),
                 location: "frontmatter.type".into(),
                 hint: Some(format!("known types: {}", known.join(", "))),
+                span: None,
             });
             return FileResult { path, diagnostics };
         }
     };
 
+    // Stable uid, if present, must be a UUID
+    if let Some(uid) = fm.get_display(crate::uid::UID_FIELD) {
+        if !crate::uid::is_uid(&uid) {
+            diagnostics.push(Diagnostic {
+                severity: Severity::Error,
+                code: "F040".into(),
+                message: format!("field \"uid\" value \"{uid}\" is not a UUID"),
+                location: "frontmatter.uid".into(),
+                hint: Some("remove it and run `md-db uid backfill` to assign a new one".into()),
+                span: None,
+            });
+        }
+    }
+
     // Validate fields
-    validate_fields(fm, type_def, schema, known_files, known_ids, &doc.path, user_config, &mut diagnostics);
+    if opts.runs_any(&[Phase::Frontmatter, Phase::Refs]) {
+        validate_fields(fm, type_def, schema, known_files, known_ids, &doc.path, user_config, &mut diagnostics);
+    }
 
     // Validate conditional rules (if/then constraints)
-    validate_rules(fm, type_def, &mut diagnostics);
+    if opts.runs(Phase::Frontmatter) {
+        validate_rules(fm, type_def, &mut diagnostics);
+    }
 
     // Validate relation fields (defined at schema level, not per-type)
-    validate_relation_fields(fm, schema, known_files, known_ids, &doc.path, &mut diagnostics);
+    if opts.runs_any(&[Phase::Frontmatter, Phase::Refs]) {
+        validate_relation_fields(fm, schema, known_files, known_ids, &doc.path, &mut diagnostics);
+    }
 
     // Validate sections
-    validate_sections(doc, &type_def.sections, &[], user_config, &mut diagnostics);
+    if opts.runs_any(&[Phase::Sections, Phase::Tables]) {
+        validate_sections(doc, &type_def.sections, &[], user_config, &mut diagnostics);
+        validate_section_patterns(doc, &type_def.section_patterns, &[], user_config, &mut diagnostics);
+    }
+
+    // Promoted fields must match their authoritative body value
+    for promo in crate::promote::check_document(doc, type_def).into_iter().filter(|_| opts.runs(Phase::Frontmatter)) {
+        if let Some(ref current) = promo.current {
+            diagnostics.push(Diagnostic {
+                severity: Severity::Warning,
+                code: "F050".into(),
+                message: format!(
+                    "field \"{}\" is {} but the body says {}",
+                    promo.field,
+                    crate::frontmatter::yaml_value_to_string(current),
+                    crate::frontmatter::yaml_value_to_string(&promo.value),
+                ),
+                location: format!("frontmatter.{}", promo.field),
+                hint: Some("run `md-db sync --promote` to copy the body value".into()),
+                span: None,
+            });
+        }
+    }
+
+    // Headings sharing an anchor make #links ambiguous
+    if opts.runs(Phase::Sections) {
+        validate_heading_anchors(doc, &mut diagnostics);
+    }
+
+    // Relative links and images must point at files that exist
+    if opts.runs(Phase::Refs) {
+        validate_asset_links(doc, &mut diagnostics);
+    }
+
+    // The rest checks the body text
+    if !opts.runs(Phase::Sections) {
+        return FileResult { path, diagnostics };
+    }
+
+    // Raw HTML the HTML export would strip
+    validate_raw_html(doc, &mut diagnostics);
+
+    // @mentions in prose must still resolve
+    if let Some(config) = user_config {
+        validate_mentions(doc, config, &mut diagnostics);
+    }
+
+    // Optional prose lint
+    if let Some(ref rules) = schema.prose {
+        validate_prose(doc, type_def, rules, &mut diagnostics);
+    }
+
+    // md-db-query blocks must parse
+    for block in crate::query::find_query_blocks(&doc.body) {
+        if let Err(e) = crate::query::Query::parse(&block.source) {
+            diagnostics.push(Diagnostic {
+                severity: Severity::Error,
+                code: "Q001".into(),
+                message: format!("invalid md-db-query block: {e}"),
+                location: format!("body:{}", block.line),
+                hint: Some("keys: type, where, has, missing, links, columns, sort, limit".into()),
+                span: None,
+            });
+        }
+    }
 
     FileResult { path, diagnostics }
 }
@@ -223,7 +539,39 @@ fn validate_fields(
     diags: &mut Vec<Diagnostic>,
 ) {
     for field_def in &type_def.fields {
-        let val = fm.get(&field_def.name);
+        let mut key = field_def.name.as_str();
+        let mut val = fm.get(key);
+
+        // Former names still count, with a nudge to rename
+        for alias in &field_def.aliases {
+            let Some(old) = fm.get(alias) else {
+                continue;
+            };
+            let name = &field_def.name;
+            let (message, hint) = if val.is_some() {
+                (
+                    format!("field \"{alias}\" is the old name of \"{name}\", which is also set; it is ignored"),
+                    format!("remove '{alias}'"),
+                )
+            } else {
+                (
+                    format!("field \"{alias}\" was renamed to \"{name}\""),
+                    format!("rename '{alias}:' to '{name}:'"),
+                )
+            };
+            diags.push(Diagnostic {
+                severity: Severity::Warning,
+                code: "F011".into(),
+                message,
+                location: format!("frontmatter.{alias}"),
+                hint: Some(hint),
+                span: None,
+            });
+            if val.is_none() {
+                key = alias;
+                val = Some(old);
+            }
+        }
 
         // Required check
         if field_def.required && val.is_none() {
@@ -240,6 +588,7 @@ fn validate_fields(
                 message: format!("missing required field \"{}\"", field_def.name),
                 location: "frontmatter".into(),
                 hint: Some(hint),
+                span: None,
             });
             continue;
         }
@@ -250,7 +599,7 @@ fn validate_fields(
         };
 
         // Type check
-        validate_field_value(&field_def.name, val, field_def, schema, known_files, known_ids, doc_path, user_config, diags);
+        validate_field_value(key, val, field_def, schema, known_files, known_ids, doc_path, user_config, diags);
     }
 }
 
@@ -281,6 +630,7 @@ fn validate_rules(
                                 "add '{}' to frontmatter (required by rule \"{}\")",
                                 required_field, rule.name
                             )),
+                            span: None,
                         });
                     }
                 }
@@ -311,6 +661,27 @@ fn validate_relation_fields(
                     // Single ref
                     if let Some(s) = val.as_str() {
                         validate_ref(key, s, schema, known_files, known_ids, doc_path, diags);
+                    } else if let Some(seq) = val.as_sequence() {
+                        let hint = match seq.as_slice() {
+                            [single] if single.as_str().is_some() => {
+                                format!("write it as a single ref: {key}: {}", single.as_str().unwrap_or_default())
+                            }
+                            _ => format!(
+                                "keep one target, or declare relation \"{}\" with cardinality=\"many\"",
+                                rel_def.name
+                            ),
+                        };
+                        diags.push(Diagnostic {
+                            severity: Severity::Error,
+                            code: "R021".into(),
+                            message: format!(
+                                "relation \"{key}\" has cardinality one but is an array of {} ref(s)",
+                                seq.len()
+                            ),
+                            location: format!("frontmatter.{key}"),
+                            hint: Some(hint),
+                            span: None,
+                        });
                     } else {
                         diags.push(type_mismatch(key, "ref (string)", val));
                     }
@@ -319,7 +690,19 @@ fn validate_relation_fields(
                     // Array of refs
                     match val.as_sequence() {
                         Some(seq) => {
+                            let mut seen: HashSet<String> = HashSet::new();
                             for (i, item) in seq.iter().enumerate() {
+                                if let Some(s) = item.as_str().filter(|s| !seen.insert(s.trim().to_uppercase())) {
+                                    diags.push(Diagnostic {
+                                        severity: Severity::Warning,
+                                        code: "R020".into(),
+                                        message: format!("relation \"{key}\" lists \"{s}\" more than once"),
+                                        location: format!("frontmatter.{key}[{i}]"),
+                                        hint: Some("remove the duplicate entry".into()),
+                                        span: None,
+                                    });
+                                    continue;
+                                }
                                 if let Some(s) = item.as_str() {
                                     validate_ref(
                                         &format!("{key}[{i}]"),
@@ -340,6 +723,7 @@ fn validate_relation_fields(
                                         ),
                                         location: format!("frontmatter.{key}[{i}]"),
                                         hint: None,
+                                        span: None,
                                     });
                                 }
                             }
@@ -375,7 +759,7 @@ fn validate_field_value(
             if !val.is_string() {
                 diags.push(type_mismatch(field_name, "string", val));
             } else if let Some(ref pattern) = field_def.pattern {
-                check_pattern(field_name, val.as_str().unwrap(), pattern, diags);
+                check_pattern(schema, field_name, val.as_str().unwrap(), pattern, diags);
             }
         }
         FieldType::Number => {
@@ -388,6 +772,9 @@ fn validate_field_value(
                 diags.push(type_mismatch(field_name, "bool", val));
             }
         }
+        FieldType::Date | FieldType::DateTime => {
+            validate_temporal(field_name, val, field_def, schema, diags);
+        }
         FieldType::Enum(allowed) => {
             match val.as_str() {
                 Some(s) => {
@@ -403,6 +790,7 @@ fn validate_field_value(
                                 "allowed values: {}",
                                 allowed.join(", ")
                             )),
+                            span: None,
                         });
                     }
                 }
@@ -431,13 +819,14 @@ fn validate_field_value(
                                 ),
                                 location: format!("frontmatter.{field_name}[{i}]"),
                                 hint: None,
+                                span: None,
                             });
                         }
                     }
                     if let Some(ref pattern) = field_def.pattern {
                         for (i, item) in seq.iter().enumerate() {
                             if let Some(s) = item.as_str() {
-                                check_pattern(&format!("{field_name}[{i}]"), s, pattern, diags);
+                                check_pattern(schema, &format!("{field_name}[{i}]"), s, pattern, diags);
                             }
                         }
                     }
@@ -471,6 +860,7 @@ fn validate_field_value(
                                 ),
                                 location: format!("frontmatter.{field_name}[{i}]"),
                                 hint: None,
+                                span: None,
                             });
                         }
                     }
@@ -503,6 +893,7 @@ fn validate_field_value(
                                 ),
                                 location: format!("frontmatter.{field_name}[{i}]"),
                                 hint: None,
+                                span: None,
                             });
                         }
                     }
@@ -524,12 +915,47 @@ fn validate_ref(
     doc_path: &Option<PathBuf>,
     diags: &mut Vec<Diagnostic>,
 ) {
+    // Refs by stable uid bypass ref-format patterns and resolve against known uids
+    if crate::uid::is_uid(value) {
+        if !known_ids.contains(&value.to_ascii_lowercase()) && !known_ids.is_empty() {
+            diags.push(Diagnostic {
+                severity: Severity::Warning,
+                code: "R011".into(),
+                message: format!(
+                    "unresolved reference \"{value}\" in \"{field_name}\""
+                ),
+                location: format!("frontmatter.{field_name}"),
+                hint: Some("no document with matching uid found in scope".into()),
+                span: None,
+            });
+        }
+        return;
+    }
+
     // Check if it matches any ref-format pattern
+    let mut rejected = Vec::new();
     let matches_format = schema.ref_formats.iter().any(|rf| {
-        safe_regex(&rf.pattern)
-            .map(|re| re.is_match(value))
-            .unwrap_or(false)
+        schema
+            .patterns()
+            .is_match(&rf.pattern, value)
+            .unwrap_or_else(|e| {
+                rejected.push(format!("ref-format \"{}\": {e}", rf.name));
+                false
+            })
     });
+    for message in rejected {
+        let message = format!("invalid regex pattern in schema for {message}");
+        if !diags.iter().any(|d| d.code == "S000" && d.message == message) {
+            diags.push(Diagnostic {
+                severity: Severity::Warning,
+                code: "S000".into(),
+                message,
+                location: "schema".into(),
+                hint: None,
+                span: None,
+            });
+        }
+    }
 
     if !matches_format && !schema.ref_formats.is_empty() {
         let patterns: Vec<&str> = schema.ref_formats.iter().map(|rf| rf.pattern.as_str()).collect();
@@ -539,6 +965,7 @@ fn validate_ref(
             message: format!("ref \"{value}\" in \"{field_name}\" doesn't match any ref-format"),
             location: format!("frontmatter.{field_name}"),
             hint: Some(format!("expected patterns: {}", patterns.join(", "))),
+            span: None,
         });
         return;
     }
@@ -564,6 +991,7 @@ fn validate_ref(
                             ),
                             location: format!("frontmatter.{field_name}"),
                             hint: Some(format!("resolved to: {}", target.display())),
+                            span: None,
                         });
                     }
                 }
@@ -580,6 +1008,7 @@ fn validate_ref(
                 ),
                 location: format!("frontmatter.{field_name}"),
                 hint: Some("no document with matching ID found in scope".into()),
+                span: None,
             });
         }
     }
@@ -602,6 +1031,7 @@ fn validate_user_ref(
             ),
             location: format!("frontmatter.{field_name}"),
             hint: Some("user references must start with @ (e.g. @onni, @team/platform)".into()),
+            span: None,
         });
         return;
     }
@@ -623,6 +1053,7 @@ fn validate_user_ref(
                 } else {
                     Some(format!("known: {}", all_refs.join(", ")))
                 },
+                span: None,
             });
         }
     }
@@ -646,46 +1077,7 @@ fn validate_sections(
 
         match section_result {
             Ok(section) => {
-                // Validate table if defined
-                if let Some(ref table_def) = sec_def.table {
-                    let tables = section.tables();
-                    if tables.is_empty() && table_def.required {
-                        diags.push(Diagnostic {
-                            severity: Severity::Error,
-                            code: "S020".into(),
-                            message: format!(
-                                "section \"{}\" requires a table but none found",
-                                sec_def.name
-                            ),
-                            location: format!("section \"{}\"", sec_def.name),
-                            hint: Some("add a markdown table to this section".into()),
-                        });
-                    } else if let Some(table) = tables.first() {
-                        validate_table_columns(table, table_def, &sec_def.name, user_config, diags);
-                    }
-                }
-
-                // Content constraint
-                if let Some(ref content_def) = sec_def.content {
-                    validate_content_constraint(&section, content_def, &sec_def.name, diags);
-                }
-
-                // List constraint
-                if let Some(ref list_def) = sec_def.list {
-                    validate_list_constraint(&section, list_def, &sec_def.name, diags);
-                }
-
-                // Diagram constraint
-                if let Some(ref diagram_def) = sec_def.diagram {
-                    validate_diagram_constraint(&section, diagram_def, &sec_def.name, diags);
-                }
-
-                // Recurse into child sections
-                if !sec_def.children.is_empty() {
-                    let mut path: Vec<&str> = parent_path.to_vec();
-                    path.push(&sec_def.name);
-                    validate_sections(doc, &sec_def.children, &path, user_config, diags);
-                }
+                validate_section_body(doc, &section, sec_def, &sec_def.name, parent_path, user_config, diags);
             }
             Err(_) => {
                 if sec_def.required {
@@ -707,13 +1099,169 @@ fn validate_sections(
                         message: format!("missing required section \"{full_name}\""),
                         location: "document body".into(),
                         hint: Some(hint),
+                        span: None,
+                    });
+                }
+            }
+        }
+    }
+}
+
+/// Check the constraints of a section that was found, then recurse into its children.
+/// `name` is the actual heading (differs from `sec_def.name` for pattern sections).
+fn validate_section_body(
+    doc: &Document,
+    section: &crate::section::Section,
+    sec_def: &SectionDef,
+    name: &str,
+    parent_path: &[&str],
+    user_config: Option<&UserConfig>,
+    diags: &mut Vec<Diagnostic>,
+) {
+    // Validate table if defined
+    if let Some(ref table_def) = sec_def.table {
+        let tables = section.tables();
+        if tables.is_empty() && table_def.required {
+            diags.push(Diagnostic {
+                severity: Severity::Error,
+                code: "S020".into(),
+                message: format!("section \"{name}\" requires a table but none found"),
+                location: format!("section \"{name}\""),
+                hint: Some("add a markdown table to this section".into()),
+                span: None,
+            });
+        } else if let Some(table) = tables.first() {
+            validate_table_columns(table, table_def, name, user_config, diags);
+            if let Some(ref order) = table_def.ordered_by {
+                validate_table_order(table, order, name, diags);
+            }
+        }
+    }
+
+    // Content constraint
+    if let Some(ref content_def) = sec_def.content {
+        validate_content_constraint(section, content_def, name, diags);
+    }
+
+    // List constraint
+    if let Some(ref list_def) = sec_def.list {
+        validate_list_constraint(section, list_def, name, diags);
+    }
+
+    // Diagram constraint
+    if let Some(ref diagram_def) = sec_def.diagram {
+        validate_diagram_constraint(doc, section, diagram_def, name, diags);
+    }
+
+    // Recurse into child sections
+    if !sec_def.children.is_empty() || !sec_def.section_patterns.is_empty() {
+        let mut path: Vec<&str> = parent_path.to_vec();
+        path.push(name);
+        validate_sections(doc, &sec_def.children, &path, user_config, diags);
+        validate_section_patterns(doc, &sec_def.section_patterns, &path, user_config, diags);
+    }
+}
+
+/// Validate repeated sections: count within min/max, numbered headings in
+/// ascending order, and each match against the pattern's constraints.
+fn validate_section_patterns(
+    doc: &Document,
+    patterns: &[SectionPatternDef],
+    parent_path: &[&str],
+    user_config: Option<&UserConfig>,
+    diags: &mut Vec<Diagnostic>,
+) {
+    if patterns.is_empty() {
+        return;
+    }
+    let siblings = if parent_path.is_empty() {
+        doc.sections()
+    } else {
+        match doc.get_section_by_path(parent_path) {
+            Ok(parent) => parent.subsections(),
+            Err(_) => return,
+        }
+    };
+    let scope = if parent_path.is_empty() {
+        String::new()
+    } else {
+        format!(" under \"{}\"", parent_path.join(" > "))
+    };
+
+    for pat in patterns {
+        let matched: Vec<&crate::section::Section> =
+            siblings.iter().filter(|s| pat.matches(&s.heading)).collect();
+
+        if let Some(min) = pat.min {
+            if matched.len() < min {
+                diags.push(Diagnostic {
+                    severity: Severity::Error,
+                    code: "S040".into(),
+                    message: format!(
+                        "expected at least {min} section(s) matching \"{}\"{scope}, found {}",
+                        pat.pattern,
+                        matched.len()
+                    ),
+                    location: "document body".into(),
+                    hint: pat.section.description.clone(),
+                    span: None,
+                });
+            }
+        }
+        if let Some(max) = pat.max {
+            if matched.len() > max {
+                diags.push(Diagnostic {
+                    severity: Severity::Error,
+                    code: "S041".into(),
+                    message: format!(
+                        "expected at most {max} section(s) matching \"{}\"{scope}, found {}",
+                        pat.pattern,
+                        matched.len()
+                    ),
+                    location: format!("section \"{}\"", matched[max].heading.trim()),
+                    hint: None,
+                    span: None,
+                });
+            }
+        }
+
+        // Numbered headings ("Day 3") must ascend in document order
+        let mut prev: Option<(u64, &str)> = None;
+        for section in &matched {
+            let heading = section.heading.trim();
+            let Some(n) = first_number(heading) else {
+                continue;
+            };
+            if let Some((p, prev_heading)) = prev {
+                if n <= p {
+                    diags.push(Diagnostic {
+                        severity: Severity::Warning,
+                        code: "S042".into(),
+                        message: format!("section \"{heading}\" comes after \"{prev_heading}\""),
+                        location: format!("section \"{heading}\""),
+                        hint: Some("keep repeated sections in ascending order".into()),
+                        span: None,
                     });
                 }
             }
+            prev = Some((n, heading));
+        }
+
+        for section in &matched {
+            validate_section_body(doc, section, &pat.section, section.heading.trim(), parent_path, user_config, diags);
         }
     }
 }
 
+fn first_number(s: &str) -> Option<u64> {
+    let digits: String = s
+        .chars()
+        .skip_while(|c| !c.is_ascii_digit())
+        .take_while(|c| c.is_ascii_digit())
+        .collect();
+    digits.parse().ok()
+}
+
 /// Validate table columns: required columns present + user type columns.
 fn validate_table_columns(
     table: &crate::table::Table,
@@ -733,6 +1281,7 @@ fn validate_table_columns(
                 ),
                 location: format!("section \"{section_name}\" > table"),
                 hint: None,
+                span: None,
             });
             continue;
         }
@@ -753,6 +1302,7 @@ fn validate_table_columns(
                                 ),
                                 location: format!("section \"{section_name}\" > table > {}[{row_idx}]", col_def.name),
                                 hint: None,
+                                span: None,
                             });
                         }
                         continue;
@@ -769,6 +1319,33 @@ fn validate_table_columns(
     }
 }
 
+/// S023: rows must be sorted by the `ordered-by` column. Reports the first row
+/// out of place; `md-db fix` sorts the whole table.
+fn validate_table_order(
+    table: &crate::table::Table,
+    order: &OrderedBy,
+    section_name: &str,
+    diags: &mut Vec<Diagnostic>,
+) {
+    let Some(row_idx) = table.first_out_of_order(&order.column, order.ascending) else {
+        return;
+    };
+    let direction = if order.ascending { "ascending" } else { "descending" };
+    let cell = table.get_cell(&order.column, row_idx).unwrap_or_default().trim();
+    diags.push(Diagnostic {
+        severity: Severity::Error,
+        code: "S023".into(),
+        message: format!(
+            "table in \"{section_name}\" is not sorted by \"{}\" ({direction}): \
+             row {row_idx} \"{cell}\" is out of order",
+            order.column
+        ),
+        location: format!("section \"{section_name}\" > table > {}[{row_idx}]", order.column),
+        hint: Some("run `md-db fix` to sort the rows".into()),
+        span: None,
+    });
+}
+
 /// Known diagram languages for fenced code blocks.
 const DIAGRAM_LANGUAGES: &[&str] = &["mermaid", "d2", "plantuml", "graphviz", "dot"];
 
@@ -797,6 +1374,7 @@ fn validate_content_constraint(
                 ),
                 location: format!("section \"{section_name}\""),
                 hint: Some("add prose content to this section".into()),
+                span: None,
             });
         }
     }
@@ -824,6 +1402,7 @@ fn validate_list_constraint(
             message: format!("section \"{section_name}\" requires a list but none found"),
             location: format!("section \"{section_name}\""),
             hint: Some("add a markdown list (- item) to this section".into()),
+            span: None,
         });
         return;
     }
@@ -849,12 +1428,14 @@ fn validate_list_constraint(
                 ),
                 location: format!("section \"{section_name}\""),
                 hint: Some(format!("add at least {min_items} list items")),
+                span: None,
             });
         }
     }
 }
 
 fn validate_diagram_constraint(
+    doc: &Document,
     section: &crate::section::Section,
     diagram_def: &DiagramDef,
     section_name: &str,
@@ -864,11 +1445,13 @@ fn validate_diagram_constraint(
     let opts = comrak::Options::default();
     let root = comrak::parse_document(&arena, &section.content, &opts);
 
-    let code_blocks: Vec<String> = root
+    // (info string, source, line of the opening fence within the section content)
+    let code_blocks: Vec<(String, String, usize)> = root
         .descendants()
         .filter_map(|n| {
-            if let NodeValue::CodeBlock(ref cb) = n.data.borrow().value {
-                Some(cb.info.trim().to_lowercase())
+            let data = n.data.borrow();
+            if let NodeValue::CodeBlock(ref cb) = data.value {
+                Some((cb.info.trim().to_lowercase(), cb.literal.clone(), data.sourcepos.start.line))
             } else {
                 None
             }
@@ -877,13 +1460,51 @@ fn validate_diagram_constraint(
 
     let has_diagram = if let Some(ref expected_type) = diagram_def.diagram_type {
         let expected = expected_type.to_lowercase();
-        code_blocks.iter().any(|info| info == &expected)
+        code_blocks.iter().any(|(info, _, _)| info == &expected)
     } else {
         code_blocks
             .iter()
-            .any(|info| DIAGRAM_LANGUAGES.iter().any(|lang| info == lang))
+            .any(|(info, _, _)| DIAGRAM_LANGUAGES.iter().any(|lang| info == lang))
     };
 
+    if diagram_def.check {
+        // Body line where the section content starts, so errors point at real lines
+        let content_line = doc
+            .body
+            .find(&section.raw)
+            .filter(|_| section.raw.ends_with(&section.content))
+            .map(|pos| {
+                let start = pos + section.raw.len() - section.content.len();
+                doc.body[..start].matches('\n').count() + 1
+            });
+        for (info, source, fence_line) in &code_blocks {
+            let wanted = diagram_def
+                .diagram_type
+                .as_ref()
+                .is_none_or(|t| t.eq_ignore_ascii_case(info));
+            if !wanted {
+                continue;
+            }
+            for e in crate::diagram::check(info, source) {
+                let location = match content_line {
+                    Some(c) => format!("body:{}", c + fence_line - 1 + e.line),
+                    None => format!("section \"{section_name}\""),
+                };
+                diags.push(Diagnostic {
+                    severity: Severity::Error,
+                    code: "S033".into(),
+                    message: format!(
+                        "{info} syntax error in section \"{section_name}\" (diagram line {}): {}",
+                        e.line, e.message
+                    ),
+                    location,
+                    hint: Some("the diagram will not render; fix it before exporting".into()),
+                    span: None,
+                });
+            }
+        }
+    }
+
     if !has_diagram && diagram_def.required {
         let hint = if let Some(ref dt) = diagram_def.diagram_type {
             format!("add a ```{dt} code block to this section")
@@ -901,33 +1522,16 @@ fn validate_diagram_constraint(
             ),
             location: format!("section \"{section_name}\""),
             hint: Some(hint),
+            span: None,
         });
     }
 }
 
-/// Compile a regex with a size limit to prevent excessive compilation time from
-/// pathological patterns in user-provided schemas.
-fn safe_regex(pattern: &str) -> Result<Regex, regex::Error> {
-    RegexBuilder::new(pattern)
-        .size_limit(1 << 20) // 1 MiB compiled NFA limit
-        .build()
-}
-
-fn check_pattern(field_name: &str, value: &str, pattern: &str, diags: &mut Vec<Diagnostic>) {
-    match safe_regex(pattern) {
-        Ok(re) => {
-            if !re.is_match(value) {
-                diags.push(Diagnostic {
-                    severity: Severity::Error,
-                    code: "F030".into(),
-                    message: format!(
-                        "field \"{field_name}\" value \"{value}\" doesn't match pattern"
-                    ),
-                    location: format!("frontmatter.{field_name}"),
-                    hint: Some(format!("expected pattern: {pattern}")),
-                });
-            }
-        }
+/// F030 when `value` doesn't match `pattern`; S000 when the schema pattern was
+/// rejected by the limits in [`crate::pattern`].
+fn check_pattern(schema: &Schema, field_name: &str, value: &str, pattern: &str, diags: &mut Vec<Diagnostic>) {
+    let re = match schema.patterns().get(pattern) {
+        Ok(re) => re,
         Err(e) => {
             diags.push(Diagnostic {
                 severity: Severity::Warning,
@@ -935,11 +1539,213 @@ fn check_pattern(field_name: &str, value: &str, pattern: &str, diags: &mut Vec<D
                 message: format!("invalid regex pattern in schema for \"{field_name}\": {e}"),
                 location: "schema".into(),
                 hint: None,
+                span: None,
             });
+            return;
         }
+    };
+    if value.len() > crate::pattern::MAX_INPUT_LEN {
+        diags.push(Diagnostic {
+            severity: Severity::Error,
+            code: "F030".into(),
+            message: format!(
+                "field \"{field_name}\" value is {} bytes, too long to match against its pattern",
+                value.len()
+            ),
+            location: format!("frontmatter.{field_name}"),
+            hint: Some(format!("patterns are matched against at most {} bytes", crate::pattern::MAX_INPUT_LEN)),
+            span: None,
+        });
+        return;
+    }
+    if !re.is_match(value) {
+        diags.push(Diagnostic {
+            severity: Severity::Error,
+            code: "F030".into(),
+            message: format!(
+                "field \"{field_name}\" value \"{value}\" doesn't match pattern"
+            ),
+            location: format!("frontmatter.{field_name}"),
+            hint: Some(format!("expected pattern: {pattern}")),
+            span: None,
+        });
     }
 }
 
+/// Check a `date`/`datetime` value's format and its `min`/`max`/`not-future` bounds.
+fn validate_temporal(
+    field_name: &str,
+    val: &serde_yaml::Value,
+    field_def: &FieldDef,
+    schema: &Schema,
+    diags: &mut Vec<Diagnostic>,
+) {
+    let is_date = field_def.field_type == FieldType::Date;
+    let (expected, example) = if is_date {
+        ("date", "YYYY-MM-DD")
+    } else {
+        ("datetime", "YYYY-MM-DDTHH:MM:SSZ")
+    };
+    let Some(s) = val.as_str() else {
+        diags.push(type_mismatch(field_name, expected, val));
+        return;
+    };
+    let parsed = if is_date {
+        parse_date(s).map(|days| days * 86_400)
+    } else {
+        parse_datetime(s)
+    };
+    let Some(ts) = parsed else {
+        diags.push(Diagnostic {
+            severity: Severity::Error,
+            code: "F020".into(),
+            message: format!("field \"{field_name}\" value \"{s}\" is not a valid {expected}"),
+            location: format!("frontmatter.{field_name}"),
+            hint: Some(format!("use ISO 8601: {example}")),
+            span: None,
+        });
+        return;
+    };
+    if let Some(pattern) = field_def.pattern.as_deref() {
+        check_pattern(schema, field_name, s, pattern, diags);
+    }
+
+    let out_of_range = |bound: &str, problem: String| Diagnostic {
+        severity: Severity::Error,
+        code: "F031".into(),
+        message: format!("field \"{field_name}\" value \"{s}\" is {problem} {bound}"),
+        location: format!("frontmatter.{field_name}"),
+        hint: None,
+        span: None,
+    };
+    if let Some(min) = field_def.min.as_deref() {
+        if parse_timestamp(min).is_some_and(|m| ts < m) {
+            diags.push(out_of_range(min, "before the minimum".into()));
+        }
+    }
+    if let Some(max) = field_def.max.as_deref() {
+        if parse_timestamp(max).is_some_and(|m| ts > m) {
+            diags.push(out_of_range(max, "after the maximum".into()));
+        }
+    }
+    if field_def.not_future {
+        let now = now_timestamp();
+        // A date is in the future only from tomorrow on
+        let limit = if is_date { now - now.rem_euclid(86_400) } else { now };
+        if ts > limit {
+            let today = if is_date {
+                crate::template::format_today()
+            } else {
+                crate::template::format_now()
+            };
+            diags.push(out_of_range(&today, "in the future, after".into()));
+        }
+    }
+}
+
+/// Parse an ISO 8601 calendar date (`YYYY-MM-DD`) into days since 1970-01-01.
+pub fn parse_date(s: &str) -> Option<i64> {
+    let b = s.as_bytes();
+    if b.len() != 10 || b[4] != b'-' || b[7] != b'-' {
+        return None;
+    }
+    let year: i64 = digits(&s[0..4])?;
+    let month: u32 = digits(&s[5..7])?;
+    let day: u32 = digits(&s[8..10])?;
+    if !(1..=12).contains(&month) || day == 0 || day > days_in_month(year, month) {
+        return None;
+    }
+    Some(days_from_civil(year, month, day))
+}
+
+/// Parse an ISO 8601 datetime into seconds since the Unix epoch (UTC).
+/// Accepts `T` or a space between date and time, optional seconds and fraction,
+/// and an optional `Z` or `±HH:MM` offset (no offset means UTC).
+pub fn parse_datetime(s: &str) -> Option<i64> {
+    if s.len() < 16 || !s.is_char_boundary(10) {
+        return None;
+    }
+    let days = parse_date(&s[..10])?;
+    let rest = s[10..].strip_prefix(['T', 't', ' '])?;
+
+    let (time, offset_secs) = if let Some(t) = rest.strip_suffix(['Z', 'z']) {
+        (t, 0)
+    } else if let Some(pos) = rest.rfind(['+', '-']) {
+        let (t, off) = rest.split_at(pos);
+        let sign = if off.starts_with('-') { -1 } else { 1 };
+        let off = off[1..].replace(':', "");
+        if off.len() != 4 {
+            return None;
+        }
+        // `get` rather than indexing: a multi-byte character may straddle byte 2
+        let hours: i64 = digits(off.get(..2)?)?;
+        let minutes: i64 = digits(off.get(2..)?)?;
+        if hours > 23 || minutes > 59 {
+            return None;
+        }
+        (t, sign * (hours * 3600 + minutes * 60))
+    } else {
+        (rest, 0)
+    };
+
+    let (hms, frac) = time.split_once('.').unwrap_or((time, ""));
+    if !frac.chars().all(|c| c.is_ascii_digit()) || (time.contains('.') && frac.is_empty()) {
+        return None;
+    }
+    let mut parts = hms.split(':');
+    let hour: i64 = parts.next().filter(|p| p.len() == 2).and_then(digits)?;
+    let minute: i64 = parts.next().filter(|p| p.len() == 2).and_then(digits)?;
+    let second: i64 = match parts.next() {
+        Some(p) if p.len() == 2 => digits(p)?,
+        Some(_) => return None,
+        None if frac.is_empty() => 0,
+        None => return None,
+    };
+    if parts.next().is_some() || hour > 23 || minute > 59 || second > 60 {
+        return None;
+    }
+    Some(days * 86_400 + hour * 3600 + minute * 60 + second - offset_secs)
+}
+
+/// A date or datetime as seconds since the Unix epoch; dates count as midnight UTC.
+pub fn parse_timestamp(s: &str) -> Option<i64> {
+    parse_datetime(s).or_else(|| parse_date(s).map(|days| days * 86_400))
+}
+
+fn now_timestamp() -> i64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or_default()
+}
+
+fn digits<T: std::str::FromStr>(s: &str) -> Option<T> {
+    if s.is_empty() || !s.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    s.parse().ok()
+}
+
+fn days_in_month(year: i64, month: u32) -> u32 {
+    match month {
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Days since 1970-01-01 for a proleptic Gregorian date (Howard Hinnant's algorithm).
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = if y >= 0 { y } else { y - 399 } / 400;
+    let yoe = y - era * 400;
+    let mp = (month as i64 + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + day as i64 - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
 fn type_mismatch(field_name: &str, expected: &str, got: &serde_yaml::Value) -> Diagnostic {
     Diagnostic {
         severity: Severity::Error,
@@ -950,6 +1756,83 @@ fn type_mismatch(field_name: &str, expected: &str, got: &serde_yaml::Value) -> D
         ),
         location: format!("frontmatter.{field_name}"),
         hint: None,
+        span: None,
+    }
+}
+
+/// Writing-quality checks on required sections. All findings are warnings.
+fn validate_prose(doc: &Document, type_def: &TypeDef, rules: &ProseRules, diags: &mut Vec<Diagnostic>) {
+    for (name, text) in crate::prose::required_section_texts(doc, type_def) {
+        let stats = crate::prose::analyze(&text);
+        if stats.sentences == 0 {
+            continue;
+        }
+
+        if let Some(max) = rules.max_sentence_words {
+            let long = crate::prose::long_sentences(&text, max);
+            if !long.is_empty() {
+                diags.push(Diagnostic {
+                    severity: Severity::Warning,
+                    code: "P001".into(),
+                    message: format!(
+                        "{} sentence(s) in \"{name}\" longer than {max} words (longest {})",
+                        long.len(),
+                        stats.longest
+                    ),
+                    location: format!("section \"{name}\""),
+                    hint: Some("split long sentences".into()),
+                    span: None,
+                });
+            }
+        }
+
+        if let Some(max) = rules.max_passive_ratio {
+            let ratio = stats.passive_ratio();
+            if ratio > max {
+                diags.push(Diagnostic {
+                    severity: Severity::Warning,
+                    code: "P002".into(),
+                    message: format!(
+                        "{:.0}% of sentences in \"{name}\" are passive (max {:.0}%)",
+                        ratio * 100.0,
+                        max * 100.0
+                    ),
+                    location: format!("section \"{name}\""),
+                    hint: Some("say who does what: \"we chose X\" rather than \"X was chosen\"".into()),
+                    span: None,
+                });
+            }
+        }
+
+        if let (Some(min), Some(score)) = (rules.min_readability, stats.reading_ease()) {
+            if score < min {
+                diags.push(Diagnostic {
+                    severity: Severity::Warning,
+                    code: "P003".into(),
+                    message: format!("\"{name}\" has reading ease {score:.0} (min {min:.0})"),
+                    location: format!("section \"{name}\""),
+                    hint: Some("use shorter sentences and simpler words".into()),
+                    span: None,
+                });
+            }
+        }
+    }
+
+    for rule in &rules.require_verbs {
+        let Ok(section) = doc.get_section(&rule.section) else {
+            continue;
+        };
+        let text = crate::prose::prose_text(&section.content);
+        if !crate::prose::uses_any_verb(&text, &rule.verbs) {
+            diags.push(Diagnostic {
+                severity: Severity::Warning,
+                code: "P004".into(),
+                message: format!("section \"{}\" does not state a decision", rule.section),
+                location: format!("section \"{}\"", rule.section),
+                hint: Some(format!("use one of: {}", rule.verbs.join(", "))),
+                span: None,
+            });
+        }
     }
 }
 
@@ -969,10 +1852,515 @@ pub fn validate_singleton(
 
     // Validate sections only (no frontmatter checks)
     validate_sections(doc, &type_def.sections, &[], user_config, &mut diagnostics);
+    validate_section_patterns(doc, &type_def.section_patterns, &[], user_config, &mut diagnostics);
+    validate_raw_html(doc, &mut diagnostics);
+    if let Some(config) = user_config {
+        validate_mentions(doc, config, &mut diagnostics);
+    }
+    crate::span::fill(doc, &mut diagnostics);
 
     FileResult { path, diagnostics }
 }
 
+/// U012: an `@mention` in the body that isn't a known user or team (warning).
+fn validate_mentions(doc: &Document, config: &UserConfig, diags: &mut Vec<Diagnostic>) {
+    for mention in crate::users::find_mentions(&doc.body) {
+        if config.is_valid_ref(&mention.handle) {
+            continue;
+        }
+        diags.push(Diagnostic {
+            severity: Severity::Warning,
+            code: "U012".into(),
+            message: format!("mention \"{}\" is not a known user or team", mention.handle),
+            location: format!("body:{}", mention.line),
+            hint: Some("update the mention, or wrap it in backticks if it isn't a person".into()),
+            span: None,
+        });
+    }
+}
+
+/// Collect the IDs refs can resolve to: display IDs from filenames and stable uids.
+pub fn collect_known_ids<'a>(files: impl IntoIterator<Item = &'a PathBuf>) -> HashSet<String> {
+    let mut known_ids = HashSet::new();
+    for path in files {
+        // Extract IDs from filenames: adr-001.md -> ADR-001
+        // Handles slugged filenames: adr-001-use-postgresql.md -> ADR-001
+        known_ids.insert(crate::graph::path_to_id(path));
+        if let Some(uid) = crate::uid::read_uid(path) {
+            known_ids.insert(uid);
+        }
+    }
+    known_ids
+}
+
+/// S050: two headings in one document resolve to the same `#anchor`.
+fn validate_heading_anchors(doc: &Document, diags: &mut Vec<Diagnostic>) {
+    let arena = Arena::new();
+    let opts = crate::ast_util::comrak_opts();
+    let root = comrak::parse_document(&arena, &doc.body, &opts);
+
+    let mut seen: HashMap<String, String> = HashMap::new();
+    for node in crate::ast_util::find_headings(root, None) {
+        let text = crate::ast_util::collect_text(node).trim().to_string();
+        let anchor = crate::ast_util::heading_anchor(&text);
+        if anchor.is_empty() {
+            continue;
+        }
+        match seen.get(&anchor) {
+            Some(first) => diags.push(Diagnostic {
+                severity: Severity::Warning,
+                code: "S050".into(),
+                message: format!("heading \"{text}\" has the same anchor (#{anchor}) as heading \"{first}\""),
+                location: format!("section \"{text}\""),
+                hint: Some(format!("rename one of them; links to #{anchor} only reach the first")),
+                span: None,
+            }),
+            None => {
+                seen.insert(anchor, text);
+            }
+        }
+    }
+}
+
+/// A010: a relative link or image points at a file that does not exist.
+fn validate_asset_links(doc: &Document, diags: &mut Vec<Diagnostic>) {
+    let Some(ref path) = doc.path else {
+        return;
+    };
+    for link in crate::assets::broken_links(path, &doc.body) {
+        diags.push(Diagnostic {
+            severity: Severity::Error,
+            code: "A010".into(),
+            message: format!("link target \"{}\" does not exist", link.url),
+            location: format!("body:{}", link.line),
+            hint: Some("paths are relative to the document's folder; fix the path or add the file".into()),
+            span: None,
+        });
+    }
+}
+
+/// H001: raw HTML in the body that the HTML export's sanitizer removes (warning).
+fn validate_raw_html(doc: &Document, diags: &mut Vec<Diagnostic>) {
+    for found in crate::sanitize::find_unsafe_html(&doc.body) {
+        diags.push(Diagnostic {
+            severity: Severity::Warning,
+            code: "H001".into(),
+            message: format!("raw HTML {} is removed from the HTML export", found.what),
+            location: format!("body:{}", found.line),
+            hint: Some("write it in markdown, or export with --unsafe-html if the content is trusted".into()),
+            span: None,
+        });
+    }
+}
+
+/// V001: a projection whose file is missing or whose tables are out of date (warning).
+/// Projections that can't be rendered are left to `md-db project` to report.
+fn validate_projections(
+    dir: &Path,
+    typed_docs: &[(PathBuf, Document)],
+    schema: &Schema,
+    file_results: &mut Vec<FileResult>,
+) {
+    let docs: Vec<(String, &Document)> = typed_docs
+        .iter()
+        .map(|(path, doc)| (crate::graph::path_to_id(path), doc))
+        .collect();
+    for def in &schema.projections {
+        let Ok(projection) = crate::projection::materialize(def, dir, &docs, schema) else {
+            continue;
+        };
+        let message = match projection.status {
+            crate::projection::ProjectionStatus::Fresh => continue,
+            crate::projection::ProjectionStatus::Stale => format!("projection \"{}\" is out of date", def.name),
+            crate::projection::ProjectionStatus::Missing => format!("projection \"{}\" has not been generated", def.name),
+        };
+        file_results.push(FileResult {
+            path: projection.path.display().to_string(),
+            diagnostics: vec![Diagnostic {
+                severity: Severity::Warning,
+                code: "V001".into(),
+                message,
+                location: format!("projection \"{}\"", def.name),
+                hint: Some("run `md-db project --write`".into()),
+                span: None,
+            }],
+        });
+    }
+}
+
+/// A011: a file in an `assets { orphans ... }` folder that no document links to (warning).
+fn validate_orphan_assets(dir: &Path, files: &[PathBuf], schema: &Schema, file_results: &mut Vec<FileResult>) {
+    let Some(ref rules) = schema.assets else {
+        return;
+    };
+    for folder in &rules.orphan_folders {
+        let Ok(orphans) = crate::assets::orphans(&dir.join(folder), files) else {
+            continue;
+        };
+        for path in orphans {
+            file_results.push(FileResult {
+                path: path.display().to_string(),
+                diagnostics: vec![Diagnostic {
+                    severity: Severity::Warning,
+                    code: "A011".into(),
+                    message: format!("asset is not linked from any document (in orphan folder \"{folder}\")"),
+                    location: "file".into(),
+                    hint: Some("link it from a document or delete it".into()),
+                    span: None,
+                }],
+            });
+        }
+    }
+}
+
+/// A schema-managed document, as seen by the corpus-wide collision checks.
+struct ManagedDoc {
+    path: String,
+    id: String,
+    doc_type: String,
+    title: Option<String>,
+}
+
+/// I001: two files resolve to the same document ID (e.g. `adr-012-a.md` and `adr-012-b.md`).
+/// I002: two documents of one type share a title (warning).
+fn validate_collisions(docs: &[ManagedDoc], file_results: &mut Vec<FileResult>) {
+    let mut ids: HashMap<&str, &str> = HashMap::new();
+    let mut titles: HashMap<(&str, String), &str> = HashMap::new();
+    let mut diags: Vec<(String, Diagnostic)> = Vec::new();
+
+    for doc in docs {
+        if let Some(first) = ids.get(doc.id.as_str()) {
+            diags.push((
+                doc.path.clone(),
+                Diagnostic {
+                    severity: Severity::Error,
+                    code: "I001".into(),
+                    message: format!("document ID \"{}\" is also used by {first}", doc.id),
+                    location: "file".into(),
+                    hint: Some(match next_free_id(&doc.id, docs) {
+                        Some(next) => format!("renumber one of them, e.g. to {next}"),
+                        None => "rename one of the files".into(),
+                    }),
+                    span: None,
+                },
+            ));
+        } else {
+            ids.insert(&doc.id, &doc.path);
+        }
+
+        let Some(ref title) = doc.title else {
+            continue;
+        };
+        let key = (doc.doc_type.as_str(), title.trim().to_lowercase());
+        if let Some(first) = titles.get(&key) {
+            diags.push((
+                doc.path.clone(),
+                Diagnostic {
+                    severity: Severity::Warning,
+                    code: "I002".into(),
+                    message: format!("title \"{title}\" is also used by {first} (type {})", doc.doc_type),
+                    location: "frontmatter.title".into(),
+                    hint: Some("make the titles distinct, or supersede the older document".into()),
+                    span: None,
+                },
+            ));
+        } else {
+            titles.insert(key, &doc.path);
+        }
+    }
+
+    push_file_diagnostics(diags, file_results);
+}
+
+/// The link graph of already-parsed documents, reusing the graph's ref resolution
+/// (IDs and uids), and each node's document.
+fn link_graph<'a>(
+    docs: &'a [(PathBuf, Document)],
+    schema: &Schema,
+) -> (crate::graph::DocGraph, HashMap<String, &'a (PathBuf, Document)>) {
+    let relation_names = schema.all_relation_field_names();
+    let mut graph = crate::graph::DocGraph {
+        nodes: Default::default(),
+        edges: Vec::new(),
+    };
+    let mut by_id = HashMap::new();
+    for entry in docs {
+        if let Some((node, edges)) = crate::graph::scan_document(&entry.0, &entry.1, schema, &relation_names) {
+            by_id.insert(node.id.clone(), entry);
+            graph.nodes.insert(node.id.clone(), node);
+            graph.edges.extend(edges);
+        }
+    }
+    graph.resolve_uid_refs();
+    (graph, by_id)
+}
+
+/// Add `(path, diagnostic)` pairs to the matching file results.
+fn push_file_diagnostics(diags: Vec<(String, Diagnostic)>, file_results: &mut Vec<FileResult>) {
+    for (path, diag) in diags {
+        if let Some(fr) = file_results.iter_mut().find(|fr| fr.path == path) {
+            fr.diagnostics.push(diag);
+        } else {
+            file_results.push(FileResult {
+                path,
+                diagnostics: vec![diag],
+            });
+        }
+    }
+}
+
+/// R012: refs to a document whose type the field's or relation's `target-type`
+/// excludes, e.g. `supersedes` pointing at an incident instead of an ADR. Refs
+/// that don't resolve are left to R011.
+fn validate_target_types(docs: &[(PathBuf, Document)], schema: &Schema, file_results: &mut Vec<FileResult>) {
+    let constrained = schema.relations.iter().any(|r| !r.target_types.is_empty())
+        || schema.types.iter().flat_map(|t| &t.fields).any(|f| !f.target_types.is_empty());
+    if !constrained {
+        return;
+    }
+
+    let (graph, _) = link_graph(docs, schema);
+    let mut diags: Vec<(String, Diagnostic)> = Vec::new();
+    for (path, doc) in docs {
+        let Some(fm) = doc.frontmatter.as_ref() else { continue };
+        let Some(doc_type) = fm.get_display("type") else { continue };
+
+        // Fields that hold refs: the type's ref fields and every relation
+        let mut names: Vec<&str> = schema
+            .get_type(&doc_type)
+            .map(|t| {
+                t.fields
+                    .iter()
+                    .filter(|f| matches!(f.field_type, FieldType::Ref | FieldType::RefArray))
+                    .map(|f| f.name.as_str())
+                    .collect()
+            })
+            .unwrap_or_default();
+        for r in &schema.relations {
+            if !names.contains(&r.name.as_str()) {
+                names.push(&r.name);
+            }
+        }
+
+        for name in names {
+            let targets = schema.ref_target_types(&doc_type, name);
+            let Some(value) = fm.get(name).filter(|_| !targets.is_empty()) else { continue };
+            for target in crate::graph::extract_refs(value) {
+                // Refs are uppercased; a file ref resolves by its file name
+                let id = if target.ends_with(".MD") {
+                    crate::graph::path_to_id(Path::new(&target))
+                } else {
+                    target.clone()
+                };
+                let node = graph.nodes.get(&id).or_else(|| graph.find_by_uid(&target));
+                let Some((node, Some(node_type))) = node.map(|n| (n, n.doc_type.as_ref())) else {
+                    continue;
+                };
+                if targets.contains(node_type) {
+                    continue;
+                }
+                diags.push((
+                    path.display().to_string(),
+                    Diagnostic {
+                        severity: Severity::Error,
+                        code: "R012".into(),
+                        message: format!(
+                            "field \"{name}\" points at {} (type \"{node_type}\"), expected {}",
+                            node.id,
+                            targets.join(" or ")
+                        ),
+                        location: format!("frontmatter.{name}"),
+                        hint: Some(format!(
+                            "reference a {} document, or use a relation that allows {node_type}",
+                            targets.join(" or ")
+                        )),
+                        span: None,
+                    },
+                ));
+            }
+        }
+    }
+    push_file_diagnostics(diags, file_results);
+}
+
+/// R022: a relation and its inverse disagree about a pair of documents. Either
+/// the relation is declared in both directions (A supersedes B and B supersedes
+/// A, or one document sets both `supersedes` and `superseded_by` to the other), or a cardinality-one side
+/// names a different document than the other side implies. A many side that just
+/// lacks the inverse entry is left to `sync`.
+fn validate_relation_pairs(docs: &[(PathBuf, Document)], schema: &Schema, file_results: &mut Vec<FileResult>) {
+    if schema.relations.iter().all(|r| r.inverse.is_none()) {
+        return;
+    }
+
+    let (graph, by_id) = link_graph(docs, schema);
+    // Each declared edge as (source, relation, target) in the relation's own direction
+    let mut declared = Vec::new();
+    for edge in &graph.edges {
+        let Some((rel, is_inverse)) = schema.find_relation(&edge.relation) else { continue };
+        if rel.inverse.is_none() || !graph.nodes.contains_key(&edge.to) || edge.from == edge.to {
+            continue;
+        }
+        let (source, target) = if is_inverse { (&edge.to, &edge.from) } else { (&edge.from, &edge.to) };
+        declared.push((edge, rel, is_inverse, (source.as_str(), rel.name.as_str(), target.as_str())));
+    }
+    let claims: HashSet<(&str, &str, &str)> = declared.iter().map(|(.., claim)| *claim).collect();
+
+    let mut diags: Vec<(String, Diagnostic)> = Vec::new();
+    for (edge, rel, is_inverse, (source, name, target)) in &declared {
+        let Some((path, _)) = by_id.get(&edge.from) else { continue };
+        let other_field = if *is_inverse { rel.name.as_str() } else { rel.inverse.as_deref().unwrap_or_default() };
+        let message = if claims.contains(&(*target, *name, *source)) {
+            format!("conflicting directions: {source} {name} {target}, and {target} {name} {source}")
+        } else if rel.cardinality == crate::schema::Cardinality::One {
+            let other_targets: Vec<&str> = graph
+                .edges
+                .iter()
+                .filter(|e| e.from == edge.to && e.relation == other_field)
+                .map(|e| e.to.as_str())
+                .collect();
+            if other_targets.is_empty() || other_targets.contains(&edge.from.as_str()) {
+                continue;
+            }
+            format!(
+                "\"{}: {}\" disagrees with {} ({other_field}: {})",
+                edge.relation,
+                edge.to,
+                edge.to,
+                other_targets.join(", ")
+            )
+        } else {
+            continue;
+        };
+        diags.push((
+            path.display().to_string(),
+            Diagnostic {
+                severity: Severity::Error,
+                code: "R022".into(),
+                message,
+                location: format!("frontmatter.{}", edge.relation),
+                hint: Some(format!(
+                    "\"{}\" and \"{}\" must describe the same pair; fix one side, then `md-db sync` fills the inverse",
+                    rel.name,
+                    rel.inverse.as_deref().unwrap_or_default()
+                )),
+                span: None,
+            },
+        ));
+    }
+    push_file_diagnostics(diags, file_results);
+}
+
+/// F060: a `consistent-with` constraint fails between a document and one it links to.
+/// The diagnostic lands on the linking document and names the other one.
+fn validate_consistency(docs: &[(PathBuf, Document)], schema: &Schema, file_results: &mut Vec<FileResult>) {
+    if schema.types.iter().all(|t| t.consistency.is_empty()) {
+        return;
+    }
+
+    let (graph, by_id) = link_graph(docs, schema);
+
+    let mut diags: Vec<(String, Diagnostic)> = Vec::new();
+    for edge in &graph.edges {
+        let (Some((path, doc)), Some((other_path, other))) = (by_id.get(&edge.from), by_id.get(&edge.to)) else {
+            continue;
+        };
+        let Some(type_def) = graph.nodes[&edge.from].doc_type.as_deref().and_then(|t| schema.get_type(t)) else {
+            continue;
+        };
+        for rule in type_def.consistency.iter().filter(|c| c.relation == edge.relation) {
+            let value = doc.frontmatter.as_ref().and_then(|fm| fm.get_display(&rule.field));
+            let other_value = other.frontmatter.as_ref().and_then(|fm| fm.get_display(&rule.other_field));
+            let (Some(value), Some(other_value)) = (value, other_value) else {
+                continue;
+            };
+            if rule.op.holds(crate::ordering::compare_text(&value, &other_value)) {
+                continue;
+            }
+            diags.push((
+                path.display().to_string(),
+                Diagnostic {
+                    severity: if rule.severity == "warning" { Severity::Warning } else { Severity::Error },
+                    code: "F060".into(),
+                    message: format!(
+                        "field \"{}\" is \"{value}\" but must be {} {}'s {} \"{other_value}\" ({} via {})",
+                        rule.field,
+                        rule.op,
+                        edge.to,
+                        rule.other_field,
+                        other_path.display(),
+                        edge.relation
+                    ),
+                    location: format!("frontmatter.{}", rule.field),
+                    hint: Some(format!(
+                        "update {} here or {} in {}",
+                        rule.field,
+                        rule.other_field,
+                        other_path.display()
+                    )),
+                    span: None,
+                },
+            ));
+        }
+    }
+
+    for (path, diag) in diags {
+        if let Some(fr) = file_results.iter_mut().find(|fr| fr.path == path) {
+            fr.diagnostics.push(diag);
+        } else {
+            file_results.push(FileResult {
+                path,
+                diagnostics: vec![diag],
+            });
+        }
+    }
+}
+
+/// Next unused number for the prefix of `id` (`ADR-012` -> `ADR-015` if 014 is the highest).
+fn next_free_id(id: &str, docs: &[ManagedDoc]) -> Option<String> {
+    let (prefix, num) = id.rsplit_once('-')?;
+    let width = num.len();
+    num.parse::<u64>().ok()?;
+    let max = docs
+        .iter()
+        .filter_map(|d| d.id.rsplit_once('-'))
+        .filter(|(p, _)| *p == prefix)
+        .filter_map(|(_, n)| n.parse::<u64>().ok())
+        .max()?;
+    Some(format!("{prefix}-{:0width$}", max + 1))
+}
+
+/// F041: the same uid appears in more than one file (usually a copied document).
+fn validate_unique_uids(files: &[PathBuf], file_results: &mut Vec<FileResult>) {
+    let mut seen: HashMap<String, String> = HashMap::new();
+    for path in files {
+        let Some(uid) = crate::uid::read_uid(path) else {
+            continue;
+        };
+        let path_str = path.display().to_string();
+        if let Some(first) = seen.get(&uid) {
+            let diag = Diagnostic {
+                severity: Severity::Error,
+                code: "F041".into(),
+                message: format!("uid \"{uid}\" is also used by {first}"),
+                location: "frontmatter.uid".into(),
+                hint: Some("remove the duplicate and run `md-db uid backfill`".into()),
+                span: None,
+            };
+            if let Some(fr) = file_results.iter_mut().find(|fr| fr.path == path_str) {
+                fr.diagnostics.push(diag);
+            } else {
+                file_results.push(FileResult {
+                    path: path_str,
+                    diagnostics: vec![diag],
+                });
+            }
+        } else {
+            seen.insert(uid, path_str);
+        }
+    }
+}
+
 /// Validate that no type exceeds its max_count.
 fn validate_type_counts(
     files: &[PathBuf],
@@ -982,6 +2370,13 @@ fn validate_type_counts(
     // Count documents per type
     let mut type_counts: HashMap<String, Vec<String>> = HashMap::new();
     for path in files {
+        if let Some(type_def) = schema.singleton_for(path) {
+            type_counts
+                .entry(type_def.name.clone())
+                .or_default()
+                .push(path.display().to_string());
+            continue;
+        }
         if let Ok(doc) = Document::from_file(path) {
             if let Some(ref fm) = doc.frontmatter {
                 if let Some(type_name) = fm.get_display("type") {
@@ -1013,6 +2408,7 @@ fn validate_type_counts(
                             "files: {}",
                             paths.join(", ")
                         )),
+                        span: None,
                     };
                     // Attach to the first excess file
                     if let Some(excess_path) = paths.get(max) {
@@ -1050,23 +2446,37 @@ pub fn validate_directory(
     schema: &Schema,
     pattern: Option<&str>,
     user_config: Option<&UserConfig>,
+) -> crate::error::Result<ValidationResult> {
+    validate_directory_with(dir, schema, pattern, user_config, &ValidationOptions::default())
+}
+
+/// [`validate_directory`] running only the phases `opts` selects.
+pub fn validate_directory_with(
+    dir: impl AsRef<Path>,
+    schema: &Schema,
+    pattern: Option<&str>,
+    user_config: Option<&UserConfig>,
+    opts: &ValidationOptions,
 ) -> crate::error::Result<ValidationResult> {
     let files = crate::discovery::discover_files(&dir, pattern, &[], false)?;
 
+    // Refs resolve across the whole project when `dir` is one root of a manifest
+    let namespace = match crate::project::Project::find(dir.as_ref())? {
+        Some(project) => project.files(None, &[], false)?,
+        None => files.clone(),
+    };
+
     // Build known file set and known ID set for cross-ref validation
-    let known_files: HashSet<PathBuf> = files
+    let known_files: HashSet<PathBuf> = namespace
         .iter()
         .map(|p| p.canonicalize().unwrap_or_else(|_| p.clone()))
         .collect();
 
-    let mut known_ids: HashSet<String> = HashSet::new();
-    // Extract IDs from filenames: adr-001.md -> ADR-001
-    // Handles slugged filenames: adr-001-use-postgresql.md -> ADR-001
-    for path in &files {
-        known_ids.insert(crate::graph::path_to_id(path));
-    }
+    let known_ids = collect_known_ids(&namespace);
 
     let mut file_results = Vec::new();
+    let mut managed = Vec::new();
+    let mut typed_docs = Vec::new();
     for path in &files {
         let doc = match Document::from_file(path) {
             Ok(d) => d,
@@ -1079,6 +2489,9 @@ pub fn validate_directory(
                         message: format!("failed to parse: {e}"),
                         location: "file".into(),
                         hint: None,
+                        span: std::fs::read_to_string(path)
+                            .ok()
+                            .and_then(|raw| crate::span::yaml_error_span(&raw)),
                     }],
                 });
                 continue;
@@ -1086,15 +2499,10 @@ pub fn validate_directory(
         };
 
         // Check if this is a singleton match
-        let is_singleton = {
-            let filename = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
-            schema.types.iter().find(|t| {
-                t.singleton && t.match_pattern.as_deref() == Some(filename)
-            })
-        };
-
-        if let Some(type_def) = is_singleton {
-            file_results.push(validate_singleton(&doc, type_def, user_config));
+        if let Some(type_def) = schema.singleton_for(path) {
+            let mut result = validate_singleton(&doc, type_def, user_config);
+            result.diagnostics.retain(|d| opts.reports(&d.code));
+            file_results.push(result);
             continue;
         }
 
@@ -1108,15 +2516,60 @@ pub fn validate_directory(
             }
         }
 
-        file_results.push(validate_document(&doc, schema, &known_files, &known_ids, user_config));
+        if let Some(doc_type) = doc.frontmatter.as_ref().and_then(|fm| fm.get_display("type")) {
+            managed.push(ManagedDoc {
+                path: path.display().to_string(),
+                id: crate::graph::path_to_id(path),
+                doc_type,
+                title: doc.frontmatter.as_ref().and_then(|fm| fm.get_display("title")),
+            });
+        }
+        file_results.push(validate_document_with(&doc, schema, &known_files, &known_ids, user_config, opts));
+        typed_docs.push((path.clone(), doc));
+    }
+
+    // The rest compares documents with each other
+    if !opts.runs(Phase::Graph) {
+        return Ok(ValidationResult { file_results });
     }
 
+    // Duplicate IDs and titles across the corpus
+    validate_collisions(&managed, &mut file_results);
+
+    // Field values that must agree with the documents they link to
+    validate_consistency(&typed_docs, schema, &mut file_results);
+
+    // Refs must point at the document types the schema allows
+    validate_target_types(&typed_docs, schema, &mut file_results);
+
+    // A relation and its inverse must agree on each pair
+    validate_relation_pairs(&typed_docs, schema, &mut file_results);
+
     // Validate max_count per type (includes singletons counted by match)
     validate_type_counts(&files, schema, &mut file_results);
 
+    // Stable uids must be unique across the corpus
+    validate_unique_uids(&files, &mut file_results);
+
     // Check for missing required singletons
     validate_singleton_presence(&files, schema, &mut file_results);
 
+    // Files in asset folders that nothing links to
+    validate_orphan_assets(dir.as_ref(), &namespace, schema, &mut file_results);
+
+    // Generated documents must match the documents they are generated from
+    if pattern.is_none() {
+        validate_projections(dir.as_ref(), &typed_docs, schema, &mut file_results);
+    }
+
+    // Cross-document diagnostics were added after the per-file spans
+    let docs: HashMap<String, &Document> = typed_docs.iter().map(|(p, d)| (p.display().to_string(), d)).collect();
+    for fr in &mut file_results {
+        if let Some(doc) = docs.get(&fr.path) {
+            crate::span::fill(doc, &mut fr.diagnostics);
+        }
+    }
+
     Ok(ValidationResult { file_results })
 }
 
@@ -1136,7 +2589,9 @@ fn validate_singleton_presence(
         };
 
         let found = files.iter().any(|p| {
-            p.file_name().and_then(|n| n.to_str()) == Some(pattern.as_str())
+            p.file_name()
+                .and_then(|n| n.to_str())
+                .is_some_and(|name| type_def.matches_filename(name))
         });
 
         if !found {
@@ -1154,100 +2609,496 @@ fn validate_singleton_presence(
                         ),
                         location: format!("type \"{}\"", type_def.name),
                         hint: Some(format!("create {} in the project", pattern)),
+                        span: None,
                     }],
                 });
             }
         }
     }
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_schema() -> Schema {
+        Schema::from_str(
+            r#"
+type "adr" {
+    field "title" type="string" required=#true
+    field "status" type="enum" required=#true {
+        values "proposed" "accepted" "rejected"
+    }
+    field "author" type="string" required=#true pattern="^@.+"
+    section "Decision" required=#true
+    section "Consequences" required=#true {
+        section "Positive" required=#true
+    }
+}
+ref-format {
+    string-id pattern="^ADR-\\d+$"
+}
+"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_valid_document() {
+        let doc = Document::from_str(
+            "---\ntype: adr\ntitle: Test\nstatus: accepted\nauthor: \"@onni\"\n---\n\n# Decision\n\nWe decided.\n\n# Consequences\n\n## Positive\n\nGood.\n",
+        )
+        .unwrap();
+        let schema = test_schema();
+        let result = validate_document(&doc, &schema, &HashSet::new(), &HashSet::new(), None);
+        assert_eq!(result.errors(), 0, "diagnostics: {:?}", result.diagnostics);
+    }
+
+    #[test]
+    fn test_missing_required_field() {
+        let doc =
+            Document::from_str("---\ntype: adr\ntitle: Test\nstatus: accepted\n---\n\n# Decision\n\nX\n\n# Consequences\n\n## Positive\n\nY\n")
+                .unwrap();
+        let schema = test_schema();
+        let result = validate_document(&doc, &schema, &HashSet::new(), &HashSet::new(), None);
+        assert!(result.errors() > 0);
+        assert!(result.diagnostics.iter().any(|d| d.code == "F010" && d.message.contains("author")));
+    }
+
+    #[test]
+    fn test_field_alias_validates_with_warning() {
+        let schema = Schema::from_str(
+            "type \"task\" {\n    field \"owner\" type=\"string\" required=#true pattern=\"^@.+\" {\n        alias \"assignee\"\n    }\n}\n",
+        )
+        .unwrap();
+        let check = |fm: &str| {
+            let doc = Document::from_str(&format!("---\ntype: task\n{fm}---\n")).unwrap();
+            validate_document(&doc, &schema, &HashSet::new(), &HashSet::new(), None).diagnostics
+        };
+
+        let diags = check("assignee: \"@ana\"\n");
+        assert_eq!(diags.len(), 1, "{diags:?}");
+        assert_eq!((diags[0].code.as_str(), diags[0].severity), ("F011", Severity::Warning));
+        assert_eq!(diags[0].location, "frontmatter.assignee");
+
+        let diags = check("assignee: ana\n");
+        assert!(diags.iter().any(|d| d.code == "F030" && d.location == "frontmatter.assignee"));
+        assert!(!diags.iter().any(|d| d.code == "F010"));
+
+        let diags = check("owner: \"@bo\"\nassignee: nope\n");
+        assert_eq!(diags.iter().map(|d| d.code.as_str()).collect::<Vec<_>>(), vec!["F011"]);
+    }
+
+    #[test]
+    fn test_invalid_enum_value() {
+        let doc = Document::from_str(
+            "---\ntype: adr\ntitle: T\nstatus: invalid\nauthor: \"@x\"\n---\n\n# Decision\n\nX\n\n# Consequences\n\n## Positive\n\nY\n",
+        )
+        .unwrap();
+        let schema = test_schema();
+        let result = validate_document(&doc, &schema, &HashSet::new(), &HashSet::new(), None);
+        assert!(result.diagnostics.iter().any(|d| d.code == "F021"));
+    }
+
+    #[test]
+    fn test_pattern_mismatch() {
+        let doc = Document::from_str(
+            "---\ntype: adr\ntitle: T\nstatus: accepted\nauthor: badformat\n---\n\n# Decision\n\nX\n\n# Consequences\n\n## Positive\n\nY\n",
+        )
+        .unwrap();
+        let schema = test_schema();
+        let result = validate_document(&doc, &schema, &HashSet::new(), &HashSet::new(), None);
+        assert!(result.diagnostics.iter().any(|d| d.code == "F030"));
+    }
+
+    #[test]
+    fn test_rejected_and_oversized_patterns() {
+        let schema = Schema::from_str(
+            r#"
+ref-format {
+    huge pattern="^(ADR-\\d{1000}){1000}$"
+}
+type "doc" {
+    field "code" type="string" pattern="(x{1000}){1000}"
+    field "slug" type="string" pattern="^[a-z-]+$"
+    field "related" type="ref"
+}
+"#,
+        )
+        .unwrap();
+        let rejected = schema.rejected_patterns();
+        assert_eq!(rejected.len(), 2, "{rejected:?}");
+        assert_eq!(rejected[0].source, "field \"code\" of type \"doc\"");
+
+        let long = "a".repeat(crate::pattern::MAX_INPUT_LEN + 1);
+        let doc = Document::from_str(&format!(
+            "---\ntype: doc\ncode: x\nslug: {long}\nrelated: ADR-001\n---\n"
+        ))
+        .unwrap();
+        let result = validate_document(&doc, &schema, &HashSet::new(), &HashSet::new(), None);
+        let s000: Vec<_> = result.diagnostics.iter().filter(|d| d.code == "S000").collect();
+        assert_eq!(s000.len(), 2, "{s000:?}");
+        assert!(s000.iter().any(|d| d.message.contains("ref-format \"huge\"")));
+        let f030 = result.diagnostics.iter().find(|d| d.code == "F030").unwrap();
+        assert!(f030.message.contains("too long"), "{}", f030.message);
+    }
+
+    #[test]
+    fn test_missing_required_section() {
+        let doc = Document::from_str(
+            "---\ntype: adr\ntitle: T\nstatus: accepted\nauthor: \"@x\"\n---\n\n# Decision\n\nX\n",
+        )
+        .unwrap();
+        let schema = test_schema();
+        let result = validate_document(&doc, &schema, &HashSet::new(), &HashSet::new(), None);
+        assert!(result.diagnostics.iter().any(|d| d.code == "S010" && d.message.contains("Consequences")));
+    }
+
+    #[test]
+    fn test_unknown_type() {
+        let doc = Document::from_str("---\ntype: unknown\ntitle: T\n---\n\n# Body\n").unwrap();
+        let schema = test_schema();
+        let result = validate_document(&doc, &schema, &HashSet::new(), &HashSet::new(), None);
+        assert!(result.diagnostics.iter().any(|d| d.code == "F002"));
+    }
+
+    #[test]
+    fn test_invalid_uid() {
+        let doc = Document::from_str(
+            "---\ntype: adr\ntitle: T\nstatus: accepted\nauthor: \"@x\"\nuid: not-a-uuid\n---\n\n# Decision\n\nX\n\n# Consequences\n\n## Positive\n\nY\n",
+        )
+        .unwrap();
+        let schema = test_schema();
+        let result = validate_document(&doc, &schema, &HashSet::new(), &HashSet::new(), None);
+        assert!(result.diagnostics.iter().any(|d| d.code == "F040"));
+    }
 
-    fn test_schema() -> Schema {
-        Schema::from_str(
+    #[test]
+    fn test_ref_by_uid() {
+        let schema = Schema::from_str(
             r#"
 type "adr" {
-    field "title" type="string" required=#true
-    field "status" type="enum" required=#true {
-        values "proposed" "accepted" "rejected"
-    }
-    field "author" type="string" required=#true pattern="^@.+"
-    section "Decision" required=#true
-    section "Consequences" required=#true {
-        section "Positive" required=#true
-    }
+    field "title" type="string"
 }
+relation "supersedes" inverse="superseded_by" cardinality="one"
 ref-format {
     string-id pattern="^ADR-\\d+$"
 }
 "#,
         )
-        .unwrap()
+        .unwrap();
+        let known_ids: HashSet<String> = ["ADR-001".to_string(), "01890a5d-ac96-774b-bcce-b302099a8057".to_string()]
+            .into_iter()
+            .collect();
+
+        let doc = Document::from_str(
+            "---\ntype: adr\ntitle: T\nsupersedes: 01890A5D-AC96-774B-BCCE-B302099A8057\n---\n",
+        )
+        .unwrap();
+        let result = validate_document(&doc, &schema, &HashSet::new(), &known_ids, None);
+        assert!(result.diagnostics.is_empty(), "diagnostics: {:?}", result.diagnostics);
+
+        let doc = Document::from_str(
+            "---\ntype: adr\ntitle: T\nsupersedes: 01890a5d-ac96-774b-bcce-000000000000\n---\n",
+        )
+        .unwrap();
+        let result = validate_document(&doc, &schema, &HashSet::new(), &known_ids, None);
+        assert!(result.diagnostics.iter().any(|d| d.code == "R011"));
+        assert!(!result.diagnostics.iter().any(|d| d.code == "R001"));
     }
 
     #[test]
-    fn test_valid_document() {
+    fn test_promoted_field_drift() {
+        let schema = Schema::from_str(
+            r#"
+type "slo" {
+    field "target" type="number"
+    promote "target" section="Objective" column="Target"
+    section "Objective"
+}
+"#,
+        )
+        .unwrap();
         let doc = Document::from_str(
-            "---\ntype: adr\ntitle: Test\nstatus: accepted\nauthor: \"@onni\"\n---\n\n# Decision\n\nWe decided.\n\n# Consequences\n\n## Positive\n\nGood.\n",
+            "---\ntype: slo\ntarget: 99.5\n---\n\n# Objective\n\n| Target |\n|--------|\n| 99.9 |\n",
         )
         .unwrap();
-        let schema = test_schema();
         let result = validate_document(&doc, &schema, &HashSet::new(), &HashSet::new(), None);
-        assert_eq!(result.errors(), 0, "diagnostics: {:?}", result.diagnostics);
+        let drift: Vec<_> = result.diagnostics.iter().filter(|d| d.code == "F050").collect();
+        assert_eq!(drift.len(), 1);
+        assert!(drift[0].message.contains("99.9"));
     }
 
     #[test]
-    fn test_missing_required_field() {
-        let doc =
-            Document::from_str("---\ntype: adr\ntitle: Test\nstatus: accepted\n---\n\n# Decision\n\nX\n\n# Consequences\n\n## Positive\n\nY\n")
-                .unwrap();
-        let schema = test_schema();
+    fn test_invalid_query_block() {
+        let schema = Schema::from_str("type \"adr\" {\n    field \"title\" type=\"string\"\n}\n").unwrap();
+        let doc = Document::from_str(
+            "---\ntype: adr\ntitle: T\n---\n\n```md-db-query\ntype: inc\n```\n\n```md-db-query\nwhere: status\n```\n",
+        )
+        .unwrap();
         let result = validate_document(&doc, &schema, &HashSet::new(), &HashSet::new(), None);
-        assert!(result.errors() > 0);
-        assert!(result.diagnostics.iter().any(|d| d.code == "F010" && d.message.contains("author")));
+        let q: Vec<_> = result.diagnostics.iter().filter(|d| d.code == "Q001").collect();
+        assert_eq!(q.len(), 1);
+        assert!(q[0].message.contains("invalid condition"));
     }
 
     #[test]
-    fn test_invalid_enum_value() {
+    fn test_raw_html_warning() {
+        let schema = Schema::from_str("type \"adr\" {\n    field \"title\" type=\"string\"\n}\n").unwrap();
         let doc = Document::from_str(
-            "---\ntype: adr\ntitle: T\nstatus: invalid\nauthor: \"@x\"\n---\n\n# Decision\n\nX\n\n# Consequences\n\n## Positive\n\nY\n",
+            "---\ntype: adr\ntitle: T\n---\n\n<details><summary>Fine</summary></details>\n\n<iframe src=\"x\"></iframe>\n",
         )
         .unwrap();
-        let schema = test_schema();
         let result = validate_document(&doc, &schema, &HashSet::new(), &HashSet::new(), None);
-        assert!(result.diagnostics.iter().any(|d| d.code == "F021"));
+        let h: Vec<_> = result.diagnostics.iter().filter(|d| d.code == "H001").collect();
+        assert_eq!(h.len(), 1, "{h:?}");
+        let line = doc.body.lines().position(|l| l.contains("<iframe")).unwrap() + 1;
+        assert_eq!((h[0].severity, h[0].location.clone()), (Severity::Warning, format!("body:{line}")));
+        assert!(h[0].message.contains("<iframe> element"));
     }
 
     #[test]
-    fn test_pattern_mismatch() {
-        let doc = Document::from_str(
-            "---\ntype: adr\ntitle: T\nstatus: accepted\nauthor: badformat\n---\n\n# Decision\n\nX\n\n# Consequences\n\n## Positive\n\nY\n",
+    fn test_duplicate_uid() {
+        let dir = tempfile::tempdir().unwrap();
+        let content = "---\ntype: adr\ntitle: T\nuid: 01890a5d-ac96-774b-bcce-b302099a8057\n---\n";
+        std::fs::write(dir.path().join("adr-001.md"), content).unwrap();
+        std::fs::write(dir.path().join("adr-002.md"), content).unwrap();
+        let schema = Schema::from_str("type \"adr\" {\n    field \"title\" type=\"string\"\n}\n").unwrap();
+
+        let result = validate_directory(dir.path(), &schema, None, None).unwrap();
+        let f041: Vec<_> = result
+            .file_results
+            .iter()
+            .flat_map(|fr| fr.diagnostics.iter())
+            .filter(|d| d.code == "F041")
+            .collect();
+        assert_eq!(f041.len(), 1);
+        assert!(f041[0].message.contains("adr-001.md"));
+    }
+
+    #[test]
+    fn test_consistent_with() {
+        let dir = tempfile::tempdir().unwrap();
+        let files = [
+            ("adr-001.md", "---\ntype: adr\ntitle: A\ndate: 2024-05-01\n---\n"),
+            ("adr-002.md", "---\ntype: adr\ntitle: B\ndate: 2024-03-01\nsupersedes: ADR-001\n---\n"),
+            ("adr-003.md", "---\ntype: adr\ntitle: C\ndate: 2024-06-01\nsupersedes: ADR-001\n---\n"),
+            ("gov-001.md", "---\ntype: gov\ntitle: G\nseverity: sev1\n---\n"),
+            ("inc-001.md", "---\ntype: inc\ntitle: I\nseverity: sev2\ngoverned_by: GOV-001\n---\n"),
+        ];
+        for (name, content) in files {
+            std::fs::write(dir.path().join(name), content).unwrap();
+        }
+        let schema = Schema::from_str(
+            r#"
+relation "supersedes" inverse="superseded_by" cardinality="one"
+relation "governed_by" cardinality="one"
+type "adr" {
+    field "date" type="date"
+    consistent-with "date" relation="supersedes" op=">="
+}
+type "gov" {
+    field "severity" type="string"
+}
+type "inc" {
+    field "severity" type="string"
+    consistent-with "severity" relation="governed_by" severity="warning"
+}
+"#,
         )
         .unwrap();
-        let schema = test_schema();
-        let result = validate_document(&doc, &schema, &HashSet::new(), &HashSet::new(), None);
-        assert!(result.diagnostics.iter().any(|d| d.code == "F030"));
+
+        let result = validate_directory(dir.path(), &schema, None, None).unwrap();
+        let f060: Vec<_> = result
+            .file_results
+            .iter()
+            .flat_map(|fr| fr.diagnostics.iter().map(move |d| (fr.path.as_str(), d)))
+            .filter(|(_, d)| d.code == "F060")
+            .collect();
+        assert_eq!(f060.len(), 2, "{f060:?}");
+
+        let (path, adr) = f060.iter().find(|(_, d)| d.location == "frontmatter.date").unwrap();
+        assert!(path.ends_with("adr-002.md"));
+        assert_eq!(adr.severity, Severity::Error);
+        assert!(adr.message.contains(">= ADR-001's date \"2024-05-01\""), "{}", adr.message);
+        assert!(adr.message.contains("adr-001.md"));
+
+        let (path, inc) = f060.iter().find(|(_, d)| d.location == "frontmatter.severity").unwrap();
+        assert!(path.ends_with("inc-001.md"));
+        assert_eq!(inc.severity, Severity::Warning);
+        assert!(inc.hint.as_deref().unwrap().contains("gov-001.md"));
     }
 
     #[test]
-    fn test_missing_required_section() {
+    fn test_target_types() {
+        let dir = tempfile::tempdir().unwrap();
+        let files = [
+            ("adr-001.md", "---\ntype: adr\ntitle: A\n---\n"),
+            ("adr-002.md", "---\ntype: adr\ntitle: B\nsupersedes: INC-001\nrelated: [ADR-001, inc-001.md]\n---\n"),
+            ("adr-003.md", "---\ntype: adr\ntitle: C\nsupersedes: ADR-001\nrelated: [ADR-404]\n---\n"),
+            ("inc-001.md", "---\ntype: inc\ntitle: I\n---\n"),
+        ];
+        for (name, content) in files {
+            std::fs::write(dir.path().join(name), content).unwrap();
+        }
+        let schema = Schema::from_str(
+            r#"
+relation "supersedes" inverse="superseded_by" cardinality="one" target-type="adr"
+type "adr" {
+    field "title" type="string"
+    field "related" type="ref[]" target-type="adr"
+}
+type "inc" {
+    field "title" type="string"
+}
+"#,
+        )
+        .unwrap();
+
+        let result = validate_directory(dir.path(), &schema, None, None).unwrap();
+        let r012: Vec<_> = result
+            .file_results
+            .iter()
+            .flat_map(|fr| fr.diagnostics.iter().map(move |d| (fr.path.as_str(), d)))
+            .filter(|(_, d)| d.code == "R012")
+            .collect();
+        // Unresolved ADR-404 is not a type mismatch
+        assert_eq!(r012.len(), 2, "{r012:?}");
+        assert!(r012.iter().all(|(p, _)| p.ends_with("adr-002.md")));
+        let supersedes = r012.iter().find(|(_, d)| d.location == "frontmatter.supersedes").unwrap().1;
+        assert!(supersedes.message.contains("INC-001 (type \"inc\"), expected adr"), "{}", supersedes.message);
+        assert!(supersedes.span.is_some());
+
+        let bad = r#"relation "supersedes" target-type="nope""#;
+        assert!(Schema::from_str(bad).unwrap_err().to_string().contains("unknown target-type 'nope'"));
+        let bad = r#"type "adr" { field "title" type="string" target-type="adr" }"#;
+        assert!(Schema::from_str(bad).is_err());
+    }
+
+    #[test]
+    fn test_relation_cardinality_and_pairs() {
+        let dir = tempfile::tempdir().unwrap();
+        let files = [
+            ("adr-001.md", "---\ntype: adr\nsupersedes: [ADR-002]\nenables: [ADR-002, adr-002, ADR-003]\n---\n"),
+            ("adr-002.md", "---\ntype: adr\nsupersedes: ADR-001\n---\n"),
+            ("adr-003.md", "---\ntype: adr\nsupersedes: ADR-004\nenables: [ADR-004]\n---\n"),
+            ("adr-004.md", "---\ntype: adr\nsuperseded_by: ADR-002\nenabled_by: [ADR-001]\n---\n"),
+        ];
+        for (name, content) in files {
+            std::fs::write(dir.path().join(name), content).unwrap();
+        }
+        let schema = Schema::from_str(
+            r#"
+relation "supersedes" inverse="superseded_by" cardinality="one"
+relation "enables" inverse="enabled_by" cardinality="many"
+type "adr" {}
+"#,
+        )
+        .unwrap();
+
+        let result = validate_directory(dir.path(), &schema, None, None).unwrap();
+        let diags = |file: &str, code: &str| -> Vec<Diagnostic> {
+            let fr = result.file_results.iter().find(|fr| fr.path.ends_with(file)).unwrap();
+            fr.diagnostics.iter().filter(|d| d.code == code).cloned().collect()
+        };
+
+        let r021 = diags("adr-001.md", "R021");
+        assert_eq!(r021.len(), 1);
+        assert_eq!(r021[0].hint.as_deref(), Some("write it as a single ref: supersedes: ADR-002"));
+        let r020 = diags("adr-001.md", "R020");
+        assert_eq!(r020.len(), 1);
+        assert_eq!(r020[0].location, "frontmatter.enables[1]");
+
+        // ADR-002 supersedes ADR-001, which (as an array) supersedes it back
+        let r022 = diags("adr-002.md", "R022");
+        assert_eq!(r022.len(), 1, "{r022:?}");
+        assert!(r022[0].message.contains("ADR-002 supersedes ADR-001, and ADR-001 supersedes ADR-002"));
+        // ADR-003 supersedes ADR-004, but ADR-004 names ADR-002 as its successor
+        let r022 = diags("adr-003.md", "R022");
+        assert_eq!(r022.len(), 1, "{r022:?}");
+        assert!(r022[0].message.contains("(superseded_by: ADR-002)"), "{}", r022[0].message);
+        assert_eq!(diags("adr-004.md", "R022").len(), 1);
+        // A many inverse that just lacks an entry is not a conflict
+        assert!(result
+            .file_results
+            .iter()
+            .flat_map(|fr| &fr.diagnostics)
+            .all(|d| d.code != "R022" || !d.location.contains("enable")));
+    }
+
+    #[test]
+    fn test_duplicate_ids_and_titles() {
+        let dir = tempfile::tempdir().unwrap();
+        let files = [
+            ("adr-012-use-kafka.md", "---\ntype: adr\ntitle: Use Kafka\n---\n"),
+            ("adr-012-use-nats.md", "---\ntype: adr\ntitle: Use NATS\n---\n"),
+            ("adr-014.md", "---\ntype: adr\ntitle: use kafka \n---\n"),
+            ("inc-001.md", "---\ntype: inc\ntitle: Use Kafka\n---\n"),
+        ];
+        for (name, content) in files {
+            std::fs::write(dir.path().join(name), content).unwrap();
+        }
+        let schema = Schema::from_str("type \"adr\" {\n}\ntype \"inc\" {\n}\n").unwrap();
+
+        let result = validate_directory(dir.path(), &schema, None, None).unwrap();
+        let diags: Vec<_> = result.file_results.iter().flat_map(|fr| fr.diagnostics.iter()).collect();
+
+        let i001: Vec<_> = diags.iter().filter(|d| d.code == "I001").collect();
+        assert_eq!(i001.len(), 1);
+        assert_eq!(i001[0].severity, Severity::Error);
+        assert!(i001[0].message.contains("ADR-012"));
+        assert_eq!(i001[0].hint.as_deref(), Some("renumber one of them, e.g. to ADR-015"));
+
+        // Titles collide within a type only
+        let i002: Vec<_> = diags.iter().filter(|d| d.code == "I002").collect();
+        assert_eq!(i002.len(), 1);
+        assert!(i002[0].message.contains("adr-012-use-kafka.md"));
+    }
+
+    #[test]
+    fn test_heading_anchor_collision() {
+        let schema = Schema::from_str("type \"adr\" {\n}\n").unwrap();
         let doc = Document::from_str(
-            "---\ntype: adr\ntitle: T\nstatus: accepted\nauthor: \"@x\"\n---\n\n# Decision\n\nX\n",
+            "---\ntype: adr\n---\n\n# Options\n\n## Pros & Cons\n\ntext\n\n# Decision\n\n## Pros and Cons\n\n## Pros  Cons\n",
         )
         .unwrap();
-        let schema = test_schema();
         let result = validate_document(&doc, &schema, &HashSet::new(), &HashSet::new(), None);
-        assert!(result.diagnostics.iter().any(|d| d.code == "S010" && d.message.contains("Consequences")));
+        let s050: Vec<_> = result.diagnostics.iter().filter(|d| d.code == "S050").collect();
+        assert_eq!(s050.len(), 1, "{s050:?}");
+        assert!(s050[0].message.contains("#pros--cons"));
+        assert_eq!(s050[0].location, "section \"Pros  Cons\"");
     }
 
     #[test]
-    fn test_unknown_type() {
-        let doc = Document::from_str("---\ntype: unknown\ntitle: T\n---\n\n# Body\n").unwrap();
-        let schema = test_schema();
-        let result = validate_document(&doc, &schema, &HashSet::new(), &HashSet::new(), None);
-        assert!(result.diagnostics.iter().any(|d| d.code == "F002"));
+    fn test_asset_links_and_orphans() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("assets")).unwrap();
+        std::fs::write(dir.path().join("assets/used.png"), b"png").unwrap();
+        std::fs::write(dir.path().join("assets/stale.png"), b"png").unwrap();
+        std::fs::write(
+            dir.path().join("adr-001.md"),
+            "---\ntype: adr\n---\n\n# Context\n\n![](assets/used.png)\n\n![](assets/gone.png)\n",
+        )
+        .unwrap();
+
+        let schema = Schema::from_str("type \"adr\" {\n}\nassets {\n    orphans \"assets\"\n}\n").unwrap();
+        let result = validate_directory(dir.path(), &schema, None, None).unwrap();
+        let diags: Vec<_> = result.file_results.iter().flat_map(|f| &f.diagnostics).collect();
+
+        let a010: Vec<_> = diags.iter().filter(|d| d.code == "A010").collect();
+        assert_eq!(a010.len(), 1, "{a010:?}");
+        assert!(a010[0].message.contains("assets/gone.png"));
+        let doc = Document::from_file(dir.path().join("adr-001.md")).unwrap();
+        let line = doc.body.lines().position(|l| l.contains("gone.png")).unwrap() + 1;
+        assert_eq!(a010[0].location, format!("body:{line}"));
+
+        let a011: Vec<_> = result
+            .file_results
+            .iter()
+            .filter(|f| f.diagnostics.iter().any(|d| d.code == "A011"))
+            .collect();
+        assert_eq!(a011.len(), 1);
+        assert!(a011[0].path.ends_with("stale.png"));
     }
 
     fn user_schema() -> Schema {
@@ -1342,6 +3193,29 @@ teams:
         assert_eq!(result.errors(), 0, "diagnostics: {:?}", result.diagnostics);
     }
 
+    #[test]
+    fn test_unknown_mention_in_body() {
+        let doc = Document::from_str(
+            "---\ntype: doc\ntitle: T\nauthor: \"@onni\"\n---\n\n# Body\n\nAsk @alice or @bob, not `@carol`.\n",
+        )
+        .unwrap();
+        let schema = user_schema();
+        let uc = test_user_config();
+        let result = validate_document(&doc, &schema, &HashSet::new(), &HashSet::new(), Some(&uc));
+        let u012: Vec<_> = result.diagnostics.iter().filter(|d| d.code == "U012").collect();
+        assert_eq!(u012.len(), 1, "{u012:?}");
+        assert_eq!(u012[0].severity, Severity::Warning);
+        assert!(u012[0].message.contains("@bob"));
+        let line = doc.body.lines().position(|l| l.contains("@bob")).unwrap() + 1;
+        assert_eq!(u012[0].location, format!("body:{line}"));
+        let span = u012[0].span.unwrap();
+        assert_eq!((span.line, span.column), (9, 1));
+
+        // Without a user config mentions can't be checked
+        let result = validate_document(&doc, &schema, &HashSet::new(), &HashSet::new(), None);
+        assert!(!result.diagnostics.iter().any(|d| d.code == "U012"));
+    }
+
     // ─── Content constraint tests ────────────────────────────────────────
 
     fn content_schema() -> Schema {
@@ -1358,6 +3232,16 @@ type "doc" {
         .unwrap()
     }
 
+    #[test]
+    fn test_diagnostic_spans() {
+        let doc = Document::from_str("---\ntype: doc\ntitle: T\n---\n\n# Body\n\nOnly one.\n").unwrap();
+        let result = validate_document(&doc, &content_schema(), &HashSet::new(), &HashSet::new(), None);
+        let s030 = result.diagnostics.iter().find(|d| d.code == "S030").unwrap();
+        let span = s030.span.unwrap();
+        assert_eq!((span.line, span.column, span.end_line, span.end_column), (6, 1, 6, 7));
+        assert!(s030.to_string().contains("--> section \"Body\" (6:1)"));
+    }
+
     #[test]
     fn test_content_constraint_pass() {
         let doc = Document::from_str(
@@ -1380,6 +3264,40 @@ type "doc" {
         assert!(result.diagnostics.iter().any(|d| d.code == "S030"));
     }
 
+    #[test]
+    fn test_table_ordered_by() {
+        let schema = Schema::from_str(
+            r#"
+type "incident" {
+    section "Timeline" required=#true {
+        table {
+            column "Time" required=#true
+            column "Event"
+            ordered-by "Time"
+        }
+    }
+}
+"#,
+        )
+        .unwrap();
+        let check = |rows: &str| {
+            let doc = Document::from_str(&format!(
+                "---\ntype: incident\n---\n\n# Timeline\n\n| Time | Event |\n|---|---|\n{rows}"
+            ))
+            .unwrap();
+            validate_document(&doc, &schema, &HashSet::new(), &HashSet::new(), None).diagnostics
+        };
+
+        let sorted = check("| 2024-03-01 09:05 | alert |\n| 2024-03-01 14:30 | deploy |\n| 2024-03-02 | review |\n");
+        assert!(sorted.is_empty(), "{sorted:?}");
+
+        let diags = check("| 2024-03-01 14:30 | deploy |\n| 2024-03-01 09:05 | alert |\n");
+        assert_eq!(diags.len(), 1, "{diags:?}");
+        assert_eq!(diags[0].code, "S023");
+        assert!(diags[0].message.contains("row 1 \"2024-03-01 09:05\""), "{}", diags[0].message);
+        assert_eq!(diags[0].location, "section \"Timeline\" > table > Time[1]");
+    }
+
     fn list_schema() -> Schema {
         Schema::from_str(
             r#"
@@ -1427,6 +3345,54 @@ type "doc" {
         assert!(result.diagnostics.iter().any(|d| d.code == "S031" && d.message.contains("2")));
     }
 
+    fn runbook_schema() -> Schema {
+        Schema::from_str(
+            r#"
+type "runbook" {
+    field "title" type="string"
+    section-pattern "Day \\d+" min=1 max=3 {
+        list min-items=1
+    }
+}
+"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_section_pattern_pass() {
+        let doc = Document::from_str(
+            "---\ntype: runbook\ntitle: T\n---\n\n# Day 1\n\n- Setup\n\n# Day 2\n\n- Deploy\n\n# Notes\n",
+        )
+        .unwrap();
+        let result = validate_document(&doc, &runbook_schema(), &HashSet::new(), &HashSet::new(), None);
+        assert!(result.diagnostics.is_empty(), "diagnostics: {:?}", result.diagnostics);
+    }
+
+    #[test]
+    fn test_section_pattern_count_order_and_constraints() {
+        let schema = runbook_schema();
+        let doc = Document::from_str("---\ntype: runbook\ntitle: T\n---\n\n# Notes\n").unwrap();
+        let result = validate_document(&doc, &schema, &HashSet::new(), &HashSet::new(), None);
+        assert!(result.diagnostics.iter().any(|d| d.code == "S040"));
+
+        let doc = Document::from_str(
+            "---\ntype: runbook\ntitle: T\n---\n\n# Day 2\n\n- a\n\n# Day 1\n\nNo list.\n\n# Day 3\n\n- c\n\n# Day 4\n\n- d\n",
+        )
+        .unwrap();
+        let result = validate_document(&doc, &schema, &HashSet::new(), &HashSet::new(), None);
+        let codes: Vec<&str> = result.diagnostics.iter().map(|d| d.code.as_str()).collect();
+        assert!(codes.contains(&"S041"), "{codes:?}");
+        assert!(result
+            .diagnostics
+            .iter()
+            .any(|d| d.code == "S042" && d.message.contains("\"Day 1\" comes after \"Day 2\"")));
+        assert!(result
+            .diagnostics
+            .iter()
+            .any(|d| d.code == "S031" && d.location.contains("Day 1")));
+    }
+
     fn diagram_schema() -> Schema {
         Schema::from_str(
             r#"
@@ -1496,6 +3462,35 @@ type "doc" {
         assert_eq!(result.errors(), 0, "diagnostics: {:?}", result.diagnostics);
     }
 
+    #[test]
+    fn test_diagram_syntax_check() {
+        let doc = Document::from_str(
+            "---\ntype: doc\ntitle: T\n---\n\n# Arch\n\n```mermaid\ngraph TD\n  A-->B\n  B-->\n```\n",
+        )
+        .unwrap();
+
+        // Without check=#true the broken diagram still satisfies the constraint
+        let result = validate_document(&doc, &diagram_schema(), &HashSet::new(), &HashSet::new(), None);
+        assert!(!result.diagnostics.iter().any(|d| d.code == "S033"));
+
+        let schema = Schema::from_str(
+            r#"
+type "doc" {
+    field "title" type="string"
+    section "Arch" required=#true {
+        diagram type="mermaid" check=#true
+    }
+}
+"#,
+        )
+        .unwrap();
+        let result = validate_document(&doc, &schema, &HashSet::new(), &HashSet::new(), None);
+        let s033: Vec<_> = result.diagnostics.iter().filter(|d| d.code == "S033").collect();
+        assert_eq!(s033.len(), 1, "diagnostics: {:?}", result.diagnostics);
+        let line = doc.body.lines().position(|l| l == "  B-->").unwrap() + 1;
+        assert_eq!(s033[0].location, format!("body:{line}"));
+    }
+
     #[test]
     fn test_description_enriches_field_hint() {
         let schema = Schema::from_str(
@@ -1631,4 +3626,174 @@ type "doc" {
         let s010 = result.diagnostics.iter().find(|d| d.code == "S010").unwrap();
         assert!(s010.hint.as_ref().unwrap().contains("The decision and rationale"));
     }
+
+    #[test]
+    fn test_validation_phases() {
+        let schema = Schema::from_str(
+            r#"
+type "incident" {
+    field "owner" type="string" required=#true
+    section "Impact" required=#true
+    section "Timeline" required=#true {
+        table {
+            column "Time" required=#true
+        }
+    }
+}
+"#,
+        )
+        .unwrap();
+        let doc = Document::from_str("---\ntype: incident\n---\n\n# Timeline\n\n| Event |\n|---|\n| paged |\n").unwrap();
+        let codes = |only: &[&str], skip: &[&str]| {
+            let names = |n: &[&str]| n.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+            let opts = ValidationOptions::from_names(&names(only), &names(skip)).unwrap();
+            let result = validate_document_with(&doc, &schema, &HashSet::new(), &HashSet::new(), None, &opts);
+            result.diagnostics.iter().map(|d| d.code.clone()).collect::<Vec<_>>()
+        };
+        assert_eq!(codes(&[], &[]), vec!["F010", "S010", "S021"]);
+        assert_eq!(codes(&["frontmatter"], &[]), vec!["F010"]);
+        assert_eq!(codes(&["sections,tables"], &[]), vec!["S010", "S021"]);
+        assert_eq!(codes(&[], &["tables"]), vec!["F010", "S010"]);
+        assert!(codes(&["refs", "graph"], &[]).is_empty());
+
+        // A missing type is reported whatever runs
+        let untyped = Document::from_str("---\ntype: nope\n---\n").unwrap();
+        let opts = ValidationOptions { only: vec![Phase::Tables], skip: vec![] };
+        let result = validate_document_with(&untyped, &schema, &HashSet::new(), &HashSet::new(), None, &opts);
+        assert_eq!(result.diagnostics[0].code, "F002");
+        let err = ValidationOptions::from_names(&["links".into()], &[]).unwrap_err();
+        assert!(err.contains("expected frontmatter, sections, tables, refs, graph"), "{err}");
+    }
+
+    fn prose_schema() -> Schema {
+        Schema::from_str(
+            r#"
+type "adr" {
+    field "title" type="string"
+    section "Context" required=#true
+    section "Decision" required=#true
+}
+prose {
+    max-sentence-words 12
+    max-passive-ratio 0.5
+    min-readability 30
+    require-verbs "will" "adopt"
+}
+"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_prose_lint_warnings() {
+        let doc = Document::from_str(
+            "---\ntype: adr\ntitle: T\n---\n\n# Context\n\nThe service was built quickly. Requests are retried. \
+             Considerable organizational infrastructure modernization necessitates interdepartmental coordination and \
+             comprehensive institutional documentation everywhere today.\n\n# Decision\n\nPostgres is fine.\n",
+        )
+        .unwrap();
+        let result = validate_document(&doc, &prose_schema(), &HashSet::new(), &HashSet::new(), None);
+        let codes: Vec<&str> = result.diagnostics.iter().map(|d| d.code.as_str()).collect();
+        assert_eq!(codes, vec!["P001", "P002", "P003", "P004"]);
+        assert!(result.diagnostics.iter().all(|d| d.severity == Severity::Warning));
+        assert!(result.diagnostics[0].message.contains("\"Context\" longer than 12 words"));
+    }
+
+    #[test]
+    fn test_prose_lint_clean() {
+        let doc = Document::from_str(
+            "---\ntype: adr\ntitle: T\n---\n\n# Context\n\nWe need a database. Our data is small.\n\n\
+             # Decision\n\nWe will use Postgres.\n\n```sql\nselect 1 from a_table_that_was_created_by_someone_else;\n```\n",
+        )
+        .unwrap();
+        let result = validate_document(&doc, &prose_schema(), &HashSet::new(), &HashSet::new(), None);
+        assert!(result.diagnostics.is_empty(), "{:?}", result.diagnostics);
+    }
+    #[test]
+    fn test_parse_date_and_datetime() {
+        assert_eq!(parse_date("1970-01-01"), Some(0));
+        assert_eq!(parse_date("2024-02-29"), Some(19_782));
+        assert_eq!(parse_date("2023-02-29"), None);
+        assert_eq!(parse_date("2024-13-01"), None);
+        assert_eq!(parse_date("2024-1-01"), None);
+        assert_eq!(parse_datetime("1970-01-01T00:01:00Z"), Some(60));
+        assert_eq!(parse_datetime("1970-01-01 02:00+02:00"), Some(0));
+        assert_eq!(parse_datetime("1970-01-01T00:00:01.250-01:00"), Some(3601));
+        assert_eq!(parse_datetime("1970-01-01T24:00:00Z"), None);
+        assert_eq!(parse_datetime("1970-01-01"), None);
+        // Non-ASCII offsets are rejected, not sliced mid-character
+        assert_eq!(parse_datetime("2024-01-01T10:00+aé1"), None);
+        assert_eq!(parse_datetime("2024-01-01T10:00+éé"), None);
+        assert_eq!(parse_timestamp("1970-01-02"), Some(86_400));
+    }
+
+    #[test]
+    fn test_date_fields_with_bounds() {
+        let schema = Schema::from_str(
+            r#"
+type "inc" {
+    field "date" type="date" required=#true min="2020-01-01" not-future=#true
+    field "detected_at" type="datetime" max="2030-01-01T00:00:00Z"
+}
+"#,
+        )
+        .unwrap();
+        let check = |fm: &str| {
+            let doc = Document::from_str(&format!("---\ntype: inc\n{fm}---\n")).unwrap();
+            validate_document(&doc, &schema, &HashSet::new(), &HashSet::new(), None)
+                .diagnostics
+                .into_iter()
+                .map(|d| d.code)
+                .collect::<Vec<_>>()
+        };
+        assert!(check("date: 2024-05-01\ndetected_at: 2024-05-01T08:00:00+02:00\n").is_empty());
+        assert_eq!(check("date: 2024-02-30\n"), vec!["F020"]);
+        assert_eq!(check("date: 2019-12-31\n"), vec!["F031"]);
+        assert_eq!(check("date: 2999-01-01\n"), vec!["F031"]);
+        assert_eq!(check("date: 2024-05-01\ndetected_at: 2031-01-01T00:00:00Z\n"), vec!["F031"]);
+        assert_eq!(check("date: 2024-05-01\ndetected_at: yesterday\n"), vec!["F020"]);
+
+        let bad = r#"type "inc" { field "n" type="number" min="2020-01-01" }"#;
+        assert!(Schema::from_str(bad).is_err());
+        let bad = r#"type "inc" { field "d" type="date" min="soon" }"#;
+        assert!(Schema::from_str(bad).is_err());
+    }
+
+    #[test]
+    fn test_diagnostic_state_delta() {
+        let diag = |code: &str, line: usize| Diagnostic {
+            severity: Severity::Error,
+            code: code.into(),
+            message: format!("{code} message"),
+            location: "frontmatter".into(),
+            hint: None,
+            span: Some(Span { line, column: 1, end_line: line, end_column: 2 }),
+        };
+        let run = |files: Vec<(&str, Vec<Diagnostic>)>| ValidationResult {
+            file_results: files
+                .into_iter()
+                .map(|(path, diagnostics)| FileResult { path: path.into(), diagnostics })
+                .collect(),
+        };
+        assert_eq!(diag("F010", 1).fingerprint("./docs/a.md"), diag("F010", 9).fingerprint("docs/a.md"));
+        assert_ne!(diag("F010", 1).fingerprint("docs/a.md"), diag("F010", 1).fingerprint("docs/b.md"));
+
+        let mut state = DiagnosticState::default();
+        let delta = state.replace(&run(vec![("a.md", vec![diag("F010", 2)]), ("b.md", vec![diag("F021", 3)])]));
+        assert_eq!(delta.new.len(), 2);
+        assert_eq!((state.errors(), state.warnings()), (2, 0));
+
+        // a.md moved its finding down a line and gained one; b.md was deleted
+        let paths = vec!["a.md".to_string(), "b.md".to_string()];
+        let delta = state.update(&paths, &run(vec![("a.md", vec![diag("F010", 5), diag("S010", 8)])]));
+        let codes = |v: &[(String, Diagnostic)]| v.iter().map(|(p, d)| format!("{p}:{}", d.code)).collect::<Vec<_>>();
+        assert_eq!(codes(&delta.new), vec!["a.md:S010"]);
+        assert_eq!(codes(&delta.resolved), vec!["b.md:F021"]);
+        assert_eq!(state.errors(), 2);
+
+        assert!(state.update(&paths[..1], &run(vec![("a.md", vec![diag("F010", 5), diag("S010", 8)])])).is_empty());
+        let delta = state.replace(&run(vec![]));
+        assert_eq!(delta.resolved.len(), 2);
+        assert_eq!(state.errors(), 0);
+    }
 }
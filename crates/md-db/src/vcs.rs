@@ -0,0 +1,184 @@
+//! Read documents at other git revisions, walk a file's history, list what
+//! changed since a revision, and stage files.
+//!
+//! Thin wrappers over the `git` binary; paths are relative to the current directory,
+//! the same way `git` itself resolves them.
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::error::{Error, Result};
+
+fn git(args: &[&str]) -> Result<Vec<u8>> {
+    let output = Command::new("git")
+        .args(args)
+        .output()
+        .map_err(|e| Error::Vcs(format!("failed to run git: {e}")))?;
+    if !output.status.success() {
+        return Err(Error::Vcs(format!(
+            "git {} failed: {}",
+            args.first().copied().unwrap_or(""),
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(output.stdout)
+}
+
+/// `git add` the given files.
+pub fn stage(paths: &[&Path]) -> Result<()> {
+    let mut args = vec!["add", "--"];
+    let paths: Vec<String> = paths.iter().map(|p| p.display().to_string()).collect();
+    args.extend(paths.iter().map(String::as_str));
+    git(&args).map(|_| ())
+}
+
+/// Resolve a revision (branch, tag, `HEAD~2`, ...) to a commit hash.
+pub fn resolve(rev: &str) -> Result<String> {
+    let spec = format!("{rev}^{{commit}}");
+    let out = git(&["rev-parse", "--verify", &spec])?;
+    Ok(String::from_utf8_lossy(&out).trim().to_string())
+}
+
+/// Best common ancestor of two revisions.
+pub fn merge_base(a: &str, b: &str) -> Result<String> {
+    let out = git(&["merge-base", a, b])?;
+    Ok(String::from_utf8_lossy(&out).trim().to_string())
+}
+
+/// Markdown files under `dir` at `rev`.
+pub fn markdown_files_at(rev: &str, dir: &Path) -> Result<Vec<PathBuf>> {
+    let dir = dir.to_string_lossy();
+    let out = git(&["ls-tree", "-r", "-z", "--name-only", rev, "--", &dir])?;
+    Ok(out
+        .split(|b| *b == 0)
+        .filter(|p| !p.is_empty())
+        .map(|p| PathBuf::from(String::from_utf8_lossy(p).into_owned()))
+        .filter(|p| p.extension().is_some_and(|e| e == "md"))
+        .collect())
+}
+
+/// Contents of `path` at `rev`.
+pub fn read_file_at(rev: &str, path: &Path) -> Result<String> {
+    let spec = format!("{rev}:./{}", path.display());
+    let out = git(&["show", &spec])?;
+    String::from_utf8(out).map_err(|_| Error::Vcs(format!("{spec} is not valid UTF-8")))
+}
+
+/// All markdown files under `dir` at `rev`, with their contents.
+pub fn read_tree_at(rev: &str, dir: &Path) -> Result<Vec<(PathBuf, String)>> {
+    markdown_files_at(rev, dir)?
+        .into_iter()
+        .map(|path| {
+            let content = read_file_at(rev, &path)?;
+            Ok((path, content))
+        })
+        .collect()
+}
+
+/// Markdown files that differ between a revision and the working tree, relative
+/// to the current directory.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ChangedFiles {
+    /// The commit compared against: the merge base of the requested revision and `HEAD`.
+    pub base: String,
+    /// Added, modified, and untracked files.
+    pub changed: Vec<PathBuf>,
+    /// Files present at `base` and deleted since.
+    pub deleted: Vec<PathBuf>,
+}
+
+/// Markdown files changed since the merge base of `rev` and `HEAD`, committed
+/// or not, under the current directory. Untracked files count as changed.
+pub fn changed_markdown_files(rev: &str) -> Result<ChangedFiles> {
+    let base = merge_base(rev, "HEAD")?;
+    let out = git(&["diff", "--name-status", "-z", "--no-renames", "--relative", &base, "--", "*.md"])?;
+    let (mut changed, deleted) = parse_name_status(&out);
+    let untracked = git(&["ls-files", "-z", "--others", "--exclude-standard", "--", "*.md"])?;
+    changed.extend(
+        untracked
+            .split(|b| *b == 0)
+            .filter(|p| !p.is_empty())
+            .map(|p| PathBuf::from(String::from_utf8_lossy(p).into_owned())),
+    );
+    changed.retain(|p| p.exists());
+    changed.sort();
+    changed.dedup();
+    Ok(ChangedFiles { base, changed, deleted })
+}
+
+/// Split `git diff --name-status -z` output into (changed, deleted) paths.
+fn parse_name_status(out: &[u8]) -> (Vec<PathBuf>, Vec<PathBuf>) {
+    let mut changed = Vec::new();
+    let mut deleted = Vec::new();
+    let mut fields = out.split(|b| *b == 0).filter(|f| !f.is_empty());
+    while let (Some(status), Some(path)) = (fields.next(), fields.next()) {
+        let path = PathBuf::from(String::from_utf8_lossy(path).into_owned());
+        if status.starts_with(b"D") {
+            deleted.push(path);
+        } else {
+            changed.push(path);
+        }
+    }
+    (changed, deleted)
+}
+
+/// A commit that touched a file, with the file's path as of that commit.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Commit {
+    pub hash: String,
+    /// Author date, `YYYY-MM-DD`.
+    pub date: String,
+    pub author: String,
+    /// Path relative to the repository root; differs from today's across renames.
+    pub path: PathBuf,
+}
+
+/// Commits that touched `path`, newest first, following renames.
+pub fn file_log(path: &Path) -> Result<Vec<Commit>> {
+    let path = path.to_string_lossy();
+    let out = git(&[
+        "log",
+        "--follow",
+        "--date=short",
+        "--format=%x1e%H%x1f%ad%x1f%an",
+        "--name-only",
+        "--",
+        &path,
+    ])?;
+    let out = String::from_utf8_lossy(&out);
+    Ok(out
+        .split('\x1e')
+        .filter_map(|record| {
+            let mut lines = record.lines().filter(|l| !l.is_empty());
+            let mut header = lines.next()?.split('\x1f');
+            let (hash, date, author) = (header.next()?, header.next()?, header.next()?);
+            Some(Commit {
+                hash: hash.to_string(),
+                date: date.to_string(),
+                author: author.to_string(),
+                path: PathBuf::from(lines.last()?),
+            })
+        })
+        .collect())
+}
+
+/// Contents of `path` (relative to the repository root) at `rev`.
+pub fn read_repo_file_at(rev: &str, path: &Path) -> Result<String> {
+    let spec = format!("{rev}:{}", path.display());
+    let out = git(&["show", &spec])?;
+    String::from_utf8(out).map_err(|_| Error::Vcs(format!("{spec} is not valid UTF-8")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_name_status() {
+        let out = b"M\0docs/adr-001.md\0D\0docs/adr-002.md\0A\0docs/new dir/adr-003.md\0";
+        let (changed, deleted) = parse_name_status(out);
+        assert_eq!(changed, vec![PathBuf::from("docs/adr-001.md"), PathBuf::from("docs/new dir/adr-003.md")]);
+        assert_eq!(deleted, vec![PathBuf::from("docs/adr-002.md")]);
+        assert_eq!(parse_name_status(b""), (Vec::new(), Vec::new()));
+    }
+}
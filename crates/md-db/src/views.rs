@@ -0,0 +1,171 @@
+//! Saved views: named queries declared in the schema (or in `views.kdl` next to it).
+//!
+//! ```kdl
+//! view "open-incidents" type="inc" where="status!=resolved" sort="-started_at" columns="id,title,severity" {
+//!     description "Incidents still being worked on"
+//!     where "severity in sev1, sev2"
+//! }
+//! ```
+//!
+//! `where` takes the conditions of `md-db-query` blocks (`=`, `!=`, `~=`, `<`,
+//! `<=`, `>`, `>=`, `in a, b`); as a property and as child nodes they all have to
+//! hold. `sort` is a field, `-` first for descending; `columns` are `list
+//! --columns` names. `md-db list --view NAME` runs a view, and the MCP server
+//! offers each one as an `md-db-view-NAME` tool.
+
+use std::path::{Path, PathBuf};
+
+use kdl::{KdlDocument, KdlNode};
+
+use crate::discovery::{self, Filter};
+use crate::error::{Error, Result};
+use crate::frontmatter::Frontmatter;
+use crate::schema::{get_i64_prop, get_string_arg, get_string_prop, Schema};
+
+/// File of extra views, read from the schema's directory.
+pub const VIEWS_FILE: &str = "views.kdl";
+
+/// A named query.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ViewDef {
+    pub name: String,
+    pub description: Option<String>,
+    pub doc_type: Option<String>,
+    /// The `where` conditions as written.
+    pub conditions: Vec<String>,
+    /// Sort spec as `list --sort` takes it (`-started_at`).
+    pub sort: Option<String>,
+    /// Comma-separated `list --columns` value.
+    pub columns: Option<String>,
+    pub limit: Option<usize>,
+}
+
+impl ViewDef {
+    /// The view's filters, its type included.
+    pub fn filters(&self) -> Vec<Filter> {
+        let doc_type = self.doc_type.iter().map(|t| Filter::FieldEquals {
+            key: "type".into(),
+            value: t.clone(),
+        });
+        let conditions = self
+            .conditions
+            .iter()
+            .filter_map(|c| crate::query::parse_condition(c).ok());
+        doc_type.chain(conditions).collect()
+    }
+
+    /// Files under `dir` the view selects, in its sort order and without its limit.
+    pub fn files(&self, dir: &Path) -> Result<Vec<PathBuf>> {
+        let files = discovery::discover_files(dir, None, &self.filters(), false)?;
+        let Some(ref spec) = self.sort else {
+            return Ok(files);
+        };
+        let (field, descending) = match spec.strip_prefix('-') {
+            Some(field) => (field, true),
+            None => (spec.as_str(), false),
+        };
+        let mut keyed: Vec<(PathBuf, String)> = files
+            .into_iter()
+            .map(|path| {
+                let value = Frontmatter::read_from_file(&path)
+                    .ok()
+                    .flatten()
+                    .and_then(|fm| fm.get_display(field))
+                    .unwrap_or_default();
+                (path, value)
+            })
+            .collect();
+        keyed.sort_by(|a, b| {
+            let cmp = crate::ordering::compare_text(&a.1, &b.1);
+            if descending { cmp.reverse() } else { cmp }
+        });
+        Ok(keyed.into_iter().map(|(path, _)| path).collect())
+    }
+}
+
+/// Parse a `view` node.
+pub(crate) fn parse_view(node: &KdlNode) -> Result<ViewDef> {
+    let name = get_string_arg(node).ok_or_else(|| Error::SchemaParse("view node missing name".into()))?;
+    let err = |msg: String| Error::SchemaParse(format!("view '{name}': {msg}"));
+    let mut view = ViewDef {
+        name: name.clone(),
+        description: get_string_prop(node, "description"),
+        doc_type: get_string_prop(node, "type"),
+        conditions: get_string_prop(node, "where").into_iter().collect(),
+        sort: get_string_prop(node, "sort"),
+        columns: get_string_prop(node, "columns"),
+        limit: None,
+    };
+    if let Some(limit) = get_i64_prop(node, "limit") {
+        view.limit = Some(usize::try_from(limit).map_err(|_| err(format!("limit must not be negative, got {limit}")))?);
+    }
+    for child in node.children().map(|c| c.nodes()).unwrap_or_default() {
+        let value = || get_string_arg(child).ok_or_else(|| err(format!("{} needs a value", child.name().value())));
+        match child.name().value() {
+            "description" => view.description = Some(value()?),
+            "where" => view.conditions.push(value()?),
+            other => return Err(err(format!("unknown node '{other}' (expected description, where)"))),
+        }
+    }
+    for condition in &view.conditions {
+        crate::query::parse_condition(condition).map_err(err)?;
+    }
+    Ok(view)
+}
+
+/// The schema's views followed by those in [`VIEWS_FILE`] beside `schema_path`.
+pub fn load(schema: &Schema, schema_path: &Path) -> Result<Vec<ViewDef>> {
+    let mut views = schema.views.clone();
+    let file = schema_path.parent().unwrap_or(Path::new(".")).join(VIEWS_FILE);
+    let Ok(content) = std::fs::read_to_string(&file) else {
+        return Ok(views);
+    };
+    let in_file = |msg: String| Error::SchemaParse(format!("{}: {msg}", file.display()));
+    let doc: KdlDocument = content.parse().map_err(|e: kdl::KdlError| in_file(format!("{e:#}")))?;
+    for node in doc.nodes() {
+        if node.name().value() != "view" {
+            return Err(in_file(format!("unknown node '{}' (expected view)", node.name().value())));
+        }
+        let view = parse_view(node).map_err(|e| in_file(e.to_string()))?;
+        if views.iter().any(|v| v.name == view.name) {
+            return Err(in_file(format!("view '{}' is declared twice", view.name)));
+        }
+        views.push(view);
+    }
+    Ok(views)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_views() {
+        let dir = tempfile::tempdir().unwrap();
+        let schema_path = dir.path().join("schema.kdl");
+        let kdl = "type \"inc\"\nview \"open\" type=\"inc\" where=\"status!=resolved\" sort=\"-started_at\" \
+                   columns=\"id,title\" {\n    where \"severity in sev1, sev2\"\n}\n";
+        std::fs::write(&schema_path, kdl).unwrap();
+        std::fs::write(dir.path().join(VIEWS_FILE), "view \"all\" limit=5\n").unwrap();
+        let doc = |name: &str, fm: &str| std::fs::write(dir.path().join(name), format!("---\n{fm}\n---\n")).unwrap();
+        doc("inc-001.md", "type: inc\nstatus: open\nseverity: sev1\nstarted_at: 2024-01-01");
+        doc("inc-002.md", "type: inc\nstatus: open\nseverity: sev2\nstarted_at: 2024-02-01");
+        doc("inc-003.md", "type: inc\nstatus: resolved\nseverity: sev1\nstarted_at: 2024-03-01");
+        doc("inc-004.md", "type: inc\nstatus: open\nseverity: sev3\nstarted_at: 2024-04-01");
+
+        let schema = Schema::from_file(&schema_path).unwrap();
+        let views = load(&schema, &schema_path).unwrap();
+        let names: Vec<&str> = views.iter().map(|v| v.name.as_str()).collect();
+        assert_eq!(names, ["open", "all"]);
+        assert_eq!(views[1].limit, Some(5));
+
+        let files = views[0].files(dir.path()).unwrap();
+        let files: Vec<_> = files.iter().map(|p| p.file_name().unwrap().to_str().unwrap()).collect();
+        assert_eq!(files, ["inc-002.md", "inc-001.md"]);
+
+        assert!(Schema::from_str("view \"x\" where=\"status\"").is_err());
+        assert!(Schema::from_str("view \"x\"\nview \"x\"\n").is_err());
+        std::fs::write(dir.path().join(VIEWS_FILE), "view \"open\"\n").unwrap();
+        assert!(load(&schema, &schema_path).unwrap_err().to_string().contains("declared twice"));
+    }
+}
@@ -1091,4 +1091,5 @@ fn singleton_appears_in_graph() {
     assert!(graph.nodes.contains_key("README"), "README should be a graph node, got: {:?}", graph.nodes.keys().collect::<Vec<_>>());
     let node = &graph.nodes["README"];
     assert_eq!(node.doc_type.as_deref(), Some("readme"));
+    assert_eq!(node.title.as_deref(), Some("My Project"));
 }